@@ -0,0 +1,203 @@
+error-http = HTTP-Fehler: {$detail}
+error-invalid-url = Ungültige URL: {$detail}
+error-json = JSON-Fehler: {$detail}
+error-ssl = SSL/TLS-Fehler: {$detail}
+error-proxy = Proxy-Fehler: {$detail}
+error-auth = Authentifizierungsfehler: {$detail}
+error-config = Konfigurationsfehler: {$detail}
+error-timeout = Zeitüberschreitung im Netzwerk
+error-too-slow = Übertragung zu langsam: {$detail}
+error-redirect-limit = Maximale Anzahl an Weiterleitungen überschritten ({$limit})
+error-permission-denied = Zugriff verweigert: {$detail}
+error-file-not-found = Datei nicht gefunden: {$detail}
+error-unsupported = Nicht unterstützter Vorgang: {$detail}
+error-io = E/A-Fehler: {$detail}
+error-browser-cookie = Browser-Cookie-Fehler: {$detail}
+error-aborted = Übertragung abgebrochen
+error-resolve-host = Host konnte nicht aufgelöst werden: {$detail}
+error-connect-timeout = Zeitüberschreitung beim Verbindungsaufbau
+error-empty-reply = Leere Antwort vom Server
+error-recv = Fehler beim Empfangen von Daten: {$detail}
+error-headers-too-large = Antwort-Header zu groß: {$detail}
+error-host-blocked = Host nicht erlaubt: {$detail}
+error-internal-target = Verbindung zu einer internen Adresse abgelehnt: {$detail}
+error-http-status = Die angeforderte URL lieferte den Fehler: {$status}
+error-responses-differ = Die Antworten unterscheiden sich
+error-no-match = Keine Zeile der Antwort passt zu --grep
+error-hint = Hinweis: {$hint}
+hint-close-browser = Schließen Sie den Browser und versuchen Sie es erneut.
+hint-close-browser-or-drop-elevation = Schließen Sie den Browser oder starten Sie ohne erhöhte Rechte.
+hint-allow-keychain-access = Erlauben Sie rurl in der Schlüsselbundverwaltung den Zugriff auf den Safe-Storage-Eintrag des Browsers.
+hint-check-host-and-port = Prüfen Sie Host und Port; dort nimmt niemand Verbindungen an.
+hint-check-host-name = Prüfen Sie den Hostnamen auf Tippfehler und Ihre DNS-Einstellungen.
+hint-trust-certificate = Geben Sie die ausstellende CA mit --cacert an oder überspringen Sie die Prüfung mit --insecure.
+hint-check-client-certificate = Prüfen Sie die mit --cert und --key angegebenen Dateien.
+hint-raise-timeout = Erhöhen Sie --timeout oder --connect-timeout oder prüfen Sie das Netzwerk.
+hint-check-proxy = Prüfen Sie die URL von --proxy und die Anmeldedaten von --proxy-user.
+
+## Warnings
+warning-host-override = Host: {$host} weicht von der URL ab; Verbindung zu {$target}
+warning-host-override-sni = Host: {$host} weicht von der URL ab; Verbindung zu {$target} mit TLS-SNI {$sni}
+warning-host-not-forwarded = Der eigene Host-Header wird nicht an {$target} gesendet
+warning-host-required = Der Host-Header kann nicht weggelassen werden; er wird mit dem Host der URL gesendet
+
+## Command line help
+about-rurl = Eine moderne curl-Alternative mit Unterstützung für Browser-Cookies
+help-rurl-url = Die abzurufende URL
+help-rurl-request = HTTP-Anfragemethode
+help-rurl-header = Eigenen HTTP-Header hinzufügen
+help-rurl-url-query = Prozentkodierten Query-Parameter anhängen (name=value, name@file, +raw)
+help-rurl-data = HTTP-POST-Daten (@- streamt die Standardeingabe); mehrere Werte werden mit & zu einem Formular verbunden
+help-rurl-edit = Anfragekörper in $EDITOR schreiben, ausgehend von -d DATA oder -d @FILE
+help-rurl-pre-hook = COMMAND vor der Anfrage ausführen; jede ausgegebene Zeile 'Name: Wert' wird als Header gesendet
+help-rurl-post-hook = COMMAND nach der Antwort ausführen, mit RURL_STATUS, RURL_HEADERS und RURL_BODY_FILE
+help-rurl-form = Multipart-Formularfeld (@DATEI hängt eine Datei an, <DATEI liest den Wert daraus)
+help-rurl-form-string = Multipart-Formularfeld mit stets wörtlichem Wert
+help-rurl-upload-file = FILE (- für Standardeingabe) als Anfragekörper streamen
+help-rurl-trailer = Trailer nach einem Chunked-Körper senden (VALUE @sha256/@sha1 hasht den Körper)
+help-rurl-data-compress = Anfragetext komprimieren (gzip, br oder zstd) und Content-Encoding senden
+help-rurl-upload-length = Content-Length eines gestreamten Körpers statt Chunked-Kodierung
+help-rurl-upload-resume = Einen -T-Upload ab dem Offset fortsetzen, den der Server bereits hat
+help-rurl-cookies-from-browser = Cookies aus dem Browser lesen
+help-rurl-token-from-browser = Einen localStorage-Wert des Browsers als Authorization: Bearer-Token senden
+help-rurl-jwt-sign = Ein JWT mit KEY signieren (PEM für RS256/ES256, Geheimnisdatei für HS256) und als Bearer-Token senden
+help-rurl-jwt-claims = JWT-Claims als Objekt oder @FILE zum Lesen aus einer Datei
+help-rurl-jwt-alg = JWT-Signaturalgorithmus (Standard: aus dem Schlüssel abgeleitet)
+help-rurl-jwt-ttl = JWT-Lebensdauer für den exp-Claim (Standard: 5m)
+help-rurl-cookie-cache = Aus dem Browser gelesene Cookies für DURATION wiederverwenden (z. B. 5m)
+help-rurl-use-cookie-store = Cookies in einem benannten, dauerhaften Cookie-Speicher sichern und senden
+help-rurl-default-scheme = Schema für URLs ohne Schema (https-first weicht auf http aus)
+help-rurl-session = Header, Anmeldedaten und Cookies für diesen Host unter NAME speichern und wiederverwenden
+help-rurl-cache = GET-Antworten auf der Festplatte zwischenspeichern und erneut validieren (RFC 9111)
+help-rurl-cache-mode = Gespeicherte Antworten verwenden: default, force oder only-if-cached
+help-rurl-verbose = Ausführliche Ausgabe mit Verbindungshinweisen (-vv mit DNS und Cookies, -vvv mit Hexdump des Körpers)
+help-rurl-silent = Stiller Modus
+help-rurl-show-error = Fehler und Warnungen auch mit -s anzeigen
+help-rurl-pretty-json = JSON-Antworten formatiert ausgeben
+help-rurl-pretty-xml = XML- und HTML-Antworten einrücken
+help-rurl-pretty = Antworten formatieren: auto wählt JSON, XML oder HTML anhand des Content-Type
+help-rurl-memory-limit = Antworten über SIZE beim Formatieren in eine temporäre Datei auslagern (Standard 64M)
+help-rurl-pager = Terminalausgabe auch dann über $PAGER anzeigen, wenn sie auf den Bildschirm passt
+help-rurl-no-pager = Terminalausgabe nie über einen Pager anzeigen
+help-rurl-copy = Den Body zusätzlich in die Zwischenablage kopieren, mit --copy=only nur kopieren
+help-rurl-no-progress-meter = Fortschrittsanzeige deaktivieren
+help-rurl-output = Ausgabe in Datei schreiben ('-' erzwingt die Standardausgabe); mehrfach angeben, um denselben Body in mehrere Dateien zu schreiben
+help-rurl-tee = Den Body zusätzlich zur Standardausgabe oder --output auch in FILE schreiben (mehrfach verwendbar)
+help-rurl-extract = Statt des Körpers NAME=Wert für einen jq-artigen Pfad (.a.b[0]) in der JSON-Antwort ausgeben (wiederholbar)
+help-rurl-export-env = --extract-Werte als export-Zeilen ausgeben oder in eine dotenv-Datei FILE schreiben
+help-rurl-grep = Nur die Body-Zeilen ausgeben, die auf den regulären Ausdruck PATTERN passen; Exit-Code 1, wenn keine passt
+help-rurl-grep-context = Zusätzlich N Zeilen vor und nach jedem --grep-Treffer ausgeben
+help-rurl-template = Statt des Bodys einen mit TEMPLATE aus der Antwort erzeugten Bericht ausgeben
+help-rurl-list-links = Statt des Bodys die absoluten URLs ausgeben, auf die die HTML-Antwort verweist (href und src)
+help-rurl-download-links = Die Links der HTML-Antwort, deren URL auf den regulären Ausdruck PATTERN passt, ins aktuelle Verzeichnis herunterladen
+help-rurl-recursive = Die Site spiegeln: die Seite und Ebene für Ebene die verlinkten Seiten und Dateien speichern
+help-rurl-depth = Mit --recursive Links höchstens N Ebenen ab der ersten Seite folgen (Standard 5)
+help-rurl-accept-regex = Mit --recursive nur Links folgen, deren URL auf PATTERN passt, statt denen auf demselben Host
+help-rurl-wait = Zwischen zwei --recursive-Anfragen an denselben Host mindestens SECONDS Sekunden warten (Standard 1)
+help-rurl-manifest = Das --recursive-Manifest in FILE schreiben (Standard rurl-manifest.json)
+help-rurl-remote-name = Ausgabe in eine Datei mit dem Namen der entfernten Datei schreiben
+help-rurl-open = Den gespeicherten Body mit der Standardanwendung öffnen (ohne -o eine temporäre Datei)
+help-rurl-print-filename = Den Pfad ausgeben, unter dem der Body gespeichert wurde (ohne -o eine temporäre Datei)
+help-rurl-remote-time = Zeitstempel der Ausgabedatei aus dem Last-Modified-Header setzen
+help-rurl-remove-on-error = Ausgabedatei löschen, wenn die Übertragung fehlschlägt
+help-rurl-fail = Bei HTTP-Fehlern ohne Ausgabe des Körpers mit Exit-Code 22 abbrechen
+help-rurl-strict = Optionen, die in dieser Kombination wirkungslos sind, als Fehler behandeln
+help-rurl-dry-run = Die Anfrage, die gesendet würde, mit geschwärzten Geheimnissen ausgeben, ohne sie zu senden
+help-rurl-show-secrets = Zugangsdaten und Cookie-Werte in der Ausgabe von --dry-run anzeigen
+help-rurl-no-clobber = Vorhandene Ausgabedateien nie überschreiben
+help-rurl-user = HTTP-Authentifizierung
+help-rurl-proxy = Proxy-Server verwenden
+help-rurl-preproxy = Den Proxy zuerst über diesen SOCKS-Proxy erreichen
+help-rurl-proxy-user = Proxy-Authentifizierung
+help-rurl-proxy-keyring = Proxy-Passwort aus dem Schlüsselbund des Betriebssystems lesen (siehe 'rurl auth add')
+help-rurl-proxy-pac = Proxys mit einem Proxy-Auto-Config-Skript (PAC) auswählen
+help-rurl-ssh-tunnel = Anfragen über einen SOCKS-Tunnel per SSH zu einem Jump-Host senden
+help-rurl-proxytunnel = Alle Anfragen mit CONNECT durch den HTTP-Proxy tunneln
+help-rurl-insecure = Unsichere SSL-Verbindungen erlauben
+help-rurl-location = Weiterleitungen folgen
+help-rurl-show-redirect-chain = Status und Weiterleitungsziel jedes Schritts auf stderr ausgeben
+help-rurl-location-trusted = Weiterleitungen folgen und Anmeldedaten an andere Hosts senden
+help-rurl-include = Antwort-Header in die Ausgabe aufnehmen
+help-rurl-log-format = Format der Diagnose auf stderr (json: ein Ereignisobjekt pro Zeile)
+help-rurl-log-file = Protokolleinträge und Anfrageereignisse an PATH anhängen
+help-rurl-log-level = Detaillierteste Stufe, die in --log-file geschrieben wird (Standard: info)
+help-rurl-metrics-out = Metriken des Laufs im Prometheus-Textformat nach FILE schreiben
+help-rurl-summary = Nach der Übertragung Status, Body-Größe, Dauer und finale URL auf stderr ausgeben
+help-rurl-summary-budget = Mit --summary warnen, wenn Body oder Übertragungsdauer das Budget überschreiten
+help-rurl-stats = Verbindungs-, DNS- und Bytezahlen des Laufs auf stderr ausgeben
+help-rurl-raw = Antwortbytes ohne Inhaltsdekodierung oder Zeichensatzumwandlung ausgeben
+help-rurl-hexdump = Antworttext als Offset/Hex/ASCII-Dump ausgeben
+help-rurl-output-charset = Den dekodierten Antworttext vor der Ausgabe in CHARSET umkodieren
+help-rurl-max-redirs = Maximale Anzahl zu folgender Weiterleitungen (-1 für unbegrenzt)
+help-rurl-allow-host = Anfragen nur an Hosts senden, die PATTERN (Glob oder CIDR) entsprechen, auch bei Weiterleitungen
+help-rurl-deny-host = Nie Anfragen an Hosts senden, die PATTERN (Glob oder CIDR) entsprechen, auch bei Weiterleitungen
+help-rurl-no-internal-targets = Loopback-, private und Link-Local-Adressen ablehnen, auch nach Weiterleitungen und DNS
+help-rurl-post301 = POST nach 301 nicht in GET umwandeln
+help-rurl-post302 = POST nach 302 nicht in GET umwandeln
+help-rurl-post303 = POST nach 303 nicht in GET umwandeln
+help-rurl-user-agent = User-Agent-Header
+help-rurl-timeout = Maximale Dauer jedes Anfrageversuchs
+help-rurl-max-time = Maximale Dauer der gesamten Übertragung einschließlich Wiederholungen und Weiterleitungen
+help-rurl-max-header-size = Fehlschlagen, wenn eine Antwort mehr als SIZE Bytes Header hat (Standard 300k)
+help-rurl-max-headers = Fehlschlagen, wenn eine Antwort mehr als COUNT Headerfelder hat (Standard 100)
+help-rurl-no-keepalive = Connection: close senden und für jede Anfrage eine neue Verbindung öffnen
+help-rurl-read-timeout = Maximale Wartezeit auf Antwortdaten
+help-rurl-write-timeout = Maximale Stillstandszeit eines gestreamten Uploads
+help-rurl-connect-timeout = Maximale Dauer des Verbindungsaufbaus
+help-rurl-local-port = Lokaler Port oder Portbereich für die Verbindung
+help-rurl-daemon = Einen Agenten starten, der Verbindungen und Browser-Cookies für spätere Aufrufe bereithält
+help-rurl-daemon-socket = Socket des zu startenden oder zu nutzenden Agenten
+help-rurl-no-daemon = Anfragen direkt senden, auch wenn ein Agent läuft
+help-rurl-speed-limit = Abbrechen, wenn langsamer als BYTES/s für --speed-time Sekunden
+help-rurl-speed-time = Zeitfenster für --speed-limit (Standard 30)
+help-rurl-retry = Anzahl der Wiederholungsversuche
+help-rurl-retry-delay = Wartezeit zwischen Wiederholungen
+help-rurl-retry-max-delay = Maximale Wartezeit, die aus Retry-After übernommen wird
+help-rurl-request-id = Pro Übertragung eine neue UUID in HEADER senden (Standard X-Request-Id)
+help-rurl-idempotency-key = Idempotency-Key: KEY bei jedem Wiederholungsversuch senden, mit 'auto' eine neue UUID pro Übertragung
+help-rurl-retry-rate-limit = Bei 429 bis zur Zeit aus RateLimit-Reset oder X-RateLimit-Reset warten
+help-rurl-retry-all-errors = Mit --retry bei jedem Übertragungsfehler erneut versuchen
+help-rurl-cacert = Datei mit CA-Zertifikaten
+help-rurl-cert = Client-Zertifikatsdatei oder pkcs11:-URI mit --engine pkcs11
+help-rurl-engine = Krypto-Engine mit dem Client-Schlüssel (pkcs11)
+help-rurl-key = Datei mit dem privaten Schlüssel
+help-rurl-lang = Sprache für Meldungen und Hilfe (z. B. ja, de, fr, zh-CN, es)
+help-rurl-manual = Die Manpage rurl(1) im roff-Format ausgeben
+about-rurl-cookies = Browser-Cookies anzeigen und exportieren
+about-rurl-cookies-export = Browser-Cookies in eine Datei exportieren
+help-rurl-cookies-export-cookies-from-browser = Browser, aus dem Cookies gelesen werden
+help-rurl-cookies-export-output = Cookies in Datei schreiben ('-' oder ohne Angabe: Standardausgabe)
+help-rurl-cookies-export-domain = Nur Cookies für DOMAIN und ihre Subdomains exportieren
+help-rurl-cookies-export-format = Ausgabeformat
+about-rurl-cookies-list-profiles = Browser-Profile und Firefox-Container auflisten
+help-rurl-cookies-list-profiles-browser = Browser, dessen Profile aufgelistet werden
+about-rurl-auth = Anmeldedaten im Schlüsselbund des Betriebssystems speichern
+about-rurl-auth-add = Anmeldedaten speichern, die bei späteren Anfragen an den Ursprung der URL gesendet werden
+help-rurl-auth-add-url = Ursprung, für den die Anmeldedaten gelten
+help-rurl-auth-add-user = Anmeldedaten für Basic-Authentifizierung
+help-rurl-auth-add-token = Bearer-Token
+about-rurl-auth-remove = Für den Ursprung der URL gespeicherte Anmeldedaten löschen
+help-rurl-auth-remove-url = Ursprung, dessen Anmeldedaten gelöscht werden
+about-rurl-auth-list = Ursprünge mit gespeicherten Anmeldedaten auflisten
+about-rurl-auth-oauth2 = Mit OAuth2 anmelden und die Tokens für den Ursprung der URL verwenden
+help-rurl-auth-oauth2-url = Ursprung, an den das Zugriffstoken gesendet wird
+help-rurl-auth-oauth2-client-id = OAuth2-Client-ID
+help-rurl-auth-oauth2-client-secret = Client-Secret für vertrauliche Clients
+help-rurl-auth-oauth2-token-url = Token-Endpunkt
+help-rurl-auth-oauth2-device-code = Device-Flow mit diesem Geräteautorisierungs-Endpunkt verwenden
+help-rurl-auth-oauth2-auth-code = Authorization-Code-Flow mit diesem Autorisierungs-Endpunkt verwenden
+help-rurl-auth-oauth2-scope = Angeforderte Scopes, durch Leerzeichen getrennt
+help-rurl-auth-oauth2-redirect-port = Lokaler Port für die Weiterleitung des Autorisierungscodes (Standard: ein freier Port)
+about-rurl-batch = Die Anfragen einer NDJSON-Datei ausführen und NDJSON-Ergebnisse ausgeben
+help-rurl-batch-file = Anfragen, ein JSON-Objekt pro Zeile ('-' liest von stdin)
+help-rurl-batch-parallel = Anzahl gleichzeitig laufender Anfragen
+help-rurl-batch-output = Ergebnisse in DATEI statt auf stdout schreiben
+help-rurl-batch-fail-fast-threshold = Restliche Anfragen an einen Host nach N Fehlschlägen in Folge überspringen
+about-rurl-diff = Zwei URLs abrufen und einen Unified-Diff der Antworten ausgeben
+help-rurl-diff-url1 = Erste URL
+help-rurl-diff-url2 = Zweite URL
+help-rurl-diff-diff-against = URL1 mit einer in FILE gespeicherten Antwort vergleichen (Inhalt oder -i-Ausgabe)
+help-rurl-diff-header = HEADER mit beiden Anfragen senden (wiederholbar)
+help-rurl-diff-json = JSON-Inhalte mit sortierten Schlüsseln und einheitlicher Einrückung vergleichen
+help-rurl-diff-ignore-header = Header NAME beim Vergleich auslassen (wiederholbar)
+help-rurl-diff-body-only = Nur die Inhalte vergleichen
@@ -6,9 +6,198 @@ error-proxy = Proxy error: {$detail}
 error-auth = Authentication error: {$detail}
 error-config = Configuration error: {$detail}
 error-timeout = Network timeout
+error-too-slow = Operation too slow: {$detail}
 error-redirect-limit = Redirect limit exceeded ({$limit})
 error-permission-denied = Permission denied: {$detail}
 error-file-not-found = File not found: {$detail}
 error-unsupported = Unsupported operation: {$detail}
 error-io = IO error: {$detail}
 error-browser-cookie = Browser cookie error: {$detail}
+error-aborted = Transfer aborted
+error-resolve-host = Could not resolve host: {$detail}
+error-connect-timeout = Connection timed out
+error-empty-reply = Empty reply from server
+error-recv = Failure when receiving data from the peer: {$detail}
+error-headers-too-large = Response headers too large: {$detail}
+error-host-blocked = Host not allowed: {$detail}
+error-internal-target = Refusing to connect to an internal address: {$detail}
+error-http-status = The requested URL returned error: {$status}
+error-responses-differ = Responses differ
+error-no-match = No line of the response matched --grep
+error-hint = hint: {$hint}
+hint-close-browser = Close the browser and try again.
+hint-close-browser-or-drop-elevation = Close the browser or run without elevation.
+hint-allow-keychain-access = Allow rurl to access the browser's Safe Storage entry in Keychain Access.
+hint-check-host-and-port = Check the host and port; nothing is accepting connections there.
+hint-check-host-name = Check the host name for typos and your DNS settings.
+hint-trust-certificate = Pass the issuing CA with --cacert, or use --insecure to skip verification.
+hint-check-client-certificate = Check the files given with --cert and --key.
+hint-raise-timeout = Increase --timeout or --connect-timeout, or check the network.
+hint-check-proxy = Check the --proxy URL and --proxy-user credentials.
+
+## Warnings
+warning-host-override = Host: {$host} differs from the URL; connecting to {$target}
+warning-host-override-sni = Host: {$host} differs from the URL; connecting to {$target} with TLS SNI {$sni}
+warning-host-not-forwarded = Not sending the custom Host header to {$target}
+warning-host-required = The Host header cannot be left out; it is sent with the URL host
+
+## Command line help
+about-rurl = A modern curl alternative with browser cookie support
+help-rurl-url = The URL to request
+help-rurl-request = HTTP request method
+help-rurl-header = Add custom HTTP header
+help-rurl-url-query = Append a percent-encoded query parameter (name=value, name@file, +raw)
+help-rurl-data = HTTP POST data (@- streams stdin); repeated values are joined with & as a form
+help-rurl-edit = Write the request body in $EDITOR, starting from -d DATA or -d @FILE
+help-rurl-pre-hook = Run COMMAND before the request; each 'Name: value' line it prints is sent as a header
+help-rurl-post-hook = Run COMMAND after the response, with RURL_STATUS, RURL_HEADERS and RURL_BODY_FILE set
+help-rurl-form = Multipart form field (@FILE attaches a file, <FILE reads the value from it)
+help-rurl-form-string = Multipart form field whose value is always literal
+help-rurl-upload-file = Stream FILE (- for stdin) as the request body
+help-rurl-trailer = Send a trailer after a chunked body (VALUE @sha256/@sha1 hashes the body)
+help-rurl-data-compress = Compress the request body (gzip, br or zstd) and send Content-Encoding
+help-rurl-upload-length = Content-Length of a streamed body instead of chunked encoding
+help-rurl-upload-resume = Continue a -T upload from the offset the server already has
+help-rurl-cookies-from-browser = Extract cookies from browser
+help-rurl-token-from-browser = Send a browser localStorage value as an Authorization: Bearer token
+help-rurl-jwt-sign = Sign a JWT with KEY (PEM for RS256/ES256, secret file for HS256) and send it as a Bearer token
+help-rurl-jwt-claims = JWT claims object, or @FILE to read it from a file
+help-rurl-jwt-alg = JWT signing algorithm (default: inferred from the key)
+help-rurl-jwt-ttl = JWT lifetime used for the exp claim (default: 5m)
+help-rurl-cookie-cache = Reuse cookies extracted from the browser for DURATION (e.g. 5m)
+help-rurl-use-cookie-store = Save and send cookies using a persistent named cookie store
+help-rurl-default-scheme = Scheme for URLs given without one (https-first falls back to http)
+help-rurl-session = Save and reuse headers, auth and cookies for this host under NAME
+help-rurl-cache = Cache GET responses on disk and revalidate them (RFC 9111)
+help-rurl-cache-mode = Use stored responses: default, force, or only-if-cached
+help-rurl-verbose = Verbose output with connection notices (-vv adds DNS and cookies, -vvv body hexdumps)
+help-rurl-silent = Silent mode
+help-rurl-show-error = Show errors and warnings even with -s
+help-rurl-pretty-json = Pretty-print JSON responses
+help-rurl-pretty-xml = Indent XML and HTML responses
+help-rurl-pretty = Pretty-print responses: auto picks JSON, XML or HTML from the Content-Type
+help-rurl-memory-limit = Spool bodies larger than SIZE to a temporary file while formatting (default 64M)
+help-rurl-pager = Page terminal output through $PAGER even when it fits the screen
+help-rurl-no-pager = Never page terminal output
+help-rurl-copy = Copy the body to the clipboard as well as writing it, or only copy it with --copy=only
+help-rurl-no-progress-meter = Disable progress meter
+help-rurl-output = Write output to file ('-' forces stdout); repeat to write the same body to several files
+help-rurl-tee = Also write the body to FILE while it goes to stdout or --output (repeatable)
+help-rurl-extract = Print NAME=value for a jq-style path (.a.b[0]) into the JSON response instead of the body (repeatable)
+help-rurl-export-env = Print --extract values as export lines, or write them to a dotenv FILE
+help-rurl-grep = Write only the body lines matching the regular expression PATTERN; exit 1 when none match
+help-rurl-grep-context = Also write N lines before and after each --grep match
+help-rurl-template = Print a report rendered from the response with TEMPLATE instead of the body
+help-rurl-list-links = Print the absolute URLs the HTML response links to (href and src) instead of the body
+help-rurl-download-links = Download the links of the HTML response whose URL matches the regular expression PATTERN into the current directory
+help-rurl-recursive = Mirror the site: save the page and, level by level, the pages and files it links to
+help-rurl-depth = Follow links at most N levels from the first page with --recursive (default 5)
+help-rurl-accept-regex = With --recursive, follow only the links whose URL matches PATTERN instead of those on the same host
+help-rurl-wait = Wait at least SECONDS between two --recursive requests to the same host (default 1)
+help-rurl-manifest = Write the --recursive manifest to FILE (default rurl-manifest.json)
+help-rurl-remote-name = Write output to a file named like the remote file
+help-rurl-open = Open the saved body with the default application (a temporary file without -o)
+help-rurl-print-filename = Print the path the body was saved to (a temporary file without -o)
+help-rurl-remote-time = Set the output file time from the Last-Modified header
+help-rurl-remove-on-error = Remove the output file when the transfer fails
+help-rurl-fail = Fail with exit code 22 on HTTP errors without writing the body
+help-rurl-strict = Treat options that have no effect in this combination as errors
+help-rurl-dry-run = Print the request that would be sent, with secrets redacted, without sending it
+help-rurl-show-secrets = Show credentials and cookie values in --dry-run output
+help-rurl-no-clobber = Never overwrite an existing output file
+help-rurl-user = HTTP authentication
+help-rurl-proxy = Use proxy server
+help-rurl-preproxy = Reach the proxy through this SOCKS proxy first
+help-rurl-proxy-user = Proxy authentication
+help-rurl-proxy-keyring = Read the proxy password from the OS keyring (see 'rurl auth add')
+help-rurl-proxy-pac = Select proxies with a proxy auto-config (PAC) script
+help-rurl-ssh-tunnel = Send requests through a SOCKS tunnel over SSH to a jump host
+help-rurl-proxytunnel = Tunnel all requests through the HTTP proxy with CONNECT
+help-rurl-insecure = Allow insecure SSL connections
+help-rurl-location = Follow redirects
+help-rurl-show-redirect-chain = Print each hop's status and redirect target to stderr
+help-rurl-location-trusted = Follow redirects and send credentials to other hosts
+help-rurl-include = Include response headers in output
+help-rurl-log-format = Diagnostics format on stderr (json: one event object per line)
+help-rurl-log-file = Append log records and request events to PATH
+help-rurl-log-level = Most detailed level written to --log-file (default: info)
+help-rurl-metrics-out = Write Prometheus text-format metrics for the run to FILE
+help-rurl-summary = Print status, body size, time and final URL to stderr after the transfer
+help-rurl-summary-budget = With --summary, warn when the body or the transfer time is over budget
+help-rurl-stats = Print connection, DNS and byte counts for the run to stderr
+help-rurl-raw = Output the response bytes without content decoding or charset conversion
+help-rurl-hexdump = Write the response body as an offset/hex/ASCII dump
+help-rurl-output-charset = Re-encode the decoded response text into CHARSET before writing it
+help-rurl-max-redirs = Maximum number of redirects to follow (-1 for unlimited)
+help-rurl-allow-host = Only send requests to hosts matching PATTERN (glob or CIDR), redirects included
+help-rurl-deny-host = Never send requests to hosts matching PATTERN (glob or CIDR), redirects included
+help-rurl-no-internal-targets = Refuse loopback, private and link-local addresses, also after redirects and DNS
+help-rurl-post301 = Do not switch POST to GET after 301
+help-rurl-post302 = Do not switch POST to GET after 302
+help-rurl-post303 = Do not switch POST to GET after 303
+help-rurl-user-agent = User-Agent header
+help-rurl-timeout = Maximum time for each request attempt
+help-rurl-max-time = Maximum time for the whole transfer, including retries and redirects
+help-rurl-max-header-size = Fail when a response has more than SIZE bytes of headers (default 300k)
+help-rurl-max-headers = Fail when a response has more than COUNT header fields (default 100)
+help-rurl-no-keepalive = Send Connection: close and open a new connection for every request
+help-rurl-read-timeout = Maximum wait for response data
+help-rurl-write-timeout = Maximum time a streamed upload may stall
+help-rurl-connect-timeout = Maximum time for connection
+help-rurl-local-port = Local port or port range to bind the connection to
+help-rurl-daemon = Run an agent that keeps connections and browser cookies warm for later runs
+help-rurl-daemon-socket = Socket of the agent to run or to send requests through
+help-rurl-no-daemon = Send requests directly even when an agent is running
+help-rurl-speed-limit = Abort if slower than BYTES/sec for --speed-time seconds
+help-rurl-speed-time = Window for --speed-limit (default 30)
+help-rurl-retry = Number of retry attempts
+help-rurl-retry-delay = Delay between retries
+help-rurl-retry-max-delay = Maximum delay honored from Retry-After
+help-rurl-request-id = Send a new UUID per transfer in HEADER (default X-Request-Id)
+help-rurl-idempotency-key = Send Idempotency-Key: KEY, or a new UUID per transfer with 'auto', on every retry
+help-rurl-retry-rate-limit = On 429, wait for the RateLimit-Reset or X-RateLimit-Reset time
+help-rurl-retry-all-errors = With --retry, retry on any transfer error
+help-rurl-cacert = CA certificate bundle file
+help-rurl-cert = Client certificate file, or a pkcs11: URI with --engine pkcs11
+help-rurl-engine = Crypto engine holding the client key (pkcs11)
+help-rurl-key = Private key file
+help-rurl-lang = Language for messages and help (e.g. ja, de, fr, zh-CN, es)
+help-rurl-manual = Print the rurl(1) man page in roff format
+about-rurl-cookies = Inspect and export browser cookies
+about-rurl-cookies-export = Export browser cookies to a file
+help-rurl-cookies-export-cookies-from-browser = Browser to read cookies from
+help-rurl-cookies-export-output = Write cookies to file ('-' or omitted writes to stdout)
+help-rurl-cookies-export-domain = Only export cookies for DOMAIN and its subdomains
+help-rurl-cookies-export-format = Output format
+about-rurl-cookies-list-profiles = List browser profiles and Firefox containers
+help-rurl-cookies-list-profiles-browser = Browser whose profiles to list
+about-rurl-auth = Store credentials in the OS keyring
+about-rurl-auth-add = Save credentials sent to URL's origin on later requests
+help-rurl-auth-add-url = Origin the credentials apply to
+help-rurl-auth-add-user = Basic auth credentials
+help-rurl-auth-add-token = Bearer token
+about-rurl-auth-remove = Delete the credentials stored for URL's origin
+help-rurl-auth-remove-url = Origin whose credentials to delete
+about-rurl-auth-list = List origins with stored credentials
+about-rurl-auth-oauth2 = Sign in with OAuth2 and use the tokens for URL's origin
+help-rurl-auth-oauth2-url = Origin the access token is sent to
+help-rurl-auth-oauth2-client-id = OAuth2 client ID
+help-rurl-auth-oauth2-client-secret = Client secret, for confidential clients
+help-rurl-auth-oauth2-token-url = Token endpoint
+help-rurl-auth-oauth2-device-code = Use the device flow with this device authorization endpoint
+help-rurl-auth-oauth2-auth-code = Use the authorization code flow with this authorization endpoint
+help-rurl-auth-oauth2-scope = Space-separated scopes to request
+help-rurl-auth-oauth2-redirect-port = Local port for the authorization code redirect (default: any free port)
+about-rurl-batch = Run the requests listed in an NDJSON file and print NDJSON results
+help-rurl-batch-file = Requests, one JSON object per line ('-' reads stdin)
+help-rurl-batch-parallel = Number of requests in flight at once
+help-rurl-batch-output = Write results to FILE instead of stdout
+help-rurl-batch-fail-fast-threshold = Skip the remaining requests to a host after N consecutive failures
+about-rurl-diff = Fetch two URLs and print a unified diff of the responses
+help-rurl-diff-url1 = First URL
+help-rurl-diff-url2 = Second URL
+help-rurl-diff-diff-against = Compare URL1 with a response saved in FILE (body, or -i output)
+help-rurl-diff-header = Send HEADER with both requests (repeatable)
+help-rurl-diff-json = Compare JSON bodies with sorted keys and uniform indentation
+help-rurl-diff-ignore-header = Leave header NAME out of the comparison (repeatable)
+help-rurl-diff-body-only = Compare bodies only
@@ -0,0 +1,203 @@
+error-http = Error HTTP: {$detail}
+error-invalid-url = URL no válida: {$detail}
+error-json = Error al analizar JSON: {$detail}
+error-ssl = Error SSL/TLS: {$detail}
+error-proxy = Error de proxy: {$detail}
+error-auth = Error de autenticación: {$detail}
+error-config = Error de configuración: {$detail}
+error-timeout = Tiempo de espera de red agotado
+error-too-slow = Transferencia demasiado lenta: {$detail}
+error-redirect-limit = Se superó el límite de redirecciones ({$limit})
+error-permission-denied = Permiso denegado: {$detail}
+error-file-not-found = Archivo no encontrado: {$detail}
+error-unsupported = Operación no admitida: {$detail}
+error-io = Error de E/S: {$detail}
+error-browser-cookie = Error de cookies del navegador: {$detail}
+error-aborted = Transferencia interrumpida
+error-resolve-host = No se pudo resolver el host: {$detail}
+error-connect-timeout = Tiempo de conexión agotado
+error-empty-reply = Respuesta vacía del servidor
+error-recv = Error al recibir datos: {$detail}
+error-headers-too-large = Cabeceras de respuesta demasiado grandes: {$detail}
+error-host-blocked = Host no permitido: {$detail}
+error-internal-target = Se rechaza la conexión a una dirección interna: {$detail}
+error-http-status = La URL solicitada devolvió el error: {$status}
+error-responses-differ = Las respuestas difieren
+error-no-match = Ninguna línea de la respuesta coincide con --grep
+error-hint = sugerencia: {$hint}
+hint-close-browser = Cierre el navegador e inténtelo de nuevo.
+hint-close-browser-or-drop-elevation = Cierre el navegador o ejecútelo sin elevación de privilegios.
+hint-allow-keychain-access = Permita a rurl acceder a la entrada Safe Storage del navegador en Acceso a Llaveros.
+hint-check-host-and-port = Compruebe el host y el puerto; nadie acepta conexiones allí.
+hint-check-host-name = Compruebe que el nombre de host esté bien escrito y su configuración de DNS.
+hint-trust-certificate = Indique la CA emisora con --cacert, o use --insecure para omitir la verificación.
+hint-check-client-certificate = Compruebe los archivos indicados con --cert y --key.
+hint-raise-timeout = Aumente --timeout o --connect-timeout, o compruebe la red.
+hint-check-proxy = Compruebe la URL de --proxy y las credenciales de --proxy-user.
+
+## Warnings
+warning-host-override = Host: {$host} difiere de la URL; conectando a {$target}
+warning-host-override-sni = Host: {$host} difiere de la URL; conectando a {$target} con TLS SNI {$sni}
+warning-host-not-forwarded = No se envía la cabecera Host personalizada a {$target}
+warning-host-required = La cabecera Host no se puede omitir; se envía con el host de la URL
+
+## Command line help
+about-rurl = Una alternativa moderna a curl compatible con las cookies del navegador
+help-rurl-url = La URL que se solicita
+help-rurl-request = Método de la petición HTTP
+help-rurl-header = Añadir una cabecera HTTP
+help-rurl-url-query = Añadir un parámetro de consulta codificado con porcentajes (name=value, name@file, +raw)
+help-rurl-data = Datos HTTP POST (@- envía la entrada estándar como flujo); varios valores se unen con & como formulario
+help-rurl-edit = Escribir el cuerpo de la petición en $EDITOR, partiendo de -d DATA o -d @FILE
+help-rurl-pre-hook = Ejecutar COMMAND antes de la petición; cada línea 'Nombre: valor' que imprima se envía como cabecera
+help-rurl-post-hook = Ejecutar COMMAND tras la respuesta, con RURL_STATUS, RURL_HEADERS y RURL_BODY_FILE definidos
+help-rurl-form = Campo de formulario multipart (@ARCHIVO adjunta un archivo, <ARCHIVO lee el valor de él)
+help-rurl-form-string = Campo de formulario multipart con valor siempre literal
+help-rurl-upload-file = Enviar FILE (- para la entrada estándar) como cuerpo de la petición en flujo
+help-rurl-trailer = Enviar un trailer tras un cuerpo chunked (VALUE @sha256/@sha1 calcula el hash del cuerpo)
+help-rurl-data-compress = Comprimir el cuerpo de la petición (gzip, br o zstd) y enviar Content-Encoding
+help-rurl-upload-length = Content-Length de un cuerpo en flujo en lugar de codificación chunked
+help-rurl-upload-resume = Continuar una subida -T desde el desplazamiento que ya tiene el servidor
+help-rurl-cookies-from-browser = Extraer cookies del navegador
+help-rurl-token-from-browser = Enviar un valor del localStorage del navegador como token Authorization: Bearer
+help-rurl-jwt-sign = Firmar un JWT con KEY (PEM para RS256/ES256, archivo secreto para HS256) y enviarlo como token Bearer
+help-rurl-jwt-claims = Objeto de claims del JWT, o @FILE para leerlo de un archivo
+help-rurl-jwt-alg = Algoritmo de firma del JWT (por defecto: deducido de la clave)
+help-rurl-jwt-ttl = Duración del JWT usada para el claim exp (por defecto: 5m)
+help-rurl-cookie-cache = Reutilizar las cookies extraídas del navegador durante DURATION (p. ej. 5m)
+help-rurl-use-cookie-store = Guardar y enviar cookies con un almacén persistente con nombre
+help-rurl-default-scheme = Esquema para URL sin esquema (https-first recurre a http)
+help-rurl-session = Guardar y reutilizar cabeceras, credenciales y cookies de este host como NAME
+help-rurl-cache = Guardar en caché en disco las respuestas GET y revalidarlas (RFC 9111)
+help-rurl-cache-mode = Uso de respuestas guardadas: default, force u only-if-cached
+help-rurl-verbose = Salida detallada con avisos de conexión (-vv añade DNS y cookies, -vvv volcados hexadecimales del cuerpo)
+help-rurl-silent = Modo silencioso
+help-rurl-show-error = Mostrar errores y advertencias incluso con -s
+help-rurl-pretty-json = Formatear las respuestas JSON
+help-rurl-pretty-xml = Indentar las respuestas XML y HTML
+help-rurl-pretty = Formatear las respuestas: auto elige JSON, XML o HTML según el Content-Type
+help-rurl-memory-limit = Volcar a un archivo temporal los cuerpos mayores que SIZE al formatear (por defecto 64M)
+help-rurl-pager = Paginar la salida del terminal con $PAGER aunque quepa en pantalla
+help-rurl-no-pager = No paginar nunca la salida del terminal
+help-rurl-copy = Copiar también el cuerpo al portapapeles, o solo copiarlo con --copy=only
+help-rurl-no-progress-meter = Desactivar el indicador de progreso
+help-rurl-output = Escribir la salida en un archivo ('-' fuerza la salida estándar); repetir para escribir el mismo cuerpo en varios archivos
+help-rurl-tee = Escribir también el cuerpo en FILE además de la salida estándar o --output (repetible)
+help-rurl-extract = Mostrar NAME=valor para una ruta estilo jq (.a.b[0]) en la respuesta JSON en lugar del cuerpo (repetible)
+help-rurl-export-env = Mostrar los valores de --extract como líneas export, o escribirlos en un archivo dotenv FILE
+help-rurl-grep = Escribir solo las líneas del cuerpo que coinciden con la expresión regular PATTERN; código de salida 1 si ninguna coincide
+help-rurl-grep-context = Escribir también N líneas antes y después de cada coincidencia de --grep
+help-rurl-template = Mostrar, en lugar del cuerpo, un informe generado a partir de la respuesta con TEMPLATE
+help-rurl-list-links = Mostrar, en lugar del cuerpo, las URL absolutas a las que enlaza la respuesta HTML (href y src)
+help-rurl-download-links = Descargar en el directorio actual los enlaces de la respuesta HTML cuya URL coincide con la expresión regular PATTERN
+help-rurl-recursive = Reflejar el sitio: guardar la página y, nivel a nivel, las páginas y archivos que enlaza
+help-rurl-depth = Con --recursive, seguir enlaces como máximo N niveles desde la primera página (5 por defecto)
+help-rurl-accept-regex = Con --recursive, seguir solo los enlaces cuya URL coincide con PATTERN en lugar de los del mismo host
+help-rurl-wait = Esperar al menos SECONDS segundos entre dos peticiones --recursive al mismo host (1 por defecto)
+help-rurl-manifest = Escribir el manifiesto de --recursive en FILE (rurl-manifest.json por defecto)
+help-rurl-remote-name = Escribir la salida en un archivo con el nombre del archivo remoto
+help-rurl-open = Abrir el cuerpo guardado con la aplicación predeterminada (un archivo temporal sin -o)
+help-rurl-print-filename = Mostrar la ruta donde se guardó el cuerpo (un archivo temporal sin -o)
+help-rurl-remote-time = Fijar la fecha del archivo de salida según la cabecera Last-Modified
+help-rurl-remove-on-error = Borrar el archivo de salida si la transferencia falla
+help-rurl-fail = Fallar con el código 22 ante errores HTTP sin escribir el cuerpo
+help-rurl-strict = Tratar como errores las opciones que no tienen efecto en esta combinación
+help-rurl-dry-run = Mostrar la petición que se enviaría, con los secretos ocultos, sin enviarla
+help-rurl-show-secrets = Mostrar credenciales y valores de cookies en la salida de --dry-run
+help-rurl-no-clobber = No sobrescribir nunca un archivo de salida existente
+help-rurl-user = Autenticación HTTP
+help-rurl-proxy = Usar un servidor proxy
+help-rurl-preproxy = Llegar al proxy pasando primero por este proxy SOCKS
+help-rurl-proxy-user = Autenticación del proxy
+help-rurl-proxy-keyring = Leer la contraseña del proxy desde el llavero del sistema (ver 'rurl auth add')
+help-rurl-proxy-pac = Elegir proxys con un script de configuración automática (PAC)
+help-rurl-ssh-tunnel = Enviar las peticiones por un túnel SOCKS sobre SSH a un host de salto
+help-rurl-proxytunnel = Tunelizar todas las peticiones a través del proxy HTTP con CONNECT
+help-rurl-insecure = Permitir conexiones SSL inseguras
+help-rurl-location = Seguir redirecciones
+help-rurl-show-redirect-chain = Mostrar en stderr el estado y el destino de redirección de cada salto
+help-rurl-location-trusted = Seguir redirecciones y enviar credenciales a otros hosts
+help-rurl-include = Incluir las cabeceras de respuesta en la salida
+help-rurl-log-format = Formato de los diagnósticos en stderr (json: un objeto por evento y línea)
+help-rurl-log-file = Añadir los registros y los eventos de las peticiones a PATH
+help-rurl-log-level = Nivel más detallado escrito en --log-file (por defecto: info)
+help-rurl-metrics-out = Escribir las métricas de la ejecución en formato de texto de Prometheus en FILE
+help-rurl-summary = Mostrar en stderr el estado, el tamaño del cuerpo, el tiempo y la URL final tras la transferencia
+help-rurl-summary-budget = Con --summary, avisar cuando el cuerpo o el tiempo de transferencia superen el presupuesto
+help-rurl-stats = Mostrar en stderr los recuentos de conexiones, DNS y bytes de la ejecución
+help-rurl-raw = Emitir los bytes de la respuesta sin decodificar el contenido ni convertir el juego de caracteres
+help-rurl-hexdump = Escribir el cuerpo de la respuesta como volcado de desplazamiento/hex/ASCII
+help-rurl-output-charset = Recodificar el texto decodificado de la respuesta en CHARSET antes de escribirlo
+help-rurl-max-redirs = Número máximo de redirecciones que seguir (-1 para ilimitado)
+help-rurl-allow-host = Enviar peticiones solo a hosts que coincidan con PATTERN (glob o CIDR), redirecciones incluidas
+help-rurl-deny-host = No enviar nunca peticiones a hosts que coincidan con PATTERN (glob o CIDR), redirecciones incluidas
+help-rurl-no-internal-targets = Rechazar direcciones de bucle local, privadas y de enlace local, también tras redirecciones y DNS
+help-rurl-post301 = No cambiar POST por GET tras un 301
+help-rurl-post302 = No cambiar POST por GET tras un 302
+help-rurl-post303 = No cambiar POST por GET tras un 303
+help-rurl-user-agent = Cabecera User-Agent
+help-rurl-timeout = Tiempo máximo de cada intento de petición
+help-rurl-max-time = Tiempo máximo de toda la transferencia, incluidos reintentos y redirecciones
+help-rurl-max-header-size = Fallar si una respuesta tiene más de SIZE bytes de cabeceras (por defecto 300k)
+help-rurl-max-headers = Fallar si una respuesta tiene más de COUNT campos de cabecera (por defecto 100)
+help-rurl-no-keepalive = Enviar Connection: close y abrir una conexión nueva para cada petición
+help-rurl-read-timeout = Espera máxima de datos de respuesta
+help-rurl-write-timeout = Tiempo máximo que puede detenerse una subida en streaming
+help-rurl-connect-timeout = Tiempo máximo de conexión
+help-rurl-local-port = Puerto local o rango de puertos para la conexión
+help-rurl-daemon = Ejecutar un agente que mantiene conexiones y cookies del navegador listas para ejecuciones posteriores
+help-rurl-daemon-socket = Socket del agente que se ejecuta o por el que se envían las peticiones
+help-rurl-no-daemon = Enviar las peticiones directamente aunque haya un agente en ejecución
+help-rurl-speed-limit = Abortar si es más lento que BYTES/s durante --speed-time segundos
+help-rurl-speed-time = Ventana de --speed-limit (30 por defecto)
+help-rurl-retry = Número de reintentos
+help-rurl-retry-delay = Espera entre reintentos
+help-rurl-retry-max-delay = Espera máxima aceptada de Retry-After
+help-rurl-request-id = Enviar un UUID nuevo por transferencia en HEADER (por defecto X-Request-Id)
+help-rurl-idempotency-key = Enviar Idempotency-Key: KEY en cada reintento, o un UUID nuevo por transferencia con 'auto'
+help-rurl-retry-rate-limit = Con 429, esperar hasta la hora de RateLimit-Reset o X-RateLimit-Reset
+help-rurl-retry-all-errors = Con --retry, reintentar ante cualquier error de transferencia
+help-rurl-cacert = Archivo con certificados de CA
+help-rurl-cert = Archivo de certificado de cliente, o URI pkcs11: con --engine pkcs11
+help-rurl-engine = Motor criptográfico que guarda la clave del cliente (pkcs11)
+help-rurl-key = Archivo de clave privada
+help-rurl-lang = Idioma de los mensajes y la ayuda (p. ej. ja, de, fr, zh-CN, es)
+help-rurl-manual = Mostrar la página de manual rurl(1) en formato roff
+about-rurl-cookies = Inspeccionar y exportar las cookies del navegador
+about-rurl-cookies-export = Exportar las cookies del navegador a un archivo
+help-rurl-cookies-export-cookies-from-browser = Navegador del que leer las cookies
+help-rurl-cookies-export-output = Escribir las cookies en un archivo ('-' u omitido: salida estándar)
+help-rurl-cookies-export-domain = Exportar solo las cookies de DOMAIN y sus subdominios
+help-rurl-cookies-export-format = Formato de salida
+about-rurl-cookies-list-profiles = Listar los perfiles del navegador y los contenedores de Firefox
+help-rurl-cookies-list-profiles-browser = Navegador cuyos perfiles se listan
+about-rurl-auth = Guardar credenciales en el llavero del sistema
+about-rurl-auth-add = Guardar credenciales que se envían en las siguientes peticiones al origen de la URL
+help-rurl-auth-add-url = Origen al que se aplican las credenciales
+help-rurl-auth-add-user = Credenciales de autenticación Basic
+help-rurl-auth-add-token = Token Bearer
+about-rurl-auth-remove = Borrar las credenciales guardadas para el origen de la URL
+help-rurl-auth-remove-url = Origen cuyas credenciales se borran
+about-rurl-auth-list = Listar los orígenes con credenciales guardadas
+about-rurl-auth-oauth2 = Iniciar sesión con OAuth2 y usar los tokens para el origen de la URL
+help-rurl-auth-oauth2-url = Origen al que se envía el token de acceso
+help-rurl-auth-oauth2-client-id = ID de cliente OAuth2
+help-rurl-auth-oauth2-client-secret = Secreto del cliente, para clientes confidenciales
+help-rurl-auth-oauth2-token-url = Endpoint de tokens
+help-rurl-auth-oauth2-device-code = Usar el flujo de dispositivo con este endpoint de autorización de dispositivos
+help-rurl-auth-oauth2-auth-code = Usar el flujo de código de autorización con este endpoint de autorización
+help-rurl-auth-oauth2-scope = Ámbitos solicitados, separados por espacios
+help-rurl-auth-oauth2-redirect-port = Puerto local para la redirección del código de autorización (por defecto: cualquier puerto libre)
+about-rurl-batch = Ejecutar las peticiones de un archivo NDJSON e imprimir resultados NDJSON
+help-rurl-batch-file = Peticiones, un objeto JSON por línea ('-' lee stdin)
+help-rurl-batch-parallel = Número de peticiones simultáneas
+help-rurl-batch-output = Escribir los resultados en ARCHIVO en lugar de stdout
+help-rurl-batch-fail-fast-threshold = Omitir las solicitudes restantes a un host tras N fallos consecutivos
+about-rurl-diff = Obtener dos URL e imprimir un diff unificado de las respuestas
+help-rurl-diff-url1 = Primera URL
+help-rurl-diff-url2 = Segunda URL
+help-rurl-diff-diff-against = Comparar URL1 con una respuesta guardada en FILE (cuerpo o salida de -i)
+help-rurl-diff-header = Enviar HEADER en ambas peticiones (repetible)
+help-rurl-diff-json = Comparar cuerpos JSON con claves ordenadas e indentación uniforme
+help-rurl-diff-ignore-header = Excluir la cabecera NAME de la comparación (repetible)
+help-rurl-diff-body-only = Comparar solo los cuerpos
@@ -0,0 +1,203 @@
+error-http = Erreur HTTP : {$detail}
+error-invalid-url = URL invalide : {$detail}
+error-json = Erreur d'analyse JSON : {$detail}
+error-ssl = Erreur SSL/TLS : {$detail}
+error-proxy = Erreur de proxy : {$detail}
+error-auth = Erreur d'authentification : {$detail}
+error-config = Erreur de configuration : {$detail}
+error-timeout = Délai réseau dépassé
+error-too-slow = Transfert trop lent : {$detail}
+error-redirect-limit = Nombre maximal de redirections dépassé ({$limit})
+error-permission-denied = Permission refusée : {$detail}
+error-file-not-found = Fichier introuvable : {$detail}
+error-unsupported = Opération non prise en charge : {$detail}
+error-io = Erreur d'E/S : {$detail}
+error-browser-cookie = Erreur de cookie du navigateur : {$detail}
+error-aborted = Transfert interrompu
+error-resolve-host = Impossible de résoudre l'hôte : {$detail}
+error-connect-timeout = Délai de connexion dépassé
+error-empty-reply = Réponse vide du serveur
+error-recv = Échec de la réception des données : {$detail}
+error-headers-too-large = En-têtes de réponse trop volumineux : {$detail}
+error-host-blocked = Hôte non autorisé : {$detail}
+error-internal-target = Connexion à une adresse interne refusée : {$detail}
+error-http-status = L'URL demandée a renvoyé l'erreur : {$status}
+error-responses-differ = Les réponses diffèrent
+error-no-match = Aucune ligne de la réponse ne correspond à --grep
+error-hint = conseil : {$hint}
+hint-close-browser = Fermez le navigateur et réessayez.
+hint-close-browser-or-drop-elevation = Fermez le navigateur ou lancez sans élévation de privilèges.
+hint-allow-keychain-access = Autorisez rurl à accéder à l'entrée Safe Storage du navigateur dans Trousseaux d'accès.
+hint-check-host-and-port = Vérifiez l'hôte et le port ; aucune connexion n'y est acceptée.
+hint-check-host-name = Vérifiez l'orthographe du nom d'hôte et vos réglages DNS.
+hint-trust-certificate = Indiquez l'autorité émettrice avec --cacert, ou utilisez --insecure pour ignorer la vérification.
+hint-check-client-certificate = Vérifiez les fichiers passés à --cert et --key.
+hint-raise-timeout = Augmentez --timeout ou --connect-timeout, ou vérifiez le réseau.
+hint-check-proxy = Vérifiez l'URL de --proxy et les identifiants de --proxy-user.
+
+## Warnings
+warning-host-override = Host : {$host} diffère de l'URL ; connexion à {$target}
+warning-host-override-sni = Host : {$host} diffère de l'URL ; connexion à {$target} avec le SNI TLS {$sni}
+warning-host-not-forwarded = L'en-tête Host personnalisé n'est pas envoyé à {$target}
+warning-host-required = L'en-tête Host ne peut pas être omis ; il est envoyé avec l'hôte de l'URL
+
+## Command line help
+about-rurl = Une alternative moderne à curl qui prend en charge les cookies du navigateur
+help-rurl-url = L'URL à demander
+help-rurl-request = Méthode de requête HTTP
+help-rurl-header = Ajouter un en-tête HTTP
+help-rurl-url-query = Ajouter un paramètre de requête encodé en pourcentage (name=value, name@file, +raw)
+help-rurl-data = Données HTTP POST (@- envoie l'entrée standard en flux) ; plusieurs valeurs sont jointes par & en formulaire
+help-rurl-edit = Rédiger le corps de la requête dans $EDITOR, à partir de -d DATA ou -d @FILE
+help-rurl-pre-hook = Exécuter COMMAND avant la requête ; chaque ligne 'Nom: valeur' affichée est envoyée comme en-tête
+help-rurl-post-hook = Exécuter COMMAND après la réponse, avec RURL_STATUS, RURL_HEADERS et RURL_BODY_FILE définis
+help-rurl-form = Champ de formulaire multipart (@FICHIER joint un fichier, <FICHIER en lit la valeur)
+help-rurl-form-string = Champ de formulaire multipart dont la valeur est toujours littérale
+help-rurl-upload-file = Envoyer FILE (- pour l'entrée standard) en flux comme corps de la requête
+help-rurl-trailer = Envoyer un trailer après un corps chunked (VALUE @sha256/@sha1 hache le corps)
+help-rurl-data-compress = Compresser le corps de la requête (gzip, br ou zstd) et envoyer Content-Encoding
+help-rurl-upload-length = Content-Length d'un corps en flux au lieu de l'encodage chunked
+help-rurl-upload-resume = Reprendre un envoi -T à partir du décalage déjà reçu par le serveur
+help-rurl-cookies-from-browser = Extraire les cookies du navigateur
+help-rurl-token-from-browser = Envoyer une valeur du localStorage du navigateur comme jeton Authorization: Bearer
+help-rurl-jwt-sign = Signer un JWT avec KEY (PEM pour RS256/ES256, fichier secret pour HS256) et l'envoyer comme jeton Bearer
+help-rurl-jwt-claims = Objet des claims du JWT, ou @FILE pour le lire depuis un fichier
+help-rurl-jwt-alg = Algorithme de signature du JWT (par défaut : déduit de la clé)
+help-rurl-jwt-ttl = Durée de vie du JWT pour le claim exp (par défaut : 5m)
+help-rurl-cookie-cache = Réutiliser les cookies extraits du navigateur pendant DURATION (ex. 5m)
+help-rurl-use-cookie-store = Enregistrer et envoyer les cookies avec un magasin persistant nommé
+help-rurl-default-scheme = Schéma des URL données sans schéma (https-first se replie sur http)
+help-rurl-session = Enregistrer et réutiliser en-têtes, identifiants et cookies de cet hôte sous NAME
+help-rurl-cache = Mettre en cache les réponses GET sur disque et les revalider (RFC 9111)
+help-rurl-cache-mode = Utilisation des réponses stockées : default, force ou only-if-cached
+help-rurl-verbose = Sortie détaillée avec les notes de connexion (-vv ajoute le DNS et les cookies, -vvv un vidage hexadécimal du corps)
+help-rurl-silent = Mode silencieux
+help-rurl-show-error = Afficher les erreurs et avertissements même avec -s
+help-rurl-pretty-json = Mettre en forme les réponses JSON
+help-rurl-pretty-xml = Indenter les réponses XML et HTML
+help-rurl-pretty = Mettre en forme les réponses : auto choisit JSON, XML ou HTML selon le Content-Type
+help-rurl-memory-limit = Déverser dans un fichier temporaire les corps plus grands que SIZE lors du formatage (64M par défaut)
+help-rurl-pager = Paginer la sortie du terminal avec $PAGER même si elle tient à l'écran
+help-rurl-no-pager = Ne jamais paginer la sortie du terminal
+help-rurl-copy = Copier aussi le corps dans le presse-papiers, ou seulement le copier avec --copy=only
+help-rurl-no-progress-meter = Désactiver l'indicateur de progression
+help-rurl-output = Écrire la sortie dans un fichier ('-' force la sortie standard) ; répéter pour écrire le même corps dans plusieurs fichiers
+help-rurl-tee = Écrire aussi le corps dans FILE en plus de la sortie standard ou de --output (répétable)
+help-rurl-extract = Afficher NAME=valeur pour un chemin de style jq (.a.b[0]) dans la réponse JSON au lieu du corps (répétable)
+help-rurl-export-env = Afficher les valeurs de --extract en lignes export, ou les écrire dans un fichier dotenv FILE
+help-rurl-grep = N'écrire que les lignes du corps correspondant à l'expression régulière PATTERN ; code de sortie 1 si aucune ne correspond
+help-rurl-grep-context = Écrire aussi N lignes avant et après chaque correspondance de --grep
+help-rurl-template = Afficher, au lieu du corps, un rapport généré à partir de la réponse avec TEMPLATE
+help-rurl-list-links = Afficher, au lieu du corps, les URL absolues vers lesquelles pointe la réponse HTML (href et src)
+help-rurl-download-links = Télécharger dans le répertoire courant les liens de la réponse HTML dont l'URL correspond à l'expression régulière PATTERN
+help-rurl-recursive = Créer un miroir du site : enregistrer la page puis, niveau par niveau, les pages et fichiers liés
+help-rurl-depth = Avec --recursive, suivre les liens sur au plus N niveaux depuis la première page (5 par défaut)
+help-rurl-accept-regex = Avec --recursive, ne suivre que les liens dont l'URL correspond à PATTERN au lieu de ceux du même hôte
+help-rurl-wait = Attendre au moins SECONDS secondes entre deux requêtes --recursive vers le même hôte (1 par défaut)
+help-rurl-manifest = Écrire le manifeste de --recursive dans FILE (rurl-manifest.json par défaut)
+help-rurl-remote-name = Écrire la sortie dans un fichier nommé comme le fichier distant
+help-rurl-open = Ouvrir le corps enregistré avec l’application par défaut (un fichier temporaire sans -o)
+help-rurl-print-filename = Afficher le chemin où le corps a été enregistré (un fichier temporaire sans -o)
+help-rurl-remote-time = Dater le fichier de sortie d'après l'en-tête Last-Modified
+help-rurl-remove-on-error = Supprimer le fichier de sortie si le transfert échoue
+help-rurl-fail = Échouer avec le code 22 sur une erreur HTTP sans écrire le corps
+help-rurl-strict = Traiter comme des erreurs les options sans effet dans cette combinaison
+help-rurl-dry-run = Afficher la requête qui serait envoyée, secrets masqués, sans l'envoyer
+help-rurl-show-secrets = Afficher les identifiants et les valeurs des cookies dans la sortie de --dry-run
+help-rurl-no-clobber = Ne jamais écraser un fichier de sortie existant
+help-rurl-user = Authentification HTTP
+help-rurl-proxy = Utiliser un serveur proxy
+help-rurl-preproxy = Joindre le proxy en passant d’abord par ce proxy SOCKS
+help-rurl-proxy-user = Authentification du proxy
+help-rurl-proxy-keyring = Lire le mot de passe du proxy dans le trousseau du système (voir 'rurl auth add')
+help-rurl-proxy-pac = Choisir les proxys avec un script de configuration automatique (PAC)
+help-rurl-ssh-tunnel = Envoyer les requêtes via un tunnel SOCKS sur SSH vers un hôte de rebond
+help-rurl-proxytunnel = Faire passer toutes les requêtes par un tunnel CONNECT via le proxy HTTP
+help-rurl-insecure = Autoriser les connexions SSL non sécurisées
+help-rurl-location = Suivre les redirections
+help-rurl-show-redirect-chain = Afficher sur stderr le statut et la cible de redirection de chaque étape
+help-rurl-location-trusted = Suivre les redirections et envoyer les identifiants aux autres hôtes
+help-rurl-include = Inclure les en-têtes de réponse dans la sortie
+help-rurl-log-format = Format des diagnostics sur stderr (json : un objet par événement et par ligne)
+help-rurl-log-file = Ajouter les journaux et les événements des requêtes à PATH
+help-rurl-log-level = Niveau le plus détaillé écrit dans --log-file (par défaut : info)
+help-rurl-metrics-out = Écrire les métriques de l'exécution au format texte Prometheus dans FILE
+help-rurl-summary = Afficher sur stderr le statut, la taille du corps, la durée et l’URL finale après le transfert
+help-rurl-summary-budget = Avec --summary, avertir quand le corps ou la durée du transfert dépasse le budget
+help-rurl-stats = Afficher sur stderr les compteurs de connexions, DNS et octets de l’exécution
+help-rurl-raw = Sortir les octets de la réponse sans décodage du contenu ni conversion de jeu de caractères
+help-rurl-hexdump = Écrire le corps de la réponse sous forme de vidage décalage/hexa/ASCII
+help-rurl-output-charset = Réencoder le texte décodé de la réponse en CHARSET avant de l'écrire
+help-rurl-max-redirs = Nombre maximal de redirections à suivre (-1 pour illimité)
+help-rurl-allow-host = N'envoyer de requêtes qu'aux hôtes correspondant à PATTERN (glob ou CIDR), redirections comprises
+help-rurl-deny-host = Ne jamais envoyer de requêtes aux hôtes correspondant à PATTERN (glob ou CIDR), redirections comprises
+help-rurl-no-internal-targets = Refuser les adresses de bouclage, privées et lien-local, y compris après redirection et DNS
+help-rurl-post301 = Ne pas remplacer POST par GET après un 301
+help-rurl-post302 = Ne pas remplacer POST par GET après un 302
+help-rurl-post303 = Ne pas remplacer POST par GET après un 303
+help-rurl-user-agent = En-tête User-Agent
+help-rurl-timeout = Durée maximale de chaque tentative de requête
+help-rurl-max-time = Durée maximale du transfert complet, tentatives et redirections comprises
+help-rurl-max-header-size = Échouer si une réponse a plus de SIZE octets d'en-têtes (300k par défaut)
+help-rurl-max-headers = Échouer si une réponse a plus de COUNT champs d'en-tête (100 par défaut)
+help-rurl-no-keepalive = Envoyer Connection: close et ouvrir une nouvelle connexion pour chaque requête
+help-rurl-read-timeout = Attente maximale des données de réponse
+help-rurl-write-timeout = Durée maximale de blocage d'un envoi en flux
+help-rurl-connect-timeout = Durée maximale de la connexion
+help-rurl-local-port = Port local ou plage de ports pour la connexion
+help-rurl-daemon = Lancer un agent qui garde connexions et cookies du navigateur prêts pour les exécutions suivantes
+help-rurl-daemon-socket = Socket de l'agent à lancer ou par lequel envoyer les requêtes
+help-rurl-no-daemon = Envoyer les requêtes directement même si un agent est lancé
+help-rurl-speed-limit = Interrompre si plus lent que BYTES/s pendant --speed-time secondes
+help-rurl-speed-time = Fenêtre de --speed-limit (30 par défaut)
+help-rurl-retry = Nombre de nouvelles tentatives
+help-rurl-retry-delay = Délai entre les tentatives
+help-rurl-retry-max-delay = Délai maximal accepté depuis Retry-After
+help-rurl-request-id = Envoyer un nouvel UUID par transfert dans HEADER (X-Request-Id par défaut)
+help-rurl-idempotency-key = Envoyer Idempotency-Key: KEY à chaque nouvel essai, ou un nouvel UUID par transfert avec 'auto'
+help-rurl-retry-rate-limit = Sur 429, attendre l’heure indiquée par RateLimit-Reset ou X-RateLimit-Reset
+help-rurl-retry-all-errors = Avec --retry, réessayer après toute erreur de transfert
+help-rurl-cacert = Fichier de certificats d'autorité (CA)
+help-rurl-cert = Fichier de certificat client, ou URI pkcs11: avec --engine pkcs11
+help-rurl-engine = Moteur cryptographique détenant la clé client (pkcs11)
+help-rurl-key = Fichier de clé privée
+help-rurl-lang = Langue des messages et de l'aide (ex. ja, de, fr, zh-CN, es)
+help-rurl-manual = Afficher la page de manuel rurl(1) au format roff
+about-rurl-cookies = Inspecter et exporter les cookies du navigateur
+about-rurl-cookies-export = Exporter les cookies du navigateur dans un fichier
+help-rurl-cookies-export-cookies-from-browser = Navigateur dont lire les cookies
+help-rurl-cookies-export-output = Écrire les cookies dans un fichier ('-' ou absent : sortie standard)
+help-rurl-cookies-export-domain = N'exporter que les cookies de DOMAIN et de ses sous-domaines
+help-rurl-cookies-export-format = Format de sortie
+about-rurl-cookies-list-profiles = Lister les profils du navigateur et les conteneurs Firefox
+help-rurl-cookies-list-profiles-browser = Navigateur dont lister les profils
+about-rurl-auth = Stocker des identifiants dans le trousseau du système
+about-rurl-auth-add = Enregistrer des identifiants envoyés aux requêtes suivantes vers l'origine de l'URL
+help-rurl-auth-add-url = Origine à laquelle s'appliquent les identifiants
+help-rurl-auth-add-user = Identifiants d'authentification Basic
+help-rurl-auth-add-token = Jeton Bearer
+about-rurl-auth-remove = Supprimer les identifiants enregistrés pour l'origine de l'URL
+help-rurl-auth-remove-url = Origine dont supprimer les identifiants
+about-rurl-auth-list = Lister les origines ayant des identifiants enregistrés
+about-rurl-auth-oauth2 = Se connecter avec OAuth2 et utiliser les jetons pour l'origine de l'URL
+help-rurl-auth-oauth2-url = Origine à laquelle le jeton d'accès est envoyé
+help-rurl-auth-oauth2-client-id = Identifiant du client OAuth2
+help-rurl-auth-oauth2-client-secret = Secret du client, pour les clients confidentiels
+help-rurl-auth-oauth2-token-url = Point de terminaison des jetons
+help-rurl-auth-oauth2-device-code = Utiliser le flux appareil avec ce point d'autorisation d'appareil
+help-rurl-auth-oauth2-auth-code = Utiliser le flux code d'autorisation avec ce point d'autorisation
+help-rurl-auth-oauth2-scope = Portées demandées, séparées par des espaces
+help-rurl-auth-oauth2-redirect-port = Port local pour la redirection du code d'autorisation (par défaut : un port libre)
+about-rurl-batch = Exécuter les requêtes d'un fichier NDJSON et afficher des résultats NDJSON
+help-rurl-batch-file = Requêtes, un objet JSON par ligne ('-' lit stdin)
+help-rurl-batch-parallel = Nombre de requêtes simultanées
+help-rurl-batch-output = Écrire les résultats dans FICHIER au lieu de stdout
+help-rurl-batch-fail-fast-threshold = Ignorer les requêtes restantes vers un hôte après N échecs consécutifs
+about-rurl-diff = Récupérer deux URL et afficher un diff unifié des réponses
+help-rurl-diff-url1 = Première URL
+help-rurl-diff-url2 = Seconde URL
+help-rurl-diff-diff-against = Comparer URL1 à une réponse enregistrée dans FILE (corps ou sortie de -i)
+help-rurl-diff-header = Envoyer HEADER avec les deux requêtes (répétable)
+help-rurl-diff-json = Comparer les corps JSON avec clés triées et indentation uniforme
+help-rurl-diff-ignore-header = Exclure l'en-tête NAME de la comparaison (répétable)
+help-rurl-diff-body-only = Comparer uniquement les corps
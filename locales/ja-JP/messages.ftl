@@ -6,9 +6,198 @@ error-proxy = プロキシエラー: {$detail}
 error-auth = 認証エラー: {$detail}
 error-config = 設定エラー: {$detail}
 error-timeout = ネットワークタイムアウト
+error-too-slow = 転送速度が遅すぎます: {$detail}
 error-redirect-limit = リダイレクト回数の上限に到達しました ({$limit})
 error-permission-denied = 権限がありません: {$detail}
 error-file-not-found = ファイルが見つかりません: {$detail}
 error-unsupported = 未対応の操作です: {$detail}
 error-io = 入出力エラー: {$detail}
 error-browser-cookie = ブラウザクッキーエラー: {$detail}
+error-aborted = 転送を中断しました
+error-resolve-host = ホスト名を解決できません: {$detail}
+error-connect-timeout = 接続がタイムアウトしました
+error-empty-reply = サーバーから空の応答が返されました
+error-recv = データの受信に失敗しました: {$detail}
+error-headers-too-large = レスポンスヘッダーが大きすぎます: {$detail}
+error-host-blocked = 許可されていないホストです: {$detail}
+error-internal-target = 内部アドレスへの接続を拒否しました: {$detail}
+error-http-status = 要求したURLがエラーを返しました: {$status}
+error-responses-differ = レスポンスに差分があります
+error-no-match = レスポンスに --grep に一致する行がありません
+error-hint = ヒント: {$hint}
+hint-close-browser = ブラウザを閉じてから再実行してください。
+hint-close-browser-or-drop-elevation = ブラウザを閉じるか、管理者権限なしで実行してください。
+hint-allow-keychain-access = キーチェーンアクセスでブラウザのSafe Storage項目へのアクセスを許可してください。
+hint-check-host-and-port = ホストとポートを確認してください。接続を受け付けていません。
+hint-check-host-name = ホスト名の綴りとDNS設定を確認してください。
+hint-trust-certificate = --cacert で発行元のCAを指定するか、--insecure で検証を省略してください。
+hint-check-client-certificate = --cert と --key で指定したファイルを確認してください。
+hint-raise-timeout = --timeout か --connect-timeout を増やすか、ネットワークを確認してください。
+hint-check-proxy = --proxy のURLと --proxy-user の認証情報を確認してください。
+
+## Warnings
+warning-host-override = Host: {$host} は URL と異なります。{$target} に接続します
+warning-host-override-sni = Host: {$host} は URL と異なります。{$target} に TLS SNI {$sni} で接続します
+warning-host-not-forwarded = カスタム Host ヘッダーは {$target} に送信しません
+warning-host-required = Host ヘッダーは省略できません。URL のホストで送信します
+
+## Command line help
+about-rurl = ブラウザのクッキーに対応したモダンなcurl代替ツール
+help-rurl-url = リクエストするURL
+help-rurl-request = HTTPリクエストメソッド
+help-rurl-header = HTTPヘッダーを追加
+help-rurl-url-query = パーセントエンコードしたクエリパラメータを追加 (name=value, name@file, +raw)
+help-rurl-data = HTTP POSTデータ (@- で標準入力をストリーム送信)。複数指定すると & で連結してフォームとして送信
+help-rurl-edit = $EDITOR でリクエスト本文を書く（-d DATA または -d @FILE の内容から開始）
+help-rurl-pre-hook = リクエスト前に COMMAND を実行し、出力された 'Name: value' 行をヘッダーとして送る
+help-rurl-post-hook = レスポンス後に RURL_STATUS、RURL_HEADERS、RURL_BODY_FILE を設定して COMMAND を実行する
+help-rurl-form = マルチパートフォームのフィールド（@FILE でファイル添付、<FILE で値をファイルから読む）
+help-rurl-form-string = 値を常にそのまま送るマルチパートフォームのフィールド
+help-rurl-upload-file = FILE (- は標準入力) をリクエストボディとしてストリーム送信
+help-rurl-trailer = チャンク形式のボディの後にトレーラーを送信 (VALUE @sha256/@sha1 でボディのハッシュ)
+help-rurl-data-compress = リクエスト本文を圧縮（gzip、br、zstd）し Content-Encoding を送信
+help-rurl-upload-length = ストリーム送信するボディのContent-Length (チャンク形式の代わり)
+help-rurl-upload-resume = -T のアップロードをサーバーが受信済みのオフセットから再開する
+help-rurl-cookies-from-browser = ブラウザからクッキーを取得
+help-rurl-token-from-browser = ブラウザのlocalStorageの値をAuthorization: Bearerトークンとして送信
+help-rurl-jwt-sign = KEY (RS256/ES256はPEM、HS256は秘密鍵ファイル) でJWTに署名しBearerトークンとして送信
+help-rurl-jwt-claims = JWTのクレーム (JSONオブジェクト、または @FILE でファイルから読み込み)
+help-rurl-jwt-alg = JWTの署名アルゴリズム (既定: 鍵から推定)
+help-rurl-jwt-ttl = expクレームに使うJWTの有効期間 (既定: 5m)
+help-rurl-cookie-cache = ブラウザから取得したクッキーをDURATIONの間再利用 (例: 5m)
+help-rurl-use-cookie-store = 名前付きの永続クッキーストアでクッキーを保存・送信
+help-rurl-default-scheme = スキームのないURLに使うスキーム (https-first はhttpにフォールバック)
+help-rurl-session = このホストのヘッダー・認証・クッキーをNAMEとして保存し再利用
+help-rurl-cache = GETレスポンスをディスクにキャッシュし再検証 (RFC 9111)
+help-rurl-cache-mode = 保存済みレスポンスの使い方: default、force、only-if-cached
+help-rurl-verbose = 接続の通知付きで詳細を出力 (-vv で DNS と Cookie、-vvv で本文の16進ダンプを追加)
+help-rurl-silent = サイレントモード
+help-rurl-show-error = -s 指定時もエラーと警告を表示
+help-rurl-pretty-json = JSONレスポンスを整形して表示
+help-rurl-pretty-xml = XML・HTMLレスポンスをインデントして表示
+help-rurl-pretty = レスポンスを整形: auto は Content-Type から JSON・XML・HTML を判別
+help-rurl-memory-limit = 整形時に SIZE を超える本文を一時ファイルに退避（既定 64M）
+help-rurl-pager = 画面に収まる場合も端末出力を $PAGER で表示
+help-rurl-no-pager = 端末出力をページャーに通さない
+help-rurl-copy = ボディを出力に加えてクリップボードにコピーする（--copy=only でコピーのみ）
+help-rurl-no-progress-meter = 進捗表示を無効化
+help-rurl-output = ファイルに出力 ('-' は標準出力を強制)。繰り返すと同じ本文を複数のファイルに書き込む
+help-rurl-tee = 標準出力または --output に加えて本文を FILE にも書き込む (複数指定可)
+help-rurl-extract = 本文の代わりに、JSON レスポンス内の jq 形式のパス (.a.b[0]) の値を NAME=value で表示（複数指定可）
+help-rurl-export-env = --extract の値を export 行で表示、または dotenv 形式の FILE に書き込む
+help-rurl-grep = 正規表現 PATTERN に一致する本文の行だけを出力 (一致がなければ終了コード 1)
+help-rurl-grep-context = --grep の各一致の前後 N 行も出力
+help-rurl-template = 本文の代わりに、TEMPLATE でレスポンスから作ったレポートを出力
+help-rurl-list-links = 本文の代わりに、HTML レスポンスのリンク先 (href と src) を絶対 URL で出力
+help-rurl-download-links = HTML レスポンスのリンクのうち URL が正規表現 PATTERN に一致するものをカレントディレクトリにダウンロード
+help-rurl-recursive = サイトをミラーする: ページと、そこから段階的にリンクされたページやファイルを保存
+help-rurl-depth = --recursive で最初のページから最大 N 段までリンクをたどる (既定 5)
+help-rurl-accept-regex = --recursive で同じホストのリンクの代わりに URL が PATTERN に一致するリンクだけをたどる
+help-rurl-wait = 同じホストへの --recursive リクエストの間隔を少なくとも SECONDS 秒空ける (既定 1)
+help-rurl-manifest = --recursive のマニフェストを FILE に書き出す (既定 rurl-manifest.json)
+help-rurl-remote-name = リモートファイルと同じ名前のファイルに出力
+help-rurl-open = 保存したボディを既定のアプリケーションで開く（-o がなければ一時ファイル）
+help-rurl-print-filename = ボディの保存先パスを表示する（-o がなければ一時ファイル）
+help-rurl-remote-time = Last-Modifiedヘッダーから出力ファイルの時刻を設定
+help-rurl-remove-on-error = 転送に失敗したら出力ファイルを削除
+help-rurl-fail = HTTPエラーのとき本文を書き出さずに終了コード22で失敗
+help-rurl-strict = この組み合わせでは効果のないオプションをエラーにする
+help-rurl-dry-run = 送信せずに、送信されるリクエストを秘密情報を伏せて表示
+help-rurl-show-secrets = --dry-run の出力で認証情報と Cookie の値を表示
+help-rurl-no-clobber = 既存の出力ファイルを上書きしない
+help-rurl-user = HTTP認証
+help-rurl-proxy = プロキシサーバーを使用
+help-rurl-preproxy = プロキシへはまずこの SOCKS プロキシ経由で接続する
+help-rurl-proxy-user = プロキシ認証
+help-rurl-proxy-keyring = プロキシのパスワードを OS のキーリングから読み込む（'rurl auth add' を参照）
+help-rurl-proxy-pac = プロキシ自動設定 (PAC) スクリプトでプロキシを選択
+help-rurl-ssh-tunnel = 踏み台ホストへの SSH 上の SOCKS トンネル経由でリクエストを送信
+help-rurl-proxytunnel = すべてのリクエストをCONNECTでHTTPプロキシ経由のトンネルにする
+help-rurl-insecure = 安全でないSSL接続を許可
+help-rurl-location = リダイレクトに従う
+help-rurl-show-redirect-chain = 各ホップのステータスとリダイレクト先を標準エラーに表示する
+help-rurl-location-trusted = リダイレクトに従い、他のホストにも認証情報を送信
+help-rurl-include = レスポンスヘッダーを出力に含める
+help-rurl-log-format = 標準エラーの診断形式 (json: 1行に1イベントのオブジェクト)
+help-rurl-log-file = ログとリクエストのイベントをPATHに追記
+help-rurl-log-level = --log-file に書き出す最も詳細なレベル (既定: info)
+help-rurl-metrics-out = 実行のメトリクスをPrometheusテキスト形式でFILEに書き出す
+help-rurl-summary = 転送後にステータス、ボディサイズ、所要時間、最終 URL を標準エラーに表示する
+help-rurl-summary-budget = --summary 使用時、ボディや転送時間が予算を超えたら警告する
+help-rurl-stats = 実行全体の接続数・DNS 参照数・バイト数を stderr に出力
+help-rurl-raw = コンテンツのデコードや文字コード変換をせずにレスポンスのバイト列を出力
+help-rurl-hexdump = レスポンス本文をオフセット・16進・ASCII のダンプ形式で出力
+help-rurl-output-charset = デコードしたレスポンス本文を書き出す前に CHARSET へ再エンコード
+help-rurl-max-redirs = 従うリダイレクトの最大回数 (-1 で無制限)
+help-rurl-allow-host = PATTERN (glob または CIDR) に一致するホストにだけリクエストを送る (リダイレクト先も含む)
+help-rurl-deny-host = PATTERN (glob または CIDR) に一致するホストにはリクエストを送らない (リダイレクト先も含む)
+help-rurl-no-internal-targets = ループバック・プライベート・リンクローカルアドレスへの接続を拒否する (リダイレクト後や DNS 解決後も)
+help-rurl-post301 = 301の後にPOSTをGETに切り替えない
+help-rurl-post302 = 302の後にPOSTをGETに切り替えない
+help-rurl-post303 = 303の後にPOSTをGETに切り替えない
+help-rurl-user-agent = User-Agentヘッダー
+help-rurl-timeout = 各リクエスト試行の最大時間
+help-rurl-max-time = リトライとリダイレクトを含む転送全体の最大時間
+help-rurl-max-header-size = レスポンスヘッダーが SIZE バイトを超えたら失敗する (既定 300k)
+help-rurl-max-headers = レスポンスヘッダーが COUNT 個を超えたら失敗する (既定 100)
+help-rurl-no-keepalive = Connection: close を送り、リクエストごとに新しい接続を開く
+help-rurl-read-timeout = レスポンスデータの最大待ち時間
+help-rurl-write-timeout = ストリーミングアップロードが停滞できる最大時間
+help-rurl-connect-timeout = 接続の最大時間
+help-rurl-local-port = 接続に使うローカルポートまたはポート範囲
+help-rurl-daemon = 接続とブラウザ Cookie を保持し、後続の実行で再利用するエージェントを起動する
+help-rurl-daemon-socket = 起動する、またはリクエストを送るエージェントのソケット
+help-rurl-no-daemon = エージェントが動作中でもリクエストを直接送信する
+help-rurl-speed-limit = --speed-time 秒の間 BYTES/秒 より遅ければ中断
+help-rurl-speed-time = --speed-limit の判定時間 (既定 30)
+help-rurl-retry = 再試行の回数
+help-rurl-retry-delay = 再試行の間隔
+help-rurl-retry-max-delay = Retry-Afterに従う最大の待ち時間
+help-rurl-request-id = 転送ごとに新しい UUID を HEADER で送信 (既定は X-Request-Id)
+help-rurl-idempotency-key = すべての再試行で Idempotency-Key: KEY を送る（'auto' なら転送ごとに新しい UUID）
+help-rurl-retry-rate-limit = 429 の場合は RateLimit-Reset または X-RateLimit-Reset の時刻まで待つ
+help-rurl-retry-all-errors = --retry 指定時、あらゆる転送エラーで再試行する
+help-rurl-cacert = CA証明書バンドルファイル
+help-rurl-cert = クライアント証明書ファイル、または --engine pkcs11 と使う pkcs11: URI
+help-rurl-engine = クライアント鍵を保持する暗号エンジン (pkcs11)
+help-rurl-key = 秘密鍵ファイル
+help-rurl-lang = メッセージとヘルプの言語 (例: ja, de, fr, zh-CN, es)
+help-rurl-manual = rurl(1) の man ページを roff 形式で出力
+about-rurl-cookies = ブラウザのクッキーを確認・エクスポート
+about-rurl-cookies-export = ブラウザのクッキーをファイルにエクスポート
+help-rurl-cookies-export-cookies-from-browser = クッキーを読み込むブラウザ
+help-rurl-cookies-export-output = クッキーの出力先ファイル ('-' または省略で標準出力)
+help-rurl-cookies-export-domain = DOMAINとそのサブドメインのクッキーのみエクスポート
+help-rurl-cookies-export-format = 出力形式
+about-rurl-cookies-list-profiles = ブラウザのプロファイルとFirefoxコンテナを一覧表示
+help-rurl-cookies-list-profiles-browser = プロファイルを一覧表示するブラウザ
+about-rurl-auth = OSのキーリングに認証情報を保存
+about-rurl-auth-add = URLのオリジンへの以降のリクエストで送る認証情報を保存
+help-rurl-auth-add-url = 認証情報を適用するオリジン
+help-rurl-auth-add-user = Basic認証の認証情報
+help-rurl-auth-add-token = Bearerトークン
+about-rurl-auth-remove = URLのオリジンに保存した認証情報を削除
+help-rurl-auth-remove-url = 認証情報を削除するオリジン
+about-rurl-auth-list = 認証情報を保存したオリジンを一覧表示
+about-rurl-auth-oauth2 = OAuth2 でサインインし、トークンを URL のオリジンに使う
+help-rurl-auth-oauth2-url = アクセストークンを送るオリジン
+help-rurl-auth-oauth2-client-id = OAuth2 クライアント ID
+help-rurl-auth-oauth2-client-secret = クライアントシークレット (コンフィデンシャルクライアント用)
+help-rurl-auth-oauth2-token-url = トークンエンドポイント
+help-rurl-auth-oauth2-device-code = このデバイス認可エンドポイントでデバイスフローを使う
+help-rurl-auth-oauth2-auth-code = この認可エンドポイントで認可コードフローを使う
+help-rurl-auth-oauth2-scope = 要求するスコープ (空白区切り)
+help-rurl-auth-oauth2-redirect-port = 認可コードのリダイレクトを受けるローカルポート (既定: 空いているポート)
+about-rurl-batch = NDJSON ファイルのリクエストを実行し、結果を NDJSON で出力する
+help-rurl-batch-file = リクエスト（1 行に 1 つの JSON オブジェクト、'-' で標準入力）
+help-rurl-batch-parallel = 同時に実行するリクエスト数
+help-rurl-batch-output = 結果を標準出力ではなく FILE に書き込む
+help-rurl-batch-fail-fast-threshold = N 回連続で失敗したホストへの残りのリクエストを省略
+about-rurl-diff = 2つのURLを取得し、レスポンスの unified diff を表示
+help-rurl-diff-url1 = 1つ目のURL
+help-rurl-diff-url2 = 2つ目のURL
+help-rurl-diff-diff-against = URL1 を FILE に保存したレスポンス（本文または -i の出力）と比較
+help-rurl-diff-header = 両方のリクエストに HEADER を付与（複数指定可）
+help-rurl-diff-json = JSON本文をキーでソートし、インデントを揃えて比較
+help-rurl-diff-ignore-header = ヘッダー NAME を比較対象から除外（複数指定可）
+help-rurl-diff-body-only = 本文のみ比較
@@ -0,0 +1,203 @@
+error-http = HTTP 错误：{$detail}
+error-invalid-url = 无效的 URL：{$detail}
+error-json = JSON 解析错误：{$detail}
+error-ssl = SSL/TLS 错误：{$detail}
+error-proxy = 代理错误：{$detail}
+error-auth = 认证错误：{$detail}
+error-config = 配置错误：{$detail}
+error-timeout = 网络超时
+error-too-slow = 传输速度过慢：{$detail}
+error-redirect-limit = 超过重定向次数上限（{$limit}）
+error-permission-denied = 权限不足：{$detail}
+error-file-not-found = 找不到文件：{$detail}
+error-unsupported = 不支持的操作：{$detail}
+error-io = 输入输出错误：{$detail}
+error-browser-cookie = 浏览器 Cookie 错误：{$detail}
+error-aborted = 传输已中止
+error-resolve-host = 无法解析主机：{$detail}
+error-connect-timeout = 连接超时
+error-empty-reply = 服务器返回空响应
+error-recv = 接收数据失败：{$detail}
+error-headers-too-large = 响应头过大：{$detail}
+error-host-blocked = 不允许的主机：{$detail}
+error-internal-target = 拒绝连接到内部地址：{$detail}
+error-http-status = 请求的 URL 返回错误：{$status}
+error-responses-differ = 响应存在差异
+error-no-match = 响应中没有与 --grep 匹配的行
+error-hint = 提示：{$hint}
+hint-close-browser = 请关闭浏览器后重试。
+hint-close-browser-or-drop-elevation = 请关闭浏览器，或不以提升的权限运行。
+hint-allow-keychain-access = 请在钥匙串访问中允许 rurl 访问浏览器的 Safe Storage 条目。
+hint-check-host-and-port = 请检查主机和端口；那里没有接受连接的服务。
+hint-check-host-name = 请检查主机名是否拼写正确以及 DNS 设置。
+hint-trust-certificate = 请用 --cacert 指定签发的 CA，或用 --insecure 跳过验证。
+hint-check-client-certificate = 请检查 --cert 和 --key 指定的文件。
+hint-raise-timeout = 请增大 --timeout 或 --connect-timeout，或检查网络。
+hint-check-proxy = 请检查 --proxy 的 URL 和 --proxy-user 的认证信息。
+
+## Warnings
+warning-host-override = Host: {$host} 与 URL 不同；正在连接 {$target}
+warning-host-override-sni = Host: {$host} 与 URL 不同；正在使用 TLS SNI {$sni} 连接 {$target}
+warning-host-not-forwarded = 不向 {$target} 发送自定义 Host 头
+warning-host-required = 无法省略 Host 头；将使用 URL 中的主机发送
+
+## Command line help
+about-rurl = 支持浏览器 Cookie 的现代 curl 替代工具
+help-rurl-url = 要请求的 URL
+help-rurl-request = HTTP 请求方法
+help-rurl-header = 添加自定义 HTTP 头
+help-rurl-url-query = 追加百分号编码的查询参数（name=value、name@file、+raw）
+help-rurl-data = HTTP POST 数据（@- 以流方式发送标准输入）；多次指定时用 & 连接为表单
+help-rurl-edit = 在 $EDITOR 中编写请求体，以 -d DATA 或 -d @FILE 的内容为起点
+help-rurl-pre-hook = 在请求前运行 COMMAND；其输出的每行 'Name: value' 作为请求头发送
+help-rurl-post-hook = 收到响应后运行 COMMAND，并设置 RURL_STATUS、RURL_HEADERS 和 RURL_BODY_FILE
+help-rurl-form = multipart 表单字段（@FILE 附加文件，<FILE 从文件读取值）
+help-rurl-form-string = 值始终按字面发送的 multipart 表单字段
+help-rurl-upload-file = 以流方式将 FILE（- 表示标准输入）作为请求体发送
+help-rurl-trailer = 在分块请求体之后发送 trailer（VALUE 为 @sha256/@sha1 时为请求体的哈希）
+help-rurl-data-compress = 压缩请求体（gzip、br 或 zstd）并发送 Content-Encoding
+help-rurl-upload-length = 流式请求体的 Content-Length，代替分块编码
+help-rurl-upload-resume = 从服务器已接收的偏移处继续 -T 上传
+help-rurl-cookies-from-browser = 从浏览器提取 Cookie
+help-rurl-token-from-browser = 将浏览器 localStorage 中的值作为 Authorization: Bearer 令牌发送
+help-rurl-jwt-sign = 用 KEY 签名 JWT（RS256/ES256 用 PEM，HS256 用密钥文件）并作为 Bearer 令牌发送
+help-rurl-jwt-claims = JWT 声明对象，或用 @FILE 从文件读取
+help-rurl-jwt-alg = JWT 签名算法（默认根据密钥推断）
+help-rurl-jwt-ttl = 用于 exp 声明的 JWT 有效期（默认 5m）
+help-rurl-cookie-cache = 在 DURATION 内复用从浏览器提取的 Cookie（例如 5m）
+help-rurl-use-cookie-store = 使用命名的持久 Cookie 存储保存和发送 Cookie
+help-rurl-default-scheme = 未指定协议的 URL 使用的协议（https-first 失败时回退到 http）
+help-rurl-session = 以 NAME 保存并复用此主机的请求头、认证信息和 Cookie
+help-rurl-cache = 在磁盘上缓存 GET 响应并重新验证（RFC 9111）
+help-rurl-cache-mode = 已存储响应的使用方式：default、force 或 only-if-cached
+help-rurl-verbose = 详细输出，含连接提示（-vv 增加 DNS 和 Cookie，-vvv 增加响应体十六进制转储）
+help-rurl-silent = 静默模式
+help-rurl-show-error = 即使使用 -s 也显示错误和警告
+help-rurl-pretty-json = 格式化输出 JSON 响应
+help-rurl-pretty-xml = 缩进显示 XML 和 HTML 响应
+help-rurl-pretty = 格式化响应：auto 根据 Content-Type 选择 JSON、XML 或 HTML
+help-rurl-memory-limit = 格式化时将大于 SIZE 的响应体转存到临时文件（默认 64M）
+help-rurl-pager = 即使终端输出能在一屏内显示，也通过 $PAGER 分页
+help-rurl-no-pager = 从不对终端输出分页
+help-rurl-copy = 将响应体同时复制到剪贴板，使用 --copy=only 时只复制
+help-rurl-no-progress-meter = 禁用进度显示
+help-rurl-output = 将输出写入文件（'-' 强制输出到标准输出）；重复指定可将同一响应体写入多个文件
+help-rurl-tee = 在写入标准输出或 --output 的同时，也将响应体写入 FILE（可重复）
+help-rurl-extract = 不输出响应体，改为输出 JSON 响应中 jq 风格路径 (.a.b[0]) 的 NAME=value（可重复）
+help-rurl-export-env = 以 export 行输出 --extract 的值，或写入 dotenv 文件 FILE
+help-rurl-grep = 只输出响应体中匹配正则表达式 PATTERN 的行；没有匹配时退出码为 1
+help-rurl-grep-context = 同时输出每个 --grep 匹配前后的 N 行
+help-rurl-template = 用 TEMPLATE 根据响应生成报告并输出，代替响应体
+help-rurl-list-links = 输出 HTML 响应中链接（href 和 src）的绝对 URL，代替响应体
+help-rurl-download-links = 将 HTML 响应中 URL 匹配正则表达式 PATTERN 的链接下载到当前目录
+help-rurl-recursive = 镜像站点：保存页面并逐层保存其链接到的页面和文件
+help-rurl-depth = 使用 --recursive 时从第一个页面起最多跟随 N 层链接（默认 5）
+help-rurl-accept-regex = 使用 --recursive 时只跟随 URL 匹配 PATTERN 的链接，而不是同一主机上的链接
+help-rurl-wait = 对同一主机的两次 --recursive 请求之间至少等待 SECONDS 秒（默认 1）
+help-rurl-manifest = 将 --recursive 的清单写入 FILE（默认 rurl-manifest.json）
+help-rurl-remote-name = 将输出写入与远程文件同名的文件
+help-rurl-open = 用默认应用打开保存的响应体（未指定 -o 时为临时文件）
+help-rurl-print-filename = 打印响应体的保存路径（未指定 -o 时为临时文件）
+help-rurl-remote-time = 根据 Last-Modified 头设置输出文件的时间
+help-rurl-remove-on-error = 传输失败时删除输出文件
+help-rurl-fail = HTTP 错误时不输出响应体并以退出码 22 失败
+help-rurl-strict = 将在此组合中不起作用的选项视为错误
+help-rurl-dry-run = 不发送请求，只打印将要发送的请求（隐藏敏感信息）
+help-rurl-show-secrets = 在 --dry-run 输出中显示凭据和 Cookie 值
+help-rurl-no-clobber = 从不覆盖已有的输出文件
+help-rurl-user = HTTP 认证
+help-rurl-proxy = 使用代理服务器
+help-rurl-preproxy = 先通过此 SOCKS 代理连接到代理服务器
+help-rurl-proxy-user = 代理认证
+help-rurl-proxy-keyring = 从操作系统密钥环读取代理密码（参见 'rurl auth add'）
+help-rurl-proxy-pac = 通过代理自动配置（PAC）脚本选择代理
+help-rurl-ssh-tunnel = 通过到跳板机的 SSH SOCKS 隧道发送请求
+help-rurl-proxytunnel = 通过 HTTP 代理以 CONNECT 隧道发送所有请求
+help-rurl-insecure = 允许不安全的 SSL 连接
+help-rurl-location = 跟随重定向
+help-rurl-show-redirect-chain = 向标准错误输出每一跳的状态和重定向目标
+help-rurl-location-trusted = 跟随重定向并向其他主机发送认证信息
+help-rurl-include = 在输出中包含响应头
+help-rurl-log-format = 标准错误上的诊断格式（json：每行一个事件对象）
+help-rurl-log-file = 将日志记录和请求事件追加到 PATH
+help-rurl-log-level = 写入 --log-file 的最详细级别（默认 info）
+help-rurl-metrics-out = 将本次运行的指标以 Prometheus 文本格式写入 FILE
+help-rurl-summary = 传输结束后向标准错误输出状态、响应体大小、耗时和最终 URL
+help-rurl-summary-budget = 配合 --summary，在响应体或传输时间超出预算时发出警告
+help-rurl-stats = 在 stderr 输出本次运行的连接、DNS 和字节计数
+help-rurl-raw = 不解码内容、不转换字符集，直接输出响应字节
+help-rurl-hexdump = 以偏移/十六进制/ASCII 转储格式输出响应体
+help-rurl-output-charset = 写出前将解码后的响应文本重新编码为 CHARSET
+help-rurl-max-redirs = 最多跟随的重定向次数（-1 表示不限）
+help-rurl-allow-host = 仅向匹配 PATTERN（glob 或 CIDR）的主机发送请求，包括重定向
+help-rurl-deny-host = 从不向匹配 PATTERN（glob 或 CIDR）的主机发送请求，包括重定向
+help-rurl-no-internal-targets = 拒绝回环、私有和链路本地地址，重定向和 DNS 解析后同样适用
+help-rurl-post301 = 301 之后不将 POST 改为 GET
+help-rurl-post302 = 302 之后不将 POST 改为 GET
+help-rurl-post303 = 303 之后不将 POST 改为 GET
+help-rurl-user-agent = User-Agent 头
+help-rurl-timeout = 每次请求尝试的最长时间
+help-rurl-max-time = 整个传输（含重试和重定向）的最长时间
+help-rurl-max-header-size = 响应头超过 SIZE 字节时失败（默认 300k）
+help-rurl-max-headers = 响应头字段超过 COUNT 个时失败（默认 100）
+help-rurl-no-keepalive = 发送 Connection: close，并为每个请求打开新连接
+help-rurl-read-timeout = 等待响应数据的最长时间
+help-rurl-write-timeout = 流式上传停滞的最长时间
+help-rurl-connect-timeout = 连接的最长时间
+help-rurl-local-port = 连接绑定的本地端口或端口范围
+help-rurl-daemon = 运行一个代理进程，为后续调用保持连接和浏览器 Cookie
+help-rurl-daemon-socket = 要运行或用于发送请求的代理套接字
+help-rurl-no-daemon = 即使代理正在运行也直接发送请求
+help-rurl-speed-limit = 在 --speed-time 秒内低于 BYTES/秒 时中止
+help-rurl-speed-time = --speed-limit 的时间窗口（默认 30）
+help-rurl-retry = 重试次数
+help-rurl-retry-delay = 重试间隔
+help-rurl-retry-max-delay = 接受的 Retry-After 最长等待时间
+help-rurl-request-id = 每次传输在 HEADER 中发送新的 UUID（默认 X-Request-Id）
+help-rurl-idempotency-key = 每次重试都发送 Idempotency-Key: KEY；使用 'auto' 时每次传输生成新的 UUID
+help-rurl-retry-rate-limit = 遇到 429 时等待至 RateLimit-Reset 或 X-RateLimit-Reset 指定的时间
+help-rurl-retry-all-errors = 配合 --retry，在任何传输错误时重试
+help-rurl-cacert = CA 证书包文件
+help-rurl-cert = 客户端证书文件，或配合 --engine pkcs11 使用的 pkcs11: URI
+help-rurl-engine = 保存客户端密钥的加密引擎（pkcs11）
+help-rurl-key = 私钥文件
+help-rurl-lang = 消息和帮助的语言（例如 ja、de、fr、zh-CN、es）
+help-rurl-manual = 以 roff 格式输出 rurl(1) 手册页
+about-rurl-cookies = 查看并导出浏览器 Cookie
+about-rurl-cookies-export = 将浏览器 Cookie 导出到文件
+help-rurl-cookies-export-cookies-from-browser = 读取 Cookie 的浏览器
+help-rurl-cookies-export-output = 将 Cookie 写入文件（'-' 或省略时写到标准输出）
+help-rurl-cookies-export-domain = 只导出 DOMAIN 及其子域名的 Cookie
+help-rurl-cookies-export-format = 输出格式
+about-rurl-cookies-list-profiles = 列出浏览器配置文件和 Firefox 容器
+help-rurl-cookies-list-profiles-browser = 要列出配置文件的浏览器
+about-rurl-auth = 在操作系统密钥环中保存认证信息
+about-rurl-auth-add = 保存之后发往该 URL 源的请求所使用的认证信息
+help-rurl-auth-add-url = 认证信息适用的源
+help-rurl-auth-add-user = Basic 认证信息
+help-rurl-auth-add-token = Bearer 令牌
+about-rurl-auth-remove = 删除为该 URL 源保存的认证信息
+help-rurl-auth-remove-url = 要删除认证信息的源
+about-rurl-auth-list = 列出已保存认证信息的源
+about-rurl-auth-oauth2 = 通过 OAuth2 登录并将令牌用于 URL 的源
+help-rurl-auth-oauth2-url = 发送访问令牌的源
+help-rurl-auth-oauth2-client-id = OAuth2 客户端 ID
+help-rurl-auth-oauth2-client-secret = 客户端密钥（用于机密客户端）
+help-rurl-auth-oauth2-token-url = 令牌端点
+help-rurl-auth-oauth2-device-code = 使用此设备授权端点进行设备流程
+help-rurl-auth-oauth2-auth-code = 使用此授权端点进行授权码流程
+help-rurl-auth-oauth2-scope = 请求的范围，以空格分隔
+help-rurl-auth-oauth2-redirect-port = 接收授权码重定向的本地端口（默认：任意空闲端口）
+about-rurl-batch = 执行 NDJSON 文件中的请求并输出 NDJSON 结果
+help-rurl-batch-file = 请求，每行一个 JSON 对象（'-' 读取标准输入）
+help-rurl-batch-parallel = 同时进行的请求数
+help-rurl-batch-output = 将结果写入 FILE 而不是标准输出
+help-rurl-batch-fail-fast-threshold = 某主机连续失败 N 次后跳过发往该主机的其余请求
+about-rurl-diff = 获取两个 URL 并输出响应的统一格式差异
+help-rurl-diff-url1 = 第一个 URL
+help-rurl-diff-url2 = 第二个 URL
+help-rurl-diff-diff-against = 将 URL1 与保存在 FILE 中的响应（响应体或 -i 输出）比较
+help-rurl-diff-header = 在两个请求中都发送 HEADER（可重复）
+help-rurl-diff-json = 按排序后的键和统一缩进比较 JSON 响应体
+help-rurl-diff-ignore-header = 比较时忽略头部 NAME（可重复）
+help-rurl-diff-body-only = 仅比较响应体
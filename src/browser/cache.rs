@@ -0,0 +1,281 @@
+//! Encrypted on-disk cache of extracted browser cookies
+//!
+//! Decrypting browser cookies can require a keychain prompt or a slow key
+//! derivation, so `--cookie-cache DURATION` keeps the result under
+//! `~/.cache/rurl/cookies` (the platform cache directory). Entries are
+//! encrypted with AES-256-CBC and authenticated with HMAC-SHA256, using
+//! random keys stored next to them with owner-only permissions, and are
+//! discarded once the TTL has passed or the browser's cookie database (or
+//! its WAL) has been modified.
+
+use crate::browser::{Cookie, CookieStore};
+use crate::config::BrowserCookieConfig;
+use crate::error::{Result, RurlError};
+use aes::Aes256;
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const KEY_FILE: &str = "key";
+/// An AES-256 key followed by an HMAC-SHA256 key
+const KEY_LEN: usize = 64;
+const IV_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+
+/// Cache of decrypted browser cookies keyed by browser, profile and filter
+pub struct CookieCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    created: SystemTime,
+    source: PathBuf,
+    source_modified: Option<SystemTime>,
+    wal_modified: Option<SystemTime>,
+    cookies: Vec<Cookie>,
+}
+
+impl CookieCache {
+    /// Cache in the default location with the given time to live
+    pub fn new(ttl: Duration) -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| RurlError::Config("Cannot determine cache directory".to_string()))?;
+        Ok(Self::with_dir(cache_dir.join("rurl").join("cookies"), ttl))
+    }
+
+    /// Cache in a specific directory
+    pub fn with_dir(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    /// Return cached cookies if they are fresh and `source` is unchanged
+    pub fn load(&self, config: &BrowserCookieConfig, source: &Path) -> Option<CookieStore> {
+        let data = fs::read(self.entry_path(config)).ok()?;
+        if data.len() < IV_LEN + TAG_LEN {
+            return None;
+        }
+        let key = fs::read(self.dir.join(KEY_FILE)).ok()?;
+        if key.len() != KEY_LEN {
+            return None;
+        }
+        let (enc_key, mac_key) = key.split_at(KEY_LEN / 2);
+        let (sealed, tag) = data.split_at(data.len() - TAG_LEN);
+        let mut mac = Hmac::<Sha256>::new_from_slice(mac_key).ok()?;
+        mac.update(sealed);
+        mac.verify_slice(tag).ok()?;
+        let (iv, ciphertext) = sealed.split_at(IV_LEN);
+        let mut buffer = ciphertext.to_vec();
+        let plaintext = cbc::Decryptor::<Aes256>::new_from_slices(enc_key, iv)
+            .ok()?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .ok()?;
+        let entry: CacheEntry = serde_json::from_slice(plaintext).ok()?;
+
+        let age = SystemTime::now().duration_since(entry.created).ok()?;
+        if age >= self.ttl
+            || entry.source != source
+            || entry.source_modified != modified(source)
+            || entry.wal_modified != modified(&wal_path(source))
+        {
+            return None;
+        }
+
+        let mut store = CookieStore::new();
+        for cookie in entry.cookies {
+            store.entry(cookie.domain.clone()).or_default().push(cookie);
+        }
+        Some(store)
+    }
+
+    /// Save cookies extracted from `source`
+    pub fn store(
+        &self,
+        config: &BrowserCookieConfig,
+        source: &Path,
+        cookies: &CookieStore,
+    ) -> Result<()> {
+        let entry = CacheEntry {
+            created: SystemTime::now(),
+            source: source.to_path_buf(),
+            source_modified: modified(source),
+            wal_modified: modified(&wal_path(source)),
+            cookies: cookies.values().flatten().cloned().collect(),
+        };
+        let mut buffer = serde_json::to_vec(&entry)?;
+
+        let key = self.load_or_create_key()?;
+        let (enc_key, mac_key) = key.split_at(KEY_LEN / 2);
+        let mut iv = [0u8; IV_LEN];
+        getrandom::getrandom(&mut iv).map_err(std::io::Error::from)?;
+        // Room for the PKCS#7 padding, at most one (IV-sized) block
+        let len = buffer.len();
+        buffer.resize(len + IV_LEN, 0);
+        let encrypt_error = || RurlError::Config("Failed to encrypt cookie cache".to_string());
+        let ciphertext = cbc::Encryptor::<Aes256>::new_from_slices(enc_key, &iv)
+            .map_err(|_| encrypt_error())?
+            .encrypt_padded_mut::<Pkcs7>(&mut buffer, len)
+            .map_err(|_| encrypt_error())?;
+
+        let mut data = iv.to_vec();
+        data.extend_from_slice(ciphertext);
+        let mut mac = Hmac::<Sha256>::new_from_slice(mac_key).map_err(|_| encrypt_error())?;
+        mac.update(&data);
+        data.extend_from_slice(&mac.finalize().into_bytes());
+        write_private(&self.entry_path(config), &data)
+    }
+
+    fn load_or_create_key(&self) -> Result<Vec<u8>> {
+        let path = self.dir.join(KEY_FILE);
+        if let Ok(key) = fs::read(&path) {
+            if key.len() == KEY_LEN {
+                return Ok(key);
+            }
+        }
+        let mut key = vec![0u8; KEY_LEN];
+        getrandom::getrandom(&mut key).map_err(std::io::Error::from)?;
+        write_private(&path, &key)?;
+        Ok(key)
+    }
+
+    fn entry_path(&self, config: &BrowserCookieConfig) -> PathBuf {
+        let descriptor = format!(
+            "{:?}\n{}\n{}\n{}\n{}",
+            config.browser,
+            config.profile.as_deref().unwrap_or(""),
+            config.container.as_deref().unwrap_or(""),
+            config.keyring.as_deref().unwrap_or(""),
+            config.domain_filter.as_deref().unwrap_or(""),
+        );
+        let name: String = Sha1::digest(descriptor.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        self.dir.join(name)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn wal_path(db: &Path) -> PathBuf {
+    let mut name = db.as_os_str().to_os_string();
+    name.push("-wal");
+    PathBuf::from(name)
+}
+
+/// Write a file readable only by the current user
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(parent, fs::Permissions::from_mode(0o700))?;
+        }
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    std::io::Write::write_all(&mut file, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CookieCache;
+    use crate::browser::{Cookie, CookieStore};
+    use crate::config::{Browser, BrowserCookieConfig};
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    fn config(profile: Option<&str>) -> BrowserCookieConfig {
+        BrowserCookieConfig {
+            browser: Browser::Firefox,
+            profile: profile.map(str::to_string),
+            container: None,
+            keyring: None,
+            domain_filter: None,
+        }
+    }
+
+    fn store() -> CookieStore {
+        let mut store = CookieStore::new();
+        store.insert(
+            "example.com".to_string(),
+            vec![Cookie {
+                name: "sid".to_string(),
+                value: "secret-value".to_string(),
+                domain: "example.com".to_string(),
+                path: "/".to_string(),
+                secure: true,
+                http_only: true,
+                expires: None,
+            }],
+        );
+        store
+    }
+
+    #[test]
+    fn cache_round_trips_encrypted() {
+        let dir = tempdir().expect("tempdir");
+        let source = dir.path().join("cookies.sqlite");
+        std::fs::write(&source, b"db").expect("source");
+        let cache = CookieCache::with_dir(dir.path().join("cache"), Duration::from_secs(300));
+
+        assert!(cache.load(&config(None), &source).is_none());
+        cache
+            .store(&config(None), &source, &store())
+            .expect("store");
+        let loaded = cache.load(&config(None), &source).expect("cached");
+        assert_eq!(loaded["example.com"][0].value, "secret-value");
+        assert!(cache.load(&config(Some("work")), &source).is_none());
+
+        let entry = cache.entry_path(&config(None));
+        let mut raw = std::fs::read(&entry).expect("entry");
+        assert!(!String::from_utf8_lossy(&raw).contains("secret-value"));
+
+        // A modified entry fails authentication
+        raw[20] ^= 1;
+        std::fs::write(&entry, &raw).expect("tamper");
+        assert!(cache.load(&config(None), &source).is_none());
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_source_change_and_ttl() {
+        let dir = tempdir().expect("tempdir");
+        let source = dir.path().join("Cookies");
+        std::fs::write(&source, b"db").expect("source");
+        let cache = CookieCache::with_dir(dir.path().join("cache"), Duration::from_secs(300));
+        cache
+            .store(&config(None), &source, &store())
+            .expect("store");
+
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&source)
+            .expect("open");
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .expect("touch");
+        assert!(cache.load(&config(None), &source).is_none());
+
+        let expired = CookieCache::with_dir(dir.path().join("cache"), Duration::ZERO);
+        expired
+            .store(&config(None), &source, &store())
+            .expect("store");
+        assert!(expired.load(&config(None), &source).is_none());
+    }
+}
@@ -15,7 +15,6 @@ use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use tempfile::tempdir;
 
 use crate::utils::FileUtils;
 
@@ -31,6 +30,14 @@ struct ChromiumSettings {
     supports_profiles: bool,
 }
 
+/// Path of the cookie database `extract_chromium_cookies` would read
+pub(super) fn cookie_db_path(
+    browser: ChromiumBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<PathBuf> {
+    find_cookie_database(&chromium_settings(browser)?, config.profile.as_deref())
+}
+
 pub fn extract_chromium_cookies(
     browser: ChromiumBrowser,
     config: &BrowserCookieConfig,
@@ -39,15 +46,7 @@ pub fn extract_chromium_cookies(
     let profile = config.profile.as_deref();
     let cookie_db = find_cookie_database(&settings, profile)?;
 
-    let temp_dir = tempdir()
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
-    let temp_db = temp_dir.path().join("chromium-cookies.sqlite");
-    fs::copy(&cookie_db, &temp_db).map_err(|e| {
-        crate::browser::map_cookie_io_error("Failed to copy cookies DB", &cookie_db, e, None)
-    })?;
-
-    let conn = Connection::open(&temp_db)
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to open cookies DB: {}", e)))?;
+    let (conn, _temp_dir) = super::open_cookie_db(&cookie_db, None)?;
     let meta_version = read_meta_version(&conn);
     let column_names = read_cookie_columns(&conn)?;
     let secure_column = if column_names.contains("is_secure") {
@@ -67,23 +66,22 @@ pub fn extract_chromium_cookies(
         LinuxChromeCookieDecryptor::new(&settings, meta_version, config.keyring.as_deref())?;
     let mut store: CookieStore = CookieStore::new();
 
-    let query = if let Some(httponly) = httponly_column {
-        format!(
-            "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, {} FROM cookies",
-            secure_column, httponly
-        )
-    } else {
-        format!(
-            "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, 0 FROM cookies",
-            secure_column
-        )
-    };
+    let (conditions, params): (Vec<String>, Vec<String>) =
+        crate::browser::host_filter("host_key", config.domain_filter.as_deref())
+            .into_iter()
+            .unzip();
+    let query = format!(
+        "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, {} FROM cookies{}",
+        secure_column,
+        httponly_column.unwrap_or("0"),
+        crate::browser::sql_where(&conditions)
+    );
 
     let mut stmt = conn
         .prepare(&query)
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare cookie query: {}", e)))?;
     let mut rows = stmt
-        .query([])
+        .query(rusqlite::params_from_iter(params.iter()))
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to query cookies: {}", e)))?;
 
     while let Some(row) = rows
@@ -104,10 +102,15 @@ pub fn extract_chromium_cookies(
     Ok(store)
 }
 
+/// Root directory holding `Local State` and the profile directories
+pub(super) fn user_data_dir(browser: ChromiumBrowser) -> Result<PathBuf> {
+    Ok(chromium_settings(browser)?.user_data_dir)
+}
+
 fn chromium_settings(browser: ChromiumBrowser) -> Result<ChromiumSettings> {
-    let config_home = config_dir().or_else(|| home_dir().map(|home| home.join(".config")));
-    let config_home = config_home
-        .ok_or_else(|| RurlError::Config("Cannot determine config directory".to_string()))?;
+    let home = home_dir()
+        .ok_or_else(|| RurlError::Config("Cannot determine home directory".to_string()))?;
+    let config_home = config_dir().unwrap_or_else(|| home.join(".config"));
     let (relative_dir, keyring_name, supports_profiles) = match browser {
         ChromiumBrowser::Chrome => ("google-chrome", "Chrome", true),
         ChromiumBrowser::Edge => ("microsoft-edge", "Chromium", true),
@@ -115,14 +118,83 @@ fn chromium_settings(browser: ChromiumBrowser) -> Result<ChromiumSettings> {
         ChromiumBrowser::Opera => ("opera", "Chromium", false),
         ChromiumBrowser::Vivaldi => ("vivaldi", "Chrome", true),
         ChromiumBrowser::Whale => ("naver-whale", "Whale", true),
+        ChromiumBrowser::Chromium => ("chromium", "Chromium", true),
+        ChromiumBrowser::Arc => {
+            return Err(RurlError::Unsupported(
+                "Arc is not available on Linux".to_string(),
+            ))
+        }
     };
+
+    let mut candidates = user_data_dir_candidates(
+        &home,
+        config_home.join(relative_dir),
+        sandboxed_app_ids(browser),
+        relative_dir,
+    );
+    if matches!(browser, ChromiumBrowser::Chromium) {
+        // The Chromium snap keeps its profile outside the usual config layout
+        candidates.push(home.join("snap/chromium/common/chromium"));
+    }
+    let user_data_dir = select_user_data_dir(&candidates);
+    log::info!("Using Chromium data dir {:?}", user_data_dir);
+
     Ok(ChromiumSettings {
-        user_data_dir: config_home.join(relative_dir),
+        user_data_dir,
         keyring_name,
         supports_profiles,
     })
 }
 
+/// Flatpak app id and snap name for each browser, where one exists
+fn sandboxed_app_ids(browser: ChromiumBrowser) -> (Option<&'static str>, Option<&'static str>) {
+    match browser {
+        ChromiumBrowser::Chrome => (Some("com.google.Chrome"), None),
+        ChromiumBrowser::Edge => (Some("com.microsoft.Edge"), None),
+        ChromiumBrowser::Brave => (Some("com.brave.Browser"), Some("brave")),
+        ChromiumBrowser::Opera => (Some("com.opera.Opera"), Some("opera")),
+        ChromiumBrowser::Vivaldi => (Some("com.vivaldi.Vivaldi"), Some("vivaldi")),
+        ChromiumBrowser::Chromium => (Some("org.chromium.Chromium"), None),
+        ChromiumBrowser::Whale | ChromiumBrowser::Arc => (None, None),
+    }
+}
+
+/// Native install first, then Flatpak, then Snap
+fn user_data_dir_candidates(
+    home: &Path,
+    native: PathBuf,
+    (flatpak_id, snap_name): (Option<&str>, Option<&str>),
+    relative_dir: &str,
+) -> Vec<PathBuf> {
+    let mut candidates = vec![native];
+    if let Some(flatpak_id) = flatpak_id {
+        candidates.push(
+            home.join(".var/app")
+                .join(flatpak_id)
+                .join("config")
+                .join(relative_dir),
+        );
+    }
+    if let Some(snap_name) = snap_name {
+        candidates.push(
+            home.join("snap")
+                .join(snap_name)
+                .join("current/.config")
+                .join(relative_dir),
+        );
+    }
+    candidates
+}
+
+fn select_user_data_dir(candidates: &[PathBuf]) -> PathBuf {
+    candidates
+        .iter()
+        .find(|candidate| candidate.is_dir())
+        .or_else(|| candidates.first())
+        .cloned()
+        .unwrap_or_default()
+}
+
 fn find_cookie_database(settings: &ChromiumSettings, profile: Option<&str>) -> Result<PathBuf> {
     let search_root = if let Some(profile) = profile {
         if is_path_like(profile) {
@@ -134,7 +206,7 @@ fn find_cookie_database(settings: &ChromiumSettings, profile: Option<&str>) -> R
         } else if settings.supports_profiles {
             settings.user_data_dir.join(profile)
         } else {
-            log::warn!("Profile selection is not supported for this browser");
+            crate::warning!("Profile selection is not supported for this browser; ignoring it");
             settings.user_data_dir.clone()
         }
     } else {
@@ -336,7 +408,10 @@ impl LinuxChromeCookieDecryptor {
                 self.meta_version >= 24,
             )
         } else {
-            log::warn!("Unknown Chrome cookie version: {:?}", version);
+            crate::warning!(
+                "Skipping cookie with unknown Chrome encryption version {:?}",
+                String::from_utf8_lossy(version)
+            );
             None
         }
     }
@@ -365,7 +440,7 @@ fn decrypt_aes_cbc_multi(
             }
         }
     }
-    log::warn!("Failed to decrypt Chrome cookie: UTF-8 decode failed");
+    crate::warning!("Failed to decrypt a Chrome cookie; skipping it");
     None
 }
 
@@ -514,7 +589,66 @@ fn parse_linux_keyring(value: &str) -> Result<LinuxKeyring> {
 }
 
 fn get_kwallet_password(browser_keyring_name: &str, keyring: LinuxKeyring) -> Vec<u8> {
-    let network_wallet = get_kwallet_network_wallet(keyring);
+    match get_kwallet_password_dbus(browser_keyring_name, keyring) {
+        Ok(password) => password,
+        Err(err) => {
+            log::debug!("KWallet D-Bus lookup failed, trying kwallet-query: {}", err);
+            get_kwallet_password_cli(browser_keyring_name, keyring)
+        }
+    }
+}
+
+/// D-Bus service name and object path of the kwalletd for `keyring`
+fn kwallet_service(keyring: LinuxKeyring) -> Option<(&'static str, &'static str)> {
+    match keyring {
+        LinuxKeyring::KWallet => Some(("org.kde.kwalletd", "/modules/kwalletd")),
+        LinuxKeyring::KWallet5 => Some(("org.kde.kwalletd5", "/modules/kwalletd5")),
+        LinuxKeyring::KWallet6 => Some(("org.kde.kwalletd6", "/modules/kwalletd6")),
+        _ => None,
+    }
+}
+
+/// Read the password by talking to kwalletd over the session bus
+fn get_kwallet_password_dbus(
+    browser_keyring_name: &str,
+    keyring: LinuxKeyring,
+) -> zbus::Result<Vec<u8>> {
+    const APP_ID: &str = "rurl";
+    let (service_name, wallet_path) = kwallet_service(keyring)
+        .ok_or_else(|| zbus::Error::Failure("not a KWallet keyring".to_string()))?;
+    let connection = zbus::blocking::Connection::session()?;
+    let proxy =
+        zbus::blocking::Proxy::new(&connection, service_name, wallet_path, "org.kde.KWallet")?;
+
+    let network_wallet: String = proxy.call("networkWallet", &())?;
+    let handle: i32 = proxy.call("open", &(network_wallet.as_str(), 0i64, APP_ID))?;
+    if handle < 0 {
+        return Err(zbus::Error::Failure(format!(
+            "failed to open wallet {}",
+            network_wallet
+        )));
+    }
+
+    let folder = format!("{} Keys", browser_keyring_name);
+    let key = format!("{} Safe Storage", browser_keyring_name);
+    let password: zbus::Result<String> = proxy.call(
+        "readPassword",
+        &(handle, folder.as_str(), key.as_str(), APP_ID),
+    );
+    if let Err(err) = proxy.call::<_, _, i32>("close", &(handle, false, APP_ID)) {
+        log::debug!("Failed to close KWallet handle: {}", err);
+    }
+
+    let password = password?;
+    if password.is_empty() {
+        log::debug!("No {} entry in KWallet folder {}", key, folder);
+    }
+    Ok(password.into_bytes())
+}
+
+/// Read the password through the `kwallet-query` and `dbus-send` CLIs
+fn get_kwallet_password_cli(browser_keyring_name: &str, keyring: LinuxKeyring) -> Vec<u8> {
+    let network_wallet = get_kwallet_network_wallet_cli(keyring);
     let output = Command::new("kwallet-query")
         .args([
             "--read-password",
@@ -549,13 +683,11 @@ fn get_kwallet_password(browser_keyring_name: &str, keyring: LinuxKeyring) -> Ve
     stdout.trim_end_matches('\n').as_bytes().to_vec()
 }
 
-fn get_kwallet_network_wallet(keyring: LinuxKeyring) -> String {
+fn get_kwallet_network_wallet_cli(keyring: LinuxKeyring) -> String {
     let default_wallet = "kdewallet".to_string();
-    let (service_name, wallet_path) = match keyring {
-        LinuxKeyring::KWallet => ("org.kde.kwalletd", "/modules/kwalletd"),
-        LinuxKeyring::KWallet5 => ("org.kde.kwalletd5", "/modules/kwalletd5"),
-        LinuxKeyring::KWallet6 => ("org.kde.kwalletd6", "/modules/kwalletd6"),
-        _ => return default_wallet,
+    let (service_name, wallet_path) = match kwallet_service(keyring) {
+        Some(service) => service,
+        None => return default_wallet,
     };
 
     let output = Command::new("dbus-send")
@@ -646,3 +778,41 @@ fn get_gnome_keyring_password(browser_keyring_name: &str) -> Result<Vec<u8>> {
     log::warn!("Failed to read from keyring");
     Ok(Vec::new())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{select_user_data_dir, user_data_dir_candidates};
+    use tempfile::tempdir;
+
+    #[test]
+    fn user_data_dir_candidates_prefer_native_then_flatpak_then_snap() {
+        let home = std::path::Path::new("/home/u");
+        let candidates = user_data_dir_candidates(
+            home,
+            home.join(".config/vivaldi"),
+            (Some("com.vivaldi.Vivaldi"), Some("vivaldi")),
+            "vivaldi",
+        );
+        assert_eq!(
+            candidates,
+            vec![
+                home.join(".config/vivaldi"),
+                home.join(".var/app/com.vivaldi.Vivaldi/config/vivaldi"),
+                home.join("snap/vivaldi/current/.config/vivaldi"),
+            ]
+        );
+    }
+
+    #[test]
+    fn select_user_data_dir_picks_first_existing() {
+        let dir = tempdir().expect("tempdir");
+        let native = dir.path().join("native");
+        let snap = dir.path().join("snap");
+        std::fs::create_dir(&snap).expect("snap dir");
+
+        let candidates = vec![native.clone(), snap.clone()];
+        assert_eq!(select_user_data_dir(&candidates), snap);
+        std::fs::create_dir(&native).expect("native dir");
+        assert_eq!(select_user_data_dir(&candidates), native);
+    }
+}
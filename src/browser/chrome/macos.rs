@@ -1,7 +1,7 @@
 use super::ChromiumBrowser;
 use crate::browser::{Cookie, CookieStore};
 use crate::config::BrowserCookieConfig;
-use crate::error::{Result, RurlError};
+use crate::error::{Hint, Result, RurlError};
 use aes::Aes128;
 use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
 use dirs::home_dir;
@@ -12,7 +12,6 @@ use sha1::Sha1;
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tempfile::tempdir;
 
 use crate::utils::FileUtils;
 
@@ -28,6 +27,14 @@ struct ChromiumSettings {
     supports_profiles: bool,
 }
 
+/// Path of the cookie database `extract_chromium_cookies` would read
+pub(super) fn cookie_db_path(
+    browser: ChromiumBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<PathBuf> {
+    find_cookie_database(&chromium_settings(browser)?, config.profile.as_deref())
+}
+
 pub fn extract_chromium_cookies(
     browser: ChromiumBrowser,
     config: &BrowserCookieConfig,
@@ -36,15 +43,7 @@ pub fn extract_chromium_cookies(
     let profile = config.profile.as_deref();
     let cookie_db = find_cookie_database(&settings, profile)?;
 
-    let temp_dir = tempdir()
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
-    let temp_db = temp_dir.path().join("chromium-cookies.sqlite");
-    fs::copy(&cookie_db, &temp_db).map_err(|e| {
-        crate::browser::map_cookie_io_error("Failed to copy cookies DB", &cookie_db, e, None)
-    })?;
-
-    let conn = Connection::open(&temp_db)
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to open cookies DB: {}", e)))?;
+    let (conn, _temp_dir) = super::open_cookie_db(&cookie_db, None)?;
     let meta_version = read_meta_version(&conn);
     let column_names = read_cookie_columns(&conn)?;
     let secure_column = if column_names.contains("is_secure") {
@@ -63,23 +62,22 @@ pub fn extract_chromium_cookies(
     let decryptor = MacChromeCookieDecryptor::new(&settings, meta_version)?;
     let mut store: CookieStore = CookieStore::new();
 
-    let query = if let Some(httponly) = httponly_column {
-        format!(
-            "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, {} FROM cookies",
-            secure_column, httponly
-        )
-    } else {
-        format!(
-            "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, 0 FROM cookies",
-            secure_column
-        )
-    };
+    let (conditions, params): (Vec<String>, Vec<String>) =
+        crate::browser::host_filter("host_key", config.domain_filter.as_deref())
+            .into_iter()
+            .unzip();
+    let query = format!(
+        "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, {} FROM cookies{}",
+        secure_column,
+        httponly_column.unwrap_or("0"),
+        crate::browser::sql_where(&conditions)
+    );
 
     let mut stmt = conn
         .prepare(&query)
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare cookie query: {}", e)))?;
     let mut rows = stmt
-        .query([])
+        .query(rusqlite::params_from_iter(params.iter()))
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to query cookies: {}", e)))?;
 
     while let Some(row) = rows
@@ -100,6 +98,11 @@ pub fn extract_chromium_cookies(
     Ok(store)
 }
 
+/// Root directory holding `Local State` and the profile directories
+pub(super) fn user_data_dir(browser: ChromiumBrowser) -> Result<PathBuf> {
+    Ok(chromium_settings(browser)?.user_data_dir)
+}
+
 fn chromium_settings(browser: ChromiumBrowser) -> Result<ChromiumSettings> {
     let home = home_dir()
         .ok_or_else(|| RurlError::Config("Cannot determine home directory".to_string()))?;
@@ -111,6 +114,8 @@ fn chromium_settings(browser: ChromiumBrowser) -> Result<ChromiumSettings> {
         ChromiumBrowser::Opera => ("com.operasoftware.Opera", "Opera", false),
         ChromiumBrowser::Vivaldi => ("Vivaldi", "Vivaldi", true),
         ChromiumBrowser::Whale => ("Naver/Whale", "Whale", true),
+        ChromiumBrowser::Chromium => ("Chromium", "Chromium", true),
+        ChromiumBrowser::Arc => ("Arc/User Data", "Arc", true),
     };
     Ok(ChromiumSettings {
         user_data_dir: app_support.join(relative_dir),
@@ -130,7 +135,7 @@ fn find_cookie_database(settings: &ChromiumSettings, profile: Option<&str>) -> R
         } else if settings.supports_profiles {
             settings.user_data_dir.join(profile)
         } else {
-            log::warn!("Profile selection is not supported for this browser");
+            crate::warning!("Profile selection is not supported for this browser; ignoring it");
             settings.user_data_dir.clone()
         }
     } else {
@@ -306,7 +311,8 @@ impl MacChromeCookieDecryptor {
                     return Err(RurlError::PermissionDenied(format!(
                         "Keychain access denied for {}",
                         settings.keychain_account
-                    )));
+                    ))
+                    .with_hint(Hint::AllowKeychainAccess));
                 }
                 log::warn!(
                     "Failed to read keychain password for {}: {}",
@@ -1,18 +1,18 @@
 use super::ChromiumBrowser;
 use crate::browser::{Cookie, CookieStore};
 use crate::config::BrowserCookieConfig;
-use crate::error::{Result, RurlError};
+use crate::error::{Hint, Result, RurlError};
 use aes_gcm::aead::{Aead, KeyInit};
 use aes_gcm::Aes256Gcm;
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
 use dirs::home_dir;
 use rusqlite::{Connection, Row};
+use std::cell::Cell;
 use std::collections::HashSet;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tempfile::tempdir;
 use winapi::um::dpapi::CryptUnprotectData;
 use winapi::um::winbase::LocalFree;
 use winapi::um::wincrypt::DATA_BLOB;
@@ -20,6 +20,11 @@ use winapi::um::wincrypt::DATA_BLOB;
 use crate::utils::FileUtils;
 
 const WINDOWS_V10_PREFIX: &[u8; 3] = b"v10";
+const WINDOWS_V20_PREFIX: &[u8; 3] = b"v20";
+/// Hex-encoded, already decrypted app-bound key for `v20` cookies.
+/// Chrome 127+ wraps this key with SYSTEM-level DPAPI and an elevation
+/// service, so it cannot be recovered from a normal user process.
+const APP_BOUND_KEY_ENV: &str = "RURL_CHROME_APP_BOUND_KEY";
 const WINDOWS_DPAPI_PREFIX: &[u8] = b"DPAPI";
 const AES_GCM_NONCE_LEN: usize = 12;
 const AES_GCM_TAG_LEN: usize = 16;
@@ -29,6 +34,14 @@ struct ChromiumSettings {
     supports_profiles: bool,
 }
 
+/// Path of the cookie database `extract_chromium_cookies` would read
+pub(super) fn cookie_db_path(
+    browser: ChromiumBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<PathBuf> {
+    find_cookie_database(&chromium_settings(browser)?, config.profile.as_deref())
+}
+
 pub fn extract_chromium_cookies(
     browser: ChromiumBrowser,
     config: &BrowserCookieConfig,
@@ -37,20 +50,8 @@ pub fn extract_chromium_cookies(
     let profile = config.profile.as_deref();
     let cookie_db = find_cookie_database(&settings, profile)?;
 
-    let temp_dir = tempdir()
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
-    let temp_db = temp_dir.path().join("chromium-cookies.sqlite");
-    fs::copy(&cookie_db, &temp_db).map_err(|e| {
-        crate::browser::map_cookie_io_error(
-            "Failed to copy cookies DB",
-            &cookie_db,
-            e,
-            Some("Close the browser or run without elevation."),
-        )
-    })?;
-
-    let conn = Connection::open(&temp_db)
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to open cookies DB: {}", e)))?;
+    let (conn, _temp_dir) =
+        super::open_cookie_db(&cookie_db, Some(Hint::CloseBrowserOrDropElevation))?;
     let meta_version = read_meta_version(&conn);
     let column_names = read_cookie_columns(&conn)?;
     let secure_column = if column_names.contains("is_secure") {
@@ -69,23 +70,22 @@ pub fn extract_chromium_cookies(
     let decryptor = WindowsChromeCookieDecryptor::new(&settings, meta_version)?;
     let mut store: CookieStore = CookieStore::new();
 
-    let query = if let Some(httponly) = httponly_column {
-        format!(
-            "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, {} FROM cookies",
-            secure_column, httponly
-        )
-    } else {
-        format!(
-            "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, 0 FROM cookies",
-            secure_column
-        )
-    };
+    let (conditions, params): (Vec<String>, Vec<String>) =
+        crate::browser::host_filter("host_key", config.domain_filter.as_deref())
+            .into_iter()
+            .unzip();
+    let query = format!(
+        "SELECT host_key, name, value, encrypted_value, path, expires_utc, {}, {} FROM cookies{}",
+        secure_column,
+        httponly_column.unwrap_or("0"),
+        crate::browser::sql_where(&conditions)
+    );
 
     let mut stmt = conn
         .prepare(&query)
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare cookie query: {}", e)))?;
     let mut rows = stmt
-        .query([])
+        .query(rusqlite::params_from_iter(params.iter()))
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to query cookies: {}", e)))?;
 
     while let Some(row) = rows
@@ -97,7 +97,22 @@ pub fn extract_chromium_cookies(
         }
     }
 
+    let app_bound_skipped = decryptor.app_bound_skipped.get();
+    if app_bound_skipped > 0 {
+        crate::warning!(
+            "Skipped {} app-bound (v20) cookies; set {} to the decrypted app-bound key to read them",
+            app_bound_skipped,
+            APP_BOUND_KEY_ENV
+        );
+    }
+
     if store.is_empty() {
+        if app_bound_skipped > 0 {
+            return Err(RurlError::BrowserCookie(format!(
+                "Cookies use Chrome app-bound encryption (v20); set {} to the decrypted app-bound key",
+                APP_BOUND_KEY_ENV
+            )));
+        }
         return Err(RurlError::BrowserCookie(
             "No Chromium cookies could be extracted".to_string(),
         ));
@@ -106,6 +121,11 @@ pub fn extract_chromium_cookies(
     Ok(store)
 }
 
+/// Root directory holding `Local State` and the profile directories
+pub(super) fn user_data_dir(browser: ChromiumBrowser) -> Result<PathBuf> {
+    Ok(chromium_settings(browser)?.user_data_dir)
+}
+
 fn chromium_settings(browser: ChromiumBrowser) -> Result<ChromiumSettings> {
     let local_appdata = env::var("LOCALAPPDATA").ok();
     let appdata = env::var("APPDATA").ok();
@@ -127,6 +147,11 @@ fn chromium_settings(browser: ChromiumBrowser) -> Result<ChromiumSettings> {
         ChromiumBrowser::Opera => ("Opera Software/Opera Stable", false),
         ChromiumBrowser::Vivaldi => ("Vivaldi/User Data", true),
         ChromiumBrowser::Whale => ("Naver/Naver Whale/User Data", true),
+        ChromiumBrowser::Chromium => ("Chromium/User Data", true),
+        ChromiumBrowser::Arc => (
+            "Packages/TheBrowserCompany.Arc_ttt1ap7aakyb4/LocalCache/Local/Arc/User Data",
+            true,
+        ),
     };
 
     let user_data_dir = if matches!(browser, ChromiumBrowser::Opera) {
@@ -152,7 +177,7 @@ fn find_cookie_database(settings: &ChromiumSettings, profile: Option<&str>) -> R
         } else if settings.supports_profiles {
             settings.user_data_dir.join(profile)
         } else {
-            log::warn!("Profile selection is not supported for this browser");
+            crate::warning!("Profile selection is not supported for this browser; ignoring it");
             settings.user_data_dir.clone()
         }
     } else {
@@ -318,15 +343,20 @@ fn chromium_expires_to_unix_seconds(expires_utc: i64) -> Option<i64> {
 
 struct WindowsChromeCookieDecryptor {
     v10_key: Option<Vec<u8>>,
+    v20_key: Option<Vec<u8>>,
     meta_version: i64,
+    app_bound_skipped: Cell<usize>,
 }
 
 impl WindowsChromeCookieDecryptor {
     fn new(settings: &ChromiumSettings, meta_version: i64) -> Result<Self> {
         let v10_key = read_windows_v10_key(&settings.user_data_dir)?;
+        let v20_key = read_app_bound_key()?;
         Ok(Self {
             v10_key,
+            v20_key,
             meta_version,
+            app_bound_skipped: Cell::new(0),
         })
     }
 
@@ -339,6 +369,17 @@ impl WindowsChromeCookieDecryptor {
             let key = self.v10_key.as_ref()?;
             let plaintext = decrypt_aes_gcm(ciphertext, key).ok()?;
             decode_cookie_value(&plaintext, self.meta_version)
+        } else if version == WINDOWS_V20_PREFIX {
+            let key = match self.v20_key.as_ref() {
+                Some(key) => key,
+                None => {
+                    self.app_bound_skipped.set(self.app_bound_skipped.get() + 1);
+                    return None;
+                }
+            };
+            let plaintext = decrypt_aes_gcm(ciphertext, key).ok()?;
+            // App-bound cookies always carry the SHA-256 host digest prefix
+            decode_cookie_value(&plaintext, self.meta_version.max(24))
         } else {
             let plaintext = decrypt_windows_dpapi(encrypted_value).ok()?;
             decode_cookie_value(&plaintext, self.meta_version)
@@ -369,7 +410,7 @@ fn read_windows_v10_key(browser_root: &Path) -> Result<Option<Vec<u8>>> {
             "Failed to read Local State",
             &local_state_path,
             e,
-            Some("Close the browser or run without elevation."),
+            Some(Hint::CloseBrowserOrDropElevation),
         )
     })?;
     let json: serde_json::Value = match serde_json::from_str(&data) {
@@ -395,6 +436,30 @@ fn read_windows_v10_key(browser_root: &Path) -> Result<Option<Vec<u8>>> {
     Ok(decrypt_windows_dpapi(&encrypted_bytes[WINDOWS_DPAPI_PREFIX.len()..]).ok())
 }
 
+fn read_app_bound_key() -> Result<Option<Vec<u8>>> {
+    let value = match env::var(APP_BOUND_KEY_ENV) {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    parse_hex_key(&value).map(Some).ok_or_else(|| {
+        RurlError::Config(format!(
+            "{} must be a 64-character hex AES-256 key",
+            APP_BOUND_KEY_ENV
+        ))
+    })
+}
+
+fn parse_hex_key(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim();
+    if value.len() != 64 || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
+
 fn decrypt_aes_gcm(ciphertext: &[u8], key: &[u8]) -> Result<Vec<u8>> {
     if key.len() != 32 {
         return Err(RurlError::BrowserCookie(
@@ -449,3 +514,16 @@ fn decrypt_windows_dpapi(ciphertext: &[u8]) -> Result<Vec<u8>> {
         Ok(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_hex_key;
+
+    #[test]
+    fn parse_hex_key_requires_32_bytes() {
+        let key = parse_hex_key(&"0a".repeat(32)).expect("key");
+        assert_eq!(key, vec![0x0a; 32]);
+        assert!(parse_hex_key("0a0b").is_none());
+        assert!(parse_hex_key(&"zz".repeat(32)).is_none());
+    }
+}
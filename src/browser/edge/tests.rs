@@ -9,6 +9,7 @@ async fn extract_cookies_returns_error_for_missing_profile() {
         profile: Some("non-existent-profile".to_string()),
         container: None,
         keyring: None,
+        domain_filter: None,
     };
     let result = extract_cookies(&config).await;
     assert!(result.is_err());
@@ -0,0 +1,183 @@
+//! Cookie export formats
+//!
+//! Used by `rurl cookies export` to hand extracted cookies to other tools.
+
+use crate::browser::{Cookie, CookieStore};
+use crate::error::{Result, RurlError};
+use serde_json::json;
+use std::str::FromStr;
+
+/// Supported export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Netscape,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = RurlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "netscape" => Ok(ExportFormat::Netscape),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(RurlError::Config(format!(
+                "Unsupported cookie export format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Flatten a store into a stable, sorted list, optionally limited to a domain
+/// and its subdomains
+pub fn collect_cookies(store: &CookieStore, domain: Option<&str>) -> Vec<Cookie> {
+    let domain = domain.map(|d| d.trim_start_matches('.').to_lowercase());
+    let mut cookies: Vec<Cookie> = store
+        .values()
+        .flatten()
+        .filter(|cookie| match &domain {
+            Some(domain) => {
+                let cookie_domain = cookie.domain.trim_start_matches('.').to_lowercase();
+                cookie_domain == *domain || cookie_domain.ends_with(&format!(".{}", domain))
+            }
+            None => true,
+        })
+        .cloned()
+        .collect();
+    cookies.sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+    cookies
+}
+
+/// Render cookies in the given format
+pub fn format_cookies(cookies: &[Cookie], format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Netscape => Ok(to_netscape(cookies)),
+        ExportFormat::Json => to_json(cookies),
+    }
+}
+
+/// Render cookies as a Netscape/curl cookie jar
+pub fn to_netscape(cookies: &[Cookie]) -> String {
+    let mut out = String::from("# Netscape HTTP Cookie File\n");
+    for cookie in cookies {
+        let prefix = if cookie.http_only { "#HttpOnly_" } else { "" };
+        let include_subdomains = if cookie.domain.starts_with('.') {
+            "TRUE"
+        } else {
+            "FALSE"
+        };
+        let secure = if cookie.secure { "TRUE" } else { "FALSE" };
+        let path = if cookie.path.is_empty() {
+            "/"
+        } else {
+            &cookie.path
+        };
+        out.push_str(&format!(
+            "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            prefix,
+            cookie.domain,
+            include_subdomains,
+            path,
+            secure,
+            cookie.expires.unwrap_or(0),
+            cookie.name,
+            cookie.value
+        ));
+    }
+    out
+}
+
+/// Render cookies as a JSON array
+pub fn to_json(cookies: &[Cookie]) -> Result<String> {
+    let entries: Vec<_> = cookies
+        .iter()
+        .map(|cookie| {
+            json!({
+                "name": cookie.name,
+                "value": cookie.value,
+                "domain": cookie.domain,
+                "path": cookie.path,
+                "secure": cookie.secure,
+                "httpOnly": cookie.http_only,
+                "expires": cookie.expires,
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&entries)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_cookies, to_json, to_netscape, ExportFormat};
+    use crate::browser::{Cookie, CookieStore};
+
+    fn cookie(domain: &str, name: &str) -> Cookie {
+        Cookie {
+            name: name.to_string(),
+            value: "v".to_string(),
+            domain: domain.to_string(),
+            path: "/".to_string(),
+            secure: true,
+            http_only: false,
+            expires: Some(1_700_000_000),
+        }
+    }
+
+    fn store() -> CookieStore {
+        let mut store = CookieStore::new();
+        store.insert(
+            ".example.com".to_string(),
+            vec![cookie(".example.com", "a")],
+        );
+        store.insert(
+            "api.example.com".to_string(),
+            vec![cookie("api.example.com", "b")],
+        );
+        store.insert("other.com".to_string(), vec![cookie("other.com", "c")]);
+        store
+    }
+
+    #[test]
+    fn collect_cookies_filters_by_domain() {
+        let cookies = collect_cookies(&store(), Some("example.com"));
+        let names: Vec<_> = cookies.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(collect_cookies(&store(), None).len(), 3);
+    }
+
+    #[test]
+    fn to_netscape_writes_tab_separated_lines() {
+        let mut http_only = cookie("example.com", "sid");
+        http_only.http_only = true;
+        let text = to_netscape(&[cookie(".example.com", "a"), http_only]);
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "# Netscape HTTP Cookie File");
+        assert_eq!(lines[1], ".example.com\tTRUE\t/\tTRUE\t1700000000\ta\tv");
+        assert_eq!(
+            lines[2],
+            "#HttpOnly_example.com\tFALSE\t/\tTRUE\t1700000000\tsid\tv"
+        );
+    }
+
+    #[test]
+    fn to_json_emits_array() {
+        let text = to_json(&[cookie("example.com", "a")]).expect("json");
+        let value: serde_json::Value = serde_json::from_str(&text).expect("parse");
+        assert_eq!(value[0]["name"], "a");
+        assert_eq!(value[0]["httpOnly"], false);
+    }
+
+    #[test]
+    fn export_format_parses_names() {
+        assert_eq!(
+            "JSON".parse::<ExportFormat>().ok(),
+            Some(ExportFormat::Json)
+        );
+        assert_eq!(
+            "netscape".parse::<ExportFormat>().ok(),
+            Some(ExportFormat::Netscape)
+        );
+        assert!("csv".parse::<ExportFormat>().is_err());
+    }
+}
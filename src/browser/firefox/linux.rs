@@ -1,3 +1,4 @@
+use super::FirefoxBrowser;
 use crate::browser::{Cookie, CookieStore};
 use crate::config::BrowserCookieConfig;
 use crate::error::{Result, RurlError};
@@ -12,10 +13,22 @@ use crate::utils::FileUtils;
 
 const MAX_SUPPORTED_DB_SCHEMA_VERSION: i64 = 17;
 
-pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
-    let search_roots = firefox_search_roots(config.profile.as_deref())?;
-    let cookie_db = newest_path(find_cookie_dbs(&search_roots))
-        .ok_or_else(|| RurlError::FileNotFound("Firefox cookies database not found".to_string()))?;
+/// Path of the cookie database `extract_cookies` would read
+pub(super) fn cookie_db_path(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<PathBuf> {
+    let search_roots = firefox_search_roots(browser, config.profile.as_deref())?;
+    newest_path(find_cookie_dbs(&search_roots))
+        .ok_or_else(|| RurlError::FileNotFound("Firefox cookies database not found".to_string()))
+}
+
+pub fn extract_cookies(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<CookieStore> {
+    let cookie_db = cookie_db_path(browser, config)?;
+    log::info!("Using Firefox cookies database {:?}", cookie_db);
 
     let temp_dir = tempdir()
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
@@ -28,7 +41,7 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to open cookies DB: {}", e)))?;
     let schema_version = read_schema_version(&conn);
     if schema_version > MAX_SUPPORTED_DB_SCHEMA_VERSION {
-        log::warn!(
+        crate::warning!(
             "Firefox cookie DB schema version {} may be unsupported",
             schema_version
         );
@@ -39,41 +52,42 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
         resolve_container(&cookie_db, config.container.as_deref())?;
 
     let mut store: CookieStore = CookieStore::new();
-    let query = match container_mode {
-        ContainerMode::Specific => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies WHERE originAttributes LIKE ? OR originAttributes LIKE ?",
-            expiry_column, secure_column, http_only_column
-        ),
-        ContainerMode::NoneOnly => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies WHERE NOT INSTR(originAttributes, 'userContextId=')",
-            expiry_column, secure_column, http_only_column
-        ),
-        ContainerMode::Any => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies",
-            expiry_column, secure_column, http_only_column
-        ),
-    };
-
-    let mut stmt = conn
-        .prepare(&query)
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare Firefox query: {}", e)))?;
-    let mut rows = match container_mode {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    match container_mode {
         ContainerMode::Specific => {
             let id = container_id.ok_or_else(|| {
                 RurlError::BrowserCookie("Firefox container id not resolved".to_string())
             })?;
-            stmt.query([
-                format!("%userContextId={}", id),
-                format!("%userContextId={}&%", id),
-            ])
-            .map_err(|e| {
-                RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e))
-            })?
+            conditions.push("(originAttributes LIKE ? OR originAttributes LIKE ?)".to_string());
+            params.push(format!("%userContextId={}", id));
+            params.push(format!("%userContextId={}&%", id));
         }
-        _ => stmt.query([]).map_err(|e| {
-            RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e))
-        })?,
-    };
+        ContainerMode::NoneOnly => {
+            conditions.push("NOT INSTR(originAttributes, 'userContextId=')".to_string());
+        }
+        ContainerMode::Any => {}
+    }
+    if let Some((condition, param)) =
+        crate::browser::host_filter("host", config.domain_filter.as_deref())
+    {
+        conditions.push(condition);
+        params.push(param);
+    }
+    let query = format!(
+        "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies{}",
+        expiry_column,
+        secure_column,
+        http_only_column,
+        crate::browser::sql_where(&conditions)
+    );
+
+    let mut stmt = conn
+        .prepare(&query)
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare Firefox query: {}", e)))?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(params.iter()))
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e)))?;
 
     while let Some(row) = rows.next().map_err(|e| {
         RurlError::BrowserCookie(format!("Failed to read Firefox cookie row: {}", e))
@@ -92,34 +106,64 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
     Ok(store)
 }
 
-fn firefox_search_roots(profile: Option<&str>) -> Result<Vec<PathBuf>> {
-    let roots = firefox_base_dirs()?;
+fn firefox_search_roots(browser: FirefoxBrowser, profile: Option<&str>) -> Result<Vec<PathBuf>> {
+    let roots = firefox_base_dirs(browser)?;
 
     if let Some(profile) = profile {
         if is_path_like(profile) {
             let expanded = FileUtils::expand_path(profile)?;
             return Ok(vec![expanded]);
         }
+        if let Some(dir) = super::resolve_profile(&roots, Some(profile)) {
+            return Ok(vec![dir]);
+        }
         return Ok(roots.iter().map(|root| root.join(profile)).collect());
     }
 
+    if let Some(dir) = super::resolve_profile(&roots, None) {
+        return Ok(vec![dir]);
+    }
     Ok(roots)
 }
 
-fn firefox_base_dirs() -> Result<Vec<PathBuf>> {
+/// Directories whose subdirectories are Firefox profiles
+pub(super) fn profile_roots(browser: FirefoxBrowser) -> Result<Vec<PathBuf>> {
+    firefox_base_dirs(browser)
+}
+
+fn firefox_base_dirs(browser: FirefoxBrowser) -> Result<Vec<PathBuf>> {
     let config_home = config_dir().or_else(|| home_dir().map(|home| home.join(".config")));
     let home = home_dir()
         .ok_or_else(|| RurlError::Config("Cannot determine home directory".to_string()))?;
     let config_home = config_home
         .ok_or_else(|| RurlError::Config("Cannot determine config directory".to_string()))?;
 
-    Ok(vec![
-        config_home.join("mozilla/firefox"),
-        home.join(".mozilla/firefox"),
-        home.join(".var/app/org.mozilla.firefox/config/mozilla/firefox"),
-        home.join(".var/app/org.mozilla.firefox/.mozilla/firefox"),
-        home.join("snap/firefox/common/.mozilla/firefox"),
-    ])
+    Ok(match browser {
+        FirefoxBrowser::Firefox => vec![
+            config_home.join("mozilla/firefox"),
+            home.join(".mozilla/firefox"),
+            home.join(".var/app/org.mozilla.firefox/config/mozilla/firefox"),
+            home.join(".var/app/org.mozilla.firefox/.mozilla/firefox"),
+            home.join("snap/firefox/common/.mozilla/firefox"),
+        ],
+        FirefoxBrowser::LibreWolf => vec![
+            home.join(".librewolf"),
+            home.join(".var/app/io.gitlab.librewolf-community/.librewolf"),
+        ],
+        FirefoxBrowser::Waterfox => vec![
+            home.join(".waterfox"),
+            home.join(".var/app/net.waterfox.waterfox/.waterfox"),
+        ],
+        FirefoxBrowser::TorBrowser => {
+            let bundle = "tor-browser/Browser/TorBrowser/Data/Browser";
+            vec![
+                home.join(".local/share/torbrowser/tbb/x86_64").join(bundle),
+                home.join(".var/app/org.torproject.torbrowser-launcher/data/torbrowser/tbb/x86_64")
+                    .join(bundle),
+                home.join(bundle),
+            ]
+        }
+    })
 }
 
 fn is_path_like(value: &str) -> bool {
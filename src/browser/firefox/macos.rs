@@ -1,3 +1,4 @@
+use super::FirefoxBrowser;
 use crate::browser::{Cookie, CookieStore};
 use crate::config::BrowserCookieConfig;
 use crate::error::{Result, RurlError};
@@ -11,10 +12,21 @@ use crate::utils::FileUtils;
 
 const MAX_SUPPORTED_DB_SCHEMA_VERSION: i64 = 17;
 
-pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
-    let search_roots = firefox_search_roots(config.profile.as_deref())?;
-    let cookie_db = newest_path(find_cookie_dbs(&search_roots))
-        .ok_or_else(|| RurlError::FileNotFound("Firefox cookies database not found".to_string()))?;
+/// Path of the cookie database `extract_cookies` would read
+pub(super) fn cookie_db_path(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<PathBuf> {
+    let search_roots = firefox_search_roots(browser, config.profile.as_deref())?;
+    newest_path(find_cookie_dbs(&search_roots))
+        .ok_or_else(|| RurlError::FileNotFound("Firefox cookies database not found".to_string()))
+}
+
+pub fn extract_cookies(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<CookieStore> {
+    let cookie_db = cookie_db_path(browser, config)?;
 
     let temp_dir = tempdir()
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
@@ -27,7 +39,7 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to open cookies DB: {}", e)))?;
     let schema_version = read_schema_version(&conn);
     if schema_version > MAX_SUPPORTED_DB_SCHEMA_VERSION {
-        log::warn!(
+        crate::warning!(
             "Firefox cookie DB schema version {} may be unsupported",
             schema_version
         );
@@ -38,41 +50,42 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
         resolve_container(&cookie_db, config.container.as_deref())?;
 
     let mut store: CookieStore = CookieStore::new();
-    let query = match container_mode {
-        ContainerMode::Specific => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies WHERE originAttributes LIKE ? OR originAttributes LIKE ?",
-            expiry_column, secure_column, http_only_column
-        ),
-        ContainerMode::NoneOnly => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies WHERE NOT INSTR(originAttributes, 'userContextId=')",
-            expiry_column, secure_column, http_only_column
-        ),
-        ContainerMode::Any => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies",
-            expiry_column, secure_column, http_only_column
-        ),
-    };
-
-    let mut stmt = conn
-        .prepare(&query)
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare Firefox query: {}", e)))?;
-    let mut rows = match container_mode {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    match container_mode {
         ContainerMode::Specific => {
             let id = container_id.ok_or_else(|| {
                 RurlError::BrowserCookie("Firefox container id not resolved".to_string())
             })?;
-            stmt.query([
-                format!("%userContextId={}", id),
-                format!("%userContextId={}&%", id),
-            ])
-            .map_err(|e| {
-                RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e))
-            })?
+            conditions.push("(originAttributes LIKE ? OR originAttributes LIKE ?)".to_string());
+            params.push(format!("%userContextId={}", id));
+            params.push(format!("%userContextId={}&%", id));
         }
-        _ => stmt.query([]).map_err(|e| {
-            RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e))
-        })?,
-    };
+        ContainerMode::NoneOnly => {
+            conditions.push("NOT INSTR(originAttributes, 'userContextId=')".to_string());
+        }
+        ContainerMode::Any => {}
+    }
+    if let Some((condition, param)) =
+        crate::browser::host_filter("host", config.domain_filter.as_deref())
+    {
+        conditions.push(condition);
+        params.push(param);
+    }
+    let query = format!(
+        "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies{}",
+        expiry_column,
+        secure_column,
+        http_only_column,
+        crate::browser::sql_where(&conditions)
+    );
+
+    let mut stmt = conn
+        .prepare(&query)
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare Firefox query: {}", e)))?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(params.iter()))
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e)))?;
 
     while let Some(row) = rows.next().map_err(|e| {
         RurlError::BrowserCookie(format!("Failed to read Firefox cookie row: {}", e))
@@ -91,19 +104,41 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
     Ok(store)
 }
 
-fn firefox_search_roots(profile: Option<&str>) -> Result<Vec<PathBuf>> {
-    let base = dirs::home_dir()
+/// Directories whose subdirectories are Firefox profiles
+pub(super) fn profile_roots(browser: FirefoxBrowser) -> Result<Vec<PathBuf>> {
+    Ok(vec![profiles_dir(browser)?])
+}
+
+fn profiles_dir(browser: FirefoxBrowser) -> Result<PathBuf> {
+    let relative_dir = match browser {
+        FirefoxBrowser::Firefox => "Firefox/Profiles",
+        FirefoxBrowser::LibreWolf => "librewolf/Profiles",
+        FirefoxBrowser::Waterfox => "Waterfox/Profiles",
+        FirefoxBrowser::TorBrowser => "TorBrowser-Data/Browser",
+    };
+    Ok(dirs::home_dir()
         .ok_or_else(|| RurlError::Config("Cannot determine home directory".to_string()))?
-        .join("Library/Application Support/Firefox/Profiles");
+        .join("Library/Application Support")
+        .join(relative_dir))
+}
+
+fn firefox_search_roots(browser: FirefoxBrowser, profile: Option<&str>) -> Result<Vec<PathBuf>> {
+    let base = profiles_dir(browser)?;
 
     if let Some(profile) = profile {
         if is_path_like(profile) {
             let expanded = FileUtils::expand_path(profile)?;
             return Ok(vec![expanded]);
         }
+        if let Some(dir) = super::resolve_profile(std::slice::from_ref(&base), Some(profile)) {
+            return Ok(vec![dir]);
+        }
         return Ok(vec![base.join(profile)]);
     }
 
+    if let Some(dir) = super::resolve_profile(std::slice::from_ref(&base), None) {
+        return Ok(vec![dir]);
+    }
     Ok(vec![base])
 }
 
@@ -1,10 +1,14 @@
 //! Firefox browser cookie extraction
 
-use crate::browser::CookieStore;
+use crate::browser::{BrowserProfile, CookieStore};
 use crate::config::BrowserCookieConfig;
 use crate::error::Result;
 #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
 use crate::error::RurlError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 #[cfg(target_os = "linux")]
 mod linux;
@@ -13,26 +17,405 @@ mod macos;
 #[cfg(target_os = "windows")]
 mod windows;
 
+/// Firefox and the forks that share its profile layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirefoxBrowser {
+    Firefox,
+    LibreWolf,
+    Waterfox,
+    TorBrowser,
+}
+
 /// Extract cookies from Firefox browser
 pub async fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
+    extract_firefox_cookies(FirefoxBrowser::Firefox, config)
+}
+
+pub fn extract_firefox_cookies(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<CookieStore> {
     #[cfg(target_os = "macos")]
     {
-        macos::extract_cookies(config)
+        macos::extract_cookies(browser, config)
     }
     #[cfg(target_os = "linux")]
     {
-        linux::extract_cookies(config)
+        linux::extract_cookies(browser, config)
     }
     #[cfg(target_os = "windows")]
     {
-        windows::extract_cookies(config)
+        windows::extract_cookies(browser, config)
     }
     #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     {
-        let _ = config;
+        let _ = (browser, config);
         Err(RurlError::Unsupported(
             "Firefox cookie extraction is only implemented for macOS, Linux, and Windows"
                 .to_string(),
         ))
     }
 }
+
+/// Path of the cookie database that would be read for `browser`
+pub fn firefox_cookie_db_path(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::cookie_db_path(browser, config)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        linux::cookie_db_path(browser, config)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows::cookie_db_path(browser, config)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        let _ = (browser, config);
+        Err(RurlError::Unsupported(
+            "Firefox cookie extraction is only implemented for macOS, Linux, and Windows"
+                .to_string(),
+        ))
+    }
+}
+
+/// List Firefox profiles and their containers
+pub fn list_profiles(browser: FirefoxBrowser) -> Result<Vec<BrowserProfile>> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(profiles_in_roots(&macos::profile_roots(browser)?))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Ok(profiles_in_roots(&linux::profile_roots(browser)?))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        Ok(profiles_in_roots(&windows::profile_roots(browser)?))
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        let _ = browser;
+        Err(RurlError::Unsupported(
+            "Firefox profile listing is only implemented for macOS, Linux, and Windows".to_string(),
+        ))
+    }
+}
+
+/// Every profile directory under the roots, named from `profiles.ini` when the
+/// root (or its parent, as on macOS and Windows) has one
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "linux", target_os = "windows")),
+    allow(dead_code)
+)]
+fn profiles_in_roots(roots: &[PathBuf]) -> Vec<BrowserProfile> {
+    let mut profiles = Vec::new();
+    for root in roots {
+        let entries = match fs::read_dir(root) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        let names = profile_names(root);
+        let mut dirs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.join("prefs.js").is_file() || path.join("cookies.sqlite").is_file())
+            .collect();
+        dirs.sort();
+        for path in dirs {
+            let id = match path.file_name().and_then(|name| name.to_str()) {
+                Some(id) => id.to_string(),
+                None => continue,
+            };
+            profiles.push(BrowserProfile {
+                name: names.get(&id).cloned().unwrap_or_else(|| id.clone()),
+                containers: read_container_names(&path),
+                id,
+                path,
+            });
+        }
+    }
+    profiles
+}
+
+fn profile_names(root: &Path) -> HashMap<String, String> {
+    read_profiles_ini(root)
+        .map(|ini| {
+            ini.profiles
+                .into_iter()
+                .filter_map(|profile| {
+                    let dir = profile.path.file_name()?.to_str()?.to_string();
+                    Some((dir, profile.name))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resolve a human-readable profile name, or the default profile when no
+/// name is given, through the `profiles.ini` next to the roots
+#[cfg_attr(
+    not(any(target_os = "macos", target_os = "linux", target_os = "windows")),
+    allow(dead_code)
+)]
+pub(super) fn resolve_profile(roots: &[PathBuf], profile: Option<&str>) -> Option<PathBuf> {
+    roots
+        .iter()
+        .filter_map(|root| read_profiles_ini(root))
+        .find_map(|ini| {
+            let path = match profile {
+                Some(name) => ini.find(name),
+                None => ini.default_profile(),
+            }?;
+            path.is_dir().then_some(path)
+        })
+}
+
+/// A `[ProfileN]` section of `profiles.ini`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IniProfile {
+    name: String,
+    path: PathBuf,
+    default: bool,
+}
+
+#[derive(Debug, Default)]
+struct ProfilesIni {
+    profiles: Vec<IniProfile>,
+    install_default: Option<PathBuf>,
+}
+
+impl ProfilesIni {
+    fn find(&self, name: &str) -> Option<PathBuf> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .map(|profile| profile.path.clone())
+    }
+
+    /// The profile Firefox opens by default: the `[Install...]` default used
+    /// by current releases, then the legacy `Default=1` flag
+    fn default_profile(&self) -> Option<PathBuf> {
+        self.install_default.clone().or_else(|| {
+            self.profiles
+                .iter()
+                .find(|profile| profile.default)
+                .map(|profile| profile.path.clone())
+        })
+    }
+}
+
+/// Read `profiles.ini` from the root, or its parent as on macOS and Windows
+fn read_profiles_ini(root: &Path) -> Option<ProfilesIni> {
+    [Some(root), root.parent()]
+        .into_iter()
+        .flatten()
+        .find_map(|dir| {
+            let data = fs::read_to_string(dir.join("profiles.ini")).ok()?;
+            Some(parse_profiles_ini(&data, dir))
+        })
+}
+
+fn parse_profiles_ini(data: &str, ini_dir: &Path) -> ProfilesIni {
+    #[derive(PartialEq)]
+    enum Section {
+        Profile,
+        Install,
+        Other,
+    }
+
+    let resolve = |path: &str, is_relative: bool| {
+        if is_relative {
+            ini_dir.join(path)
+        } else {
+            PathBuf::from(path)
+        }
+    };
+
+    let mut ini = ProfilesIni::default();
+    let mut section = Section::Other;
+    let mut name: Option<String> = None;
+    let mut path: Option<String> = None;
+    let mut is_relative = true;
+    let mut default = false;
+
+    let flush = |ini: &mut ProfilesIni,
+                 name: &mut Option<String>,
+                 path: &mut Option<String>,
+                 is_relative: bool,
+                 default: bool| {
+        if let (Some(name), Some(path)) = (name.take(), path.take()) {
+            ini.profiles.push(IniProfile {
+                name,
+                path: resolve(&path, is_relative),
+                default,
+            });
+        }
+    };
+
+    for line in data.lines() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            if section == Section::Profile {
+                flush(&mut ini, &mut name, &mut path, is_relative, default);
+            }
+            section = if header.starts_with("Profile") {
+                Section::Profile
+            } else if header.starts_with("Install") {
+                Section::Install
+            } else {
+                Section::Other
+            };
+            is_relative = true;
+            default = false;
+            continue;
+        }
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        match (&section, key) {
+            (Section::Profile, "Name") => name = Some(value.to_string()),
+            (Section::Profile, "Path") => path = Some(value.to_string()),
+            (Section::Profile, "IsRelative") => is_relative = value != "0",
+            (Section::Profile, "Default") => default = value == "1",
+            (Section::Install, "Default") if ini.install_default.is_none() => {
+                let absolute = Path::new(value).is_absolute();
+                ini.install_default = Some(resolve(value, !absolute));
+            }
+            _ => {}
+        }
+    }
+    if section == Section::Profile {
+        flush(&mut ini, &mut name, &mut path, is_relative, default);
+    }
+    ini
+}
+
+/// Container names from `containers.json`, usable as `::CONTAINER`
+fn read_container_names(profile_dir: &Path) -> Vec<String> {
+    fs::read_to_string(profile_dir.join("containers.json"))
+        .ok()
+        .map(|data| parse_container_names(&data))
+        .unwrap_or_default()
+}
+
+fn parse_container_names(data: &str) -> Vec<String> {
+    let value: Value = match serde_json::from_str(data) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+    value
+        .get("identities")
+        .and_then(|v| v.as_array())
+        .map(|identities| {
+            identities
+                .iter()
+                .filter(|identity| identity.get("public").and_then(|v| v.as_bool()) != Some(false))
+                .filter_map(|identity| {
+                    if let Some(name) = identity.get("name").and_then(|v| v.as_str()) {
+                        return Some(name.to_string());
+                    }
+                    identity
+                        .get("l10nID")
+                        .and_then(|v| v.as_str())
+                        .and_then(|id| id.strip_prefix("userContext"))
+                        .and_then(|id| id.strip_suffix(".label"))
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_container_names, parse_profiles_ini, profiles_in_roots, resolve_profile};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    #[test]
+    fn parse_profiles_ini_reads_profiles_and_defaults() {
+        let data = "[Install1]\nDefault=Profiles/abc.default-release\n\n\
+                    [Profile0]\nName=default-release\nIsRelative=1\nPath=Profiles/abc.default-release\n\n\
+                    [Profile1]\nName=work\nIsRelative=0\nPath=/data/xyz.work\nDefault=1\n";
+        let root = Path::new("/ff");
+        let ini = parse_profiles_ini(data, root);
+        assert_eq!(ini.profiles.len(), 2);
+        assert_eq!(
+            ini.find("default-release"),
+            Some(root.join("Profiles/abc.default-release"))
+        );
+        assert_eq!(ini.find("work"), Some(PathBuf::from("/data/xyz.work")));
+        assert_eq!(
+            ini.default_profile(),
+            Some(root.join("Profiles/abc.default-release"))
+        );
+
+        let legacy = parse_profiles_ini(
+            "[Profile0]\nName=a\nPath=a.a\n[Profile1]\nName=b\nPath=b.b\nDefault=1\n",
+            root,
+        );
+        assert_eq!(legacy.default_profile(), Some(root.join("b.b")));
+    }
+
+    #[test]
+    fn resolve_profile_uses_names_and_default() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path().join("Profiles");
+        fs::create_dir_all(root.join("abc.default-release")).expect("profile");
+        fs::create_dir_all(root.join("def.dev-edition-default")).expect("profile");
+        fs::write(
+            dir.path().join("profiles.ini"),
+            "[Profile0]\nName=default-release\nPath=Profiles/abc.default-release\nDefault=1\n\
+             [Profile1]\nName=dev-edition-default\nPath=Profiles/def.dev-edition-default\n",
+        )
+        .expect("ini");
+
+        let roots = [root.clone()];
+        assert_eq!(
+            resolve_profile(&roots, Some("dev-edition-default")),
+            Some(dir.path().join("Profiles/def.dev-edition-default"))
+        );
+        assert_eq!(
+            resolve_profile(&roots, None),
+            Some(dir.path().join("Profiles/abc.default-release"))
+        );
+        assert_eq!(resolve_profile(&roots, Some("missing")), None);
+    }
+
+    #[test]
+    fn parse_container_names_skips_private_identities() {
+        let data = r#"{"identities":[
+            {"l10nID":"userContextPersonal.label","public":true,"userContextId":1},
+            {"name":"Banking","public":true,"userContextId":5},
+            {"name":"userContextIdInternal.thumbnail","public":false,"userContextId":4294967295}
+        ]}"#;
+        assert_eq!(parse_container_names(data), vec!["Personal", "Banking"]);
+    }
+
+    #[test]
+    fn profiles_in_roots_lists_profile_directories() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        fs::write(
+            root.join("profiles.ini"),
+            "[Profile0]\nName=main\nPath=p1.main\n",
+        )
+        .expect("ini");
+        fs::create_dir(root.join("p1.main")).expect("profile");
+        fs::write(root.join("p1.main/prefs.js"), "").expect("prefs");
+        fs::create_dir(root.join("Crash Reports")).expect("other dir");
+
+        let profiles = profiles_in_roots(&[root.to_path_buf()]);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].id, "p1.main");
+        assert_eq!(profiles[0].name, "main");
+        assert!(profiles[0].containers.is_empty());
+    }
+}
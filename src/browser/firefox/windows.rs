@@ -1,6 +1,7 @@
+use super::FirefoxBrowser;
 use crate::browser::{Cookie, CookieStore};
 use crate::config::BrowserCookieConfig;
-use crate::error::{Result, RurlError};
+use crate::error::{Hint, Result, RurlError};
 use rusqlite::{Connection, Row};
 use serde_json::Value;
 use std::env;
@@ -12,10 +13,21 @@ use crate::utils::FileUtils;
 
 const MAX_SUPPORTED_DB_SCHEMA_VERSION: i64 = 17;
 
-pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
-    let search_roots = firefox_search_roots(config.profile.as_deref())?;
-    let cookie_db = newest_path(find_cookie_dbs(&search_roots))
-        .ok_or_else(|| RurlError::FileNotFound("Firefox cookies database not found".to_string()))?;
+/// Path of the cookie database `extract_cookies` would read
+pub(super) fn cookie_db_path(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<PathBuf> {
+    let search_roots = firefox_search_roots(browser, config.profile.as_deref())?;
+    newest_path(find_cookie_dbs(&search_roots))
+        .ok_or_else(|| RurlError::FileNotFound("Firefox cookies database not found".to_string()))
+}
+
+pub fn extract_cookies(
+    browser: FirefoxBrowser,
+    config: &BrowserCookieConfig,
+) -> Result<CookieStore> {
+    let cookie_db = cookie_db_path(browser, config)?;
 
     let temp_dir = tempdir()
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
@@ -25,7 +37,7 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
             "Failed to copy cookies DB",
             &cookie_db,
             e,
-            Some("Close the browser or run without elevation."),
+            Some(Hint::CloseBrowserOrDropElevation),
         )
     })?;
 
@@ -33,7 +45,7 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
         .map_err(|e| RurlError::BrowserCookie(format!("Failed to open cookies DB: {}", e)))?;
     let schema_version = read_schema_version(&conn);
     if schema_version > MAX_SUPPORTED_DB_SCHEMA_VERSION {
-        log::warn!(
+        crate::warning!(
             "Firefox cookie DB schema version {} may be unsupported",
             schema_version
         );
@@ -44,41 +56,42 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
         resolve_container(&cookie_db, config.container.as_deref())?;
 
     let mut store: CookieStore = CookieStore::new();
-    let query = match container_mode {
-        ContainerMode::Specific => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies WHERE originAttributes LIKE ? OR originAttributes LIKE ?",
-            expiry_column, secure_column, http_only_column
-        ),
-        ContainerMode::NoneOnly => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies WHERE NOT INSTR(originAttributes, 'userContextId=')",
-            expiry_column, secure_column, http_only_column
-        ),
-        ContainerMode::Any => format!(
-            "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies",
-            expiry_column, secure_column, http_only_column
-        ),
-    };
-
-    let mut stmt = conn
-        .prepare(&query)
-        .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare Firefox query: {}", e)))?;
-    let mut rows = match container_mode {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    match container_mode {
         ContainerMode::Specific => {
             let id = container_id.ok_or_else(|| {
                 RurlError::BrowserCookie("Firefox container id not resolved".to_string())
             })?;
-            stmt.query([
-                format!("%userContextId={}", id),
-                format!("%userContextId={}&%", id),
-            ])
-            .map_err(|e| {
-                RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e))
-            })?
+            conditions.push("(originAttributes LIKE ? OR originAttributes LIKE ?)".to_string());
+            params.push(format!("%userContextId={}", id));
+            params.push(format!("%userContextId={}&%", id));
         }
-        _ => stmt.query([]).map_err(|e| {
-            RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e))
-        })?,
-    };
+        ContainerMode::NoneOnly => {
+            conditions.push("NOT INSTR(originAttributes, 'userContextId=')".to_string());
+        }
+        ContainerMode::Any => {}
+    }
+    if let Some((condition, param)) =
+        crate::browser::host_filter("host", config.domain_filter.as_deref())
+    {
+        conditions.push(condition);
+        params.push(param);
+    }
+    let query = format!(
+        "SELECT host, name, value, path, {}, {}, {} FROM moz_cookies{}",
+        expiry_column,
+        secure_column,
+        http_only_column,
+        crate::browser::sql_where(&conditions)
+    );
+
+    let mut stmt = conn
+        .prepare(&query)
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to prepare Firefox query: {}", e)))?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(params.iter()))
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to query Firefox cookies: {}", e)))?;
 
     while let Some(row) = rows.next().map_err(|e| {
         RurlError::BrowserCookie(format!("Failed to read Firefox cookie row: {}", e))
@@ -97,29 +110,63 @@ pub fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore> {
     Ok(store)
 }
 
-fn firefox_search_roots(profile: Option<&str>) -> Result<Vec<PathBuf>> {
-    let roots = firefox_base_dirs()?;
+fn firefox_search_roots(browser: FirefoxBrowser, profile: Option<&str>) -> Result<Vec<PathBuf>> {
+    let roots = firefox_base_dirs(browser)?;
 
     if let Some(profile) = profile {
         if is_path_like(profile) {
             let expanded = FileUtils::expand_path(profile)?;
             return Ok(vec![expanded]);
         }
+        if let Some(dir) = super::resolve_profile(&roots, Some(profile)) {
+            return Ok(vec![dir]);
+        }
         return Ok(roots.iter().map(|root| root.join(profile)).collect());
     }
 
+    if let Some(dir) = super::resolve_profile(&roots, None) {
+        return Ok(vec![dir]);
+    }
     Ok(roots)
 }
 
-fn firefox_base_dirs() -> Result<Vec<PathBuf>> {
+/// Directories whose subdirectories are Firefox profiles
+pub(super) fn profile_roots(browser: FirefoxBrowser) -> Result<Vec<PathBuf>> {
+    firefox_base_dirs(browser)
+}
+
+fn firefox_base_dirs(browser: FirefoxBrowser) -> Result<Vec<PathBuf>> {
     let mut roots = Vec::new();
-    if let Ok(appdata) = env::var("APPDATA") {
-        roots.push(PathBuf::from(appdata).join("Mozilla/Firefox/Profiles"));
-    }
-    if let Ok(local_appdata) = env::var("LOCALAPPDATA") {
-        roots.push(PathBuf::from(local_appdata).join(
-            "Packages/Mozilla.Firefox_n80bbvh6b1yt2/LocalCache/Roaming/Mozilla/Firefox/Profiles",
-        ));
+    let appdata = env::var("APPDATA").ok().map(PathBuf::from);
+    match browser {
+        FirefoxBrowser::Firefox => {
+            if let Some(appdata) = &appdata {
+                roots.push(appdata.join("Mozilla/Firefox/Profiles"));
+            }
+            if let Ok(local_appdata) = env::var("LOCALAPPDATA") {
+                roots.push(PathBuf::from(local_appdata).join(
+                    "Packages/Mozilla.Firefox_n80bbvh6b1yt2/LocalCache/Roaming/Mozilla/Firefox/Profiles",
+                ));
+            }
+        }
+        FirefoxBrowser::LibreWolf => {
+            if let Some(appdata) = &appdata {
+                roots.push(appdata.join("librewolf/Profiles"));
+            }
+        }
+        FirefoxBrowser::Waterfox => {
+            if let Some(appdata) = &appdata {
+                roots.push(appdata.join("Waterfox/Profiles"));
+            }
+        }
+        FirefoxBrowser::TorBrowser => {
+            if let Ok(profile) = env::var("USERPROFILE") {
+                roots.push(
+                    PathBuf::from(profile)
+                        .join("Desktop/Tor Browser/Browser/TorBrowser/Data/Browser"),
+                );
+            }
+        }
     }
     if roots.is_empty() {
         return Err(RurlError::Config(
@@ -301,7 +348,7 @@ fn resolve_container(
             "Failed to read containers.json",
             &containers_path,
             e,
-            Some("Close the browser or run without elevation."),
+            Some(Hint::CloseBrowserOrDropElevation),
         )
     })?;
     let value: Value = serde_json::from_str(&data)?;
@@ -4,19 +4,23 @@
 //! across different operating systems.
 
 use crate::config::{Browser, BrowserCookieConfig};
-use crate::error::Result;
+use crate::error::{Hint, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 use url::Url;
 
+pub mod cache;
 pub mod chrome;
 pub mod edge;
+pub mod export;
 pub mod firefox;
 pub mod safari;
+pub mod storage;
 
 /// Represents a browser cookie
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cookie {
     pub name: String,
     pub value: String,
@@ -30,6 +34,18 @@ pub struct Cookie {
 /// Cookie store for managing extracted cookies
 pub type CookieStore = HashMap<String, Vec<Cookie>>;
 
+/// A browser profile that can be passed as `BROWSER:PROFILE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowserProfile {
+    /// Value accepted by `--cookies-from-browser BROWSER:PROFILE`
+    pub id: String,
+    /// Display name shown by the browser
+    pub name: String,
+    pub path: std::path::PathBuf,
+    /// Firefox container names usable as `::CONTAINER`
+    pub containers: Vec<String>,
+}
+
 /// Main interface for extracting browser cookies
 pub struct BrowserCookieExtractor {
     config: BrowserCookieConfig,
@@ -60,47 +76,190 @@ impl BrowserCookieExtractor {
             Browser::Whale => {
                 chrome::extract_chromium_cookies(chrome::ChromiumBrowser::Whale, &self.config)
             }
+            Browser::Chromium => {
+                chrome::extract_chromium_cookies(chrome::ChromiumBrowser::Chromium, &self.config)
+            }
+            Browser::Arc => {
+                chrome::extract_chromium_cookies(chrome::ChromiumBrowser::Arc, &self.config)
+            }
+            Browser::LibreWolf => {
+                firefox::extract_firefox_cookies(firefox::FirefoxBrowser::LibreWolf, &self.config)
+            }
+            Browser::Waterfox => {
+                firefox::extract_firefox_cookies(firefox::FirefoxBrowser::Waterfox, &self.config)
+            }
+            Browser::TorBrowser => {
+                firefox::extract_firefox_cookies(firefox::FirefoxBrowser::TorBrowser, &self.config)
+            }
+        }
+    }
+
+    /// Extract cookies, reusing an encrypted on-disk copy younger than `ttl`
+    /// as long as the browser's cookie database has not changed since
+    pub async fn extract_cookies_cached(&self, ttl: std::time::Duration) -> Result<CookieStore> {
+        let source = match self.source_path() {
+            Ok(source) => source,
+            Err(_) => return self.extract_cookies().await,
+        };
+        let cache = match cache::CookieCache::new(ttl) {
+            Ok(cache) => cache,
+            Err(err) => {
+                log::warn!("Cookie cache unavailable: {}", err);
+                return self.extract_cookies().await;
+            }
+        };
+        if let Some(store) = cache.load(&self.config, &source) {
+            log::debug!("Using cached cookies for {:?}", source);
+            return Ok(store);
         }
+        let store = self.extract_cookies().await?;
+        if let Err(err) = cache.store(&self.config, &source, &store) {
+            log::warn!("Failed to write cookie cache: {}", err);
+        }
+        Ok(store)
+    }
+
+    /// Path of the cookie database the configured browser would be read from
+    pub fn source_path(&self) -> Result<std::path::PathBuf> {
+        use chrome::{chromium_cookie_db_path as chromium, ChromiumBrowser};
+        use firefox::{firefox_cookie_db_path as firefox, FirefoxBrowser};
+
+        let config = &self.config;
+        match config.browser {
+            Browser::Safari => safari::cookie_db_path(config),
+            Browser::Firefox => firefox(FirefoxBrowser::Firefox, config),
+            Browser::LibreWolf => firefox(FirefoxBrowser::LibreWolf, config),
+            Browser::Waterfox => firefox(FirefoxBrowser::Waterfox, config),
+            Browser::TorBrowser => firefox(FirefoxBrowser::TorBrowser, config),
+            Browser::Chrome => chromium(ChromiumBrowser::Chrome, config),
+            Browser::Edge => chromium(ChromiumBrowser::Edge, config),
+            Browser::Brave => chromium(ChromiumBrowser::Brave, config),
+            Browser::Opera => chromium(ChromiumBrowser::Opera, config),
+            Browser::Vivaldi => chromium(ChromiumBrowser::Vivaldi, config),
+            Browser::Whale => chromium(ChromiumBrowser::Whale, config),
+            Browser::Chromium => chromium(ChromiumBrowser::Chromium, config),
+            Browser::Arc => chromium(ChromiumBrowser::Arc, config),
+        }
+    }
+
+    /// List the profiles available for the configured browser
+    pub fn list_profiles(&self) -> Result<Vec<BrowserProfile>> {
+        let chromium = match self.config.browser {
+            Browser::Firefox => return firefox::list_profiles(firefox::FirefoxBrowser::Firefox),
+            Browser::LibreWolf => {
+                return firefox::list_profiles(firefox::FirefoxBrowser::LibreWolf)
+            }
+            Browser::Waterfox => return firefox::list_profiles(firefox::FirefoxBrowser::Waterfox),
+            Browser::TorBrowser => {
+                return firefox::list_profiles(firefox::FirefoxBrowser::TorBrowser)
+            }
+            Browser::Safari => return safari::list_profiles(),
+            Browser::Chrome => chrome::ChromiumBrowser::Chrome,
+            Browser::Edge => chrome::ChromiumBrowser::Edge,
+            Browser::Brave => chrome::ChromiumBrowser::Brave,
+            Browser::Opera => chrome::ChromiumBrowser::Opera,
+            Browser::Vivaldi => chrome::ChromiumBrowser::Vivaldi,
+            Browser::Whale => chrome::ChromiumBrowser::Whale,
+            Browser::Chromium => chrome::ChromiumBrowser::Chromium,
+            Browser::Arc => chrome::ChromiumBrowser::Arc,
+        };
+        chrome::list_chromium_profiles(chromium)
     }
 
     /// Convert cookies to HTTP header format
     pub fn cookies_to_header(&self, cookies: &[Cookie]) -> String {
-        cookies
-            .iter()
-            .map(|c| format!("{}={}", c.name, c.value))
-            .collect::<Vec<_>>()
-            .join("; ")
+        cookies_to_header(cookies)
     }
 
     /// Filter cookies for a specific URL using standard matching rules
     pub fn cookies_for_url(&self, store: &CookieStore, url: &Url) -> Vec<Cookie> {
-        let host = match url.host_str() {
-            Some(host) => host.to_lowercase(),
-            None => return Vec::new(),
-        };
-        let path = url.path();
-        let is_https = url.scheme() == "https";
-        let now = unix_timestamp_seconds();
-
-        let mut matched = Vec::new();
-        for cookies in store.values() {
-            for cookie in cookies {
-                if cookie.secure && !is_https {
-                    continue;
-                }
-                if is_expired(cookie.expires, now) {
-                    continue;
-                }
-                if !domain_matches(&host, &cookie.domain) {
-                    continue;
-                }
-                if !path_matches(path, &cookie.path) {
-                    continue;
-                }
-                matched.push(cookie.clone());
-            }
+        cookies_for_url(store, url)
+    }
+}
+
+/// Convert cookies to HTTP header format
+pub fn cookies_to_header(cookies: &[Cookie]) -> String {
+    cookies
+        .iter()
+        .map(|c| format!("{}={}", c.name, c.value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Filter cookies for a specific URL using standard matching rules
+pub fn cookies_for_url(store: &CookieStore, url: &Url) -> Vec<Cookie> {
+    cookie_decisions(store, url)
+        .into_iter()
+        .filter(|(_, skipped)| skipped.is_none())
+        .map(|(cookie, _)| cookie.clone())
+        .collect()
+}
+
+/// Every cookie in `store` with the reason it is not sent to `url`, or
+/// `None` when it is sent
+pub fn cookie_decisions<'a>(
+    store: &'a CookieStore,
+    url: &Url,
+) -> Vec<(&'a Cookie, Option<&'static str>)> {
+    let host = match url.host_str() {
+        Some(host) => host.to_lowercase(),
+        None => return Vec::new(),
+    };
+    let path = url.path();
+    let is_https = url.scheme() == "https";
+    let now = unix_timestamp_seconds();
+
+    let mut decisions = Vec::new();
+    for cookies in store.values() {
+        for cookie in cookies {
+            let skipped = if cookie.secure && !is_https {
+                Some("secure cookie over plain http")
+            } else if is_expired(cookie.expires, now) {
+                Some("expired")
+            } else if !domain_matches(&host, &cookie.domain) {
+                Some("domain does not match")
+            } else if !path_matches(path, &cookie.path) {
+                Some("path does not match")
+            } else {
+                None
+            };
+            decisions.push((cookie, skipped));
         }
-        matched
+    }
+    decisions
+}
+
+/// Domain used to narrow cookie database queries for `url`.
+///
+/// This is the last two labels of the host, which is never longer than the
+/// registrable domain, so every cookie that could match the URL is still read.
+pub fn cookie_filter_domain(url: &Url) -> Option<String> {
+    match url.host()? {
+        url::Host::Domain(host) => {
+            let host = host.trim_end_matches('.').to_lowercase();
+            let labels: Vec<&str> = host.split('.').collect();
+            let start = labels.len().saturating_sub(2);
+            Some(labels[start..].join("."))
+        }
+        host => Some(host.to_string()),
+    }
+}
+
+/// SQL condition restricting `column` to hosts ending in `domain`
+pub(crate) fn host_filter(column: &str, domain: Option<&str>) -> Option<(String, String)> {
+    let domain = domain?.trim_start_matches('.');
+    if domain.is_empty() {
+        return None;
+    }
+    Some((format!("{} LIKE ?", column), format!("%{}", domain)))
+}
+
+/// Join SQL conditions into a `WHERE` clause (empty when there are none)
+pub(crate) fn sql_where(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
     }
 }
 
@@ -108,15 +267,14 @@ pub(crate) fn map_cookie_io_error(
     context: &str,
     path: &Path,
     err: std::io::Error,
-    permission_hint: Option<&str>,
+    permission_hint: Option<Hint>,
 ) -> crate::error::RurlError {
     if err.kind() == std::io::ErrorKind::PermissionDenied {
-        let detail = if let Some(hint) = permission_hint {
-            format!("{context}: {:?}. {}", path, hint)
-        } else {
-            format!("{context}: {:?}", path)
-        };
-        crate::error::RurlError::PermissionDenied(detail)
+        let err = crate::error::RurlError::PermissionDenied(format!("{context}: {:?}", path));
+        match permission_hint {
+            Some(hint) => err.with_hint(hint),
+            None => err,
+        }
     } else {
         crate::error::RurlError::BrowserCookie(format!("{context}: {:?}: {}", path, err))
     }
@@ -173,7 +331,10 @@ fn path_matches(request_path: &str, cookie_path: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{domain_matches, is_expired, path_matches, BrowserCookieExtractor, Cookie};
+    use super::{
+        cookie_decisions, cookie_filter_domain, domain_matches, host_filter, is_expired,
+        path_matches, sql_where, BrowserCookieExtractor, Cookie,
+    };
     use crate::config::{Browser, BrowserCookieConfig};
     use std::collections::HashMap;
     use url::Url;
@@ -184,9 +345,33 @@ mod tests {
             profile: None,
             container: None,
             keyring: None,
+            domain_filter: None,
         })
     }
 
+    #[test]
+    fn cookie_filter_domain_keeps_last_two_labels() {
+        let url = Url::parse("https://a.b.example.co.uk/path").expect("url");
+        assert_eq!(cookie_filter_domain(&url).as_deref(), Some("co.uk"));
+        let url = Url::parse("https://www.example.com/").expect("url");
+        assert_eq!(cookie_filter_domain(&url).as_deref(), Some("example.com"));
+        let url = Url::parse("http://127.0.0.1:8080/").expect("url");
+        assert_eq!(cookie_filter_domain(&url).as_deref(), Some("127.0.0.1"));
+    }
+
+    #[test]
+    fn host_filter_builds_suffix_pattern() {
+        let (condition, param) = host_filter("host", Some(".example.com")).expect("filter");
+        assert_eq!(condition, "host LIKE ?");
+        assert_eq!(param, "%example.com");
+        assert!(host_filter("host", None).is_none());
+        assert_eq!(sql_where(&[]), "");
+        assert_eq!(
+            sql_where(&["a = 1".to_string(), "b = 2".to_string()]),
+            " WHERE a = 1 AND b = 2"
+        );
+    }
+
     #[test]
     fn domain_matches_accepts_subdomains() {
         assert!(domain_matches("example.com", ".example.com"));
@@ -258,6 +443,28 @@ mod tests {
         assert_eq!(cookies.len(), 2);
     }
 
+    #[test]
+    fn cookie_decisions_give_skip_reasons() {
+        let mut store: HashMap<String, Vec<Cookie>> = HashMap::new();
+        store.insert(
+            "example.com".to_string(),
+            vec![Cookie {
+                name: "admin".to_string(),
+                value: "1".to_string(),
+                domain: "example.com".to_string(),
+                path: "/admin".to_string(),
+                secure: false,
+                http_only: false,
+                expires: None,
+            }],
+        );
+
+        let url = Url::parse("http://example.com/public").expect("url");
+        let decisions = cookie_decisions(&store, &url);
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].1, Some("path does not match"));
+    }
+
     #[test]
     fn cookies_to_header_formats_pairs() {
         let cookies = vec![
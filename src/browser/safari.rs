@@ -1,6 +1,6 @@
 //! Safari browser cookie extraction (macOS only)
 
-use crate::browser::CookieStore;
+use crate::browser::{BrowserProfile, CookieStore};
 use crate::config::BrowserCookieConfig;
 use crate::error::{Result, RurlError};
 
@@ -20,6 +20,37 @@ pub async fn extract_cookies(config: &BrowserCookieConfig) -> Result<CookieStore
     }
 }
 
+/// Path of the Safari cookie file that would be read
+pub fn cookie_db_path(config: &BrowserCookieConfig) -> Result<std::path::PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::safari_cookie_path(config.profile.as_deref())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = config;
+        Err(RurlError::Unsupported(
+            "Safari is only available on macOS".to_string(),
+        ))
+    }
+}
+
+/// List Safari profiles (Safari 17+) with their cookie files
+pub fn list_profiles() -> Result<Vec<BrowserProfile>> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::list_profiles()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err(RurlError::Unsupported(
+            "Safari is only available on macOS".to_string(),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests;
 
@@ -27,8 +58,10 @@ mod tests;
 mod macos {
     use super::*;
     use crate::browser::Cookie;
+    use rusqlite::{Connection, OpenFlags};
+    use std::collections::HashMap;
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     use crate::utils::FileUtils;
 
@@ -54,24 +87,37 @@ mod macos {
         Ok(store)
     }
 
-    fn safari_cookie_path(profile: Option<&str>) -> Result<PathBuf> {
-        if let Some(profile) = profile {
-            let expanded = FileUtils::expand_path(profile)?;
-            if expanded.is_file() {
-                return Ok(expanded);
+    const SAFARI_LIBRARY: &str = "~/Library/Containers/com.apple.Safari/Data/Library";
+    const DEFAULT_PROFILE: &str = "default";
+
+    /// Resolve the cookie file for `profile`: a Safari 17 profile name or
+    /// UUID, `default`, or a path to a `Cookies.binarycookies` file
+    pub fn safari_cookie_path(profile: Option<&str>) -> Result<PathBuf> {
+        match profile {
+            Some(profile) if !profile.eq_ignore_ascii_case(DEFAULT_PROFILE) => {
+                let expanded = FileUtils::expand_path(profile)?;
+                if expanded.is_file() {
+                    return Ok(expanded);
+                }
+                if let Some(path) = find_profile_cookie_path(&expand_home(SAFARI_LIBRARY)?, profile)
+                {
+                    return Ok(path);
+                }
+                Err(RurlError::FileNotFound(format!(
+                    "Safari profile or cookies file not found: {}",
+                    profile
+                )))
             }
-            return Err(RurlError::FileNotFound(
-                "Custom Safari cookies path not found".to_string(),
-            ));
+            _ => default_cookie_path(),
         }
+    }
 
+    fn default_cookie_path() -> Result<PathBuf> {
         let primary = expand_home("~/Library/Cookies/Cookies.binarycookies")?;
         if primary.is_file() {
             return Ok(primary);
         }
-        let secondary = expand_home(
-            "~/Library/Containers/com.apple.Safari/Data/Library/Cookies/Cookies.binarycookies",
-        )?;
+        let secondary = expand_home(SAFARI_LIBRARY)?.join("Cookies/Cookies.binarycookies");
         if secondary.is_file() {
             return Ok(secondary);
         }
@@ -81,6 +127,88 @@ mod macos {
         ))
     }
 
+    /// List the default profile and every Safari 17 profile container
+    pub fn list_profiles() -> Result<Vec<BrowserProfile>> {
+        let mut profiles = Vec::new();
+        if let Ok(path) = default_cookie_path() {
+            profiles.push(BrowserProfile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Default".to_string(),
+                path,
+                containers: Vec::new(),
+            });
+        }
+        profiles.extend(profiles_in_library(&expand_home(SAFARI_LIBRARY)?));
+        Ok(profiles)
+    }
+
+    /// Profiles stored as `WebKit/WebsiteDataStore/<UUID>` under the Safari
+    /// container, named from `Safari/SafariTabs.db` when it is readable
+    pub(super) fn profiles_in_library(library: &Path) -> Vec<BrowserProfile> {
+        let names = profile_names(&library.join("Safari").join("SafariTabs.db"));
+        let entries = match fs::read_dir(library.join("WebKit").join("WebsiteDataStore")) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut profiles: Vec<BrowserProfile> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let id = entry.file_name().to_string_lossy().to_string();
+                let path = entry.path().join("Cookies").join("Cookies.binarycookies");
+                if !path.is_file() {
+                    return None;
+                }
+                let name = names
+                    .get(&id.to_uppercase())
+                    .cloned()
+                    .unwrap_or_else(|| id.clone());
+                Some(BrowserProfile {
+                    id,
+                    name,
+                    path,
+                    containers: Vec::new(),
+                })
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles
+    }
+
+    pub(super) fn find_profile_cookie_path(library: &Path, profile: &str) -> Option<PathBuf> {
+        profiles_in_library(library)
+            .into_iter()
+            .find(|p| p.id.eq_ignore_ascii_case(profile) || p.name.eq_ignore_ascii_case(profile))
+            .map(|p| p.path)
+    }
+
+    /// Map profile UUIDs (uppercase) to display names
+    fn profile_names(tabs_db: &Path) -> HashMap<String, String> {
+        let conn = match Connection::open_with_flags(tabs_db, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+            Ok(conn) => conn,
+            Err(_) => return HashMap::new(),
+        };
+        // Profiles are bookmark rows with subtype 2
+        let mut stmt =
+            match conn.prepare("SELECT external_uuid, title FROM bookmarks WHERE subtype = 2") {
+                Ok(stmt) => stmt,
+                Err(err) => {
+                    log::debug!("Failed to read Safari profile names: {}", err);
+                    return HashMap::new();
+                }
+            };
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        });
+        match rows {
+            Ok(rows) => rows
+                .flatten()
+                .map(|(uuid, title)| (uuid.to_uppercase(), title))
+                .collect(),
+            Err(_) => HashMap::new(),
+        }
+    }
+
     fn expand_home(path: &str) -> Result<PathBuf> {
         FileUtils::expand_path(path)
     }
@@ -264,6 +392,14 @@ mod macos {
             super::safari_cookie_path(profile)
         }
 
+        pub fn profiles_in_library(library: &Path) -> Vec<BrowserProfile> {
+            super::profiles_in_library(library)
+        }
+
+        pub fn find_profile_cookie_path(library: &Path, profile: &str) -> Option<PathBuf> {
+            super::find_profile_cookie_path(library, profile)
+        }
+
         pub fn read_null_terminated_string_at(data: &[u8], offset: usize) -> Result<String> {
             super::read_null_terminated_string_at(data, offset)
         }
@@ -10,6 +10,7 @@ async fn safari_extraction_is_unsupported_off_macos() {
         profile: None,
         container: None,
         keyring: None,
+        domain_filter: None,
     };
     let err = extract_cookies(&cfg).await.expect_err("unsupported");
     assert!(matches!(err, RurlError::Unsupported(_)));
@@ -18,7 +19,8 @@ async fn safari_extraction_is_unsupported_off_macos() {
 #[cfg(target_os = "macos")]
 mod macos_tests {
     use super::super::macos::test_support::{
-        mac_absolute_to_unix, mac_epoch_offset, read_null_terminated_string_at, safari_cookie_path,
+        find_profile_cookie_path, mac_absolute_to_unix, mac_epoch_offset, profiles_in_library,
+        read_null_terminated_string_at, safari_cookie_path,
     };
     use std::fs;
     use tempfile::tempdir;
@@ -69,8 +71,43 @@ mod macos_tests {
             profile: Some("/nonexistent/Cookies.binarycookies".to_string()),
             container: None,
             keyring: None,
+            domain_filter: None,
         };
         let err = safari_cookie_path(cfg.profile.as_deref()).expect_err("missing");
         assert!(matches!(err, RurlError::FileNotFound(_)));
     }
+
+    #[test]
+    fn profiles_in_library_reads_names_from_safari_tabs() {
+        let temp = tempdir().expect("tempdir");
+        let library = temp.path();
+        let uuid = "0A1B2C3D-0000-4000-8000-000000000001";
+        let cookies = library
+            .join("WebKit/WebsiteDataStore")
+            .join(uuid)
+            .join("Cookies");
+        fs::create_dir_all(&cookies).expect("mkdir");
+        fs::write(cookies.join("Cookies.binarycookies"), b"cook").expect("write");
+        fs::create_dir_all(library.join("WebKit/WebsiteDataStore/empty")).expect("mkdir");
+
+        fs::create_dir_all(library.join("Safari")).expect("mkdir");
+        let conn = rusqlite::Connection::open(library.join("Safari/SafariTabs.db")).expect("db");
+        conn.execute_batch(&format!(
+            "CREATE TABLE bookmarks (external_uuid TEXT, title TEXT, subtype INTEGER);
+             INSERT INTO bookmarks VALUES ('{}', 'Work', 2);",
+            uuid.to_lowercase()
+        ))
+        .expect("seed");
+        drop(conn);
+
+        let profiles = profiles_in_library(library);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].id, uuid);
+        assert_eq!(profiles[0].name, "Work");
+
+        let by_name = find_profile_cookie_path(library, "work").expect("name");
+        assert_eq!(by_name, cookies.join("Cookies.binarycookies"));
+        assert_eq!(find_profile_cookie_path(library, uuid), Some(by_name));
+        assert!(find_profile_cookie_path(library, "Personal").is_none());
+    }
 }
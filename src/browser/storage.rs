@@ -0,0 +1,302 @@
+//! Browser localStorage access
+//!
+//! Single-page apps often keep bearer tokens in localStorage instead of
+//! cookies. `--token-from-browser BROWSER:ORIGIN:KEY` reads one value from the
+//! profile the cookie extractor would use and sends it as
+//! `Authorization: Bearer ...`.
+
+use crate::browser::BrowserCookieExtractor;
+use crate::config::{Browser, BrowserTokenConfig};
+use crate::error::{Result, RurlError};
+use rusqlite::{params, Connection, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Read the localStorage value named by `config`
+pub fn read_local_storage(config: &BrowserTokenConfig) -> Result<String> {
+    let cookie_db = BrowserCookieExtractor::new(config.browser.clone()).source_path()?;
+    let value = match config.browser.browser {
+        Browser::Safari => {
+            return Err(RurlError::Unsupported(
+                "Reading Safari localStorage is not supported".to_string(),
+            ))
+        }
+        Browser::Firefox | Browser::LibreWolf | Browser::Waterfox | Browser::TorBrowser => {
+            let profile = cookie_db.parent().unwrap_or(Path::new("."));
+            read_firefox_value(profile, &config.origin, &config.key)?
+        }
+        _ => {
+            let leveldb = chromium_profile_dir(&cookie_db)
+                .join("Local Storage")
+                .join("leveldb");
+            read_chromium_value(&leveldb, &config.origin, &config.key)?
+        }
+    };
+    value.ok_or_else(|| {
+        RurlError::BrowserCookie(format!(
+            "No localStorage entry '{}' for {}",
+            config.key, config.origin
+        ))
+    })
+}
+
+/// Profile directory of a Chromium cookie database (`<profile>/Network/Cookies`
+/// on current versions, `<profile>/Cookies` on older ones)
+fn chromium_profile_dir(cookie_db: &Path) -> PathBuf {
+    let parent = cookie_db.parent().unwrap_or(Path::new("."));
+    if parent.file_name().is_some_and(|name| name == "Network") {
+        parent.parent().unwrap_or(parent).to_path_buf()
+    } else {
+        parent.to_path_buf()
+    }
+}
+
+fn read_chromium_value(leveldb_dir: &Path, origin: &str, key: &str) -> Result<Option<String>> {
+    if !leveldb_dir.is_dir() {
+        return Err(RurlError::FileNotFound(format!(
+            "Local Storage not found: {:?}",
+            leveldb_dir
+        )));
+    }
+
+    // The running browser holds the LOCK, and opening may rewrite the log
+    let temp_dir = tempfile::tempdir()
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
+    for entry in fs::read_dir(leveldb_dir)? {
+        let entry = entry?;
+        if entry.file_name() == "LOCK" || !entry.path().is_file() {
+            continue;
+        }
+        fs::copy(entry.path(), temp_dir.path().join(entry.file_name())).map_err(|e| {
+            crate::browser::map_cookie_io_error(
+                "Failed to copy Local Storage",
+                &entry.path(),
+                e,
+                None,
+            )
+        })?;
+    }
+
+    let mut options = rusty_leveldb::Options::default();
+    options.create_if_missing = false;
+    let mut db = rusty_leveldb::DB::open(temp_dir.path(), options)
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to open Local Storage: {}", e)))?;
+    for db_key in chromium_keys(origin, key) {
+        if let Some(value) = db.get(&db_key) {
+            return Ok(decode_chromium_string(&value));
+        }
+    }
+    Ok(None)
+}
+
+/// LevelDB keys for `key` under `origin`: `_<origin>\0` followed by the
+/// script key, either Latin-1 (prefix 1) or UTF-16LE (prefix 0)
+fn chromium_keys(origin: &str, key: &str) -> Vec<Vec<u8>> {
+    let mut prefix = format!("_{}", origin).into_bytes();
+    prefix.push(0);
+
+    let mut keys = Vec::new();
+    if key.chars().all(|c| (c as u32) <= 0xFF) {
+        let mut latin1 = prefix.clone();
+        latin1.push(1);
+        latin1.extend(key.chars().map(|c| c as u8));
+        keys.push(latin1);
+    }
+    let mut utf16 = prefix;
+    utf16.push(0);
+    utf16.extend(key.encode_utf16().flat_map(u16::to_le_bytes));
+    keys.push(utf16);
+    keys
+}
+
+fn decode_chromium_string(bytes: &[u8]) -> Option<String> {
+    match bytes.split_first() {
+        Some((0, rest)) => decode_utf16le(rest),
+        Some((1, rest)) => Some(rest.iter().map(|&b| b as char).collect()),
+        _ => None,
+    }
+}
+
+fn decode_utf16le(bytes: &[u8]) -> Option<String> {
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16(&units).ok()
+}
+
+/// Firefox stores each origin under `storage/default/<scheme>+++<host>[+port]`
+fn firefox_origin_dir(origin: &str) -> String {
+    origin.replace("://", "+++").replace(':', "+")
+}
+
+fn read_firefox_value(profile: &Path, origin: &str, key: &str) -> Result<Option<String>> {
+    let data_db = profile
+        .join("storage")
+        .join("default")
+        .join(firefox_origin_dir(origin))
+        .join("ls")
+        .join("data.sqlite");
+    if !data_db.is_file() {
+        return Ok(None);
+    }
+
+    // Copy with the WAL so recent writes by the running browser are visible
+    let temp_dir = tempfile::tempdir()
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to create temp dir: {}", e)))?;
+    let temp_db = temp_dir.path().join("data.sqlite");
+    fs::copy(&data_db, &temp_db).map_err(|e| {
+        crate::browser::map_cookie_io_error("Failed to copy localStorage", &data_db, e, None)
+    })?;
+    let mut wal = data_db.into_os_string();
+    wal.push("-wal");
+    let wal = PathBuf::from(wal);
+    if wal.is_file() {
+        if let Err(err) = fs::copy(&wal, temp_dir.path().join("data.sqlite-wal")) {
+            log::warn!("Failed to copy localStorage WAL {:?}: {}", wal, err);
+        }
+    }
+
+    let conn = Connection::open_with_flags(&temp_db, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|e| RurlError::BrowserCookie(format!("Failed to open localStorage: {}", e)))?;
+    let row = conn.query_row(
+        "SELECT value, compression_type, conversion_type FROM data WHERE key = ?1",
+        params![key],
+        |row| {
+            Ok((
+                row.get::<_, Vec<u8>>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        },
+    );
+    let (value, compression_type, conversion_type) = match row {
+        Ok(row) => row,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => {
+            return Err(RurlError::BrowserCookie(format!(
+                "Failed to read localStorage: {}",
+                e
+            )))
+        }
+    };
+    Ok(decode_firefox_value(
+        value,
+        compression_type,
+        conversion_type,
+    ))
+}
+
+/// Values may be snappy-compressed (compression type 1) and are UTF-8 when
+/// converted from UTF-16 (conversion type 1)
+fn decode_firefox_value(
+    value: Vec<u8>,
+    compression_type: i64,
+    conversion_type: i64,
+) -> Option<String> {
+    let value = if compression_type == 1 {
+        snap::raw::Decoder::new().decompress_vec(&value).ok()?
+    } else {
+        value
+    };
+    if conversion_type == 1 {
+        String::from_utf8(value).ok()
+    } else {
+        decode_utf16le(&value).or_else(|| String::from_utf8(value).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        chromium_keys, chromium_profile_dir, decode_chromium_string, decode_firefox_value,
+        firefox_origin_dir, read_firefox_value,
+    };
+    use rusqlite::{params, Connection};
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn chromium_keys_encode_origin_and_key() {
+        let keys = chromium_keys("https://app.example.com", "token");
+        assert_eq!(keys[0], b"_https://app.example.com\x00\x01token".to_vec());
+        assert_eq!(keys[1].len(), "_https://app.example.com".len() + 2 + 10);
+        assert_eq!(chromium_keys("https://a.com", "鍵").len(), 1);
+    }
+
+    #[test]
+    fn decode_chromium_string_handles_both_encodings() {
+        assert_eq!(decode_chromium_string(b"\x01abc").as_deref(), Some("abc"));
+        assert_eq!(
+            decode_chromium_string(b"\x00a\x00b\x00").as_deref(),
+            Some("ab")
+        );
+        assert!(decode_chromium_string(b"\x02abc").is_none());
+    }
+
+    #[test]
+    fn chromium_profile_dir_skips_network_dir() {
+        assert_eq!(
+            chromium_profile_dir(Path::new("/u/Default/Network/Cookies")),
+            Path::new("/u/Default")
+        );
+        assert_eq!(
+            chromium_profile_dir(Path::new("/u/Default/Cookies")),
+            Path::new("/u/Default")
+        );
+    }
+
+    #[test]
+    fn firefox_origin_dir_replaces_separators() {
+        assert_eq!(
+            firefox_origin_dir("https://app.example.com"),
+            "https+++app.example.com"
+        );
+        assert_eq!(
+            firefox_origin_dir("http://localhost:3000"),
+            "http+++localhost+3000"
+        );
+    }
+
+    #[test]
+    fn read_firefox_value_decodes_compressed_entries() {
+        let profile = tempdir().expect("tempdir");
+        let ls = profile
+            .path()
+            .join("storage/default/https+++app.example.com/ls");
+        std::fs::create_dir_all(&ls).expect("mkdir");
+        let conn = Connection::open(ls.join("data.sqlite")).expect("db");
+        conn.execute_batch(
+            "CREATE TABLE data (key TEXT PRIMARY KEY, utf16_length INTEGER,
+             conversion_type INTEGER, compression_type INTEGER, value BLOB)",
+        )
+        .expect("schema");
+        let compressed = snap::raw::Encoder::new()
+            .compress_vec(b"eyJhbGciOi")
+            .expect("compress");
+        conn.execute(
+            "INSERT INTO data VALUES ('token', 10, 1, 1, ?1)",
+            params![compressed],
+        )
+        .expect("insert");
+        drop(conn);
+
+        let value =
+            read_firefox_value(profile.path(), "https://app.example.com", "token").expect("read");
+        assert_eq!(value.as_deref(), Some("eyJhbGciOi"));
+        let missing =
+            read_firefox_value(profile.path(), "https://app.example.com", "other").expect("read");
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn decode_firefox_value_reads_plain_utf8() {
+        assert_eq!(
+            decode_firefox_value(b"abc".to_vec(), 0, 1).as_deref(),
+            Some("abc")
+        );
+    }
+}
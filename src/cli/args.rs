@@ -1 +1,164 @@
 //! Command line argument definitions and validation
+
+use crate::config::{FormField, FormValue};
+use crate::error::{Result, RurlError};
+use crate::utils::FileUtils;
+
+/// Parse `-F NAME=CONTENT[;type=TYPE][;filename=NAME]`: `@FILE` attaches a
+/// file, `<FILE` sends the file's text as the value, anything else is sent
+/// literally
+pub fn parse_form_field(spec: &str) -> Result<FormField> {
+    let (name, content) = split_form_spec(spec)?;
+    let (content, params) = split_form_params(content)?;
+    let value = if let Some(path) = content.strip_prefix('@') {
+        FormValue::File(FileUtils::expand_path(path)?)
+    } else if let Some(path) = content.strip_prefix('<') {
+        let path = FileUtils::expand_path(path)?;
+        FileUtils::check_file_readable(&path)?;
+        FormValue::Text(std::fs::read_to_string(&path)?)
+    } else {
+        FormValue::Text(content.to_string())
+    };
+    let mut field = FormField {
+        name,
+        value,
+        content_type: None,
+        filename: None,
+    };
+    for (key, value) in params {
+        match key.to_ascii_lowercase().as_str() {
+            "type" => field.content_type = Some(value),
+            "filename" => field.filename = Some(value),
+            _ => {
+                return Err(RurlError::Config(format!(
+                    "Unknown form parameter '{}' in '{}'",
+                    key, spec
+                )))
+            }
+        }
+    }
+    Ok(field)
+}
+
+/// Parse `--form-string NAME=VALUE`, whose value is never read from a file
+pub fn parse_form_string(spec: &str) -> Result<FormField> {
+    let (name, content) = split_form_spec(spec)?;
+    Ok(FormField {
+        name,
+        value: FormValue::Text(content.to_string()),
+        content_type: None,
+        filename: None,
+    })
+}
+
+fn split_form_spec(spec: &str) -> Result<(String, &str)> {
+    match spec.split_once('=') {
+        Some((name, content)) if !name.is_empty() => Ok((name.to_string(), content)),
+        _ => Err(RurlError::Config(format!(
+            "Invalid form field '{}': expected NAME=CONTENT",
+            spec
+        ))),
+    }
+}
+
+/// Split `CONTENT;key=value;...` at the first `;type=` or `;filename=`;
+/// values may be double-quoted to contain `;`, with `\\"` for a quote
+fn split_form_params(content: &str) -> Result<(&str, Vec<(String, String)>)> {
+    let start = content.match_indices(';').map(|(i, _)| i).find(|&i| {
+        let rest = content[i + 1..].trim_start().to_ascii_lowercase();
+        rest.starts_with("type=") || rest.starts_with("filename=")
+    });
+    let Some(start) = start else {
+        return Ok((content, Vec::new()));
+    };
+
+    let invalid = || RurlError::Config(format!("Invalid form parameters '{}'", &content[start..]));
+    let mut params = Vec::new();
+    let mut rest = &content[start + 1..];
+    loop {
+        let (key, after) = rest.trim_start().split_once('=').ok_or_else(invalid)?;
+        let key = key.trim().to_string();
+        let mut value = String::new();
+        let remainder = if let Some(quoted) = after.strip_prefix('"') {
+            let mut chars = quoted.char_indices();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some((_, escaped)) = chars.next() {
+                            value.push(escaped);
+                        }
+                    }
+                    '"' => {
+                        end = Some(i + 1);
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+            let after_quote = quoted[end.ok_or_else(invalid)?..].trim_start();
+            if !after_quote.is_empty() && !after_quote.starts_with(';') {
+                return Err(invalid());
+            }
+            after_quote
+        } else {
+            let end = after.find(';').unwrap_or(after.len());
+            value.push_str(after[..end].trim_end());
+            &after[end..]
+        };
+        params.push((key, value));
+        match remainder.strip_prefix(';') {
+            Some(next) => rest = next,
+            None => return Ok((&content[..start], params)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_form_field, parse_form_string};
+    use crate::config::FormValue;
+    use std::path::PathBuf;
+
+    #[test]
+    fn form_field_prefixes_select_the_source() {
+        let field = parse_form_field("upload=@/tmp/big.iso").expect("file");
+        assert_eq!(field.name, "upload");
+        assert_eq!(field.value, FormValue::File(PathBuf::from("/tmp/big.iso")));
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let note = dir.path().join("note.txt");
+        std::fs::write(&note, "from file").expect("write");
+        let field = parse_form_field(&format!("note=<{}", note.display())).expect("content");
+        assert_eq!(field.value, FormValue::Text("from file".to_string()));
+
+        assert!(parse_form_field("=value").is_err());
+        assert!(parse_form_field("novalue").is_err());
+    }
+
+    #[test]
+    fn form_field_parameters_set_type_and_filename() {
+        let field =
+            parse_form_field("file=@photo.png;type=image/png;filename=other.png").expect("params");
+        assert_eq!(field.value, FormValue::File(PathBuf::from("photo.png")));
+        assert_eq!(field.content_type.as_deref(), Some("image/png"));
+        assert_eq!(field.filename.as_deref(), Some("other.png"));
+
+        let field = parse_form_field(r#"doc=@a.txt; filename="x;\"y\".txt""#).expect("quoted");
+        assert_eq!(field.filename.as_deref(), Some(r#"x;"y".txt"#));
+        assert_eq!(field.content_type, None);
+
+        let field = parse_form_field("note=a;b;type=text/plain").expect("text");
+        assert_eq!(field.value, FormValue::Text("a;b".to_string()));
+        assert_eq!(field.content_type.as_deref(), Some("text/plain"));
+
+        assert!(parse_form_field("file=@a;type=text/plain;encoder=b64").is_err());
+        assert!(parse_form_field(r#"file=@a;filename="open"#).is_err());
+    }
+
+    #[test]
+    fn form_string_is_literal() {
+        let field = parse_form_string("handle=@someone").expect("literal");
+        assert_eq!(field.value, FormValue::Text("@someone".to_string()));
+    }
+}
@@ -0,0 +1,217 @@
+//! `rurl auth` subcommand
+//!
+//! Manages credentials kept in the OS keyring for later requests. `oauth2`
+//! signs in with an OAuth2 flow and stores the tokens, which requests then
+//! refresh on their own.
+
+use crate::credentials::{CredentialStore, StoredCredential};
+use crate::error::{Result, RurlError};
+use crate::http::oauth2::OAuth2Client;
+use clap::{Arg, ArgMatches, Command};
+use std::io::Write;
+use url::Url;
+
+/// Build the `auth` subcommand
+pub(crate) fn auth_command() -> Command {
+    Command::new("auth")
+        .about("Store credentials in the OS keyring")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("add")
+                .about("Save credentials sent to URL's origin on later requests")
+                .arg(
+                    Arg::new("url")
+                        .value_name("URL")
+                        .help("Origin the credentials apply to")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("user")
+                        .short('u')
+                        .long("user")
+                        .value_name("USER:PASSWORD")
+                        .help("Basic auth credentials")
+                        .conflicts_with("token"),
+                )
+                .arg(
+                    Arg::new("token")
+                        .long("token")
+                        .value_name("TOKEN")
+                        .help("Bearer token")
+                        .required_unless_present("user"),
+                ),
+        )
+        .subcommand(
+            Command::new("remove")
+                .about("Delete the credentials stored for URL's origin")
+                .arg(
+                    Arg::new("url")
+                        .value_name("URL")
+                        .help("Origin whose credentials to delete")
+                        .required(true),
+                ),
+        )
+        .subcommand(Command::new("list").about("List origins with stored credentials"))
+        .subcommand(
+            Command::new("oauth2")
+                .about("Sign in with OAuth2 and use the tokens for URL's origin")
+                .arg(
+                    Arg::new("url")
+                        .value_name("URL")
+                        .help("Origin the access token is sent to")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("client-id")
+                        .long("client-id")
+                        .value_name("ID")
+                        .help("OAuth2 client ID")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("client-secret")
+                        .long("client-secret")
+                        .value_name("SECRET")
+                        .help("Client secret, for confidential clients"),
+                )
+                .arg(
+                    Arg::new("token-url")
+                        .long("token-url")
+                        .value_name("URL")
+                        .help("Token endpoint")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("device-code")
+                        .long("device-code")
+                        .value_name("URL")
+                        .help("Use the device flow with this device authorization endpoint")
+                        .conflicts_with("auth-code")
+                        .required_unless_present("auth-code"),
+                )
+                .arg(
+                    Arg::new("auth-code")
+                        .long("auth-code")
+                        .value_name("URL")
+                        .help("Use the authorization code flow with this authorization endpoint"),
+                )
+                .arg(
+                    Arg::new("scope")
+                        .long("scope")
+                        .value_name("SCOPE")
+                        .help("Space-separated scopes to request"),
+                )
+                .arg(
+                    Arg::new("redirect-port")
+                        .long("redirect-port")
+                        .value_name("PORT")
+                        .help("Local port for the authorization code redirect (default: any free port)")
+                        .value_parser(clap::value_parser!(u16))
+                        .default_value("0"),
+                ),
+        )
+}
+
+/// Run the `auth` subcommand
+pub(crate) async fn run_auth(matches: &ArgMatches) -> Result<()> {
+    let store = CredentialStore::open()?;
+    match matches.subcommand() {
+        Some(("add", add)) => {
+            let url = parse_url(add)?;
+            let credential = match add.get_one::<String>("user") {
+                Some(user) => {
+                    let (username, password) = user.split_once(':').ok_or_else(|| {
+                        RurlError::Config("--user must be USER:PASSWORD".to_string())
+                    })?;
+                    StoredCredential::Basic {
+                        username: username.to_string(),
+                        password: password.to_string(),
+                    }
+                }
+                None => StoredCredential::Bearer {
+                    token: add
+                        .get_one::<String>("token")
+                        .cloned()
+                        .ok_or_else(|| RurlError::Config("--token is required".to_string()))?,
+                },
+            };
+            store.add(&url, &credential)
+        }
+        Some(("remove", remove)) => {
+            let url = parse_url(remove)?;
+            if !store.remove(&url)? {
+                eprintln!(
+                    "rurl: no stored credentials for {}",
+                    url.origin().ascii_serialization()
+                );
+            }
+            Ok(())
+        }
+        Some(("list", _)) => {
+            let mut stdout = std::io::stdout();
+            for origin in store.origins()? {
+                writeln!(stdout, "{}", origin)?;
+            }
+            Ok(())
+        }
+        Some(("oauth2", oauth2)) => {
+            let url = parse_url(oauth2)?;
+            let credential = sign_in(oauth2).await?;
+            store.add(&url, &credential)?;
+            eprintln!(
+                "rurl: signed in; requests to {} now use the OAuth2 token",
+                url.origin().ascii_serialization()
+            );
+            Ok(())
+        }
+        _ => Err(RurlError::Config("Unknown auth subcommand".to_string())),
+    }
+}
+
+/// Run the flow chosen on the command line
+async fn sign_in(matches: &ArgMatches) -> Result<StoredCredential> {
+    let endpoint = |id: &str| -> Result<Option<Url>> {
+        matches
+            .get_one::<String>(id)
+            .map(|url| {
+                Url::parse(url).map_err(|e| {
+                    RurlError::InvalidUrl(format!("Invalid --{} URL '{}': {}", id, url, e))
+                })
+            })
+            .transpose()
+    };
+    let client = OAuth2Client {
+        token_url: endpoint("token-url")?
+            .ok_or_else(|| RurlError::Config("--token-url is required".to_string()))?,
+        client_id: matches
+            .get_one::<String>("client-id")
+            .cloned()
+            .ok_or_else(|| RurlError::Config("--client-id is required".to_string()))?,
+        client_secret: matches.get_one::<String>("client-secret").cloned(),
+        scope: matches.get_one::<String>("scope").cloned(),
+    };
+    let http = reqwest::Client::builder()
+        .user_agent(format!("rurl/{}", crate::VERSION))
+        .build()
+        .map_err(RurlError::transport)?;
+    match (endpoint("device-code")?, endpoint("auth-code")?) {
+        (Some(device_url), _) => client.device_flow(&http, &device_url).await,
+        (None, Some(auth_url)) => {
+            let port = matches
+                .get_one::<u16>("redirect-port")
+                .copied()
+                .unwrap_or(0);
+            client.auth_code_flow(&http, &auth_url, port).await
+        }
+        (None, None) => Err(RurlError::Config(
+            "Pass --device-code or --auth-code".to_string(),
+        )),
+    }
+}
+
+fn parse_url(matches: &ArgMatches) -> Result<Url> {
+    let url = matches
+        .get_one::<String>("url")
+        .ok_or_else(|| RurlError::Config("URL is required".to_string()))?;
+    Url::parse(url).map_err(|e| RurlError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))
+}
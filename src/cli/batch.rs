@@ -0,0 +1,360 @@
+//! `rurl batch` subcommand
+//!
+//! Runs the requests listed in an NDJSON file, a bounded number at a time,
+//! and writes one JSON result per request in input order. With
+//! `--fail-fast-threshold`, a host that keeps failing gets no more requests.
+
+use crate::config::{Config, HttpMethod};
+use crate::error::{Result, RurlError};
+use crate::exit_code::exit_code_for_error;
+use crate::http::HttpClient;
+use crate::output::template::{self, TemplateValues};
+use crate::utils::{FileUtils, UrlUtils};
+use base64::Engine;
+use clap::{Arg, ArgMatches, Command};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Build the `batch` subcommand
+pub(crate) fn batch_command() -> Command {
+    Command::new("batch")
+        .about("Run the requests listed in an NDJSON file and print NDJSON results")
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("Requests, one JSON object per line ('-' reads stdin)")
+                .required(true),
+        )
+        .arg(
+            Arg::new("parallel")
+                .short('P')
+                .long("parallel")
+                .value_name("N")
+                .help("Number of requests in flight at once")
+                .default_value("4"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write results to FILE instead of stdout"),
+        )
+        .arg(
+            Arg::new("fail-fast-threshold")
+                .long("fail-fast-threshold")
+                .value_name("N")
+                .help("Skip the remaining requests to a host after N consecutive failures"),
+        )
+}
+
+/// One line of the request file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchRequest {
+    url: String,
+    /// Defaults to POST with a body and GET without
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<String>,
+    /// Save the response body to this file instead of the result
+    #[serde(default)]
+    output: Option<PathBuf>,
+    /// Copied to the result to match it up with the request
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+}
+
+/// One line of the results
+#[derive(Debug, Default, Serialize)]
+struct BatchResult {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<serde_json::Value>,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    /// Bodies that are not UTF-8
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    /// Not sent because its host kept failing
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    skipped: bool,
+}
+
+impl BatchResult {
+    /// Transport errors and server errors count against the host
+    fn failed(&self) -> bool {
+        self.error.is_some() || self.status.is_some_and(|status| status >= 500)
+    }
+}
+
+/// Consecutive failures per host, for `--fail-fast-threshold`
+struct CircuitBreaker {
+    threshold: u32,
+    failures: Mutex<HashMap<String, u32>>,
+}
+
+impl CircuitBreaker {
+    fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether requests to `host` are no longer sent
+    fn is_open(&self, host: &str) -> bool {
+        let failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        failures
+            .get(host)
+            .is_some_and(|&count| count >= self.threshold)
+    }
+
+    /// Count a failure for `host`, or reset its count after a success
+    fn record(&self, host: &str, failed: bool) {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        let count = failures.entry(host.to_string()).or_default();
+        *count = if failed { *count + 1 } else { 0 };
+    }
+}
+
+/// Run the `batch` subcommand
+pub(crate) async fn run_batch(matches: &ArgMatches) -> Result<()> {
+    let file = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| RurlError::Config("A request file is required".to_string()))?;
+    let input = if file == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        input
+    } else {
+        let path = FileUtils::expand_path(file)?;
+        FileUtils::check_file_readable(&path)?;
+        std::fs::read_to_string(path)?
+    };
+    let requests = parse_requests(&input)?;
+
+    let parallel = matches
+        .get_one::<String>("parallel")
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|&value| value > 0)
+        .ok_or_else(|| RurlError::Config("--parallel must be a positive number".to_string()))?;
+    let mut out: Box<dyn Write + Send> = match matches.get_one::<String>("output") {
+        Some(path) if path != "-" => Box::new(std::io::BufWriter::new(std::fs::File::create(
+            FileUtils::expand_path(path)?,
+        )?)),
+        _ => Box::new(std::io::stdout()),
+    };
+
+    let breaker = match matches.get_one::<String>("fail-fast-threshold") {
+        Some(value) => Some(CircuitBreaker::new(
+            value
+                .parse::<u32>()
+                .ok()
+                .filter(|&value| value > 0)
+                .ok_or_else(|| {
+                    RurlError::Config("--fail-fast-threshold must be a positive number".to_string())
+                })?,
+        )),
+        None => None,
+    };
+
+    let (mut succeeded, mut failed, mut skipped) = (0, 0, 0);
+    let mut results = futures_util::stream::iter(requests)
+        .map(|(line, request)| run_request(line, request, breaker.as_ref()))
+        .buffered(parallel);
+    while let Some(result) = results.next().await {
+        match (result.skipped, result.failed()) {
+            (true, _) => skipped += 1,
+            (false, true) => failed += 1,
+            (false, false) => succeeded += 1,
+        }
+        serde_json::to_writer(&mut out, &result)?;
+        out.write_all(b"\n")?;
+        out.flush()?;
+    }
+    if breaker.is_some() {
+        eprintln!(
+            "rurl: batch: {} succeeded, {} failed, {} skipped",
+            succeeded, failed, skipped
+        );
+    }
+    Ok(())
+}
+
+/// Requests with their 1-based line numbers; blank lines are skipped
+fn parse_requests(input: &str) -> Result<Vec<(usize, BatchRequest)>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map(|request| (index + 1, request))
+                .map_err(|e| {
+                    RurlError::Config(format!("Invalid request on line {}: {}", index + 1, e))
+                })
+        })
+        .collect()
+}
+
+async fn run_request(
+    line: usize,
+    request: BatchRequest,
+    breaker: Option<&CircuitBreaker>,
+) -> BatchResult {
+    let mut result = BatchResult {
+        line,
+        id: request.id.clone(),
+        url: request.url.clone(),
+        ..BatchResult::default()
+    };
+    let host = host_key(&request.url);
+    if breaker.is_some_and(|breaker| breaker.is_open(&host)) {
+        result.skipped = true;
+        return result;
+    }
+    let started = Instant::now();
+    if let Err(err) = send(&request, &mut result).await {
+        result.exit_code = Some(exit_code_for_error(&err));
+        result.error = Some(err.to_string());
+    }
+    result.time_ms = started.elapsed().as_millis() as u64;
+    if let Some(breaker) = breaker {
+        breaker.record(&host, result.failed());
+    }
+    result
+}
+
+/// `host:port` a request goes to; the URL itself when it does not parse
+fn host_key(url: &str) -> String {
+    match UrlUtils::validate_url(url) {
+        Ok(url) => format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        ),
+        Err(_) => url.to_string(),
+    }
+}
+
+async fn send(request: &BatchRequest, result: &mut BatchResult) -> Result<()> {
+    let mut config = Config {
+        url: request.url.clone(),
+        headers: request
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+        data: request.body.clone(),
+        ..Config::default()
+    };
+    match &request.method {
+        Some(method) => {
+            config.method = method
+                .parse()
+                .map_err(|_| RurlError::Config(format!("Unsupported method: {}", method)))?;
+            config.request_method_explicit = true;
+        }
+        None if request.body.is_some() => config.method = HttpMethod::Post,
+        None => {}
+    }
+
+    let response = HttpClient::new(config)?.execute().await?;
+    let status = response.status();
+    result.status = Some(status.as_u16());
+    let url = response.url().clone();
+    let body = response.bytes().await.map_err(RurlError::transport)?;
+    match &request.output {
+        Some(path) => {
+            let path = match path.to_str().filter(|name| template::is_template(name)) {
+                Some(name) => template::render(
+                    name,
+                    &TemplateValues {
+                        url: &url,
+                        status,
+                        globs: &[],
+                    },
+                )?,
+                None => path.clone(),
+            };
+            tokio::fs::write(&path, &body).await?;
+            result.output = Some(path);
+            result.bytes = Some(body.len() as u64);
+        }
+        None => match std::str::from_utf8(&body) {
+            Ok(text) => result.body = Some(text.to_string()),
+            Err(_) => {
+                result.body_base64 = Some(base64::engine::general_purpose::STANDARD.encode(&body))
+            }
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{host_key, parse_requests, BatchResult, CircuitBreaker};
+
+    #[test]
+    fn parse_requests_numbers_lines_and_rejects_unknown_fields() {
+        let input = "{\"url\":\"https://a.example\"}\n\n{\"url\":\"https://b.example\",\"method\":\"PUT\",\"body\":\"x\",\"id\":7}\n";
+        let requests = parse_requests(input).expect("requests");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].0, 3);
+        assert_eq!(requests[1].1.method.as_deref(), Some("PUT"));
+
+        let err =
+            parse_requests("{\"url\":\"https://a.example\",\"metod\":\"GET\"}").expect_err("typo");
+        assert!(err.to_string().contains("line 1"));
+    }
+
+    #[test]
+    fn circuit_opens_after_consecutive_failures_per_host() {
+        let breaker = CircuitBreaker::new(2);
+        let host = host_key("https://api.example.com/a");
+        assert_eq!(host, "api.example.com:443");
+        breaker.record(&host, true);
+        breaker.record(&host, false);
+        breaker.record(&host, true);
+        assert!(!breaker.is_open(&host));
+        breaker.record(&host, true);
+        assert!(breaker.is_open(&host));
+        assert!(!breaker.is_open(&host_key("http://api.example.com/a")));
+    }
+
+    #[test]
+    fn results_omit_missing_fields() {
+        let result = BatchResult {
+            line: 2,
+            url: "https://a.example".to_string(),
+            status: Some(204),
+            time_ms: 5,
+            ..BatchResult::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&result).expect("json"),
+            r#"{"line":2,"url":"https://a.example","status":204,"time_ms":5}"#
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! `rurl cookies` subcommand
+//!
+//! Works with browser cookies and profiles without making an HTTP request.
+
+use crate::browser::export::{collect_cookies, format_cookies, ExportFormat};
+use crate::browser::BrowserCookieExtractor;
+use crate::config::BrowserCookieConfig;
+use crate::error::{Result, RurlError};
+use clap::{Arg, ArgMatches, Command};
+use std::io::Write;
+
+/// Build the `cookies` subcommand
+pub(crate) fn cookies_command() -> Command {
+    Command::new("cookies")
+        .about("Inspect and export browser cookies")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("export")
+                .about("Export browser cookies to a file")
+                .arg(
+                    Arg::new("cookies-from-browser")
+                        .long("cookies-from-browser")
+                        .value_name("BROWSER[+KEYRING][:PROFILE][::CONTAINER]")
+                        .help("Browser to read cookies from")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Write cookies to file ('-' or omitted writes to stdout)"),
+                )
+                .arg(
+                    Arg::new("domain")
+                        .long("domain")
+                        .value_name("DOMAIN")
+                        .help("Only export cookies for DOMAIN and its subdomains"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(["netscape", "json"])
+                        .default_value("netscape")
+                        .help("Output format"),
+                ),
+        )
+        .subcommand(
+            Command::new("list-profiles")
+                .about("List browser profiles and Firefox containers")
+                .arg(
+                    Arg::new("browser")
+                        .long("browser")
+                        .value_name("BROWSER")
+                        .help("Browser whose profiles to list")
+                        .required(true),
+                ),
+        )
+}
+
+/// Run the `cookies` subcommand
+pub(crate) async fn run_cookies(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("export", export)) => run_export(export).await,
+        Some(("list-profiles", list)) => run_list_profiles(list),
+        _ => Err(RurlError::Config("Unknown cookies subcommand".to_string())),
+    }
+}
+
+async fn run_export(matches: &ArgMatches) -> Result<()> {
+    let browser = matches
+        .get_one::<String>("cookies-from-browser")
+        .ok_or_else(|| RurlError::Config("--cookies-from-browser is required".to_string()))?;
+    let mut browser_config = BrowserCookieConfig::parse(browser)?;
+    let format: ExportFormat = matches
+        .get_one::<String>("format")
+        .map(String::as_str)
+        .unwrap_or("netscape")
+        .parse()?;
+    let domain = matches.get_one::<String>("domain").map(String::as_str);
+    browser_config.domain_filter = domain.map(str::to_string);
+
+    let store = BrowserCookieExtractor::new(browser_config)
+        .extract_cookies()
+        .await?;
+    let rendered = format_cookies(&collect_cookies(&store, domain), format)?;
+
+    match matches.get_one::<String>("output").map(String::as_str) {
+        Some(path) if path != "-" => std::fs::write(path, rendered)?,
+        _ => std::io::stdout().write_all(rendered.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn run_list_profiles(matches: &ArgMatches) -> Result<()> {
+    let browser = matches
+        .get_one::<String>("browser")
+        .ok_or_else(|| RurlError::Config("--browser is required".to_string()))?;
+    let browser_config = BrowserCookieConfig::parse(browser)?;
+    let profiles = BrowserCookieExtractor::new(browser_config).list_profiles()?;
+
+    let mut stdout = std::io::stdout();
+    for profile in profiles {
+        writeln!(
+            stdout,
+            "{}\t{}\t{}",
+            profile.id,
+            profile.name,
+            profile.path.display()
+        )?;
+        for container in profile.containers {
+            writeln!(stdout, "  ::{}", container)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,315 @@
+//! `rurl diff` subcommand
+//!
+//! Fetches two URLs, or one URL and a saved response, and prints a unified
+//! diff of their status lines, headers and bodies.
+
+use crate::config::{Config, HeaderList};
+use crate::error::{Result, RurlError};
+use crate::http::HttpClient;
+use crate::output::{decode_body_with_charset, http_version_label};
+use crate::utils::FileUtils;
+use clap::{Arg, ArgMatches, Command};
+use reqwest::header::CONTENT_TYPE;
+use serde_json::Value;
+use similar::TextDiff;
+use std::collections::HashSet;
+
+/// Build the `diff` subcommand
+pub(crate) fn diff_command() -> Command {
+    Command::new("diff")
+        .about("Fetch two URLs and print a unified diff of the responses")
+        .arg(
+            Arg::new("url1")
+                .value_name("URL1")
+                .help("First URL")
+                .required(true),
+        )
+        .arg(
+            Arg::new("url2")
+                .value_name("URL2")
+                .help("Second URL")
+                .required_unless_present("diff-against"),
+        )
+        .arg(
+            Arg::new("diff-against")
+                .long("diff-against")
+                .value_name("FILE")
+                .help("Compare URL1 with a response saved in FILE (body, or -i output)")
+                .conflicts_with("url2"),
+        )
+        .arg(
+            Arg::new("header")
+                .short('H')
+                .long("header")
+                .value_name("HEADER")
+                .help("Send HEADER with both requests (repeatable)")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Compare JSON bodies with sorted keys and uniform indentation")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("ignore-header")
+                .long("ignore-header")
+                .value_name("NAME")
+                .help("Leave header NAME out of the comparison (repeatable)")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("body-only")
+                .long("body-only")
+                .help("Compare bodies only")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+/// A response reduced to the text that is compared
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    /// Status line and headers, one per line; `None` for a saved body
+    head: Option<Vec<String>>,
+    body: String,
+}
+
+/// How responses are normalized before comparing
+struct Normalize {
+    json: bool,
+    ignore_headers: HashSet<String>,
+    body_only: bool,
+}
+
+/// Run the `diff` subcommand; differences exit with
+/// [`RurlError::ResponsesDiffer`]
+pub(crate) async fn run_diff(matches: &ArgMatches) -> Result<()> {
+    let mut headers = HeaderList::new();
+    for header in matches.get_many::<String>("header").into_iter().flatten() {
+        headers.add_arg(header)?;
+    }
+    let normalize = Normalize {
+        json: matches.get_flag("json"),
+        ignore_headers: matches
+            .get_many::<String>("ignore-header")
+            .into_iter()
+            .flatten()
+            .map(|name| name.to_ascii_lowercase())
+            .collect(),
+        body_only: matches.get_flag("body-only"),
+    };
+
+    let url1 = matches
+        .get_one::<String>("url1")
+        .ok_or_else(|| RurlError::Config("A URL is required".to_string()))?;
+    let (left, right, right_label) = match matches.get_one::<String>("diff-against") {
+        Some(file) => {
+            let path = FileUtils::expand_path(file)?;
+            FileUtils::check_file_readable(&path)?;
+            let saved = String::from_utf8_lossy(&std::fs::read(&path)?).into_owned();
+            (
+                fetch(url1, &headers).await?,
+                parse_saved(&saved),
+                file.clone(),
+            )
+        }
+        None => {
+            let url2 = matches
+                .get_one::<String>("url2")
+                .ok_or_else(|| RurlError::Config("A second URL is required".to_string()))?;
+            let (left, right) = tokio::try_join!(fetch(url1, &headers), fetch(url2, &headers))?;
+            (left, right, url2.clone())
+        }
+    };
+
+    let diff = unified_diff(&left, &right, &normalize, url1, &right_label);
+    if diff.is_empty() {
+        return Ok(());
+    }
+    print!("{}", diff);
+    Err(RurlError::ResponsesDiffer)
+}
+
+async fn fetch(url: &str, headers: &HeaderList) -> Result<Snapshot> {
+    let config = Config {
+        url: url.to_string(),
+        headers: headers.clone(),
+        ..Config::default()
+    };
+    let response = HttpClient::new(config)?.execute().await?;
+    let mut head = vec![format!(
+        "{} {}",
+        http_version_label(response.version()),
+        response.status()
+    )];
+    for (name, value) in response.headers() {
+        head.push(format!(
+            "{}: {}",
+            name,
+            value.to_str().unwrap_or("<non-utf8>")
+        ));
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let body = response.bytes().await.map_err(RurlError::transport)?;
+    Ok(Snapshot {
+        head: Some(head),
+        body: decode_body_with_charset(body.to_vec(), content_type.as_deref())?,
+    })
+}
+
+/// Read a saved response: `-i` output keeps its last header block, anything
+/// else is a bare body
+fn parse_saved(saved: &str) -> Snapshot {
+    let mut rest = saved;
+    let mut head = None;
+    while rest.starts_with("HTTP/") {
+        let Some((block, body)) = rest
+            .split_once("\r\n\r\n")
+            .filter(|(block, _)| !block.contains("\n\n"))
+            .or_else(|| rest.split_once("\n\n"))
+        else {
+            break;
+        };
+        head = Some(block.lines().map(|line| line.to_string()).collect());
+        rest = body;
+    }
+    Snapshot {
+        head,
+        body: rest.to_string(),
+    }
+}
+
+/// Unified diff of the normalized snapshots, empty when they match
+fn unified_diff(
+    left: &Snapshot,
+    right: &Snapshot,
+    normalize: &Normalize,
+    left_label: &str,
+    right_label: &str,
+) -> String {
+    // A bare saved body has no headers to compare against
+    let with_head = !normalize.body_only && left.head.is_some() && right.head.is_some();
+    let left = render(left, normalize, with_head);
+    let right = render(right, normalize, with_head);
+    if left == right {
+        return String::new();
+    }
+    TextDiff::from_lines(&left, &right)
+        .unified_diff()
+        .header(left_label, right_label)
+        .to_string()
+}
+
+fn render(snapshot: &Snapshot, normalize: &Normalize, with_head: bool) -> String {
+    let mut text = String::new();
+    if let (true, Some(head)) = (with_head, &snapshot.head) {
+        let mut lines = head.iter();
+        if let Some(status) = lines.next() {
+            text.push_str(status.trim_end());
+            text.push('\n');
+        }
+        // Header order is not significant; the value order of a repeated
+        // header is, and a stable sort keeps it
+        let mut headers: Vec<(String, &str)> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()))
+            .filter(|(name, _)| !normalize.ignore_headers.contains(name))
+            .collect();
+        headers.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, value) in headers {
+            text.push_str(&format!("{}: {}\n", name, value));
+        }
+        text.push('\n');
+    }
+    match canonical_json(&snapshot.body).filter(|_| normalize.json) {
+        Some(body) => text.push_str(&body),
+        None => text.push_str(&snapshot.body),
+    }
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+/// `body` pretty-printed with object keys sorted, if it is JSON
+fn canonical_json(body: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    serde_json::to_string_pretty(&sort_keys(value)).ok()
+}
+
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_saved, unified_diff, Normalize, Snapshot};
+    use std::collections::HashSet;
+
+    fn snapshot(head: &[&str], body: &str) -> Snapshot {
+        Snapshot {
+            head: Some(head.iter().map(|line| line.to_string()).collect()),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_ignores_header_order_key_order_and_ignored_headers() {
+        let normalize = Normalize {
+            json: true,
+            ignore_headers: HashSet::from(["date".to_string()]),
+            body_only: false,
+        };
+        let left = snapshot(
+            &[
+                "HTTP/1.1 200 OK",
+                "Date: Mon",
+                "X-A: 1",
+                "Content-Type: application/json",
+            ],
+            r#"{"b":1,"a":[{"d":2,"c":3}]}"#,
+        );
+        let right = snapshot(
+            &[
+                "HTTP/1.1 200 OK",
+                "content-type: application/json",
+                "x-a: 1",
+                "date: Tue",
+            ],
+            r#"{"a":[{"c":3,"d":2}],"b":1}"#,
+        );
+        assert_eq!(unified_diff(&left, &right, &normalize, "a", "b"), "");
+
+        let changed = snapshot(&["HTTP/1.1 500 Internal Server Error"], r#"{"a":[],"b":1}"#);
+        let diff = unified_diff(&left, &changed, &normalize, "staging", "prod");
+        assert!(diff.starts_with("--- staging\n+++ prod\n"));
+        assert!(diff.contains("\n-HTTP/1.1 200 OK\n"));
+        assert!(diff.contains("\n+HTTP/1.1 500 Internal Server Error\n"));
+        assert!(diff.contains("-x-a: 1\n"));
+    }
+
+    #[test]
+    fn saved_responses_keep_the_last_header_block() {
+        let saved =
+            parse_saved("HTTP/1.1 301 Moved\nLocation: /b\n\nHTTP/2 200 OK\r\nX-A: 1\r\n\r\nbody");
+        assert_eq!(saved, snapshot(&["HTTP/2 200 OK", "X-A: 1"], "body"));
+        assert_eq!(parse_saved("plain").head, None);
+    }
+}
@@ -0,0 +1,131 @@
+//! `--edit`: compose the request body in an editor
+//!
+//! The body starts from `-d` (`@FILE` reads the file) or a template for the
+//! `Content-Type` header, is written to a temporary file and opened with
+//! `$VISUAL`, `$EDITOR` or `vi`. Whatever is saved is sent; saving an empty
+//! file cancels the request.
+
+use crate::config::{Config, HttpMethod};
+use crate::error::{Result, RurlError};
+use crate::utils::FileUtils;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+/// Replace the body of `config` with the one edited by the user
+pub(crate) fn edit_request_body(config: &mut Config) -> Result<()> {
+    if config.upload.is_some() || !config.form.is_empty() {
+        return Err(RurlError::Config(
+            "--edit only works with -d bodies, not -T, -F or -d @-".to_string(),
+        ));
+    }
+    let content_type = config.headers.get("content-type").map(String::as_str);
+    let initial = initial_body(config.data.as_deref(), content_type)?;
+
+    let mut file = tempfile::Builder::new()
+        .prefix("rurl-body-")
+        .suffix(file_suffix(content_type))
+        .tempfile()?;
+    file.write_all(initial.as_bytes())?;
+    file.flush()?;
+    run_editor(file.path())?;
+
+    let mut body = std::fs::read_to_string(file.path())?;
+    if body.trim().is_empty() {
+        return Err(RurlError::Aborted);
+    }
+    // Editors end the last line; that newline is not part of the body
+    if body.ends_with('\n') && !initial.ends_with('\n') {
+        body.pop();
+    }
+    config.data = Some(body);
+    if !config.request_method_explicit {
+        config.method = HttpMethod::Post;
+    }
+    Ok(())
+}
+
+/// Text the editor opens with
+fn initial_body(data: Option<&str>, content_type: Option<&str>) -> Result<String> {
+    match data {
+        Some(data) => match data.strip_prefix('@') {
+            Some(path) => {
+                let path = FileUtils::expand_path(path)?;
+                FileUtils::check_file_readable(&path)?;
+                Ok(std::fs::read_to_string(path)?)
+            }
+            None => Ok(data.to_string()),
+        },
+        None if file_suffix(content_type) == ".json" => Ok("{\n}\n".to_string()),
+        None => Ok(String::new()),
+    }
+}
+
+/// Extension that lets the editor pick its syntax mode
+fn file_suffix(content_type: Option<&str>) -> &'static str {
+    let essence = content_type
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    if essence == "application/json" || essence.ends_with("+json") {
+        ".json"
+    } else if essence.ends_with("/xml") || essence.ends_with("+xml") {
+        ".xml"
+    } else {
+        ".txt"
+    }
+}
+
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|name| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        })
+        .unwrap_or_else(|| "vi".to_string());
+    // `code --wait` and the like carry their own arguments
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| RurlError::Config(format!("Cannot start editor {}: {}", program, e)))?;
+    if !status.success() {
+        return Err(RurlError::Config(format!(
+            "Editor {} exited with {}; nothing was sent",
+            program, status
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_suffix, initial_body};
+    use tempfile::tempdir;
+
+    #[test]
+    fn initial_body_reads_data_files_or_uses_a_template() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("payload.json");
+        std::fs::write(&path, "{\"a\":1}").expect("write");
+        let arg = format!("@{}", path.display());
+        assert_eq!(initial_body(Some(&arg), None).expect("body"), "{\"a\":1}");
+        assert_eq!(initial_body(Some("x=1"), None).expect("body"), "x=1");
+        assert_eq!(
+            initial_body(None, Some("application/json; charset=utf-8")).expect("body"),
+            "{\n}\n"
+        );
+        assert_eq!(initial_body(None, None).expect("body"), "");
+    }
+
+    #[test]
+    fn file_suffix_follows_content_type() {
+        assert_eq!(file_suffix(Some("application/problem+json")), ".json");
+        assert_eq!(file_suffix(Some("text/xml")), ".xml");
+        assert_eq!(file_suffix(None), ".txt");
+    }
+}
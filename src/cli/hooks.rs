@@ -0,0 +1,165 @@
+//! `--pre-hook` and `--post-hook`: commands run around the transfer
+//!
+//! Both run through the shell (`sh -c`, or `cmd /C` on Windows). The
+//! pre-hook sees the request in `RURL_METHOD`, `RURL_URL` and
+//! `RURL_BODY_FILE`; each `Name: value` line it prints is sent as a request
+//! header, so a signing helper can add its signature. The post-hook sees the
+//! response in `RURL_STATUS`, `RURL_URL_EFFECTIVE`, `RURL_HEADERS` and, when
+//! the body was saved to a file, `RURL_BODY_FILE`.
+
+use crate::config::{Config, UploadSource};
+use crate::error::{Result, RurlError};
+use crate::http::response::ResponseHistory;
+use crate::utils::FileUtils;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// What the post-hook is told about the response
+#[derive(Debug, Clone)]
+pub(crate) struct HookResponse {
+    status: u16,
+    url_effective: String,
+    headers: String,
+    body_file: Option<PathBuf>,
+}
+
+impl HookResponse {
+    pub(crate) fn new(history: &ResponseHistory) -> Self {
+        let headers = history
+            .response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                format!("{}: {}\n", name, String::from_utf8_lossy(value.as_bytes()))
+            })
+            .collect();
+        Self {
+            status: history.response.status().as_u16(),
+            url_effective: history.url_effective().to_string(),
+            headers,
+            body_file: None,
+        }
+    }
+
+    /// File the body was written to
+    pub(crate) fn with_body_file(mut self, path: Option<PathBuf>) -> Self {
+        self.body_file = path;
+        self
+    }
+}
+
+/// Run `command` before the request and add the headers it prints
+pub(crate) fn run_pre_hook(command: &str, config: &mut Config) -> Result<()> {
+    // Kept until the hook exits
+    let mut body_copy = None;
+    let body_file = match (&config.data, &config.upload) {
+        (Some(data), _) if data == "@-" => None,
+        (Some(data), _) => match data.strip_prefix('@') {
+            Some(path) => Some(FileUtils::expand_path(path)?),
+            None => {
+                let mut file = tempfile::Builder::new().prefix("rurl-body-").tempfile()?;
+                file.write_all(data.as_bytes())?;
+                file.flush()?;
+                let path = file.path().to_path_buf();
+                body_copy = Some(file);
+                Some(path)
+            }
+        },
+        (None, Some(UploadSource::File(path))) => Some(path.clone()),
+        _ => None,
+    };
+
+    let mut hook = shell(command);
+    hook.env("RURL_METHOD", config.method.to_string())
+        .env("RURL_URL", &config.url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped());
+    if let Some(path) = &body_file {
+        hook.env("RURL_BODY_FILE", path);
+    }
+    let output = hook
+        .output()
+        .map_err(|e| RurlError::Config(format!("Cannot start --pre-hook {}: {}", command, e)))?;
+    drop(body_copy);
+    if !output.status.success() {
+        return Err(RurlError::Config(format!(
+            "--pre-hook {} exited with {}; nothing was sent",
+            command, output.status
+        )));
+    }
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        config.headers.add_arg(line).map_err(|_| {
+            RurlError::Config(format!(
+                "--pre-hook {} printed '{}', not a 'Name: value' header",
+                command, line
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// Run `command` after the response; a failure is only a warning, since the
+/// transfer itself is done
+pub(crate) fn run_post_hook(command: &str, response: &HookResponse) {
+    let mut hook = shell(command);
+    hook.env("RURL_STATUS", response.status.to_string())
+        .env("RURL_URL_EFFECTIVE", &response.url_effective)
+        .env("RURL_HEADERS", &response.headers)
+        .stdin(Stdio::null());
+    match &response.body_file {
+        Some(path) => hook.env("RURL_BODY_FILE", path),
+        None => hook.env_remove("RURL_BODY_FILE"),
+    };
+    match hook.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => crate::warning!("--post-hook {} exited with {}", command, status),
+        Err(err) => crate::warning!("Cannot start --post-hook {}: {}", command, err),
+    }
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::run_pre_hook;
+    use crate::config::Config;
+
+    #[test]
+    fn pre_hook_headers_are_added_and_see_the_request() {
+        let mut config = Config {
+            url: "https://example.com/items".to_string(),
+            data: Some("{\"a\":1}".to_string()),
+            ..Config::default()
+        };
+        run_pre_hook(
+            "echo \"X-Signature: $RURL_METHOD $RURL_URL $(cat \"$RURL_BODY_FILE\")\"",
+            &mut config,
+        )
+        .expect("hook");
+        assert_eq!(
+            config.headers.get("x-signature").map(String::as_str),
+            Some("GET https://example.com/items {\"a\":1}")
+        );
+    }
+
+    #[test]
+    fn failing_or_garbled_pre_hooks_stop_the_request() {
+        let mut config = Config::default();
+        assert!(run_pre_hook("exit 3", &mut config).is_err());
+        assert!(run_pre_hook("echo not-a-header", &mut config).is_err());
+    }
+}
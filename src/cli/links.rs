@@ -0,0 +1,175 @@
+//! `--download-links PATTERN`: fetch the assets an HTML page links to
+//!
+//! Every link on the page whose absolute URL matches PATTERN is downloaded
+//! with the options of the page request, a few at a time like `rurl batch`,
+//! into the current directory under the link's file name. Links are not
+//! followed any deeper.
+
+use crate::config::{Config, HttpMethod, OutputConfig};
+use crate::error::{Result, RurlError};
+use crate::http::HttpClient;
+use crate::output::{resumable_part_len, OutputManager};
+use crate::utils::UrlUtils;
+use futures_util::StreamExt;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+/// Links downloaded at once, as `rurl batch --parallel` defaults to
+const LINKS_IN_FLIGHT: usize = 4;
+
+/// File name for a link without a last path segment
+pub(super) const INDEX_FILE_NAME: &str = "index.html";
+
+/// Download the `links` matching `pattern`; prints each saved file and,
+/// after trying them all, fails with the first error
+pub(crate) async fn download_links<F>(
+    page: &Config,
+    links: Vec<Url>,
+    pattern: &Regex,
+    build_client: F,
+    cancel: &CancellationToken,
+) -> Result<()>
+where
+    F: Fn(Config) -> Result<HttpClient>,
+{
+    let downloads = link_configs(
+        page,
+        links
+            .into_iter()
+            .filter(|link| pattern.is_match(link.as_str())),
+    );
+    if downloads.is_empty() {
+        crate::warning!("No link on the page matched --download-links {}", pattern);
+        return Ok(());
+    }
+    let build_client = &build_client;
+    let mut results = futures_util::stream::iter(downloads)
+        .map(|(config, path)| async move {
+            let url = config.url.clone();
+            let result = download(config, build_client, cancel).await;
+            (url, result.map(|()| path))
+        })
+        .buffered(LINKS_IN_FLIGHT);
+    let mut first_error = None;
+    while let Some((url, result)) = results.next().await {
+        match result {
+            Ok(path) => println!("{}", path.display()),
+            Err(RurlError::Aborted) => return Err(RurlError::Aborted),
+            Err(err) => {
+                crate::warning!("Cannot download {}: {}", url, err);
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// A GET of each link with the page's options, saved under the link's file
+/// name; a name taken by an earlier link gets a `.1`, `.2`, ... suffix
+fn link_configs(page: &Config, links: impl Iterator<Item = Url>) -> Vec<(Config, PathBuf)> {
+    let mut taken = HashSet::new();
+    links
+        .map(|link| {
+            let name =
+                UrlUtils::remote_file_name(&link).unwrap_or_else(|| INDEX_FILE_NAME.to_string());
+            let mut file_name = name.clone();
+            for suffix in 1.. {
+                if taken.insert(file_name.clone()) {
+                    break;
+                }
+                file_name = format!("{}.{}", name, suffix);
+            }
+            let path = PathBuf::from(file_name);
+            (link_config(page, &link, Some(path.clone())), path)
+        })
+        .collect()
+}
+
+/// A GET of `link` with the page's options, saved to `file`; without one,
+/// the caller names the file once the response is in
+pub(super) fn link_config(page: &Config, link: &Url, file: Option<PathBuf>) -> Config {
+    let output = OutputConfig {
+        file,
+        remove_on_error: page.output.remove_on_error,
+        remote_time: page.output.remote_time,
+        verbosity: page.output.verbosity,
+        silent: page.output.silent,
+        raw: page.output.raw,
+        speed_limit: page.output.speed_limit,
+        speed_time: page.output.speed_time,
+        memory_limit: page.output.memory_limit,
+        // Several meters at once would overwrite each other
+        show_progress: false,
+        ..Config::default().output
+    };
+    Config {
+        url: link.to_string(),
+        method: HttpMethod::Get,
+        request_method_explicit: false,
+        data: None,
+        upload: None,
+        upload_length: None,
+        upload_resume: false,
+        trailers: Vec::new(),
+        form: Vec::new(),
+        data_compress: None,
+        idempotency_key: None,
+        resume_from: resumable_part_len(&output),
+        output,
+        ..page.clone()
+    }
+}
+
+async fn download<F>(config: Config, build_client: &F, cancel: &CancellationToken) -> Result<()>
+where
+    F: Fn(Config) -> Result<HttpClient>,
+{
+    let output = config.output.clone();
+    let history = build_client(config)?
+        .execute_with_history_cancellable(cancel)
+        .await?;
+    let status = history.response.status();
+    if status.is_client_error() || status.is_server_error() {
+        return Err(RurlError::HttpStatus(status.as_u16()));
+    }
+    OutputManager::new(output)
+        .with_cancellation(cancel.clone())
+        .write_response(history.response, &history.chain)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::link_configs;
+    use crate::config::{Config, HttpMethod};
+    use url::Url;
+
+    #[test]
+    fn link_configs_get_each_link_into_its_own_file() {
+        let page = Config {
+            url: "https://example.com/".to_string(),
+            method: HttpMethod::Post,
+            data: Some("q=1".to_string()),
+            ..Config::default()
+        };
+        let links = [
+            "https://example.com/a/logo.png",
+            "https://example.com/b/logo.png",
+            "https://example.com/",
+        ]
+        .map(|link| Url::parse(link).expect("url"));
+        let configs = link_configs(&page, links.into_iter());
+        let names: Vec<_> = configs
+            .iter()
+            .map(|(_, path)| path.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["logo.png", "logo.png.1", "index.html"]);
+        let (config, _) = &configs[1];
+        assert_eq!(config.url, "https://example.com/b/logo.png");
+        assert_eq!(config.method, HttpMethod::Get);
+        assert!(config.data.is_none());
+    }
+}
@@ -0,0 +1,114 @@
+//! `rurl --manual`: a man page generated from [`create_app`]
+//!
+//! clap_mangen renders the synopsis, options and subcommands from the same
+//! definitions `--help` uses; the exit status section comes from
+//! [`EXIT_CODES`]. Packagers ship the output of `rurl --manual > rurl.1`.
+
+use super::create_app;
+use crate::error::Result;
+use crate::exit_code::EXIT_CODES;
+use std::io::Write;
+
+/// Command lines shown under EXAMPLES, each with what it does
+const EXAMPLES: &[(&str, &str)] = &[
+    (
+        "rurl https://example.com/",
+        "Print the page body to stdout.",
+    ),
+    (
+        "rurl --cookies-from-browser chrome https://example.com/account",
+        "Send the cookies Chrome holds for the site.",
+    ),
+    (
+        "rurl -d '{\"name\":\"x\"}' -H 'Content-Type: application/json' https://api.example.com/items",
+        "POST a JSON body.",
+    ),
+    (
+        "rurl -fLO https://example.com/file.tar.gz",
+        "Follow redirects and save the file under its remote name; exit 22 on HTTP errors.",
+    ),
+    (
+        "rurl --dry-run -u user:pass https://example.com/",
+        "Show the request that would be sent, with secrets hidden.",
+    ),
+    (
+        "rurl auth add https://api.example.com --token TOKEN",
+        "Store a bearer token for later requests to api.example.com.",
+    ),
+];
+
+/// Write the roff man page to `out`
+pub(crate) fn render_manual(out: &mut dyn Write) -> Result<()> {
+    let man = clap_mangen::Man::new(create_app());
+    man.render_title(out)?;
+    man.render_name_section(out)?;
+    man.render_synopsis_section(out)?;
+    man.render_description_section(out)?;
+    man.render_options_section(out)?;
+    man.render_subcommands_section(out)?;
+    out.write_all(exit_status_section().as_bytes())?;
+    out.write_all(examples_section().as_bytes())?;
+    man.render_version_section(out)?;
+    Ok(())
+}
+
+pub(crate) fn print_manual() -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    render_manual(&mut stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn exit_status_section() -> String {
+    let mut roff = String::from(".SH \"EXIT STATUS\"\n");
+    for (code, meaning) in EXIT_CODES {
+        roff.push_str(&format!(".TP\n\\fB{}\\fR\n{}\n", code, escape(meaning)));
+    }
+    roff
+}
+
+fn examples_section() -> String {
+    let mut roff = String::from(".SH EXAMPLES\n");
+    for (command, meaning) in EXAMPLES {
+        roff.push_str(&format!(
+            ".TP\n\\fB{}\\fR\n{}\n",
+            escape(command),
+            escape(meaning)
+        ));
+    }
+    roff
+}
+
+/// Text safe to put on a roff line
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with(['.', '\'']) {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, render_manual};
+
+    #[test]
+    fn manual_has_options_exit_codes_and_examples() {
+        let mut out = Vec::new();
+        render_manual(&mut out).expect("render");
+        let page = String::from_utf8(out).expect("utf-8");
+        assert!(page.contains(".TH rurl"));
+        assert!(page.contains(".SH OPTIONS"));
+        assert!(page.contains("\\-\\-dry\\-run"));
+        assert!(page.contains(".SH \"EXIT STATUS\"\n.TP\n\\fB0\\fR\nSuccess\n"));
+        assert!(page.contains(".TP\n\\fB22\\fR\n"));
+        assert!(page.contains(".SH EXAMPLES"));
+    }
+
+    #[test]
+    fn escape_protects_roff_syntax() {
+        assert_eq!(escape("-f a\\b"), "\\-f a\\eb");
+        assert_eq!(escape(".x"), "\\&.x");
+    }
+}
@@ -0,0 +1,84 @@
+//! `--open` and `--print-filename`: hand the saved body to the desktop
+//!
+//! Without `-o`, the body is saved to a file in the temporary directory named
+//! after its `Content-Type`, so the default application for that type (a PDF
+//! viewer, an image viewer, ...) is the one that opens it. The file is left
+//! behind for the viewer to read.
+
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// New file in the temporary directory with an extension for `content_type`
+pub(crate) fn temp_body_file(content_type: Option<&str>) -> Result<PathBuf> {
+    let suffix = extension_for(content_type)
+        .map(|extension| format!(".{}", extension))
+        .unwrap_or_default();
+    let (_, path) = tempfile::Builder::new()
+        .prefix("rurl-")
+        .suffix(&suffix)
+        .tempfile()?
+        .keep()
+        .map_err(|e| e.error)?;
+    Ok(path)
+}
+
+/// Usual extension for a media type, if there is one
+fn extension_for(content_type: Option<&str>) -> Option<&'static str> {
+    let essence = content_type?.split(';').next()?.trim().to_ascii_lowercase();
+    match essence.as_str() {
+        // mime_guess lists rarer extensions first for these
+        "text/plain" => Some("txt"),
+        "text/html" => Some("html"),
+        "image/jpeg" => Some("jpg"),
+        essence => mime_guess::get_mime_extensions_str(essence)?
+            .first()
+            .copied(),
+    }
+}
+
+/// Open `path` with the platform's default application; a failure is a
+/// warning, since the body is already saved
+pub(crate) fn open_with_default_app(path: &Path) {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty argument is the window title `start` expects first
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(path).stdin(Stdio::null()).stdout(Stdio::null());
+    match command.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => crate::warning!("Opening {} failed with {}", path.display(), status),
+        Err(err) => crate::warning!("Cannot open {}: {}", path.display(), err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extension_for, temp_body_file};
+
+    #[test]
+    fn extension_follows_content_type() {
+        assert_eq!(extension_for(Some("application/pdf")), Some("pdf"));
+        assert_eq!(extension_for(Some("image/PNG")), Some("png"));
+        assert_eq!(
+            extension_for(Some("text/plain; charset=utf-8")),
+            Some("txt")
+        );
+        assert_eq!(extension_for(Some("application/x-unknown-thing")), None);
+        assert_eq!(extension_for(None), None);
+    }
+
+    #[test]
+    fn temp_body_file_is_kept_with_its_extension() {
+        let path = temp_body_file(Some("application/json")).expect("temp file");
+        assert!(path.exists());
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some("json"));
+        std::fs::remove_file(path).expect("remove");
+    }
+}
@@ -1 +1,270 @@
 //! Application runner and execution logic
+//!
+//! `--recursive` mirrors a small site: starting from the request URL, pages
+//! are fetched breadth first with the options of the request and the links
+//! of every HTML page are followed up to `--depth` levels. Only links on the
+//! start host are followed, or with `--accept-regex` only the URLs matching
+//! it. Requests to one host are at least `--wait` apart. Each file is saved
+//! as `HOST/PATH` (or by the `-o` template) and listed in an NDJSON manifest.
+//! With `--cache`, a second run revalidates pages instead of fetching them
+//! again.
+
+use super::links::{self, INDEX_FILE_NAME};
+use crate::config::Config;
+use crate::error::{Result, RurlError};
+use crate::http::{markup, HttpClient};
+use crate::output::template::{self, TemplateValues};
+use crate::output::OutputManager;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+/// `--recursive` settings
+#[derive(Debug, Clone)]
+pub(crate) struct CrawlOptions {
+    /// Link levels followed from the start page
+    pub depth: usize,
+    /// URLs followed instead of the links on the start host
+    pub accept: Option<Regex>,
+    /// Least time between two requests to the same host
+    pub wait: Duration,
+    pub manifest: PathBuf,
+}
+
+/// One line of the manifest
+#[derive(Debug, Default, Serialize)]
+struct ManifestEntry {
+    url: String,
+    depth: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A fetched and saved page
+struct Saved {
+    file: PathBuf,
+    status: u16,
+    bytes: u64,
+    /// Final URL, after redirects
+    url: Url,
+    links: Vec<Url>,
+}
+
+/// Mirror the site `start` points at; prints each saved file and, after
+/// trying every page, fails with the first error
+pub(crate) async fn mirror<F>(
+    start: &Config,
+    options: &CrawlOptions,
+    build_client: F,
+    cancel: &CancellationToken,
+) -> Result<()>
+where
+    F: Fn(Config) -> Result<HttpClient>,
+{
+    if start.output.file.is_some() {
+        return Err(RurlError::Config(
+            "--recursive saves every page under its own name; -o must be a template".to_string(),
+        ));
+    }
+    let start_url = Url::parse(&start.url)
+        .map_err(|e| RurlError::InvalidUrl(format!("Invalid URL '{}': {}", start.url, e)))?;
+    let mut manifest = BufWriter::new(File::create(&options.manifest)?);
+    let mut queue = VecDeque::from([(start_url.clone(), 0)]);
+    let mut seen = HashSet::from([start_url.clone()]);
+    let mut last_request: HashMap<String, Instant> = HashMap::new();
+    let mut first_error = None;
+    while let Some((url, depth)) = queue.pop_front() {
+        let host = url.host_str().unwrap_or_default().to_string();
+        if let Some(last) = last_request.get(&host) {
+            let pause = options.wait.saturating_sub(last.elapsed());
+            tokio::select! {
+                _ = cancel.cancelled() => return Err(RurlError::Aborted),
+                _ = tokio::time::sleep(pause) => {}
+            }
+        }
+        let want_links = depth < options.depth;
+        let result = fetch(start, &url, want_links, &build_client, cancel).await;
+        last_request.insert(host, Instant::now());
+        let mut entry = ManifestEntry {
+            url: url.to_string(),
+            depth,
+            ..ManifestEntry::default()
+        };
+        match result {
+            Ok(saved) => {
+                println!("{}", saved.file.display());
+                seen.insert(saved.url);
+                for link in saved.links {
+                    let follow = options.accept.as_ref().map_or_else(
+                        || link.host_str() == start_url.host_str(),
+                        |accept| accept.is_match(link.as_str()),
+                    );
+                    if follow && seen.insert(link.clone()) {
+                        queue.push_back((link, depth + 1));
+                    }
+                }
+                entry.file = Some(saved.file);
+                entry.status = Some(saved.status);
+                entry.bytes = Some(saved.bytes);
+            }
+            Err(RurlError::Aborted) => return Err(RurlError::Aborted),
+            Err(err) => {
+                crate::warning!("Cannot download {}: {}", url, err);
+                entry.error = Some(err.to_string());
+                first_error.get_or_insert(err);
+            }
+        }
+        serde_json::to_writer(&mut manifest, &entry)?;
+        manifest.write_all(b"\n")?;
+        manifest.flush()?;
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// GET `url` with the options of `start` and save it; the links of an HTML
+/// page are read back from the saved file when `want_links`
+async fn fetch<F>(
+    start: &Config,
+    url: &Url,
+    want_links: bool,
+    build_client: &F,
+    cancel: &CancellationToken,
+) -> Result<Saved>
+where
+    F: Fn(Config) -> Result<HttpClient>,
+{
+    let name_template = start.output.file_template.as_deref();
+    let config = links::link_config(
+        start,
+        url,
+        name_template.is_none().then(|| mirror_path(url)),
+    );
+    let mut output = config.output.clone();
+    let history = build_client(config)?
+        .execute_with_history_cancellable(cancel)
+        .await?;
+    let status = history.response.status();
+    if status.is_client_error() || status.is_server_error() {
+        return Err(RurlError::HttpStatus(status.as_u16()));
+    }
+    let effective = history.url_effective().clone();
+    let file = match name_template {
+        Some(name) => template::render(
+            name,
+            &TemplateValues {
+                url: &effective,
+                status,
+                globs: &[],
+            },
+        )?,
+        None => mirror_path(url),
+    };
+    if let Some(dir) = file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    output.file = Some(file.clone());
+    let is_html = history
+        .response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("html"));
+    OutputManager::new(output)
+        .with_cancellation(cancel.clone())
+        .write_response(history.response, &history.chain)
+        .await?;
+    let links = if want_links && is_html {
+        markup::links(&String::from_utf8_lossy(&fs::read(&file)?), &effective)
+    } else {
+        Vec::new()
+    };
+    Ok(Saved {
+        bytes: fs::metadata(&file)?.len(),
+        file,
+        status: status.as_u16(),
+        url: effective,
+        links,
+    })
+}
+
+/// Where the mirror keeps `url`: `HOST[_PORT]/PATH`, with `index.html` for
+/// a directory and `@QUERY` after the name, as wget does on Windows
+fn mirror_path(url: &Url) -> PathBuf {
+    let mut host = url.host_str().unwrap_or_default().to_string();
+    if let Some(port) = url.port() {
+        host = format!("{}_{}", host, port);
+    }
+    let mut path = PathBuf::from(host);
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    let (name, dirs) = segments.split_last().unwrap_or((&"", &[]));
+    for dir in dirs {
+        path.push(safe_segment(dir));
+    }
+    let mut name = match *name {
+        "" => INDEX_FILE_NAME.to_string(),
+        name => safe_segment(name),
+    };
+    if let Some(query) = url.query() {
+        name = format!("{}@{}", name, query.replace('/', "%2F"));
+    }
+    path.push(name);
+    path
+}
+
+/// A path segment that cannot climb out of the mirror
+fn safe_segment(segment: &str) -> String {
+    match segment {
+        "" | "." | ".." => "_".to_string(),
+        segment => segment.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mirror_path;
+    use std::path::PathBuf;
+    use url::Url;
+
+    fn path(url: &str) -> PathBuf {
+        mirror_path(&Url::parse(url).expect("url"))
+    }
+
+    #[test]
+    fn mirror_path_follows_host_and_path() {
+        assert_eq!(
+            path("https://docs.example.com/guide/intro.html"),
+            PathBuf::from("docs.example.com/guide/intro.html")
+        );
+        assert_eq!(
+            path("http://127.0.0.1:8080/guide/"),
+            PathBuf::from("127.0.0.1_8080/guide/index.html")
+        );
+        assert_eq!(
+            path("https://example.com"),
+            PathBuf::from("example.com/index.html")
+        );
+        assert_eq!(
+            path("https://example.com/search?q=a/b"),
+            PathBuf::from("example.com/search@q=a%2Fb")
+        );
+        assert_eq!(
+            path("https://example.com/a//b.css"),
+            PathBuf::from("example.com/a/_/b.css")
+        );
+    }
+}
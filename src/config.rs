@@ -1,15 +1,17 @@
 //! Configuration management for rurl
 
-use std::collections::HashMap;
 use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
-use crate::error::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, RurlError};
+use crate::host_filter::HostFilter;
 
 /// Browser types supported for cookie extraction
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Browser {
     Chrome,
     Firefox,
@@ -19,6 +21,30 @@ pub enum Browser {
     Opera,
     Vivaldi,
     Whale,
+    Chromium,
+    Arc,
+    LibreWolf,
+    Waterfox,
+    TorBrowser,
+}
+
+impl Browser {
+    /// Names accepted by `--cookies-from-browser`, for error messages
+    pub const NAMES: &'static [&'static str] = &[
+        "chrome",
+        "chromium",
+        "arc",
+        "firefox",
+        "safari",
+        "edge",
+        "brave",
+        "opera",
+        "vivaldi",
+        "whale",
+        "librewolf",
+        "waterfox",
+        "tor",
+    ];
 }
 
 impl FromStr for Browser {
@@ -26,7 +52,9 @@ impl FromStr for Browser {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "chrome" | "chromium" => Ok(Browser::Chrome),
+            "chrome" => Ok(Browser::Chrome),
+            "chromium" => Ok(Browser::Chromium),
+            "arc" => Ok(Browser::Arc),
             "firefox" => Ok(Browser::Firefox),
             "safari" => Ok(Browser::Safari),
             "edge" => Ok(Browser::Edge),
@@ -34,21 +62,38 @@ impl FromStr for Browser {
             "opera" => Ok(Browser::Opera),
             "vivaldi" => Ok(Browser::Vivaldi),
             "whale" => Ok(Browser::Whale),
+            "librewolf" => Ok(Browser::LibreWolf),
+            "waterfox" => Ok(Browser::Waterfox),
+            "tor" | "tor-browser" | "torbrowser" => Ok(Browser::TorBrowser),
             _ => Err(()),
         }
     }
 }
 
 /// Browser cookie configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct BrowserCookieConfig {
     pub browser: Browser,
     pub profile: Option<String>,
     pub container: Option<String>,
     pub keyring: Option<String>,
+    /// Only read cookies whose host ends with this domain
+    pub domain_filter: Option<String>,
 }
 
 impl BrowserCookieConfig {
+    /// Cookies of the default profile of `browser`
+    pub fn new(browser: Browser) -> Self {
+        BrowserCookieConfig {
+            browser,
+            profile: None,
+            container: None,
+            keyring: None,
+            domain_filter: None,
+        }
+    }
+
     /// Parse from yt-dlp style format: BROWSER[+KEYRING][:PROFILE][::CONTAINER]
     pub fn parse(input: &str) -> Result<Self> {
         let mut parts = input.split("::");
@@ -64,7 +109,18 @@ impl BrowserCookieConfig {
         let keyring = browser_keyring_split.next().map(|s| s.to_string());
 
         let browser = browser_str.parse::<Browser>().map_err(|_| {
-            crate::error::RurlError::Config(format!("Unsupported browser: {}", browser_str))
+            let suggestion = crate::utils::StringUtils::closest_match(
+                browser_str,
+                Browser::NAMES.iter().copied(),
+            )
+            .map(|name| format!(" (did you mean '{}'?)", name))
+            .unwrap_or_default();
+            RurlError::Config(format!(
+                "Unsupported browser: {}{}; supported browsers: {}",
+                browser_str,
+                suggestion,
+                Browser::NAMES.join(", ")
+            ))
         })?;
 
         Ok(BrowserCookieConfig {
@@ -72,10 +128,289 @@ impl BrowserCookieConfig {
             profile,
             container,
             keyring,
+            domain_filter: None,
         })
     }
 }
 
+/// localStorage entry to send as a bearer token (`BROWSER:ORIGIN:KEY`)
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BrowserTokenConfig {
+    pub browser: BrowserCookieConfig,
+    /// Serialized origin, e.g. `https://app.example.com`
+    pub origin: String,
+    pub key: String,
+}
+
+impl BrowserTokenConfig {
+    /// Parse `BROWSER:ORIGIN:KEY`, e.g. `chrome:https://app.example.com:access_token`
+    pub fn parse(input: &str) -> Result<Self> {
+        let invalid = || {
+            RurlError::Config(format!(
+                "Invalid token source '{}', expected BROWSER:ORIGIN:KEY",
+                input
+            ))
+        };
+        let (browser, rest) = input.split_once(':').ok_or_else(invalid)?;
+        let (origin, key) = rest.rsplit_once(':').ok_or_else(invalid)?;
+        if key.is_empty() {
+            return Err(invalid());
+        }
+        let origin = url::Url::parse(origin)
+            .map(|url| url.origin())
+            .ok()
+            .filter(|origin| origin.is_tuple())
+            .ok_or_else(invalid)?
+            .ascii_serialization();
+
+        Ok(BrowserTokenConfig {
+            browser: BrowserCookieConfig::parse(browser)?,
+            origin,
+            key: key.to_string(),
+        })
+    }
+}
+
+/// Self-signed JWT sent as a bearer token (`--jwt-sign`)
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct JwtConfig {
+    /// PEM private key (RS256/ES256) or raw shared secret (HS256)
+    pub key_file: PathBuf,
+    /// Claims object; `iat` and `exp` are added unless present
+    pub claims: serde_json::Map<String, serde_json::Value>,
+    /// Explicit algorithm, otherwise inferred from the key
+    pub algorithm: Option<String>,
+    pub ttl: Duration,
+}
+
+impl JwtConfig {
+    /// HS256/RS256/ES256 token signed with `key_file`, valid for five minutes
+    pub fn new(key_file: impl Into<PathBuf>) -> Self {
+        JwtConfig {
+            key_file: key_file.into(),
+            claims: serde_json::Map::new(),
+            algorithm: None,
+            ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Request body streamed instead of held in memory (`-d @-`, `-T FILE`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadSource {
+    Stdin,
+    File(PathBuf),
+}
+
+/// Field of a multipart form (`-F`, `--form-string`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormField {
+    pub name: String,
+    pub value: FormValue,
+    /// Part `Content-Type` (`;type=`); files default to a guess from the extension
+    pub content_type: Option<String>,
+    /// File name sent for the part (`;filename=`) instead of the file's own
+    pub filename: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormValue {
+    Text(String),
+    /// File attached as a part and streamed from disk (`NAME=@FILE`)
+    File(PathBuf),
+}
+
+/// Trailer field sent after a chunked request body (`--trailer`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrailerSpec {
+    pub name: String,
+    pub value: TrailerValue,
+}
+
+/// Trailer value, either given literally or computed over the sent body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrailerValue {
+    Literal(String),
+    /// Lowercase hex SHA-256 of the body (`@sha256`)
+    Sha256,
+    /// Lowercase hex SHA-1 of the body (`@sha1`)
+    Sha1,
+}
+
+impl TrailerSpec {
+    /// Parse `NAME: VALUE`, where VALUE may be `@sha256` or `@sha1`
+    pub fn parse(input: &str) -> Result<Self> {
+        let (name, value) = crate::utils::StringUtils::parse_header(input)?;
+        let value = match value.as_str() {
+            "@sha256" => TrailerValue::Sha256,
+            "@sha1" => TrailerValue::Sha1,
+            _ if value.starts_with('@') => {
+                return Err(RurlError::Config(format!(
+                    "Unknown trailer value '{}', expected @sha256 or @sha1",
+                    value
+                )))
+            }
+            _ => TrailerValue::Literal(value),
+        };
+        Ok(TrailerSpec { name, value })
+    }
+}
+
+/// How `--cache` uses stored responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Serve fresh entries, revalidate stale ones
+    #[default]
+    Default,
+    /// Serve any stored entry without checking freshness
+    Force,
+    /// Never contact the server; answer 504 on a miss
+    OnlyIfCached,
+}
+
+impl FromStr for CacheMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(CacheMode::Default),
+            "force" => Ok(CacheMode::Force),
+            "only-if-cached" => Ok(CacheMode::OnlyIfCached),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Compression applied to the request body (`--data-compress`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyCompression {
+    Gzip,
+    Brotli,
+    Zstd,
+}
+
+impl BodyCompression {
+    /// `Content-Encoding` token announcing the compression
+    pub fn content_encoding(self) -> &'static str {
+        match self {
+            BodyCompression::Gzip => "gzip",
+            BodyCompression::Brotli => "br",
+            BodyCompression::Zstd => "zstd",
+        }
+    }
+}
+
+impl FromStr for BodyCompression {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "gzip" => Ok(BodyCompression::Gzip),
+            "br" | "brotli" => Ok(BodyCompression::Brotli),
+            "zstd" => Ok(BodyCompression::Zstd),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Amount of transfer detail written to stderr (`-v`, `-vv`, `-vvv`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum VerbosityLevel {
+    #[default]
+    Off,
+    /// Request and response headers, with connection, TLS and redirect notices
+    Headers,
+    /// Also DNS lookups and cookie matching decisions
+    Connection,
+    /// Also hexdumps of the response body
+    Body,
+}
+
+impl VerbosityLevel {
+    /// Level for `-v` given `count` times
+    pub fn from_count(count: u8) -> Self {
+        match count {
+            0 => VerbosityLevel::Off,
+            1 => VerbosityLevel::Headers,
+            2 => VerbosityLevel::Connection,
+            _ => VerbosityLevel::Body,
+        }
+    }
+}
+
+/// When terminal output goes through a pager (`--pager`, `--no-pager`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PagerMode {
+    /// When the output is taller than the terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whether the written body also goes to the clipboard (`--copy`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipboardCopy {
+    #[default]
+    Off,
+    /// Copy it and write it as usual
+    Also,
+    /// Copy it instead of writing it
+    Only,
+}
+
+/// Body lines kept by `--grep`
+#[derive(Debug, Clone)]
+pub struct GrepFilter {
+    pub pattern: regex::bytes::Regex,
+    /// Lines kept before and after each match (`--grep-context`)
+    pub context: usize,
+}
+
+/// `Idempotency-Key` sent with every retry of a transfer (`--idempotency-key`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyKey {
+    /// A new UUID per transfer
+    Auto,
+    Value(String),
+}
+
+/// Scheme assumed for URLs given without one (`--default-scheme`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefaultScheme {
+    /// `http://`, as curl does
+    #[default]
+    Http,
+    Https,
+    /// `https://`, retried over `http://` when the connection or TLS fails
+    HttpsFirst,
+}
+
+impl DefaultScheme {
+    /// Scheme tried first
+    pub fn scheme(self) -> &'static str {
+        match self {
+            DefaultScheme::Http => "http",
+            DefaultScheme::Https | DefaultScheme::HttpsFirst => "https",
+        }
+    }
+}
+
+impl FromStr for DefaultScheme {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "http" => Ok(DefaultScheme::Http),
+            "https" => Ok(DefaultScheme::Https),
+            "https-first" => Ok(DefaultScheme::HttpsFirst),
+            _ => Err(()),
+        }
+    }
+}
+
 /// HTTP method enumeration
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HttpMethod {
@@ -125,40 +460,272 @@ impl FromStr for HttpMethod {
 
 /// Proxy configuration
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ProxyConfig {
     pub url: String,
     pub username: Option<String>,
     pub password: Option<String>,
+    pub tunnel: bool,
+    /// Hosts that bypass the proxy (`NO_PROXY` for a proxy from the environment)
+    pub no_proxy: Option<String>,
+}
+
+impl ProxyConfig {
+    /// Proxy at `url`, without credentials
+    pub fn new(url: impl Into<String>) -> Self {
+        ProxyConfig {
+            url: url.into(),
+            username: None,
+            password: None,
+            tunnel: false,
+            no_proxy: None,
+        }
+    }
 }
 
 /// SSL/TLS configuration
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct SslConfig {
     pub verify_certs: bool,
     pub ca_cert_file: Option<PathBuf>,
     pub client_cert_file: Option<PathBuf>,
     pub client_key_file: Option<PathBuf>,
+    /// Token-held client key (`--cert pkcs11:...` with `--engine pkcs11`)
+    pub pkcs11_uri: Option<String>,
 }
 
+/// Default `--memory-limit`: 64 MiB
+pub const DEFAULT_MEMORY_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// Default `--max-header-size`: 300 KiB, curl's limit for one response
+pub const DEFAULT_MAX_HEADER_SIZE: u64 = 300 * 1024;
+
+/// Default `--max-headers`, the most the HTTP/1 parser accepts
+pub const DEFAULT_MAX_HEADERS: usize = 100;
+
+/// Largest `--max-header-size`: the HTTP/1 parser buffers a response head of
+/// about 400 KiB and fails larger ones itself
+pub const MAX_HEADER_SIZE_LIMIT: u64 = 400 * 1024;
+
 /// Output configuration
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct OutputConfig {
     pub file: Option<PathBuf>,
-    pub verbose: bool,
+    /// `-o` name with placeholders, resolved into `file` once the response
+    /// arrives
+    pub file_template: Option<String>,
+    /// More files that get a copy of the output (`--tee`, repeated `-o`)
+    pub tee: Vec<PathBuf>,
+    pub allow_binary_stdout: bool,
+    pub remove_on_error: bool,
+    pub no_clobber: bool,
+    pub remote_time: bool,
+    pub verbosity: VerbosityLevel,
     pub silent: bool,
     pub show_progress: bool,
     pub format_json: bool,
+    /// Indent XML and HTML bodies (`--pretty-xml`)
+    pub format_xml: bool,
     pub include_headers: bool,
+    /// Skip content decoding and charset conversion (`--raw`)
+    pub raw: bool,
+    pub speed_limit: Option<u64>,
+    pub speed_time: Duration,
+    /// Bodies buffered for formatting spill to a temporary file above this
+    /// many bytes (`--memory-limit`)
+    pub memory_limit: u64,
+    /// Re-encode decoded text into this charset before writing (`--output-charset`)
+    pub output_charset: Option<&'static encoding_rs::Encoding>,
+    /// Write the body as an offset/hex/ASCII dump (`--hexdump`)
+    pub hexdump: bool,
+    /// Page terminal output through `$PAGER`
+    pub pager: PagerMode,
+    /// Put the body on the system clipboard (`--copy`)
+    pub copy: ClipboardCopy,
+    /// Print each hop's status and redirect target to stderr
+    /// (`--show-redirect-chain`)
+    pub show_redirect_chain: bool,
+    /// Write only the body lines matching a pattern (`--grep`)
+    pub grep: Option<GrepFilter>,
+}
+
+/// Request headers in the order given; a name may repeat (`-H` twice)
+///
+/// Names are matched case-insensitively. A suppressed name (`-H 'Name:'`)
+/// is left out of the request even where rurl would add it itself, while an
+/// empty value (`-H 'Name;'`) is sent as is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderList {
+    entries: Vec<(String, String)>,
+    suppressed: Vec<String>,
+}
+
+impl HeaderList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a header given curl style: `Name: value`, `Name;` for an empty
+    /// value, or `Name:` to suppress it
+    pub fn add_arg(&mut self, arg: &str) -> Result<()> {
+        if let Some(name) = arg.trim_end().strip_suffix(';') {
+            if !name.contains(':') {
+                self.append(name.trim(), "");
+                return Ok(());
+            }
+        }
+        let (name, value) = crate::utils::StringUtils::parse_header(arg)?;
+        if value.is_empty() {
+            self.suppress(name);
+        } else {
+            self.append(name, value);
+        }
+        Ok(())
+    }
+
+    /// Add a header after the others, keeping earlier values of the same name
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        self.suppressed
+            .retain(|existing| !existing.eq_ignore_ascii_case(&name));
+        self.entries.push((name, value.into()));
+    }
+
+    /// Set the only value of `name`, in the position of its first occurrence
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        match self.position(&name) {
+            Some(index) => {
+                let mut position = 0;
+                self.entries.retain(|(existing, _)| {
+                    let keep = position <= index || !existing.eq_ignore_ascii_case(&name);
+                    position += 1;
+                    keep
+                });
+                self.entries[index] = (name, value.into());
+            }
+            None => self.append(name, value),
+        }
+    }
+
+    /// First value of `name`
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.get_all(name).next()
+    }
+
+    /// Every value of `name`, in order
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Drop every value of `name`
+    pub fn remove(&mut self, name: &str) {
+        self.entries
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+    }
+
+    /// Drop every value of `name` and keep it out of the request
+    pub fn suppress(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        self.remove(&name);
+        if !self.is_suppressed(&name) {
+            self.suppressed.push(name);
+        }
+    }
+
+    pub fn is_suppressed(&self, name: &str) -> bool {
+        self.suppressed
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(name))
+    }
+
+    /// Names to leave out of the request
+    pub fn suppressed(&self) -> &[String] {
+        &self.suppressed
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.suppressed.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (String, String)> {
+        self.entries.iter()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(existing, _)| existing.eq_ignore_ascii_case(name))
+    }
+}
+
+impl FromIterator<(String, String)> for HeaderList {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+            suppressed: Vec::new(),
+        }
+    }
+}
+
+impl IntoIterator for HeaderList {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a HeaderList {
+    type Item = &'a (String, String);
+    type IntoIter = std::slice::Iter<'a, (String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
 }
 
 /// Main configuration struct
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct Config {
     pub url: String,
     pub method: HttpMethod,
-    pub headers: HashMap<String, String>,
+    pub headers: HeaderList,
     pub data: Option<String>,
+    pub upload: Option<UploadSource>,
+    /// Content-Length to send for a streamed body (`--upload-length`)
+    pub upload_length: Option<u64>,
+    /// Continue a `-T FILE` upload from the offset the server reports
+    pub upload_resume: bool,
+    pub trailers: Vec<TrailerSpec>,
+    /// Multipart form fields; sent instead of `data` when not empty
+    pub form: Vec<FormField>,
+    /// Compress the request body and send it with `Content-Encoding`
+    pub data_compress: Option<BodyCompression>,
+    pub resume_from: Option<u64>,
     pub user_agent: Option<String>,
+    /// Retry over `http://` when a guessed `https://` URL fails to connect
+    pub http_fallback: bool,
     pub request_method_explicit: bool,
     pub follow_redirects: bool,
     pub location_trusted: bool,
@@ -166,16 +733,69 @@ pub struct Config {
     pub post301: bool,
     pub post302: bool,
     pub post303: bool,
+    /// Limit for each request attempt (`--timeout`)
     pub timeout: Duration,
     pub connect_timeout: Duration,
+    /// Longest wait for the next chunk of response data (`--read-timeout`)
+    pub read_timeout: Option<Duration>,
+    /// Longest time a streamed request body may go unsent (`--write-timeout`)
+    pub write_timeout: Option<Duration>,
+    /// Most header bytes accepted in one response (`--max-header-size`)
+    pub max_header_size: u64,
+    /// Most header fields accepted in one response (`--max-headers`)
+    pub max_headers: usize,
+    /// Send `Connection: close` and never reuse a connection (`--no-keepalive`)
+    pub no_keepalive: bool,
+    /// Budget for the whole transfer, across retries and redirects (`--max-time`)
+    pub max_time: Option<Duration>,
+    /// Source port range of outgoing connections (`--local-port`)
+    pub local_port: Option<(u16, u16)>,
+    /// Socket of a running `rurl --daemon` to send requests through
+    pub daemon_socket: Option<PathBuf>,
     pub retry_count: u32,
     pub retry_delay: Duration,
+    pub retry_max_delay: Option<Duration>,
+    /// Wait for `RateLimit-Reset`/`X-RateLimit-Reset` on a 429 without
+    /// Retry-After (`--retry-rate-limit`)
+    pub retry_rate_limit: bool,
+    /// Retry any failed transfer, such as an empty reply (`--retry-all-errors`)
+    pub retry_all_errors: bool,
+    /// Header that carries a generated UUID per transfer (`--request-id`)
+    pub request_id_header: Option<String>,
+    /// `Idempotency-Key` for the transfer, the same on every retry
+    pub idempotency_key: Option<IdempotencyKey>,
+    /// Hosts requests and redirects may go to (`--allow-host`, `--deny-host`)
+    pub host_filter: HostFilter,
     pub browser_cookies: Option<BrowserCookieConfig>,
+    /// Reuse encrypted extracted browser cookies for this long
+    pub cookie_cache: Option<Duration>,
+    pub cookie_store: Option<String>,
+    /// Named session (`--session`) holding headers, auth and cookies per host
+    pub session: Option<String>,
+    pub token_from_browser: Option<BrowserTokenConfig>,
+    pub jwt: Option<JwtConfig>,
+    /// Directory of the HTTP response cache, when enabled
+    pub cache_dir: Option<PathBuf>,
+    pub cache_mode: CacheMode,
     pub proxy: Option<ProxyConfig>,
+    pub proxy_pac: Option<String>,
+    /// SOCKS proxy used to reach `proxy` (`--preproxy`)
+    pub preproxy: Option<String>,
+    /// `[USER@]HOST` to forward all requests through over SSH (`--ssh-tunnel`)
+    pub ssh_tunnel: Option<String>,
     pub ssl: SslConfig,
     pub output: OutputConfig,
     pub auth_username: Option<String>,
     pub auth_password: Option<String>,
+    /// Send credentials saved with `rurl auth add` for matching origins
+    pub keyring_credentials: bool,
+    /// Fail with exit code 22 on HTTP 400 and above instead of writing the body
+    pub fail_on_http_error: bool,
+    /// Return a description of the first request as the response instead of
+    /// sending it (`--dry-run`)
+    pub dry_run: bool,
+    /// Leave credentials and cookie values unredacted in that description
+    pub show_secrets: bool,
 }
 
 impl Default for Config {
@@ -183,9 +803,17 @@ impl Default for Config {
         Config {
             url: String::new(),
             method: HttpMethod::Get,
-            headers: HashMap::new(),
+            headers: HeaderList::new(),
             data: None,
+            upload: None,
+            upload_length: None,
+            upload_resume: false,
+            trailers: Vec::new(),
+            form: Vec::new(),
+            data_compress: None,
+            resume_from: None,
             user_agent: Some(format!("rurl/{}", crate::VERSION)),
+            http_fallback: false,
             request_method_explicit: false,
             follow_redirects: false,
             location_trusted: false,
@@ -195,42 +823,402 @@ impl Default for Config {
             post303: false,
             timeout: Duration::from_secs(300),
             connect_timeout: Duration::from_secs(30),
+            read_timeout: None,
+            max_header_size: DEFAULT_MAX_HEADER_SIZE,
+            max_headers: DEFAULT_MAX_HEADERS,
+            no_keepalive: false,
+            write_timeout: None,
+            max_time: None,
+            local_port: None,
+            daemon_socket: None,
             retry_count: 0,
             retry_delay: Duration::from_secs(1),
+            retry_max_delay: None,
+            retry_rate_limit: false,
+            retry_all_errors: false,
+            request_id_header: None,
+            idempotency_key: None,
+            host_filter: HostFilter::default(),
             browser_cookies: None,
+            cookie_cache: None,
+            cookie_store: None,
+            session: None,
+            token_from_browser: None,
+            jwt: None,
+            cache_dir: None,
+            cache_mode: CacheMode::Default,
             proxy: None,
-            ssl: SslConfig {
-                verify_certs: true,
-                ca_cert_file: None,
-                client_cert_file: None,
-                client_key_file: None,
-            },
-            output: OutputConfig {
-                file: None,
-                verbose: false,
-                silent: false,
-                show_progress: true,
-                format_json: false,
-                include_headers: false,
-            },
+            proxy_pac: None,
+            preproxy: None,
+            ssh_tunnel: None,
+            ssl: SslConfig::default(),
+            output: OutputConfig::default(),
             auth_username: None,
             auth_password: None,
+            keyring_credentials: false,
+            fail_on_http_error: false,
+            dry_run: false,
+            show_secrets: false,
+        }
+    }
+}
+
+impl Default for SslConfig {
+    fn default() -> Self {
+        SslConfig {
+            verify_certs: true,
+            ca_cert_file: None,
+            client_cert_file: None,
+            client_key_file: None,
+            pkcs11_uri: None,
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig {
+            file: None,
+            file_template: None,
+            tee: Vec::new(),
+            allow_binary_stdout: false,
+            remove_on_error: false,
+            no_clobber: false,
+            remote_time: false,
+            verbosity: VerbosityLevel::Off,
+            silent: false,
+            show_progress: true,
+            format_json: false,
+            format_xml: false,
+            include_headers: false,
+            raw: false,
+            speed_limit: None,
+            speed_time: Duration::from_secs(30),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            output_charset: None,
+            hexdump: false,
+            pager: PagerMode::Auto,
+            copy: ClipboardCopy::Off,
+            show_redirect_chain: false,
+            grep: None,
         }
     }
 }
 
+impl OutputConfig {
+    /// Whether details of `level` are written (never with `-s`)
+    pub fn shows(&self, level: VerbosityLevel) -> bool {
+        !self.silent && self.verbosity >= level
+    }
+
+    /// Whether the body is rewritten before it is written, so it is read
+    /// whole first and never resumed
+    pub fn rewrites_body(&self) -> bool {
+        self.format_json
+            || self.format_xml
+            || self.output_charset.is_some()
+            || self.hexdump
+            || self.copy != ClipboardCopy::Off
+    }
+
+    /// Whether a download to `file` can be resumed from its `FILE.part`:
+    /// `--tee` copies and `--grep` output start over, and with `-i` the part
+    /// file starts with headers, not body bytes
+    pub fn resumable(&self) -> bool {
+        self.file.is_some()
+            && !self.rewrites_body()
+            && self.tee.is_empty()
+            && self.grep.is_none()
+            && !self.include_headers
+    }
+}
+
+impl Config {
+    /// Start building a configuration with default options
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// Options that are set but have no effect in this combination; empty
+    /// when the configuration is consistent
+    pub fn strict_conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        let has_body = self.data.is_some() || self.upload.is_some() || !self.form.is_empty();
+        if self.method == HttpMethod::Head && has_body {
+            conflicts.push("-d, -F and -T bodies are not sent with HEAD".to_string());
+        }
+        if self.cookie_cache.is_some() && self.browser_cookies.is_none() {
+            conflicts
+                .push("--cookie-cache has no effect without --cookies-from-browser".to_string());
+        }
+        // `-H 'Cookie:'` turns off the cookie engine: no stored cookie is sent
+        if self.headers.is_suppressed("cookie") {
+            if self.browser_cookies.is_some() {
+                conflicts
+                    .push("--cookies-from-browser has no effect with -H 'Cookie:'".to_string());
+            }
+            if self.cookie_store.is_some() {
+                conflicts.push(
+                    "--use-cookie-store only saves cookies, never sends them, with -H 'Cookie:'"
+                        .to_string(),
+                );
+            }
+        }
+        if self.ssl.client_key_file.is_some()
+            && self.ssl.client_cert_file.is_none()
+            && self.ssl.pkcs11_uri.is_none()
+        {
+            conflicts.push("--key has no effect without --cert".to_string());
+        }
+        if self.ssl.ca_cert_file.is_some() && !self.ssl.verify_certs {
+            conflicts.push("--cacert has no effect with --insecure".to_string());
+        }
+        if !self.follow_redirects {
+            for (flag, set) in [
+                ("post301", self.post301),
+                ("post302", self.post302),
+                ("post303", self.post303),
+            ] {
+                if set {
+                    conflicts.push(format!("--{} has no effect without --location", flag));
+                }
+            }
+        }
+        if self.output.show_redirect_chain && !self.follow_redirects {
+            conflicts.push("--show-redirect-chain has no effect without --location".to_string());
+        }
+        if self.retry_all_errors && self.retry_count == 0 {
+            conflicts.push("--retry-all-errors has no effect without --retry".to_string());
+        }
+        if self.output.file.is_none()
+            && self.output.file_template.is_none()
+            && self.output.tee.is_empty()
+        {
+            for (flag, set) in [
+                ("remote-time", self.output.remote_time),
+                ("remove-on-error", self.output.remove_on_error),
+                ("no-clobber", self.output.no_clobber),
+            ] {
+                if set {
+                    conflicts.push(format!(
+                        "--{} has no effect without --output FILE or --remote-name",
+                        flag
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// Typed builder for [`Config`]
+///
+/// Prefer this over struct literals: options added to `Config` later get
+/// their defaults here without breaking existing callers.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+    headers: Vec<(String, String)>,
+}
+
+impl ConfigBuilder {
+    /// Request URL; `http://` is assumed when no scheme is given
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.config.url = url.into();
+        self
+    }
+
+    pub fn method(mut self, method: HttpMethod) -> Self {
+        self.config.method = method;
+        self.config.request_method_explicit = true;
+        self
+    }
+
+    /// Add a request header; validated in [`ConfigBuilder::build`]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Leave header `name` out, even where rurl would add it (`-H 'Name:'`)
+    pub fn without_header(mut self, name: impl Into<String>) -> Self {
+        self.config.headers.suppress(name);
+        self
+    }
+
+    /// Request body; implies POST unless a method was set
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.config.data = Some(data.into());
+        self
+    }
+
+    /// Add a multipart form field; fields are sent as a POST form
+    pub fn form_field(mut self, field: FormField) -> Self {
+        self.config.form.push(field);
+        self
+    }
+
+    /// Compress the request body with `compression` while sending it
+    pub fn data_compress(mut self, compression: BodyCompression) -> Self {
+        self.config.data_compress = Some(compression);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = timeout;
+        self
+    }
+
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.config.read_timeout = Some(timeout);
+        self
+    }
+
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.config.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Give up after `budget` in total, including retries and redirects
+    pub fn max_time(mut self, budget: Duration) -> Self {
+        self.config.max_time = Some(budget);
+        self
+    }
+
+    /// Follow redirects, at most `max_redirects` when given
+    pub fn follow_redirects(mut self, max_redirects: Option<usize>) -> Self {
+        self.config.follow_redirects = true;
+        self.config.max_redirects = max_redirects;
+        self
+    }
+
+    pub fn retry(mut self, count: u32, delay: Duration) -> Self {
+        self.config.retry_count = count;
+        self.config.retry_delay = delay;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn basic_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.config.auth_username = Some(username.into());
+        self.config.auth_password = Some(password.into());
+        self
+    }
+
+    /// Send credentials saved with `rurl auth add` for matching origins
+    pub fn keyring_credentials(mut self, enabled: bool) -> Self {
+        self.config.keyring_credentials = enabled;
+        self
+    }
+
+    /// Treat HTTP 400 and above as [`RurlError::HttpStatus`] (`--fail`)
+    pub fn fail_on_http_error(mut self, enabled: bool) -> Self {
+        self.config.fail_on_http_error = enabled;
+        self
+    }
+
+    pub fn browser_cookies(mut self, browser_cookies: BrowserCookieConfig) -> Self {
+        self.config.browser_cookies = Some(browser_cookies);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Forward requests through `ssh -D` to `destination` (`[USER@]HOST`)
+    pub fn ssh_tunnel(mut self, destination: impl Into<String>) -> Self {
+        self.config.ssh_tunnel = Some(destination.into());
+        self
+    }
+
+    pub fn ssl(mut self, ssl: SslConfig) -> Self {
+        self.config.ssl = ssl;
+        self
+    }
+
+    pub fn output(mut self, output: OutputConfig) -> Self {
+        self.config.output = output;
+        self
+    }
+
+    /// Validate the URL and headers and produce the configuration
+    pub fn build(self) -> Result<Config> {
+        let mut config = self.config;
+        if config.url.trim().is_empty() {
+            return Err(RurlError::Config("No URL given".to_string()));
+        }
+        config.url = crate::utils::UrlUtils::validate_url(&config.url)?.to_string();
+
+        for (name, value) in self.headers {
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| RurlError::Config(format!("Invalid header name: {}", name)))?;
+            reqwest::header::HeaderValue::from_str(&value).map_err(|_| {
+                RurlError::Config(format!("Invalid value for header {}: {}", name, value))
+            })?;
+            config.headers.append(name, value);
+        }
+
+        if (config.data.is_some() || !config.form.is_empty()) && !config.request_method_explicit {
+            config.method = HttpMethod::Post;
+        }
+        if config.auth_username.is_some() != config.auth_password.is_some() {
+            return Err(RurlError::Config(
+                "Basic auth needs both username and password".to_string(),
+            ));
+        }
+        Ok(config)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Browser, BrowserCookieConfig, Config, HttpMethod};
+    use super::{
+        Browser, BrowserCookieConfig, BrowserTokenConfig, Config, HeaderList, HttpMethod,
+        TrailerSpec, TrailerValue,
+    };
+    use std::time::Duration;
 
     #[test]
     fn browser_from_str_accepts_known_values() {
         assert_eq!("chrome".parse::<Browser>().ok(), Some(Browser::Chrome));
-        assert_eq!("chromium".parse::<Browser>().ok(), Some(Browser::Chrome));
+        assert_eq!("chromium".parse::<Browser>().ok(), Some(Browser::Chromium));
+        assert_eq!("arc".parse::<Browser>().ok(), Some(Browser::Arc));
         assert_eq!("firefox".parse::<Browser>().ok(), Some(Browser::Firefox));
+        assert_eq!(
+            "LibreWolf".parse::<Browser>().ok(),
+            Some(Browser::LibreWolf)
+        );
+        assert_eq!(
+            "tor-browser".parse::<Browser>().ok(),
+            Some(Browser::TorBrowser)
+        );
         assert!("unknown".parse::<Browser>().is_err());
     }
 
+    #[test]
+    fn browser_token_config_parses_origin_with_port() {
+        let token = BrowserTokenConfig::parse("chrome:https://app.example.com:8443/x:access_token")
+            .expect("token");
+        assert_eq!(token.browser.browser, Browser::Chrome);
+        assert_eq!(token.origin, "https://app.example.com:8443");
+        assert_eq!(token.key, "access_token");
+
+        assert!(BrowserTokenConfig::parse("chrome:https://app.example.com").is_err());
+        assert!(BrowserTokenConfig::parse("chrome:app.example.com:token").is_err());
+        assert!(BrowserTokenConfig::parse("netscape:https://a.com:token").is_err());
+    }
+
     #[test]
     fn browser_cookie_config_parses_profile_container_keyring() {
         let config =
@@ -250,6 +1238,17 @@ mod tests {
         assert!(config.keyring.is_none());
     }
 
+    #[test]
+    fn browser_cookie_config_suggests_known_browsers() {
+        let err = BrowserCookieConfig::parse("chorme:Default").expect_err("typo");
+        let message = err.to_string();
+        assert!(message.contains("did you mean 'chrome'?"), "{}", message);
+        assert!(message.contains("supported browsers: chrome, chromium,"));
+        for name in Browser::NAMES {
+            assert!(name.parse::<Browser>().is_ok(), "{}", name);
+        }
+    }
+
     #[test]
     fn http_method_parse_and_display_roundtrip() {
         let method = "POST".parse::<HttpMethod>().expect("method");
@@ -264,4 +1263,82 @@ mod tests {
         assert!(!config.follow_redirects);
         assert!(config.output.show_progress);
     }
+
+    #[test]
+    fn trailer_spec_parses_computed_values() {
+        let spec = TrailerSpec::parse("X-Checksum: @sha256").expect("parse");
+        assert_eq!(spec.name, "X-Checksum");
+        assert_eq!(spec.value, TrailerValue::Sha256);
+        assert_eq!(
+            TrailerSpec::parse("X-Note: done").expect("parse").value,
+            TrailerValue::Literal("done".to_string())
+        );
+        assert!(TrailerSpec::parse("X-Sum: @md4").is_err());
+    }
+
+    #[test]
+    fn header_list_keeps_order_and_repeats() {
+        let mut headers = HeaderList::new();
+        headers.append("X-Forwarded-For", "10.0.0.1");
+        headers.append("Accept", "*/*");
+        headers.append("x-forwarded-for", "10.0.0.2");
+        let values: Vec<&str> = headers
+            .get_all("X-FORWARDED-FOR")
+            .map(String::as_str)
+            .collect();
+        assert_eq!(values, ["10.0.0.1", "10.0.0.2"]);
+
+        headers.insert("X-Forwarded-For", "10.0.0.3");
+        let names: Vec<&str> = headers.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["X-Forwarded-For", "Accept"]);
+        headers.remove("accept");
+        assert_eq!(headers.len(), 1);
+    }
+
+    #[test]
+    fn header_args_suppress_or_send_empty() {
+        let mut headers = HeaderList::new();
+        headers.add_arg("X-Empty;").expect("empty");
+        headers.add_arg("X-Trace: a").expect("value");
+        headers.add_arg("User-Agent:").expect("suppress");
+        headers.add_arg("x-trace:").expect("suppress");
+        assert_eq!(headers.get("x-empty").map(String::as_str), Some(""));
+        assert!(!headers.contains_key("X-Trace"));
+        assert!(headers.is_suppressed("user-agent"));
+        assert!(headers.is_suppressed("X-Trace"));
+
+        headers.add_arg("X-Trace: b").expect("value");
+        assert!(!headers.is_suppressed("X-Trace"));
+        assert!(headers.add_arg("missing").is_err());
+    }
+
+    #[test]
+    fn builder_sets_fields_and_validates() {
+        let config = Config::builder()
+            .url("example.com/api")
+            .header("X-Test", "1")
+            .data("a=1")
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("config");
+        assert_eq!(config.url, "http://example.com/api");
+        assert_eq!(config.headers.get("X-Test").map(String::as_str), Some("1"));
+        assert_eq!(config.method, HttpMethod::Post);
+        assert_eq!(config.timeout, Duration::from_secs(5));
+
+        let config = Config::builder()
+            .url("https://example.com")
+            .method(HttpMethod::Put)
+            .data("x")
+            .build()
+            .expect("config");
+        assert_eq!(config.method, HttpMethod::Put);
+
+        assert!(Config::builder().build().is_err());
+        assert!(Config::builder()
+            .url("https://example.com")
+            .header("Bad Name", "1")
+            .build()
+            .is_err());
+    }
 }
@@ -0,0 +1,91 @@
+//! User config file
+//!
+//! `~/.config/rurl/config` (in the platform config directory, or the file
+//! named by `RURL_CONFIG`) holds `key = value` lines supplying defaults for
+//! options not given on the command line. Blank lines and lines starting with
+//! `#` are ignored. Recognized keys: `default-scheme`.
+
+use crate::error::{Result, RurlError};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the config file location
+pub const CONFIG_ENV: &str = "RURL_CONFIG";
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    values: HashMap<String, String>,
+}
+
+impl ConfigFile {
+    /// Load the user config file; a missing file yields no settings
+    pub fn load() -> Result<Self> {
+        match config_file_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content).map_err(|err| match err {
+                RurlError::Config(detail) => {
+                    RurlError::Config(format!("{}: {}", path.display(), detail))
+                }
+                other => other,
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut values = HashMap::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                RurlError::Config(format!("line {}: expected 'key = value'", index + 1))
+            })?;
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+        Ok(Self { values })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+}
+
+fn config_file_path() -> Option<PathBuf> {
+    match std::env::var_os(CONFIG_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs::config_dir().map(|dir| dir.join("rurl").join("config")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConfigFile;
+
+    #[test]
+    fn parse_reads_keys_and_skips_comments() {
+        let file = ConfigFile::parse("# defaults\n\ndefault-scheme = https\n").expect("parse");
+        assert_eq!(file.get("default-scheme"), Some("https"));
+        assert_eq!(file.get("missing"), None);
+    }
+
+    #[test]
+    fn parse_rejects_lines_without_value() {
+        assert!(ConfigFile::parse("default-scheme").is_err());
+    }
+
+    #[test]
+    fn load_from_missing_file_is_empty() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let file = ConfigFile::load_from(&dir.path().join("config")).expect("load");
+        assert_eq!(file.get("default-scheme"), None);
+    }
+}
@@ -0,0 +1,124 @@
+//! Pluggable cookie sources
+//!
+//! Anything that can answer "which cookies go to this URL" implements
+//! [`CookieSource`]: extracted browser stores, Netscape cookie files, the
+//! persistent store, or a caller's own jar. `HttpClient::add_cookie_source`
+//! merges extra sources into every request.
+
+use crate::browser::{cookies_for_url, Cookie, CookieStore};
+use crate::error::{Result, RurlError};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+pub trait CookieSource: Send + Sync {
+    /// Cookies to send with a request to `url`
+    fn cookies_for_url(&self, url: &Url) -> Vec<Cookie>;
+}
+
+/// In-memory stores, including cookies extracted from a browser
+impl CookieSource for CookieStore {
+    fn cookies_for_url(&self, url: &Url) -> Vec<Cookie> {
+        cookies_for_url(self, url)
+    }
+}
+
+/// Several sources merged in order
+impl CookieSource for Vec<Box<dyn CookieSource>> {
+    fn cookies_for_url(&self, url: &Url) -> Vec<Cookie> {
+        self.iter()
+            .flat_map(|source| source.cookies_for_url(url))
+            .collect()
+    }
+}
+
+/// Cookies read from a Netscape/curl cookie jar file
+#[derive(Debug, Clone, Default)]
+pub struct NetscapeCookieFile {
+    store: CookieStore,
+}
+
+impl NetscapeCookieFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            RurlError::Config(format!("Failed to read cookie file {:?}: {}", path, e))
+        })?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parse jar contents, skipping malformed and expired lines
+    pub fn parse(content: &str) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or(0);
+        let mut store = CookieStore::new();
+        for line in content.lines() {
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(rest) => (rest, true),
+                None if line.starts_with('#') => continue,
+                None => (line, false),
+            };
+            let fields: Vec<&str> = line.trim_end_matches('\r').split('\t').collect();
+            let [domain, _include_subdomains, path, secure, expires, name, value] =
+                fields.as_slice()
+            else {
+                continue;
+            };
+            let expires = match expires.parse::<i64>() {
+                Ok(0) => None,
+                Ok(expires) if expires <= now => continue,
+                Ok(expires) => Some(expires),
+                Err(_) => continue,
+            };
+            let cookie = Cookie {
+                name: name.to_string(),
+                value: value.to_string(),
+                domain: domain.to_string(),
+                path: path.to_string(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                http_only,
+                expires,
+            };
+            store.entry(cookie.domain.clone()).or_default().push(cookie);
+        }
+        Self { store }
+    }
+}
+
+impl CookieSource for NetscapeCookieFile {
+    fn cookies_for_url(&self, url: &Url) -> Vec<Cookie> {
+        cookies_for_url(&self.store, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CookieSource, NetscapeCookieFile};
+    use url::Url;
+
+    #[test]
+    fn netscape_file_matches_domain_and_secure() {
+        let jar = NetscapeCookieFile::parse(
+            "# Netscape HTTP Cookie File\n\
+             .example.com\tTRUE\t/\tFALSE\t0\tsession\tabc\n\
+             #HttpOnly_example.com\tFALSE\t/\tTRUE\t4102444800\tsecret\txyz\n\
+             example.com\tFALSE\t/\tFALSE\t1\texpired\told\n\
+             malformed line\n",
+        );
+        let http = Url::parse("http://www.example.com/").expect("url");
+        let names: Vec<String> = jar
+            .cookies_for_url(&http)
+            .into_iter()
+            .map(|cookie| cookie.name)
+            .collect();
+        assert_eq!(names, ["session"]);
+
+        let https = Url::parse("https://example.com/").expect("url");
+        let cookies = jar.cookies_for_url(&https);
+        assert_eq!(cookies.len(), 2);
+        assert!(cookies
+            .iter()
+            .any(|cookie| cookie.name == "secret" && cookie.http_only));
+    }
+}
@@ -0,0 +1,349 @@
+//! Persistent cookie store
+//!
+//! Cookies received via `Set-Cookie` are saved to
+//! `~/.local/share/rurl/cookies.sqlite` (the platform data directory) when
+//! `--use-cookie-store [NAME]` is given, and matching cookies are sent on later
+//! invocations. Each NAME is an independent jar inside the same database,
+//! which only the current user can read.
+
+use crate::browser::{Cookie, CookieStore};
+use crate::cookie_source::CookieSource;
+use crate::error::{Result, RurlError};
+use crate::utils::StringUtils;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Jar name used when `--use-cookie-store` is given without a value
+pub const DEFAULT_STORE_NAME: &str = "default";
+
+/// SQLite-backed cookie jar shared between rurl invocations
+pub struct PersistentCookieStore {
+    conn: Mutex<Connection>,
+    name: String,
+}
+
+impl PersistentCookieStore {
+    /// Open (and create if needed) the named jar in the default location
+    pub fn open(name: &str) -> Result<Self> {
+        Self::open_at(&default_store_path()?, name)
+    }
+
+    /// Open the named jar in a specific database file
+    pub fn open_at(path: &Path, name: &str) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // SQLite gives its journal the permissions of the database file
+        let mut options = std::fs::OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(path)?;
+        let conn = Connection::open(path).map_err(|e| store_error("open", e))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cookies (
+                store TEXT NOT NULL,
+                domain TEXT NOT NULL,
+                path TEXT NOT NULL,
+                name TEXT NOT NULL,
+                value TEXT NOT NULL,
+                secure INTEGER NOT NULL,
+                http_only INTEGER NOT NULL,
+                expires INTEGER,
+                PRIMARY KEY (store, domain, path, name)
+            )",
+        )
+        .map_err(|e| store_error("initialize", e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            name: name.to_string(),
+        })
+    }
+
+    /// Load all unexpired cookies of this jar
+    pub fn load(&self) -> Result<CookieStore> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute(
+            "DELETE FROM cookies WHERE store = ?1 AND expires IS NOT NULL AND expires <= ?2",
+            params![self.name, unix_now()],
+        )
+        .map_err(|e| store_error("prune", e))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT domain, path, name, value, secure, http_only, expires
+                 FROM cookies WHERE store = ?1",
+            )
+            .map_err(|e| store_error("query", e))?;
+        let rows = stmt
+            .query_map(params![self.name], |row| {
+                Ok(Cookie {
+                    domain: row.get(0)?,
+                    path: row.get(1)?,
+                    name: row.get(2)?,
+                    value: row.get(3)?,
+                    secure: row.get::<_, i64>(4)? != 0,
+                    http_only: row.get::<_, i64>(5)? != 0,
+                    expires: row.get(6)?,
+                })
+            })
+            .map_err(|e| store_error("query", e))?;
+
+        let mut store = CookieStore::new();
+        for row in rows {
+            let cookie = row.map_err(|e| store_error("read", e))?;
+            store.entry(cookie.domain.clone()).or_default().push(cookie);
+        }
+        Ok(store)
+    }
+
+    /// Save a cookie, or delete it when it is already expired
+    pub fn save(&self, cookie: &Cookie) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        if cookie.expires.is_some_and(|expires| expires <= unix_now()) {
+            conn.execute(
+                "DELETE FROM cookies WHERE store = ?1 AND domain = ?2 AND path = ?3 AND name = ?4",
+                params![self.name, cookie.domain, cookie.path, cookie.name],
+            )
+            .map_err(|e| store_error("delete", e))?;
+            return Ok(());
+        }
+        conn.execute(
+            "INSERT OR REPLACE INTO cookies
+                 (store, domain, path, name, value, secure, http_only, expires)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                self.name,
+                cookie.domain,
+                cookie.path,
+                cookie.name,
+                cookie.value,
+                cookie.secure as i64,
+                cookie.http_only as i64,
+                cookie.expires,
+            ],
+        )
+        .map_err(|e| store_error("save", e))?;
+        Ok(())
+    }
+}
+
+/// Reads the jar on every lookup, so cookies saved meanwhile are included
+impl CookieSource for PersistentCookieStore {
+    fn cookies_for_url(&self, url: &Url) -> Vec<Cookie> {
+        match self.load() {
+            Ok(store) => store.cookies_for_url(url),
+            Err(err) => {
+                log::warn!("Failed to read cookie store '{}': {}", self.name, err);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Replace any cookie with the same domain/path/name in an in-memory store
+pub fn upsert_cookie(store: &mut CookieStore, cookie: Cookie) {
+    let cookies = store.entry(cookie.domain.clone()).or_default();
+    cookies.retain(|existing| existing.name != cookie.name || existing.path != cookie.path);
+    if !cookie.expires.is_some_and(|expires| expires <= unix_now()) {
+        cookies.push(cookie);
+    }
+}
+
+/// Parse a `Set-Cookie` header received from `request_url`
+pub fn parse_set_cookie(header: &str, request_url: &Url) -> Option<Cookie> {
+    let host = request_url.host_str()?.to_lowercase();
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = Cookie {
+        name: name.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        domain: host.clone(),
+        path: default_cookie_path(request_url.path()),
+        secure: false,
+        http_only: false,
+        expires: None,
+    };
+    let mut max_age = None;
+
+    for attribute in parts {
+        let (key, value) = match attribute.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (attribute.trim(), ""),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "domain" => {
+                let domain = value.trim_start_matches('.').to_lowercase();
+                if domain.is_empty() {
+                    continue;
+                }
+                // Reject cookies for domains the response host does not belong to
+                if host != domain && !host.ends_with(&format!(".{}", domain)) {
+                    return None;
+                }
+                // A public suffix only names the host itself (RFC 6265 section 5.3)
+                if psl::suffix_str(&domain) == Some(domain.as_str()) {
+                    if host != domain {
+                        return None;
+                    }
+                    continue;
+                }
+                cookie.domain = format!(".{}", domain);
+            }
+            "path" if value.starts_with('/') => cookie.path = value.to_string(),
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            "expires" => {
+                if let Some(expires) = StringUtils::parse_http_date(value) {
+                    cookie.expires = Some(system_time_to_unix(expires));
+                }
+            }
+            "max-age" => max_age = value.parse::<i64>().ok(),
+            _ => {}
+        }
+    }
+
+    // Max-Age takes precedence over Expires (RFC 6265 section 5.3)
+    if let Some(max_age) = max_age {
+        cookie.expires = Some(if max_age <= 0 {
+            0
+        } else {
+            unix_now().saturating_add(max_age)
+        });
+    }
+    Some(cookie)
+}
+
+fn default_cookie_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
+
+fn default_store_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| RurlError::Config("Cannot determine data directory".to_string()))?;
+    Ok(data_dir.join("rurl").join("cookies.sqlite"))
+}
+
+fn store_error(action: &str, err: rusqlite::Error) -> RurlError {
+    RurlError::Config(format!("Cookie store failed to {}: {}", action, err))
+}
+
+fn system_time_to_unix(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn unix_now() -> i64 {
+    system_time_to_unix(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_set_cookie, upsert_cookie, PersistentCookieStore};
+    use crate::browser::CookieStore;
+    use tempfile::tempdir;
+    use url::Url;
+
+    #[test]
+    fn parse_set_cookie_reads_attributes() {
+        let url = Url::parse("https://app.example.com/account/settings").expect("url");
+        let cookie = parse_set_cookie(
+            "sid=abc; Domain=example.com; Path=/; Secure; HttpOnly; Max-Age=3600",
+            &url,
+        )
+        .expect("cookie");
+        assert_eq!(cookie.name, "sid");
+        assert_eq!(cookie.value, "abc");
+        assert_eq!(cookie.domain, ".example.com");
+        assert_eq!(cookie.path, "/");
+        assert!(cookie.secure && cookie.http_only);
+        assert!(cookie.expires.is_some());
+
+        let host_only = parse_set_cookie("a=1", &url).expect("cookie");
+        assert_eq!(host_only.domain, "app.example.com");
+        assert_eq!(host_only.path, "/account");
+    }
+
+    #[test]
+    fn parse_set_cookie_rejects_foreign_domain() {
+        let url = Url::parse("https://example.com/").expect("url");
+        assert!(parse_set_cookie("a=1; Domain=other.com", &url).is_none());
+        assert!(parse_set_cookie("=novalue", &url).is_none());
+    }
+
+    #[test]
+    fn parse_set_cookie_rejects_public_suffix_domain() {
+        let url = Url::parse("https://shop.example.co.uk/").expect("url");
+        assert!(parse_set_cookie("a=1; Domain=co.uk", &url).is_none());
+        assert!(parse_set_cookie("a=1; Domain=.uk", &url).is_none());
+        let cookie = parse_set_cookie("a=1; Domain=example.co.uk", &url).expect("cookie");
+        assert_eq!(cookie.domain, ".example.co.uk");
+
+        let url = Url::parse("https://co.uk/").expect("url");
+        let host_only = parse_set_cookie("a=1; Domain=co.uk", &url).expect("cookie");
+        assert_eq!(host_only.domain, "co.uk");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn persistent_store_is_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("cookies.sqlite");
+        PersistentCookieStore::open_at(&path, "work").expect("open");
+        let mode = std::fs::metadata(&path)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn persistent_store_round_trips_and_deletes() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("cookies.sqlite");
+        let url = Url::parse("https://example.com/").expect("url");
+
+        let store = PersistentCookieStore::open_at(&path, "work").expect("open");
+        store
+            .save(&parse_set_cookie("a=1", &url).expect("cookie"))
+            .expect("save");
+        let other = PersistentCookieStore::open_at(&path, "personal").expect("open");
+        assert!(other.load().expect("load").is_empty());
+
+        let reopened = PersistentCookieStore::open_at(&path, "work").expect("open");
+        let loaded = reopened.load().expect("load");
+        assert_eq!(loaded["example.com"][0].value, "1");
+
+        reopened
+            .save(&parse_set_cookie("a=gone; Max-Age=0", &url).expect("cookie"))
+            .expect("delete");
+        assert!(reopened.load().expect("load").is_empty());
+    }
+
+    #[test]
+    fn upsert_cookie_replaces_same_name_and_path() {
+        let url = Url::parse("https://example.com/").expect("url");
+        let mut store = CookieStore::new();
+        upsert_cookie(&mut store, parse_set_cookie("a=1", &url).expect("cookie"));
+        upsert_cookie(&mut store, parse_set_cookie("a=2", &url).expect("cookie"));
+        assert_eq!(store["example.com"].len(), 1);
+        assert_eq!(store["example.com"][0].value, "2");
+    }
+}
@@ -0,0 +1,415 @@
+//! OS keyring-backed credentials
+//!
+//! `rurl auth add URL` stores a username/password or bearer token for the
+//! origin of URL in the platform keyring: the macOS Keychain, the Secret
+//! Service on Linux or the Windows Credential Manager. The origins are also
+//! listed in `credentials.json` in the platform data directory, so requests to
+//! other hosts never touch the keyring. A stored credential is sent when the
+//! command line gives no `-u`, bearer token or `Authorization` header.
+
+use crate::error::{Result, RurlError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Keyring service name all rurl credentials are stored under
+const KEYRING_SERVICE: &str = "rurl";
+
+/// Secret stored for one origin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StoredCredential {
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    /// Tokens from `rurl auth oauth2`, refreshed at `token_url` when expired
+    #[serde(rename = "oauth2")]
+    OAuth2 {
+        token_url: String,
+        client_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_secret: Option<String>,
+        access_token: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        refresh_token: Option<String>,
+        /// Unix time the access token expires at
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<u64>,
+    },
+}
+
+impl StoredCredential {
+    /// Add the matching `Authorization` header to `request`
+    pub fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self {
+            StoredCredential::Basic { username, password } => {
+                request.basic_auth(username, Some(password))
+            }
+            StoredCredential::Bearer { token } => request.bearer_auth(token),
+            StoredCredential::OAuth2 { access_token, .. } => request.bearer_auth(access_token),
+        }
+    }
+}
+
+/// `scheme://host[:port]` key credentials are stored under
+pub fn credential_origin(url: &Url) -> Result<String> {
+    match url.origin() {
+        origin @ url::Origin::Tuple(..) => Ok(origin.ascii_serialization()),
+        url::Origin::Opaque(_) => Err(RurlError::InvalidUrl(format!(
+            "Cannot store credentials for {}",
+            url
+        ))),
+    }
+}
+
+/// Credentials in the OS keyring, indexed by origin
+pub struct CredentialStore {
+    index_path: PathBuf,
+}
+
+impl CredentialStore {
+    /// Use the index in the default location
+    pub fn open() -> Result<Self> {
+        Ok(Self::open_at(&default_index_path()?))
+    }
+
+    /// Use a specific index file; secrets still go to the OS keyring
+    pub fn open_at(index_path: &Path) -> Self {
+        Self {
+            index_path: index_path.to_path_buf(),
+        }
+    }
+
+    /// Store `credential` for the origin of `url`, replacing an existing one
+    pub fn add(&self, url: &Url, credential: &StoredCredential) -> Result<()> {
+        let origin = credential_origin(url)?;
+        keyring::set(&origin, &serde_json::to_string(credential)?)?;
+        let mut origins = self.origins()?;
+        if !origins.contains(&origin) {
+            origins.push(origin);
+            origins.sort();
+            self.write_index(&origins)?;
+        }
+        Ok(())
+    }
+
+    /// Delete the credential for the origin of `url`; `false` if none was stored
+    pub fn remove(&self, url: &Url) -> Result<bool> {
+        let origin = credential_origin(url)?;
+        let mut origins = self.origins()?;
+        let known = origins.contains(&origin);
+        keyring::delete(&origin)?;
+        if known {
+            origins.retain(|stored| stored != &origin);
+            self.write_index(&origins)?;
+        }
+        Ok(known)
+    }
+
+    /// Origins with stored credentials
+    pub fn origins(&self) -> Result<Vec<String>> {
+        match std::fs::read_to_string(&self.index_path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Credential for the origin of `url`; the keyring is only queried for
+    /// origins in the index
+    pub fn lookup(&self, url: &Url) -> Result<Option<StoredCredential>> {
+        let Ok(origin) = credential_origin(url) else {
+            return Ok(None);
+        };
+        if !self.origins()?.contains(&origin) {
+            return Ok(None);
+        }
+        match keyring::get(&origin)? {
+            Some(secret) => Ok(Some(serde_json::from_str(&secret)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn write_index(&self, origins: &[String]) -> Result<()> {
+        if let Some(parent) = self.index_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.index_path, serde_json::to_string_pretty(origins)?)?;
+        Ok(())
+    }
+}
+
+fn default_index_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| RurlError::Config("Cannot determine data directory".to_string()))?;
+    Ok(data_dir.join("rurl").join("credentials.json"))
+}
+
+fn keyring_error(action: &str, err: impl std::fmt::Display) -> RurlError {
+    RurlError::Auth(format!("Keyring failed to {}: {}", action, err))
+}
+
+#[cfg(target_os = "macos")]
+mod keyring {
+    use super::{keyring_error, KEYRING_SERVICE};
+    use crate::error::Result;
+    use security_framework::passwords::{
+        delete_generic_password, get_generic_password, set_generic_password,
+    };
+
+    const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+    pub(super) fn set(account: &str, secret: &str) -> Result<()> {
+        set_generic_password(KEYRING_SERVICE, account, secret.as_bytes())
+            .map_err(|e| keyring_error("store credential", e))
+    }
+
+    pub(super) fn get(account: &str) -> Result<Option<String>> {
+        match get_generic_password(KEYRING_SERVICE, account) {
+            Ok(secret) => Ok(Some(String::from_utf8_lossy(&secret).into_owned())),
+            Err(err) if err.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(None),
+            Err(err) => Err(keyring_error("read credential", err)),
+        }
+    }
+
+    pub(super) fn delete(account: &str) -> Result<()> {
+        match delete_generic_password(KEYRING_SERVICE, account) {
+            Err(err) if err.code() != ERR_SEC_ITEM_NOT_FOUND => {
+                Err(keyring_error("delete credential", err))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod keyring {
+    use super::{keyring_error, KEYRING_SERVICE};
+    use crate::error::Result;
+    use secret_service::blocking::SecretService;
+    use secret_service::EncryptionType;
+    use std::collections::HashMap;
+
+    fn attributes(account: &str) -> HashMap<&str, &str> {
+        HashMap::from([("service", KEYRING_SERVICE), ("account", account)])
+    }
+
+    fn connect() -> Result<SecretService<'static>> {
+        SecretService::connect(EncryptionType::Dh)
+            .map_err(|e| keyring_error("connect to the secret service", e))
+    }
+
+    pub(super) fn set(account: &str, secret: &str) -> Result<()> {
+        let service = connect()?;
+        let collection = service
+            .get_default_collection()
+            .map_err(|e| keyring_error("open the default collection", e))?;
+        collection
+            .unlock()
+            .map_err(|e| keyring_error("unlock the default collection", e))?;
+        collection
+            .create_item(
+                &format!("rurl credentials for {}", account),
+                attributes(account),
+                secret.as_bytes(),
+                true,
+                "application/json",
+            )
+            .map_err(|e| keyring_error("store credential", e))?;
+        Ok(())
+    }
+
+    pub(super) fn get(account: &str) -> Result<Option<String>> {
+        let service = connect()?;
+        let found = service
+            .search_items(attributes(account))
+            .map_err(|e| keyring_error("search credentials", e))?;
+        let Some(item) = found.unlocked.into_iter().chain(found.locked).next() else {
+            return Ok(None);
+        };
+        item.unlock()
+            .map_err(|e| keyring_error("unlock credential", e))?;
+        let secret = item
+            .get_secret()
+            .map_err(|e| keyring_error("read credential", e))?;
+        Ok(Some(String::from_utf8_lossy(&secret).into_owned()))
+    }
+
+    pub(super) fn delete(account: &str) -> Result<()> {
+        let service = connect()?;
+        let found = service
+            .search_items(attributes(account))
+            .map_err(|e| keyring_error("search credentials", e))?;
+        for item in found.unlocked.into_iter().chain(found.locked) {
+            item.delete()
+                .map_err(|e| keyring_error("delete credential", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod keyring {
+    use super::{keyring_error, KEYRING_SERVICE};
+    use crate::error::Result;
+    use std::io;
+    use std::ptr;
+    use winapi::shared::winerror::ERROR_NOT_FOUND;
+    use winapi::um::wincred::{
+        CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE,
+        CRED_TYPE_GENERIC, PCREDENTIALW,
+    };
+
+    fn target(account: &str) -> Vec<u16> {
+        format!("{}:{}", KEYRING_SERVICE, account)
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect()
+    }
+
+    fn not_found(err: &io::Error) -> bool {
+        err.raw_os_error() == Some(ERROR_NOT_FOUND as i32)
+    }
+
+    pub(super) fn set(account: &str, secret: &str) -> Result<()> {
+        let mut target = target(account);
+        let mut blob = secret.as_bytes().to_vec();
+        // SAFETY: CREDENTIALW is a plain C struct; the pointers stay valid for the call
+        let written = unsafe {
+            let mut credential: CREDENTIALW = std::mem::zeroed();
+            credential.Type = CRED_TYPE_GENERIC;
+            credential.TargetName = target.as_mut_ptr();
+            credential.CredentialBlobSize = blob.len() as u32;
+            credential.CredentialBlob = blob.as_mut_ptr();
+            credential.Persist = CRED_PERSIST_LOCAL_MACHINE;
+            CredWriteW(&mut credential, 0)
+        };
+        if written == 0 {
+            return Err(keyring_error(
+                "store credential",
+                io::Error::last_os_error(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub(super) fn get(account: &str) -> Result<Option<String>> {
+        let target = target(account);
+        let mut credential: PCREDENTIALW = ptr::null_mut();
+        // SAFETY: CredReadW allocates `credential`, which is released with CredFree
+        unsafe {
+            if CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) == 0 {
+                let err = io::Error::last_os_error();
+                if not_found(&err) {
+                    return Ok(None);
+                }
+                return Err(keyring_error("read credential", err));
+            }
+            let blob = std::slice::from_raw_parts(
+                (*credential).CredentialBlob,
+                (*credential).CredentialBlobSize as usize,
+            );
+            let secret = String::from_utf8_lossy(blob).into_owned();
+            CredFree(credential.cast());
+            Ok(Some(secret))
+        }
+    }
+
+    pub(super) fn delete(account: &str) -> Result<()> {
+        let target = target(account);
+        // SAFETY: `target` is a NUL-terminated UTF-16 string
+        if unsafe { CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) } == 0 {
+            let err = io::Error::last_os_error();
+            if !not_found(&err) {
+                return Err(keyring_error("delete credential", err));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+mod keyring {
+    use crate::error::{Result, RurlError};
+
+    fn unsupported() -> RurlError {
+        RurlError::Unsupported("No OS keyring is available on this platform".to_string())
+    }
+
+    pub(super) fn set(_account: &str, _secret: &str) -> Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn get(_account: &str) -> Result<Option<String>> {
+        Err(unsupported())
+    }
+
+    pub(super) fn delete(_account: &str) -> Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{credential_origin, CredentialStore, StoredCredential};
+    use url::Url;
+
+    #[test]
+    fn origin_drops_path_and_default_port() {
+        let url = Url::parse("https://API.example.com:443/v1/items?x=1").expect("url");
+        assert_eq!(
+            credential_origin(&url).expect("origin"),
+            "https://api.example.com"
+        );
+        let url = Url::parse("http://localhost:8080/").expect("url");
+        assert_eq!(
+            credential_origin(&url).expect("origin"),
+            "http://localhost:8080"
+        );
+        assert!(credential_origin(&Url::parse("data:text/plain,x").expect("url")).is_err());
+    }
+
+    #[test]
+    fn lookup_skips_keyring_for_unknown_origins() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = CredentialStore::open_at(&dir.path().join("credentials.json"));
+        assert!(store.origins().expect("origins").is_empty());
+        let url = Url::parse("https://example.com/").expect("url");
+        assert_eq!(store.lookup(&url).expect("lookup"), None);
+    }
+
+    #[test]
+    fn credential_serializes_with_type_tag() {
+        let credential = StoredCredential::Bearer {
+            token: "abc".to_string(),
+        };
+        let json = serde_json::to_string(&credential).expect("json");
+        assert_eq!(json, r#"{"type":"bearer","token":"abc"}"#);
+        assert_eq!(
+            serde_json::from_str::<StoredCredential>(&json).expect("parse"),
+            credential
+        );
+
+        let credential = StoredCredential::OAuth2 {
+            token_url: "https://auth.example.com/token".to_string(),
+            client_id: "cli".to_string(),
+            client_secret: None,
+            access_token: "at".to_string(),
+            refresh_token: Some("rt".to_string()),
+            expires_at: Some(1_700_000_000),
+        };
+        let json = serde_json::to_string(&credential).expect("json");
+        assert_eq!(
+            json,
+            r#"{"type":"oauth2","token_url":"https://auth.example.com/token","client_id":"cli","access_token":"at","refresh_token":"rt","expires_at":1700000000}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<StoredCredential>(&json).expect("parse"),
+            credential
+        );
+    }
+}
@@ -0,0 +1,590 @@
+//! Background agent (`rurl --daemon`)
+//!
+//! Every invocation normally pays for DNS, TCP and TLS handshakes and, with
+//! `--cookies-from-browser`, for reading and decrypting the browser's cookie
+//! database. `rurl --daemon` stays running with warm connection pools (rustls
+//! keeps TLS sessions of a client in memory) and extracted browser cookies.
+//! Later invocations that find its Unix socket hand it their fully built
+//! requests; redirects, cookies, output and everything else still happen in
+//! the invoking process.
+//!
+//! Each connection carries one call: a JSON line and the request body, then a
+//! JSON line with the reply and, for responses, the body as length-prefixed
+//! frames ending with an empty one. When the agent is unreachable or cannot
+//! tell why a request failed, the invoking process sends the request itself.
+
+use crate::browser::{BrowserCookieExtractor, CookieStore};
+use crate::config::{BrowserCookieConfig, Config};
+use crate::error::{Result, RurlError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(unix)]
+use bytes::Bytes;
+#[cfg(unix)]
+use reqwest::header::HeaderMap;
+#[cfg(unix)]
+use reqwest::{Body, Method, ResponseBuilderExt, Version};
+#[cfg(unix)]
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::sync::{Arc, Mutex};
+#[cfg(unix)]
+use std::time::{Instant, SystemTime};
+#[cfg(unix)]
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+/// How long the agent reuses extracted browser cookies without `--cookie-cache`
+pub const DEFAULT_COOKIE_TTL: Duration = Duration::from_secs(300);
+
+/// Default socket: `$XDG_RUNTIME_DIR/rurl/agent.sock`, or under the cache directory
+pub fn default_socket_path() -> Result<PathBuf> {
+    let dir = dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .ok_or_else(|| RurlError::Config("Cannot determine the agent socket path".to_string()))?;
+    Ok(dir.join("rurl").join("agent.sock"))
+}
+
+/// Client settings the agent must share with the invoking process
+#[cfg_attr(not(unix), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) struct ClientOptions {
+    insecure: bool,
+    raw: bool,
+    connect_timeout_ms: u64,
+    read_timeout_ms: Option<u64>,
+    max_header_size: u64,
+}
+
+impl ClientOptions {
+    /// Options for `config`, or `None` when its client needs settings only the
+    /// invoking process has (proxies, tunnels, CA or client certificates, the
+    /// host filter its resolver applies, source ports, and leaving out
+    /// `Accept` or `Accept-Encoding`, which the agent's client would add)
+    pub(crate) fn for_config(config: &Config) -> Option<Self> {
+        let local_only = config.proxy.is_some()
+            || config.preproxy.is_some()
+            || config.proxy_pac.is_some()
+            || config.ssh_tunnel.is_some()
+            || config.ssl.ca_cert_file.is_some()
+            || config.ssl.client_cert_file.is_some()
+            || config.ssl.pkcs11_uri.is_some()
+            || config.host_filter.is_active()
+            || config.headers.is_suppressed("accept")
+            || config.headers.is_suppressed("accept-encoding")
+            || config.local_port.is_some()
+            || config.no_keepalive;
+        (!local_only).then(|| Self {
+            insecure: !config.ssl.verify_certs,
+            raw: config.output.raw,
+            connect_timeout_ms: config.connect_timeout.as_millis() as u64,
+            read_timeout_ms: config.read_timeout.map(|limit| limit.as_millis() as u64),
+            max_header_size: config.max_header_size,
+        })
+    }
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Call {
+    Send {
+        options: ClientOptions,
+        method: String,
+        url: String,
+        headers: Vec<(String, Vec<u8>)>,
+        timeout_ms: u64,
+        body_len: Option<usize>,
+    },
+    Cookies {
+        browser: BrowserCookieConfig,
+    },
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "reply", rename_all = "snake_case")]
+enum Reply {
+    Response {
+        status: u16,
+        version: String,
+        headers: Vec<(String, Vec<u8>)>,
+    },
+    Cookies {
+        cookies: CookieStore,
+    },
+    Failed {
+        error: Failure,
+    },
+}
+
+/// Request failures the invoking process can report with the same exit code
+#[cfg_attr(not(unix), allow(dead_code))]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+enum Failure {
+    Timeout,
+    ConnectTimeout,
+    ResolveHost(String),
+    EmptyReply,
+    Recv(String),
+    /// Anything else; the invoking process repeats the call for the exact error
+    Other(String),
+}
+
+impl From<RurlError> for Failure {
+    fn from(err: RurlError) -> Self {
+        match err {
+            RurlError::Timeout => Failure::Timeout,
+            RurlError::ConnectTimeout => Failure::ConnectTimeout,
+            RurlError::ResolveHost(host) => Failure::ResolveHost(host),
+            RurlError::EmptyReply => Failure::EmptyReply,
+            RurlError::Recv(message) => Failure::Recv(message),
+            other => Failure::Other(other.to_string()),
+        }
+    }
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+impl Failure {
+    fn into_error(self) -> Option<RurlError> {
+        match self {
+            Failure::Timeout => Some(RurlError::Timeout),
+            Failure::ConnectTimeout => Some(RurlError::ConnectTimeout),
+            Failure::ResolveHost(host) => Some(RurlError::ResolveHost(host)),
+            Failure::EmptyReply => Some(RurlError::EmptyReply),
+            Failure::Recv(message) => Some(RurlError::Recv(message)),
+            Failure::Other(_) => None,
+        }
+    }
+}
+
+/// Run the agent on `socket` until Ctrl-C, reusing extracted browser cookies
+/// for `cookie_ttl`
+#[cfg(unix)]
+pub async fn serve(socket: &Path, cookie_ttl: Duration) -> Result<()> {
+    use std::fs::{DirBuilder, Permissions};
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    // Requests carry credentials and cookies, so only the owner may reach
+    // the socket: its directory must be private before the socket exists
+    let dir = socket
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if !dir.exists() {
+        DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    }
+    check_private_dir(dir)?;
+    if socket.exists() {
+        if UnixStream::connect(socket).await.is_ok() {
+            return Err(RurlError::Config(format!(
+                "An agent is already listening on {}",
+                socket.display()
+            )));
+        }
+        std::fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    std::fs::set_permissions(socket, Permissions::from_mode(0o600))?;
+
+    let agent = Arc::new(Agent {
+        clients: Mutex::default(),
+        cookies: tokio::sync::Mutex::default(),
+        cookie_ttl,
+    });
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let agent = agent.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = agent.handle(stream).await {
+                            log::debug!("Agent call failed: {}", err);
+                        }
+                    });
+                }
+                Err(err) => break Err(err.into()),
+            },
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+    };
+    let _ = std::fs::remove_file(socket);
+    result
+}
+
+/// Refuse a socket directory that is not the user's own with mode 0700
+#[cfg(unix)]
+fn check_private_dir(dir: &Path) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(dir)?;
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(RurlError::Config(format!(
+            "The agent socket needs a directory only you can access (mode 0700): {}",
+            dir.display()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn serve(_socket: &Path, _cookie_ttl: Duration) -> Result<()> {
+    Err(RurlError::Unsupported(
+        "--daemon needs Unix domain sockets".to_string(),
+    ))
+}
+
+#[cfg(unix)]
+struct Agent {
+    clients: Mutex<HashMap<ClientOptions, reqwest::Client>>,
+    /// Extracted cookies by browser configuration
+    cookies: tokio::sync::Mutex<HashMap<String, ExtractedCookies>>,
+    cookie_ttl: Duration,
+}
+
+#[cfg(unix)]
+struct ExtractedCookies {
+    extracted: Instant,
+    /// Modification time of the cookie database when it was read
+    modified: Option<SystemTime>,
+    cookies: CookieStore,
+}
+
+#[cfg(unix)]
+impl Agent {
+    /// The pooled client for `options`, built on first use
+    fn client(&self, options: ClientOptions) -> Result<reqwest::Client> {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(client) = clients.get(&options) {
+            return Ok(client.clone());
+        }
+        let mut builder = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .connect_timeout(Duration::from_millis(options.connect_timeout_ms))
+            .danger_accept_invalid_certs(options.insecure)
+            .http2_max_header_list_size(u32::try_from(options.max_header_size).unwrap_or(u32::MAX));
+        if let Some(limit) = options.read_timeout_ms {
+            builder = builder.read_timeout(Duration::from_millis(limit));
+        }
+        if options.raw {
+            builder = builder.no_gzip().no_brotli().no_deflate();
+        }
+        let client = builder.build().map_err(RurlError::Http)?;
+        clients.insert(options, client.clone());
+        Ok(client)
+    }
+
+    /// Cookies for `browser`, read again after `cookie_ttl` or once the
+    /// browser has written its cookie database
+    async fn cookies(&self, browser: BrowserCookieConfig) -> Result<CookieStore> {
+        let key = serde_json::to_string(&browser)?;
+        let extractor = BrowserCookieExtractor::new(browser);
+        let modified = extractor
+            .source_path()
+            .ok()
+            .and_then(|path| std::fs::metadata(path).ok())
+            .and_then(|metadata| metadata.modified().ok());
+        let mut cached = self.cookies.lock().await;
+        if let Some(entry) = cached.get(&key) {
+            if entry.extracted.elapsed() < self.cookie_ttl && entry.modified == modified {
+                return Ok(entry.cookies.clone());
+            }
+        }
+        let cookies = extractor.extract_cookies().await?;
+        let entry = ExtractedCookies {
+            extracted: Instant::now(),
+            modified,
+            cookies: cookies.clone(),
+        };
+        cached.insert(key, entry);
+        Ok(cookies)
+    }
+
+    async fn handle(&self, stream: UnixStream) -> Result<()> {
+        let (read, mut write) = stream.into_split();
+        let mut reader = BufReader::new(read);
+        let call = read_json(&mut reader).await?;
+        let (method, url, headers, timeout_ms, body_len, options) = match call {
+            Call::Cookies { browser } => {
+                let reply = match self.cookies(browser).await {
+                    Ok(cookies) => Reply::Cookies { cookies },
+                    Err(err) => Reply::Failed {
+                        error: Failure::Other(err.to_string()),
+                    },
+                };
+                return write_json(&mut write, &reply).await;
+            }
+            Call::Send {
+                options,
+                method,
+                url,
+                headers,
+                timeout_ms,
+                body_len,
+            } => (method, url, headers, timeout_ms, body_len, options),
+        };
+
+        let method = Method::from_bytes(method.as_bytes())
+            .map_err(|_| RurlError::Config(format!("Invalid method: {}", method)))?;
+        let mut request = self
+            .client(options)?
+            .request(method, &url)
+            .timeout(Duration::from_millis(timeout_ms));
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(len) = body_len {
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).await?;
+            request = request.body(body);
+        }
+
+        let mut response = match request.send().await {
+            Ok(response) => response,
+            Err(err) => {
+                let error = Failure::from(RurlError::transport(err));
+                return write_json(&mut write, &Reply::Failed { error }).await;
+            }
+        };
+        let head = Reply::Response {
+            status: response.status().as_u16(),
+            version: format!("{:?}", response.version()),
+            headers: headers_to_wire(response.headers()),
+        };
+        write_json(&mut write, &head).await?;
+        // A body error closes the connection without the final empty frame
+        while let Some(chunk) = response.chunk().await.map_err(RurlError::transport)? {
+            write_frame(&mut write, &chunk).await?;
+        }
+        write_frame(&mut write, &[]).await
+    }
+}
+
+/// An invoking process's connection to a running agent
+#[derive(Debug, Clone)]
+pub(crate) struct AgentClient {
+    socket: PathBuf,
+    options: ClientOptions,
+}
+
+impl AgentClient {
+    pub(crate) fn new(socket: PathBuf, options: ClientOptions) -> Self {
+        Self { socket, options }
+    }
+
+    /// Send `request` through the agent; `None` when the agent is unreachable,
+    /// the body is streamed, or the failure needs the local client to report it
+    #[cfg(unix)]
+    pub(crate) async fn send(
+        &self,
+        request: &reqwest::Request,
+        timeout: Duration,
+    ) -> Option<Result<reqwest::Response>> {
+        let body = match request.body() {
+            Some(body) => Some(body.as_bytes()?),
+            None => None,
+        };
+        let call = Call::Send {
+            options: self.options,
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers: headers_to_wire(request.headers()),
+            timeout_ms: timeout.as_millis() as u64,
+            body_len: body.map(<[u8]>::len),
+        };
+        let (reply, reader) = self
+            .call(&call, body.unwrap_or_default())
+            .await
+            .map_err(|err| log::debug!("Agent unavailable, sending directly: {}", err))
+            .ok()?;
+        match reply {
+            Reply::Response {
+                status,
+                version,
+                headers,
+            } => Some(response_from_wire(
+                request.url().clone(),
+                status,
+                &version,
+                headers,
+                reader,
+            )),
+            Reply::Failed { error } => error.into_error().map(Err),
+            Reply::Cookies { .. } => None,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) async fn send(
+        &self,
+        _request: &reqwest::Request,
+        _timeout: Duration,
+    ) -> Option<Result<reqwest::Response>> {
+        None
+    }
+
+    /// Browser cookies held by the agent; `None` sends the caller to the
+    /// browser itself, which also reports any error in full
+    #[cfg(unix)]
+    pub(crate) async fn cookies(&self, browser: &BrowserCookieConfig) -> Option<CookieStore> {
+        let call = Call::Cookies {
+            browser: browser.clone(),
+        };
+        match self.call(&call, &[]).await {
+            Ok((Reply::Cookies { cookies }, _)) => Some(cookies),
+            Ok(_) => None,
+            Err(err) => {
+                log::debug!("Agent unavailable, reading cookies directly: {}", err);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) async fn cookies(&self, _browser: &BrowserCookieConfig) -> Option<CookieStore> {
+        None
+    }
+
+    #[cfg(unix)]
+    async fn call(
+        &self,
+        call: &Call,
+        body: &[u8],
+    ) -> Result<(Reply, BufReader<tokio::net::unix::OwnedReadHalf>)> {
+        let (read, mut write) = UnixStream::connect(&self.socket).await?.into_split();
+        write_json(&mut write, call).await?;
+        write.write_all(body).await?;
+        let mut reader = BufReader::new(read);
+        let reply = read_json(&mut reader).await?;
+        Ok((reply, reader))
+    }
+}
+
+#[cfg(unix)]
+fn headers_to_wire(headers: &HeaderMap) -> Vec<(String, Vec<u8>)> {
+    headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+        .collect()
+}
+
+#[cfg(unix)]
+fn response_from_wire(
+    url: url::Url,
+    status: u16,
+    version: &str,
+    headers: Vec<(String, Vec<u8>)>,
+    reader: impl AsyncRead + Send + Sync + Unpin + 'static,
+) -> Result<reqwest::Response> {
+    let version = match version {
+        "HTTP/0.9" => Version::HTTP_09,
+        "HTTP/1.0" => Version::HTTP_10,
+        "HTTP/2.0" => Version::HTTP_2,
+        "HTTP/3.0" => Version::HTTP_3,
+        _ => Version::HTTP_11,
+    };
+    let mut builder = http::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(Body::wrap_stream(frames(reader)))
+        .map_err(|e| RurlError::Recv(format!("Invalid response from the agent: {}", e)))?;
+    Ok(reqwest::Response::from(response))
+}
+
+/// Body chunks sent as frames by the agent
+#[cfg(unix)]
+fn frames(
+    reader: impl AsyncRead + Send + Sync + Unpin + 'static,
+) -> impl futures_util::Stream<Item = std::io::Result<Bytes>> + Send + Sync + 'static {
+    futures_util::stream::try_unfold(reader, |mut reader| async move {
+        let len = reader.read_u32().await.map_err(|err| {
+            std::io::Error::new(
+                err.kind(),
+                "agent connection closed before the end of the body",
+            )
+        })?;
+        if len == 0 {
+            return Ok(None);
+        }
+        let mut chunk = vec![0; len as usize];
+        reader.read_exact(&mut chunk).await?;
+        Ok(Some((Bytes::from(chunk), reader)))
+    })
+}
+
+#[cfg(unix)]
+async fn write_frame(writer: &mut (impl AsyncWrite + Unpin), chunk: &[u8]) -> Result<()> {
+    writer.write_u32(chunk.len() as u32).await?;
+    writer.write_all(chunk).await?;
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn write_json(writer: &mut (impl AsyncWrite + Unpin), value: &impl Serialize) -> Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn read_json<T: for<'de> Deserialize<'de>>(
+    reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+) -> Result<T> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Err(RurlError::Recv("agent closed the connection".to_string()));
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{frames, write_frame, Failure};
+    use crate::error::RurlError;
+    use futures_util::TryStreamExt;
+
+    #[tokio::test]
+    async fn frames_round_trip_and_detect_truncation() {
+        let mut wire = Vec::new();
+        write_frame(&mut wire, b"hello ").await.expect("frame");
+        write_frame(&mut wire, b"agent").await.expect("frame");
+        let truncated = wire.clone();
+        write_frame(&mut wire, &[]).await.expect("end");
+
+        let chunks: Vec<_> = frames(std::io::Cursor::new(wire))
+            .try_collect()
+            .await
+            .expect("frames");
+        assert_eq!(chunks.concat(), b"hello agent");
+
+        let result: std::io::Result<Vec<_>> =
+            frames(std::io::Cursor::new(truncated)).try_collect().await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn failures_keep_exit_code_relevant_kinds() {
+        let failure = Failure::from(RurlError::ResolveHost("nowhere.invalid".to_string()));
+        let wire = serde_json::to_string(&failure).expect("json");
+        let failure: Failure = serde_json::from_str(&wire).expect("parse");
+        assert!(matches!(
+            failure.into_error(),
+            Some(RurlError::ResolveHost(host)) if host == "nowhere.invalid"
+        ));
+        assert!(Failure::from(RurlError::Config("bad".to_string()))
+            .into_error()
+            .is_none());
+    }
+}
@@ -35,6 +35,9 @@ pub enum RurlError {
     #[error("Network timeout")]
     Timeout,
 
+    #[error("Operation too slow: {0}")]
+    TooSlow(String),
+
     #[error("Redirect limit exceeded: {0}")]
     RedirectLimitExceeded(usize),
 
@@ -46,7 +49,248 @@ pub enum RurlError {
 
     #[error("Unsupported operation: {0}")]
     Unsupported(String),
+
+    #[error("Transfer aborted")]
+    Aborted,
+
+    #[error("Could not resolve host: {0}")]
+    ResolveHost(String),
+
+    #[error("Connection timed out")]
+    ConnectTimeout,
+
+    #[error("Empty reply from server")]
+    EmptyReply,
+
+    #[error("Failure when receiving data from the peer: {0}")]
+    Recv(String),
+
+    /// A response exceeded `--max-header-size` or `--max-headers`
+    #[error("Response headers too large: {0}")]
+    HeadersTooLarge(String),
+
+    /// `--allow-host`/`--deny-host` refused the host
+    #[error("Host not allowed: {0}")]
+    HostBlocked(String),
+
+    /// `--no-internal-targets` refused a loopback, private or link-local address
+    #[error("Refusing to connect to an internal address: {0}")]
+    InternalTarget(String),
+
+    #[error("The requested URL returned error: {0}")]
+    HttpStatus(u16),
+
+    /// `rurl diff` found differences
+    #[error("Responses differ")]
+    ResponsesDiffer,
+
+    /// `--grep` matched no line of the body
+    #[error("No line of the response matched --grep")]
+    NoMatch,
+
+    /// An error with an explicit suggestion for the user
+    #[error("{error}")]
+    Hinted { error: Box<RurlError>, hint: Hint },
+}
+
+/// Suggestion printed after an error message, localized through the
+/// `hint-*` messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    /// The browser holds a lock on its database
+    CloseBrowser,
+    /// The browser database is locked or needs the same privileges (Windows)
+    CloseBrowserOrDropElevation,
+    /// macOS denied access to the browser's Safe Storage key
+    AllowKeychainAccess,
+    /// Nothing accepted the connection
+    CheckHostAndPort,
+    /// The host name did not resolve
+    CheckHostName,
+    /// The server certificate could not be verified
+    TrustCertificate,
+    /// The client certificate or key could not be loaded
+    CheckClientCertificate,
+    /// The transfer ran into `--timeout` or `--connect-timeout`
+    RaiseTimeout,
+    /// The proxy URL or credentials were rejected
+    CheckProxy,
+}
+
+impl Hint {
+    /// Fluent message id of the hint text
+    pub fn message_id(self) -> &'static str {
+        match self {
+            Hint::CloseBrowser => "hint-close-browser",
+            Hint::CloseBrowserOrDropElevation => "hint-close-browser-or-drop-elevation",
+            Hint::AllowKeychainAccess => "hint-allow-keychain-access",
+            Hint::CheckHostAndPort => "hint-check-host-and-port",
+            Hint::CheckHostName => "hint-check-host-name",
+            Hint::TrustCertificate => "hint-trust-certificate",
+            Hint::CheckClientCertificate => "hint-check-client-certificate",
+            Hint::RaiseTimeout => "hint-raise-timeout",
+            Hint::CheckProxy => "hint-check-proxy",
+        }
+    }
+}
+
+impl RurlError {
+    /// Attach `hint`, replacing any hint already attached
+    pub fn with_hint(self, hint: Hint) -> Self {
+        RurlError::Hinted {
+            error: Box::new(self.into_unhinted()),
+            hint,
+        }
+    }
+
+    /// The error without an attached hint
+    pub fn unhinted(&self) -> &RurlError {
+        match self {
+            RurlError::Hinted { error, .. } => error.unhinted(),
+            other => other,
+        }
+    }
+
+    fn into_unhinted(self) -> RurlError {
+        match self {
+            RurlError::Hinted { error, .. } => error.into_unhinted(),
+            other => other,
+        }
+    }
+
+    /// The attached hint, or one derived from the kind of error
+    pub fn hint(&self) -> Option<Hint> {
+        match self {
+            RurlError::Hinted { hint, .. } => Some(*hint),
+            RurlError::ResolveHost(_) => Some(Hint::CheckHostName),
+            RurlError::Timeout | RurlError::ConnectTimeout => Some(Hint::RaiseTimeout),
+            RurlError::Proxy(_) => Some(Hint::CheckProxy),
+            RurlError::Ssl(message) if message.to_ascii_lowercase().contains("client") => {
+                Some(Hint::CheckClientCertificate)
+            }
+            RurlError::Ssl(message) if message.to_ascii_lowercase().contains("ca certificate") => {
+                Some(Hint::TrustCertificate)
+            }
+            RurlError::Http(err) if err.is_connect() => {
+                if source_mentions(err, &["certificate"]) {
+                    Some(Hint::TrustCertificate)
+                } else if source_mentions(err, &["connection refused"]) {
+                    Some(Hint::CheckHostAndPort)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Classify a reqwest error from sending a request or reading its body;
+    /// errors without a finer variant stay [`RurlError::Http`]
+    pub fn transport(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            return if err.is_connect() {
+                RurlError::ConnectTimeout
+            } else {
+                RurlError::Timeout
+            };
+        }
+        match source_of::<RurlError>(&err) {
+            Some(RurlError::HostBlocked(detail)) => return RurlError::HostBlocked(detail.clone()),
+            Some(RurlError::InternalTarget(detail)) => {
+                return RurlError::InternalTarget(detail.clone())
+            }
+            _ => {}
+        }
+        if err.is_connect() {
+            if let Some(host) = resolve_failure(&err) {
+                return RurlError::ResolveHost(host);
+            }
+            return RurlError::Http(err);
+        }
+        if let Some(hyper_err) = source_of::<hyper::Error>(&err) {
+            return RurlError::from_hyper(hyper_err);
+        }
+        if err.is_body() {
+            return RurlError::Recv(err.to_string());
+        }
+        RurlError::Http(err)
+    }
+
+    /// Classify an error hyper reported while exchanging a request
+    pub(crate) fn from_hyper(err: &hyper::Error) -> Self {
+        if err.is_parse_too_large() {
+            return RurlError::HeadersTooLarge(err.to_string());
+        }
+        if err.is_incomplete_message() {
+            return RurlError::EmptyReply;
+        }
+        RurlError::Recv(err.to_string())
+    }
+}
+
+/// Host whose lookup failed, when `err` is a DNS failure
+fn resolve_failure(err: &reqwest::Error) -> Option<String> {
+    if !source_mentions(err, &["dns error", "failed to lookup address"]) {
+        return None;
+    }
+    let host = err.url().and_then(|url| url.host_str()).unwrap_or_default();
+    Some(host.to_string())
+}
+
+/// Whether a source of `err` mentions one of the lowercase `needles`
+fn source_mentions(err: &reqwest::Error, needles: &[&str]) -> bool {
+    let mut source = std::error::Error::source(err);
+    while let Some(current) = source {
+        let message = current.to_string().to_ascii_lowercase();
+        if needles.iter().any(|needle| message.contains(needle)) {
+            return true;
+        }
+        source = current.source();
+    }
+    false
+}
+
+pub(crate) fn source_of<'a, T: std::error::Error + 'static>(
+    err: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a T> {
+    let mut source = err.source();
+    while let Some(current) = source {
+        if let Some(found) = current.downcast_ref::<T>() {
+            return Some(found);
+        }
+        source = current.source();
+    }
+    None
 }
 
 /// Result type alias for rurl operations
 pub type Result<T> = std::result::Result<T, RurlError>;
+
+#[cfg(test)]
+mod tests {
+    use super::{Hint, RurlError};
+
+    #[test]
+    fn with_hint_replaces_hint_and_keeps_error() {
+        let err = RurlError::BrowserCookie("locked".to_string())
+            .with_hint(Hint::CloseBrowser)
+            .with_hint(Hint::CloseBrowserOrDropElevation);
+        assert_eq!(err.hint(), Some(Hint::CloseBrowserOrDropElevation));
+        assert!(matches!(err.unhinted(), RurlError::BrowserCookie(_)));
+        assert_eq!(err.to_string(), "Browser cookie error: locked");
+    }
+
+    #[test]
+    fn hint_is_derived_from_error_kind() {
+        assert_eq!(
+            RurlError::ResolveHost("nowhere.invalid".to_string()).hint(),
+            Some(Hint::CheckHostName)
+        );
+        assert_eq!(RurlError::ConnectTimeout.hint(), Some(Hint::RaiseTimeout));
+        assert_eq!(
+            RurlError::Ssl("Invalid CA certificate \"ca.pem\"".to_string()).hint(),
+            Some(Hint::TrustCertificate)
+        );
+        assert_eq!(RurlError::EmptyReply.hint(), None);
+    }
+}
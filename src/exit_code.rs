@@ -1,13 +1,84 @@
+//! curl-compatible process exit codes
+//!
+//! | Code | curl name | Errors |
+//! | --- | --- | --- |
+//! | 1 | (like diff and grep) | `ResponsesDiffer`, `NoMatch` |
+//! | 2 | CURLE_FAILED_INIT | `Config`, invalid requests |
+//! | 3 | CURLE_URL_MALFORMAT | `InvalidUrl` |
+//! | 4 | CURLE_NOT_BUILT_IN | `Unsupported` |
+//! | 5 | CURLE_COULDNT_RESOLVE_PROXY | `Proxy` |
+//! | 6 | CURLE_COULDNT_RESOLVE_HOST | `ResolveHost`, DNS failures |
+//! | 7 | CURLE_COULDNT_CONNECT | `HostBlocked`, `InternalTarget`, other connection failures |
+//! | 22 | CURLE_HTTP_RETURNED_ERROR | `HttpStatus` (`--fail`) |
+//! | 23 | CURLE_WRITE_ERROR | `Io` |
+//! | 26 | CURLE_READ_ERROR | `Json` |
+//! | 28 | CURLE_OPERATION_TIMEDOUT | `Timeout`, `ConnectTimeout`, `TooSlow` |
+//! | 35 | CURLE_SSL_CONNECT_ERROR | `Ssl` |
+//! | 37 | CURLE_FILE_COULDNT_READ_FILE | `PermissionDenied`, `FileNotFound` |
+//! | 43 | CURLE_BAD_FUNCTION_ARGUMENT | `BrowserCookie`, unclassified HTTP errors |
+//! | 47 | CURLE_TOO_MANY_REDIRECTS | `RedirectLimitExceeded` |
+//! | 52 | CURLE_GOT_NOTHING | `EmptyReply` |
+//! | 56 | CURLE_RECV_ERROR | `Recv`, body read failures |
+//! | 58 | CURLE_SSL_CERTPROBLEM | client certificate errors |
+//! | 61 | CURLE_BAD_CONTENT_ENCODING | body decoding failures |
+//! | 77 | CURLE_SSL_CACERT_BADFILE | CA certificate errors |
+//! | 94 | CURLE_AUTH_ERROR | `Auth` |
+//! | 100 | CURLE_TOO_LARGE | `HeadersTooLarge` |
+//! | 130 | (SIGINT) | `Aborted` |
+//!
+//! `Hinted` errors exit with the code of the error they wrap.
+
 use crate::error::RurlError;
 
+/// Every code [`exit_code_for_error`] returns, with what it means, for
+/// `rurl --manual`
+pub const EXIT_CODES: &[(i32, &str)] = &[
+    (0, "Success"),
+    (
+        1,
+        "rurl diff: the responses differ; --grep: no line matched",
+    ),
+    (2, "Invalid options or request"),
+    (3, "Malformed URL"),
+    (4, "Feature not supported by this build or backend"),
+    (5, "Proxy error"),
+    (6, "Could not resolve host"),
+    (7, "Could not connect, or the host is not allowed"),
+    (22, "HTTP status 400 or above with --fail"),
+    (23, "Could not write output"),
+    (26, "Could not read input"),
+    (28, "Timed out or too slow"),
+    (35, "TLS handshake failed"),
+    (37, "Could not read a file"),
+    (43, "Browser cookie or other internal error"),
+    (47, "Too many redirects"),
+    (52, "The server replied with nothing"),
+    (56, "Failure receiving data"),
+    (58, "Problem with the client certificate"),
+    (61, "Could not decode the response body"),
+    (77, "Problem with the CA certificate"),
+    (94, "Authentication failed"),
+    (
+        100,
+        "Response headers larger than --max-header-size or --max-headers",
+    ),
+    (130, "Interrupted"),
+];
+
 pub fn exit_code_for_error(err: &RurlError) -> i32 {
     match err {
         RurlError::InvalidUrl(_) => 3,
         RurlError::Config(_) => 2,
         RurlError::Proxy(_) => 5,
+        RurlError::ResolveHost(_) => 6,
+        RurlError::HostBlocked(_) | RurlError::InternalTarget(_) => 7,
+        RurlError::HttpStatus(_) => 22,
         RurlError::Auth(_) => 94,
-        RurlError::Timeout => 28,
+        RurlError::HeadersTooLarge(_) => 100,
+        RurlError::Timeout | RurlError::ConnectTimeout | RurlError::TooSlow(_) => 28,
         RurlError::RedirectLimitExceeded(_) => 47,
+        RurlError::EmptyReply => 52,
+        RurlError::Recv(_) => 56,
         RurlError::PermissionDenied(_) | RurlError::FileNotFound(_) => 37,
         RurlError::Ssl(message) => ssl_exit_code(message),
         RurlError::Io(_) => 23,
@@ -15,9 +86,14 @@ pub fn exit_code_for_error(err: &RurlError) -> i32 {
         RurlError::Unsupported(_) => 4,
         RurlError::Http(err) => http_exit_code(err),
         RurlError::BrowserCookie(_) => 43,
+        RurlError::Aborted => 130,
+        RurlError::ResponsesDiffer | RurlError::NoMatch => 1,
+        RurlError::Hinted { error, .. } => exit_code_for_error(error),
     }
 }
 
+/// Exit code of a reqwest error that did not go through
+/// [`RurlError::transport`]
 fn http_exit_code(err: &reqwest::Error) -> i32 {
     if err.is_timeout() {
         return 28;
@@ -25,7 +101,16 @@ fn http_exit_code(err: &reqwest::Error) -> i32 {
     if err.is_connect() {
         return 7;
     }
-    if err.is_request() {
+    if err.is_redirect() {
+        return 47;
+    }
+    if err.is_decode() {
+        return 61;
+    }
+    if err.is_body() {
+        return 56;
+    }
+    if err.is_builder() || err.is_request() {
         return 2;
     }
     43
@@ -44,7 +129,7 @@ fn ssl_exit_code(message: &str) -> i32 {
 
 #[cfg(test)]
 mod tests {
-    use super::exit_code_for_error;
+    use super::{exit_code_for_error, EXIT_CODES};
     use crate::error::RurlError;
 
     #[test]
@@ -59,6 +144,11 @@ mod tests {
         assert_eq!(exit_code_for_error(&err), 47);
     }
 
+    #[test]
+    fn exit_code_maps_aborted_like_sigint() {
+        assert_eq!(exit_code_for_error(&RurlError::Aborted), 130);
+    }
+
     #[test]
     fn exit_code_maps_ssl_variants() {
         let err = RurlError::Ssl("CA certificate failed".to_string());
@@ -112,5 +202,62 @@ mod tests {
             23
         );
         assert_eq!(exit_code_for_error(&RurlError::Timeout), 28);
+        assert_eq!(
+            exit_code_for_error(&RurlError::TooSlow("slow".to_string())),
+            28
+        );
+    }
+
+    #[test]
+    fn exit_code_maps_transfer_failures() {
+        assert_eq!(
+            exit_code_for_error(&RurlError::ResolveHost("nowhere.invalid".to_string())),
+            6
+        );
+        assert_eq!(exit_code_for_error(&RurlError::HttpStatus(404)), 22);
+        assert_eq!(exit_code_for_error(&RurlError::ConnectTimeout), 28);
+        assert_eq!(exit_code_for_error(&RurlError::EmptyReply), 52);
+        assert_eq!(
+            exit_code_for_error(&RurlError::Recv("reset".to_string())),
+            56
+        );
+    }
+
+    #[test]
+    fn exit_code_table_lists_every_mapped_code() {
+        let errors = [
+            RurlError::ResponsesDiffer,
+            RurlError::NoMatch,
+            RurlError::Config("x".to_string()),
+            RurlError::InvalidUrl("x".to_string()),
+            RurlError::Unsupported("x".to_string()),
+            RurlError::Proxy("x".to_string()),
+            RurlError::ResolveHost("x".to_string()),
+            RurlError::HostBlocked("x".to_string()),
+            RurlError::InternalTarget("x".to_string()),
+            RurlError::HttpStatus(500),
+            RurlError::Io(std::io::Error::from(std::io::ErrorKind::Other)),
+            RurlError::Timeout,
+            RurlError::Ssl("handshake".to_string()),
+            RurlError::Ssl("client certificate".to_string()),
+            RurlError::Ssl("CA certificate".to_string()),
+            RurlError::FileNotFound("x".to_string()),
+            RurlError::BrowserCookie("x".to_string()),
+            RurlError::RedirectLimitExceeded(1),
+            RurlError::EmptyReply,
+            RurlError::Recv("x".to_string()),
+            RurlError::Auth("x".to_string()),
+            RurlError::HeadersTooLarge("x".to_string()),
+            RurlError::Aborted,
+        ];
+        for err in &errors {
+            let code = exit_code_for_error(err);
+            assert!(
+                EXIT_CODES.iter().any(|(listed, _)| *listed == code),
+                "exit code {} of {:?} is not listed",
+                code,
+                err
+            );
+        }
     }
 }
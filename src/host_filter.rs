@@ -0,0 +1,413 @@
+//! Host allow and deny lists (`--allow-host`, `--deny-host`)
+//!
+//! Patterns are globs over host names (`*.example.com`, `api-?.internal`) or
+//! IP networks in CIDR notation (`10.0.0.0/8`, `fd00::/8`; a bare address is
+//! a single-host network). The URL host is checked before every request,
+//! redirect hops included; network patterns are also checked against the
+//! addresses rurl resolves names to, so a name that points into a denied
+//! network is refused too. A deny match always wins; with allow patterns,
+//! the host must match one of them.
+//!
+//! `--no-internal-targets` refuses loopback, private (RFC 1918, unique local),
+//! shared (RFC 6598), link-local and unspecified addresses the same way,
+//! whether they are written in the URL or a name resolves to them.
+
+use crate::error::{Result, RurlError};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostPattern {
+    /// Lowercase glob with `*` and `?`
+    Glob(String),
+    Network(IpAddr, u8),
+}
+
+impl HostPattern {
+    fn parse(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        let invalid = || RurlError::Config(format!("Invalid host pattern '{}'", pattern));
+        if pattern.is_empty() {
+            return Err(invalid());
+        }
+        let (address, prefix) = match pattern.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (pattern, None),
+        };
+        let address = address.trim_start_matches('[').trim_end_matches(']');
+        match (address.parse::<IpAddr>(), prefix) {
+            (Ok(ip), prefix) => {
+                let max = if ip.is_ipv4() { 32 } else { 128 };
+                let prefix = match prefix {
+                    Some(prefix) => prefix.parse::<u8>().map_err(|_| invalid())?,
+                    None => max,
+                };
+                if prefix > max {
+                    return Err(invalid());
+                }
+                Ok(HostPattern::Network(ip, prefix))
+            }
+            (Err(_), Some(_)) => Err(invalid()),
+            (Err(_), None) => Ok(HostPattern::Glob(pattern.to_ascii_lowercase())),
+        }
+    }
+
+    fn matches_name(&self, host: &str) -> bool {
+        match self {
+            HostPattern::Glob(glob) => glob_match(glob.as_bytes(), host.as_bytes()),
+            HostPattern::Network(..) => false,
+        }
+    }
+
+    fn matches_addr(&self, addr: IpAddr) -> bool {
+        match *self {
+            HostPattern::Network(network, prefix) => network_contains(network, prefix, addr),
+            HostPattern::Glob(_) => false,
+        }
+    }
+
+    fn matches(&self, host: &str, addr: Option<IpAddr>) -> bool {
+        self.matches_name(host) || addr.is_some_and(|addr| self.matches_addr(addr))
+    }
+}
+
+/// Hosts requests may be sent to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostFilter {
+    allow: Vec<HostPattern>,
+    deny: Vec<HostPattern>,
+    block_internal: bool,
+}
+
+impl HostFilter {
+    /// Add an `--allow-host` pattern
+    pub fn allow(&mut self, pattern: &str) -> Result<()> {
+        self.allow.push(HostPattern::parse(pattern)?);
+        Ok(())
+    }
+
+    /// Add a `--deny-host` pattern
+    pub fn deny(&mut self, pattern: &str) -> Result<()> {
+        self.deny.push(HostPattern::parse(pattern)?);
+        Ok(())
+    }
+
+    /// Refuse internal addresses (`--no-internal-targets`)
+    pub fn block_internal(&mut self) {
+        self.block_internal = true;
+    }
+
+    /// Whether any pattern was given or internal addresses are blocked
+    pub fn is_active(&self) -> bool {
+        self.block_internal || !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    /// Check a URL host (a name, or an IP address without brackets). Names
+    /// that only allow networks could match are left to [`Self::filter_resolved`]
+    pub fn check_host(&self, host: &str) -> Result<()> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let host = host.to_ascii_lowercase();
+        let addr = host.parse::<IpAddr>().ok();
+        if let Some(addr) = addr.filter(|addr| self.block_internal && is_internal(*addr)) {
+            return Err(RurlError::InternalTarget(format!(
+                "{} is an internal address",
+                addr
+            )));
+        }
+        if self.deny.iter().any(|pattern| pattern.matches(&host, addr)) {
+            return Err(RurlError::HostBlocked(format!(
+                "{} matches --deny-host",
+                host
+            )));
+        }
+        let deferred = addr.is_none()
+            && self
+                .allow
+                .iter()
+                .any(|pattern| matches!(pattern, HostPattern::Network(..)));
+        if self.allow.is_empty()
+            || deferred
+            || self
+                .allow
+                .iter()
+                .any(|pattern| pattern.matches(&host, addr))
+        {
+            return Ok(());
+        }
+        Err(RurlError::HostBlocked(format!(
+            "{} matches no --allow-host pattern",
+            host
+        )))
+    }
+
+    /// The addresses `host` resolved to that may be connected to. Any denied
+    /// address refuses the name; addresses outside the allow list are dropped
+    pub fn filter_resolved(&self, host: &str, addrs: Vec<SocketAddr>) -> Result<Vec<SocketAddr>> {
+        let host = host.to_ascii_lowercase();
+        if let Some(addr) = addrs
+            .iter()
+            .find(|addr| self.block_internal && is_internal(addr.ip()))
+        {
+            return Err(RurlError::InternalTarget(format!(
+                "{} resolves to the internal address {}",
+                host,
+                addr.ip()
+            )));
+        }
+        if let Some(addr) = addrs.iter().find(|addr| {
+            self.deny
+                .iter()
+                .any(|pattern| pattern.matches_addr(addr.ip()))
+        }) {
+            return Err(RurlError::HostBlocked(format!(
+                "{} resolves to {}, which matches --deny-host",
+                host,
+                addr.ip()
+            )));
+        }
+        if self.allow.is_empty() || self.allow.iter().any(|pattern| pattern.matches_name(&host)) {
+            return Ok(addrs);
+        }
+        let allowed: Vec<SocketAddr> = addrs
+            .into_iter()
+            .filter(|addr| {
+                self.allow
+                    .iter()
+                    .any(|pattern| pattern.matches_addr(addr.ip()))
+            })
+            .collect();
+        if allowed.is_empty() {
+            return Err(RurlError::HostBlocked(format!(
+                "{} resolves to no address matching --allow-host",
+                host
+            )));
+        }
+        Ok(allowed)
+    }
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_match(&pattern[1..], text) || (!text.is_empty() && glob_match(pattern, &text[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_match(&pattern[1..], &text[1..]),
+        (Some(expected), Some(actual)) if expected == actual => {
+            glob_match(&pattern[1..], &text[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Loopback, private, shared, link-local, multicast, reserved or
+/// unspecified, also as the IPv4 address inside an IPv6 one
+fn is_internal(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => {
+            let [first, second, ..] = v4.octets();
+            let shared = first == 100 && (second & 0xc0) == 64;
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || first == 0
+                || shared
+                // 224.0.0.0/4 multicast, 240.0.0.0/4 reserved and broadcast
+                || first >= 224
+        }
+        IpAddr::V6(v6) => match embedded_ipv4(v6) {
+            Some(v4) => is_internal(IpAddr::V4(v4)),
+            None => {
+                let segment = v6.segments()[0];
+                v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_multicast()
+                    // fc00::/7 unique local and fe80::/10 link-local
+                    || (segment & 0xfe00) == 0xfc00
+                    || (segment & 0xffc0) == 0xfe80
+            }
+        },
+    }
+}
+
+/// The IPv4 address an IPv4-mapped (`::ffff:0:0/96`), IPv4-compatible
+/// (`::/96`), NAT64 (`64:ff9b::/96`) or 6to4 (`2002::/16`) address reaches
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    let octets = v6.octets();
+    let at = |start: usize| {
+        Ipv4Addr::new(
+            octets[start],
+            octets[start + 1],
+            octets[start + 2],
+            octets[start + 3],
+        )
+    };
+    match v6.segments() {
+        [0, 0, 0, 0, 0, 0 | 0xffff, _, _] | [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some(at(12)),
+        [0x2002, ..] => Some(at(2)),
+        _ => None,
+    }
+}
+
+fn network_contains(network: IpAddr, prefix: u8, addr: IpAddr) -> bool {
+    // IPv4-mapped IPv6 addresses are compared as IPv4
+    let addr = match addr {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(addr),
+        addr => addr,
+    };
+    match (network, addr) {
+        (IpAddr::V4(network), IpAddr::V4(addr)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(network) & mask == u32::from(addr) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(addr)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(network) & mask == u128::from(addr) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HostFilter;
+    use std::net::SocketAddr;
+
+    fn filter(allow: &[&str], deny: &[&str]) -> HostFilter {
+        let mut filter = HostFilter::default();
+        for pattern in allow {
+            filter.allow(pattern).expect("allow");
+        }
+        for pattern in deny {
+            filter.deny(pattern).expect("deny");
+        }
+        filter
+    }
+
+    fn addrs(ips: &[&str]) -> Vec<SocketAddr> {
+        ips.iter()
+            .map(|ip| SocketAddr::new(ip.parse().expect("ip"), 0))
+            .collect()
+    }
+
+    #[test]
+    fn deny_globs_and_networks_win() {
+        let filter = filter(&["*.example.com"], &["admin.example.com", "10.0.0.0/8"]);
+        assert!(filter.check_host("api.example.com").is_ok());
+        assert!(filter.check_host("API.Example.com").is_ok());
+        assert!(filter.check_host("admin.example.com").is_err());
+        assert!(filter.check_host("example.org").is_err());
+        assert!(filter.check_host("10.1.2.3").is_err());
+        assert!(filter
+            .filter_resolved("api.example.com", addrs(&["93.184.216.34", "10.9.9.9"]))
+            .is_err());
+        assert!(filter
+            .filter_resolved("api.example.com", addrs(&["::ffff:10.0.0.1"]))
+            .is_err());
+        assert_eq!(
+            filter
+                .filter_resolved("api.example.com", addrs(&["93.184.216.34"]))
+                .expect("allowed"),
+            addrs(&["93.184.216.34"])
+        );
+    }
+
+    #[test]
+    fn allow_networks_are_checked_after_resolution() {
+        let filter = filter(&["192.168.0.0/16", "fd00::/8"], &[]);
+        assert!(filter.check_host("192.168.1.10").is_ok());
+        assert!(filter.check_host("[fd12::1]").is_ok());
+        assert!(filter.check_host("8.8.8.8").is_err());
+        assert!(filter.check_host("printer.lan").is_ok());
+        assert_eq!(
+            filter
+                .filter_resolved("printer.lan", addrs(&["::1", "192.168.1.9"]))
+                .expect("allowed"),
+            addrs(&["192.168.1.9"])
+        );
+        assert!(filter
+            .filter_resolved("printer.lan", addrs(&["1.1.1.1"]))
+            .is_err());
+    }
+
+    #[test]
+    fn internal_targets_are_refused_in_urls_and_resolutions() {
+        let mut filter = HostFilter::default();
+        filter.block_internal();
+        for host in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "[::1]",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(
+                matches!(
+                    filter.check_host(host),
+                    Err(crate::error::RurlError::InternalTarget(_))
+                ),
+                "{}",
+                host
+            );
+        }
+        assert!(filter.check_host("93.184.216.34").is_ok());
+        assert!(filter.check_host("172.32.0.1").is_ok());
+        assert!(filter.check_host("example.com").is_ok());
+        assert!(filter
+            .filter_resolved("rebind.example", addrs(&["93.184.216.34", "10.0.0.5"]))
+            .is_err());
+        assert!(filter
+            .filter_resolved("example.com", addrs(&["2606:2800:220:1::"]))
+            .is_ok());
+    }
+
+    #[test]
+    fn embedded_ipv4_multicast_and_reserved_targets_are_internal() {
+        let mut filter = HostFilter::default();
+        filter.block_internal();
+        for host in [
+            // NAT64, 6to4 and IPv4-compatible forms of 127.0.0.1
+            "64:ff9b::7f00:1",
+            "2002:7f00:1::",
+            "::127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+            "ff02::1",
+            "224.0.0.1",
+            "239.255.255.250",
+            "240.0.0.1",
+            "255.255.255.255",
+        ] {
+            assert!(
+                matches!(
+                    filter.check_host(host),
+                    Err(crate::error::RurlError::InternalTarget(_))
+                ),
+                "{}",
+                host
+            );
+        }
+        // The same forms of a public address are allowed
+        assert!(filter.check_host("64:ff9b::5db8:d822").is_ok());
+        assert!(filter.check_host("2002:5db8:d822::").is_ok());
+        assert!(filter
+            .filter_resolved("nat64.example", addrs(&["64:ff9b::a00:5"]))
+            .is_err());
+    }
+
+    #[test]
+    fn patterns_reject_bad_networks() {
+        let mut filter = HostFilter::default();
+        assert!(filter.allow("10.0.0.0/33").is_err());
+        assert!(filter.allow("example.com/8").is_err());
+        assert!(filter.allow("").is_err());
+        assert!(!filter.is_active());
+        assert!(filter.deny("0.0.0.0/0").is_ok());
+        assert!(filter.check_host("127.0.0.1").is_err());
+    }
+}
@@ -1,7 +1,11 @@
 //! HTTP authentication utilities
 
+use crate::config::JwtConfig;
 use crate::error::{Result, RurlError};
 use base64::Engine;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Authentication types supported
 #[derive(Debug, Clone)]
@@ -27,6 +31,30 @@ impl Auth {
         format!("Bearer {}", token)
     }
 
+    /// Mint a short-lived JWT from `config`
+    pub fn sign_jwt(config: &JwtConfig) -> Result<String> {
+        let key = std::fs::read(&config.key_file).map_err(|e| {
+            RurlError::Auth(format!(
+                "Failed to read JWT key {:?}: {}",
+                config.key_file, e
+            ))
+        })?;
+        let (algorithm, encoding_key) = jwt_encoding_key(&key, config.algorithm.as_deref())?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut claims = config.claims.clone();
+        claims.entry("iat").or_insert(Value::from(now));
+        claims
+            .entry("exp")
+            .or_insert(Value::from(now + config.ttl.as_secs()));
+
+        jsonwebtoken::encode(&Header::new(algorithm), &claims, &encoding_key)
+            .map_err(|e| RurlError::Auth(format!("Failed to sign JWT: {}", e)))
+    }
+
     /// Parse user:password format
     pub fn parse_user_pass(input: &str) -> Result<(String, String)> {
         let parts: Vec<&str> = input.splitn(2, ':').collect();
@@ -38,9 +66,46 @@ impl Auth {
     }
 }
 
+/// Pick the algorithm and key: PEM keys are RSA or EC, anything else is an
+/// HMAC secret
+fn jwt_encoding_key(key: &[u8], algorithm: Option<&str>) -> Result<(Algorithm, EncodingKey)> {
+    let invalid =
+        |e: jsonwebtoken::errors::Error| RurlError::Auth(format!("Invalid JWT key: {}", e));
+    let is_pem = String::from_utf8_lossy(key).contains("-----BEGIN");
+
+    let algorithm = match algorithm {
+        Some(name) => name
+            .to_uppercase()
+            .parse::<Algorithm>()
+            .map_err(|_| RurlError::Auth(format!("Unsupported JWT algorithm: {}", name)))?,
+        None if !is_pem => Algorithm::HS256,
+        None if EncodingKey::from_rsa_pem(key).is_ok() => Algorithm::RS256,
+        None => Algorithm::ES256,
+    };
+
+    let encoding_key = match algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+            let secret = String::from_utf8_lossy(key);
+            EncodingKey::from_secret(secret.trim_end_matches(['\r', '\n']).as_bytes())
+        }
+        Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512 => EncodingKey::from_rsa_pem(key).map_err(invalid)?,
+        Algorithm::ES256 | Algorithm::ES384 => EncodingKey::from_ec_pem(key).map_err(invalid)?,
+        Algorithm::EdDSA => EncodingKey::from_ed_pem(key).map_err(invalid)?,
+    };
+    Ok((algorithm, encoding_key))
+}
+
 #[cfg(test)]
 mod tests {
     use super::Auth;
+    use crate::config::JwtConfig;
+    use base64::Engine;
+    use std::time::Duration;
 
     #[test]
     fn basic_auth_formats_header() {
@@ -69,4 +134,47 @@ mod tests {
         assert_eq!(user, "user");
         assert_eq!(pass, "pass:extra");
     }
+
+    #[test]
+    fn sign_jwt_uses_hmac_for_plain_secret() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let key_file = dir.path().join("secret");
+        std::fs::write(&key_file, "s3cret\n").expect("write");
+        let claims = serde_json::json!({"iss": "rurl", "exp": 42});
+        let config = JwtConfig {
+            key_file,
+            claims: claims.as_object().cloned().expect("object"),
+            algorithm: None,
+            ttl: Duration::from_secs(300),
+        };
+
+        let token = Auth::sign_jwt(&config).expect("token");
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        let decode = |part: &str| {
+            let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(part)
+                .expect("base64");
+            serde_json::from_slice::<serde_json::Value>(&bytes).expect("json")
+        };
+        assert_eq!(decode(parts[0])["alg"], "HS256");
+        let payload = decode(parts[1]);
+        assert_eq!(payload["iss"], "rurl");
+        assert_eq!(payload["exp"], 42);
+        assert!(payload["iat"].as_u64().is_some());
+    }
+
+    #[test]
+    fn sign_jwt_rejects_unknown_algorithm() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let key_file = dir.path().join("secret");
+        std::fs::write(&key_file, "s3cret").expect("write");
+        let config = JwtConfig {
+            key_file,
+            claims: serde_json::Map::new(),
+            algorithm: Some("none".to_string()),
+            ttl: Duration::from_secs(60),
+        };
+        assert!(Auth::sign_jwt(&config).is_err());
+    }
 }
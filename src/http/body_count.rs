@@ -0,0 +1,104 @@
+//! Body byte counts for `--stats` and the event log
+//!
+//! Request and response bodies are wrapped on their way through
+//! [`super::HttpClient::send`]; each publishes one event with the bytes that
+//! passed once it is dropped, so partly read bodies count what was read.
+//! Response bytes are counted after reqwest decodes `Content-Encoding`.
+
+use super::events::{EventBus, HttpEvent};
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Which way a counted body travels
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Direction {
+    Sent,
+    Received,
+}
+
+/// Passes a body through, counting its data bytes
+pub(crate) struct CountingBody<B> {
+    inner: B,
+    bytes: u64,
+    url: String,
+    direction: Direction,
+    events: EventBus,
+}
+
+impl<B> CountingBody<B> {
+    pub(crate) fn new(inner: B, url: &str, direction: Direction, events: EventBus) -> Self {
+        Self {
+            inner,
+            bytes: 0,
+            url: url.to_string(),
+            direction,
+            events,
+        }
+    }
+}
+
+impl<B: Body<Data = Bytes> + Unpin> Body for CountingBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, B::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                self.bytes += data.len() as u64;
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<B> Drop for CountingBody<B> {
+    fn drop(&mut self) {
+        let (url, bytes) = (std::mem::take(&mut self.url), self.bytes);
+        self.events.publish_with(|| match self.direction {
+            Direction::Sent => HttpEvent::BodySent { url, bytes },
+            Direction::Received => HttpEvent::BodyReceived { url, bytes },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CountingBody, Direction};
+    use crate::http::events::{EventBus, HttpEvent};
+    use http_body_util::{BodyExt, Full};
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn counts_bytes_read_before_drop() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut events = EventBus::default();
+        let recorder = seen.clone();
+        events.subscribe(move |event| recorder.lock().expect("lock").push(event.clone()));
+
+        let body = Full::new(bytes::Bytes::from_static(b"hello"));
+        let body = CountingBody::new(body, "http://a/", Direction::Received, events);
+        let collected = body.collect().await.expect("body").to_bytes();
+        assert_eq!(collected.as_ref(), b"hello");
+        assert_eq!(
+            *seen.lock().expect("lock"),
+            [HttpEvent::BodyReceived {
+                url: "http://a/".to_string(),
+                bytes: 5,
+            }]
+        );
+    }
+}
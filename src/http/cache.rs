@@ -0,0 +1,533 @@
+//! HTTP response cache (RFC 9111)
+//!
+//! Enabled with `--cache [DIR]`. Responses to GET requests are stored per URL,
+//! with one variant per combination of the request headers named by `Vary`.
+//! Fresh entries are served without contacting the server, stale entries are
+//! revalidated with `If-None-Match`/`If-Modified-Since`.
+
+use crate::config::CacheMode;
+use crate::error::{Result, RurlError};
+use crate::utils::StringUtils;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+/// Statuses that may be cached without explicit freshness (RFC 9110 15.1)
+const HEURISTICALLY_CACHEABLE: &[u16] = &[200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+/// How a response was obtained, for verbose output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+    Revalidated,
+}
+
+/// On-disk HTTP cache
+pub struct HttpCache {
+    dir: PathBuf,
+    mode: CacheMode,
+}
+
+/// A stored response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Request header values selected by the response's `Vary`
+    pub vary: Vec<(String, Option<String>)>,
+    pub request_time: SystemTime,
+    pub response_time: SystemTime,
+    #[serde(skip)]
+    pub body: Vec<u8>,
+}
+
+impl HttpCache {
+    /// Cache stored in `dir`
+    pub fn new(dir: PathBuf, mode: CacheMode) -> Self {
+        Self { dir, mode }
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// Find the stored variant matching the request headers
+    pub fn lookup(&self, url: &Url, request_headers: &HeaderMap) -> Option<CachedResponse> {
+        let variants = self.read_index(url);
+        let mut entry = variants
+            .into_iter()
+            .find(|entry| entry.matches_vary(request_headers))?;
+        entry.body = fs::read(self.body_path(url, &entry.vary)).ok()?;
+        Some(entry)
+    }
+
+    /// Store a response, replacing the variant with the same `Vary` values
+    pub fn store(&self, url: &Url, entry: &CachedResponse) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.body_path(url, &entry.vary), &entry.body)?;
+
+        let mut variants = self.read_index(url);
+        variants.retain(|existing| existing.vary != entry.vary);
+        variants.push(entry.clone());
+        fs::write(self.index_path(url), serde_json::to_vec(&variants)?)?;
+        Ok(())
+    }
+
+    /// Drop every stored variant of `url`
+    pub fn invalidate(&self, url: &Url) {
+        for entry in self.read_index(url) {
+            let _ = fs::remove_file(self.body_path(url, &entry.vary));
+        }
+        let _ = fs::remove_file(self.index_path(url));
+    }
+
+    fn read_index(&self, url: &Url) -> Vec<CachedResponse> {
+        fs::read(self.index_path(url))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn index_path(&self, url: &Url) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", hex_sha1(cache_key(url).as_bytes())))
+    }
+
+    fn body_path(&self, url: &Url, vary: &[(String, Option<String>)]) -> PathBuf {
+        let mut key = cache_key(url);
+        for (name, value) in vary {
+            key.push('\n');
+            key.push_str(name);
+            key.push(':');
+            key.push_str(value.as_deref().unwrap_or(""));
+        }
+        self.dir.join(format!("{}.body", hex_sha1(key.as_bytes())))
+    }
+}
+
+impl CachedResponse {
+    /// Capture a response for storage; check [`is_storable`] first
+    pub fn new(
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: Vec<u8>,
+        request_headers: &HeaderMap,
+        request_time: SystemTime,
+    ) -> Self {
+        Self {
+            status: status.as_u16(),
+            headers: header_pairs(headers),
+            vary: vary_values(headers, request_headers).unwrap_or_default(),
+            request_time,
+            response_time: SystemTime::now(),
+            body,
+        }
+    }
+
+    /// Response headers as a `HeaderMap`
+    pub fn header_map(&self) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                map.append(name, value);
+            }
+        }
+        map
+    }
+
+    /// Whether the response can be served without revalidation
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        let headers = self.header_map();
+        if has_directive(&headers, "no-cache") {
+            return false;
+        }
+        freshness_lifetime(&headers).is_some_and(|lifetime| self.current_age(now) < lifetime)
+    }
+
+    /// Add conditional request headers from the stored validators
+    pub fn add_validators(&self, request_headers: &mut HeaderMap) {
+        let headers = self.header_map();
+        if let Some(etag) = headers.get("etag") {
+            request_headers.insert("if-none-match", etag.clone());
+        }
+        if let Some(last_modified) = headers.get("last-modified") {
+            request_headers.insert("if-modified-since", last_modified.clone());
+        }
+    }
+
+    /// Merge the headers of a `304 Not Modified` into the stored response
+    pub fn refresh(&mut self, not_modified: &HeaderMap) {
+        let mut headers = self.header_map();
+        for name in not_modified.keys() {
+            headers.remove(name);
+            for value in not_modified.get_all(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        self.headers = header_pairs(&headers);
+        self.response_time = SystemTime::now();
+        self.request_time = self.response_time;
+    }
+
+    /// Build a response to hand to the caller
+    pub fn to_response(&self) -> Result<reqwest::Response> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let response = builder
+            .body(self.body.clone())
+            .map_err(|e| RurlError::Config(format!("Invalid cached response: {}", e)))?;
+        Ok(reqwest::Response::from(response))
+    }
+
+    fn matches_vary(&self, request_headers: &HeaderMap) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, value)| header_string(request_headers, name) == *value)
+    }
+
+    /// Age per RFC 9111 section 4.2.3
+    fn current_age(&self, now: SystemTime) -> Duration {
+        let headers = self.header_map();
+        let age_value = headers
+            .get("age")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+        let apparent_age = headers
+            .get("date")
+            .and_then(|v| v.to_str().ok())
+            .and_then(StringUtils::parse_http_date)
+            .and_then(|date| self.response_time.duration_since(date).ok())
+            .unwrap_or_default();
+        let response_delay = self
+            .response_time
+            .duration_since(self.request_time)
+            .unwrap_or_default();
+        let resident_time = now.duration_since(self.response_time).unwrap_or_default();
+        apparent_age.max(age_value + response_delay) + resident_time
+    }
+}
+
+/// Response served when `only-if-cached` finds nothing (RFC 9111 5.2.1.7)
+pub fn gateway_timeout_response() -> Result<reqwest::Response> {
+    let response = http::Response::builder()
+        .status(StatusCode::GATEWAY_TIMEOUT)
+        .body(Vec::new())
+        .map_err(|e| RurlError::Config(format!("Invalid cached response: {}", e)))?;
+    Ok(reqwest::Response::from(response))
+}
+
+/// Default cache directory (`~/.cache/rurl/http` on Linux)
+pub fn default_cache_dir() -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir()
+        .ok_or_else(|| RurlError::Config("Cannot determine cache directory".to_string()))?;
+    Ok(cache_dir.join("rurl").join("http"))
+}
+
+/// Whether a request carries `Cache-Control: no-cache` or `Pragma: no-cache`
+pub fn request_forbids_cached(request_headers: &HeaderMap) -> bool {
+    has_directive(request_headers, "no-cache")
+        || has_directive(request_headers, "max-age=0")
+        || request_headers
+            .get("pragma")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("no-cache"))
+}
+
+/// Parse `Cache-Control` into lowercase directives with optional values
+pub fn cache_control(headers: &HeaderMap) -> Vec<(String, Option<String>)> {
+    headers
+        .get_all("cache-control")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|directive| {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                return None;
+            }
+            Some(match directive.split_once('=') {
+                Some((name, value)) => (
+                    name.trim().to_ascii_lowercase(),
+                    Some(value.trim().trim_matches('"').to_string()),
+                ),
+                None => (directive.to_ascii_lowercase(), None),
+            })
+        })
+        .collect()
+}
+
+fn has_directive(headers: &HeaderMap, directive: &str) -> bool {
+    let (name, value) = match directive.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (directive, None),
+    };
+    cache_control(headers)
+        .iter()
+        .any(|(n, v)| n == name && (value.is_none() || v.as_deref() == value))
+}
+
+/// Freshness lifetime from `max-age`, `Expires`, or the `Last-Modified`
+/// heuristic (10% of the time since the last modification)
+pub fn freshness_lifetime(headers: &HeaderMap) -> Option<Duration> {
+    let directives = cache_control(headers);
+    if let Some(max_age) = directives
+        .iter()
+        .find(|(name, _)| name == "max-age")
+        .and_then(|(_, value)| value.as_deref()?.parse::<u64>().ok())
+    {
+        return Some(Duration::from_secs(max_age));
+    }
+
+    let date = headers
+        .get("date")
+        .and_then(|v| v.to_str().ok())
+        .and_then(StringUtils::parse_http_date)
+        .unwrap_or_else(SystemTime::now);
+    if let Some(expires) = headers.get("expires").and_then(|v| v.to_str().ok()) {
+        // Invalid dates such as "0" mean already expired
+        return Some(
+            StringUtils::parse_http_date(expires)
+                .and_then(|expires| expires.duration_since(date).ok())
+                .unwrap_or_default(),
+        );
+    }
+
+    headers
+        .get("last-modified")
+        .and_then(|v| v.to_str().ok())
+        .and_then(StringUtils::parse_http_date)
+        .and_then(|modified| date.duration_since(modified).ok())
+        .map(|since| since / 10)
+}
+
+/// Whether a response may be stored (RFC 9111 section 3)
+pub fn is_storable(status: StatusCode, headers: &HeaderMap, request_headers: &HeaderMap) -> bool {
+    if has_directive(request_headers, "no-store") || has_directive(headers, "no-store") {
+        return false;
+    }
+    if vary_values(headers, request_headers).is_none() {
+        return false;
+    }
+    if status == StatusCode::PARTIAL_CONTENT || status == StatusCode::NOT_MODIFIED {
+        return false;
+    }
+    let explicit = cache_control(headers)
+        .iter()
+        .any(|(name, _)| matches!(name.as_str(), "max-age" | "public" | "no-cache"))
+        || headers.contains_key("expires");
+    explicit || HEURISTICALLY_CACHEABLE.contains(&status.as_u16())
+}
+
+/// Request header values named by `Vary`, or `None` for `Vary: *`
+fn vary_values(
+    headers: &HeaderMap,
+    request_headers: &HeaderMap,
+) -> Option<Vec<(String, Option<String>)>> {
+    let mut names: Vec<String> = headers
+        .get_all("vary")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if names.iter().any(|name| name == "*") {
+        return None;
+    }
+    names.sort();
+    names.dedup();
+    Some(
+        names
+            .into_iter()
+            .map(|name| {
+                let value = header_string(request_headers, &name);
+                (name, value)
+            })
+            .collect(),
+    )
+}
+
+fn header_string(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(", "))
+    }
+}
+
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// URL without the fragment, which is never sent to the server
+fn cache_key(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.to_string()
+}
+
+fn hex_sha1(data: &[u8]) -> String {
+    Sha1::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cache_control, freshness_lifetime, is_storable, CachedResponse, HttpCache};
+    use crate::config::CacheMode;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(*name, HeaderValue::from_str(value).expect("value"));
+        }
+        map
+    }
+
+    #[test]
+    fn cache_control_parses_directives() {
+        let directives = cache_control(&headers(&[(
+            "cache-control",
+            "public, Max-Age=60, no-cache=\"set-cookie\"",
+        )]));
+        assert_eq!(directives[0], ("public".to_string(), None));
+        assert_eq!(
+            directives[1],
+            ("max-age".to_string(), Some("60".to_string()))
+        );
+        assert_eq!(
+            directives[2],
+            ("no-cache".to_string(), Some("set-cookie".to_string()))
+        );
+    }
+
+    #[test]
+    fn freshness_lifetime_prefers_max_age_over_expires() {
+        let lifetime = freshness_lifetime(&headers(&[
+            ("cache-control", "max-age=120"),
+            ("expires", "Thu, 01 Jan 1970 00:00:00 GMT"),
+        ]));
+        assert_eq!(lifetime, Some(Duration::from_secs(120)));
+
+        let lifetime = freshness_lifetime(&headers(&[
+            ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+            ("expires", "Sun, 06 Nov 1994 08:50:37 GMT"),
+        ]));
+        assert_eq!(lifetime, Some(Duration::from_secs(60)));
+        assert_eq!(
+            freshness_lifetime(&headers(&[("expires", "0")])),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(freshness_lifetime(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn is_storable_rejects_no_store_and_vary_star() {
+        let request = HeaderMap::new();
+        assert!(is_storable(StatusCode::OK, &HeaderMap::new(), &request));
+        assert!(!is_storable(
+            StatusCode::OK,
+            &headers(&[("cache-control", "no-store")]),
+            &request
+        ));
+        assert!(!is_storable(
+            StatusCode::OK,
+            &headers(&[("vary", "*")]),
+            &request
+        ));
+        assert!(!is_storable(
+            StatusCode::CREATED,
+            &HeaderMap::new(),
+            &request
+        ));
+        assert!(is_storable(
+            StatusCode::CREATED,
+            &headers(&[("cache-control", "max-age=5")]),
+            &request
+        ));
+    }
+
+    #[test]
+    fn store_and_lookup_respects_vary() {
+        let dir = tempdir().expect("tempdir");
+        let cache = HttpCache::new(dir.path().to_path_buf(), CacheMode::Default);
+        let url = Url::parse("https://example.com/data#frag").expect("url");
+        let response_headers = headers(&[
+            ("cache-control", "max-age=60"),
+            ("vary", "Accept-Language"),
+            ("etag", "\"v1\""),
+        ]);
+        let english = headers(&[("accept-language", "en")]);
+        let entry = CachedResponse::new(
+            StatusCode::OK,
+            &response_headers,
+            b"hello".to_vec(),
+            &english,
+            SystemTime::now(),
+        );
+        cache.store(&url, &entry).expect("store");
+
+        let hit = cache.lookup(&url, &english).expect("hit");
+        assert_eq!(hit.body, b"hello");
+        assert!(hit.is_fresh(SystemTime::now()));
+        assert!(!hit.is_fresh(SystemTime::now() + Duration::from_secs(120)));
+        assert!(cache
+            .lookup(&url, &headers(&[("accept-language", "ja")]))
+            .is_none());
+
+        let mut request = HeaderMap::new();
+        hit.add_validators(&mut request);
+        assert_eq!(request.get("if-none-match").expect("etag"), "\"v1\"");
+
+        cache.invalidate(&url);
+        assert!(cache.lookup(&url, &english).is_none());
+    }
+
+    #[test]
+    fn refresh_merges_not_modified_headers() {
+        let mut entry = CachedResponse::new(
+            StatusCode::OK,
+            &headers(&[("cache-control", "max-age=0"), ("etag", "\"v1\"")]),
+            Vec::new(),
+            &HeaderMap::new(),
+            SystemTime::now(),
+        );
+        assert!(!entry.is_fresh(SystemTime::now()));
+        entry.refresh(&headers(&[("cache-control", "max-age=60")]));
+        assert!(entry.is_fresh(SystemTime::now()));
+        assert_eq!(entry.header_map().get("etag").expect("etag"), "\"v1\"");
+    }
+}
@@ -0,0 +1,138 @@
+//! `--dry-run` request descriptions
+//!
+//! The first request is built exactly as it would be sent, cookies and
+//! credentials included, and described as an HTTP message instead. Secrets
+//! are redacted unless `--show-secrets` is given: credentials keep their
+//! scheme and cookies their names. Headers the HTTP backend adds while
+//! sending (`Accept`, `Accept-Encoding`) are not part of the request yet.
+
+use super::host_header_value;
+use crate::error::{Result, RurlError};
+use reqwest::header::{CONTENT_TYPE, HOST};
+use reqwest::ResponseBuilderExt;
+
+/// Text bodies up to this size are shown in full
+const BODY_PREVIEW_LIMIT: usize = 4096;
+
+const REDACTED: &str = "<redacted>";
+
+/// `request` as `METHOD URL`, its headers and a body summary
+pub(crate) fn describe(request: &reqwest::Request, show_secrets: bool) -> String {
+    let mut text = format!("{} {}\n", request.method(), request.url());
+    if !request.headers().contains_key(HOST) {
+        text.push_str(&format!("host: {}\n", host_header_value(request.url())));
+    }
+    for (name, value) in request.headers() {
+        let value = value.to_str().unwrap_or("<non-utf8>");
+        let value = match show_secrets {
+            true => value.to_string(),
+            false => redact(name.as_str(), value),
+        };
+        text.push_str(&format!("{}: {}\n", name, value));
+    }
+    text.push('\n');
+
+    let Some(body) = request.body() else {
+        return text;
+    };
+    match body.as_bytes() {
+        Some([]) => {}
+        Some(bytes) => match std::str::from_utf8(bytes) {
+            Ok(body) if bytes.len() <= BODY_PREVIEW_LIMIT => {
+                text.push_str(body);
+                if !body.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            Ok(_) => text.push_str(&format!("[{} bytes of text]\n", bytes.len())),
+            Err(_) => text.push_str(&format!("[{} bytes of binary data]\n", bytes.len())),
+        },
+        None => text.push_str("[streamed body: file upload or multipart form]\n"),
+    }
+    text
+}
+
+/// Plain-text response carrying the description, in place of the real one
+pub(crate) fn response(
+    request: &reqwest::Request,
+    show_secrets: bool,
+) -> Result<reqwest::Response> {
+    let response = http::Response::builder()
+        .url(request.url().clone())
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(describe(request, show_secrets))
+        .map_err(|e| RurlError::Config(format!("Cannot describe request: {}", e)))?;
+    Ok(reqwest::Response::from(response))
+}
+
+fn redact(name: &str, value: &str) -> String {
+    match name {
+        "authorization" | "proxy-authorization" => match value.split_once(' ') {
+            Some((scheme, _)) => format!("{} {}", scheme, REDACTED),
+            None => REDACTED.to_string(),
+        },
+        "cookie" => value
+            .split(';')
+            .map(|pair| match pair.trim().split_once('=') {
+                Some((name, _)) => format!("{}={}", name, REDACTED),
+                None => pair.trim().to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("; "),
+        name if ["token", "secret", "api-key", "apikey"]
+            .iter()
+            .any(|part| name.contains(part)) =>
+        {
+            REDACTED.to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::describe;
+
+    fn request() -> reqwest::Request {
+        reqwest::Client::new()
+            .post("https://api.example.com/v1/items?page=2")
+            .header("Authorization", "Bearer abc.def")
+            .header("Cookie", "session=s3cret; theme=dark")
+            .header("X-Api-Key", "k")
+            .header("Content-Type", "application/json")
+            .body(r#"{"name":"x"}"#)
+            .build()
+            .expect("request")
+    }
+
+    #[test]
+    fn describe_redacts_secrets_unless_asked() {
+        assert_eq!(
+            describe(&request(), false),
+            "POST https://api.example.com/v1/items?page=2\n\
+             host: api.example.com\n\
+             authorization: Bearer <redacted>\n\
+             cookie: session=<redacted>; theme=<redacted>\n\
+             x-api-key: <redacted>\n\
+             content-type: application/json\n\
+             \n\
+             {\"name\":\"x\"}\n"
+        );
+        let shown = describe(&request(), true);
+        assert!(shown.contains("authorization: Bearer abc.def\n"));
+        assert!(shown.contains("cookie: session=s3cret; theme=dark\n"));
+    }
+
+    #[test]
+    fn describe_summarizes_binary_bodies() {
+        let request = reqwest::Client::new()
+            .put("http://example.com:8080/blob")
+            .body(vec![0xff, 0x00, 0x10])
+            .build()
+            .expect("request");
+        assert_eq!(
+            describe(&request, false),
+            "PUT http://example.com:8080/blob\nhost: example.com:8080\n\n[3 bytes of binary data]\n"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Request lifecycle events
+//!
+//! `HttpClient` publishes an [`HttpEvent`] at each step of a transfer. The CLI
+//! subscribes a JSON writer for `--log-format json`; library users can
+//! subscribe their own handlers with [`super::HttpClient::on_event`]. With
+//! `-v` the client prints connection, TLS and redirect notices itself, and
+//! with `-vv` DNS lookups and cookie decisions too.
+
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// One step of a transfer; field names are stable for machine consumers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HttpEvent {
+    /// A request is about to be sent; `attempt` starts at 1 and counts retries
+    RequestStart {
+        method: String,
+        url: String,
+        attempt: u32,
+        /// Sent in the `--request-id` header
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+    },
+    /// A response (final, redirect or error status) arrived
+    Response {
+        url: String,
+        status: u16,
+        version: String,
+        elapsed_ms: u64,
+    },
+    /// A redirect is being followed
+    Redirect {
+        from: String,
+        to: String,
+        status: u16,
+    },
+    /// The request will be sent again after `delay_ms`
+    Retry {
+        attempt: u32,
+        reason: String,
+        delay_ms: u64,
+    },
+    /// The transfer failed
+    Error { message: String, exit_code: i32 },
+    /// A host name was looked up
+    Resolved { host: String, addrs: Vec<String> },
+    /// A response arrived over a connection to `remote_addr`
+    Connected {
+        url: String,
+        remote_addr: Option<String>,
+        /// Local end of the connection; the same pair means a reused one
+        local_addr: Option<String>,
+        version: String,
+        tls: bool,
+        /// Protocol the server picked during the TLS handshake
+        alpn: Option<String>,
+        /// Whether the server certificate was verified; `None` without TLS
+        cert_verified: Option<bool>,
+    },
+    /// A request body finished sending, or was dropped after `bytes`
+    BodySent { url: String, bytes: u64 },
+    /// A response body was read to the end, or dropped after `bytes`
+    BodyReceived { url: String, bytes: u64 },
+    /// A stored cookie was sent, or skipped for `reason`
+    CookieDecision {
+        name: String,
+        domain: String,
+        path: String,
+        sent: bool,
+        reason: Option<String>,
+    },
+}
+
+pub type EventHandler = Arc<dyn Fn(&HttpEvent) + Send + Sync>;
+
+/// Fan-out of events to subscribed handlers; clones share their handlers,
+/// so the DNS resolver sees handlers subscribed after the client was built
+#[derive(Clone, Default)]
+pub struct EventBus {
+    handlers: Arc<RwLock<Vec<EventHandler>>>,
+}
+
+impl EventBus {
+    pub fn subscribe<F>(&mut self, handler: F)
+    where
+        F: Fn(&HttpEvent) + Send + Sync + 'static,
+    {
+        self.handlers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(handler));
+    }
+
+    pub fn publish(&self, event: &HttpEvent) {
+        let handlers = self.handlers.read().unwrap_or_else(|e| e.into_inner());
+        for handler in handlers.iter() {
+            handler(event);
+        }
+    }
+
+    /// Publish the event built by `event` only when someone listens
+    pub fn publish_with<F>(&self, event: F)
+    where
+        F: FnOnce() -> HttpEvent,
+    {
+        if !self.is_empty() {
+            self.publish(&event());
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.handlers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field(
+                "handlers",
+                &self
+                    .handlers
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .len(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventBus, HttpEvent};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn events_serialize_with_event_tag() {
+        let event = HttpEvent::Redirect {
+            from: "http://a/".to_string(),
+            to: "http://b/".to_string(),
+            status: 302,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).expect("json"),
+            r#"{"event":"redirect","from":"http://a/","to":"http://b/","status":302}"#
+        );
+    }
+
+    #[test]
+    fn bus_delivers_to_every_handler() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = EventBus::default();
+        for _ in 0..2 {
+            let seen = seen.clone();
+            bus.subscribe(move |event| seen.lock().expect("lock").push(event.clone()));
+        }
+        bus.publish_with(|| HttpEvent::Error {
+            message: "boom".to_string(),
+            exit_code: 7,
+        });
+        assert_eq!(seen.lock().expect("lock").len(), 2);
+    }
+
+    #[test]
+    fn clones_share_handlers_subscribed_later() {
+        let seen = Arc::new(Mutex::new(0));
+        let mut bus = EventBus::default();
+        let clone = bus.clone();
+        let counter = seen.clone();
+        bus.subscribe(move |_| *counter.lock().expect("lock") += 1);
+        clone.publish(&HttpEvent::Resolved {
+            host: "example.com".to_string(),
+            addrs: vec!["192.0.2.1".to_string()],
+        });
+        assert_eq!(*seen.lock().expect("lock"), 1);
+    }
+}
@@ -0,0 +1,371 @@
+//! Indentation of XML and HTML bodies, and the links in HTML
+//!
+//! The indenter is fed text in arbitrary chunks, so spooled bodies can be
+//! formatted while streaming. It does not validate: malformed markup is
+//! passed through with a best-effort layout.
+
+use url::Url;
+
+const INDENT: &str = "  ";
+
+/// HTML elements that never have content or a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// HTML elements whose content is written exactly as received
+const RAW_TEXT_ELEMENTS: &[&str] = &["pre", "script", "style", "textarea"];
+
+/// Attributes that hold a link (`--list-links`)
+const LINK_ATTRIBUTES: &[&str] = &["href", "src"];
+
+/// Puts every element on its own line, indented by nesting depth; an element
+/// holding only text stays on one line
+#[derive(Debug, Default)]
+pub(crate) struct MarkupIndenter {
+    html: bool,
+    depth: usize,
+    /// Markup token read so far, starting at its `<`
+    token: String,
+    in_token: bool,
+    quote: Option<char>,
+    /// Text since the last token
+    text: String,
+    /// The last token written opened an element that may hold only text
+    open: bool,
+    /// Inside an HTML raw text element, waiting for `</name`
+    raw: Option<String>,
+    started: bool,
+}
+
+impl MarkupIndenter {
+    /// `html` enables void and raw text elements
+    pub(crate) fn new(html: bool) -> Self {
+        Self {
+            html,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn push(&mut self, input: &str) -> String {
+        let mut out = String::with_capacity(input.len() + input.len() / 4);
+        for c in input.chars() {
+            if self.in_token {
+                self.token.push(c);
+                if self.token_complete(c) {
+                    let token = std::mem::take(&mut self.token);
+                    self.in_token = false;
+                    self.emit_token(&token, &mut out);
+                }
+            } else if let Some(name) = &self.raw {
+                self.text.push(c);
+                let close = format!("</{}", name);
+                let start = self.text.len().saturating_sub(close.len());
+                if self.text.is_char_boundary(start)
+                    && self.text[start..].eq_ignore_ascii_case(&close)
+                {
+                    self.token = self.text.split_off(start);
+                    self.in_token = true;
+                }
+            } else if c == '<' {
+                self.token.push(c);
+                self.in_token = true;
+            } else {
+                self.text.push(c);
+            }
+        }
+        out
+    }
+
+    /// Write whatever is still buffered at the end of the body
+    pub(crate) fn finish(&mut self) -> String {
+        let mut out = String::new();
+        if self.raw.take().is_some() {
+            out.push_str(&std::mem::take(&mut self.text));
+        } else {
+            self.flush_text(&mut out);
+        }
+        out.push_str(&std::mem::take(&mut self.token));
+        self.in_token = false;
+        out
+    }
+
+    fn token_complete(&mut self, c: char) -> bool {
+        let token = self.token.as_str();
+        if token.starts_with("<!--") {
+            return token.len() >= 7 && token.ends_with("-->");
+        }
+        if token.starts_with("<![CDATA[") {
+            return token.ends_with("]]>");
+        }
+        if "<!--".starts_with(token) || "<![CDATA[".starts_with(token) {
+            return false;
+        }
+        match self.quote {
+            Some(quote) if c == quote => self.quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => self.quote = Some(c),
+            None => return c == '>',
+        }
+        false
+    }
+
+    fn emit_token(&mut self, token: &str, out: &mut String) {
+        if let Some(name) = token.strip_prefix("</") {
+            let name = tag_name(name);
+            if let Some(raw) = self.raw.take() {
+                if !raw.eq_ignore_ascii_case(name) {
+                    self.text.push_str(token);
+                    self.raw = Some(raw);
+                    return;
+                }
+                // Raw text stays glued to its tags
+                out.push_str(&std::mem::take(&mut self.text));
+                out.push_str(token);
+                return;
+            }
+            let text = self.text.trim();
+            if self.open && !text.contains('\n') {
+                out.push_str(text);
+                self.text.clear();
+                self.depth = self.depth.saturating_sub(1);
+            } else {
+                self.flush_text(out);
+                self.depth = self.depth.saturating_sub(1);
+                self.newline(out);
+            }
+            out.push_str(token);
+            self.open = false;
+            return;
+        }
+
+        self.flush_text(out);
+        self.newline(out);
+        out.push_str(token);
+        self.open = false;
+        if token.starts_with("<!") || token.starts_with("<?") || token.ends_with("/>") {
+            return;
+        }
+        let name = tag_name(&token[1..]);
+        if self.html
+            && VOID_ELEMENTS
+                .iter()
+                .any(|void| void.eq_ignore_ascii_case(name))
+        {
+            return;
+        }
+        if self.html
+            && RAW_TEXT_ELEMENTS
+                .iter()
+                .any(|raw| raw.eq_ignore_ascii_case(name))
+        {
+            self.raw = Some(name.to_string());
+            return;
+        }
+        self.depth += 1;
+        self.open = true;
+    }
+
+    /// Write pending text on its own line
+    fn flush_text(&mut self, out: &mut String) {
+        let text = std::mem::take(&mut self.text);
+        let text = text.trim();
+        if !text.is_empty() {
+            self.newline(out);
+            out.push_str(text);
+            self.open = false;
+        }
+    }
+
+    fn newline(&mut self, out: &mut String) {
+        if self.started {
+            out.push('\n');
+            for _ in 0..self.depth {
+                out.push_str(INDENT);
+            }
+        }
+        self.started = true;
+    }
+}
+
+/// Element name at the start of a tag, after `<` or `</`
+fn tag_name(tag: &str) -> &str {
+    let end = tag
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(tag.len());
+    &tag[..end]
+}
+
+/// Absolute http(s) URLs in the `href` and `src` attributes of `html`, in
+/// document order without repeats or fragments; relative links are resolved
+/// against `<base href>` when there is one, else against `base`
+pub(crate) fn links(html: &str, base: &Url) -> Vec<Url> {
+    let mut base = base.clone();
+    let mut links: Vec<Url> = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let end = tag_end(rest);
+        let tag = &rest[..end];
+        rest = rest.get(end + 1..).unwrap_or("");
+        let name = tag_name(tag).to_ascii_lowercase();
+        for (attribute, value) in attributes(tag) {
+            let value = value.trim();
+            // `#section` points back into the same page
+            if !LINK_ATTRIBUTES.contains(&attribute.as_str()) || value.starts_with('#') {
+                continue;
+            }
+            let Ok(mut url) = base.join(&decode_entities(value)) else {
+                continue;
+            };
+            if name == "base" && attribute == "href" {
+                base = url;
+                continue;
+            }
+            if !matches!(url.scheme(), "http" | "https") {
+                continue;
+            }
+            url.set_fragment(None);
+            if !links.contains(&url) {
+                links.push(url);
+            }
+        }
+        if name == "script" || name == "style" {
+            let close = format!("</{}", name);
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .map_or("", |end| &rest[end..]);
+        }
+    }
+    links
+}
+
+/// Offset of the `>` closing a tag, skipping quoted attribute values
+fn tag_end(tag: &str) -> usize {
+    let mut quote = None;
+    for (offset, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => return offset,
+            _ => {}
+        }
+    }
+    tag.len()
+}
+
+/// `(name, value)` attributes of a start tag, names lower-cased
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = &tag[tag_name(tag).len()..];
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            return attributes;
+        }
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..end].to_ascii_lowercase();
+        rest = rest[end..].trim_start();
+        let Some(value) = rest.strip_prefix('=') else {
+            attributes.push((name, String::new()));
+            continue;
+        };
+        let value = value.trim_start();
+        let (value, after) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let inner = &value[1..];
+                let end = inner.find(quote).unwrap_or(inner.len());
+                (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+            }
+            _ => value.split_at(value.find(char::is_whitespace).unwrap_or(value.len())),
+        };
+        attributes.push((name, value.to_string()));
+        rest = after;
+    }
+}
+
+/// Undo the character references common in attribute values
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{links, MarkupIndenter};
+    use url::Url;
+
+    #[test]
+    fn links_are_absolute_and_unique() {
+        let html = r##"<!doctype html><html><head>
+            <link rel=stylesheet href="/css/site.css">
+            <!-- <a href="/commented-out"> -->
+            <script src='app.js?v=1&amp;x=2'>if (a<b) { go("<a href='/in-script'>"); }</script>
+        </head><body>
+            <A HREF="docs/guide.html#intro">Guide</A>
+            <img alt="a > b" src="//cdn.example.com/logo.png">
+            <a href="docs/guide.html">again</a>
+            <a href="mailto:me@example.com">mail</a> <a href="#top">top</a>
+        </body></html>"##;
+        let base = Url::parse("https://example.com/dir/page.html").expect("base");
+        let links: Vec<String> = links(html, &base).iter().map(Url::to_string).collect();
+        assert_eq!(
+            links,
+            [
+                "https://example.com/css/site.css",
+                "https://example.com/dir/app.js?v=1&x=2",
+                "https://example.com/dir/docs/guide.html",
+                "https://cdn.example.com/logo.png",
+            ]
+        );
+    }
+
+    #[test]
+    fn base_element_changes_where_links_resolve() {
+        let html = r#"<base href="https://static.example.com/v2/"><img src=pic.png>"#;
+        let base = Url::parse("https://example.com/").expect("base");
+        assert_eq!(
+            links(html, &base),
+            [Url::parse("https://static.example.com/v2/pic.png").expect("url")]
+        );
+    }
+
+    fn indent(html: bool, chunks: &[&str]) -> String {
+        let mut indenter = MarkupIndenter::new(html);
+        let mut out: String = chunks.iter().map(|chunk| indenter.push(chunk)).collect();
+        out.push_str(&indenter.finish());
+        out
+    }
+
+    #[test]
+    fn indents_nested_xml_and_keeps_text_inline() {
+        let body = r#"<?xml version="1.0"?><feed><!-- a > b --><entry id="x>y"><title>Hi</title><link href="/a"/></entry></feed>"#;
+        let expected = "<?xml version=\"1.0\"?>\n<feed>\n  <!-- a > b -->\n  <entry id=\"x>y\">\n    <title>Hi</title>\n    <link href=\"/a\"/>\n  </entry>\n</feed>";
+        assert_eq!(indent(false, &[body]), expected);
+        // Token and text boundaries may fall anywhere
+        let (first, second) = body.split_at(40);
+        assert_eq!(indent(false, &[first, second]), expected);
+    }
+
+    #[test]
+    fn html_void_and_raw_text_elements() {
+        let body = "<html><head><meta charset=utf-8><script>if (a<b) x();</SCRIPT></head><body><br><p>Hi</p></body></html>";
+        assert_eq!(
+            indent(true, &[body]),
+            "<html>\n  <head>\n    <meta charset=utf-8>\n    <script>if (a<b) x();</SCRIPT>\n  </head>\n  <body>\n    <br>\n    <p>Hi</p>\n  </body>\n</html>"
+        );
+    }
+}
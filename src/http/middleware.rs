@@ -0,0 +1,31 @@
+//! Interceptor hooks on `HttpClient`
+//!
+//! Interceptors run inside the redirect and retry loops, so library users can
+//! mutate requests (e.g. inject refreshed credentials), inspect responses and
+//! veto redirects without reimplementing them. Every hook has a no-op default.
+
+use crate::error::Result;
+use reqwest::{Request, Response, StatusCode};
+use url::Url;
+
+pub trait Interceptor: Send + Sync {
+    /// Called for every request, including redirects and retries, before it is
+    /// sent; changes are applied to the request that goes out
+    fn on_request(&self, _request: &mut Request) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with each response before redirects are followed
+    fn on_response(&self, _response: &Response) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called before following a redirect; an error stops the transfer
+    fn on_redirect(&self, _from: &Url, _to: &Url, _status: StatusCode) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called before attempt `attempt + 1` is sent; `reason` is an HTTP status
+    /// (`HTTP 503`) or the transport error
+    fn on_retry(&self, _attempt: u32, _reason: &str) {}
+}
@@ -2,34 +2,142 @@
 //!
 //! This module provides the core HTTP/HTTPS client functionality.
 
-use crate::browser::BrowserCookieExtractor;
-use crate::config::{Config, HttpMethod};
+use crate::browser::{
+    cookie_decisions, cookie_filter_domain, cookies_to_header, BrowserCookieExtractor, CookieStore,
+};
+use crate::config::{
+    CacheMode, Config, HeaderList, HttpMethod, IdempotencyKey, ProxyConfig, UploadSource,
+    VerbosityLevel,
+};
+use crate::cookie_source::CookieSource;
+use crate::cookie_store::{parse_set_cookie, upsert_cookie, PersistentCookieStore};
+use crate::credentials::CredentialStore;
+use crate::daemon::{AgentClient, ClientOptions};
 use crate::error::{Result, RurlError};
-use crate::utils::FileUtils;
-use reqwest::header::{LOCATION, RETRY_AFTER};
-use reqwest::{Client, ClientBuilder, Method};
-use std::time::Duration;
+use crate::i18n;
+use crate::progress::ProgressObserver;
+use crate::session::SessionFile;
+use crate::utils::{FileUtils, StringUtils};
+use body_count::{CountingBody, Direction};
+use cache::{CacheStatus, CachedResponse, HttpCache};
+use events::{EventBus, HttpEvent};
+use hyper_util::client::legacy::connect::HttpInfo;
+use middleware::Interceptor;
+use redirect::{RedirectAction, RedirectPolicy};
+use reqwest::header::{
+    ACCEPT_ENCODING, CONNECTION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, DATE, LOCATION,
+    RANGE, RETRY_AFTER, SET_COOKIE, TE, TRAILER,
+};
+use reqwest::{Client, ClientBuilder, Method, ResponseBuilderExt, StatusCode};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio_util::sync::CancellationToken;
+use upload::{UploadExtent, UploadOpener, WriteWatch};
 use url::Url;
 
 pub mod auth;
+mod body_count;
+pub mod cache;
+mod dry_run;
+pub mod events;
+pub(crate) mod markup;
+pub mod middleware;
+pub mod oauth2;
+pub mod pac;
+mod preproxy;
+pub mod redirect;
 pub mod request;
+mod resolve;
 pub mod response;
+mod ssh;
+mod transport;
+mod tunnel;
+mod upload;
 
 pub use response::{ResponseHistory, ResponseInfo};
 
+/// Longest wait for a rate limit reset without `--retry-max-delay`
+const RATE_LIMIT_MAX_WAIT: Duration = Duration::from_secs(600);
+
+const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
 /// HTTP client wrapper
 pub struct HttpClient {
     client: Client,
     config: Config,
+    uploads: UploadOpener,
+    events: EventBus,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    cookie_sources: Vec<Box<dyn CookieSource>>,
+    progress: Option<Arc<dyn ProgressObserver>>,
+    redirect_policy: RedirectPolicy,
+    session: Option<SessionFile>,
+    /// Running `rurl --daemon` that sends requests on this client's behalf
+    agent: Option<AgentClient>,
+    /// `--proxy-pac` decisions, made from each request's full URL
+    pac: Option<pac::PacRoutes>,
+    /// Sends requests that leave out `Accept` (`-H 'Accept:'`), go through
+    /// `--preproxy` or bind `--local-port`
+    transport: Option<transport::Transport>,
+    /// Keeps the `--ssh-tunnel` process running while the client is in use
+    ssh_tunnel: Option<ssh::SshTunnel>,
 }
 
 impl HttpClient {
     /// Create a new HTTP client with the given configuration
-    pub fn new(config: Config) -> Result<Self> {
+    pub fn new(mut config: Config) -> Result<Self> {
+        // Session headers and auth apply as if given in `config`
+        let session = match config.session.clone() {
+            Some(name) => Some(SessionFile::open(&name, &mut config)?),
+            None => None,
+        };
+        let mut events = EventBus::default();
+        if config.output.shows(VerbosityLevel::Headers) {
+            let verbosity = config.output.verbosity;
+            events.subscribe(move |event| {
+                for line in verbose_event_lines(event, verbosity) {
+                    eprintln!("{}", line);
+                }
+            });
+        }
+        let resolver = Arc::new(
+            resolve::ObservedResolver::new(events.clone()).with_host_filter(
+                config.host_filter.clone(),
+                config
+                    .proxy
+                    .as_ref()
+                    .and_then(|proxy| Url::parse(&proxy.url).ok())
+                    .and_then(|url| url.host_str().map(str::to_string)),
+            ),
+        );
         let mut builder = ClientBuilder::new()
             .timeout(config.timeout)
             .connect_timeout(config.connect_timeout)
-            .redirect(reqwest::redirect::Policy::none());
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(resolver.clone());
+
+        // HTTP/1.1 requires Host, so hyper adds it to every request
+        if config.headers.is_suppressed("host") {
+            crate::warning!("{}", i18n::localize("warning-host-required", &[]));
+        }
+
+        // Send everything through a SOCKS proxy forwarded over SSH
+        let ssh_tunnel = match &config.ssh_tunnel {
+            Some(_) if config.proxy.is_some() || config.proxy_pac.is_some() => {
+                return Err(RurlError::Config(
+                    "--ssh-tunnel cannot be combined with --proxy or --proxy-pac".to_string(),
+                ));
+            }
+            Some(destination) => {
+                let tunnel = ssh::SshTunnel::open(destination, config.connect_timeout)?;
+                let proxy = reqwest::Proxy::all(tunnel.proxy_url())
+                    .map_err(|e| RurlError::Proxy(format!("Invalid SSH tunnel proxy: {}", e)))?;
+                builder = builder.proxy(proxy);
+                Some(tunnel)
+            }
+            None => None,
+        };
 
         // Configure proxy if specified
         if let Some(proxy_config) = &config.proxy {
@@ -44,9 +152,48 @@ impl HttpClient {
                 proxy
             };
 
+            let no_proxy = proxy_config.no_proxy.as_deref().unwrap_or_default();
+            let proxy = proxy.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+
             builder = builder.proxy(proxy);
         }
 
+        // Select proxies per request from a PAC script unless a proxy is forced
+        let pac = match (&config.proxy_pac, &config.proxy) {
+            (Some(source), None) => {
+                let routes =
+                    pac::PacRoutes::new(pac::PacScript::parse(&pac::load_pac_source(source)?)?);
+                let connecting = routes.clone();
+                builder = builder.proxy(reqwest::Proxy::custom(move |origin| {
+                    connecting.proxy(origin)
+                }));
+                Some(routes)
+            }
+            _ => None,
+        };
+
+        if let Some(read_timeout) = config.read_timeout {
+            builder = builder.read_timeout(read_timeout);
+        }
+
+        // HTTP/2 peers learn the size limit. reqwest has no setting for the
+        // HTTP/1 parser, which stops at 100 fields and about 400 KiB, so the
+        // CLI refuses larger limits; smaller ones, and every response the
+        // agent relays, are checked in `send`
+        builder = builder
+            .http2_max_header_list_size(u32::try_from(config.max_header_size).unwrap_or(u32::MAX));
+
+        // Keep no idle connections, so each request opens its own
+        if config.no_keepalive {
+            builder = builder.pool_max_idle_per_host(0);
+        }
+
+        // Leave Content-Encoding intact so --raw sees the wire bytes;
+        // without decoders reqwest sends no Accept-Encoding either
+        if config.output.raw || config.headers.is_suppressed("accept-encoding") {
+            builder = builder.no_gzip().no_brotli().no_deflate();
+        }
+
         // Configure SSL/TLS
         if !config.ssl.verify_certs {
             builder = builder.danger_accept_invalid_certs(true);
@@ -82,9 +229,46 @@ impl HttpClient {
             (None, None) => {}
         }
 
+        // Token-held keys need a rustls config that signs on the device
+        if config.ssl.pkcs11_uri.is_some() {
+            crate::ssl::SslUtils::validate_config(&config.ssl)?;
+            #[cfg(feature = "pkcs11")]
+            {
+                builder = builder
+                    .use_preconfigured_tls(crate::ssl::pkcs11::client_tls_config(&config.ssl)?);
+            }
+        }
+
         let client = builder.build().map_err(RurlError::Http)?;
+        // reqwest always adds `Accept: */*`, cannot reach its proxy through
+        // another one and cannot bind a source port, so such requests
+        // bypass it
+        let bypass = config.headers.is_suppressed("accept")
+            || config.preproxy.is_some()
+            || config.local_port.is_some();
+        let transport = match bypass {
+            true => Some(transport::Transport::new(&config, resolver)?),
+            false => None,
+        };
+        let agent = config.daemon_socket.clone().and_then(|socket| {
+            ClientOptions::for_config(&config).map(|options| AgentClient::new(socket, options))
+        });
 
-        Ok(Self { client, config })
+        Ok(Self {
+            client,
+            redirect_policy: RedirectPolicy::from_config(&config),
+            config,
+            uploads: UploadOpener::default(),
+            events,
+            interceptors: Vec::new(),
+            cookie_sources: Vec::new(),
+            progress: None,
+            session,
+            agent,
+            pac,
+            transport,
+            ssh_tunnel,
+        })
     }
 
     /// Execute an HTTP request
@@ -92,57 +276,229 @@ impl HttpClient {
         Ok(self.execute_with_history().await?.response)
     }
 
+    /// Send the request with retries and redirects; `max_time` bounds the whole
+    /// operation and fails it with `RurlError::Timeout`
     pub async fn execute_with_history(&self) -> Result<ResponseHistory> {
+        match self.config.max_time {
+            Some(budget) => {
+                let deadline = Instant::now() + budget;
+                tokio::time::timeout(budget, self.execute_with_retries(Some(deadline)))
+                    .await
+                    .unwrap_or(Err(RurlError::Timeout))
+            }
+            None => self.execute_with_retries(None).await,
+        }
+    }
+
+    async fn execute_with_retries(&self, deadline: Option<Instant>) -> Result<ResponseHistory> {
         let mut retries_left = self.config.retry_count;
+        let mut attempt = 1u32;
+        // One ID for every hop and retry of the transfer; a value given with
+        // -H is used as is
+        let request_id = match &self.config.request_id_header {
+            Some(name) => Some(match self.config.headers.get(name) {
+                Some(value) => value.clone(),
+                None => new_request_id()?,
+            }),
+            None => None,
+        };
+        // Retries carry the same key, so the server applies the request once
+        let idempotency_key = match &self.config.idempotency_key {
+            Some(IdempotencyKey::Auto) => Some(new_request_id()?),
+            Some(IdempotencyKey::Value(value)) => Some(value.clone()),
+            None => None,
+        };
         loop {
-            let result = self.execute_with_history_once().await;
+            let result = self
+                .execute_with_history_once(
+                    attempt,
+                    request_id.as_deref(),
+                    idempotency_key.as_deref(),
+                )
+                .await;
             match result {
                 Ok(history) => {
                     if retries_left == 0 {
                         return Ok(history);
                     }
+                    let reset = rate_limit_reset_delay(
+                        history.response.status(),
+                        history.response.headers(),
+                        self.config.retry_max_delay,
+                    )
+                    .filter(|_| self.config.retry_rate_limit);
                     if let Some(delay) = retry_delay_from_response(
                         history.response.status(),
                         history.response.headers(),
                         self.config.retry_delay,
-                    ) {
+                        self.config.retry_max_delay,
+                    )
+                    .map(|delay| reset.map_or(delay, |reset| reset.max(delay)))
+                    {
+                        if !fits_budget(deadline, delay) {
+                            return Ok(history);
+                        }
+                        let reason = format!("HTTP {}", history.response.status().as_u16());
+                        for interceptor in &self.interceptors {
+                            interceptor.on_retry(attempt, &reason);
+                        }
+                        self.events.publish_with(|| HttpEvent::Retry {
+                            attempt,
+                            reason,
+                            delay_ms: delay.as_millis() as u64,
+                        });
                         if delay > Duration::from_millis(0) {
                             tokio::time::sleep(delay).await;
                         }
                         retries_left -= 1;
+                        attempt += 1;
                         continue;
                     }
                     return Ok(history);
                 }
                 Err(err) => {
-                    if retries_left == 0 || !should_retry_error(&err) {
+                    if retries_left == 0
+                        || !should_retry_error(&err, self.config.retry_all_errors)
+                        || !fits_budget(deadline, self.config.retry_delay)
+                    {
                         return Err(err);
                     }
+                    let reason = err.to_string();
+                    for interceptor in &self.interceptors {
+                        interceptor.on_retry(attempt, &reason);
+                    }
+                    self.events.publish_with(|| HttpEvent::Retry {
+                        attempt,
+                        reason,
+                        delay_ms: self.config.retry_delay.as_millis() as u64,
+                    });
                     if self.config.retry_delay > Duration::from_millis(0) {
                         tokio::time::sleep(self.config.retry_delay).await;
                     }
                     retries_left -= 1;
+                    attempt += 1;
                 }
             }
         }
     }
 
-    async fn execute_with_history_once(&self) -> Result<ResponseHistory> {
+    /// Like [`HttpClient::execute_with_history`], but stop with
+    /// `RurlError::Aborted` as soon as `cancel` fires; in-flight requests and
+    /// pending retry delays are dropped
+    pub async fn execute_with_history_cancellable(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<ResponseHistory> {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(RurlError::Aborted),
+            result = self.execute_with_history() => result,
+        }
+    }
+
+    /// Send cookies from `source` in addition to the configured browser and
+    /// persistent stores
+    pub fn add_cookie_source(&mut self, source: Box<dyn CookieSource>) {
+        self.cookie_sources.push(source);
+    }
+
+    /// Replace the redirect rules derived from `-L`, `--max-redirs` and
+    /// `--post30x`
+    pub fn set_redirect_policy(&mut self, policy: RedirectPolicy) {
+        self.redirect_policy = policy;
+    }
+
+    /// Register an interceptor; interceptors run in registration order
+    pub fn add_interceptor<I>(&mut self, interceptor: I)
+    where
+        I: Interceptor + 'static,
+    {
+        self.interceptors.push(Arc::new(interceptor));
+    }
+
+    /// Report streamed request body (`-T`, `-d @-`) progress to `observer`
+    pub fn set_progress_observer(&mut self, observer: Arc<dyn ProgressObserver>) {
+        self.progress = Some(observer);
+    }
+
+    /// Subscribe `handler` to the request lifecycle events of this client
+    pub fn on_event<F>(&mut self, handler: F)
+    where
+        F: Fn(&HttpEvent) + Send + Sync + 'static,
+    {
+        self.events.subscribe(handler);
+    }
+
+    async fn execute_with_history_once(
+        &self,
+        attempt: u32,
+        request_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<ResponseHistory> {
         let mut history = Vec::new();
         let mut current_url = Url::parse(&self.config.url).map_err(|e| {
             RurlError::InvalidUrl(format!("Invalid URL '{}': {}", self.config.url, e))
         })?;
+        let initial_url = current_url.clone();
         let initial_origin = redirect_origin_key(&current_url);
         let mut current_method = self.config.method.clone();
         let mut current_data = self.config.data.clone();
+        let mut current_upload = self.config.upload.clone();
+        let mut send_form = !self.config.form.is_empty();
         let mut redirects_followed = 0usize;
-        let cookie_context = if let Some(browser_config) = &self.config.browser_cookies {
-            let extractor = BrowserCookieExtractor::new(browser_config.clone());
-            let store = extractor.extract_cookies().await?;
-            Some((extractor, store))
+        let browser_cookies = if let Some(browser_config) = &self.config.browser_cookies {
+            let mut browser_config = browser_config.clone();
+            // Redirects may leave the domain, so only narrow the query without them
+            if !self.redirect_policy.follows() {
+                browser_config.domain_filter = cookie_filter_domain(&current_url);
+            }
+            let extractor = BrowserCookieExtractor::new(browser_config);
+            if self.config.output.shows(VerbosityLevel::Headers) {
+                write_verbose_browser_profiles(&extractor);
+            }
+            let from_agent = match &self.agent {
+                Some(agent) => agent.cookies(&browser_config).await,
+                None => None,
+            };
+            match (from_agent, self.config.cookie_cache) {
+                (Some(cookies), _) => cookies,
+                (None, Some(ttl)) => extractor.extract_cookies_cached(ttl).await?,
+                (None, None) => extractor.extract_cookies().await?,
+            }
+        } else {
+            CookieStore::new()
+        };
+        let bearer_token = match (&self.config.jwt, &self.config.token_from_browser) {
+            (Some(jwt), _) => Some(auth::Auth::sign_jwt(jwt)?),
+            (None, Some(token_config)) => {
+                Some(crate::browser::storage::read_local_storage(token_config)?)
+            }
+            (None, None) => None,
+        };
+        let http_cache = self
+            .config
+            .cache_dir
+            .clone()
+            .map(|dir| HttpCache::new(dir, self.config.cache_mode));
+        let persistent_store = match &self.config.cookie_store {
+            Some(name) => Some(PersistentCookieStore::open(name)?),
+            None => None,
+        };
+        let credential_store = if self.config.keyring_credentials {
+            Some(CredentialStore::open()?)
         } else {
             None
         };
+        let mut session_cookies = match &persistent_store {
+            Some(store) => store.load()?,
+            None => CookieStore::new(),
+        };
+        if let Some(session) = &self.session {
+            for cookie in session.cookie_store().into_values().flatten() {
+                upsert_cookie(&mut session_cookies, cookie);
+            }
+        }
+        let mut resume_from = self.config.resume_from;
 
         loop {
             let method = match current_method {
@@ -155,73 +511,376 @@ impl HttpClient {
                 HttpMethod::Patch => Method::PATCH,
                 HttpMethod::Trace => Method::TRACE,
             };
+            if let Some(host) = current_url.host_str() {
+                self.config.host_filter.check_host(host)?;
+            }
             let same_origin = redirect_origin_key(&current_url) == initial_origin;
+            // `-H Host` only applies to the server of the first URL; the
+            // connection and TLS SNI always use the URL host
+            let host_header = self
+                .config
+                .headers
+                .get("host")
+                .filter(|_| same_origin)
+                .map(String::as_str);
+            let host_override = host_header
+                .filter(|host| !host.eq_ignore_ascii_case(&host_header_value(&current_url)));
+            if self.config.output.shows(VerbosityLevel::Headers) {
+                match host_override {
+                    Some(host) => {
+                        crate::warning!("{}", host_override_notice(host, &current_url))
+                    }
+                    None if host_header.is_none() && self.config.headers.contains_key("host") => {
+                        crate::warning!(
+                            "{}",
+                            i18n::localize(
+                                "warning-host-not-forwarded",
+                                &[("target", &host_header_value(&current_url))]
+                            )
+                        )
+                    }
+                    None => {}
+                }
+            }
 
             let mut request = self.client.request(method, current_url.as_str());
 
-            // Add headers
+            // Add headers; cookies are sent below as one header with the
+            // stored ones
             for (key, value) in &self.config.headers {
                 if !same_origin && is_sensitive_header(key) && !self.config.location_trusted {
                     continue;
                 }
+                if key.eq_ignore_ascii_case("cookie")
+                    || (!same_origin && key.eq_ignore_ascii_case("host"))
+                {
+                    continue;
+                }
                 request = request.header(key, value);
             }
 
-            // Add User-Agent
-            if let Some(user_agent) = &self.config.user_agent {
+            // Resume a partial download
+            if let Some(offset) = resume_from {
+                request = request.header(RANGE, format!("bytes={}-", offset));
+            }
+            // Ranges of a resumable download count bytes of the identity
+            // encoding, which is what the part file holds
+            if self.config.output.resumable()
+                && !self.config.headers.contains_key(ACCEPT_ENCODING.as_str())
+                && !self.config.headers.is_suppressed(ACCEPT_ENCODING.as_str())
+            {
+                request = request.header(ACCEPT_ENCODING, "identity");
+            }
+
+            if let (Some(name), Some(id)) = (&self.config.request_id_header, request_id) {
+                if !self.config.headers.contains_key(name) {
+                    request = request.header(name, id);
+                }
+            }
+
+            // A key given with -H wins
+            if let Some(key) = idempotency_key {
+                if !self.config.headers.contains_key(IDEMPOTENCY_KEY) {
+                    request = request.header(IDEMPOTENCY_KEY, key);
+                }
+            }
+
+            // A Connection header given with -H wins
+            if self.config.no_keepalive && !self.config.headers.contains_key("connection") {
+                request = request.header(CONNECTION, "close");
+            }
+
+            // Add User-Agent unless given with -H
+            if let (Some(user_agent), false) = (
+                &self.config.user_agent,
+                self.config.headers.contains_key("user-agent"),
+            ) {
                 request = request.header("User-Agent", user_agent);
             }
 
             // Add authentication
+            let explicit_authorization = self.config.headers.contains_key("authorization");
+            if let Some(store) = &credential_store {
+                // Stored credentials are keyed by origin, so they apply on every hop
+                if self.config.auth_username.is_none()
+                    && bearer_token.is_none()
+                    && !explicit_authorization
+                {
+                    match store.lookup(&current_url) {
+                        Ok(Some(credential)) => {
+                            let credential =
+                                oauth2::refreshed(&self.client, store, &current_url, credential)
+                                    .await?;
+                            request = credential.apply(request);
+                        }
+                        Ok(None) => {}
+                        Err(err) => log::warn!("Skipping stored credentials: {}", err),
+                    }
+                }
+            }
             if same_origin || self.config.location_trusted {
                 if let (Some(username), Some(password)) =
                     (&self.config.auth_username, &self.config.auth_password)
                 {
                     request = request.basic_auth(username, Some(password));
                 }
+                // An explicit Authorization header wins over a minted or browser token
+                if let Some(token) = &bearer_token {
+                    if !explicit_authorization {
+                        request = request.bearer_auth(token);
+                    }
+                }
             }
 
             // Add request body for POST/PUT/PATCH
+            let trailers = &self.config.trailers;
+            let mut write_watch = None;
+            let compression = self.config.data_compress;
             if let Some(data) = &current_data {
-                request = request.body(data.clone());
-            }
-
-            if let Some((extractor, store)) = &cookie_context {
-                let cookies = extractor.cookies_for_url(store, &current_url);
-                if !cookies.is_empty() {
-                    let mut header_value = extractor.cookies_to_header(&cookies);
-                    let existing = if same_origin || self.config.location_trusted {
-                        find_cookie_header(&self.config.headers)
-                    } else {
-                        None
-                    };
-                    if let Some(existing) = existing {
-                        header_value = format!("{}; {}", existing, header_value);
+                if trailers.is_empty() && compression.is_none() {
+                    request = request.body(data.clone());
+                } else {
+                    request = request.body(upload::data_body(data, trailers, compression)?);
+                }
+            } else if let Some(source) = &current_upload {
+                let mut extent = UploadExtent {
+                    offset: 0,
+                    length: self.config.upload_length,
+                };
+                // The offset probe is a request of its own
+                let resume = self.config.upload_resume && !self.config.dry_run;
+                if let (true, UploadSource::File(path)) = (resume, source) {
+                    let size = tokio::fs::metadata(path).await?.len();
+                    let (probe, info, offset) =
+                        self.probe_upload_offset(&request, &current_url).await?;
+                    if offset > size {
+                        return Err(RurlError::Config(format!(
+                            "Server reports {} bytes of '{}', which has only {}",
+                            offset,
+                            path.display(),
+                            size
+                        )));
+                    }
+                    if size > 0 && offset == size {
+                        if self.config.output.shows(VerbosityLevel::Headers) {
+                            eprintln!("* Upload already complete ({} bytes)", size);
+                        }
+                        history.push(info);
+                        return Ok(ResponseHistory {
+                            response: probe,
+                            chain: history,
+                        });
                     }
-                    request = request.header("Cookie", header_value);
+                    if offset > 0 {
+                        if self.config.output.shows(VerbosityLevel::Headers) {
+                            eprintln!("* Resuming upload at byte {} of {}", offset, size);
+                        }
+                        request = request.header(
+                            CONTENT_RANGE,
+                            format!("bytes {}-{}/{}", offset, size - 1, size),
+                        );
+                    }
+                    extent.offset = offset;
+                }
+                write_watch = self
+                    .config
+                    .write_timeout
+                    .map(|limit| (WriteWatch::new(), limit));
+                let (body, length) = self
+                    .uploads
+                    .open(
+                        source,
+                        extent,
+                        trailers,
+                        compression,
+                        self.progress.clone(),
+                        write_watch.as_ref().map(|(watch, _)| watch.clone()),
+                    )
+                    .await?;
+                request = request.body(body);
+                if let Some(length) = length {
+                    request = request.header(CONTENT_LENGTH, length);
+                }
+            } else if send_form {
+                request = request
+                    .multipart(upload::form_body(&self.config.form, self.progress.clone()).await?);
+            }
+            let has_body = current_data.is_some() || current_upload.is_some();
+            if !trailers.is_empty() && has_body {
+                request = request.header(TRAILER, upload::trailer_header_value(trailers));
+            }
+            if let (Some(compression), true) = (compression, has_body) {
+                request = request.header(CONTENT_ENCODING, compression.content_encoding());
+            }
+            // Servers only send response trailers to clients that accept them
+            if self.config.output.include_headers
+                || self.config.output.verbosity >= VerbosityLevel::Headers
+            {
+                request = request.header(TE, "trailers");
+            }
+
+            for store in [&browser_cookies, &session_cookies] {
+                for (cookie, skipped) in cookie_decisions(store, &current_url) {
+                    self.events.publish_with(|| HttpEvent::CookieDecision {
+                        name: cookie.name.clone(),
+                        domain: cookie.domain.clone(),
+                        path: cookie.path.clone(),
+                        sent: skipped.is_none(),
+                        reason: skipped.map(str::to_string),
+                    });
                 }
             }
+            let mut cookies = browser_cookies.cookies_for_url(&current_url);
+            cookies.extend(session_cookies.cookies_for_url(&current_url));
+            cookies.extend(self.cookie_sources.cookies_for_url(&current_url));
+            let existing = if same_origin || self.config.location_trusted {
+                find_cookie_header(&self.config.headers)
+            } else {
+                None
+            };
+            let stored = (!cookies.is_empty()).then(|| cookies_to_header(&cookies));
+            let header_value = match (existing, stored) {
+                (Some(existing), Some(stored)) => Some(format!("{}; {}", existing, stored)),
+                (existing, stored) => existing.or(stored),
+            };
+            if let Some(header_value) = header_value {
+                request = request.header("Cookie", header_value);
+            }
 
-            let request = request.build().map_err(RurlError::Http)?;
+            let mut request = request.build().map_err(RurlError::Http)?;
+            for name in self.config.headers.suppressed() {
+                request.headers_mut().remove(name.as_str());
+            }
+            let interim = capture_informational(&mut request, &current_url);
+            for interceptor in &self.interceptors {
+                interceptor.on_request(&mut request)?;
+            }
 
-            if self.config.output.verbose && !self.config.output.silent {
+            if self.config.dry_run {
+                return Ok(ResponseHistory {
+                    response: dry_run::response(&request, self.config.show_secrets)?,
+                    chain: history,
+                });
+            }
+            if self.config.output.shows(VerbosityLevel::Headers) {
                 write_verbose_request_headers(&request);
             }
+            self.events.publish_with(|| HttpEvent::RequestStart {
+                method: request.method().to_string(),
+                url: current_url.to_string(),
+                attempt,
+                request_id: request_id.map(str::to_string),
+            });
+            let started = Instant::now();
 
-            let response = self
-                .client
-                .execute(request)
-                .await
-                .map_err(RurlError::Http)?;
+            let send = async {
+                match &http_cache {
+                    Some(cache) => {
+                        let (response, cache_status) =
+                            self.execute_cached(cache, &current_url, request).await?;
+                        if self.config.output.shows(VerbosityLevel::Headers) {
+                            if let Some(cache_status) = cache_status {
+                                eprintln!(
+                                    "* Cache {}: {}",
+                                    cache_status_label(cache_status),
+                                    current_url
+                                );
+                            }
+                        }
+                        Ok(response)
+                    }
+                    None => self.send(request).await,
+                }
+            };
+            let response = match &write_watch {
+                Some((watch, limit)) => tokio::select! {
+                    response = send => response?,
+                    _ = watch.stalled(*limit) => {
+                        return Err(RurlError::TooSlow(format!(
+                            "request body not accepted for {} ms (--write-timeout)",
+                            limit.as_millis()
+                        )));
+                    }
+                },
+                None => send.await?,
+            };
+            for interceptor in &self.interceptors {
+                interceptor.on_response(&response)?;
+            }
+            history.append(&mut interim.lock().unwrap_or_else(|e| e.into_inner()));
             let status = response.status();
+            // A part file that is not a prefix of the resource cannot be
+            // resumed; fetch the whole resource instead
+            if let (StatusCode::RANGE_NOT_SATISFIABLE, Some(offset)) = (status, resume_from) {
+                if crate::output::content_range_total(response.headers()) != Some(offset) {
+                    if self.config.output.shows(VerbosityLevel::Headers) {
+                        eprintln!(
+                            "* Cannot resume at byte {}; restarting the download",
+                            offset
+                        );
+                    }
+                    resume_from = None;
+                    continue;
+                }
+            }
+            let tls = current_url.scheme() == "https";
+            self.events.publish_with(|| HttpEvent::Connected {
+                url: current_url.to_string(),
+                remote_addr: remote_addr(&response).map(|addr| addr.to_string()),
+                local_addr: local_addr(&response).map(|addr| addr.to_string()),
+                version: format!("{:?}", response.version()),
+                tls,
+                alpn: tls.then(|| alpn_protocol(response.version())).flatten(),
+                cert_verified: tls.then_some(self.config.ssl.verify_certs),
+            });
+            if let Some(host) = host_override.filter(|_| {
+                response.version() == reqwest::Version::HTTP_2
+                    && self.config.output.shows(VerbosityLevel::Headers)
+            }) {
+                eprintln!(
+                    "* HTTP/2 sends :authority {}; the server may ignore Host: {}",
+                    host_header_value(&current_url),
+                    host
+                );
+            }
+            self.events.publish_with(|| HttpEvent::Response {
+                url: current_url.to_string(),
+                status: status.as_u16(),
+                version: format!("{:?}", response.version()),
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            });
             let info = ResponseInfo {
                 version: response.version(),
                 status,
                 headers: response.headers().clone(),
+                url: current_url.clone(),
+                elapsed: started.elapsed(),
+                remote_addr: remote_addr(&response),
             };
             history.push(info);
 
-            if !self.config.follow_redirects || !status.is_redirection() {
+            if persistent_store.is_some() || self.session.is_some() {
+                let received: Vec<_> = response
+                    .headers()
+                    .get_all(SET_COOKIE)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .filter_map(|value| parse_set_cookie(value, &current_url))
+                    .collect();
+                if let Some(store) = &persistent_store {
+                    for cookie in &received {
+                        store.save(cookie)?;
+                    }
+                }
+                for cookie in &received {
+                    upsert_cookie(&mut session_cookies, cookie.clone());
+                }
+                if let Some(session) = &self.session {
+                    session.save_with_cookies(received)?;
+                }
+            }
+
+            if !self.redirect_policy.follows() || !status.is_redirection() {
                 return Ok(ResponseHistory {
                     response,
                     chain: history,
@@ -241,47 +900,296 @@ impl HttpClient {
                 RurlError::InvalidUrl("Redirect location contains invalid characters".to_string())
             })?;
 
-            if let Some(limit) = self.config.max_redirects {
-                if redirects_followed >= limit {
-                    return Err(RurlError::RedirectLimitExceeded(limit));
-                }
-            }
-            redirects_followed += 1;
-
             let next_url = current_url.join(location_str).map_err(|e| {
                 RurlError::InvalidUrl(format!("Invalid redirect URL '{}': {}", location_str, e))
             })?;
-
-            if !self.config.request_method_explicit {
-                let status_code = status.as_u16();
-                if current_method == HttpMethod::Post {
-                    let keep_post = match status_code {
-                        301 => self.config.post301,
-                        302 => self.config.post302,
-                        303 => self.config.post303,
-                        _ => false,
-                    };
-                    if matches!(status_code, 301..=303) && !keep_post {
-                        current_method = HttpMethod::Get;
-                        current_data = None;
-                    }
-                } else if status_code == 303 && current_method != HttpMethod::Get {
-                    current_method = HttpMethod::Get;
-                    current_data = None;
+            let action = self.redirect_policy.check(
+                &initial_url,
+                &current_url,
+                &next_url,
+                status,
+                redirects_followed,
+            )?;
+            if let RedirectAction::Stop(reason) = action {
+                if self.config.output.shows(VerbosityLevel::Headers) {
+                    eprintln!("* Not following redirect to {}: {}", next_url, reason);
                 }
+                return Ok(ResponseHistory {
+                    response,
+                    chain: history,
+                });
+            }
+            redirects_followed += 1;
+            for interceptor in &self.interceptors {
+                interceptor.on_redirect(&current_url, &next_url, status)?;
+            }
+            self.events.publish_with(|| HttpEvent::Redirect {
+                from: current_url.to_string(),
+                to: next_url.to_string(),
+                status: status.as_u16(),
+            });
+
+            let next_method = self.redirect_policy.next_method(&current_method, status);
+            if next_method != current_method {
+                current_method = next_method;
+                current_data = None;
+                current_upload = None;
+                send_form = false;
             }
             current_url = next_url;
         }
     }
 }
 
-fn find_cookie_header(headers: &std::collections::HashMap<String, String>) -> Option<String> {
-    for (key, value) in headers {
-        if key.eq_ignore_ascii_case("cookie") {
-            return Some(value.clone());
+/// Collect 1xx responses (100 Continue, 103 Early Hints) received before the
+/// final response; hyper reports them only on HTTP/1.1 connections
+fn capture_informational(
+    request: &mut reqwest::Request,
+    url: &Url,
+) -> Arc<Mutex<Vec<ResponseInfo>>> {
+    let interim = Arc::new(Mutex::new(Vec::new()));
+    let sink = interim.clone();
+    let url = url.clone();
+    let sent = Instant::now();
+    // hyper reads the callback from the request extensions reqwest forwards
+    let mut carrier = http::Request::new(());
+    hyper::ext::on_informational(&mut carrier, move |response| {
+        let info = ResponseInfo {
+            version: response.version(),
+            status: response.status(),
+            headers: response.headers().clone(),
+            url: url.clone(),
+            elapsed: sent.elapsed(),
+            remote_addr: None,
+        };
+        sink.lock().unwrap_or_else(|e| e.into_inner()).push(info);
+    });
+    request
+        .extensions_mut()
+        .extend(std::mem::take(carrier.extensions_mut()));
+    interim
+}
+
+impl HttpClient {
+    /// Send one request, through the agent when one is running and can
+    async fn send(&self, mut request: reqwest::Request) -> Result<reqwest::Response> {
+        let counting = !self.events.is_empty();
+        if counting {
+            self.count_request_body(&mut request);
+        }
+        if let Some(agent) = &self.agent {
+            if let Some(result) = agent.send(&request, self.config.timeout).await {
+                let response = result?;
+                check_header_limits(&response, &self.config)?;
+                return Ok(response);
+            }
+        }
+        if let Some(pac) = &self.pac {
+            pac.prepare(request.url())?;
+        }
+        if let Some(tunnel) = &self.ssh_tunnel {
+            tunnel.wait_ready().await?;
+        }
+        let response = match (&self.transport, self.tunnel_proxy(request.url())) {
+            (Some(transport), _) => transport.send(request).await?,
+            (None, Some(proxy)) => tunnel::send(proxy, request, &self.config).await?,
+            (None, None) => self
+                .client
+                .execute(request)
+                .await
+                .map_err(RurlError::transport)?,
+        };
+        check_header_limits(&response, &self.config)?;
+        Ok(match counting {
+            true => self.count_response_body(response),
+            false => response,
+        })
+    }
+
+    fn count_request_body(&self, request: &mut reqwest::Request) {
+        let url = request.url().to_string();
+        let Some(body) = request.body_mut().take() else {
+            return;
+        };
+        let body = match body.as_bytes() {
+            Some(bytes) => {
+                let bytes = bytes.len() as u64;
+                self.events
+                    .publish_with(|| HttpEvent::BodySent { url, bytes });
+                body
+            }
+            None => reqwest::Body::wrap(CountingBody::new(
+                body,
+                &url,
+                Direction::Sent,
+                self.events.clone(),
+            )),
+        };
+        *request.body_mut() = Some(body);
+    }
+
+    fn count_response_body(&self, response: reqwest::Response) -> reqwest::Response {
+        let url = response.url().clone();
+        let (mut parts, body) = http::Response::from(response).into_parts();
+        let body = reqwest::Body::wrap(CountingBody::new(
+            body,
+            url.as_str(),
+            Direction::Received,
+            self.events.clone(),
+        ));
+        // reqwest keeps the response URL in an extension only its builder sets
+        if let Ok(with_url) = http::Response::builder().url(url).body(()) {
+            parts.extensions.extend(with_url.into_parts().0.extensions);
+        }
+        reqwest::Response::from(http::Response::from_parts(parts, body))
+    }
+
+    /// Ask how much of a resumable upload to `url` the server already holds,
+    /// with a HEAD request carrying the upload's headers and credentials
+    async fn probe_upload_offset(
+        &self,
+        request: &reqwest::RequestBuilder,
+        url: &Url,
+    ) -> Result<(reqwest::Response, ResponseInfo, u64)> {
+        let mut probe = request
+            .try_clone()
+            .ok_or_else(|| RurlError::Unsupported("Cannot probe the upload offset".to_string()))?
+            .build()
+            .map_err(RurlError::Http)?;
+        *probe.method_mut() = Method::HEAD;
+        let started = Instant::now();
+        let response = self.send(probe).await?;
+        let offset = upload::received_offset(response.status(), response.headers());
+        let info = ResponseInfo {
+            version: response.version(),
+            status: response.status(),
+            headers: response.headers().clone(),
+            url: url.clone(),
+            elapsed: started.elapsed(),
+            remote_addr: remote_addr(&response),
+        };
+        Ok((response, info, offset))
+    }
+
+    /// Send `request` for `url` through the response cache
+    async fn execute_cached(
+        &self,
+        cache: &HttpCache,
+        url: &Url,
+        mut request: reqwest::Request,
+    ) -> Result<(reqwest::Response, Option<CacheStatus>)> {
+        if request.method() != Method::GET {
+            let safe = matches!(
+                *request.method(),
+                Method::HEAD | Method::OPTIONS | Method::TRACE
+            );
+            let response = self.send(request).await?;
+            // Unsafe methods invalidate stored responses (RFC 9111 section 4.4)
+            if !safe && (response.status().is_success() || response.status().is_redirection()) {
+                cache.invalidate(url);
+            }
+            return Ok((response, None));
+        }
+
+        let stored = cache.lookup(url, request.headers());
+        if let Some(entry) = &stored {
+            let usable = match cache.mode() {
+                CacheMode::Force | CacheMode::OnlyIfCached => true,
+                CacheMode::Default => {
+                    !cache::request_forbids_cached(request.headers())
+                        && entry.is_fresh(SystemTime::now())
+                }
+            };
+            if usable {
+                return Ok((entry.to_response()?, Some(CacheStatus::Hit)));
+            }
+        }
+        if cache.mode() == CacheMode::OnlyIfCached {
+            return Ok((cache::gateway_timeout_response()?, Some(CacheStatus::Miss)));
+        }
+
+        let request_headers = request.headers().clone();
+        if let Some(entry) = &stored {
+            entry.add_validators(request.headers_mut());
+        }
+        let request_time = SystemTime::now();
+        let response = self.send(request).await?;
+        let status = response.status();
+
+        if let (StatusCode::NOT_MODIFIED, Some(mut entry)) = (status, stored) {
+            entry.refresh(response.headers());
+            if let Err(err) = cache.store(url, &entry) {
+                log::warn!("Failed to update HTTP cache: {}", err);
+            }
+            return Ok((entry.to_response()?, Some(CacheStatus::Revalidated)));
+        }
+
+        let headers = response.headers().clone();
+        if !cache::is_storable(status, &headers, &request_headers) {
+            return Ok((response, Some(CacheStatus::Miss)));
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(RurlError::transport)?
+            .to_vec();
+        let entry = CachedResponse::new(status, &headers, body, &request_headers, request_time);
+        if let Err(err) = cache.store(url, &entry) {
+            log::warn!("Failed to write HTTP cache: {}", err);
         }
+        Ok((entry.to_response()?, Some(CacheStatus::Miss)))
+    }
+
+    /// The `--proxytunnel` proxy when `url` is plain HTTP, which reqwest
+    /// would forward instead of tunneling
+    fn tunnel_proxy(&self, url: &Url) -> Option<&ProxyConfig> {
+        self.config
+            .proxy
+            .as_ref()
+            .filter(|proxy| proxy.tunnel && url.scheme() == "http")
+    }
+}
+
+fn cache_status_label(status: CacheStatus) -> &'static str {
+    match status {
+        CacheStatus::Hit => "hit",
+        CacheStatus::Miss => "miss",
+        CacheStatus::Revalidated => "revalidated",
+    }
+}
+
+fn host_header_value(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+/// `-v` warning for a Host header naming another host than `url`
+fn host_override_notice(host: &str, url: &Url) -> String {
+    let target = format!(
+        "{}:{}",
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default()
+    );
+    // No SNI is sent for IP addresses
+    match (url.scheme(), url.domain()) {
+        ("https", Some(domain)) => i18n::localize(
+            "warning-host-override-sni",
+            &[("host", host), ("target", &target), ("sni", domain)],
+        ),
+        _ => i18n::localize(
+            "warning-host-override",
+            &[("host", host), ("target", &target)],
+        ),
     }
-    None
+}
+
+/// `-H Cookie` values, joined like a single header when repeated
+fn find_cookie_header(headers: &HeaderList) -> Option<String> {
+    let values: Vec<&str> = headers.get_all("cookie").map(String::as_str).collect();
+    (!values.is_empty()).then(|| values.join("; "))
 }
 
 fn is_sensitive_header(name: &str) -> bool {
@@ -292,6 +1200,136 @@ fn redirect_origin_key(url: &Url) -> (String, Option<u16>) {
     (url.host_str().unwrap_or_default().to_string(), url.port())
 }
 
+fn write_verbose_browser_profiles(extractor: &BrowserCookieExtractor) {
+    match extractor.list_profiles() {
+        Ok(profiles) => {
+            for profile in profiles {
+                eprintln!(
+                    "* Browser profile: {} ({}) {}",
+                    profile.id,
+                    profile.name,
+                    profile.path.display()
+                );
+            }
+        }
+        Err(err) => log::debug!("Could not list browser profiles: {}", err),
+    }
+}
+
+/// curl-style `*` lines for `event` at `verbosity`; DNS lookups and
+/// cookie decisions only from `-vv`
+fn verbose_event_lines(event: &HttpEvent, verbosity: VerbosityLevel) -> Vec<String> {
+    let detailed = verbosity >= VerbosityLevel::Connection;
+    match event {
+        HttpEvent::Resolved { host, addrs } if detailed => {
+            vec![format!("* Resolved {} to {}", host, addrs.join(", "))]
+        }
+        HttpEvent::Connected {
+            url,
+            remote_addr,
+            version,
+            alpn,
+            cert_verified,
+            ..
+        } => {
+            let url = Url::parse(url).ok();
+            let host = url
+                .as_ref()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_default();
+            let port = url
+                .as_ref()
+                .and_then(Url::port_or_known_default)
+                .map(|port| port.to_string())
+                .unwrap_or_default();
+            let ip = remote_addr
+                .as_deref()
+                .and_then(|addr| addr.parse::<SocketAddr>().ok())
+                .map(|addr| addr.ip().to_string())
+                .unwrap_or_else(|| "<unknown address>".to_string());
+            let mut lines = vec![format!("* Connected to {} ({}) port {}", host, ip, port)];
+            if let Some(alpn) = alpn {
+                lines.push(format!("* ALPN: server accepted {}", alpn));
+            }
+            match cert_verified {
+                Some(true) => lines.push("* SSL certificate verify ok".to_string()),
+                Some(false) => {
+                    lines.push("* SSL certificate not verified (--insecure)".to_string())
+                }
+                None => lines.push(format!("* Using {}", version)),
+            }
+            lines
+        }
+        HttpEvent::RequestStart {
+            request_id: Some(id),
+            ..
+        } => vec![format!("* Request ID: {}", id)],
+        HttpEvent::Redirect { to, .. } => {
+            vec![format!("* Issue another request to this URL: '{}'", to)]
+        }
+        HttpEvent::CookieDecision {
+            name,
+            domain,
+            path,
+            reason,
+            ..
+        } if detailed => match reason {
+            None => vec![format!("* Cookie {} ({}{}) sent", name, domain, path)],
+            Some(reason) => vec![format!(
+                "* Cookie {} ({}{}) not sent: {}",
+                name, domain, path, reason
+            )],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Random (version 4) UUID for `--request-id`
+fn new_request_id() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(std::io::Error::from)?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Peer address of the connection `response` arrived on, from reqwest's
+/// connector or rurl's own transport
+fn remote_addr(response: &reqwest::Response) -> Option<SocketAddr> {
+    match response.extensions().get::<transport::ConnInfo>() {
+        Some(info) => Some(info.remote_addr),
+        None => response.remote_addr(),
+    }
+}
+
+/// Local address of the connection `response` arrived on
+fn local_addr(response: &reqwest::Response) -> Option<SocketAddr> {
+    match response.extensions().get::<transport::ConnInfo>() {
+        Some(info) => Some(info.local_addr),
+        None => response
+            .extensions()
+            .get::<HttpInfo>()
+            .map(HttpInfo::local_addr),
+    }
+}
+
+/// ALPN identifier of the protocol a TLS connection settled on
+fn alpn_protocol(version: reqwest::Version) -> Option<String> {
+    match version {
+        reqwest::Version::HTTP_2 => Some("h2".to_string()),
+        reqwest::Version::HTTP_11 => Some("http/1.1".to_string()),
+        _ => None,
+    }
+}
+
 fn write_verbose_request_headers(request: &reqwest::Request) {
     let url = request.url();
     let path = request_path(url);
@@ -300,12 +1338,8 @@ fn write_verbose_request_headers(request: &reqwest::Request) {
     if let Some(host_value) = request.headers().get("host") {
         let host = host_value.to_str().unwrap_or("<non-utf8>");
         eprintln!("> Host: {}", host);
-    } else if let Some(host) = url.host_str() {
-        let host = match url.port() {
-            Some(port) => format!("{}:{}", host, port),
-            None => host.to_string(),
-        };
-        eprintln!("> Host: {}", host);
+    } else if url.host_str().is_some() {
+        eprintln!("> Host: {}", host_header_value(url));
     }
 
     for (name, value) in request.headers().iter() {
@@ -325,10 +1359,53 @@ fn request_path(url: &Url) -> String {
     }
 }
 
-fn should_retry_error(err: &RurlError) -> bool {
-    match err {
-        RurlError::Http(http_err) => http_err.is_timeout() || http_err.is_connect(),
-        _ => false,
+/// Whether an attempt after `delay` still starts within the `--max-time` budget
+fn fits_budget(deadline: Option<Instant>, delay: Duration) -> bool {
+    deadline.is_none_or(|deadline| Instant::now() + delay < deadline)
+}
+
+/// Refuse responses with more header fields or bytes than configured; a
+/// backstop for limits the transport could not enforce while parsing
+fn check_header_limits(response: &reqwest::Response, config: &Config) -> Result<()> {
+    let headers = response.headers();
+    if headers.len() > config.max_headers {
+        return Err(RurlError::HeadersTooLarge(format!(
+            "{} header fields from {}, more than --max-headers {}",
+            headers.len(),
+            response.url(),
+            config.max_headers
+        )));
+    }
+    // Counted as sent on the wire: `Name: value` and CRLF
+    let bytes: u64 = headers
+        .iter()
+        .map(|(name, value)| (name.as_str().len() + value.len() + 4) as u64)
+        .sum();
+    if bytes > config.max_header_size {
+        return Err(RurlError::HeadersTooLarge(format!(
+            "{} bytes of headers from {}, more than --max-header-size {}",
+            bytes,
+            response.url(),
+            config.max_header_size
+        )));
+    }
+    Ok(())
+}
+
+/// Whether a failed attempt is worth another try; `all_errors` widens this
+/// to every transfer failure (`--retry-all-errors`), but never to errors
+/// that would fail the same way again
+fn should_retry_error(err: &RurlError, all_errors: bool) -> bool {
+    match err.unhinted() {
+        RurlError::Http(http_err) if http_err.is_timeout() || http_err.is_connect() => true,
+        RurlError::Timeout | RurlError::ConnectTimeout | RurlError::ResolveHost(_) => true,
+        RurlError::Aborted
+        | RurlError::Config(_)
+        | RurlError::InvalidUrl(_)
+        | RurlError::Unsupported(_)
+        | RurlError::HostBlocked(_)
+        | RurlError::InternalTarget(_) => false,
+        _ => all_errors,
     }
 }
 
@@ -336,6 +1413,7 @@ fn retry_delay_from_response(
     status: reqwest::StatusCode,
     headers: &reqwest::header::HeaderMap,
     default_delay: Duration,
+    max_delay: Option<Duration>,
 ) -> Option<Duration> {
     let is_retryable = matches!(
         status.as_u16(),
@@ -345,30 +1423,144 @@ fn retry_delay_from_response(
         return None;
     }
     let mut delay = default_delay;
-    if let Some(value) = headers.get(RETRY_AFTER) {
-        if let Ok(text) = value.to_str() {
-            if let Ok(seconds) = text.parse::<u64>() {
-                let retry_after = Duration::from_secs(seconds);
-                if retry_after > delay {
-                    delay = retry_after;
-                }
-            }
+    if let Some(retry_after) = headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|text| parse_retry_after(text, headers))
+    {
+        if retry_after > delay {
+            delay = retry_after;
         }
     }
+    if let Some(max_delay) = max_delay {
+        delay = delay.min(max_delay);
+    }
     Some(delay)
 }
 
+/// Wait until the rate limit window resets, for a 429 without Retry-After
+///
+/// `RateLimit-Reset` is delta-seconds; `X-RateLimit-Reset` is delta-seconds
+/// or, as GitHub sends it, a Unix timestamp. The wait is capped by
+/// `max_delay`, or [`RATE_LIMIT_MAX_WAIT`] without one.
+fn rate_limit_reset_delay(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    max_delay: Option<Duration>,
+) -> Option<Duration> {
+    if status != StatusCode::TOO_MANY_REQUESTS || headers.contains_key(RETRY_AFTER) {
+        return None;
+    }
+    let reset = ["ratelimit-reset", "x-ratelimit-reset"]
+        .iter()
+        .find_map(|name| {
+            headers
+                .get(*name)?
+                .to_str()
+                .ok()?
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })?;
+    // No window is decades long, so large values are timestamps
+    let delay = if reset >= 1_000_000_000 {
+        let now = headers
+            .get(DATE)
+            .and_then(|date| date.to_str().ok())
+            .and_then(StringUtils::parse_http_date)
+            .unwrap_or_else(SystemTime::now);
+        (SystemTime::UNIX_EPOCH + Duration::from_secs(reset))
+            .duration_since(now)
+            .unwrap_or_default()
+    } else {
+        Duration::from_secs(reset)
+    };
+    Some(delay.min(max_delay.unwrap_or(RATE_LIMIT_MAX_WAIT)))
+}
+
+/// Parse a Retry-After value given either as delay-seconds or as an HTTP-date.
+///
+/// HTTP-dates are measured against the response `Date` header when present so
+/// that clock skew between client and server does not inflate the delay.
+fn parse_retry_after(value: &str, headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let retry_at = StringUtils::parse_http_date(value)?;
+    let now = headers
+        .get(DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(StringUtils::parse_http_date)
+        .unwrap_or_else(SystemTime::now);
+    Some(retry_at.duration_since(now).unwrap_or_default())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        find_cookie_header, is_sensitive_header, redirect_origin_key, request_path,
-        retry_delay_from_response,
+        find_cookie_header, host_header_value, host_override_notice, is_sensitive_header,
+        new_request_id, rate_limit_reset_delay, redirect_origin_key, request_path,
+        retry_delay_from_response, should_retry_error, verbose_event_lines,
     };
-    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
-    use std::collections::HashMap;
+    use crate::config::{HeaderList, VerbosityLevel};
+    use crate::error::RurlError;
+    use crate::http::events::HttpEvent;
+    use reqwest::header::{HeaderMap, HeaderValue, DATE, RETRY_AFTER};
     use std::time::Duration;
     use url::Url;
 
+    #[test]
+    fn request_ids_are_version_4_uuids() {
+        let id = new_request_id().expect("request id");
+        let groups: Vec<usize> = id.split('-').map(str::len).collect();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(id, new_request_id().expect("request id"));
+    }
+
+    #[test]
+    fn verbose_events_read_like_curl() {
+        let connected = HttpEvent::Connected {
+            url: "https://example.com/a".to_string(),
+            remote_addr: Some("93.184.216.34:443".to_string()),
+            local_addr: None,
+            version: "HTTP/2.0".to_string(),
+            tls: true,
+            alpn: Some("h2".to_string()),
+            cert_verified: Some(true),
+        };
+        assert_eq!(
+            verbose_event_lines(&connected, VerbosityLevel::Headers),
+            [
+                "* Connected to example.com (93.184.216.34) port 443",
+                "* ALPN: server accepted h2",
+                "* SSL certificate verify ok",
+            ]
+        );
+
+        let redirect = HttpEvent::Redirect {
+            from: "http://a/".to_string(),
+            to: "http://b/".to_string(),
+            status: 302,
+        };
+        assert_eq!(
+            verbose_event_lines(&redirect, VerbosityLevel::Headers),
+            ["* Issue another request to this URL: 'http://b/'"]
+        );
+
+        let resolved = HttpEvent::Resolved {
+            host: "example.com".to_string(),
+            addrs: vec!["192.0.2.1".to_string()],
+        };
+        assert!(verbose_event_lines(&resolved, VerbosityLevel::Headers).is_empty());
+        assert_eq!(
+            verbose_event_lines(&resolved, VerbosityLevel::Connection),
+            ["* Resolved example.com to 192.0.2.1"]
+        );
+    }
+
     #[test]
     fn redirect_origin_key_ignores_scheme() {
         let http = Url::parse("http://example.com/path").expect("valid url");
@@ -385,10 +1577,12 @@ mod tests {
 
     #[test]
     fn find_cookie_header_is_case_insensitive() {
-        let mut headers = HashMap::new();
+        let mut headers = HeaderList::new();
         headers.insert("Content-Type".to_string(), "text/plain".to_string());
         headers.insert("CoOkIe".to_string(), "a=1".to_string());
         assert_eq!(find_cookie_header(&headers), Some("a=1".to_string()));
+        headers.append("cookie", "b=2");
+        assert_eq!(find_cookie_header(&headers), Some("a=1; b=2".to_string()));
 
         headers.clear();
         assert_eq!(find_cookie_header(&headers), None);
@@ -401,6 +1595,27 @@ mod tests {
         assert!(!is_sensitive_header("x-test"));
     }
 
+    #[test]
+    fn host_header_value_keeps_explicit_port() {
+        let url = Url::parse("http://example.com/").expect("valid url");
+        assert_eq!(host_header_value(&url), "example.com");
+        let url = Url::parse("http://example.com:8080/").expect("valid url");
+        assert_eq!(host_header_value(&url), "example.com:8080");
+    }
+
+    #[test]
+    fn host_override_notice_names_connection_and_sni() {
+        let url = Url::parse("https://edge.example/").expect("valid url");
+        let notice = host_override_notice("api.example", &url);
+        assert!(notice.contains("api.example"), "{}", notice);
+        assert!(notice.contains("edge.example:443"), "{}", notice);
+        assert!(notice.contains("SNI"), "{}", notice);
+        let url = Url::parse("http://localhost:8080/").expect("valid url");
+        let notice = host_override_notice("api.example", &url);
+        assert!(notice.contains("localhost:8080"), "{}", notice);
+        assert!(!notice.contains("SNI"), "{}", notice);
+    }
+
     #[test]
     fn request_path_handles_empty_and_query() {
         let url = Url::parse("http://example.com").expect("valid url");
@@ -409,6 +1624,19 @@ mod tests {
         assert_eq!(request_path(&url), "/path?query=1");
     }
 
+    #[test]
+    fn retry_all_errors_widens_retries_but_not_to_fatal_errors() {
+        assert!(should_retry_error(&RurlError::Timeout, false));
+        assert!(!should_retry_error(&RurlError::EmptyReply, false));
+        assert!(should_retry_error(&RurlError::EmptyReply, true));
+        assert!(should_retry_error(&RurlError::Recv("reset".into()), true));
+        assert!(!should_retry_error(&RurlError::Aborted, true));
+        assert!(!should_retry_error(
+            &RurlError::HostBlocked("example.com".into()),
+            true
+        ));
+    }
+
     #[test]
     fn retry_delay_from_response_respects_retry_after() {
         let mut headers = HeaderMap::new();
@@ -417,6 +1645,7 @@ mod tests {
             reqwest::StatusCode::TOO_MANY_REQUESTS,
             &headers,
             Duration::from_secs(3),
+            None,
         )
         .expect("delay");
         assert_eq!(delay, Duration::from_secs(10));
@@ -427,6 +1656,7 @@ mod tests {
             reqwest::StatusCode::SERVICE_UNAVAILABLE,
             &headers,
             Duration::from_secs(5),
+            None,
         )
         .expect("delay");
         assert_eq!(delay, Duration::from_secs(5));
@@ -435,7 +1665,90 @@ mod tests {
             reqwest::StatusCode::OK,
             &HeaderMap::new(),
             Duration::from_secs(5),
+            None,
         );
         assert!(delay.is_none());
     }
+
+    #[test]
+    fn retry_delay_from_response_parses_http_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:30 GMT"),
+        );
+        headers.insert(
+            DATE,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        let delay = retry_delay_from_response(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            &headers,
+            Duration::from_secs(1),
+            None,
+        )
+        .expect("delay");
+        assert_eq!(delay, Duration::from_secs(30));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:27:00 GMT"),
+        );
+        let delay = retry_delay_from_response(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            &headers,
+            Duration::from_secs(1),
+            None,
+        )
+        .expect("delay");
+        assert_eq!(delay, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn rate_limit_reset_delay_reads_seconds_and_timestamps() {
+        let too_many = reqwest::StatusCode::TOO_MANY_REQUESTS;
+        let mut headers = HeaderMap::new();
+        headers.insert("RateLimit-Reset", HeaderValue::from_static("42"));
+        assert_eq!(
+            rate_limit_reset_delay(too_many, &headers, None),
+            Some(Duration::from_secs(42))
+        );
+        assert_eq!(
+            rate_limit_reset_delay(reqwest::StatusCode::SERVICE_UNAVAILABLE, &headers, None),
+            None
+        );
+
+        // Wed, 21 Oct 2015 07:28:00 GMT is 1445412480
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Reset", HeaderValue::from_static("1445412510"));
+        headers.insert(
+            DATE,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            rate_limit_reset_delay(too_many, &headers, None),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            rate_limit_reset_delay(too_many, &headers, Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(5))
+        );
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("1"));
+        assert_eq!(rate_limit_reset_delay(too_many, &headers, None), None);
+    }
+
+    #[test]
+    fn retry_delay_from_response_caps_with_max_delay() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7200"));
+        let delay = retry_delay_from_response(
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+            &headers,
+            Duration::from_secs(1),
+            Some(Duration::from_secs(60)),
+        )
+        .expect("delay");
+        assert_eq!(delay, Duration::from_secs(60));
+    }
 }
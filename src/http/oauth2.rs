@@ -0,0 +1,415 @@
+//! OAuth2 sign-in for `rurl auth oauth2`
+//!
+//! The device grant (RFC 8628) and the authorization code grant with PKCE
+//! (RFC 7636) both end at the token endpoint; the tokens are kept in the
+//! keyring as a [`StoredCredential::OAuth2`] for the API origin. Requests to
+//! that origin refresh the access token shortly before it expires and store
+//! the new tokens.
+
+use crate::credentials::{CredentialStore, StoredCredential};
+use crate::error::{Result, RurlError};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Tokens this close to expiry are refreshed before use
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Client registration used for both flows and for refreshing
+#[derive(Debug, Clone)]
+pub struct OAuth2Client {
+    pub token_url: Url,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// Why the token endpoint did not hand out tokens
+enum TokenError {
+    /// The user has not finished signing in yet
+    Pending,
+    SlowDown,
+    Failed(RurlError),
+}
+
+impl From<RurlError> for TokenError {
+    fn from(err: RurlError) -> Self {
+        TokenError::Failed(err)
+    }
+}
+
+impl OAuth2Client {
+    /// Sign in with the device grant: the user enters a code on another
+    /// device while this polls the token endpoint
+    pub async fn device_flow(
+        &self,
+        http: &reqwest::Client,
+        device_url: &Url,
+    ) -> Result<StoredCredential> {
+        let mut form = vec![("client_id", self.client_id.as_str())];
+        if let Some(scope) = &self.scope {
+            form.push(("scope", scope.as_str()));
+        }
+        let response = http
+            .post(device_url.clone())
+            .form(&form)
+            .send()
+            .await
+            .map_err(RurlError::transport)?;
+        if !response.status().is_success() {
+            return Err(endpoint_error(response).await);
+        }
+        let device: DeviceAuthorization = response.json().await.map_err(RurlError::transport)?;
+        eprintln!(
+            "To sign in, open {} and enter the code {}",
+            device
+                .verification_uri_complete
+                .as_deref()
+                .unwrap_or(&device.verification_uri),
+            device.user_code
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(device.expires_in);
+        let mut interval = Duration::from_secs(device.interval.unwrap_or(5));
+        loop {
+            tokio::time::sleep(interval).await;
+            if Instant::now() > deadline {
+                return Err(RurlError::Auth(
+                    "The device code expired before sign-in finished".to_string(),
+                ));
+            }
+            let grant = [
+                ("grant_type", DEVICE_GRANT),
+                ("device_code", device.device_code.as_str()),
+            ];
+            match self.request_token(http, &grant, None).await {
+                Ok(credential) => return Ok(credential),
+                Err(TokenError::Pending) => {}
+                Err(TokenError::SlowDown) => interval += Duration::from_secs(5),
+                Err(TokenError::Failed(err)) => return Err(err),
+            }
+        }
+    }
+
+    /// Sign in with the authorization code grant: the browser is sent to
+    /// `auth_url` and comes back to a listener on loopback `port` (0 picks one)
+    pub async fn auth_code_flow(
+        &self,
+        http: &reqwest::Client,
+        auth_url: &Url,
+        port: u16,
+    ) -> Result<StoredCredential> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        let redirect_uri = format!(
+            "http://127.0.0.1:{}/callback",
+            listener.local_addr()?.port()
+        );
+        let verifier = random_token(32)?;
+        let state = random_token(16)?;
+        let mut url = auth_url.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("code_challenge", &pkce_challenge(&verifier))
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", &state);
+        if let Some(scope) = &self.scope {
+            url.query_pairs_mut().append_pair("scope", scope);
+        }
+        eprintln!("To sign in, open {}", url);
+
+        let code = receive_code(&listener, &state).await?;
+        let grant = [
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("code_verifier", verifier.as_str()),
+        ];
+        match self.request_token(http, &grant, None).await {
+            Ok(credential) => Ok(credential),
+            Err(TokenError::Failed(err)) => Err(err),
+            Err(_) => Err(RurlError::Auth(
+                "The token endpoint did not issue tokens for the authorization code".to_string(),
+            )),
+        }
+    }
+
+    /// POST `grant` to the token endpoint; `refresh_token` is kept when the
+    /// response does not rotate it
+    async fn request_token(
+        &self,
+        http: &reqwest::Client,
+        grant: &[(&str, &str)],
+        refresh_token: Option<&str>,
+    ) -> std::result::Result<StoredCredential, TokenError> {
+        let mut form = grant.to_vec();
+        form.push(("client_id", self.client_id.as_str()));
+        if let Some(secret) = &self.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        let response = http
+            .post(self.token_url.clone())
+            .form(&form)
+            .send()
+            .await
+            .map_err(RurlError::transport)?;
+        if !response.status().is_success() {
+            let body = response.text().await.map_err(RurlError::transport)?;
+            return Err(match serde_json::from_str::<ErrorResponse>(&body) {
+                Ok(error) if error.error == "authorization_pending" => TokenError::Pending,
+                Ok(error) if error.error == "slow_down" => TokenError::SlowDown,
+                Ok(error) => TokenError::Failed(token_error(&error)),
+                Err(_) => TokenError::Failed(RurlError::Auth(format!(
+                    "Token endpoint failed: {}",
+                    body.trim()
+                ))),
+            });
+        }
+        let token: TokenResponse = response.json().await.map_err(RurlError::transport)?;
+        Ok(StoredCredential::OAuth2 {
+            token_url: self.token_url.to_string(),
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            access_token: token.access_token,
+            refresh_token: token
+                .refresh_token
+                .or_else(|| refresh_token.map(str::to_string)),
+            expires_at: token.expires_in.map(|secs| unix_now() + secs),
+        })
+    }
+}
+
+/// `credential`, refreshed and stored again under `url` first if it is an
+/// OAuth2 token about to expire
+pub async fn refreshed(
+    http: &reqwest::Client,
+    store: &CredentialStore,
+    url: &Url,
+    credential: StoredCredential,
+) -> Result<StoredCredential> {
+    if !needs_refresh(&credential, unix_now()) {
+        return Ok(credential);
+    }
+    let credential = refresh(http, &credential).await.map_err(|err| {
+        RurlError::Auth(format!(
+            "Cannot refresh the OAuth2 token for {} ({}); sign in again with 'rurl auth oauth2'",
+            url.origin().ascii_serialization(),
+            err
+        ))
+    })?;
+    store.add(url, &credential)?;
+    Ok(credential)
+}
+
+/// Exchange the refresh token of an OAuth2 credential for new tokens
+pub async fn refresh(
+    http: &reqwest::Client,
+    credential: &StoredCredential,
+) -> Result<StoredCredential> {
+    let StoredCredential::OAuth2 {
+        token_url,
+        client_id,
+        client_secret,
+        refresh_token: Some(refresh_token),
+        ..
+    } = credential
+    else {
+        return Err(RurlError::Auth("No refresh token is stored".to_string()));
+    };
+    let client = OAuth2Client {
+        token_url: Url::parse(token_url).map_err(|e| {
+            RurlError::InvalidUrl(format!("Invalid token URL '{}': {}", token_url, e))
+        })?,
+        client_id: client_id.clone(),
+        client_secret: client_secret.clone(),
+        scope: None,
+    };
+    let grant = [
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+    ];
+    match client
+        .request_token(http, &grant, Some(refresh_token))
+        .await
+    {
+        Ok(credential) => Ok(credential),
+        Err(TokenError::Failed(err)) => Err(err),
+        Err(_) => Err(RurlError::Auth(
+            "The token endpoint did not issue new tokens".to_string(),
+        )),
+    }
+}
+
+/// Whether `credential` is an OAuth2 token that expires within the margin at `now`
+fn needs_refresh(credential: &StoredCredential, now: u64) -> bool {
+    matches!(
+        credential,
+        StoredCredential::OAuth2 {
+            expires_at: Some(expires_at),
+            ..
+        } if *expires_at <= now + EXPIRY_MARGIN.as_secs()
+    )
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn random_token(len: usize) -> Result<String> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(std::io::Error::from)?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// S256 code challenge for `verifier`
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn token_error(error: &ErrorResponse) -> RurlError {
+    RurlError::Auth(match &error.error_description {
+        Some(description) => format!("{}: {}", error.error, description),
+        None => error.error.clone(),
+    })
+}
+
+async fn endpoint_error(response: reqwest::Response) -> RurlError {
+    let status = response.status();
+    match response.json::<ErrorResponse>().await {
+        Ok(error) => token_error(&error),
+        Err(_) => RurlError::Auth(format!("Device authorization failed with {}", status)),
+    }
+}
+
+/// Wait for the browser to come back with `?code=...&state=...`
+async fn receive_code(listener: &TcpListener, state: &str) -> Result<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut buffer = vec![0u8; 8192];
+        let read = stream.read(&mut buffer).await?;
+        let request = String::from_utf8_lossy(&buffer[..read]);
+        let target = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/");
+        let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", target)) else {
+            continue;
+        };
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let (status, message, result) = match (param("code"), param("error")) {
+            (_, Some(error)) => (
+                "400 Bad Request",
+                "Sign-in failed; see the terminal.",
+                Some(Err(RurlError::Auth(format!(
+                    "Authorization failed: {}",
+                    param("error_description").unwrap_or(error)
+                )))),
+            ),
+            (Some(_), None) if param("state").as_deref() != Some(state) => (
+                "400 Bad Request",
+                "Sign-in failed; see the terminal.",
+                Some(Err(RurlError::Auth(
+                    "The authorization response carried the wrong state".to_string(),
+                ))),
+            ),
+            (Some(code), None) => (
+                "200 OK",
+                "Signed in to rurl; you can close this window.",
+                Some(Ok(code)),
+            ),
+            // e.g. the browser asking for /favicon.ico
+            (None, None) => ("404 Not Found", "Not found", None),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            message.len(),
+            message
+        );
+        stream.write_all(response.as_bytes()).await?;
+        if let Some(result) = result {
+            return result;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{needs_refresh, pkce_challenge};
+    use crate::credentials::StoredCredential;
+
+    fn credential(expires_at: Option<u64>) -> StoredCredential {
+        StoredCredential::OAuth2 {
+            token_url: "https://auth.example.com/token".to_string(),
+            client_id: "cli".to_string(),
+            client_secret: None,
+            access_token: "at".to_string(),
+            refresh_token: Some("rt".to_string()),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn refreshes_only_oauth2_tokens_near_expiry() {
+        assert!(needs_refresh(&credential(Some(1_000)), 1_000));
+        assert!(needs_refresh(&credential(Some(1_020)), 1_000));
+        assert!(!needs_refresh(&credential(Some(1_100)), 1_000));
+        assert!(!needs_refresh(&credential(None), 1_000));
+        let bearer = StoredCredential::Bearer {
+            token: "t".to_string(),
+        };
+        assert!(!needs_refresh(&bearer, u64::MAX - 60));
+    }
+
+    #[test]
+    fn pkce_challenge_matches_rfc_7636_example() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-1mB31LRNE1u3cizyh-K2Q_3X0AHhYk"),
+            "E9Melhoa2OwvFXEMTJguCHaoeK1t1URWbuGMSqw-cM"
+        );
+    }
+}
@@ -0,0 +1,1008 @@
+//! Proxy auto-config (PAC) support
+//!
+//! PAC files are JavaScript programs exposing `FindProxyForURL(url, host)`.
+//! Real-world PAC files use a small, predictable subset of the language, so
+//! rather than embedding a full engine this module interprets that subset:
+//! function declarations, `var`/`let`/`const`, assignments, `if`/`else`,
+//! `return`, string/number/boolean literals, `+`, comparisons, `!`, `&&`,
+//! `||`, common string methods, and the standard PAC helper functions.
+//!
+//! reqwest asks its proxy callback with only the scheme, host and port of a
+//! request, so [`PacRoutes`] evaluates the script with the full URL just
+//! before each request is sent and answers the callback from that decision.
+//! A script that fails to evaluate fails the request instead of connecting
+//! directly past the proxy it would have chosen.
+
+use crate::error::{Result, RurlError};
+use crate::utils::FileUtils;
+use std::collections::HashMap;
+use std::net::{IpAddr, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+const MAX_CALL_DEPTH: usize = 64;
+
+/// Proxy for a connection nothing was decided for; `.invalid` never
+/// resolves (RFC 6761), so such a connection fails instead of going direct
+const UNROUTABLE_PROXY: &str = "http://pac-undecided.invalid";
+
+/// A parsed PAC script
+pub struct PacScript {
+    functions: HashMap<String, Function>,
+    globals: Vec<(String, Expr)>,
+}
+
+impl PacScript {
+    /// Parse PAC source code
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let mut functions = HashMap::new();
+        let mut globals = Vec::new();
+        while !parser.at_end() {
+            if parser.peek_keyword("function") {
+                let function = parser.function()?;
+                functions.insert(function.name.clone(), function);
+            } else {
+                match parser.statement()? {
+                    Stmt::Var(name, Some(expr)) | Stmt::Assign(name, expr) => {
+                        globals.push((name, expr))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !functions.contains_key("FindProxyForURL") {
+            return Err(pac_error("script does not define FindProxyForURL"));
+        }
+        Ok(Self { functions, globals })
+    }
+
+    /// Select the proxy URL for a request, or `None` to connect directly
+    pub fn proxy_for_url(&self, url: &Url) -> Result<Option<String>> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| pac_error(&format!("{} has no host", url)))?
+            .trim_matches(|c| c == '[' || c == ']');
+        Ok(parse_pac_result(&self.find_proxy(url.as_str(), host)?))
+    }
+
+    /// Evaluate `FindProxyForURL(url, host)` and return its raw result
+    pub fn find_proxy(&self, url: &str, host: &str) -> Result<String> {
+        let mut interpreter = Interpreter {
+            script: self,
+            scopes: vec![HashMap::new()],
+            depth: 0,
+        };
+        for (name, expr) in &self.globals {
+            let value = interpreter.eval(expr)?;
+            interpreter.scopes[0].insert(name.clone(), value);
+        }
+        let value = interpreter.call(
+            "FindProxyForURL",
+            vec![Value::Str(url.to_string()), Value::Str(host.to_string())],
+        )?;
+        Ok(value.to_js_string())
+    }
+}
+
+/// Decisions of a PAC script for the requests a client sends, by origin
+#[derive(Clone)]
+pub(crate) struct PacRoutes {
+    script: Arc<PacScript>,
+    decisions: Arc<Mutex<HashMap<String, Option<String>>>>,
+}
+
+impl PacRoutes {
+    pub(crate) fn new(script: PacScript) -> Self {
+        Self {
+            script: Arc::new(script),
+            decisions: Arc::default(),
+        }
+    }
+
+    /// Decide the proxy for `url` before it is sent
+    pub(crate) fn prepare(&self, url: &Url) -> Result<()> {
+        let decision = self.script.proxy_for_url(url)?;
+        self.decisions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(origin_key(url), decision);
+        Ok(())
+    }
+
+    /// The proxy decided for the origin reqwest is connecting to
+    pub(crate) fn proxy(&self, origin: &Url) -> Option<String> {
+        let decisions = self.decisions.lock().unwrap_or_else(|e| e.into_inner());
+        match decisions.get(&origin_key(origin)) {
+            Some(decision) => decision.clone(),
+            None => {
+                log::warn!("No PAC decision for {}, refusing to connect", origin);
+                Some(UNROUTABLE_PROXY.to_string())
+            }
+        }
+    }
+}
+
+fn origin_key(url: &Url) -> String {
+    format!(
+        "{}://{}:{}",
+        url.scheme(),
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default()
+    )
+}
+
+/// Load PAC source from a local file or an `http(s)://`/`file://` URL
+pub fn load_pac_source(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return fetch_pac(source);
+    }
+    let path = match source.strip_prefix("file://") {
+        Some(path) => std::path::PathBuf::from(path),
+        None => FileUtils::expand_path(source)?,
+    };
+    FileUtils::check_file_readable(&path)?;
+    Ok(std::fs::read_to_string(&path)?)
+}
+
+/// Fetch a remote PAC file on a helper thread so callers may already be
+/// running inside an async runtime.
+fn fetch_pac(url: &str) -> Result<String> {
+    let url = url.to_string();
+    std::thread::spawn(move || -> Result<String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let response = reqwest::Client::builder()
+                .no_proxy()
+                .build()?
+                .get(&url)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(response.text().await?)
+        })
+    })
+    .join()
+    .map_err(|_| pac_error("PAC download thread panicked"))?
+}
+
+/// Convert the first usable entry of a PAC result into a proxy URL
+fn parse_pac_result(result: &str) -> Option<String> {
+    for entry in result.split(';') {
+        let mut parts = entry.split_whitespace();
+        let kind = match parts.next() {
+            Some(kind) => kind.to_ascii_uppercase(),
+            None => continue,
+        };
+        let address = parts.next();
+        let scheme = match kind.as_str() {
+            "DIRECT" => return None,
+            "PROXY" | "HTTP" => "http",
+            "HTTPS" => "https",
+            "SOCKS" | "SOCKS5" => "socks5",
+            "SOCKS4" => "socks4",
+            _ => continue,
+        };
+        if let Some(address) = address {
+            return Some(format!("{}://{}", scheme, address));
+        }
+    }
+    None
+}
+
+fn pac_error(detail: &str) -> RurlError {
+    RurlError::Proxy(format!("PAC error: {}", detail))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Punct(&'static str),
+}
+
+const PUNCTUATION: &[&str] = &[
+    "===", "!==", "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", ",", ";", ".", "+", "-",
+    "!", "=", "<", ">",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                }
+                value.push(chars[i]);
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(pac_error("unterminated string literal"));
+            }
+            i += 1;
+            tokens.push(Token::Str(value));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse()
+                .map_err(|_| pac_error(&format!("invalid number '{}'", text)))?;
+            tokens.push(Token::Num(value));
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let punct = PUNCTUATION
+                .iter()
+                .find(|punct| rest.starts_with(**punct))
+                .ok_or_else(|| pac_error(&format!("unexpected character '{}'", c)))?;
+            i += punct.len();
+            tokens.push(Token::Punct(punct));
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+    name: String,
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    Var(String, Option<Expr>),
+    Assign(String, Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    Return(Option<Expr>),
+    Expr(Expr),
+    Block(Vec<Stmt>),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Ident(String),
+    Call(String, Vec<Expr>),
+    Method(Box<Expr>, String, Vec<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, &'static str, Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == keyword)
+    }
+
+    fn peek_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        if self.peek_punct(punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<()> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            Err(pac_error(&format!(
+                "expected '{}' but found {:?}",
+                punct,
+                self.peek()
+            )))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                Ok(name.clone())
+            }
+            other => Err(pac_error(&format!(
+                "expected identifier but found {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn function(&mut self) -> Result<Function> {
+        self.ident()?;
+        let name = self.ident()?;
+        self.expect_punct("(")?;
+        let mut params = Vec::new();
+        while !self.eat_punct(")") {
+            params.push(self.ident()?);
+            if !self.peek_punct(")") {
+                self.expect_punct(",")?;
+            }
+        }
+        let body = self.block()?;
+        Ok(Function { name, params, body })
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>> {
+        self.expect_punct("{")?;
+        let mut body = Vec::new();
+        while !self.eat_punct("}") {
+            if self.at_end() {
+                return Err(pac_error("unterminated block"));
+            }
+            body.push(self.statement()?);
+        }
+        Ok(body)
+    }
+
+    fn statement(&mut self) -> Result<Stmt> {
+        if self.peek_punct("{") {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        if self.peek_keyword("var") || self.peek_keyword("let") || self.peek_keyword("const") {
+            self.pos += 1;
+            let name = self.ident()?;
+            let init = if self.eat_punct("=") {
+                Some(self.expr()?)
+            } else {
+                None
+            };
+            self.eat_punct(";");
+            return Ok(Stmt::Var(name, init));
+        }
+        if self.peek_keyword("if") {
+            self.pos += 1;
+            self.expect_punct("(")?;
+            let condition = self.expr()?;
+            self.expect_punct(")")?;
+            let then_branch = vec![self.statement()?];
+            let else_branch = if self.peek_keyword("else") {
+                self.pos += 1;
+                vec![self.statement()?]
+            } else {
+                Vec::new()
+            };
+            return Ok(Stmt::If(condition, then_branch, else_branch));
+        }
+        if self.peek_keyword("return") {
+            self.pos += 1;
+            let value = if self.peek_punct(";") || self.peek_punct("}") {
+                None
+            } else {
+                Some(self.expr()?)
+            };
+            self.eat_punct(";");
+            return Ok(Stmt::Return(value));
+        }
+        if let (Some(Token::Ident(name)), Some(Token::Punct("="))) =
+            (self.tokens.get(self.pos), self.tokens.get(self.pos + 1))
+        {
+            let name = name.clone();
+            self.pos += 2;
+            let value = self.expr()?;
+            self.eat_punct(";");
+            return Ok(Stmt::Assign(name, value));
+        }
+        let expr = self.expr()?;
+        self.eat_punct(";");
+        Ok(Stmt::Expr(expr))
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        const LEVELS: &[&[&str]] = &[
+            &["||"],
+            &["&&"],
+            &["===", "!==", "==", "!="],
+            &["<=", ">=", "<", ">"],
+            &["+", "-"],
+        ];
+        if level >= LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Punct(p)) if LEVELS[level].contains(p) => *p,
+                _ => break,
+            };
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat_punct("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat_punct("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let mut expr = self.primary()?;
+        while self.eat_punct(".") {
+            let method = self.ident()?;
+            let args = if self.peek_punct("(") {
+                self.args()?
+            } else {
+                Vec::new()
+            };
+            expr = Expr::Method(Box::new(expr), method, args);
+        }
+        Ok(expr)
+    }
+
+    fn args(&mut self) -> Result<Vec<Expr>> {
+        self.expect_punct("(")?;
+        let mut args = Vec::new();
+        while !self.eat_punct(")") {
+            args.push(self.expr()?);
+            if !self.peek_punct(")") {
+                self.expect_punct(",")?;
+            }
+        }
+        Ok(args)
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| pac_error("unexpected end of script"))?;
+        self.pos += 1;
+        match token {
+            Token::Str(value) => Ok(Expr::Literal(Value::Str(value))),
+            Token::Num(value) => Ok(Expr::Literal(Value::Num(value))),
+            Token::Punct("(") => {
+                let expr = self.expr()?;
+                self.expect_punct(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" | "undefined" => Ok(Expr::Literal(Value::Null)),
+                _ if self.peek_punct("(") => Ok(Expr::Call(name, self.args()?)),
+                _ => Ok(Expr::Ident(name)),
+            },
+            other => Err(pac_error(&format!("unexpected token {:?}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Str(value) => !value.is_empty(),
+            Value::Num(value) => *value != 0.0 && !value.is_nan(),
+            Value::Bool(value) => *value,
+            Value::Null => false,
+        }
+    }
+
+    fn to_number(&self) -> f64 {
+        match self {
+            Value::Str(value) => value.trim().parse().unwrap_or(f64::NAN),
+            Value::Num(value) => *value,
+            Value::Bool(value) => f64::from(u8::from(*value)),
+            Value::Null => 0.0,
+        }
+    }
+
+    fn to_js_string(&self) -> String {
+        match self {
+            Value::Str(value) => value.clone(),
+            Value::Num(value) if value.fract() == 0.0 && value.is_finite() => {
+                format!("{}", *value as i64)
+            }
+            Value::Num(value) => value.to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Null => "null".to_string(),
+        }
+    }
+
+    fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Null, Value::Null) => true,
+            (Value::Null, _) | (_, Value::Null) => false,
+            _ => self.to_number() == other.to_number(),
+        }
+    }
+}
+
+enum Flow {
+    Normal,
+    Return(Value),
+}
+
+struct Interpreter<'a> {
+    script: &'a PacScript,
+    scopes: Vec<HashMap<String, Value>>,
+    depth: usize,
+}
+
+impl Interpreter<'_> {
+    fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value> {
+        let function = match self.script.functions.get(name) {
+            Some(function) => function,
+            None => return builtin(name, &args),
+        };
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(pac_error("maximum call depth exceeded"));
+        }
+        let mut scope = HashMap::new();
+        for (index, param) in function.params.iter().enumerate() {
+            scope.insert(
+                param.clone(),
+                args.get(index).cloned().unwrap_or(Value::Null),
+            );
+        }
+        self.depth += 1;
+        self.scopes.push(scope);
+        let result = self.exec_block(&function.body);
+        self.scopes.pop();
+        self.depth -= 1;
+        match result? {
+            Flow::Return(value) => Ok(value),
+            Flow::Normal => Ok(Value::Null),
+        }
+    }
+
+    fn exec_block(&mut self, body: &[Stmt]) -> Result<Flow> {
+        for stmt in body {
+            if let Flow::Return(value) = self.exec(stmt)? {
+                return Ok(Flow::Return(value));
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn exec(&mut self, stmt: &Stmt) -> Result<Flow> {
+        match stmt {
+            Stmt::Var(name, init) => {
+                let value = match init {
+                    Some(expr) => self.eval(expr)?,
+                    None => Value::Null,
+                };
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(name.clone(), value);
+                }
+            }
+            Stmt::Assign(name, expr) => {
+                let value = self.eval(expr)?;
+                let scope = self
+                    .scopes
+                    .iter_mut()
+                    .rev()
+                    .find(|scope| scope.contains_key(name));
+                match scope {
+                    Some(scope) => {
+                        scope.insert(name.clone(), value);
+                    }
+                    None => {
+                        self.scopes[0].insert(name.clone(), value);
+                    }
+                }
+            }
+            Stmt::If(condition, then_branch, else_branch) => {
+                let branch = if self.eval(condition)?.truthy() {
+                    then_branch
+                } else {
+                    else_branch
+                };
+                return self.exec_block(branch);
+            }
+            Stmt::Return(value) => {
+                let value = match value {
+                    Some(expr) => self.eval(expr)?,
+                    None => Value::Null,
+                };
+                return Ok(Flow::Return(value));
+            }
+            Stmt::Expr(expr) => {
+                self.eval(expr)?;
+            }
+            Stmt::Block(body) => return self.exec_block(body),
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Ident(name) => self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(name).cloned())
+                .ok_or_else(|| pac_error(&format!("undefined variable '{}'", name))),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                self.call(name, args)
+            }
+            Expr::Method(target, method, args) => {
+                let target = self.eval(target)?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                string_method(&target.to_js_string(), method, &args)
+            }
+            Expr::Not(inner) => Ok(Value::Bool(!self.eval(inner)?.truthy())),
+            Expr::Neg(inner) => Ok(Value::Num(-self.eval(inner)?.to_number())),
+            Expr::Binary(left, "&&", right) => {
+                let left = self.eval(left)?;
+                if left.truthy() {
+                    self.eval(right)
+                } else {
+                    Ok(left)
+                }
+            }
+            Expr::Binary(left, "||", right) => {
+                let left = self.eval(left)?;
+                if left.truthy() {
+                    Ok(left)
+                } else {
+                    self.eval(right)
+                }
+            }
+            Expr::Binary(left, op, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                binary_op(&left, op, &right)
+            }
+        }
+    }
+}
+
+fn binary_op(left: &Value, op: &str, right: &Value) -> Result<Value> {
+    let value = match op {
+        "+" => match (left, right) {
+            (Value::Str(_), _) | (_, Value::Str(_)) => {
+                Value::Str(format!("{}{}", left.to_js_string(), right.to_js_string()))
+            }
+            _ => Value::Num(left.to_number() + right.to_number()),
+        },
+        "-" => Value::Num(left.to_number() - right.to_number()),
+        "==" => Value::Bool(left.loose_eq(right)),
+        "!=" => Value::Bool(!left.loose_eq(right)),
+        "===" => Value::Bool(left == right),
+        "!==" => Value::Bool(left != right),
+        "<" => Value::Bool(left.to_number() < right.to_number()),
+        ">" => Value::Bool(left.to_number() > right.to_number()),
+        "<=" => Value::Bool(left.to_number() <= right.to_number()),
+        ">=" => Value::Bool(left.to_number() >= right.to_number()),
+        _ => return Err(pac_error(&format!("unsupported operator '{}'", op))),
+    };
+    Ok(value)
+}
+
+fn string_method(target: &str, method: &str, args: &[Value]) -> Result<Value> {
+    let arg_str = |index: usize| args.get(index).map(Value::to_js_string).unwrap_or_default();
+    let arg_index = |index: usize, default: usize| {
+        args.get(index)
+            .map(|value| value.to_number().max(0.0) as usize)
+            .unwrap_or(default)
+    };
+    let chars: Vec<char> = target.chars().collect();
+    let value = match method {
+        "toLowerCase" => Value::Str(target.to_lowercase()),
+        "toUpperCase" => Value::Str(target.to_uppercase()),
+        "length" => Value::Num(chars.len() as f64),
+        "indexOf" => Value::Num(
+            target
+                .find(&arg_str(0))
+                .map(|index| target[..index].chars().count() as f64)
+                .unwrap_or(-1.0),
+        ),
+        "startsWith" => Value::Bool(target.starts_with(&arg_str(0))),
+        "endsWith" => Value::Bool(target.ends_with(&arg_str(0))),
+        "substring" | "substr" | "slice" => {
+            let start = arg_index(0, 0).min(chars.len());
+            let end = if method == "substr" {
+                start.saturating_add(arg_index(1, chars.len()))
+            } else {
+                arg_index(1, chars.len())
+            }
+            .min(chars.len());
+            let (start, end) = if start <= end {
+                (start, end)
+            } else {
+                (end, start)
+            };
+            Value::Str(chars[start..end].iter().collect())
+        }
+        _ => {
+            return Err(pac_error(&format!(
+                "unsupported string method '{}'",
+                method
+            )))
+        }
+    };
+    Ok(value)
+}
+
+fn builtin(name: &str, args: &[Value]) -> Result<Value> {
+    let arg = |index: usize| args.get(index).map(Value::to_js_string).unwrap_or_default();
+    let value = match name {
+        "isPlainHostName" => Value::Bool(!arg(0).contains('.')),
+        "dnsDomainIs" => Value::Bool(arg(0).to_lowercase().ends_with(&arg(1).to_lowercase())),
+        "localHostOrDomainIs" => {
+            let host = arg(0).to_lowercase();
+            let hostdom = arg(1).to_lowercase();
+            Value::Bool(
+                host == hostdom
+                    || (!host.contains('.') && hostdom.starts_with(&format!("{}.", host))),
+            )
+        }
+        "shExpMatch" => Value::Bool(shell_match(&arg(0), &arg(1))),
+        "dnsDomainLevels" => Value::Num(arg(0).matches('.').count() as f64),
+        "isResolvable" => Value::Bool(resolve_host(&arg(0)).is_some()),
+        "dnsResolve" => resolve_host(&arg(0))
+            .map(|ip| Value::Str(ip.to_string()))
+            .unwrap_or(Value::Null),
+        "myIpAddress" => Value::Str(my_ip_address().to_string()),
+        "isInNet" => Value::Bool(is_in_net(&arg(0), &arg(1), &arg(2))),
+        "alert" => {
+            log::debug!("PAC alert: {}", arg(0));
+            Value::Null
+        }
+        _ => return Err(pac_error(&format!("unsupported function '{}'", name))),
+    };
+    Ok(value)
+}
+
+/// Match a shell expression supporting `*` and `?` wildcards
+fn shell_match(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut v, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            v += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            v = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn resolve_host(host: &str) -> Option<IpAddr> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    (host, 0)
+        .to_socket_addrs()
+        .ok()?
+        .map(|addr| addr.ip())
+        .find(IpAddr::is_ipv4)
+}
+
+fn my_ip_address() -> IpAddr {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.0.2.1:80")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::from([127, 0, 0, 1]))
+}
+
+fn is_in_net(host: &str, pattern: &str, mask: &str) -> bool {
+    let (ip, pattern, mask) = match (
+        resolve_host(host),
+        pattern.parse::<IpAddr>(),
+        mask.parse::<IpAddr>(),
+    ) {
+        (Some(IpAddr::V4(ip)), Ok(IpAddr::V4(pattern)), Ok(IpAddr::V4(mask))) => {
+            (u32::from(ip), u32::from(pattern), u32::from(mask))
+        }
+        _ => return false,
+    };
+    ip & mask == pattern & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pac_result, shell_match, PacRoutes, PacScript};
+    use url::Url;
+
+    const SCRIPT: &str = r#"
+        // Corporate PAC
+        var corporate = "PROXY proxy.corp:3128; DIRECT";
+        function isInternal(host) {
+            return dnsDomainIs(host, ".corp.example") || isPlainHostName(host);
+        }
+        function FindProxyForURL(url, host) {
+            host = host.toLowerCase();
+            if (isInternal(host)) return "DIRECT";
+            if (shExpMatch(host, "*.socks.example")) {
+                return "SOCKS5 socks.corp:1080";
+            }
+            if (url.substring(0, 5) == "http:" && host.indexOf("legacy") != -1) {
+                return "PROXY legacy.corp:8080";
+            }
+            return corporate;
+        }
+    "#;
+
+    #[test]
+    fn find_proxy_evaluates_branches() {
+        let script = PacScript::parse(SCRIPT).expect("parse");
+        assert_eq!(
+            script
+                .find_proxy("http://intranet/", "intranet")
+                .expect("eval"),
+            "DIRECT"
+        );
+        assert_eq!(
+            script
+                .find_proxy("http://a.socks.example/", "a.socks.example")
+                .expect("eval"),
+            "SOCKS5 socks.corp:1080"
+        );
+        assert_eq!(
+            script
+                .find_proxy("http://legacy.example/", "LEGACY.example")
+                .expect("eval"),
+            "PROXY legacy.corp:8080"
+        );
+        assert_eq!(
+            script
+                .find_proxy("https://example.com/", "example.com")
+                .expect("eval"),
+            "PROXY proxy.corp:3128; DIRECT"
+        );
+    }
+
+    #[test]
+    fn proxy_for_url_maps_result_to_proxy_url() {
+        let script = PacScript::parse(SCRIPT).expect("parse");
+        let url = Url::parse("https://example.com/path").expect("url");
+        assert_eq!(
+            script.proxy_for_url(&url).expect("eval"),
+            Some("http://proxy.corp:3128".to_string())
+        );
+        let url = Url::parse("http://app.corp.example/").expect("url");
+        assert_eq!(script.proxy_for_url(&url).expect("eval"), None);
+    }
+
+    #[test]
+    fn routes_follow_the_full_url_of_each_request() {
+        let script = PacScript::parse(
+            r#"function FindProxyForURL(url, host) {
+                if (shExpMatch(url, "*/private/*")) return "PROXY gate.corp:3128";
+                return "DIRECT";
+            }"#,
+        )
+        .expect("parse");
+        let routes = PacRoutes::new(script);
+        let origin = Url::parse("http://example.com").expect("url");
+
+        let url = Url::parse("http://example.com/private/report").expect("url");
+        routes.prepare(&url).expect("eval");
+        assert_eq!(
+            routes.proxy(&origin),
+            Some("http://gate.corp:3128".to_string())
+        );
+        let url = Url::parse("http://example.com/public/").expect("url");
+        routes.prepare(&url).expect("eval");
+        assert_eq!(routes.proxy(&origin), None);
+    }
+
+    #[test]
+    fn failed_evaluation_does_not_connect_directly() {
+        let script = PacScript::parse(
+            "function FindProxyForURL(url, host) { return undefinedHelper(host); }",
+        )
+        .expect("parse");
+        let routes = PacRoutes::new(script);
+        let url = Url::parse("http://example.com/").expect("url");
+        assert!(routes.prepare(&url).is_err());
+        let decision = routes.proxy(&url).expect("not direct");
+        assert!(decision.ends_with(".invalid"));
+    }
+
+    #[test]
+    fn parse_requires_find_proxy_function() {
+        assert!(PacScript::parse("function other() { return 1; }").is_err());
+        assert!(PacScript::parse("function FindProxyForURL(url, host) { return \"").is_err());
+    }
+
+    #[test]
+    fn parse_pac_result_handles_entry_kinds() {
+        assert_eq!(parse_pac_result("DIRECT"), None);
+        assert_eq!(
+            parse_pac_result("HTTPS secure:443; DIRECT"),
+            Some("https://secure:443".to_string())
+        );
+        assert_eq!(
+            parse_pac_result("SOCKS s:1080"),
+            Some("socks5://s:1080".to_string())
+        );
+    }
+
+    #[test]
+    fn shell_match_supports_wildcards() {
+        assert!(shell_match("www.example.com", "*.example.com"));
+        assert!(shell_match("host1", "host?"));
+        assert!(!shell_match("example.org", "*.example.com"));
+        assert!(shell_match("anything", "*"));
+    }
+}
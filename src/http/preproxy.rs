@@ -0,0 +1,416 @@
+//! SOCKS pre-proxy in front of the HTTP proxy (`--preproxy`)
+//!
+//! reqwest talks to a single proxy, so requests with a pre-proxy go through
+//! rurl's own transport: it connects to the SOCKS proxy, asks it to connect
+//! to the HTTP proxy, and then talks to the HTTP proxy over that same stream,
+//! forwarding plain HTTP or opening a CONNECT tunnel, all in process.
+
+use crate::error::{Result, RurlError};
+use crate::utils::StringUtils;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::Url;
+
+const SOCKS_DEFAULT_PORT: u16 = 1080;
+
+/// SOCKS flavour named by the pre-proxy URL scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SocksVersion {
+    /// `socks4`: rurl resolves the target
+    V4,
+    /// `socks4a`: the SOCKS proxy resolves the target
+    V4a,
+    /// `socks5`: rurl resolves the target
+    V5,
+    /// `socks5h`: the SOCKS proxy resolves the target
+    V5h,
+}
+
+/// SOCKS endpoint and credentials
+#[derive(Debug, Clone)]
+pub(super) struct SocksProxy {
+    version: SocksVersion,
+    address: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+/// Where the SOCKS proxy is asked to connect
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Addr(SocketAddr),
+    Name(String, u16),
+}
+
+impl SocksProxy {
+    /// `HOST:PORT` of the SOCKS proxy
+    pub(super) fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+/// Ask the SOCKS proxy `socks`, connected on `stream`, to connect to
+/// `address` (`HOST:PORT`, the HTTP proxy)
+pub(super) async fn open(
+    mut stream: TcpStream,
+    socks: &SocksProxy,
+    address: &str,
+) -> io::Result<TcpStream> {
+    let (host, port) = address
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| invalid(format!("Proxy address without a port: {}", address)))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    handshake(&mut stream, socks, host, port)
+        .await
+        .map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!(
+                    "Pre-proxy {} could not reach {}: {}",
+                    socks.address, address, err
+                ),
+            )
+        })?;
+    Ok(stream)
+}
+
+/// The SOCKS proxy of `--preproxy`; the HTTP proxy it leads to must be one
+/// rurl can forward to and tunnel through
+pub(super) fn socks_proxy_for(preproxy: &str, proxy_url: &str) -> Result<SocksProxy> {
+    let socks = socks_proxy(preproxy)?;
+    let url = Url::parse(proxy_url)
+        .map_err(|e| RurlError::Proxy(format!("Invalid proxy '{}': {}", proxy_url, e)))?;
+    if url.scheme() != "http" {
+        return Err(RurlError::Proxy(format!(
+            "--preproxy requires an HTTP proxy, got '{}'",
+            url.scheme()
+        )));
+    }
+    Ok(socks)
+}
+
+fn socks_proxy(preproxy: &str) -> Result<SocksProxy> {
+    let url = Url::parse(preproxy)
+        .map_err(|e| RurlError::Proxy(format!("Invalid pre-proxy '{}': {}", preproxy, e)))?;
+    let version = match url.scheme() {
+        "socks4" => SocksVersion::V4,
+        "socks4a" => SocksVersion::V4a,
+        "socks5" => SocksVersion::V5,
+        "socks5h" => SocksVersion::V5h,
+        other => {
+            return Err(RurlError::Proxy(format!(
+                "--preproxy must be a SOCKS proxy (socks4, socks4a, socks5 or socks5h), got '{}'",
+                other
+            )))
+        }
+    };
+    let host = url
+        .host_str()
+        .ok_or_else(|| RurlError::Proxy(format!("Pre-proxy '{}' has no host", preproxy)))?;
+    let port = url.port().unwrap_or(SOCKS_DEFAULT_PORT);
+    Ok(SocksProxy {
+        version,
+        address: format!("{}:{}", host, port),
+        username: Some(StringUtils::percent_decode(url.username())).filter(|u| !u.is_empty()),
+        password: url.password().map(StringUtils::percent_decode),
+    })
+}
+
+/// Ask the SOCKS proxy on `stream` to connect to `host:port`
+async fn handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    socks: &SocksProxy,
+    host: &str,
+    port: u16,
+) -> io::Result<()> {
+    let target = match (socks.version, host.parse::<IpAddr>()) {
+        (_, Ok(ip)) => Target::Addr(SocketAddr::new(ip, port)),
+        (SocksVersion::V4a | SocksVersion::V5h, Err(_)) => Target::Name(host.to_string(), port),
+        (SocksVersion::V4, Err(_)) => Target::Addr(
+            tokio::net::lookup_host((host, port))
+                .await?
+                .find(SocketAddr::is_ipv4)
+                .ok_or_else(|| invalid(format!("{} has no IPv4 address for SOCKS4", host)))?,
+        ),
+        (SocksVersion::V5, Err(_)) => Target::Addr(
+            tokio::net::lookup_host((host, port))
+                .await?
+                .next()
+                .ok_or_else(|| invalid(format!("{} has no address", host)))?,
+        ),
+    };
+    match socks.version {
+        SocksVersion::V4 | SocksVersion::V4a => {
+            stream
+                .write_all(&socks4_request(&target, socks.username.as_deref())?)
+                .await?;
+            let mut reply = [0u8; 8];
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0x5a {
+                return Err(refused(format!(
+                    "SOCKS4 request rejected (code {})",
+                    reply[1]
+                )));
+            }
+            Ok(())
+        }
+        SocksVersion::V5 | SocksVersion::V5h => socks5_handshake(stream, socks, &target).await,
+    }
+}
+
+fn socks4_request(target: &Target, username: Option<&str>) -> io::Result<Vec<u8>> {
+    let (port, ip, name) = match target {
+        Target::Addr(SocketAddr::V4(addr)) => (addr.port(), addr.ip().octets(), None),
+        Target::Addr(SocketAddr::V6(_)) => {
+            return Err(invalid(
+                "SOCKS4 cannot connect to IPv6 addresses".to_string(),
+            ))
+        }
+        // SOCKS4a: an address of 0.0.0.x asks the proxy to resolve the name
+        Target::Name(name, port) => (*port, [0, 0, 0, 1], Some(name)),
+    };
+    let mut request = vec![4, 1];
+    request.extend_from_slice(&port.to_be_bytes());
+    request.extend_from_slice(&ip);
+    request.extend_from_slice(username.unwrap_or_default().as_bytes());
+    request.push(0);
+    if let Some(name) = name {
+        request.extend_from_slice(name.as_bytes());
+        request.push(0);
+    }
+    Ok(request)
+}
+
+async fn socks5_handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    socks: &SocksProxy,
+    target: &Target,
+) -> io::Result<()> {
+    // Offer username/password only when there are credentials
+    if socks.username.is_some() {
+        stream.write_all(&[5, 2, 0, 2]).await?;
+    } else {
+        stream.write_all(&[5, 1, 0]).await?;
+    }
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await?;
+    match choice {
+        [5, 0] => {}
+        [5, 2] if socks.username.is_some() => {
+            let username = socks.username.as_deref().unwrap_or_default();
+            let password = socks.password.as_deref().unwrap_or_default();
+            if username.len() > 255 || password.len() > 255 {
+                return Err(invalid(
+                    "SOCKS5 user name and password must be at most 255 bytes".to_string(),
+                ));
+            }
+            let mut auth = vec![1, username.len() as u8];
+            auth.extend_from_slice(username.as_bytes());
+            auth.push(password.len() as u8);
+            auth.extend_from_slice(password.as_bytes());
+            stream.write_all(&auth).await?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await?;
+            if status[1] != 0 {
+                return Err(refused("SOCKS5 authentication failed".to_string()));
+            }
+        }
+        _ => {
+            return Err(refused(
+                "SOCKS5 proxy accepts none of the offered authentication methods".to_string(),
+            ))
+        }
+    }
+
+    stream.write_all(&socks5_request(target)?).await?;
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(refused(format!(
+            "SOCKS5 connect failed: {}",
+            socks5_reply_reason(reply[1])
+        )));
+    }
+    // Skip the bound address that follows
+    let rest = match reply[3] {
+        1 => 4 + 2,
+        4 => 16 + 2,
+        3 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            len[0] as usize + 2
+        }
+        other => return Err(invalid(format!("SOCKS5 reply has address type {}", other))),
+    };
+    stream.read_exact(&mut vec![0u8; rest]).await?;
+    Ok(())
+}
+
+fn socks5_request(target: &Target) -> io::Result<Vec<u8>> {
+    let mut request = vec![5, 1, 0];
+    let port = match target {
+        Target::Addr(SocketAddr::V4(addr)) => {
+            request.push(1);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        Target::Addr(SocketAddr::V6(addr)) => {
+            request.push(4);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        Target::Name(name, port) => {
+            if name.len() > 255 {
+                return Err(invalid(format!("Host name too long for SOCKS5: {}", name)));
+            }
+            request.push(3);
+            request.push(name.len() as u8);
+            request.extend_from_slice(name.as_bytes());
+            *port
+        }
+    };
+    request.extend_from_slice(&port.to_be_bytes());
+    Ok(request)
+}
+
+fn socks5_reply_reason(code: u8) -> &'static str {
+    match code {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn refused(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionRefused, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handshake, socks4_request, socks5_request, socks_proxy, SocksVersion, Target};
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    /// Replays canned proxy replies and records what was written
+    struct Scripted {
+        replies: io::Cursor<Vec<u8>>,
+        written: Vec<u8>,
+    }
+
+    impl AsyncRead for Scripted {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.replies).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for Scripted {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.written).poll_write(cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn socks_proxy_reads_scheme_port_and_credentials() {
+        let socks = socks_proxy("socks5h://us%40er:pw@bastion:9050").expect("parse");
+        assert_eq!(socks.version, SocksVersion::V5h);
+        assert_eq!(socks.address, "bastion:9050");
+        assert_eq!(socks.username.as_deref(), Some("us@er"));
+        assert_eq!(socks.password.as_deref(), Some("pw"));
+
+        let socks = socks_proxy("socks4://bastion").expect("parse");
+        assert_eq!(socks.version, SocksVersion::V4);
+        assert_eq!(socks.address, "bastion:1080");
+
+        assert!(socks_proxy("http://bastion:8080").is_err());
+    }
+
+    #[test]
+    fn socks4_requests_carry_address_or_name() {
+        let target = Target::Addr("10.0.0.1:3128".parse().expect("addr"));
+        assert_eq!(
+            socks4_request(&target, Some("u")).expect("request"),
+            [4, 1, 0x0c, 0x38, 10, 0, 0, 1, b'u', 0]
+        );
+        let target = Target::Name("proxy".to_string(), 80);
+        assert_eq!(
+            socks4_request(&target, None).expect("request"),
+            [4, 1, 0, 80, 0, 0, 0, 1, 0, b'p', b'r', b'o', b'x', b'y', 0]
+        );
+        let v6 = Target::Addr("[::1]:80".parse().expect("addr"));
+        assert!(socks4_request(&v6, None).is_err());
+    }
+
+    #[test]
+    fn socks5_request_encodes_each_address_type() {
+        let target = Target::Addr("127.0.0.1:8080".parse().expect("addr"));
+        assert_eq!(
+            socks5_request(&target).expect("request"),
+            [5, 1, 0, 1, 127, 0, 0, 1, 0x1f, 0x90]
+        );
+        let target = Target::Name("px".to_string(), 3128);
+        assert_eq!(
+            socks5_request(&target).expect("request"),
+            [5, 1, 0, 3, 2, b'p', b'x', 0x0c, 0x38]
+        );
+    }
+
+    #[tokio::test]
+    async fn socks5_handshake_authenticates_and_connects() {
+        let socks = socks_proxy("socks5h://user:pw@bastion").expect("parse");
+        let mut stream = Scripted {
+            replies: io::Cursor::new(vec![5, 2, 1, 0, 5, 0, 0, 1, 0, 0, 0, 0, 0, 0]),
+            written: Vec::new(),
+        };
+        handshake(&mut stream, &socks, "proxy.internal", 3128)
+            .await
+            .expect("handshake");
+        let mut expected = vec![5, 2, 0, 2, 1, 4];
+        expected.extend_from_slice(b"user");
+        expected.extend_from_slice(&[2, b'p', b'w']);
+        expected.extend_from_slice(&[5, 1, 0, 3, 14]);
+        expected.extend_from_slice(b"proxy.internal");
+        expected.extend_from_slice(&[0x0c, 0x38]);
+        assert_eq!(stream.written, expected);
+
+        let mut refused = Scripted {
+            replies: io::Cursor::new(vec![5, 0, 5, 5, 0, 1, 0, 0, 0, 0, 0, 0]),
+            written: Vec::new(),
+        };
+        let socks = socks_proxy("socks5://bastion").expect("parse");
+        let err = handshake(&mut refused, &socks, "127.0.0.1", 3128)
+            .await
+            .expect_err("refused");
+        assert!(err.to_string().contains("connection refused"));
+    }
+}
@@ -0,0 +1,312 @@
+//! Redirect policy
+//!
+//! [`RedirectPolicy`] decides whether a 3xx response is followed and which
+//! method the next request uses. [`RedirectPolicy::from_config`] reproduces
+//! curl's `-L`, `--max-redirs` and `--post301/302/303` behavior; library users
+//! can narrow it to the original host or an allow-list of hosts, refuse
+//! https→http downgrades, or decide each hop with a callback.
+//!
+//! A redirect the policy does not allow is not an error: the 3xx response is
+//! returned as the final response, as without `-L`. Only exceeding the
+//! redirect limit fails the transfer.
+
+use crate::config::{Config, HttpMethod};
+use crate::error::{Result, RurlError};
+use reqwest::StatusCode;
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+type RedirectCallback = dyn Fn(&Url, &Url, StatusCode) -> bool + Send + Sync;
+
+/// Hosts a redirect may lead to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectScope {
+    /// Any host (curl's behavior)
+    Any,
+    /// Only the host of the original request
+    SameHost,
+    /// Only the listed hosts, compared case-insensitively
+    Hosts(Vec<String>),
+}
+
+/// Outcome of [`RedirectPolicy::check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectAction {
+    Follow,
+    /// Return the redirect response; the reason is shown in verbose mode
+    Stop(String),
+}
+
+#[derive(Clone)]
+pub struct RedirectPolicy {
+    follow: bool,
+    max_redirects: Option<usize>,
+    scope: RedirectScope,
+    allow_downgrade: bool,
+    preserve_method: bool,
+    keep_post: [bool; 3],
+    callback: Option<Arc<RedirectCallback>>,
+}
+
+impl RedirectPolicy {
+    /// Never follow redirects (curl without `-L`)
+    pub fn none() -> Self {
+        Self {
+            follow: false,
+            max_redirects: None,
+            scope: RedirectScope::Any,
+            allow_downgrade: true,
+            preserve_method: false,
+            keep_post: [false; 3],
+            callback: None,
+        }
+    }
+
+    /// Follow at most `max_redirects` redirects, or any number when `None`
+    pub fn limited(max_redirects: Option<usize>) -> Self {
+        Self {
+            follow: true,
+            max_redirects,
+            ..Self::none()
+        }
+    }
+
+    /// Policy described by the `-L`, `--max-redirs`, `-X` and `--post30x` options
+    pub fn from_config(config: &Config) -> Self {
+        let base = if config.follow_redirects {
+            Self::limited(config.max_redirects)
+        } else {
+            Self::none()
+        };
+        Self {
+            preserve_method: config.request_method_explicit,
+            keep_post: [config.post301, config.post302, config.post303],
+            ..base
+        }
+    }
+
+    /// Only follow redirects to the host of the original request
+    pub fn same_host_only(mut self) -> Self {
+        self.scope = RedirectScope::SameHost;
+        self
+    }
+
+    /// Only follow redirects to one of `hosts`
+    pub fn allow_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.scope = RedirectScope::Hosts(hosts.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Do not follow redirects from https to http
+    pub fn refuse_downgrade(mut self) -> Self {
+        self.allow_downgrade = false;
+        self
+    }
+
+    /// Keep POST after a 301, 302 or 303 instead of switching to GET
+    pub fn keep_post(mut self, status: u16) -> Self {
+        if let 301..=303 = status {
+            self.keep_post[usize::from(status - 301)] = true;
+        }
+        self
+    }
+
+    /// Ask `callback` with the current URL, the target and the status before
+    /// following; `false` stops at the redirect response
+    pub fn with_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&Url, &Url, StatusCode) -> bool + Send + Sync + 'static,
+    {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn follows(&self) -> bool {
+        self.follow
+    }
+
+    pub fn scope(&self) -> &RedirectScope {
+        &self.scope
+    }
+
+    /// Decide whether to follow the redirect from `from` to `to`, given the
+    /// URL of the original request and the redirects followed so far
+    pub fn check(
+        &self,
+        initial: &Url,
+        from: &Url,
+        to: &Url,
+        status: StatusCode,
+        followed: usize,
+    ) -> Result<RedirectAction> {
+        if !self.follow {
+            return Ok(RedirectAction::Stop(
+                "redirects are not followed".to_string(),
+            ));
+        }
+        if let Some(limit) = self.max_redirects {
+            if followed >= limit {
+                return Err(RurlError::RedirectLimitExceeded(limit));
+            }
+        }
+        let host = to.host_str().unwrap_or_default();
+        let in_scope = match &self.scope {
+            RedirectScope::Any => true,
+            RedirectScope::SameHost => {
+                host.eq_ignore_ascii_case(initial.host_str().unwrap_or_default())
+            }
+            RedirectScope::Hosts(hosts) => hosts.iter().any(|h| h.eq_ignore_ascii_case(host)),
+        };
+        if !in_scope {
+            return Ok(RedirectAction::Stop(format!(
+                "host {} is not allowed by the redirect policy",
+                host
+            )));
+        }
+        if !self.allow_downgrade && from.scheme() == "https" && to.scheme() == "http" {
+            return Ok(RedirectAction::Stop(
+                "redirect from https to http refused".to_string(),
+            ));
+        }
+        if let Some(callback) = &self.callback {
+            if !callback(from, to, status) {
+                return Ok(RedirectAction::Stop(
+                    "redirect rejected by callback".to_string(),
+                ));
+            }
+        }
+        Ok(RedirectAction::Follow)
+    }
+
+    /// Method for the request following a `status` redirect; 303 switches to
+    /// GET and 301/302 turn POST into GET unless kept or `-X` was given
+    pub fn next_method(&self, method: &HttpMethod, status: StatusCode) -> HttpMethod {
+        if self.preserve_method {
+            return method.clone();
+        }
+        match (method, status.as_u16()) {
+            (HttpMethod::Post, code @ 301..=303) if self.keep_post[usize::from(code - 301)] => {
+                HttpMethod::Post
+            }
+            (HttpMethod::Post, 301..=303) | (_, 303) => HttpMethod::Get,
+            _ => method.clone(),
+        }
+    }
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedirectPolicy")
+            .field("follow", &self.follow)
+            .field("max_redirects", &self.max_redirects)
+            .field("scope", &self.scope)
+            .field("allow_downgrade", &self.allow_downgrade)
+            .field("preserve_method", &self.preserve_method)
+            .field("keep_post", &self.keep_post)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RedirectAction, RedirectPolicy};
+    use crate::config::{Config, HttpMethod};
+    use reqwest::StatusCode;
+    use url::Url;
+
+    fn url(value: &str) -> Url {
+        Url::parse(value).expect("url")
+    }
+
+    #[test]
+    fn default_config_policy_does_not_follow() {
+        let policy = RedirectPolicy::from_config(&Config::default());
+        assert!(!policy.follows());
+        let a = url("http://a.test/");
+        let action = policy
+            .check(&a, &a, &url("http://b.test/"), StatusCode::FOUND, 0)
+            .expect("check");
+        assert!(matches!(action, RedirectAction::Stop(_)));
+    }
+
+    #[test]
+    fn limit_is_an_error() {
+        let policy = RedirectPolicy::limited(Some(1));
+        let a = url("http://a.test/");
+        assert_eq!(
+            policy
+                .check(&a, &a, &a, StatusCode::FOUND, 0)
+                .expect("check"),
+            RedirectAction::Follow
+        );
+        assert!(policy.check(&a, &a, &a, StatusCode::FOUND, 1).is_err());
+    }
+
+    #[test]
+    fn scope_and_downgrade_stop_following() {
+        let start = url("https://a.test/");
+        let other = url("https://b.test/");
+        let same_host = RedirectPolicy::limited(None).same_host_only();
+        assert!(matches!(
+            same_host.check(&start, &start, &other, StatusCode::FOUND, 0),
+            Ok(RedirectAction::Stop(_))
+        ));
+        let allowed = RedirectPolicy::limited(None).allow_hosts(["B.test"]);
+        assert_eq!(
+            allowed
+                .check(&start, &start, &other, StatusCode::FOUND, 0)
+                .expect("check"),
+            RedirectAction::Follow
+        );
+        let no_downgrade = RedirectPolicy::limited(None).refuse_downgrade();
+        assert!(matches!(
+            no_downgrade.check(&start, &start, &url("http://a.test/"), StatusCode::FOUND, 0),
+            Ok(RedirectAction::Stop(_))
+        ));
+    }
+
+    #[test]
+    fn callback_can_reject() {
+        let policy =
+            RedirectPolicy::limited(None).with_callback(|_, to, _| to.path() != "/blocked");
+        let a = url("http://a.test/");
+        assert!(matches!(
+            policy.check(&a, &a, &url("http://a.test/blocked"), StatusCode::FOUND, 0),
+            Ok(RedirectAction::Stop(_))
+        ));
+    }
+
+    #[test]
+    fn next_method_follows_curl_rules() {
+        let policy = RedirectPolicy::limited(None);
+        let moved = StatusCode::MOVED_PERMANENTLY;
+        let see_other = StatusCode::SEE_OTHER;
+        let temporary = StatusCode::TEMPORARY_REDIRECT;
+        assert_eq!(
+            policy.next_method(&HttpMethod::Post, moved),
+            HttpMethod::Get
+        );
+        assert_eq!(
+            policy.next_method(&HttpMethod::Put, see_other),
+            HttpMethod::Get
+        );
+        assert_eq!(
+            policy.next_method(&HttpMethod::Post, temporary),
+            HttpMethod::Post
+        );
+        let kept = RedirectPolicy::limited(None).keep_post(301);
+        assert_eq!(kept.next_method(&HttpMethod::Post, moved), HttpMethod::Post);
+    }
+}
@@ -0,0 +1,61 @@
+//! DNS resolution that reports each lookup as [`HttpEvent::Resolved`]
+//!
+//! Addresses are checked against `--allow-host`/`--deny-host` networks here,
+//! before anything connects to them.
+
+use super::events::{EventBus, HttpEvent};
+use crate::host_filter::HostFilter;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::SocketAddr;
+
+/// System resolver (`getaddrinfo` via tokio) publishing its results
+pub(crate) struct ObservedResolver {
+    events: EventBus,
+    host_filter: HostFilter,
+    /// The configured proxy, which is not a request target
+    proxy_host: Option<String>,
+}
+
+impl ObservedResolver {
+    pub(crate) fn new(events: EventBus) -> Self {
+        Self {
+            events,
+            host_filter: HostFilter::default(),
+            proxy_host: None,
+        }
+    }
+
+    /// Refuse names that resolve to addresses `host_filter` does not allow
+    pub(crate) fn with_host_filter(
+        mut self,
+        host_filter: HostFilter,
+        proxy_host: Option<String>,
+    ) -> Self {
+        self.host_filter = host_filter;
+        self.proxy_host = proxy_host;
+        self
+    }
+}
+
+impl Resolve for ObservedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let events = self.events.clone();
+        let host_filter = Some(self.host_filter.clone())
+            .filter(|filter| filter.is_active())
+            .filter(|_| self.proxy_host.as_deref() != Some(name.as_str()));
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let mut addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            events.publish_with(|| HttpEvent::Resolved {
+                host: host.clone(),
+                addrs: addrs.iter().map(|addr| addr.ip().to_string()).collect(),
+            });
+            if let Some(filter) = host_filter {
+                addrs = filter.filter_resolved(&host, addrs)?;
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
@@ -1,13 +1,26 @@
 //! HTTP response handling and formatting
 
+use super::markup::MarkupIndenter;
 use crate::error::Result;
 use reqwest::header::HeaderMap;
 use reqwest::{StatusCode, Version};
 use serde_json::Value;
+use std::net::SocketAddr;
+use std::time::Duration;
+use url::Url;
 
 /// Response formatter for different output formats
 pub struct ResponseFormatter {
     format_json: bool,
+    format_xml: bool,
+}
+
+/// Body layouts [`ResponseFormatter`] can pretty-print
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFormat {
+    Json,
+    Xml,
+    Html,
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +28,12 @@ pub struct ResponseInfo {
     pub version: Version,
     pub status: StatusCode,
     pub headers: HeaderMap,
+    /// URL requested for this hop
+    pub url: Url,
+    /// Time from sending the request until the response headers arrived
+    pub elapsed: Duration,
+    /// Peer address, when the connection reports it
+    pub remote_addr: Option<SocketAddr>,
 }
 
 pub struct ResponseHistory {
@@ -22,24 +41,68 @@ pub struct ResponseHistory {
     pub chain: Vec<ResponseInfo>,
 }
 
+impl ResponseHistory {
+    /// URL of the last hop, after redirects (`%{url_effective}` in curl)
+    pub fn url_effective(&self) -> &Url {
+        self.chain
+            .last()
+            .map(|info| &info.url)
+            .unwrap_or_else(|| self.response.url())
+    }
+}
+
 impl ResponseFormatter {
     pub fn new(format_json: bool) -> Self {
-        Self { format_json }
+        Self {
+            format_json,
+            format_xml: false,
+        }
     }
 
-    /// Format response body based on content type
-    pub fn format(&self, body: &str, content_type: Option<&str>) -> Result<String> {
-        if self.format_json && self.is_json_content(content_type) {
-            self.format_json_body(body)
+    /// Also indent XML and HTML bodies (`--pretty-xml`)
+    pub fn with_xml(mut self, format_xml: bool) -> Self {
+        self.format_xml = format_xml;
+        self
+    }
+
+    /// Whether [`ResponseFormatter::format`] changes a body of `content_type`
+    pub fn reformats(&self, content_type: Option<&str>) -> bool {
+        self.body_format(content_type).is_some()
+    }
+
+    /// How a body of `content_type` is pretty-printed, if at all
+    pub fn body_format(&self, content_type: Option<&str>) -> Option<BodyFormat> {
+        let mime = content_type?
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        if self.format_json && mime.contains("application/json") {
+            Some(BodyFormat::Json)
+        } else if self.format_xml && mime == "text/html" {
+            Some(BodyFormat::Html)
+        } else if self.format_xml
+            && (mime == "application/xml" || mime == "text/xml" || mime.ends_with("+xml"))
+        {
+            Some(BodyFormat::Xml)
         } else {
-            Ok(body.to_string())
+            None
         }
     }
 
-    fn is_json_content(&self, content_type: Option<&str>) -> bool {
-        content_type
-            .map(|ct| ct.contains("application/json"))
-            .unwrap_or(false)
+    /// Format response body based on content type
+    pub fn format(&self, body: &str, content_type: Option<&str>) -> Result<String> {
+        match self.body_format(content_type) {
+            Some(BodyFormat::Json) => self.format_json_body(body),
+            Some(format) => {
+                let mut indenter = MarkupIndenter::new(format == BodyFormat::Html);
+                let mut formatted = indenter.push(body);
+                formatted.push_str(&indenter.finish());
+                Ok(formatted)
+            }
+            None => Ok(body.to_string()),
+        }
     }
 
     fn format_json_body(&self, body: &str) -> Result<String> {
@@ -71,4 +134,18 @@ mod tests {
             .expect("format should succeed");
         assert_eq!(formatted, body);
     }
+
+    #[test]
+    fn format_xml_body_indents_by_content_type() {
+        let formatter = ResponseFormatter::new(false).with_xml(true);
+        let formatted = formatter
+            .format(
+                "<a><b>x</b></a>",
+                Some("application/atom+xml; charset=utf-8"),
+            )
+            .expect("format should succeed");
+        assert_eq!(formatted, "<a>\n  <b>x</b>\n</a>");
+        assert!(!formatter.reformats(Some("application/json")));
+        assert!(formatter.reformats(Some("Text/HTML")));
+    }
 }
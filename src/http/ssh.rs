@@ -0,0 +1,151 @@
+//! SSH tunnel transport (`--ssh-tunnel [USER@]HOST`)
+//!
+//! Runs the system `ssh` client with dynamic port forwarding (`-D`) to a jump
+//! host and sends every request through the resulting SOCKS5 proxy, so host
+//! names are resolved and connections are made from the bastion. The ssh
+//! process lives as long as the client and is stopped when it is dropped.
+//! The first request waits, without blocking the runtime, until the SOCKS
+//! port accepts connections.
+
+use crate::error::{Result, RurlError};
+use std::net::{SocketAddr, TcpListener};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::OnceCell;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A running `ssh -D` process and the SOCKS proxy it serves
+#[derive(Debug)]
+pub(crate) struct SshTunnel {
+    child: Mutex<Child>,
+    port: u16,
+    destination: String,
+    /// Authentication must finish by then
+    deadline: Instant,
+    ready: OnceCell<()>,
+}
+
+impl SshTunnel {
+    /// Start `ssh` to `destination`; authentication must finish within
+    /// `connect_timeout`, see [`Self::wait_ready`]
+    pub(crate) fn open(destination: &str, connect_timeout: Duration) -> Result<Self> {
+        if destination.trim().is_empty() || destination.starts_with('-') {
+            return Err(RurlError::Config(format!(
+                "Invalid SSH tunnel destination: '{}'",
+                destination
+            )));
+        }
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let child = Command::new("ssh")
+            .args(ssh_args(destination, port))
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| RurlError::Proxy(format!("Failed to start ssh: {}", e)))?;
+        Ok(Self {
+            child: Mutex::new(child),
+            port,
+            destination: destination.to_string(),
+            deadline: Instant::now() + connect_timeout,
+            ready: OnceCell::new(),
+        })
+    }
+
+    /// Wait until the SOCKS port accepts connections
+    pub(crate) async fn wait_ready(&self) -> Result<()> {
+        self.ready
+            .get_or_try_init(|| self.poll_ready())
+            .await
+            .map(|_| ())
+    }
+
+    async fn poll_ready(&self) -> Result<()> {
+        let addr = SocketAddr::from(([127, 0, 0, 1], self.port));
+        loop {
+            let exited = self
+                .child
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .try_wait()?;
+            if let Some(status) = exited {
+                return Err(RurlError::Proxy(format!(
+                    "ssh to {} exited before the tunnel was ready ({})",
+                    self.destination, status
+                )));
+            }
+            let probe = tokio::time::timeout(POLL_INTERVAL, TcpStream::connect(addr)).await;
+            if matches!(probe, Ok(Ok(_))) {
+                return Ok(());
+            }
+            if Instant::now() >= self.deadline {
+                self.stop();
+                return Err(RurlError::Proxy(format!(
+                    "Timed out waiting for the SSH tunnel to {}",
+                    self.destination
+                )));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Proxy URL for reqwest; `socks5h` leaves name resolution to the bastion
+    pub(crate) fn proxy_url(&self) -> String {
+        format!("socks5h://127.0.0.1:{}", self.port)
+    }
+
+    fn stop(&self) {
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn ssh_args(destination: &str, port: u16) -> Vec<String> {
+    vec![
+        "-N".to_string(),
+        "-D".to_string(),
+        format!("127.0.0.1:{}", port),
+        "-o".to_string(),
+        "ExitOnForwardFailure=yes".to_string(),
+        "--".to_string(),
+        destination.to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ssh_args, SshTunnel};
+    use std::time::Duration;
+
+    #[test]
+    fn ssh_args_forward_a_loopback_socks_port() {
+        assert_eq!(
+            ssh_args("ops@bastion.example.com", 1080),
+            [
+                "-N",
+                "-D",
+                "127.0.0.1:1080",
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "--",
+                "ops@bastion.example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn option_like_destination_is_rejected() {
+        assert!(SshTunnel::open("-oProxyCommand=evil", Duration::from_secs(1)).is_err());
+        assert!(SshTunnel::open(" ", Duration::from_secs(1)).is_err());
+    }
+}
@@ -0,0 +1,664 @@
+//! Requests sent with hyper instead of reqwest
+//!
+//! reqwest adds `Accept: */*` to every request that lacks the header and has
+//! no setting to leave it out, and reaches its proxy only directly. When
+//! `-H 'Accept:'` suppresses the header, or `--preproxy` puts a SOCKS proxy
+//! in front of the HTTP proxy, rurl sends the request through this transport
+//! instead: a pooled hyper client that speaks HTTP/1.1 and, when the server offers it over TLS,
+//! HTTP/2. It behaves like the reqwest client: hosts are resolved through the
+//! same host filter, connections go directly or through the `-x` proxy, TLS
+//! uses rustls with the same `--cacert`, `--cert` and `--insecure` options,
+//! `--timeout` and `--read-timeout` cover the response body, and gzip,
+//! brotli and deflate bodies are decoded unless `--raw` asks for the wire
+//! bytes. reqwest cannot pick the source port either, so `--local-port`
+//! requests use this transport too.
+
+use super::preproxy::{self, SocksProxy};
+use super::resolve::ObservedResolver;
+use super::tunnel::{self, HeadLimits, TunnelProxy};
+use crate::config::Config;
+use crate::error::{source_of, Result, RurlError};
+use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder};
+use bytes::Bytes;
+use futures_util::TryStreamExt;
+use http_body::{Body, Frame, SizeHint};
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::rt::ReadBufCursor;
+use hyper_util::client::legacy::connect::{Connected, Connection};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use reqwest::dns::{Name, Resolve};
+use reqwest::header::{
+    HeaderMap, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, PROXY_AUTHORIZATION,
+    RANGE,
+};
+use reqwest::{Method, ResponseBuilderExt, StatusCode};
+use rustls::pki_types::ServerName;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpSocket, TcpStream};
+use tokio::time::{Instant, Sleep};
+use tokio_rustls::TlsConnector;
+use tokio_util::io::{ReaderStream, StreamReader};
+use url::Url;
+
+/// Encodings the transport decodes, as reqwest offers them
+const ACCEPTED_ENCODINGS: &str = "gzip, br, deflate";
+
+/// A connection a request can be written to
+trait Io: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Io for T {}
+
+/// Addresses of the connection a response arrived on; reqwest's own
+/// connection info cannot be built outside its connector
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConnInfo {
+    pub(crate) remote_addr: SocketAddr,
+    pub(crate) local_addr: SocketAddr,
+}
+
+/// Sends requests with hyper instead of reqwest
+pub(crate) struct Transport {
+    client: Client<Connector, reqwest::Body>,
+    connector: Connector,
+    timeout: Duration,
+    read_timeout: Option<Duration>,
+    /// Offer and decode compressed bodies
+    decompress: bool,
+}
+
+impl Transport {
+    pub(crate) fn new(config: &Config, resolver: Arc<ObservedResolver>) -> Result<Self> {
+        if config.proxy_pac.is_some() || config.ssh_tunnel.is_some() {
+            return Err(RurlError::Config(
+                "-H 'Accept:', --preproxy and --local-port cannot be combined with --proxy-pac \
+                 or --ssh-tunnel"
+                    .to_string(),
+            ));
+        }
+        let preproxy = match (&config.preproxy, &config.proxy) {
+            (Some(socks), Some(proxy)) => Some(preproxy::socks_proxy_for(socks, &proxy.url)?),
+            (Some(_), None) => {
+                return Err(RurlError::Config(
+                    "--preproxy needs an HTTP proxy set with --proxy".to_string(),
+                ))
+            }
+            (None, _) => None,
+        };
+        let proxy = match &config.proxy {
+            // Only HTTP proxies, which CONNECT tunnels need, are supported
+            Some(proxy) => Some(Proxy {
+                endpoint: tunnel::tunnel_proxy(proxy)?,
+                no_proxy: proxy.no_proxy.clone(),
+                force_tunnel: proxy.tunnel,
+            }),
+            None => None,
+        };
+        let mut tls = crate::ssl::tls::client_config(&config.ssl)?;
+        tls.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        let connector = Connector(Arc::new(Connect {
+            resolver,
+            proxy,
+            preproxy,
+            local_port: config.local_port,
+            tls: TlsConnector::from(Arc::new(tls)),
+            connect_timeout: config.connect_timeout,
+        }));
+
+        let limits = HeadLimits::from_config(config);
+        let mut builder = Client::builder(TokioExecutor::new());
+        builder
+            .http1_max_headers(limits.max_headers)
+            .http1_max_buf_size(limits.buf_size())
+            .http2_max_header_list_size(u32::try_from(limits.max_header_size).unwrap_or(u32::MAX));
+        if config.no_keepalive {
+            builder.pool_max_idle_per_host(0);
+        }
+        Ok(Self {
+            client: builder.build(connector.clone()),
+            connector,
+            timeout: config.timeout,
+            read_timeout: config.read_timeout,
+            decompress: !config.output.raw && !config.headers.is_suppressed("accept-encoding"),
+        })
+    }
+
+    /// Send `request`; `--timeout` runs until the body has been read, and
+    /// `--read-timeout` limits each wait for the head or a body chunk
+    pub(crate) async fn send(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        let deadline = Instant::now() + self.timeout;
+        let url = request.url().clone();
+        let method = request.method().clone();
+        let mut request =
+            http::Request::<reqwest::Body>::try_from(request).map_err(RurlError::transport)?;
+        let headers = request.headers_mut();
+        // A forward proxy gets the full URL and the credentials
+        if let Some(authorization) = self
+            .connector
+            .0
+            .forward_proxy(&url)
+            .and_then(|proxy| proxy.endpoint.authorization.as_deref())
+        {
+            let value = HeaderValue::from_str(authorization)
+                .map_err(|e| RurlError::Proxy(format!("Invalid proxy credentials: {}", e)))?;
+            headers.insert(PROXY_AUTHORIZATION, value);
+        }
+        // Like reqwest, offer no encodings with a range
+        if self.decompress && !headers.contains_key(ACCEPT_ENCODING) && !headers.contains_key(RANGE)
+        {
+            headers.insert(
+                ACCEPT_ENCODING,
+                HeaderValue::from_static(ACCEPTED_ENCODINGS),
+            );
+        }
+
+        let pending = self.client.request(request);
+        let head = async {
+            match self.read_timeout {
+                Some(limit) => tokio::time::timeout(limit, pending)
+                    .await
+                    .map_err(|_| RurlError::Timeout)?,
+                None => pending.await,
+            }
+            .map_err(client_error)
+        };
+        let response = tokio::time::timeout_at(deadline, head)
+            .await
+            .map_err(|_| RurlError::Timeout)??;
+
+        let (mut parts, body) = response.into_parts();
+        let body = TimedBody::new(body, deadline, self.read_timeout);
+        let bodyless = method == Method::HEAD
+            || matches!(
+                parts.status,
+                StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED
+            );
+        let encoding = match self.decompress && !bodyless {
+            true => take_content_encoding(&mut parts.headers),
+            false => None,
+        };
+        let body = match encoding {
+            Some(encoding) => decode(encoding, body),
+            None => reqwest::Body::wrap(body),
+        };
+        let mut response = http::Response::builder()
+            .url(url)
+            .body(body)
+            .map_err(|e| RurlError::Recv(e.to_string()))?;
+        *response.status_mut() = parts.status;
+        *response.version_mut() = parts.version;
+        *response.headers_mut() = parts.headers;
+        response.extensions_mut().extend(parts.extensions);
+        Ok(reqwest::Response::from(response))
+    }
+}
+
+/// The `-x` proxy and the hosts that bypass it
+struct Proxy {
+    endpoint: TunnelProxy,
+    no_proxy: Option<String>,
+    /// Tunnel plain HTTP too (`--proxytunnel`)
+    force_tunnel: bool,
+}
+
+/// Opens connections for the hyper client
+#[derive(Clone)]
+struct Connector(Arc<Connect>);
+
+struct Connect {
+    resolver: Arc<ObservedResolver>,
+    proxy: Option<Proxy>,
+    /// SOCKS proxy that leads to `proxy` (`--preproxy`)
+    preproxy: Option<SocksProxy>,
+    /// Source ports to bind (`--local-port`)
+    local_port: Option<(u16, u16)>,
+    tls: TlsConnector,
+    connect_timeout: Duration,
+}
+
+impl Connect {
+    /// The proxy for requests to `host`, unless it is in `no_proxy`
+    fn proxy_for(&self, host: &str) -> Option<&Proxy> {
+        self.proxy
+            .as_ref()
+            .filter(|proxy| !bypasses_proxy(proxy.no_proxy.as_deref(), host))
+    }
+
+    /// The proxy `url` is forwarded to, rather than tunneled through
+    fn forward_proxy(&self, url: &Url) -> Option<&Proxy> {
+        let host = url.host_str()?.trim_matches(|c| c == '[' || c == ']');
+        self.proxy_for(host)
+            .filter(|proxy| url.scheme() == "http" && !proxy.force_tunnel)
+    }
+
+    async fn connect(&self, uri: http::Uri) -> Result<Conn> {
+        let authority = uri
+            .authority()
+            .ok_or_else(|| RurlError::InvalidUrl(format!("URL has no host: {}", uri)))?;
+        let host = authority
+            .host()
+            .trim_matches(|c| c == '[' || c == ']')
+            .to_string();
+        let https = uri.scheme_str() == Some("https");
+        let port = authority.port_u16().unwrap_or(if https { 443 } else { 80 });
+
+        let mut proxied = false;
+        let (stream, info): (Box<dyn Io>, ConnInfo) = match self.proxy_for(&host) {
+            None => {
+                let stream = self.connect_host(&host, port).await?;
+                let info = conn_info(&stream)?;
+                (Box::new(stream), info)
+            }
+            Some(proxy) if https || proxy.force_tunnel => {
+                let target = format!("{}:{}", authority.host(), port);
+                let upstream = self.connect_proxy(&proxy.endpoint).await?;
+                let info = conn_info(&upstream)?;
+                let handshake = tunnel::handshake(upstream, &proxy.endpoint, &target);
+                let stream = tokio::time::timeout(self.connect_timeout, handshake)
+                    .await
+                    .map_err(|_| RurlError::ConnectTimeout)?
+                    .map_err(|err| {
+                        RurlError::Proxy(format!("Proxy tunnel to {} failed: {}", target, err))
+                    })?;
+                (Box::new(stream), info)
+            }
+            Some(proxy) => {
+                proxied = true;
+                let stream = self.connect_proxy(&proxy.endpoint).await?;
+                let info = conn_info(&stream)?;
+                (Box::new(stream), info)
+            }
+        };
+        if !https {
+            return Ok(Conn {
+                io: TokioIo::new(stream),
+                proxied,
+                h2: false,
+                info,
+            });
+        }
+
+        let name = ServerName::try_from(host.clone())
+            .map_err(|e| RurlError::InvalidUrl(format!("Invalid host '{}': {}", host, e)))?;
+        let stream = tokio::time::timeout(self.connect_timeout, self.tls.connect(name, stream))
+            .await
+            .map_err(|_| RurlError::ConnectTimeout)?
+            .map_err(|err| {
+                RurlError::Ssl(format!("TLS handshake with {} failed: {}", host, err))
+            })?;
+        let h2 = stream.get_ref().1.alpn_protocol() == Some(b"h2");
+        Ok(Conn {
+            io: TokioIo::new(Box::new(stream)),
+            proxied,
+            h2,
+            info,
+        })
+    }
+
+    /// Connect to `host` at an address the host filter allows
+    async fn connect_host(&self, host: &str, port: u16) -> Result<TcpStream> {
+        let name: Name = host
+            .parse()
+            .map_err(|_| RurlError::InvalidUrl(format!("Invalid host '{}'", host)))?;
+        let addrs =
+            self.resolver
+                .resolve(name)
+                .await
+                .map_err(|err| match err.downcast::<RurlError>() {
+                    Ok(err) => *err,
+                    Err(_) => RurlError::ResolveHost(host.to_string()),
+                })?;
+        let mut last_error = None;
+        for mut addr in addrs {
+            addr.set_port(port);
+            match tokio::time::timeout(self.connect_timeout, self.connect_addr(addr)).await {
+                Ok(Ok(stream)) => return Ok(stream),
+                Ok(Err(err)) => last_error = Some(RurlError::Io(err)),
+                Err(_) => last_error = Some(RurlError::ConnectTimeout),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| RurlError::ResolveHost(host.to_string())))
+    }
+
+    /// Connect to the HTTP proxy, through the pre-proxy when there is one
+    async fn connect_proxy(&self, proxy: &TunnelProxy) -> Result<TcpStream> {
+        let connect = async {
+            match &self.preproxy {
+                Some(socks) => {
+                    let stream = self.connect_named(socks.address()).await?;
+                    preproxy::open(stream, socks, &proxy.address).await
+                }
+                None => self.connect_named(&proxy.address).await,
+            }
+        };
+        tokio::time::timeout(self.connect_timeout, connect)
+            .await
+            .map_err(|_| RurlError::ConnectTimeout)?
+            .map_err(|err| {
+                RurlError::Proxy(format!(
+                    "Cannot connect to proxy {}: {}",
+                    proxy.address, err
+                ))
+            })
+    }
+
+    /// Connect to a proxy at `address` (`HOST:PORT`); proxies are not
+    /// subject to the host filter
+    async fn connect_named(&self, address: &str) -> io::Result<TcpStream> {
+        let mut last_error = None;
+        for addr in tokio::net::lookup_host(address).await? {
+            match self.connect_addr(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} has no address", address),
+            )
+        }))
+    }
+
+    /// Connect to `addr` from the first free port of `--local-port`, or
+    /// from any port without it
+    async fn connect_addr(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let Some((first, last)) = self.local_port else {
+            return TcpStream::connect(addr).await;
+        };
+        for port in first..=last {
+            let (socket, local) = match addr {
+                SocketAddr::V4(_) => (
+                    TcpSocket::new_v4()?,
+                    SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)),
+                ),
+                SocketAddr::V6(_) => (
+                    TcpSocket::new_v6()?,
+                    SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)),
+                ),
+            };
+            match socket.bind(local) {
+                Err(err) if err.kind() == io::ErrorKind::AddrInUse => continue,
+                result => result?,
+            }
+            // The port may be free to bind but already used towards `addr`
+            match socket.connect(addr).await {
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::AddrInUse | io::ErrorKind::AddrNotAvailable
+                    ) =>
+                {
+                    continue
+                }
+                result => return result,
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("No free local port in {}-{}", first, last),
+        ))
+    }
+}
+
+fn conn_info(stream: &TcpStream) -> Result<ConnInfo> {
+    Ok(ConnInfo {
+        remote_addr: stream.peer_addr()?,
+        local_addr: stream.local_addr()?,
+    })
+}
+
+impl tower_service::Service<http::Uri> for Connector {
+    type Response = Conn;
+    type Error = ConnectFailure;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<Conn, ConnectFailure>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: http::Uri) -> Self::Future {
+        let connect = self.0.clone();
+        Box::pin(async move {
+            connect
+                .connect(uri)
+                .await
+                .map_err(|err| ConnectFailure(Mutex::new(Some(err))))
+        })
+    }
+}
+
+/// A connection opened by [`Connector`]
+struct Conn {
+    io: TokioIo<Box<dyn Io>>,
+    /// Plain HTTP sent to a forward proxy, with absolute URLs
+    proxied: bool,
+    /// TLS negotiated HTTP/2
+    h2: bool,
+    info: ConnInfo,
+}
+
+impl Connection for Conn {
+    fn connected(&self) -> Connected {
+        let connected = Connected::new().proxy(self.proxied).extra(self.info);
+        match self.h2 {
+            true => connected.negotiated_h2(),
+            false => connected,
+        }
+    }
+}
+
+impl hyper::rt::Read for Conn {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl hyper::rt::Write for Conn {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+/// Carries a connect error through hyper, which only keeps it as a source
+#[derive(Debug)]
+struct ConnectFailure(Mutex<Option<RurlError>>);
+
+impl fmt::Display for ConnectFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &*self.0.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(err) => write!(f, "{}", err),
+            None => f.write_str("connection failed"),
+        }
+    }
+}
+
+impl std::error::Error for ConnectFailure {}
+
+/// The connect error, or what hyper reported, as a rurl error
+fn client_error(err: hyper_util::client::legacy::Error) -> RurlError {
+    let connect_error = source_of::<ConnectFailure>(&err)
+        .and_then(|failure| failure.0.lock().unwrap_or_else(|e| e.into_inner()).take());
+    if let Some(err) = connect_error {
+        return err;
+    }
+    if let Some(hyper_err) = source_of::<hyper::Error>(&err) {
+        return RurlError::from_hyper(hyper_err);
+    }
+    RurlError::Recv(err.to_string())
+}
+
+/// A response body bounded by `--timeout` and, per chunk, `--read-timeout`
+struct TimedBody {
+    inner: Incoming,
+    deadline: Pin<Box<Sleep>>,
+    read_timeout: Option<(Duration, Pin<Box<Sleep>>)>,
+}
+
+impl TimedBody {
+    fn new(inner: Incoming, deadline: Instant, read_timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            deadline: Box::pin(tokio::time::sleep_until(deadline)),
+            read_timeout: read_timeout.map(|limit| (limit, Box::pin(tokio::time::sleep(limit)))),
+        }
+    }
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+impl Body for TimedBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Frame<Bytes>, BoxError>>> {
+        let this = self.get_mut();
+        if let Poll::Ready(frame) = Pin::new(&mut this.inner).poll_frame(cx) {
+            if let Some((limit, idle)) = &mut this.read_timeout {
+                idle.as_mut().reset(Instant::now() + *limit);
+            }
+            return Poll::Ready(frame.map(|frame| frame.map_err(BoxError::from)));
+        }
+        let idle = this
+            .read_timeout
+            .as_mut()
+            .is_some_and(|(_, idle)| idle.as_mut().poll(cx).is_ready());
+        if idle || this.deadline.as_mut().poll(cx).is_ready() {
+            let err = io::Error::new(io::ErrorKind::TimedOut, "response body timed out");
+            return Poll::Ready(Some(Err(err.into())));
+        }
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// A `Content-Encoding` the transport decodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Gzip,
+    Brotli,
+    Deflate,
+}
+
+/// Take the body's encoding out of `headers` when it is one the transport
+/// decodes, with the length of the encoded body; an empty body stays as is
+fn take_content_encoding(headers: &mut HeaderMap) -> Option<Encoding> {
+    let value = headers.get(CONTENT_ENCODING)?.to_str().ok()?;
+    let encoding = match value.trim().to_ascii_lowercase().as_str() {
+        "gzip" | "x-gzip" => Encoding::Gzip,
+        "br" => Encoding::Brotli,
+        "deflate" => Encoding::Deflate,
+        _ => return None,
+    };
+    if headers
+        .get(CONTENT_LENGTH)
+        .is_some_and(|length| length == "0")
+    {
+        return None;
+    }
+    headers.remove(CONTENT_ENCODING);
+    headers.remove(CONTENT_LENGTH);
+    Some(encoding)
+}
+
+/// Decode `body`; timeouts keep their kind, so they still fail as timeouts
+fn decode(encoding: Encoding, body: TimedBody) -> reqwest::Body {
+    let reader =
+        StreamReader::new(body.into_data_stream().map_err(
+            |err| match err.downcast::<io::Error>() {
+                Ok(err) => *err,
+                Err(err) => io::Error::other(err),
+            },
+        ));
+    match encoding {
+        Encoding::Gzip => reqwest::Body::wrap_stream(ReaderStream::new(GzipDecoder::new(reader))),
+        Encoding::Brotli => {
+            reqwest::Body::wrap_stream(ReaderStream::new(BrotliDecoder::new(reader)))
+        }
+        Encoding::Deflate => {
+            reqwest::Body::wrap_stream(ReaderStream::new(ZlibDecoder::new(reader)))
+        }
+    }
+}
+
+/// Whether `host` is in a `no_proxy` list: `*`, or names matched with
+/// their subdomains
+fn bypasses_proxy(no_proxy: Option<&str>, host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    no_proxy
+        .unwrap_or_default()
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').to_ascii_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bypasses_proxy, take_content_encoding, Encoding};
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH};
+
+    #[test]
+    fn no_proxy_matches_hosts_and_subdomains() {
+        assert!(bypasses_proxy(
+            Some("localhost, .corp.example"),
+            "LOCALHOST"
+        ));
+        assert!(bypasses_proxy(Some("corp.example"), "app.corp.example"));
+        assert!(bypasses_proxy(Some("*"), "example.com"));
+        assert!(!bypasses_proxy(Some("corp.example"), "notcorp.example"));
+        assert!(!bypasses_proxy(None, "example.com"));
+    }
+
+    #[test]
+    fn content_encoding_is_taken_only_when_decoded() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("GZIP"));
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("20"));
+        assert_eq!(take_content_encoding(&mut headers), Some(Encoding::Gzip));
+        assert!(headers.is_empty());
+
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("zstd"));
+        assert_eq!(take_content_encoding(&mut headers), None);
+        assert!(headers.contains_key(CONTENT_ENCODING));
+
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("br"));
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("0"));
+        assert_eq!(take_content_encoding(&mut headers), None);
+    }
+}
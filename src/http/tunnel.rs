@@ -0,0 +1,286 @@
+//! Forced CONNECT tunnels through HTTP proxies (`--proxytunnel`)
+//!
+//! reqwest already tunnels HTTPS through proxies with CONNECT but forwards
+//! plain HTTP with absolute URIs. To force a tunnel for plain HTTP, such a
+//! request bypasses reqwest's connector: rurl opens a CONNECT tunnel to the
+//! target through the proxy and sends the request over it with hyper, all in
+//! process, so no other program can reach the tunnel or the proxy
+//! credentials.
+
+use crate::config::{Config, ProxyConfig};
+use crate::error::{Result, RurlError};
+use crate::http::auth::Auth;
+use hyper_util::rt::TokioIo;
+use reqwest::header::{HeaderValue, HOST};
+use reqwest::ResponseBuilderExt;
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use url::{Position, Url};
+
+const MAX_CONNECT_RESPONSE_BYTES: usize = 16 * 1024;
+
+/// Proxy endpoint and credentials used to open CONNECT tunnels
+#[derive(Debug, Clone)]
+pub(super) struct TunnelProxy {
+    pub(super) address: String,
+    /// `Proxy-Authorization` value for the proxy's credentials
+    pub(super) authorization: Option<String>,
+}
+
+/// Response head limits hyper enforces while it parses
+/// (`--max-headers`, `--max-header-size`)
+#[derive(Debug, Clone, Copy)]
+pub(super) struct HeadLimits {
+    pub(super) max_headers: usize,
+    pub(super) max_header_size: u64,
+}
+
+impl HeadLimits {
+    pub(super) fn from_config(config: &Config) -> Self {
+        Self {
+            max_headers: config.max_headers,
+            max_header_size: config.max_header_size,
+        }
+    }
+
+    /// Read buffer size that holds a head of `max_header_size` bytes; hyper
+    /// needs room for at least one 8 KiB read
+    pub(super) fn buf_size(self) -> usize {
+        usize::try_from(self.max_header_size)
+            .unwrap_or(usize::MAX)
+            .max(8192)
+    }
+}
+
+/// Send `request` over a new CONNECT tunnel to its host through `proxy`;
+/// the tunnel is closed once the response body has been read
+pub(crate) async fn send(
+    proxy: &ProxyConfig,
+    request: reqwest::Request,
+    config: &Config,
+) -> Result<reqwest::Response> {
+    let tunnel_proxy = tunnel_proxy(proxy)?;
+    let target = target(request.url())?;
+    let stream = open_with_timeout(&tunnel_proxy, &target, config.connect_timeout).await?;
+    exchange(stream, request, true, HeadLimits::from_config(config)).await
+}
+
+/// `HOST:PORT` a CONNECT tunnel for `url` leads to
+pub(super) fn target(url: &Url) -> Result<String> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| RurlError::InvalidUrl(format!("URL has no host: {}", url)))?;
+    Ok(format!(
+        "{}:{}",
+        host,
+        url.port_or_known_default().unwrap_or(80)
+    ))
+}
+
+/// Open a tunnel to `target`, failing after `connect_timeout`
+async fn open_with_timeout(
+    proxy: &TunnelProxy,
+    target: &str,
+    connect_timeout: Duration,
+) -> Result<BufReader<tokio::net::TcpStream>> {
+    tokio::time::timeout(connect_timeout, open(proxy, target))
+        .await
+        .map_err(|_| RurlError::ConnectTimeout)?
+        .map_err(|err| RurlError::Proxy(format!("Proxy tunnel to {} failed: {}", target, err)))
+}
+
+/// Send `request` over `io` with hyper, as HTTP/1.1, and read the response
+/// head; the path is enough in `origin_form`, otherwise the request line
+/// carries the full URL, as a forward proxy expects
+pub(super) async fn exchange<T>(
+    io: T,
+    request: reqwest::Request,
+    origin_form: bool,
+    limits: HeadLimits,
+) -> Result<reqwest::Response>
+where
+    T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let url = request.url().clone();
+    let authority = &url[Position::BeforeHost..Position::AfterPort];
+    let (mut sender, connection) = hyper::client::conn::http1::Builder::new()
+        .max_headers(limits.max_headers)
+        .max_buf_size(limits.buf_size())
+        .handshake(TokioIo::new(io))
+        .await
+        .map_err(|err| RurlError::from_hyper(&err))?;
+    let peer = authority.to_string();
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            log::debug!("Connection for {} closed: {}", peer, err);
+        }
+    });
+
+    let mut request =
+        http::Request::<reqwest::Body>::try_from(request).map_err(RurlError::transport)?;
+    let uri = match origin_form {
+        true => &url[Position::BeforePath..Position::AfterQuery],
+        false => &url[..Position::AfterQuery],
+    };
+    *request.uri_mut() = uri
+        .parse()
+        .map_err(|e| RurlError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))?;
+    if !request.headers().contains_key(HOST) {
+        let host = HeaderValue::from_str(authority)
+            .map_err(|e| RurlError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))?;
+        request.headers_mut().insert(HOST, host);
+    }
+    let response = sender
+        .send_request(request)
+        .await
+        .map_err(|err| RurlError::from_hyper(&err))?;
+
+    let (parts, body) = response.into_parts();
+    let mut builder = http::Response::builder()
+        .status(parts.status)
+        .version(parts.version)
+        .url(url);
+    if let Some(headers) = builder.headers_mut() {
+        *headers = parts.headers;
+    }
+    let response = builder
+        .body(reqwest::Body::wrap(body))
+        .map_err(|e| RurlError::Recv(e.to_string()))?;
+    Ok(reqwest::Response::from(response))
+}
+
+pub(super) fn tunnel_proxy(proxy: &ProxyConfig) -> Result<TunnelProxy> {
+    let url = Url::parse(&proxy.url)
+        .map_err(|e| RurlError::Proxy(format!("Invalid proxy '{}': {}", proxy.url, e)))?;
+    if url.scheme() != "http" {
+        return Err(RurlError::Proxy(format!(
+            "--proxytunnel requires an HTTP proxy, got '{}'",
+            url.scheme()
+        )));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| RurlError::Proxy(format!("Proxy '{}' has no host", proxy.url)))?;
+    let port = url.port_or_known_default().unwrap_or(1080);
+    let authorization = proxy
+        .username
+        .as_ref()
+        .map(|username| Auth::basic_auth(username, proxy.password.as_deref().unwrap_or_default()));
+
+    Ok(TunnelProxy {
+        address: format!("{}:{}", host, port),
+        authorization,
+    })
+}
+
+/// Connect to the proxy and open a tunnel to `target`
+async fn open(proxy: &TunnelProxy, target: &str) -> io::Result<BufReader<tokio::net::TcpStream>> {
+    let upstream = tokio::net::TcpStream::connect(&proxy.address).await?;
+    handshake(upstream, proxy, target).await
+}
+
+/// Ask the proxy on `upstream` for a tunnel to `target`; bytes the proxy
+/// sent after its response stay in the buffer, as they already belong to
+/// the tunnel
+pub(super) async fn handshake(
+    mut upstream: tokio::net::TcpStream,
+    proxy: &TunnelProxy,
+    target: &str,
+) -> io::Result<BufReader<tokio::net::TcpStream>> {
+    upstream
+        .write_all(connect_request(target, proxy.authorization.as_deref()).as_bytes())
+        .await?;
+    let mut reader = BufReader::new(upstream);
+    read_connect_response(&mut reader).await?;
+    Ok(reader)
+}
+
+fn connect_request(target: &str, authorization: Option<&str>) -> String {
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some(authorization) = authorization {
+        request.push_str(&format!("Proxy-Authorization: {}\r\n", authorization));
+    }
+    request.push_str("Proxy-Connection: Keep-Alive\r\n\r\n");
+    request
+}
+
+async fn read_connect_response<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<()> {
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line).await?;
+    let status = parse_connect_status(&status_line).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Malformed CONNECT response: {}", status_line.trim_end()),
+        )
+    })?;
+
+    let mut consumed = status_line.len();
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).await?;
+        consumed += read;
+        if read == 0 || line == "\r\n" || line == "\n" {
+            break;
+        }
+        if consumed > MAX_CONNECT_RESPONSE_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "CONNECT response headers too large",
+            ));
+        }
+    }
+
+    if !(200..300).contains(&status) {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("Proxy refused CONNECT with status {}", status),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_connect_status(status_line: &str) -> Option<u16> {
+    let mut parts = status_line.split_whitespace();
+    let version = parts.next()?;
+    if !version.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{connect_request, parse_connect_status, read_connect_response};
+    use tokio::io::BufReader;
+
+    #[test]
+    fn connect_request_includes_authorization() {
+        let request = connect_request("example.com:80", Some("Basic dTpw"));
+        assert!(request.starts_with("CONNECT example.com:80 HTTP/1.1\r\n"));
+        assert!(request.contains("Host: example.com:80\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic dTpw\r\n"));
+        assert!(request.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn parse_connect_status_reads_code() {
+        assert_eq!(
+            parse_connect_status("HTTP/1.1 200 Connection established\r\n"),
+            Some(200)
+        );
+        assert_eq!(parse_connect_status("garbage"), None);
+    }
+
+    #[tokio::test]
+    async fn read_connect_response_rejects_non_success() {
+        let mut ok = BufReader::new(&b"HTTP/1.1 200 OK\r\nX-Proxy: 1\r\n\r\nrest"[..]);
+        read_connect_response(&mut ok)
+            .await
+            .expect("tunnel established");
+        assert_eq!(ok.buffer(), b"rest");
+
+        let mut denied = BufReader::new(&b"HTTP/1.1 403 Forbidden\r\n\r\n"[..]);
+        assert!(read_connect_response(&mut denied).await.is_err());
+    }
+}
@@ -0,0 +1,660 @@
+//! Streaming request bodies
+//!
+//! `-d @-` and `-T FILE|-` send the body straight from stdin or a file instead
+//! of reading it into memory first. Stdin goes out chunked unless
+//! `--upload-length` gives its size, and it can only be sent once: a retry or a
+//! 307/308 redirect that would replay it fails instead.
+//!
+//! `--trailer` fields are sent after the last chunk, so bodies carrying them
+//! are always chunked.
+//!
+//! `-F NAME=@FILE` parts are streamed from disk as well, with their lengths
+//! known so the form still gets a Content-Length.
+//!
+//! `--data-compress` compresses the body on the fly, which also makes it
+//! chunked since the compressed size is not known up front.
+//!
+//! `--upload-resume` asks the server how much of a `-T` file it already holds
+//! and sends only the rest, announced with `Content-Range`.
+//!
+//! The connection only pulls more of a streamed body once it has written the
+//! previous chunk, so [`WriteWatch`] detects a peer that stopped accepting
+//! data for `--write-timeout`.
+
+use crate::config::{
+    BodyCompression, FormField, FormValue, TrailerSpec, TrailerValue, UploadSource,
+};
+use crate::error::{Result, RurlError};
+use crate::progress::{ProgressObserver, TransferPhase};
+use crate::utils::FileUtils;
+use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder, ZstdEncoder};
+use bytes::Bytes;
+use futures_util::Stream;
+use http_body::Frame;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Body, StatusCode};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncSeekExt, BufReader, ReadBuf};
+use tokio::time::Instant;
+use tokio_util::io::ReaderStream;
+
+type UploadReader = Box<dyn AsyncRead + Send + Sync + Unpin>;
+
+/// Part of the upload source to send
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct UploadExtent {
+    /// Bytes to skip at the start of a file (`--upload-resume`)
+    pub(crate) offset: u64,
+    /// Content-Length to send instead of the source's size (`--upload-length`)
+    pub(crate) length: Option<u64>,
+}
+
+/// Opens the upload source once per request attempt
+#[derive(Debug, Default)]
+pub(crate) struct UploadOpener {
+    stdin_taken: AtomicBool,
+}
+
+impl UploadOpener {
+    /// Body for the `extent` of `source` with its Content-Length, if known.
+    /// Bytes read from the source are reported to `progress`
+    pub(crate) async fn open(
+        &self,
+        source: &UploadSource,
+        extent: UploadExtent,
+        trailers: &[TrailerSpec],
+        compression: Option<BodyCompression>,
+        progress: Option<Arc<dyn ProgressObserver>>,
+        watch: Option<WriteWatch>,
+    ) -> Result<(Body, Option<u64>)> {
+        let (reader, size): (UploadReader, Option<u64>) = match source {
+            UploadSource::Stdin => {
+                if self.stdin_taken.swap(true, Ordering::SeqCst) {
+                    return Err(RurlError::Unsupported(
+                        "Cannot resend a request body streamed from stdin".to_string(),
+                    ));
+                }
+                (Box::new(tokio::io::stdin()), None)
+            }
+            UploadSource::File(path) => {
+                FileUtils::check_file_readable(path)?;
+                let mut file = tokio::fs::File::open(path).await?;
+                let size = file.metadata().await?.len();
+                if extent.offset > 0 {
+                    file.seek(io::SeekFrom::Start(extent.offset)).await?;
+                }
+                (Box::new(file), Some(size.saturating_sub(extent.offset)))
+            }
+        };
+        let length = extent.length;
+        let reader: UploadReader = match progress {
+            Some(observer) => {
+                observer.on_phase(TransferPhase::Uploading);
+                Box::new(ProgressReader {
+                    inner: reader,
+                    observer,
+                    sent: Arc::default(),
+                    total: length.or(size),
+                })
+            }
+            None => reader,
+        };
+        let (reader, length) = match compression {
+            Some(compression) => (compress(reader, compression), None),
+            None => (reader, length.or(size)),
+        };
+        let reader: UploadReader = match watch {
+            Some(watch) => Box::new(WatchedReader {
+                inner: reader,
+                watch,
+            }),
+            None => reader,
+        };
+        if trailers.is_empty() {
+            Ok((Body::wrap_stream(ReaderStream::new(reader)), length))
+        } else {
+            Ok((trailer_body(reader, trailers)?, None))
+        }
+    }
+}
+
+/// Multipart form for `fields`; file parts are streamed from disk and the
+/// bytes read from all files are reported to `progress` as one upload
+pub(crate) async fn form_body(
+    fields: &[FormField],
+    progress: Option<Arc<dyn ProgressObserver>>,
+) -> Result<Form> {
+    let mut total = 0;
+    for field in fields {
+        if let FormValue::File(path) = &field.value {
+            FileUtils::check_file_readable(path)?;
+            total += tokio::fs::metadata(path).await?.len();
+        }
+    }
+    if let Some(observer) = &progress {
+        observer.on_phase(TransferPhase::Uploading);
+    }
+    // Shared so progress counts across all file parts
+    let sent = Arc::new(AtomicU64::new(0));
+    let mut form = Form::new();
+    for field in fields {
+        let mut part = match &field.value {
+            FormValue::Text(text) => Part::text(text.clone()),
+            FormValue::File(path) => {
+                let file = tokio::fs::File::open(path).await?;
+                let size = file.metadata().await?.len();
+                let reader: UploadReader = match &progress {
+                    Some(observer) => Box::new(ProgressReader {
+                        inner: Box::new(file),
+                        observer: observer.clone(),
+                        sent: sent.clone(),
+                        total: Some(total),
+                    }),
+                    None => Box::new(file),
+                };
+                let part =
+                    Part::stream_with_length(Body::wrap_stream(ReaderStream::new(reader)), size);
+                let name = field.filename.clone().or_else(|| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                });
+                match name {
+                    Some(name) => part.file_name(name),
+                    None => part,
+                }
+            }
+        };
+        let content_type = match (&field.content_type, &field.value) {
+            (Some(content_type), _) => Some(content_type.clone()),
+            (None, FormValue::File(path)) => Some(
+                mime_guess::from_path(path)
+                    .first_or_octet_stream()
+                    .to_string(),
+            ),
+            (None, FormValue::Text(_)) => None,
+        };
+        if let Some(content_type) = content_type {
+            part = part.mime_str(&content_type).map_err(|_| {
+                RurlError::Config(format!(
+                    "Invalid content type '{}' for form field '{}'",
+                    content_type, field.name
+                ))
+            })?;
+        }
+        form = form.part(field.name.clone(), part);
+    }
+    Ok(form)
+}
+
+/// Reports the bytes read from `inner` as upload progress
+struct ProgressReader {
+    inner: UploadReader,
+    observer: Arc<dyn ProgressObserver>,
+    /// Bytes read so far, possibly shared with other readers of one upload
+    sent: Arc<AtomicU64>,
+    total: Option<u64>,
+}
+
+impl AsyncRead for ProgressReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - before;
+        if read > 0 {
+            let sent = self.sent.fetch_add(read as u64, Ordering::Relaxed) + read as u64;
+            self.observer.on_upload(sent, self.total);
+        }
+        result
+    }
+}
+
+/// When a streamed request body was last pulled by the connection
+#[derive(Debug, Clone)]
+pub(crate) struct WriteWatch {
+    state: Arc<Mutex<WatchState>>,
+}
+
+#[derive(Debug, Default)]
+struct WatchState {
+    /// Unset until the connection first asks for body data
+    last_pull: Option<Instant>,
+    finished: bool,
+}
+
+impl WriteWatch {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(WatchState::default())),
+        }
+    }
+
+    fn record(&self, finished: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.last_pull = Some(Instant::now());
+        state.finished |= finished;
+    }
+
+    /// Resolve once the body has not been pulled for `limit`; never resolves
+    /// after the whole body was sent
+    pub(crate) async fn stalled(&self, limit: Duration) {
+        loop {
+            let (last_pull, finished) = {
+                let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                (state.last_pull, state.finished)
+            };
+            match last_pull {
+                _ if finished => return std::future::pending().await,
+                Some(last_pull) if Instant::now() >= last_pull + limit => return,
+                Some(last_pull) => tokio::time::sleep_until(last_pull + limit).await,
+                // Connecting is bounded by --connect-timeout instead
+                None => tokio::time::sleep(limit).await,
+            }
+        }
+    }
+}
+
+/// Records each read from `inner` in a [`WriteWatch`]
+struct WatchedReader {
+    inner: UploadReader,
+    watch: WriteWatch,
+}
+
+impl AsyncRead for WatchedReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = result {
+            self.watch.record(buf.filled().len() == before);
+        }
+        result
+    }
+}
+
+/// Chunked body for in-memory `data`, compressed when asked and followed by
+/// `trailers`
+pub(crate) fn data_body(
+    data: &str,
+    trailers: &[TrailerSpec],
+    compression: Option<BodyCompression>,
+) -> Result<Body> {
+    let reader: UploadReader = Box::new(io::Cursor::new(data.as_bytes().to_vec()));
+    let reader = match compression {
+        Some(compression) => compress(reader, compression),
+        None => reader,
+    };
+    if trailers.is_empty() {
+        Ok(Body::wrap_stream(ReaderStream::new(reader)))
+    } else {
+        trailer_body(reader, trailers)
+    }
+}
+
+/// Compress everything read from `reader`
+fn compress(reader: UploadReader, compression: BodyCompression) -> UploadReader {
+    let reader = BufReader::new(reader);
+    match compression {
+        BodyCompression::Gzip => Box::new(GzipEncoder::new(reader)),
+        BodyCompression::Brotli => Box::new(BrotliEncoder::new(reader)),
+        BodyCompression::Zstd => Box::new(ZstdEncoder::new(reader)),
+    }
+}
+
+/// Bytes of a resumable upload the server already holds, from the response
+/// to a HEAD probe: `Upload-Offset`, the `Range: bytes=0-N` of a 308, or the
+/// Content-Length of the partially stored resource
+pub(crate) fn received_offset(status: StatusCode, headers: &HeaderMap) -> u64 {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    if let Some(offset) = header("upload-offset").and_then(|value| value.parse().ok()) {
+        return offset;
+    }
+    if status == StatusCode::PERMANENT_REDIRECT {
+        return header("range")
+            .and_then(|value| value.strip_prefix("bytes=0-"))
+            .and_then(|end| end.parse::<u64>().ok())
+            .map_or(0, |end| end + 1);
+    }
+    if status.is_success() {
+        return header("content-length")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+    }
+    0
+}
+
+/// `Trailer` request header announcing the fields, which hyper requires
+pub(crate) fn trailer_header_value(trailers: &[TrailerSpec]) -> String {
+    trailers
+        .iter()
+        .map(|trailer| trailer.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn trailer_body(reader: UploadReader, trailers: &[TrailerSpec]) -> Result<Body> {
+    let fields = trailers
+        .iter()
+        .map(|trailer| {
+            let name = HeaderName::from_bytes(trailer.name.as_bytes()).map_err(|_| {
+                RurlError::Config(format!("Invalid trailer name: {}", trailer.name))
+            })?;
+            if let TrailerValue::Literal(value) = &trailer.value {
+                HeaderValue::from_str(value)
+                    .map_err(|_| RurlError::Config(format!("Invalid trailer value: {}", value)))?;
+            }
+            Ok((name, trailer.value.clone()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Body::wrap(TrailerBody {
+        stream: ReaderStream::new(reader),
+        sha256: Sha256::new(),
+        sha1: Sha1::new(),
+        fields,
+        finished: false,
+    }))
+}
+
+/// Streams a reader, hashing what is sent, then yields the trailer fields
+struct TrailerBody {
+    stream: ReaderStream<UploadReader>,
+    sha256: Sha256,
+    sha1: Sha1,
+    fields: Vec<(HeaderName, TrailerValue)>,
+    finished: bool,
+}
+
+impl TrailerBody {
+    fn trailers(&mut self) -> HeaderMap {
+        let sha256 = format!("{:x}", self.sha256.finalize_reset());
+        let sha1 = format!("{:x}", self.sha1.finalize_reset());
+        let mut map = HeaderMap::new();
+        for (name, value) in &self.fields {
+            let value = match value {
+                TrailerValue::Literal(value) => value.as_str(),
+                TrailerValue::Sha256 => sha256.as_str(),
+                TrailerValue::Sha1 => sha1.as_str(),
+            };
+            if let Ok(value) = HeaderValue::from_str(value) {
+                map.append(name.clone(), value);
+            }
+        }
+        map
+    }
+}
+
+impl http_body::Body for TrailerBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Frame<Bytes>, io::Error>>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        match Pin::new(&mut self.stream).poll_next(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.sha256.update(&chunk);
+                self.sha1.update(&chunk);
+                Poll::Ready(Some(Ok(Frame::data(chunk))))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(None) => {
+                self.finished = true;
+                Poll::Ready(Some(Ok(Frame::trailers(self.trailers()))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{data_body, form_body, received_offset, UploadExtent, UploadOpener, WriteWatch};
+    use crate::config::{BodyCompression, FormField, FormValue, TrailerSpec, UploadSource};
+    use crate::progress::ProgressObserver;
+    use futures_util::TryStreamExt;
+    use http_body_util::BodyExt;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn file_upload_reports_file_size() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("body.bin");
+        std::fs::write(&path, b"0123456789").expect("write");
+
+        let opener = UploadOpener::default();
+        let source = UploadSource::File(path);
+        let (_, length) = opener
+            .open(&source, UploadExtent::default(), &[], None, None, None)
+            .await
+            .expect("open");
+        assert_eq!(length, Some(10));
+        // Files can be reopened for retries and redirects
+        let forced = UploadExtent {
+            offset: 0,
+            length: Some(4),
+        };
+        let (_, length) = opener
+            .open(&source, forced, &[], None, None, None)
+            .await
+            .expect("reopen");
+        assert_eq!(length, Some(4));
+    }
+
+    #[tokio::test]
+    async fn resumed_file_upload_sends_the_remainder() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("body.bin");
+        std::fs::write(&path, b"0123456789").expect("write");
+
+        let extent = UploadExtent {
+            offset: 4,
+            length: None,
+        };
+        let (body, length) = UploadOpener::default()
+            .open(&UploadSource::File(path), extent, &[], None, None, None)
+            .await
+            .expect("open");
+        assert_eq!(length, Some(6));
+        let collected = body.collect().await.expect("collect");
+        assert_eq!(collected.to_bytes().as_ref(), b"456789");
+    }
+
+    #[test]
+    fn received_offset_reads_probe_responses() {
+        let mut headers = HeaderMap::new();
+        headers.insert("range", HeaderValue::from_static("bytes=0-99"));
+        assert_eq!(
+            received_offset(StatusCode::PERMANENT_REDIRECT, &headers),
+            100
+        );
+        assert_eq!(
+            received_offset(StatusCode::PERMANENT_REDIRECT, &HeaderMap::new()),
+            0
+        );
+
+        headers.insert("content-length", HeaderValue::from_static("40"));
+        assert_eq!(received_offset(StatusCode::OK, &headers), 40);
+        assert_eq!(received_offset(StatusCode::NOT_FOUND, &headers), 0);
+
+        headers.insert("upload-offset", HeaderValue::from_static("7"));
+        assert_eq!(received_offset(StatusCode::NOT_FOUND, &headers), 7);
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(u64, Option<u64>)>>);
+
+    impl ProgressObserver for Recorder {
+        fn on_upload(&self, sent: u64, total: Option<u64>) {
+            self.0.lock().unwrap().push((sent, total));
+        }
+    }
+
+    #[tokio::test]
+    async fn file_upload_reports_progress() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("body.bin");
+        std::fs::write(&path, b"0123456789").expect("write");
+
+        let recorder = Arc::new(Recorder::default());
+        let (body, _) = UploadOpener::default()
+            .open(
+                &UploadSource::File(path),
+                UploadExtent::default(),
+                &[],
+                None,
+                Some(recorder.clone()),
+                None,
+            )
+            .await
+            .expect("open");
+        let collected = body.collect().await.expect("collect");
+        assert_eq!(collected.to_bytes().len(), 10);
+        let updates = recorder.0.lock().unwrap();
+        assert_eq!(updates.last(), Some(&(10, Some(10))));
+    }
+
+    #[tokio::test]
+    async fn stdin_upload_is_single_use() {
+        let opener = UploadOpener::default();
+        let _first = opener
+            .open(
+                &UploadSource::Stdin,
+                UploadExtent::default(),
+                &[],
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("open");
+        assert!(opener
+            .open(
+                &UploadSource::Stdin,
+                UploadExtent::default(),
+                &[],
+                None,
+                None,
+                None
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn write_watch_fires_only_while_body_is_pending() {
+        let watch = WriteWatch::new();
+        let limit = Duration::from_millis(50);
+        watch.record(false);
+        assert!(
+            tokio::time::timeout(Duration::from_secs(5), watch.stalled(limit))
+                .await
+                .is_ok()
+        );
+        watch.record(true);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), watch.stalled(limit))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn data_with_trailers_appends_checksum() {
+        let trailers = vec![
+            TrailerSpec::parse("X-Checksum: @sha256").expect("spec"),
+            TrailerSpec::parse("X-Note: done").expect("spec"),
+        ];
+        let body = data_body("abc", &trailers, None).expect("body");
+        let collected = body.collect().await.expect("collect");
+        let trailers = collected.trailers().cloned().expect("trailers");
+        assert_eq!(
+            trailers.get("x-checksum").expect("checksum"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(trailers.get("x-note").expect("note"), "done");
+        assert_eq!(collected.to_bytes().as_ref(), b"abc");
+    }
+
+    #[tokio::test]
+    async fn compressed_data_round_trips() {
+        use async_compression::tokio::bufread::GzipDecoder;
+        use tokio::io::AsyncReadExt;
+
+        let body = data_body("hello hello hello", &[], Some(BodyCompression::Gzip)).expect("body");
+        let compressed = body.collect().await.expect("collect").to_bytes();
+        assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+        let mut decoded = String::new();
+        GzipDecoder::new(&compressed[..])
+            .read_to_string(&mut decoded)
+            .await
+            .expect("decode");
+        assert_eq!(decoded, "hello hello hello");
+    }
+
+    #[tokio::test]
+    async fn form_file_parts_report_combined_progress() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let first = dir.path().join("a.bin");
+        let second = dir.path().join("b.bin");
+        std::fs::write(&first, b"0123").expect("write");
+        std::fs::write(&second, b"456789").expect("write");
+        let fields = [
+            FormField {
+                name: "note".to_string(),
+                value: FormValue::Text("hi".to_string()),
+                content_type: None,
+                filename: None,
+            },
+            FormField {
+                name: "a".to_string(),
+                value: FormValue::File(first),
+                content_type: None,
+                filename: None,
+            },
+            FormField {
+                name: "b".to_string(),
+                value: FormValue::File(second),
+                content_type: Some("image/png".to_string()),
+                filename: Some("photo.png".to_string()),
+            },
+        ];
+
+        let recorder = Arc::new(Recorder::default());
+        let form = form_body(&fields, Some(recorder.clone()))
+            .await
+            .expect("form");
+        let chunks: Vec<_> = form.into_stream().try_collect().await.expect("collect");
+        let text = String::from_utf8_lossy(&chunks.concat()).into_owned();
+        assert!(text.contains("filename=\"a.bin\"\r\nContent-Type: application/octet-stream"));
+        assert!(text.contains("filename=\"photo.png\"\r\nContent-Type: image/png"));
+        assert!(text.contains("456789"));
+        let updates = recorder.0.lock().unwrap();
+        assert_eq!(updates.last(), Some(&(10, Some(10))));
+    }
+}
@@ -1,9 +1,23 @@
+//! Localized messages
+//!
+//! Errors, warnings and the clap help text are looked up in
+//! `locales/LANG/messages.ftl`, falling back to en-US for missing messages.
+//! The language comes from `--lang`, then `RURL_LANG`, then the usual locale
+//! variables.
+
 use crate::error::RurlError;
+use clap::Command;
 use fluent_templates::fluent_bundle::FluentValue;
 use fluent_templates::{static_loader, Loader};
 use std::collections::HashMap;
+use std::sync::Mutex;
 use unic_langid::LanguageIdentifier;
 
+/// Environment variable overriding the locale variables
+pub const LANG_ENV: &str = "RURL_LANG";
+
+static LANGUAGE_OVERRIDE: Mutex<Option<LanguageIdentifier>> = Mutex::new(None);
+
 static_loader! {
     static LOCALES = {
         locales: "locales",
@@ -11,49 +25,186 @@ static_loader! {
     };
 }
 
+/// Localized message of `err`, followed by a `hint:` line when the error
+/// carries or implies a [`Hint`](crate::error::Hint)
 pub fn localize_error(err: &RurlError) -> String {
     let langid = resolve_language();
+    let message = localize_message(&langid, err.unhinted());
+    match err.hint() {
+        Some(hint) => {
+            let mut args = HashMap::new();
+            args.insert(
+                "hint",
+                FluentValue::from(LOCALES.lookup(&langid, hint.message_id())),
+            );
+            format!(
+                "{}\n{}",
+                message,
+                LOCALES.lookup_with_args(&langid, "error-hint", &args)
+            )
+        }
+        None => message,
+    }
+}
+
+fn localize_message(langid: &LanguageIdentifier, err: &RurlError) -> String {
     match err {
-        RurlError::InvalidUrl(detail) => message_with_detail(&langid, "error-invalid-url", detail),
-        RurlError::Http(detail) => message_with_detail(&langid, "error-http", &detail.to_string()),
-        RurlError::Json(detail) => message_with_detail(&langid, "error-json", &detail.to_string()),
-        RurlError::Ssl(detail) => message_with_detail(&langid, "error-ssl", detail),
-        RurlError::Proxy(detail) => message_with_detail(&langid, "error-proxy", detail),
-        RurlError::Auth(detail) => message_with_detail(&langid, "error-auth", detail),
-        RurlError::Config(detail) => message_with_detail(&langid, "error-config", detail),
-        RurlError::Timeout => LOCALES.lookup(&langid, "error-timeout"),
+        RurlError::InvalidUrl(detail) => message_with_detail(langid, "error-invalid-url", detail),
+        RurlError::Http(detail) => message_with_detail(langid, "error-http", &detail.to_string()),
+        RurlError::Json(detail) => message_with_detail(langid, "error-json", &detail.to_string()),
+        RurlError::Ssl(detail) => message_with_detail(langid, "error-ssl", detail),
+        RurlError::Proxy(detail) => message_with_detail(langid, "error-proxy", detail),
+        RurlError::Auth(detail) => message_with_detail(langid, "error-auth", detail),
+        RurlError::Config(detail) => message_with_detail(langid, "error-config", detail),
+        RurlError::Timeout => LOCALES.lookup(langid, "error-timeout"),
+        RurlError::TooSlow(detail) => message_with_detail(langid, "error-too-slow", detail),
         RurlError::RedirectLimitExceeded(limit) => {
             let mut args = HashMap::new();
             args.insert("limit", FluentValue::from(*limit));
-            LOCALES.lookup_with_args(&langid, "error-redirect-limit", &args)
+            LOCALES.lookup_with_args(langid, "error-redirect-limit", &args)
         }
         RurlError::PermissionDenied(detail) => {
-            message_with_detail(&langid, "error-permission-denied", detail)
+            message_with_detail(langid, "error-permission-denied", detail)
         }
         RurlError::FileNotFound(detail) => {
-            message_with_detail(&langid, "error-file-not-found", detail)
+            message_with_detail(langid, "error-file-not-found", detail)
         }
-        RurlError::Unsupported(detail) => message_with_detail(&langid, "error-unsupported", detail),
-        RurlError::Io(detail) => message_with_detail(&langid, "error-io", &detail.to_string()),
+        RurlError::Unsupported(detail) => message_with_detail(langid, "error-unsupported", detail),
+        RurlError::Io(detail) => message_with_detail(langid, "error-io", &detail.to_string()),
         RurlError::BrowserCookie(detail) => {
-            message_with_detail(&langid, "error-browser-cookie", detail)
+            message_with_detail(langid, "error-browser-cookie", detail)
+        }
+        RurlError::Aborted => LOCALES.lookup(langid, "error-aborted"),
+        RurlError::ResolveHost(detail) => message_with_detail(langid, "error-resolve-host", detail),
+        RurlError::ConnectTimeout => LOCALES.lookup(langid, "error-connect-timeout"),
+        RurlError::EmptyReply => LOCALES.lookup(langid, "error-empty-reply"),
+        RurlError::Recv(detail) => message_with_detail(langid, "error-recv", detail),
+        RurlError::HeadersTooLarge(detail) => {
+            message_with_detail(langid, "error-headers-too-large", detail)
         }
+        RurlError::HostBlocked(detail) => message_with_detail(langid, "error-host-blocked", detail),
+        RurlError::InternalTarget(detail) => {
+            message_with_detail(langid, "error-internal-target", detail)
+        }
+        RurlError::HttpStatus(status) => {
+            let mut args = HashMap::new();
+            args.insert("status", FluentValue::from(*status));
+            LOCALES.lookup_with_args(langid, "error-http-status", &args)
+        }
+        RurlError::ResponsesDiffer => LOCALES.lookup(langid, "error-responses-differ"),
+        RurlError::NoMatch => LOCALES.lookup(langid, "error-no-match"),
+        RurlError::Hinted { error, .. } => localize_message(langid, error),
     }
 }
 
+/// Localized message `key`, with `args` filled in
+pub fn localize(key: &str, args: &[(&str, &str)]) -> String {
+    let args: HashMap<&str, FluentValue> = args
+        .iter()
+        .map(|(name, value)| (*name, FluentValue::from(*value)))
+        .collect();
+    LOCALES.lookup_with_args(&resolve_language(), key, &args)
+}
+
 fn message_with_detail(langid: &LanguageIdentifier, key: &str, detail: &str) -> String {
     let mut args = HashMap::new();
     args.insert("detail", FluentValue::from(detail));
     LOCALES.lookup_with_args(langid, key, &args)
 }
 
+/// Use `code` (e.g. `ja`, `zh-CN`) instead of the environment's language
+pub fn set_language(code: &str) -> Result<(), String> {
+    let langid = parse_language(code)?;
+    *LANGUAGE_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) = Some(langid);
+    Ok(())
+}
+
+/// Parse a language code, mapping a bare language (`de`) to the bundled
+/// locale of that language (`de-DE`)
+pub fn parse_language(code: &str) -> Result<LanguageIdentifier, String> {
+    normalize_lang(code.to_string())
+        .and_then(|lang| lang.parse().ok())
+        .map(|langid| bundled_locale(&langid).unwrap_or(langid))
+        .ok_or_else(|| format!("invalid language code '{}'", code))
+}
+
+fn bundled_locale(langid: &LanguageIdentifier) -> Option<LanguageIdentifier> {
+    let mut locales = LOCALES.locales();
+    if locales.any(|locale| locale == langid) {
+        return Some(langid.clone());
+    }
+    LOCALES
+        .locales()
+        .find(|locale| locale.language == langid.language)
+        .cloned()
+}
+
+/// Value of `--lang CODE` or `--lang=CODE` in raw arguments, which must be
+/// known before the localized help is built
+pub fn lang_from_args<I>(args: I) -> Option<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--lang" {
+            return args.next();
+        }
+        if let Some(code) = arg.strip_prefix("--lang=") {
+            return Some(code.to_string());
+        }
+    }
+    None
+}
+
+/// Replace the help and about texts of `command` and its subcommands with
+/// the `help-*` and `about-*` messages of the current language
+pub fn localize_command(command: Command) -> Command {
+    localize_command_for(command, &resolve_language(), "")
+}
+
+fn localize_command_for(command: Command, langid: &LanguageIdentifier, parent: &str) -> Command {
+    let path = if parent.is_empty() {
+        command.get_name().to_string()
+    } else {
+        format!("{}-{}", parent, command.get_name())
+    };
+    let mut command = command
+        .mut_args(|arg| {
+            let key = format!("help-{}-{}", path, arg.get_id());
+            match LOCALES.try_lookup(langid, &key) {
+                Some(help) => arg.help(help),
+                None => arg,
+            }
+        })
+        .mut_subcommands(|subcommand| localize_command_for(subcommand, langid, &path));
+    if let Some(about) = LOCALES.try_lookup(langid, &format!("about-{}", path)) {
+        command = command.about(about);
+    }
+    command
+}
+
 fn resolve_language() -> LanguageIdentifier {
+    if let Some(langid) = LANGUAGE_OVERRIDE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        return langid;
+    }
+    if let Some(langid) = std::env::var(LANG_ENV)
+        .ok()
+        .and_then(|code| parse_language(&code).ok())
+    {
+        return langid;
+    }
     for key in ["LC_ALL", "LC_MESSAGES", "LANG"] {
         if let Ok(value) = std::env::var(key) {
-            if let Some(lang) = normalize_lang(value) {
-                if let Ok(langid) = lang.parse::<LanguageIdentifier>() {
-                    return langid;
-                }
+            if let Ok(langid) = parse_language(&value) {
+                return langid;
             }
         }
     }
@@ -72,8 +223,87 @@ fn normalize_lang(value: String) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{localize_error, normalize_lang, resolve_language};
-    use crate::error::RurlError;
+    use super::{
+        lang_from_args, localize_command_for, localize_error, normalize_lang, parse_language,
+        resolve_language, LOCALES,
+    };
+    use crate::cli::create_app;
+    use crate::error::{Hint, RurlError};
+    use clap::Command;
+    use fluent_templates::Loader;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn assert_help_keys(command: &Command, parent: &str) {
+        let path = if parent.is_empty() {
+            command.get_name().to_string()
+        } else {
+            format!("{}-{}", parent, command.get_name())
+        };
+        let en_us = parse_language("en-US").expect("langid");
+        let key = format!("about-{}", path);
+        assert!(
+            LOCALES.try_lookup(&en_us, &key).is_some(),
+            "missing {}",
+            key
+        );
+        for arg in command.get_arguments() {
+            let key = format!("help-{}-{}", path, arg.get_id());
+            assert!(
+                LOCALES.try_lookup(&en_us, &key).is_some(),
+                "missing {}",
+                key
+            );
+        }
+        for subcommand in command.get_subcommands() {
+            assert_help_keys(subcommand, &path);
+        }
+    }
+
+    #[test]
+    fn every_option_has_en_us_help() {
+        assert_help_keys(&create_app(), "");
+    }
+
+    #[test]
+    fn localize_command_uses_requested_language() {
+        let ja = parse_language("ja").expect("langid");
+        let command = localize_command_for(create_app(), &ja, "");
+        let url = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == "url")
+            .expect("url arg");
+        let en_us = parse_language("en-US").expect("langid");
+        assert_ne!(
+            url.get_help().map(ToString::to_string),
+            LOCALES.try_lookup(&en_us, "help-rurl-url")
+        );
+    }
+
+    #[test]
+    fn lang_from_args_finds_both_forms() {
+        assert_eq!(
+            lang_from_args(args(&["rurl", "--lang", "de", "http://x"])),
+            Some("de".to_string())
+        );
+        assert_eq!(
+            lang_from_args(args(&["rurl", "--lang=zh-CN"])),
+            Some("zh-CN".to_string())
+        );
+        assert_eq!(lang_from_args(args(&["rurl", "--", "--lang=fr"])), None);
+    }
+
+    #[test]
+    fn parse_language_accepts_locale_style_codes() {
+        assert_eq!(
+            parse_language("zh_CN.UTF-8").expect("langid").to_string(),
+            "zh-CN"
+        );
+        assert_eq!(parse_language("de").expect("langid").to_string(), "de-DE");
+        assert!(parse_language("").is_err());
+    }
 
     #[test]
     fn normalize_lang_trims_and_normalizes() {
@@ -91,10 +321,25 @@ mod tests {
         assert!(message.contains("detail"));
     }
 
+    #[test]
+    fn localize_fills_in_args() {
+        let message = localize("warning-host-not-forwarded", &[("target", "example.com")]);
+        assert!(message.contains("example.com"), "{}", message);
+    }
+
+    #[test]
+    fn localize_error_appends_hint() {
+        let err = RurlError::BrowserCookie("locked".to_string()).with_hint(Hint::CloseBrowser);
+        let message = localize_error(&err);
+        let mut lines = message.lines();
+        assert!(lines.next().is_some_and(|line| line.contains("locked")));
+        assert!(lines.next().is_some());
+    }
+
     #[test]
     fn resolve_language_falls_back_to_en() {
         // Ensure env vars don't interfere
-        for key in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        for key in ["RURL_LANG", "LC_ALL", "LC_MESSAGES", "LANG"] {
             std::env::remove_var(key);
         }
         let lang = resolve_language();
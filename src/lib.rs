@@ -6,16 +6,33 @@
 pub mod browser;
 pub mod cli;
 pub mod config;
+pub mod config_file;
+pub mod cookie_source;
+pub mod cookie_store;
+pub mod credentials;
+pub mod daemon;
 pub mod error;
 pub mod exit_code;
+pub mod host_filter;
 pub mod http;
 pub mod i18n;
 pub mod logging;
+pub mod metrics;
 pub mod output;
+pub mod progress;
+pub mod session;
 pub mod ssl;
+pub mod stats;
 pub mod utils;
+pub mod warning;
 
+pub use cli::run_async;
 pub use error::{Result, RurlError};
 
+/// Send the request described by `config` on the caller's runtime
+pub async fn execute(config: config::Config) -> Result<http::ResponseHistory> {
+    http::HttpClient::new(config)?.execute_with_history().await
+}
+
 /// Version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
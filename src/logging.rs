@@ -1,17 +1,213 @@
 //! Logging initialization utilities.
+//!
+//! Log records go to stderr, filtered by `RUST_LOG`, and with `--log-file`
+//! also to a file at their own level, so batch runs keep an audit trail
+//! whatever the console shows.
 
+use crate::error::Result;
+use crate::http::events::HttpEvent;
+use crate::utils::StringUtils;
 use env_logger::Env;
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static LOG_FILE: OnceLock<LogFile> = OnceLock::new();
+
+/// Format of diagnostics written to stderr (`--log-format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
 
 /// Initialize logging with a default filter level.
 pub fn init() {
+    init_with_format(LogFormat::Text);
+}
+
+/// Initialize logging, emitting log records as JSON lines in `LogFormat::Json`.
+pub fn init_with_format(format: LogFormat) {
+    let _ = init_with_file(format, None);
+}
+
+/// File written by `--log-file`, appended to across runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileOptions {
+    pub path: PathBuf,
+    /// Most verbose level written to the file (`--log-level`)
+    pub level: LevelFilter,
+}
+
+/// Initialize logging to stderr and, when given, to a log file in `format`
+pub fn init_with_file(format: LogFormat, file: Option<LogFileOptions>) -> Result<()> {
     let env = Env::default().default_filter_or("info");
-    env_logger::Builder::from_env(env).init();
+    let mut builder = env_logger::Builder::from_env(env);
+    if format == LogFormat::Json {
+        builder.format(|buf, record| writeln!(buf, "{}", json_record(record, SystemTime::now())));
+    }
+    let console = builder.build();
+    let mut max_level = console.filter();
+    if let Some(options) = file {
+        let handle = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&options.path)?;
+        max_level = max_level.max(options.level);
+        let _ = LOG_FILE.set(LogFile {
+            writer: Mutex::new(LineWriter::new(handle)),
+            level: options.level,
+            format,
+        });
+    }
+    if log::set_boxed_logger(Box::new(TeeLogger { console })).is_ok() {
+        log::set_max_level(max_level);
+    }
+    Ok(())
+}
+
+/// Write `event` to stderr as one JSON line
+pub fn write_json_event(event: &HttpEvent) {
+    eprintln!("{}", json_line(event, SystemTime::now()));
+}
+
+/// Append `event` to the `--log-file`, if one is open
+pub fn write_file_event(event: &HttpEvent) {
+    if let Some(file) = LOG_FILE.get() {
+        let now = SystemTime::now();
+        let line = match file.format {
+            LogFormat::Json => json_line(event, now),
+            LogFormat::Text => format!(
+                "{} EVENT {}",
+                StringUtils::format_rfc3339(now),
+                serde_json::to_string(event).unwrap_or_default()
+            ),
+        };
+        file.write_line(&line);
+    }
+}
+
+struct LogFile {
+    writer: Mutex<LineWriter<File>>,
+    level: LevelFilter,
+    format: LogFormat,
+}
+
+impl LogFile {
+    fn write_line(&self, line: &str) {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        // A full disk must not fail the transfer
+        let _ = writeln!(writer, "{}", line);
+    }
+}
+
+/// Sends each record to the console logger and the log file independently
+struct TeeLogger {
+    console: env_logger::Logger,
+}
+
+impl Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata)
+            || LOG_FILE
+                .get()
+                .is_some_and(|file| metadata.level() <= file.level)
+    }
+
+    fn log(&self, record: &Record) {
+        // Warnings reach the console through `crate::warning` instead
+        if self.console.matches(record) && record.target() != crate::warning::TARGET {
+            self.console.log(record);
+        }
+        if let Some(file) = LOG_FILE.get() {
+            if record.level() <= file.level {
+                file.write_line(&file_record(record, file.format, SystemTime::now()));
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Some(file) = LOG_FILE.get() {
+            let _ = file
+                .writer
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .flush();
+        }
+    }
+}
+
+fn json_record(record: &Record, now: SystemTime) -> String {
+    let event = LogRecordEvent {
+        event: "log",
+        level: record.level().as_str().to_lowercase(),
+        target: record.target(),
+        message: record.args().to_string(),
+    };
+    json_line(&event, now)
+}
+
+fn file_record(record: &Record, format: LogFormat, now: SystemTime) -> String {
+    match format {
+        LogFormat::Json => json_record(record, now),
+        LogFormat::Text => format!(
+            "{} {:<5} {}: {}",
+            StringUtils::format_rfc3339(now),
+            record.level(),
+            record.target(),
+            record.args()
+        ),
+    }
+}
+
+#[derive(Serialize)]
+struct LogRecordEvent<'a> {
+    event: &'static str,
+    level: String,
+    target: &'a str,
+    message: String,
+}
+
+/// Serialize `event` with a leading `ts_ms` field (Unix epoch milliseconds)
+fn json_line<T: Serialize>(event: &T, now: SystemTime) -> String {
+    let ts_ms = now
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0);
+    let body = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+    match body.strip_prefix('{') {
+        Some("}") | None => format!("{{\"ts_ms\":{}}}", ts_ms),
+        Some(fields) => format!("{{\"ts_ms\":{},{}", ts_ms, fields),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::init;
+    use super::{file_record, init, json_line, LogFormat};
+    use crate::http::events::HttpEvent;
+    use log::{Level, Record};
     use std::sync::Once;
+    use std::time::{Duration, UNIX_EPOCH};
 
     static INIT: Once = Once::new();
 
@@ -19,4 +215,49 @@ mod tests {
     fn init_sets_logger_once() {
         INIT.call_once(init);
     }
+
+    #[test]
+    fn json_line_starts_with_timestamp() {
+        let event = HttpEvent::Retry {
+            attempt: 1,
+            reason: "HTTP 503".to_string(),
+            delay_ms: 0,
+        };
+        let line = json_line(&event, UNIX_EPOCH + Duration::from_millis(1500));
+        assert_eq!(
+            line,
+            r#"{"ts_ms":1500,"event":"retry","attempt":1,"reason":"HTTP 503","delay_ms":0}"#
+        );
+    }
+
+    #[test]
+    fn file_record_formats_text_and_json() {
+        let now = UNIX_EPOCH + Duration::from_millis(1500);
+        let line = |format| {
+            file_record(
+                &Record::builder()
+                    .args(format_args!("retrying"))
+                    .level(Level::Warn)
+                    .target("rurl::http")
+                    .build(),
+                format,
+                now,
+            )
+        };
+        assert_eq!(
+            line(LogFormat::Text),
+            "1970-01-01T00:00:01.500Z WARN  rurl::http: retrying"
+        );
+        assert_eq!(
+            line(LogFormat::Json),
+            r#"{"ts_ms":1500,"event":"log","level":"warn","target":"rurl::http","message":"retrying"}"#
+        );
+    }
+
+    #[test]
+    fn log_format_parses_names() {
+        assert_eq!("JSON".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert_eq!("text".parse::<LogFormat>(), Ok(LogFormat::Text));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
 }
@@ -1,6 +1,6 @@
-use rurl::{cli, logging};
+use rurl::cli;
 
-fn main() {
-    logging::init();
-    cli::run();
+#[tokio::main]
+async fn main() {
+    cli::run().await;
 }
@@ -0,0 +1,328 @@
+//! Prometheus text-format metrics (`--metrics-out`) and the `--summary` line
+//!
+//! Counters and a duration histogram are fed from the `HttpClient` event bus
+//! and written once the run finishes, so batch jobs can be scraped through a
+//! textfile collector or post-processed. The same counts give the one-line
+//! summary printed after a transfer.
+
+use crate::error::{Result, RurlError};
+use crate::http::events::HttpEvent;
+use crate::utils::StringUtils;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the `rurl_request_duration_seconds` buckets
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metrics {
+    requests_total: u64,
+    errors_total: u64,
+    retries_total: u64,
+    redirects_total: u64,
+    /// Cumulative count per bucket in `DURATION_BUCKETS`
+    duration_buckets: Vec<u64>,
+    duration_count: u64,
+    duration_sum: f64,
+    /// Response body bytes, after content decoding
+    bytes_received: u64,
+    /// Status and URL of the latest response
+    last_response: Option<(u16, String)>,
+    /// Body bytes received since the latest response
+    last_body_bytes: u64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            requests_total: 0,
+            errors_total: 0,
+            retries_total: 0,
+            redirects_total: 0,
+            duration_buckets: vec![0; DURATION_BUCKETS.len()],
+            duration_count: 0,
+            duration_sum: 0.0,
+            bytes_received: 0,
+            last_response: None,
+            last_body_bytes: 0,
+        }
+    }
+}
+
+impl Metrics {
+    pub fn record(&mut self, event: &HttpEvent) {
+        match event {
+            HttpEvent::RequestStart { .. } => self.requests_total += 1,
+            HttpEvent::Response {
+                url,
+                status,
+                elapsed_ms,
+                ..
+            } => {
+                self.last_response = Some((*status, url.clone()));
+                self.last_body_bytes = 0;
+                let seconds = *elapsed_ms as f64 / 1000.0;
+                for (bucket, bound) in self.duration_buckets.iter_mut().zip(DURATION_BUCKETS) {
+                    if seconds <= *bound {
+                        *bucket += 1;
+                    }
+                }
+                self.duration_count += 1;
+                self.duration_sum += seconds;
+            }
+            HttpEvent::Redirect { .. } => self.redirects_total += 1,
+            HttpEvent::Retry { .. } => self.retries_total += 1,
+            HttpEvent::Error { .. } => self.errors_total += 1,
+            HttpEvent::BodyReceived { bytes, .. } => {
+                self.bytes_received += bytes;
+                self.last_body_bytes += bytes;
+            }
+            HttpEvent::Resolved { .. }
+            | HttpEvent::Connected { .. }
+            | HttpEvent::BodySent { .. }
+            | HttpEvent::CookieDecision { .. } => {}
+        }
+    }
+
+    /// Render in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, help, value) in [
+            (
+                "rurl_requests_total",
+                "Requests sent, including retries and redirects",
+                self.requests_total,
+            ),
+            ("rurl_errors_total", "Failed transfers", self.errors_total),
+            ("rurl_retries_total", "Retried requests", self.retries_total),
+            (
+                "rurl_redirects_total",
+                "Redirects followed",
+                self.redirects_total,
+            ),
+            (
+                "rurl_response_bytes_total",
+                "Response body bytes received, after content decoding",
+                self.bytes_received,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+
+        let name = "rurl_request_duration_seconds";
+        let _ = writeln!(
+            out,
+            "# HELP {} Time from sending a request to its response headers",
+            name
+        );
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bucket, bound) in self.duration_buckets.iter().zip(DURATION_BUCKETS) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, bucket);
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{le=\"+Inf\"}} {}",
+            name, self.duration_count
+        );
+        let _ = writeln!(out, "{}_sum {}", name, self.duration_sum);
+        let _ = writeln!(out, "{}_count {}", name, self.duration_count);
+        out
+    }
+
+    /// Replace `path` with the rendered metrics
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.render())?;
+        Ok(())
+    }
+
+    /// `200 OK  14.2 kB  312 ms  https://final.url/` for a transfer that took
+    /// `elapsed`; `None` before any response
+    pub fn summary_line(&self, elapsed: Duration) -> Option<String> {
+        let (status, url) = self.last_response.as_ref()?;
+        let reason = reqwest::StatusCode::from_u16(*status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .map(|reason| format!(" {}", reason))
+            .unwrap_or_default();
+        Some(format!(
+            "{}{}  {}  {}  {}",
+            status,
+            reason,
+            format_bytes(self.last_body_bytes),
+            format_elapsed(elapsed),
+            url
+        ))
+    }
+}
+
+/// Size and time limits that `--summary-budget` warns about
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SummaryBudget {
+    pub max_bytes: Option<u64>,
+    pub max_time: Option<Duration>,
+}
+
+impl SummaryBudget {
+    /// Parse `size=SIZE`, `time=TIME` or both, comma separated; TIME takes
+    /// `ms`, `s`, `m` or `h`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut budget = Self::default();
+        for part in spec.split(',').map(str::trim) {
+            match part.split_once('=') {
+                Some(("size", size)) => budget.max_bytes = Some(StringUtils::parse_size(size)?),
+                Some(("time", time)) => budget.max_time = Some(parse_budget_time(time)?),
+                _ => {
+                    return Err(RurlError::Config(format!(
+                        "Invalid --summary-budget '{}': use size=SIZE and/or time=TIME",
+                        part
+                    )))
+                }
+            }
+        }
+        Ok(budget)
+    }
+
+    /// One message per limit the transfer went over
+    pub fn overruns(&self, metrics: &Metrics, elapsed: Duration) -> Vec<String> {
+        let mut overruns = Vec::new();
+        if let Some(max) = self.max_bytes.filter(|max| metrics.last_body_bytes > *max) {
+            overruns.push(format!(
+                "Response body of {} is over the {} budget",
+                format_bytes(metrics.last_body_bytes),
+                format_bytes(max)
+            ));
+        }
+        if let Some(max) = self.max_time.filter(|max| elapsed > *max) {
+            overruns.push(format!(
+                "Transfer time of {} is over the {} budget",
+                format_elapsed(elapsed),
+                format_elapsed(max)
+            ));
+        }
+        overruns
+    }
+}
+
+fn parse_budget_time(input: &str) -> Result<Duration> {
+    match input.strip_suffix("ms") {
+        Some(millis) => millis
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| RurlError::Config(format!("Invalid time: '{}'", input))),
+        None => StringUtils::parse_timeout(input),
+    }
+}
+
+/// Byte count in decimal units, like `14.2 kB`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{} ms", elapsed.as_millis())
+    } else {
+        format!("{:.1} s", elapsed.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metrics, SummaryBudget};
+    use crate::http::events::HttpEvent;
+    use std::time::Duration;
+
+    fn response(elapsed_ms: u64) -> HttpEvent {
+        HttpEvent::Response {
+            url: "http://example.com/".to_string(),
+            status: 200,
+            version: "HTTP/1.1".to_string(),
+            elapsed_ms,
+        }
+    }
+
+    #[test]
+    fn render_counts_requests_and_buckets() {
+        let mut metrics = Metrics::default();
+        metrics.record(&HttpEvent::RequestStart {
+            method: "GET".to_string(),
+            url: "http://example.com/".to_string(),
+            attempt: 1,
+            request_id: None,
+        });
+        metrics.record(&response(500));
+        metrics.record(&response(3000));
+
+        let text = metrics.render();
+        assert!(text.contains("rurl_requests_total 1\n"));
+        assert!(text.contains("rurl_errors_total 0\n"));
+        assert!(text.contains("rurl_request_duration_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(text.contains("rurl_request_duration_seconds_bucket{le=\"5\"} 2\n"));
+        assert!(text.contains("rurl_request_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("rurl_request_duration_seconds_sum 3.5\n"));
+        assert!(text.contains("# TYPE rurl_request_duration_seconds histogram\n"));
+    }
+
+    #[test]
+    fn summary_line_shows_the_final_response() {
+        let mut metrics = Metrics::default();
+        assert_eq!(metrics.summary_line(Duration::from_millis(5)), None);
+        metrics.record(&HttpEvent::Response {
+            url: "https://example.com/final".to_string(),
+            status: 200,
+            version: "HTTP/1.1".to_string(),
+            elapsed_ms: 300,
+        });
+        metrics.record(&HttpEvent::BodyReceived {
+            url: "https://example.com/final".to_string(),
+            bytes: 14_230,
+        });
+        assert_eq!(
+            metrics.summary_line(Duration::from_millis(312)).as_deref(),
+            Some("200 OK  14.2 kB  312 ms  https://example.com/final")
+        );
+        assert!(metrics
+            .render()
+            .contains("rurl_response_bytes_total 14230\n"));
+    }
+
+    #[test]
+    fn summary_budget_reports_overruns() {
+        let budget = SummaryBudget::parse("size=10k, time=250ms").expect("budget");
+        assert_eq!(budget.max_bytes, Some(10 * 1024));
+        assert_eq!(budget.max_time, Some(Duration::from_millis(250)));
+        assert!(SummaryBudget::parse("speed=1").is_err());
+
+        let mut metrics = Metrics::default();
+        metrics.record(&HttpEvent::BodyReceived {
+            url: "https://example.com/".to_string(),
+            bytes: 2_500_000,
+        });
+        assert_eq!(
+            budget.overruns(&metrics, Duration::from_millis(1500)),
+            [
+                "Response body of 2.5 MB is over the 10.2 kB budget",
+                "Transfer time of 1.5 s is over the 250 ms budget"
+            ]
+        );
+        assert!(budget
+            .overruns(&Metrics::default(), Duration::from_millis(10))
+            .is_empty());
+    }
+}
@@ -1,16 +1,31 @@
 //! Output formatting and display utilities
 
-use crate::config::OutputConfig;
+use crate::config::{ClipboardCopy, GrepFilter, OutputConfig, PagerMode, VerbosityLevel};
 use crate::error::{Result, RurlError};
 use crate::http::response::{ResponseFormatter, ResponseInfo};
+use crate::progress::{ProgressObserver, TransferPhase};
+use crate::utils::StringUtils;
 use encoding_rs::Encoding;
-use futures_util::StreamExt;
-use reqwest::header::CONTENT_TYPE;
-use reqwest::Response;
-use std::fs::File;
-use std::io::{self, Write};
-use std::path::Path;
-use std::time::{Duration, Instant};
+use grep::LineFilter;
+use http_body_util::BodyExt;
+use reqwest::header::{
+    HeaderMap, CONTENT_ENCODING, CONTENT_RANGE, CONTENT_TYPE, LAST_MODIFIED, LOCATION,
+};
+use reqwest::{Response, StatusCode};
+use spool::{Spool, SpooledBody};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+/// Bytes inspected by [`looks_binary`]
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Highest `name.N` suffix tried by `--no-clobber` before giving up
+const MAX_NO_CLOBBER_ATTEMPTS: usize = 100;
 
 /// Output writer that handles file vs stdout
 pub struct OutputWriter {
@@ -24,16 +39,80 @@ impl OutputWriter {
 
     /// Write content to configured output
     pub fn write(&self, content: &str) -> Result<()> {
+        self.write_with_mtime(content, None)
+    }
+
+    /// Write content, stamping an output file with `modified` when given
+    pub fn write_with_mtime(&self, content: &str, modified: Option<SystemTime>) -> Result<()> {
+        self.write_bytes_with_mtime(content.as_bytes(), modified)
+    }
+
+    /// Write bytes unchanged, stamping an output file with `modified` when given
+    pub fn write_bytes_with_mtime(
+        &self,
+        content: &[u8],
+        modified: Option<SystemTime>,
+    ) -> Result<()> {
         if let Some(file_path) = &self.config.file {
-            self.write_to_file(content, file_path)
+            self.write_to_file(content, file_path, modified)?;
         } else {
-            self.write_to_stdout(content)
+            self.write_to_stdout(content)?;
+        }
+        for file_path in &self.config.tee {
+            self.write_to_file(content, file_path, modified)?;
         }
+        Ok(())
+    }
+
+    /// Write whatever `produce` emits, stamping an output file with `modified`
+    /// when given
+    pub fn write_streamed_with_mtime<F>(
+        &self,
+        modified: Option<SystemTime>,
+        produce: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<()>,
+    {
+        let mut tee = self.create_tee_files()?;
+        let result = self.write_streamed_to_primary(modified, |out| {
+            produce(&mut TeeWriter {
+                primary: out,
+                copies: &mut tee.files,
+            })
+        });
+        self.finish_tee_files(tee, result, modified)
+    }
+
+    /// Stream into the output file, or stdout when there is none
+    fn write_streamed_to_primary<F>(&self, modified: Option<SystemTime>, produce: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<()>,
+    {
+        let Some(file_path) = &self.config.file else {
+            // Streamed bodies are past --memory-limit, so taller than any screen
+            if let Some(pager) = self.pages(None).then(spawn_pager).flatten() {
+                return page_through(pager, produce);
+            }
+            return produce(&mut io::stdout().lock());
+        };
+        let (file, written_path) = self.create_output_file(file_path)?;
+        let mut out = io::BufWriter::new(file);
+        let result = produce(&mut out).and_then(|()| Ok(out.flush()?));
+        if let Err(err) = result {
+            drop(out);
+            self.remove_partial(&written_path);
+            return Err(err);
+        }
+        if let Some(modified) = modified {
+            out.get_ref().set_modified(modified)?;
+        }
+        Ok(())
     }
 
     /// Write verbose information (if enabled)
     pub fn write_verbose(&self, message: &str) -> Result<()> {
-        if self.config.verbose && !self.config.silent {
+        if self.config.shows(VerbosityLevel::Headers) {
             eprintln!("* {}", message);
         }
         Ok(())
@@ -47,69 +126,470 @@ impl OutputWriter {
         Ok(())
     }
 
-    fn write_to_file(&self, content: &str, file_path: &Path) -> Result<()> {
-        let mut file = File::create(file_path)?;
-        file.write_all(content.as_bytes())?;
+    /// Delete a partially written output file when `--remove-on-error` is set
+    pub fn remove_partial(&self, file_path: &Path) {
+        if !self.config.remove_on_error {
+            return;
+        }
+        if let Err(err) = fs::remove_file(file_path) {
+            if err.kind() != io::ErrorKind::NotFound {
+                log::warn!("Failed to remove {:?}: {}", file_path, err);
+            }
+        }
+    }
+
+    fn write_to_file(
+        &self,
+        content: &[u8],
+        file_path: &Path,
+        modified: Option<SystemTime>,
+    ) -> Result<()> {
+        let (mut file, written_path) = self.create_output_file(file_path)?;
+        if let Err(err) = file.write_all(content) {
+            drop(file);
+            self.remove_partial(&written_path);
+            return Err(err.into());
+        }
+        if let Some(modified) = modified {
+            file.set_modified(modified)?;
+        }
+        Ok(())
+    }
+
+    /// Open the `--tee` files for one write
+    fn create_tee_files(&self) -> Result<TeeFiles> {
+        let mut tee = TeeFiles::default();
+        for file_path in &self.config.tee {
+            let (file, written_path) = self.create_output_file(file_path)?;
+            tee.files.push(io::BufWriter::new(file));
+            tee.paths.push(written_path);
+        }
+        Ok(tee)
+    }
+
+    /// Flush the `--tee` files and stamp them with `modified`; they are
+    /// partial, like the output file, when `result` is an error
+    fn finish_tee_files(
+        &self,
+        tee: TeeFiles,
+        result: Result<()>,
+        modified: Option<SystemTime>,
+    ) -> Result<()> {
+        let TeeFiles { files, paths } = tee;
+        let result = result.and_then(|()| {
+            for file in files {
+                let file = file.into_inner().map_err(|err| err.into_error())?;
+                if let Some(modified) = modified {
+                    file.set_modified(modified)?;
+                }
+            }
+            Ok(())
+        });
+        if result.is_err() {
+            for path in &paths {
+                self.remove_partial(path);
+            }
+        }
+        result
+    }
+
+    /// Open the output file, choosing `name.N` alternatives under `--no-clobber`
+    fn create_output_file(&self, file_path: &Path) -> Result<(File, PathBuf)> {
+        if !self.config.no_clobber {
+            return Ok((File::create(file_path)?, file_path.to_path_buf()));
+        }
+        for attempt in 0..=MAX_NO_CLOBBER_ATTEMPTS {
+            let candidate = if attempt == 0 {
+                file_path.to_path_buf()
+            } else {
+                let mut name = file_path.as_os_str().to_os_string();
+                name.push(format!(".{}", attempt));
+                PathBuf::from(name)
+            };
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&candidate)
+            {
+                Ok(file) => return Ok((file, candidate)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Err(RurlError::Io(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Refusing to overwrite {:?}: no free file name", file_path),
+        )))
+    }
+
+    fn write_to_stdout(&self, content: &[u8]) -> Result<()> {
+        if let Some(pager) = self.pages(Some(content)).then(spawn_pager).flatten() {
+            return page_through(pager, |out| Ok(out.write_all(content)?));
+        }
+        io::stdout().write_all(content)?;
         Ok(())
     }
 
-    fn write_to_stdout(&self, content: &str) -> Result<()> {
-        io::stdout().write_all(content.as_bytes())?;
+    /// Whether stdout output goes through the pager; `content` is `None` when
+    /// it is streamed
+    fn pages(&self, content: Option<&[u8]>) -> bool {
+        if self.config.pager == PagerMode::Never || !io::stdout().is_terminal() {
+            return false;
+        }
+        match (self.config.pager, content) {
+            (PagerMode::Always, _) | (_, None) => true,
+            (_, Some(content)) => {
+                terminal_size::terminal_size().is_some_and(|(_, terminal_size::Height(rows))| {
+                    content.iter().filter(|&&byte| byte == b'\n').count() >= usize::from(rows)
+                })
+            }
+        }
+    }
+}
+
+/// `--tee` files opened for one write, under the names they were created as
+#[derive(Default)]
+struct TeeFiles {
+    files: Vec<io::BufWriter<File>>,
+    paths: Vec<PathBuf>,
+}
+
+/// Writes everything to `primary` and a copy to each of `copies`
+struct TeeWriter<'a> {
+    primary: &'a mut dyn Write,
+    copies: &'a mut [io::BufWriter<File>],
+}
+
+impl Write for TeeWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.primary.write(buf)?;
+        for copy in self.copies.iter_mut() {
+            copy.write_all(&buf[..written])?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.primary.flush()?;
+        for copy in self.copies.iter_mut() {
+            copy.flush()?;
+        }
         Ok(())
     }
 }
 
+/// Start `$PAGER` (default `less`); `None` when it is unset to `cat` or
+/// cannot be started
+fn spawn_pager() -> Option<Child> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next().filter(|&program| program != "cat")?;
+    let mut command = Command::new(program);
+    command.args(words).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        // Quit when it fits one screen, pass ANSI colors through, leave the
+        // output on screen afterwards
+        command.env("LESS", "FRX");
+    }
+    match command.spawn() {
+        Ok(child) => Some(child),
+        Err(err) => {
+            log::debug!("Cannot start pager {}: {}", program, err);
+            None
+        }
+    }
+}
+
+/// Write whatever `produce` emits to `pager` and wait for it to exit
+fn page_through<F>(mut pager: Child, produce: F) -> Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> Result<()>,
+{
+    let result = match pager.stdin.take() {
+        Some(stdin) => {
+            let mut stdin = io::BufWriter::new(stdin);
+            produce(&mut stdin).and_then(|()| Ok(stdin.flush()?))
+        }
+        None => Ok(()),
+    };
+    pager.wait()?;
+    match result {
+        // The pager was quit before the end of the output
+        Err(RurlError::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
 pub struct OutputManager {
     config: OutputConfig,
     writer: OutputWriter,
     formatter: ResponseFormatter,
+    cancel: CancellationToken,
+    observer: Option<Arc<dyn ProgressObserver>>,
 }
 
 impl OutputManager {
     pub fn new(config: OutputConfig) -> Self {
         let writer = OutputWriter::new(config.clone());
-        let formatter = ResponseFormatter::new(config.format_json);
+        let formatter = ResponseFormatter::new(config.format_json).with_xml(config.format_xml);
         Self {
             config,
             writer,
             formatter,
+            cancel: CancellationToken::new(),
+            observer: None,
         }
     }
 
+    /// Report download progress to `observer` as well as the progress meter
+    pub fn with_progress_observer(mut self, observer: Arc<dyn ProgressObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Abort body transfers with `RurlError::Aborted` when `cancel` fires;
+    /// bytes already received are still written and `.part` files are removed
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
     pub async fn write_response(&self, response: Response, history: &[ResponseInfo]) -> Result<()> {
-        if self.config.verbose && !self.config.silent {
+        if self.config.shows(VerbosityLevel::Headers) {
             self.write_verbose_headers(history);
         }
+        if self.config.show_redirect_chain {
+            eprint!("{}", redirect_chain_table(history));
+        }
+        if let (Some(encoding), false) = (
+            unknown_content_encoding(response.headers()),
+            self.config.raw,
+        ) {
+            crate::warning!(
+                "Unrecognized Content-Encoding '{}'; writing the body as received",
+                encoding
+            );
+        }
+
+        if let (Some(grep), false) = (&self.config.grep, self.config.rewrites_body()) {
+            return self.write_grep_streamed(response, history, grep).await;
+        }
+        if let (Some(file_path), false) = (&self.config.file, self.config.rewrites_body()) {
+            return self.download_to_file(response, history, file_path).await;
+        }
 
         let content_type = response
             .headers()
             .get(CONTENT_TYPE)
             .and_then(|value| value.to_str().ok())
             .map(|value| value.to_string());
-        let body = self.read_body_with_progress(response).await?;
-        let decoded = decode_body_with_charset(body, content_type.as_deref())?;
-        let formatted = self.formatter.format(&decoded, content_type.as_deref())?;
+        let (mut body, trailers) = self.read_body_with_progress(response).await?;
+        self.write_verbose_trailers(&trailers);
+        if self.config.file.is_none()
+            && !self.config.allow_binary_stdout
+            && !self.config.hexdump
+            && self.config.copy != ClipboardCopy::Only
+            && io::stdout().is_terminal()
+            && looks_binary(&body.prefix(BINARY_SNIFF_LEN)?)
+        {
+            return Err(RurlError::Io(io::Error::other(
+                "Binary output can mess up your terminal. Use \"--output -\" to tell rurl to output it to your terminal anyway, or consider \"--output <FILE>\" to save to a file",
+            )));
+        }
+        let body = match body {
+            SpooledBody::Memory(body) => body,
+            SpooledBody::File(_) if self.config.copy != ClipboardCopy::Off => {
+                return Err(RurlError::Io(io::Error::other(
+                    "The body is larger than --memory-limit and was not copied to the clipboard",
+                )));
+            }
+            SpooledBody::File(file) => {
+                return self.write_spooled(file, content_type.as_deref(), history, &trailers)
+            }
+        };
+        // --raw emits the body bytes exactly as received
+        let body = if self.config.hexdump {
+            StringUtils::hexdump(&body, 0).into_bytes()
+        } else if self.config.raw {
+            body
+        } else {
+            let decoded = decode_body_with_charset(body, content_type.as_deref())?;
+            let text = self.formatter.format(&decoded, content_type.as_deref())?;
+            match self.config.output_charset {
+                Some(encoding) => encoding.encode(&text).0.into_owned(),
+                None => text.into_bytes(),
+            }
+        };
+        let (body, matched) = match &self.config.grep {
+            Some(grep) => grep::filter(&grep.pattern, grep.context, &body)?,
+            None => (body, true),
+        };
 
         let output = if self.config.include_headers {
-            let mut combined = String::new();
+            let mut combined = Vec::new();
             for info in history {
-                combined.push_str(&format_response_headers(
-                    info.version,
-                    info.status,
-                    &info.headers,
-                ));
+                combined.extend_from_slice(
+                    format_response_headers(info.version, info.status, &info.headers).as_bytes(),
+                );
             }
-            combined.push_str(&formatted);
+            combined.extend_from_slice(&body);
+            combined.extend_from_slice(format_trailers(&trailers).as_bytes());
             combined
         } else {
-            formatted
+            body
         };
 
-        self.writer.write(&output)
+        if self.config.copy != ClipboardCopy::Off {
+            clipboard::copy(&output)?;
+        }
+        if self.config.copy != ClipboardCopy::Only {
+            self.writer
+                .write_bytes_with_mtime(&output, self.remote_time(history))?;
+        }
+        if !matched {
+            return Err(RurlError::NoMatch);
+        }
+        Ok(())
+    }
+
+    /// Stream a body that is written as received through `--grep`, writing
+    /// the matching lines as they arrive
+    async fn write_grep_streamed(
+        &self,
+        response: Response,
+        history: &[ResponseInfo],
+        grep: &GrepFilter,
+    ) -> Result<()> {
+        let (mut primary, written_path): (Box<dyn Write>, Option<PathBuf>) = match &self.config.file
+        {
+            Some(file_path) => {
+                let (file, written_path) = self.writer.create_output_file(file_path)?;
+                (Box::new(io::BufWriter::new(file)), Some(written_path))
+            }
+            None => (Box::new(io::stdout()), None),
+        };
+        let mut tee = self.writer.create_tee_files()?;
+        let mut out = TeeWriter {
+            primary: primary.as_mut(),
+            copies: &mut tee.files,
+        };
+        if self.config.include_headers {
+            for info in history {
+                out.write_all(
+                    format_response_headers(info.version, info.status, &info.headers).as_bytes(),
+                )?;
+            }
+        }
+
+        let mut lines = LineFilter::new(&grep.pattern, grep.context, out);
+        let streamed = self
+            .stream_body(response, |chunk| Ok(lines.write_all(chunk)?))
+            .await;
+        // Lines matched before a failure are still written
+        let finished = lines.finish();
+        let mut matched = false;
+        let result = streamed.and_then(|trailers| {
+            let (mut out, any) = finished?;
+            matched = any;
+            self.write_verbose_trailers(&trailers);
+            if self.config.include_headers {
+                out.write_all(format_trailers(&trailers).as_bytes())?;
+            }
+            Ok(out.flush()?)
+        });
+        drop(primary);
+
+        let modified = self.remote_time(history);
+        let result = self.writer.finish_tee_files(tee, result, modified);
+        if let Some(path) = &written_path {
+            match (&result, modified) {
+                (Err(_), _) => self.writer.remove_partial(path),
+                (Ok(()), Some(modified)) => OpenOptions::new()
+                    .write(true)
+                    .open(path)?
+                    .set_modified(modified)?,
+                (Ok(()), None) => {}
+            }
+        }
+        result?;
+        if !matched {
+            return Err(RurlError::NoMatch);
+        }
+        Ok(())
+    }
+
+    /// Write a body spilled past `--memory-limit`, converting it while streaming
+    fn write_spooled(
+        &self,
+        file: File,
+        content_type: Option<&str>,
+        history: &[ResponseInfo],
+        trailers: &HeaderMap,
+    ) -> Result<()> {
+        let mut reader = io::BufReader::new(file);
+        let mut matched = true;
+        self.writer
+            .write_streamed_with_mtime(self.remote_time(history), |out| {
+                if self.config.include_headers {
+                    for info in history {
+                        let headers =
+                            format_response_headers(info.version, info.status, &info.headers);
+                        out.write_all(headers.as_bytes())?;
+                    }
+                }
+                match &self.config.grep {
+                    Some(grep) => {
+                        let mut lines = LineFilter::new(&grep.pattern, grep.context, &mut *out);
+                        self.write_spooled_body(&mut reader, content_type, &mut lines)?;
+                        matched = lines.finish()?.1;
+                    }
+                    None => self.write_spooled_body(&mut reader, content_type, out)?,
+                }
+                if self.config.include_headers {
+                    out.write_all(format_trailers(trailers).as_bytes())?;
+                }
+                Ok(())
+            })?;
+        if !matched {
+            return Err(RurlError::NoMatch);
+        }
+        Ok(())
+    }
+
+    fn write_spooled_body(
+        &self,
+        reader: &mut io::BufReader<File>,
+        content_type: Option<&str>,
+        out: &mut dyn Write,
+    ) -> Result<()> {
+        if self.config.hexdump {
+            spool::write_hexdump(reader, out)?;
+        } else if self.config.raw {
+            io::copy(reader, out)?;
+        } else {
+            spool::write_decoded(
+                reader,
+                extract_charset(content_type).as_deref(),
+                self.formatter.body_format(content_type),
+                self.config.output_charset,
+                out,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// `Last-Modified` of the final response when `-R/--remote-time` is set
+    fn remote_time(&self, history: &[ResponseInfo]) -> Option<SystemTime> {
+        if !self.config.remote_time {
+            return None;
+        }
+        let value = history.last()?.headers.get(LAST_MODIFIED)?.to_str().ok()?;
+        StringUtils::parse_http_date(value)
     }
 
     fn write_verbose_headers(&self, history: &[ResponseInfo]) {
         for info in history {
+            eprintln!("* {}", hop_summary(info));
             eprintln!("< {} {}", http_version_label(info.version), info.status);
             for (name, value) in info.headers.iter() {
                 let value = value.to_str().unwrap_or("<non-utf8>");
@@ -119,70 +599,436 @@ impl OutputManager {
         }
     }
 
-    async fn read_body_with_progress(&self, response: Response) -> Result<Vec<u8>> {
+    async fn read_body_with_progress(
+        &self,
+        response: Response,
+    ) -> Result<(SpooledBody, HeaderMap)> {
+        let mut spool = Spool::new(self.config.memory_limit);
+        let result = self
+            .stream_body(response, |chunk| Ok(spool.push(chunk)?))
+            .await;
+        match result {
+            Ok(trailers) => Ok((spool.finish()?, trailers)),
+            Err(RurlError::Aborted) => {
+                // Flush what arrived before the abort, undecoded
+                match spool.finish()? {
+                    SpooledBody::Memory(buffer) => {
+                        self.writer.write_bytes_with_mtime(&buffer, None)?
+                    }
+                    SpooledBody::File(mut file) => {
+                        self.writer.write_streamed_with_mtime(None, |out| {
+                            io::copy(&mut file, out)?;
+                            Ok(())
+                        })?
+                    }
+                }
+                Err(RurlError::Aborted)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn write_verbose_trailers(&self, trailers: &HeaderMap) {
+        if !self.config.shows(VerbosityLevel::Headers) {
+            return;
+        }
+        for (name, value) in trailers.iter() {
+            let value = value.to_str().unwrap_or("<non-utf8>");
+            eprintln!("< {}: {}", name, value);
+        }
+    }
+
+    /// Stream the body into `sink`, reporting progress and enforcing speed limits;
+    /// returns the trailer fields received after the body
+    async fn stream_body<F>(&self, response: Response, mut sink: F) -> Result<HeaderMap>
+    where
+        F: FnMut(&[u8]) -> Result<()>,
+    {
         let total = response.content_length();
-        let mut progress =
-            ProgressReporter::new(self.config.show_progress && !self.config.silent, total);
-        let mut stream = response.bytes_stream();
-        let mut buffer = Vec::new();
+        let meter = ProgressReporter::new(self.config.show_progress && !self.config.silent);
+        let mut observers: Vec<&dyn ProgressObserver> = vec![&meter];
+        if let Some(observer) = &self.observer {
+            observers.push(observer.as_ref());
+        }
+        let notify_phase = |phase| observers.iter().for_each(|o| o.on_phase(phase));
+        notify_phase(TransferPhase::Downloading);
+        let mut low_speed = self
+            .config
+            .speed_limit
+            .map(|limit| LowSpeedMonitor::new(limit, self.config.speed_time, Instant::now()));
+        let mut body = http::Response::from(response).into_body();
+        let mut trailers = HeaderMap::new();
         let mut current = 0u64;
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(RurlError::Http)?;
+        let stall_window = low_speed.as_ref().map(|monitor| monitor.window);
+
+        loop {
+            let frame = async {
+                match stall_window {
+                    Some(window) => tokio::time::timeout(window, body.frame()).await.ok(),
+                    None => Some(body.frame().await),
+                }
+            };
+            let next = tokio::select! {
+                biased;
+                _ = self.cancel.cancelled() => {
+                    notify_phase(TransferPhase::Aborted);
+                    return Err(RurlError::Aborted);
+                }
+                next = frame => next,
+            };
+            let next = match next {
+                Some(next) => next,
+                None => {
+                    // Nothing arrived for a whole window: record a stalled sample.
+                    if let Some(monitor) = low_speed.as_mut() {
+                        if monitor.record(0, Instant::now()) {
+                            return Err(monitor.error());
+                        }
+                    }
+                    continue;
+                }
+            };
+            let frame = match next {
+                Some(frame) => frame.map_err(RurlError::transport)?,
+                None => break,
+            };
+            let chunk = match frame.into_data() {
+                Ok(chunk) => chunk,
+                Err(frame) => {
+                    if let Ok(received) = frame.into_trailers() {
+                        trailers.extend(received);
+                    }
+                    continue;
+                }
+            };
+            if self.config.shows(VerbosityLevel::Body) {
+                eprintln!("<= Recv data, {} bytes", chunk.len());
+                eprint!("{}", StringUtils::hexdump(&chunk, current));
+            }
             current = current.saturating_add(chunk.len() as u64);
-            buffer.extend_from_slice(&chunk);
-            progress.update(current);
+            sink(&chunk)?;
+            observers
+                .iter()
+                .for_each(|observer| observer.on_download(current, total));
+            if let Some(monitor) = low_speed.as_mut() {
+                if monitor.record(chunk.len() as u64, Instant::now()) {
+                    return Err(monitor.error());
+                }
+            }
+        }
+
+        notify_phase(TransferPhase::Finished);
+        Ok(trailers)
+    }
+
+    /// Download raw bytes into `FILE.part`, resuming a previous partial
+    /// download when the server answered a range request, then rename the
+    /// completed file into place. With `-i` the file starts with headers
+    /// and is never resumed.
+    async fn download_to_file(
+        &self,
+        response: Response,
+        history: &[ResponseInfo],
+        file_path: &Path,
+    ) -> Result<()> {
+        let part_path = part_path(file_path);
+        let status = response.status();
+        let resumable = self.config.resumable();
+
+        if resumable && status == StatusCode::RANGE_NOT_SATISFIABLE && part_path.is_file() {
+            // The previous run already fetched every byte, if the part file
+            // is as long as the resource
+            let part_len = fs::metadata(&part_path)?.len();
+            if content_range_total(response.headers()) != Some(part_len) {
+                return Err(RurlError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Server cannot resume at byte {} and {:?} does not match the resource",
+                        part_len, part_path
+                    ),
+                )));
+            }
+            return self.finish_part_file(&part_path, file_path).map(|_| ());
         }
 
-        progress.finish(current);
-        Ok(buffer)
+        let resume_offset = if resumable && status == StatusCode::PARTIAL_CONTENT {
+            content_range_start(response.headers())
+        } else {
+            None
+        };
+        let mut file = match resume_offset {
+            Some(offset) => {
+                let file = OpenOptions::new().append(true).open(&part_path)?;
+                if file.metadata()?.len() != offset {
+                    return Err(RurlError::Io(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Server resumed at byte {} but {:?} has a different size",
+                            offset, part_path
+                        ),
+                    )));
+                }
+                file
+            }
+            None => File::create(&part_path)?,
+        };
+
+        let mut tee = self.writer.create_tee_files()?;
+        let mut out = TeeWriter {
+            primary: &mut file,
+            copies: &mut tee.files,
+        };
+        if self.config.include_headers {
+            for info in history {
+                out.write_all(
+                    format_response_headers(info.version, info.status, &info.headers).as_bytes(),
+                )?;
+            }
+        }
+
+        let include_headers = self.config.include_headers;
+        let result = self
+            .stream_body(response, |chunk| {
+                out.write_all(chunk).map_err(RurlError::Io)
+            })
+            .await
+            .and_then(|trailers| {
+                self.write_verbose_trailers(&trailers);
+                if include_headers {
+                    out.write_all(format_trailers(&trailers).as_bytes())?;
+                }
+                out.flush().map_err(RurlError::Io)
+            });
+        drop(file);
+        let result = self
+            .writer
+            .finish_tee_files(tee, result, self.remote_time(history));
+        match result {
+            Ok(()) => {
+                let target = self.finish_part_file(&part_path, file_path)?;
+                if let Some(modified) = self.remote_time(history) {
+                    OpenOptions::new()
+                        .write(true)
+                        .open(&target)?
+                        .set_modified(modified)?;
+                }
+                Ok(())
+            }
+            Err(RurlError::Aborted) => {
+                // An aborted transfer is not resumed later
+                if let Err(err) = fs::remove_file(&part_path) {
+                    log::warn!("Failed to remove {:?}: {}", part_path, err);
+                }
+                Err(RurlError::Aborted)
+            }
+            Err(err) => {
+                self.writer.remove_partial(&part_path);
+                Err(err)
+            }
+        }
+    }
+
+    fn finish_part_file(&self, part_path: &Path, file_path: &Path) -> Result<PathBuf> {
+        let target = if self.config.no_clobber {
+            // Reserve a free name, then atomically replace the placeholder
+            let (_, reserved) = self.writer.create_output_file(file_path)?;
+            reserved
+        } else {
+            file_path.to_path_buf()
+        };
+        fs::rename(part_path, &target)?;
+        Ok(target)
     }
 }
 
-/// Tracks and renders download progress; kept private to output module.
+/// Trailer fields as `-i` prints them after the body
+fn format_trailers(trailers: &HeaderMap) -> String {
+    let mut output = String::new();
+    for (name, value) in trailers.iter() {
+        output.push_str(&format!(
+            "{}: {}\n",
+            name,
+            value.to_str().unwrap_or("<non-utf8>")
+        ));
+    }
+    output
+}
+
+/// Size of an existing `FILE.part` left by an interrupted download
+pub fn resumable_part_len(config: &OutputConfig) -> Option<u64> {
+    let file_path = config.file.as_ref().filter(|_| config.resumable())?;
+    let len = fs::metadata(part_path(file_path)).ok()?.len();
+    (len > 0).then_some(len)
+}
+
+fn part_path(file_path: &Path) -> PathBuf {
+    let mut name = file_path.as_os_str().to_os_string();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Parse the first byte position from `Content-Range: bytes FIRST-LAST/TOTAL`
+fn content_range_start(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.trim().strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
+
+/// Parse the complete length from `Content-Range: bytes */TOTAL`, as sent
+/// with 416 Range Not Satisfiable
+pub(crate) fn content_range_total(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.trim().strip_prefix("bytes ")?;
+    range.rsplit('/').next()?.trim().parse().ok()
+}
+
+/// One line per hop: number, status and the URL it redirected to, e.g.
+/// `1  301 Moved Permanently  http://a/old -> http://a/new`
+fn redirect_chain_table(history: &[ResponseInfo]) -> String {
+    let width = history.len().to_string().len();
+    let mut table = String::new();
+    for (hop, info) in history.iter().enumerate() {
+        let target = info
+            .headers
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .filter(|_| info.status.is_redirection())
+            .map(|location| {
+                let target = info
+                    .url
+                    .join(location)
+                    .map_or_else(|_| location.to_string(), |url| url.to_string());
+                format!(" -> {}", target)
+            })
+            .unwrap_or_default();
+        table.push_str(&format!(
+            "{:>width$}  {}  {}{}\n",
+            hop + 1,
+            info.status,
+            info.url,
+            target,
+            width = width
+        ));
+    }
+    table
+}
+
+/// Verbose line naming the URL, peer and latency of one response
+fn hop_summary(info: &ResponseInfo) -> String {
+    let millis = info.elapsed.as_millis();
+    match info.remote_addr {
+        Some(addr) => format!("Response from {} ({}) in {} ms", info.url, addr, millis),
+        None => format!("Response from {} in {} ms", info.url, millis),
+    }
+}
+
+/// Renders download progress on stderr; kept private to output module.
 struct ProgressReporter {
     enabled: bool,
+    state: Mutex<ProgressState>,
+}
+
+struct ProgressState {
+    current: u64,
     total: Option<u64>,
     last_update: Instant,
     rendered: bool,
 }
 
 impl ProgressReporter {
-    fn new(enabled: bool, total: Option<u64>) -> Self {
+    fn new(enabled: bool) -> Self {
         Self {
             enabled,
-            total,
-            last_update: Instant::now(),
-            rendered: false,
+            state: Mutex::new(ProgressState {
+                current: 0,
+                total: None,
+                last_update: Instant::now(),
+                rendered: false,
+            }),
         }
     }
 
-    fn update(&mut self, current: u64) {
+    fn state(&self) -> std::sync::MutexGuard<'_, ProgressState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[cfg(test)]
+    fn rendered(&self) -> bool {
+        self.state().rendered
+    }
+}
+
+impl ProgressObserver for ProgressReporter {
+    fn on_download(&self, received: u64, total: Option<u64>) {
         if !self.enabled {
             return;
         }
-        if self.last_update.elapsed() < Duration::from_millis(100) {
+        let mut state = self.state();
+        state.current = received;
+        state.total = total;
+        if state.last_update.elapsed() < Duration::from_millis(100) {
             return;
         }
-        self.last_update = Instant::now();
-        self.rendered = true;
-        eprint!("{}", progress_line(current, self.total));
+        state.last_update = Instant::now();
+        state.rendered = true;
+        eprint!("{}", progress_line(received, total));
     }
 
-    fn finish(&mut self, current: u64) {
-        if !self.enabled {
+    fn on_phase(&self, phase: TransferPhase) {
+        if !self.enabled || phase != TransferPhase::Finished {
             return;
         }
-        if !self.rendered {
-            eprint!("{}", progress_line(current, self.total));
-            self.rendered = true;
+        let mut state = self.state();
+        if !state.rendered {
+            eprint!("{}", progress_line(state.current, state.total));
+            state.rendered = true;
         }
         eprintln!();
     }
+}
 
-    #[cfg(test)]
-    fn rendered(&self) -> bool {
-        self.rendered
+/// Detects transfers whose average rate stays below `--speed-limit` for a
+/// full `--speed-time` window.
+struct LowSpeedMonitor {
+    limit: u64,
+    window: Duration,
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl LowSpeedMonitor {
+    fn new(limit: u64, window: Duration, now: Instant) -> Self {
+        Self {
+            limit,
+            window,
+            window_start: now,
+            window_bytes: 0,
+        }
+    }
+
+    /// Record received bytes; returns true when the last window was too slow.
+    fn record(&mut self, bytes: u64, now: Instant) -> bool {
+        self.window_bytes = self.window_bytes.saturating_add(bytes);
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < self.window {
+            return false;
+        }
+        let rate = self.window_bytes as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        if rate < self.limit as f64 {
+            return true;
+        }
+        self.window_start = now;
+        self.window_bytes = 0;
+        false
+    }
+
+    fn error(&self) -> RurlError {
+        RurlError::TooSlow(format!(
+            "less than {} bytes/sec transferred the last {} seconds",
+            self.limit,
+            self.window.as_secs()
+        ))
     }
 }
 
@@ -201,7 +1047,10 @@ fn progress_line(current: u64, total: Option<u64>) -> String {
     }
 }
 
-fn decode_body_with_charset(body: Vec<u8>, content_type: Option<&str>) -> Result<String> {
+pub(crate) fn decode_body_with_charset(
+    body: Vec<u8>,
+    content_type: Option<&str>,
+) -> Result<String> {
     if let Some(charset) = extract_charset(content_type) {
         if let Some(encoding) = Encoding::for_label(charset.as_bytes()) {
             let (decoded, _, _) = encoding.decode(&body);
@@ -211,6 +1060,26 @@ fn decode_body_with_charset(body: Vec<u8>, content_type: Option<&str>) -> Result
     Ok(String::from_utf8_lossy(&body).to_string())
 }
 
+/// `Content-Encoding` left on a response after decoding, i.e. one the
+/// client does not understand
+fn unknown_content_encoding(headers: &HeaderMap) -> Option<String> {
+    let value = headers
+        .get(CONTENT_ENCODING)?
+        .to_str()
+        .unwrap_or_default()
+        .trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("identity") {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Heuristic used by curl: a NUL byte near the start means binary data
+fn looks_binary(body: &[u8]) -> bool {
+    body[..body.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
 fn extract_charset(content_type: Option<&str>) -> Option<String> {
     let content_type = content_type?;
     for part in content_type.split(';').skip(1) {
@@ -241,7 +1110,7 @@ fn format_response_headers(
     output
 }
 
-fn http_version_label(version: reqwest::Version) -> &'static str {
+pub(crate) fn http_version_label(version: reqwest::Version) -> &'static str {
     match version {
         reqwest::Version::HTTP_09 => "HTTP/0.9",
         reqwest::Version::HTTP_10 => "HTTP/1.0",
@@ -252,5 +1121,12 @@ fn http_version_label(version: reqwest::Version) -> &'static str {
     }
 }
 
+pub(crate) mod clipboard;
+pub mod extract;
+mod grep;
+pub mod report;
+mod spool;
+pub mod template;
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,64 @@
+//! `--copy`: the written body on the system clipboard
+//!
+//! Clipboard access needs platform libraries, so it is only built with
+//! `--features clipboard`; without it `--copy` fails before any request.
+
+use crate::error::{Result, RurlError};
+use std::io;
+
+/// Fail early when this build cannot reach the clipboard
+pub(crate) fn ensure_available() -> Result<()> {
+    if cfg!(feature = "clipboard") {
+        Ok(())
+    } else {
+        Err(RurlError::Unsupported(
+            "--copy requires building rurl with --features clipboard".to_string(),
+        ))
+    }
+}
+
+/// Replace the clipboard contents with `body`, which must be text
+pub(crate) fn copy(body: &[u8]) -> Result<()> {
+    let text = std::str::from_utf8(body).map_err(|_| {
+        RurlError::Io(io::Error::other(
+            "The body is not text and was not copied to the clipboard; try --hexdump",
+        ))
+    })?;
+    set_text(text)
+}
+
+#[cfg(feature = "clipboard")]
+fn set_text(text: &str) -> Result<()> {
+    // Dropping the clipboard hands the contents to a running clipboard
+    // manager on X11 and Wayland, so they outlive rurl
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| {
+            RurlError::Io(io::Error::other(format!(
+                "Cannot copy to the clipboard: {}",
+                e
+            )))
+        })
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn set_text(_text: &str) -> Result<()> {
+    ensure_available()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy, ensure_available};
+
+    #[test]
+    fn binary_bodies_are_not_copied() {
+        assert!(copy(&[0xff, 0xfe, 0x00]).is_err());
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    #[test]
+    fn copy_needs_the_clipboard_feature() {
+        assert!(ensure_available().is_err());
+        assert!(copy(b"text").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Shell variables from JSON responses (`--extract NAME=SELECTOR`)
+//!
+//! Selectors are a jq-style subset: `.` is the whole body, `.key` and
+//! `["key"]` pick object members, `[N]` picks array elements (negative counts
+//! from the end). Strings are used as-is; other values as compact JSON. Names
+//! are upper-cased, so `token=.access_token` sets `TOKEN`.
+
+use crate::error::{Result, RurlError};
+use serde_json::Value;
+
+/// One `--extract` argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extraction {
+    pub name: String,
+    selector: Selector,
+}
+
+/// Path into a JSON value, such as `.items[0].id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Selector {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    Index(i64),
+}
+
+impl Extraction {
+    /// Parse `NAME=SELECTOR`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (name, selector) = spec.split_once('=').ok_or_else(|| {
+            RurlError::Config(format!("--extract needs NAME=SELECTOR, got '{}'", spec))
+        })?;
+        let name = name.trim().to_ascii_uppercase();
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(RurlError::Config(format!(
+                "Invalid variable name '{}' in --extract",
+                name
+            )));
+        }
+        let selector = selector.trim();
+        Ok(Self {
+            name,
+            selector: Selector::parse(selector).ok_or_else(|| {
+                RurlError::Config(format!("Invalid --extract selector '{}'", selector))
+            })?,
+        })
+    }
+
+    /// The selected value as shell text; `None` when the path is missing
+    pub fn value(&self, body: &Value) -> Option<String> {
+        self.selector.select(body).map(value_text)
+    }
+}
+
+impl Selector {
+    /// Parse a selector; `None` when it is malformed
+    pub(crate) fn parse(selector: &str) -> Option<Self> {
+        let mut rest = selector.strip_prefix('.')?;
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            if let Some(bracket) = rest.strip_prefix('[') {
+                let (inner, after) = bracket.split_once(']')?;
+                steps.push(
+                    match inner
+                        .strip_prefix('"')
+                        .and_then(|key| key.strip_suffix('"'))
+                    {
+                        Some(key) => Step::Key(key.to_string()),
+                        None => Step::Index(inner.trim().parse().ok()?),
+                    },
+                );
+                rest = after.strip_prefix('.').unwrap_or(after);
+            } else {
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                if end == 0 {
+                    return None;
+                }
+                steps.push(Step::Key(rest[..end].to_string()));
+                rest = rest[end..].strip_prefix('.').unwrap_or(&rest[end..]);
+            }
+        }
+        Some(Self { steps })
+    }
+
+    /// Object member the selector starts with
+    pub(crate) fn first_key(&self) -> Option<&str> {
+        match self.steps.first()? {
+            Step::Key(key) => Some(key),
+            Step::Index(_) => None,
+        }
+    }
+
+    /// The selected value; `None` when the path is missing
+    pub(crate) fn select<'a>(&self, body: &'a Value) -> Option<&'a Value> {
+        let mut value = body;
+        for step in &self.steps {
+            value = match (step, value) {
+                (Step::Key(key), Value::Object(map)) => map.get(key)?,
+                (Step::Index(index), Value::Array(items)) => {
+                    let index = match *index {
+                        index if index < 0 => {
+                            items.len().checked_sub(index.unsigned_abs() as usize)?
+                        }
+                        index => index as usize,
+                    };
+                    items.get(index)?
+                }
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+/// A value as text: strings as-is, null as nothing, anything else as compact
+/// JSON
+pub(crate) fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// `NAME=value` pairs for every extraction; a missing path is an error
+pub fn extract_all(body: &[u8], extractions: &[Extraction]) -> Result<Vec<(String, String)>> {
+    let body: Value = serde_json::from_slice(body)
+        .map_err(|e| RurlError::Config(format!("--extract needs a JSON response: {}", e)))?;
+    extractions
+        .iter()
+        .map(|extraction| {
+            let value = extraction.value(&body).ok_or_else(|| {
+                RurlError::Config(format!(
+                    "--extract {}: selector matched nothing in the response",
+                    extraction.name
+                ))
+            })?;
+            Ok((extraction.name.clone(), value))
+        })
+        .collect()
+}
+
+/// Lines for `eval`, or for a dotenv file without the `export` prefix
+pub fn render(variables: &[(String, String)], export: bool) -> String {
+    let prefix = if export { "export " } else { "" };
+    variables
+        .iter()
+        .map(|(name, value)| format!("{}{}={}\n", prefix, name, shell_quote(value)))
+        .collect()
+}
+
+/// Single-quote `value` unless it is made of characters no shell treats specially
+fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.,:/@%+=".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_all, render, Extraction};
+
+    #[test]
+    fn selectors_pick_members_and_elements() {
+        let body = br#"{"access_token":"abc","items":[{"id":1},{"id":2,"tags":["a b"]}],"meta":{"a.b":null}}"#;
+        let extractions: Vec<Extraction> = [
+            "token=.access_token",
+            "last_id=.items[-1].id",
+            "tag=.items[1].tags[0]",
+            "dotted=.meta[\"a.b\"]",
+            "first=.items[0]",
+        ]
+        .iter()
+        .map(|spec| Extraction::parse(spec).expect("spec"))
+        .collect();
+        let variables = extract_all(body, &extractions).expect("values");
+        assert_eq!(
+            render(&variables, true),
+            "export TOKEN=abc\nexport LAST_ID=2\nexport TAG='a b'\nexport DOTTED=''\nexport FIRST='{\"id\":1}'\n"
+        );
+
+        let missing = Extraction::parse("x=.items[5]").expect("spec");
+        assert!(extract_all(body, &[missing]).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_bad_names_and_selectors() {
+        assert!(Extraction::parse("1x=.a").is_err());
+        assert!(Extraction::parse("x=a").is_err());
+        assert!(Extraction::parse("x=.a[").is_err());
+        assert!(Extraction::parse("x=.a..b").is_err());
+        assert_eq!(
+            render(&[("Q".to_string(), "it's".to_string())], false),
+            "Q='it'\\''s'\n"
+        );
+    }
+}
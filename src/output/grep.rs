@@ -0,0 +1,161 @@
+//! `--grep`: keep only the body lines matching a pattern
+//!
+//! Lines are matched as the body streams in, so a large log can be searched
+//! without holding it in memory. As with grep, `--grep-context N` also keeps
+//! N lines around each match and `--` separates groups that are apart.
+
+use regex::bytes::Regex;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// Writes the lines of everything pushed through it that match `pattern`
+pub(crate) struct LineFilter<W: Write> {
+    pattern: Regex,
+    context: usize,
+    out: W,
+    /// Start of a line whose newline has not arrived yet
+    partial: Vec<u8>,
+    /// Up to `context` unmatched lines, kept in case a match follows
+    before: VecDeque<Vec<u8>>,
+    /// Lines still to be kept after the last match
+    after: usize,
+    line: u64,
+    last_written: Option<u64>,
+    matched: bool,
+}
+
+impl<W: Write> LineFilter<W> {
+    pub(crate) fn new(pattern: &Regex, context: usize, out: W) -> Self {
+        Self {
+            pattern: pattern.clone(),
+            context,
+            out,
+            partial: Vec::new(),
+            before: VecDeque::new(),
+            after: 0,
+            line: 0,
+            last_written: None,
+            matched: false,
+        }
+    }
+
+    /// Feed the next bytes of the body
+    pub(crate) fn push(&mut self, mut chunk: &[u8]) -> io::Result<()> {
+        while let Some(end) = chunk.iter().position(|&byte| byte == b'\n') {
+            let (line, rest) = chunk.split_at(end + 1);
+            if self.partial.is_empty() {
+                self.filter_line(line)?;
+            } else {
+                let mut joined = std::mem::take(&mut self.partial);
+                joined.extend_from_slice(line);
+                self.filter_line(&joined)?;
+            }
+            chunk = rest;
+        }
+        self.partial.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    /// Match the last line even without a final newline and flush; returns
+    /// the writer and whether any line matched
+    pub(crate) fn finish(mut self) -> io::Result<(W, bool)> {
+        if !self.partial.is_empty() {
+            let last = std::mem::take(&mut self.partial);
+            self.filter_line(&last)?;
+        }
+        self.out.flush()?;
+        Ok((self.out, self.matched))
+    }
+
+    fn filter_line(&mut self, line: &[u8]) -> io::Result<()> {
+        self.line += 1;
+        let text = line.strip_suffix(b"\n").unwrap_or(line);
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+        if self.pattern.is_match(text) {
+            self.matched = true;
+            let first = self.line - self.before.len() as u64;
+            if self.context > 0 && self.last_written.is_some_and(|last| first > last + 1) {
+                self.out.write_all(b"--\n")?;
+            }
+            while let Some(kept) = self.before.pop_front() {
+                write_line(&mut self.out, &kept)?;
+            }
+            write_line(&mut self.out, line)?;
+            self.last_written = Some(self.line);
+            self.after = self.context;
+        } else if self.after > 0 {
+            self.after -= 1;
+            write_line(&mut self.out, line)?;
+            self.last_written = Some(self.line);
+        } else if self.context > 0 {
+            if self.before.len() == self.context {
+                self.before.pop_front();
+            }
+            self.before.push_back(line.to_vec());
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for LineFilter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.push(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Filter a whole body at once; returns the kept lines and whether any matched
+pub(crate) fn filter(pattern: &Regex, context: usize, body: &[u8]) -> io::Result<(Vec<u8>, bool)> {
+    let mut filter = LineFilter::new(pattern, context, Vec::new());
+    filter.push(body)?;
+    filter.finish()
+}
+
+fn write_line(out: &mut impl Write, line: &[u8]) -> io::Result<()> {
+    out.write_all(line)?;
+    if !line.ends_with(b"\n") {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{filter, LineFilter};
+    use regex::bytes::Regex;
+
+    #[test]
+    fn keeps_matching_lines_split_across_chunks() {
+        let pattern = Regex::new("ERROR").expect("pattern");
+        let mut lines = LineFilter::new(&pattern, 0, Vec::new());
+        for chunk in ["INFO start\nERR", "OR disk full\nINFO ok\n", "ERROR last"] {
+            lines.push(chunk.as_bytes()).expect("push");
+        }
+        let (out, matched) = lines.finish().expect("finish");
+        assert!(matched);
+        assert_eq!(out, b"ERROR disk full\nERROR last\n");
+    }
+
+    #[test]
+    fn context_lines_and_separators_follow_grep() {
+        let pattern = Regex::new("^x").expect("pattern");
+        let body = b"a\nb\nx1\nc\nd\ne\nf\nx2\ng\n";
+        let (out, _) = filter(&pattern, 1, body).expect("filter");
+        assert_eq!(String::from_utf8_lossy(&out), "b\nx1\nc\n--\nf\nx2\ng\n");
+
+        let (out, _) = filter(&pattern, 2, b"x1\na\nb\nx2\n").expect("filter");
+        assert_eq!(String::from_utf8_lossy(&out), "x1\na\nb\nx2\n");
+    }
+
+    #[test]
+    fn reports_when_nothing_matched() {
+        let pattern = Regex::new("missing").expect("pattern");
+        let (out, matched) = filter(&pattern, 3, b"one\ntwo\n").expect("filter");
+        assert!(!matched);
+        assert!(out.is_empty());
+    }
+}
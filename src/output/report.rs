@@ -0,0 +1,403 @@
+//! One-line reports from a response (`--template '{{ .json.items | length }} items'`)
+//!
+//! Text outside `{{ ... }}` is printed as written. Each action is a value
+//! piped through filters: `.status`, `.url`, `.body`, `.headers.NAME` (any
+//! case) and `.json.PATH` (the `--extract` selector syntax), or a `"string"`
+//! or number literal. Missing paths are null, which prints as nothing.
+
+use super::extract::{value_text, Selector};
+use crate::error::{Result, RurlError};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::{Map, Value};
+use url::Url;
+
+/// Values an action may start from
+const FIELDS: &[&str] = &["status", "url", "headers", "json", "body"];
+
+/// A parsed `--template`
+#[derive(Debug, Clone)]
+pub struct ReportTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    Action(Operand, Vec<Filter>),
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Path(Selector),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Length,
+    Keys,
+    First,
+    Last,
+    Upper,
+    Lower,
+    Trim,
+    Json,
+    Join(String),
+    Default(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(String),
+    Literal(Value),
+    Word(String),
+    Pipe,
+}
+
+/// What a template is filled in from
+pub struct ReportResponse<'a> {
+    pub status: StatusCode,
+    /// Final URL, after redirects
+    pub url: &'a Url,
+    pub headers: &'a HeaderMap,
+    pub body: &'a [u8],
+}
+
+impl ReportTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let action = &rest[start + 2..];
+            let end = action
+                .find("}}")
+                .ok_or_else(|| invalid(format!("unclosed '{{{{' in '{}'", template)))?;
+            parts.push(parse_action(&action[..end])?);
+            rest = &action[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(Self { parts })
+    }
+
+    /// The report for `response`, ending in a newline
+    pub fn render(&self, response: &ReportResponse) -> Result<String> {
+        let root = self.root(response)?;
+        let mut report = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => report.push_str(text),
+                Part::Action(operand, filters) => {
+                    let mut value = match operand {
+                        Operand::Path(selector) => {
+                            selector.select(&root).cloned().unwrap_or(Value::Null)
+                        }
+                        Operand::Literal(value) => value.clone(),
+                    };
+                    for filter in filters {
+                        value = filter.apply(value)?;
+                    }
+                    report.push_str(&value_text(&value));
+                }
+            }
+        }
+        if !report.ends_with('\n') {
+            report.push('\n');
+        }
+        Ok(report)
+    }
+
+    /// Whether an action reads `.json`
+    fn uses_json(&self) -> bool {
+        self.parts.iter().any(|part| {
+            matches!(part, Part::Action(Operand::Path(selector), _)
+                if selector.first_key() == Some("json"))
+        })
+    }
+
+    fn root(&self, response: &ReportResponse) -> Result<Value> {
+        let mut headers = Map::new();
+        for (name, value) in response.headers {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            match headers.get_mut(name.as_str()) {
+                Some(Value::String(joined)) => {
+                    joined.push_str(", ");
+                    joined.push_str(&value);
+                }
+                _ => {
+                    headers.insert(name.as_str().to_string(), Value::String(value));
+                }
+            }
+        }
+        let json = if self.uses_json() {
+            serde_json::from_slice(response.body).map_err(|e| {
+                RurlError::Config(format!(
+                    "--template reads .json but the response is not JSON: {}",
+                    e
+                ))
+            })?
+        } else {
+            Value::Null
+        };
+        let mut root = Map::new();
+        root.insert("status".into(), response.status.as_u16().into());
+        root.insert("url".into(), response.url.as_str().into());
+        root.insert("headers".into(), Value::Object(headers));
+        root.insert("json".into(), json);
+        root.insert(
+            "body".into(),
+            String::from_utf8_lossy(response.body).into_owned().into(),
+        );
+        Ok(Value::Object(root))
+    }
+}
+
+impl Filter {
+    fn apply(&self, value: Value) -> Result<Value> {
+        Ok(match (self, value) {
+            (Filter::Length, Value::Array(items)) => items.len().into(),
+            (Filter::Length, Value::Object(map)) => map.len().into(),
+            (Filter::Length, Value::String(text)) => text.chars().count().into(),
+            (Filter::Length, Value::Null) => 0.into(),
+            (Filter::Keys, Value::Object(map)) => {
+                Value::Array(map.keys().map(|key| key.as_str().into()).collect())
+            }
+            (Filter::First, Value::Array(items)) => items.into_iter().next().unwrap_or(Value::Null),
+            (Filter::Last, Value::Array(mut items)) => items.pop().unwrap_or(Value::Null),
+            (Filter::Upper, value) => value_text(&value).to_uppercase().into(),
+            (Filter::Lower, value) => value_text(&value).to_lowercase().into(),
+            (Filter::Trim, value) => value_text(&value).trim().into(),
+            (Filter::Json, value) => value.to_string().into(),
+            (Filter::Join(separator), Value::Array(items)) => items
+                .iter()
+                .map(value_text)
+                .collect::<Vec<_>>()
+                .join(separator)
+                .into(),
+            (Filter::Default(fallback), Value::Null) => fallback.clone(),
+            (Filter::Default(fallback), Value::String(text)) if text.is_empty() => fallback.clone(),
+            (Filter::Default(_), value) => value,
+            (filter, value) => {
+                return Err(RurlError::Config(format!(
+                    "--template: {} cannot be applied to {}",
+                    filter.name(),
+                    value
+                )))
+            }
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Filter::Length => "length",
+            Filter::Keys => "keys",
+            Filter::First => "first",
+            Filter::Last => "last",
+            Filter::Upper => "upper",
+            Filter::Lower => "lower",
+            Filter::Trim => "trim",
+            Filter::Json => "json",
+            Filter::Join(_) => "join",
+            Filter::Default(_) => "default",
+        }
+    }
+}
+
+fn parse_action(action: &str) -> Result<Part> {
+    let tokens = tokenize(action)?;
+    let mut stages = tokens.split(|token| *token == Token::Pipe);
+    let operand = match stages.next().unwrap_or_default() {
+        [Token::Path(path)] => {
+            // Header names are matched in any case
+            let path = if path.starts_with(".headers") {
+                path.to_ascii_lowercase()
+            } else {
+                path.clone()
+            };
+            let selector = Selector::parse(&path)
+                .filter(|selector| {
+                    selector
+                        .first_key()
+                        .is_some_and(|key| FIELDS.contains(&key))
+                })
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "'{}' does not start with .status, .url, .headers, .json or .body",
+                        path
+                    ))
+                })?;
+            Operand::Path(selector)
+        }
+        [Token::Literal(value)] => Operand::Literal(value.clone()),
+        _ => {
+            return Err(invalid(format!(
+                "'{}' needs one value before any '|'",
+                action.trim()
+            )))
+        }
+    };
+    let filters = stages.map(parse_filter).collect::<Result<Vec<_>>>()?;
+    Ok(Part::Action(operand, filters))
+}
+
+fn parse_filter(stage: &[Token]) -> Result<Filter> {
+    let filter = match stage {
+        [Token::Word(name)] => match name.as_str() {
+            "length" => Filter::Length,
+            "keys" => Filter::Keys,
+            "first" => Filter::First,
+            "last" => Filter::Last,
+            "upper" => Filter::Upper,
+            "lower" => Filter::Lower,
+            "trim" => Filter::Trim,
+            "json" => Filter::Json,
+            "join" | "default" => {
+                return Err(invalid(format!("filter '{}' needs an argument", name)))
+            }
+            _ => return Err(invalid(format!("unknown filter '{}'", name))),
+        },
+        [Token::Word(name), Token::Literal(Value::String(separator))] if name == "join" => {
+            Filter::Join(separator.clone())
+        }
+        [Token::Word(name), Token::Literal(fallback)] if name == "default" => {
+            Filter::Default(fallback.clone())
+        }
+        [] => return Err(invalid("empty filter after '|'".to_string())),
+        [Token::Word(name), ..] => {
+            return Err(invalid(format!("wrong arguments for filter '{}'", name)))
+        }
+        _ => return Err(invalid("a filter must start with its name".to_string())),
+    };
+    Ok(filter)
+}
+
+fn tokenize(action: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = action.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '|' {
+            chars.next();
+            tokens.push(Token::Pipe);
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some(escaped) => text.push(escaped),
+                        None => return Err(invalid("unterminated string".to_string())),
+                    },
+                    Some(c) => text.push(c),
+                    None => return Err(invalid("unterminated string".to_string())),
+                }
+            }
+            tokens.push(Token::Literal(Value::String(text)));
+        } else {
+            // A path may contain `["a b"]`, so spaces end it only outside quotes
+            let mut word = String::new();
+            let mut quoted = false;
+            while let Some(&c) = chars.peek() {
+                if !quoted && (c.is_whitespace() || c == '|') {
+                    break;
+                }
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(if word.starts_with('.') {
+                Token::Path(word)
+            } else if let Ok(number) = word.parse::<serde_json::Number>() {
+                Token::Literal(Value::Number(number))
+            } else {
+                Token::Word(word)
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+fn invalid(detail: String) -> RurlError {
+    RurlError::Config(format!("Invalid --template: {}", detail))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReportResponse, ReportTemplate};
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use reqwest::StatusCode;
+    use url::Url;
+
+    fn render(template: &str, body: &str) -> String {
+        let url = Url::parse("https://api.example.com/items").expect("url");
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        ReportTemplate::parse(template)
+            .expect("template")
+            .render(&ReportResponse {
+                status: StatusCode::OK,
+                url: &url,
+                headers: &headers,
+                body: body.as_bytes(),
+            })
+            .expect("render")
+    }
+
+    #[test]
+    fn actions_read_json_headers_and_status() {
+        let body = r#"{"items":[{"name":"a"},{"name":"b"}],"next":null}"#;
+        assert_eq!(
+            render("{{ .json.items | length }} items", body),
+            "2 items\n"
+        );
+        assert_eq!(
+            render("{{.status}} {{ .headers.Content-Type }} {{ .url }}", body),
+            "200 application/json https://api.example.com/items\n"
+        );
+        assert_eq!(
+            render(
+                r#"{{ .json.items[-1].name | upper }} next={{ .json.next | default "none" }}"#,
+                body
+            ),
+            "B next=none\n"
+        );
+        assert_eq!(
+            render(r#"{{ .json.items[0] | keys | join ", " }}"#, body),
+            "name\n"
+        );
+        assert_eq!(render(r#"{{ "{{" }}"#, "plain text"), "{{\n");
+    }
+
+    #[test]
+    fn bad_templates_are_config_errors() {
+        for template in [
+            "{{ .json",
+            "{{ .nope }}",
+            "{{ .json | frobnicate }}",
+            "{{ .json | join }}",
+            "{{ | length }}",
+        ] {
+            assert!(ReportTemplate::parse(template).is_err(), "{}", template);
+        }
+        let template = ReportTemplate::parse("{{ .status | length }}").expect("template");
+        let url = Url::parse("https://example.com/").expect("url");
+        let response = ReportResponse {
+            status: StatusCode::OK,
+            url: &url,
+            headers: &HeaderMap::new(),
+            body: b"",
+        };
+        assert!(template.render(&response).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+//! Buffering of response bodies that are processed as a whole
+//!
+//! Pretty-printing and charset conversion need the complete body. Bodies up
+//! to `--memory-limit` stay in memory; larger ones spill to an anonymous
+//! temporary file and are converted by streaming readers, so a huge JSON
+//! response does not exhaust memory.
+
+use crate::http::markup::MarkupIndenter;
+use crate::http::response::BodyFormat;
+use crate::utils::StringUtils;
+use encoding_rs::{CoderResult, Encoding, UTF_8};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+const INDENT: &str = "  ";
+
+/// Collects body chunks, moving them to a temporary file past `limit` bytes
+pub(crate) struct Spool {
+    limit: u64,
+    buffer: Vec<u8>,
+    file: Option<File>,
+}
+
+/// A complete body, in memory or in a temporary file
+pub(crate) enum SpooledBody {
+    Memory(Vec<u8>),
+    File(File),
+}
+
+impl Spool {
+    pub(crate) fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            buffer: Vec::new(),
+            file: None,
+        }
+    }
+
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> io::Result<()> {
+        if let Some(file) = &mut self.file {
+            return file.write_all(chunk);
+        }
+        if (self.buffer.len() + chunk.len()) as u64 <= self.limit {
+            self.buffer.extend_from_slice(chunk);
+            return Ok(());
+        }
+        log::debug!(
+            "Body exceeds --memory-limit of {} bytes; spooling to a temporary file",
+            self.limit
+        );
+        let mut file = tempfile::tempfile()?;
+        file.write_all(&self.buffer)?;
+        file.write_all(chunk)?;
+        self.buffer = Vec::new();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> io::Result<SpooledBody> {
+        match self.file {
+            Some(mut file) => {
+                file.seek(SeekFrom::Start(0))?;
+                Ok(SpooledBody::File(file))
+            }
+            None => Ok(SpooledBody::Memory(self.buffer)),
+        }
+    }
+}
+
+impl SpooledBody {
+    /// Up to `len` bytes from the start of the body
+    pub(crate) fn prefix(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        match self {
+            SpooledBody::Memory(buffer) => Ok(buffer[..buffer.len().min(len)].to_vec()),
+            SpooledBody::File(file) => {
+                let mut prefix = Vec::with_capacity(len);
+                file.by_ref().take(len as u64).read_to_end(&mut prefix)?;
+                file.seek(SeekFrom::Start(0))?;
+                Ok(prefix)
+            }
+        }
+    }
+}
+
+/// Copy `reader` to `out` decoded from `charset` (UTF-8 when unset or
+/// unknown), re-indenting it when `format` is set; the text is written as
+/// UTF-8 unless `output` names another encoding
+pub(crate) fn write_decoded(
+    reader: &mut dyn Read,
+    charset: Option<&str>,
+    format: Option<BodyFormat>,
+    output: Option<&'static Encoding>,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let mut decoder = match charset.and_then(|label| Encoding::for_label(label.as_bytes())) {
+        Some(encoding) => encoding.new_decoder(),
+        None => UTF_8.new_decoder_without_bom_handling(),
+    };
+    let mut indenter = format.map(Indenter::new);
+    let mut encoder = output.map(|encoding| encoding.new_encoder());
+    let mut input = vec![0u8; CHUNK_SIZE];
+    let mut text = String::new();
+    let mut encoded = Vec::new();
+    loop {
+        let read = reader.read(&mut input)?;
+        let last = read == 0;
+        text.clear();
+        text.reserve(
+            decoder
+                .max_utf8_buffer_length(read)
+                .unwrap_or(read * 3 + 16),
+        );
+        let _ = decoder.decode_to_string(&input[..read], &mut text, last);
+        if let Some(indenter) = &mut indenter {
+            text = indenter.push(&text, last);
+        }
+        match &mut encoder {
+            Some(encoder) => {
+                // Unmappable characters become numeric character references,
+                // which can outgrow the buffer; keep going until it is all out
+                let mut remaining = text.as_str();
+                loop {
+                    encoded.clear();
+                    encoded.reserve(
+                        encoder
+                            .max_buffer_length_from_utf8_if_no_unmappables(remaining.len())
+                            .unwrap_or(remaining.len() * 3 + 16),
+                    );
+                    let (result, read, _) =
+                        encoder.encode_from_utf8_to_vec(remaining, &mut encoded, last);
+                    out.write_all(&encoded)?;
+                    remaining = &remaining[read..];
+                    if result == CoderResult::InputEmpty {
+                        break;
+                    }
+                }
+            }
+            None => out.write_all(text.as_bytes())?,
+        }
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+/// Copy `reader` to `out` as a `hexdump -C` style dump
+pub(crate) fn write_hexdump(reader: &mut dyn Read, out: &mut dyn Write) -> io::Result<()> {
+    // A multiple of 16, so chunks never split a dump line
+    let mut input = vec![0u8; CHUNK_SIZE];
+    let mut offset = 0u64;
+    loop {
+        let mut filled = 0;
+        while filled < input.len() {
+            match reader.read(&mut input[filled..])? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        out.write_all(StringUtils::hexdump(&input[..filled], offset).as_bytes())?;
+        offset += filled as u64;
+        if filled < input.len() {
+            return Ok(());
+        }
+    }
+}
+
+/// Streaming pretty-printer for one [`BodyFormat`]
+enum Indenter {
+    Json(JsonIndenter),
+    Markup(MarkupIndenter),
+}
+
+impl Indenter {
+    fn new(format: BodyFormat) -> Self {
+        match format {
+            BodyFormat::Json => Indenter::Json(JsonIndenter::default()),
+            BodyFormat::Xml => Indenter::Markup(MarkupIndenter::new(false)),
+            BodyFormat::Html => Indenter::Markup(MarkupIndenter::new(true)),
+        }
+    }
+
+    fn push(&mut self, text: &str, last: bool) -> String {
+        match self {
+            Indenter::Json(indenter) => indenter.push(text),
+            Indenter::Markup(indenter) => {
+                let mut out = indenter.push(text);
+                if last {
+                    out.push_str(&indenter.finish());
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Re-indents JSON text as it streams by, in the layout of
+/// `serde_json::to_string_pretty`; object keys keep their original order
+#[derive(Debug, Default)]
+pub(crate) struct JsonIndenter {
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// An opening bracket was written and the container may still be empty
+    opened: bool,
+}
+
+impl JsonIndenter {
+    pub(crate) fn push(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() + text.len() / 4);
+        for c in text.chars() {
+            if self.in_string {
+                out.push(c);
+                if self.escaped {
+                    self.escaped = false;
+                } else if c == '\\' {
+                    self.escaped = true;
+                } else if c == '"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+            if c.is_whitespace() {
+                continue;
+            }
+            if self.opened {
+                self.opened = false;
+                if c == '}' || c == ']' {
+                    out.push(c);
+                    continue;
+                }
+                self.depth += 1;
+                self.newline(&mut out);
+            }
+            match c {
+                '{' | '[' => {
+                    out.push(c);
+                    self.opened = true;
+                }
+                '}' | ']' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    self.newline(&mut out);
+                    out.push(c);
+                }
+                ',' => {
+                    out.push(',');
+                    self.newline(&mut out);
+                }
+                ':' => out.push_str(": "),
+                '"' => {
+                    out.push('"');
+                    self.in_string = true;
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    fn newline(&self, out: &mut String) {
+        out.push('\n');
+        for _ in 0..self.depth {
+            out.push_str(INDENT);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_decoded, write_hexdump, JsonIndenter, Spool, SpooledBody, CHUNK_SIZE};
+    use crate::http::response::BodyFormat;
+    use encoding_rs::SHIFT_JIS;
+    use std::io::Read;
+
+    #[test]
+    fn spool_moves_to_file_past_limit() {
+        let mut spool = Spool::new(4);
+        spool.push(b"ab").expect("push");
+        spool.push(b"cdef").expect("push");
+        let mut body = spool.finish().expect("finish");
+        assert_eq!(body.prefix(3).expect("prefix"), b"abc");
+        let SpooledBody::File(mut file) = body else {
+            panic!("expected a spooled file");
+        };
+        let mut content = String::new();
+        file.read_to_string(&mut content).expect("read");
+        assert_eq!(content, "abcdef");
+
+        let mut small = Spool::new(4);
+        small.push(b"abcd").expect("push");
+        assert!(matches!(
+            small.finish().expect("finish"),
+            SpooledBody::Memory(_)
+        ));
+    }
+
+    #[test]
+    fn indenter_matches_serde_pretty_output() {
+        let body = r#"{"a":[1,{"b":"x\"}]{,"}],"c":{},"d":[ ],"e":null}"#;
+        let value: serde_json::Value = serde_json::from_str(body).expect("json");
+        let expected = serde_json::to_string_pretty(&value).expect("pretty");
+        let mut indenter = JsonIndenter::default();
+        let (first, second) = body.split_at(9);
+        let streamed = indenter.push(first) + &indenter.push(second);
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn write_decoded_converts_charset() {
+        let mut out = Vec::new();
+        write_decoded(
+            &mut &b"caf\xe9"[..],
+            Some("iso-8859-1"),
+            None,
+            None,
+            &mut out,
+        )
+        .expect("write");
+        assert_eq!(out, "café".as_bytes());
+    }
+
+    #[test]
+    fn write_hexdump_numbers_lines_across_chunks() {
+        let body: Vec<u8> = (0..=255u8).cycle().take(CHUNK_SIZE + 20).collect();
+        let mut out = Vec::new();
+        write_hexdump(&mut body.as_slice(), &mut out).expect("write");
+        let dump = String::from_utf8(out).expect("utf8");
+        assert_eq!(dump.lines().count(), CHUNK_SIZE / 16 + 2);
+        assert!(dump
+            .lines()
+            .last()
+            .unwrap_or_default()
+            .starts_with("00010010  10 11 12 13 "));
+    }
+
+    #[test]
+    fn write_decoded_indents_markup() {
+        let mut out = Vec::new();
+        write_decoded(
+            &mut &b"<a><b>x</b>tail</a>"[..],
+            None,
+            Some(BodyFormat::Xml),
+            None,
+            &mut out,
+        )
+        .expect("write");
+        assert_eq!(out, b"<a>\n  <b>x</b>\n  tail\n</a>");
+    }
+
+    #[test]
+    fn write_decoded_re_encodes_output() {
+        let mut out = Vec::new();
+        write_decoded(
+            &mut "日本 ☃".as_bytes(),
+            None,
+            None,
+            Some(SHIFT_JIS),
+            &mut out,
+        )
+        .expect("write");
+        assert_eq!(out, b"\x93\xfa\x96\x7b &#9731;");
+    }
+}
@@ -0,0 +1,196 @@
+//! Output file name templates (`-o '{host}_{path_basename}_{status}.json'`)
+//!
+//! Placeholders are filled in per transfer once the response status is
+//! known; `{{` and `}}` stand for literal braces. `#N` is replaced by the
+//! text the Nth URL glob matched and, as in curl, kept as-is when there is
+//! no such glob.
+
+use crate::error::{Result, RurlError};
+use crate::utils::{FileUtils, UrlUtils};
+use reqwest::StatusCode;
+use std::path::PathBuf;
+use url::Url;
+
+const PLACEHOLDERS: &[&str] = &[
+    "host",
+    "port",
+    "scheme",
+    "path",
+    "path_basename",
+    "query",
+    "status",
+];
+
+/// What a template is filled in from
+pub struct TemplateValues<'a> {
+    /// Final URL, after redirects
+    pub url: &'a Url,
+    pub status: StatusCode,
+    /// Text matched by each URL glob, in order
+    pub globs: &'a [String],
+}
+
+/// Whether `output` uses placeholders or glob references
+pub fn is_template(output: &str) -> bool {
+    output.contains(['{', '}'])
+        || output
+            .split('#')
+            .skip(1)
+            .any(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Check `template` for unknown placeholders and unbalanced braces
+pub fn validate(template: &str) -> Result<()> {
+    parse(template).map(|_| ())
+}
+
+/// File name for one transfer
+pub fn render(template: &str, values: &TemplateValues) -> Result<PathBuf> {
+    let mut name = String::new();
+    for part in parse(template)? {
+        match part {
+            Part::Text(text) => name.push_str(&text),
+            Part::Placeholder(placeholder) => {
+                name.push_str(&sanitize(&placeholder_value(placeholder, values)))
+            }
+            Part::Glob(index) => match values.globs.get(index - 1) {
+                Some(value) => name.push_str(&sanitize(value)),
+                None => name.push_str(&format!("#{}", index)),
+            },
+        }
+    }
+    FileUtils::expand_path(&name)
+}
+
+enum Part {
+    Text(String),
+    Placeholder(&'static str),
+    /// 1-based
+    Glob(usize),
+}
+
+fn parse(template: &str) -> Result<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|&(_, next)| next == '{').is_some() => text.push('{'),
+            '}' if chars.next_if(|&(_, next)| next == '}').is_some() => text.push('}'),
+            '{' => {
+                let rest = &template[index + 1..];
+                let name = rest.split_once('}').map(|(name, _)| name).ok_or_else(|| {
+                    RurlError::Config(format!("Unclosed '{{' in output name: {}", template))
+                })?;
+                let placeholder = PLACEHOLDERS
+                    .iter()
+                    .find(|&&known| known == name)
+                    .ok_or_else(|| {
+                        RurlError::Config(format!(
+                            "Unknown output name placeholder {{{}}}; use one of {}",
+                            name,
+                            PLACEHOLDERS.join(", ")
+                        ))
+                    })?;
+                for _ in 0..=name.chars().count() {
+                    chars.next();
+                }
+                parts.push(Part::Text(std::mem::take(&mut text)));
+                parts.push(Part::Placeholder(placeholder));
+            }
+            '}' => {
+                return Err(RurlError::Config(format!(
+                    "Unmatched '}}' in output name: {}",
+                    template
+                )))
+            }
+            '#' => {
+                let digits: String = template[index + 1..]
+                    .chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect();
+                match digits.parse::<usize>() {
+                    Ok(glob) if glob > 0 => {
+                        for _ in 0..digits.len() {
+                            chars.next();
+                        }
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                        parts.push(Part::Glob(glob));
+                    }
+                    _ => text.push('#'),
+                }
+            }
+            c => text.push(c),
+        }
+    }
+    parts.push(Part::Text(text));
+    Ok(parts)
+}
+
+fn placeholder_value(placeholder: &str, values: &TemplateValues) -> String {
+    let url = values.url;
+    match placeholder {
+        "host" => url.host_str().unwrap_or_default().to_string(),
+        "port" => url
+            .port_or_known_default()
+            .map(|port| port.to_string())
+            .unwrap_or_default(),
+        "scheme" => url.scheme().to_string(),
+        "path" => url.path().trim_matches('/').replace('/', "_"),
+        "path_basename" => UrlUtils::remote_file_name(url).unwrap_or_else(|| "index".to_string()),
+        "query" => url.query().unwrap_or_default().to_string(),
+        "status" => values.status.as_u16().to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Keep a value from adding directories to the output path
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_template, render, validate, TemplateValues};
+    use reqwest::StatusCode;
+    use std::path::PathBuf;
+    use url::Url;
+
+    #[test]
+    fn render_fills_placeholders_and_globs() {
+        let url = Url::parse("https://api.example.com/v1/users/42.json?page=2").expect("url");
+        let globs = ["a/b".to_string()];
+        let values = TemplateValues {
+            url: &url,
+            status: StatusCode::OK,
+            globs: &globs,
+        };
+        assert_eq!(
+            render("{host}_{path_basename}_{status}.json", &values).expect("render"),
+            PathBuf::from("api.example.com_42.json_200.json")
+        );
+        assert_eq!(
+            render("{{{path}}}#1-{port}#", &values).expect("render"),
+            PathBuf::from("{v1_users_42.json}a_b-443#")
+        );
+        assert_eq!(render("#2", &values).expect("render"), PathBuf::from("#2"));
+    }
+
+    #[test]
+    fn templates_are_recognized_and_checked() {
+        assert!(is_template("{host}.html"));
+        assert!(is_template("page_#1.html"));
+        assert!(!is_template("notes#draft.txt"));
+        assert!(validate("{host}_#1.html").is_ok());
+        assert!(validate("{hots}.html").is_err());
+        assert!(validate("{host.html").is_err());
+        assert!(validate("host}.html").is_err());
+    }
+}
@@ -1,11 +1,16 @@
 use super::{
-    decode_body_with_charset, extract_charset, format_response_headers, http_version_label,
-    progress_line, OutputWriter, ProgressReporter,
+    content_range_start, content_range_total, decode_body_with_charset, extract_charset,
+    format_response_headers, format_trailers, hop_summary, http_version_label, looks_binary,
+    part_path, progress_line, redirect_chain_table, resumable_part_len, unknown_content_encoding,
+    LowSpeedMonitor, OutputWriter, ProgressReporter,
 };
-use crate::config::OutputConfig;
+use crate::config::{ClipboardCopy, OutputConfig, PagerMode, VerbosityLevel, DEFAULT_MEMORY_LIMIT};
+use crate::progress::{ProgressObserver, TransferPhase};
 use encoding_rs::WINDOWS_1252;
 use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::{StatusCode, Version};
+use std::io::Write;
+use std::time::{Duration, Instant};
 use tempfile::tempdir;
 
 #[test]
@@ -49,19 +54,78 @@ fn progress_line_formats_with_and_without_total() {
 
 #[test]
 fn progress_reporter_respects_rate_limit_and_finish() {
-    let mut reporter = ProgressReporter::new(true, Some(200));
-    reporter.update(50);
-    reporter.finish(200);
+    let reporter = ProgressReporter::new(true);
+    reporter.on_download(50, Some(200));
+    reporter.on_download(200, Some(200));
+    reporter.on_phase(TransferPhase::Finished);
     assert!(reporter.rendered());
 }
 
 #[test]
 fn progress_reporter_renders_on_finish_without_updates() {
-    let mut reporter = ProgressReporter::new(true, Some(10));
-    reporter.finish(10);
+    let reporter = ProgressReporter::new(true);
+    reporter.on_phase(TransferPhase::Finished);
     assert!(reporter.rendered());
 }
 
+#[test]
+fn hop_summary_includes_url_peer_and_latency() {
+    let mut info = crate::http::ResponseInfo {
+        version: Version::HTTP_11,
+        status: StatusCode::OK,
+        headers: HeaderMap::new(),
+        url: url::Url::parse("http://example.com/a").expect("url"),
+        elapsed: Duration::from_millis(12),
+        remote_addr: Some("127.0.0.1:8080".parse().expect("addr")),
+    };
+    assert_eq!(
+        hop_summary(&info),
+        "Response from http://example.com/a (127.0.0.1:8080) in 12 ms"
+    );
+    info.remote_addr = None;
+    assert_eq!(
+        hop_summary(&info),
+        "Response from http://example.com/a in 12 ms"
+    );
+}
+
+#[test]
+fn redirect_chain_table_lists_hops_and_targets() {
+    let hop = |url: &str, status: StatusCode, location: Option<&str>| {
+        let mut headers = HeaderMap::new();
+        if let Some(location) = location {
+            headers.insert("location", HeaderValue::from_str(location).expect("value"));
+        }
+        crate::http::ResponseInfo {
+            version: Version::HTTP_11,
+            status,
+            headers,
+            url: url::Url::parse(url).expect("url"),
+            elapsed: Duration::from_millis(1),
+            remote_addr: None,
+        }
+    };
+    let history = [
+        hop(
+            "http://example.com/old",
+            StatusCode::MOVED_PERMANENTLY,
+            Some("/new"),
+        ),
+        hop(
+            "http://example.com/new",
+            StatusCode::FOUND,
+            Some("https://cdn.example.com/file"),
+        ),
+        hop("https://cdn.example.com/file", StatusCode::OK, None),
+    ];
+    assert_eq!(
+        redirect_chain_table(&history),
+        "1  301 Moved Permanently  http://example.com/old -> http://example.com/new\n\
+         2  302 Found  http://example.com/new -> https://cdn.example.com/file\n\
+         3  200 OK  https://cdn.example.com/file\n"
+    );
+}
+
 #[test]
 fn http_version_label_maps_known_versions() {
     assert_eq!(http_version_label(Version::HTTP_11), "HTTP/1.1");
@@ -78,19 +142,261 @@ fn format_response_headers_includes_status_and_headers() {
     assert!(output.ends_with('\n'));
 }
 
+#[test]
+fn format_trailers_lists_fields() {
+    let mut trailers = HeaderMap::new();
+    assert_eq!(format_trailers(&trailers), "");
+    trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+    assert_eq!(format_trailers(&trailers), "x-checksum: abc\n");
+}
+
 #[test]
 fn output_writer_writes_to_file() {
     let temp = tempdir().expect("tempdir");
     let path = temp.path().join("out.txt");
     let writer = OutputWriter::new(OutputConfig {
         file: Some(path.clone()),
-        verbose: false,
+        file_template: None,
+        tee: Vec::new(),
+        allow_binary_stdout: false,
+        remove_on_error: false,
+        no_clobber: false,
+        remote_time: false,
+        verbosity: VerbosityLevel::Off,
         silent: false,
         show_progress: false,
         format_json: false,
+        format_xml: false,
         include_headers: false,
+        raw: false,
+        speed_limit: None,
+        speed_time: Duration::from_secs(30),
+        memory_limit: DEFAULT_MEMORY_LIMIT,
+        output_charset: None,
+        hexdump: false,
+        pager: PagerMode::Never,
+        copy: ClipboardCopy::Off,
+        show_redirect_chain: false,
+        grep: None,
     });
     writer.write("data").expect("write");
     let written = std::fs::read_to_string(path).expect("read");
     assert_eq!(written, "data");
 }
+
+#[test]
+fn output_writer_tees_streamed_output_into_every_file() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("out.txt");
+    let copy = temp.path().join("copy.txt");
+    let writer = OutputWriter::new(OutputConfig {
+        tee: vec![copy.clone()],
+        ..file_output_config(path.clone())
+    });
+    writer
+        .write_streamed_with_mtime(None, |out| {
+            out.write_all(b"first ")?;
+            out.write_all(b"second")?;
+            Ok(())
+        })
+        .expect("write");
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("read"),
+        "first second"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&copy).expect("read"),
+        "first second"
+    );
+}
+
+#[test]
+fn low_speed_monitor_flags_slow_window() {
+    let start = Instant::now();
+    let mut monitor = LowSpeedMonitor::new(100, Duration::from_secs(2), start);
+    assert!(!monitor.record(50, start + Duration::from_secs(1)));
+    assert!(monitor.record(50, start + Duration::from_secs(2)));
+}
+
+#[test]
+fn low_speed_monitor_resets_after_fast_window() {
+    let start = Instant::now();
+    let mut monitor = LowSpeedMonitor::new(100, Duration::from_secs(1), start);
+    assert!(!monitor.record(500, start + Duration::from_secs(1)));
+    assert!(!monitor.record(150, start + Duration::from_secs(2)));
+    assert!(monitor.record(0, start + Duration::from_secs(3)));
+}
+
+#[test]
+fn looks_binary_detects_nul_bytes() {
+    assert!(looks_binary(b"\x89PNG\r\n\x1a\n\x00\x00"));
+    assert!(!looks_binary("plain text \u{3042}".as_bytes()));
+    assert!(!looks_binary(b""));
+}
+
+#[test]
+fn output_writer_no_clobber_picks_numbered_name() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("out.txt");
+    std::fs::write(&path, "existing").expect("write");
+    std::fs::write(temp.path().join("out.txt.1"), "existing").expect("write");
+    let writer = OutputWriter::new(OutputConfig {
+        no_clobber: true,
+        ..file_output_config(path.clone())
+    });
+    writer.write("data").expect("write");
+    assert_eq!(std::fs::read_to_string(&path).expect("read"), "existing");
+    assert_eq!(
+        std::fs::read_to_string(temp.path().join("out.txt.2")).expect("read"),
+        "data"
+    );
+}
+
+#[test]
+fn output_writer_remove_partial_deletes_file() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("partial.txt");
+    std::fs::write(&path, "partial").expect("write");
+    let writer = OutputWriter::new(OutputConfig {
+        remove_on_error: true,
+        ..file_output_config(path.clone())
+    });
+    writer.remove_partial(&path);
+    assert!(!path.exists());
+}
+
+fn file_output_config(path: std::path::PathBuf) -> OutputConfig {
+    OutputConfig {
+        file: Some(path),
+        file_template: None,
+        tee: Vec::new(),
+        allow_binary_stdout: false,
+        remove_on_error: false,
+        no_clobber: false,
+        remote_time: false,
+        verbosity: VerbosityLevel::Off,
+        silent: false,
+        show_progress: false,
+        format_json: false,
+        format_xml: false,
+        include_headers: false,
+        raw: false,
+        speed_limit: None,
+        speed_time: Duration::from_secs(30),
+        memory_limit: DEFAULT_MEMORY_LIMIT,
+        output_charset: None,
+        hexdump: false,
+        pager: PagerMode::Never,
+        copy: ClipboardCopy::Off,
+        show_redirect_chain: false,
+        grep: None,
+    }
+}
+
+#[test]
+fn content_range_start_parses_first_byte() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "content-range",
+        HeaderValue::from_static("bytes 100-199/200"),
+    );
+    assert_eq!(content_range_start(&headers), Some(100));
+    assert_eq!(content_range_start(&HeaderMap::new()), None);
+}
+
+#[test]
+fn content_range_total_parses_complete_length() {
+    let mut headers = HeaderMap::new();
+    headers.insert("content-range", HeaderValue::from_static("bytes */200"));
+    assert_eq!(content_range_total(&headers), Some(200));
+    headers.insert("content-range", HeaderValue::from_static("bytes */*"));
+    assert_eq!(content_range_total(&headers), None);
+}
+
+#[test]
+fn resumable_part_len_reports_existing_part_file() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("download.bin");
+    let config = file_output_config(path.clone());
+    assert_eq!(resumable_part_len(&config), None);
+
+    std::fs::write(part_path(&path), b"12345").expect("write part");
+    assert_eq!(resumable_part_len(&config), Some(5));
+}
+
+#[test]
+fn resumable_part_len_ignores_part_file_with_headers() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("download.bin");
+    let mut config = file_output_config(path.clone());
+    config.include_headers = true;
+    std::fs::write(part_path(&path), b"HTTP/1.1 200 OK\n").expect("write part");
+    assert_eq!(resumable_part_len(&config), None);
+}
+
+#[test]
+fn output_writer_sets_remote_mtime() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("stamped.txt");
+    let writer = OutputWriter::new(file_output_config(path.clone()));
+    let modified = std::time::UNIX_EPOCH + Duration::from_secs(784_111_777);
+    writer
+        .write_with_mtime("data", Some(modified))
+        .expect("write");
+    let metadata = std::fs::metadata(&path).expect("metadata");
+    assert_eq!(metadata.modified().expect("mtime"), modified);
+}
+
+#[derive(Default)]
+struct PhaseRecorder {
+    phases: std::sync::Mutex<Vec<TransferPhase>>,
+    received: std::sync::Mutex<Vec<(u64, Option<u64>)>>,
+}
+
+impl ProgressObserver for PhaseRecorder {
+    fn on_phase(&self, phase: TransferPhase) {
+        self.phases.lock().unwrap().push(phase);
+    }
+
+    fn on_download(&self, received: u64, total: Option<u64>) {
+        self.received.lock().unwrap().push((received, total));
+    }
+}
+
+#[tokio::test]
+async fn output_manager_reports_download_to_observer() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("out.txt");
+    let recorder = std::sync::Arc::new(PhaseRecorder::default());
+    let manager = super::OutputManager::new(file_output_config(path.clone()))
+        .with_progress_observer(recorder.clone());
+
+    let response = http::Response::builder()
+        .header("content-length", "5")
+        .body("hello")
+        .expect("response");
+    manager
+        .write_response(reqwest::Response::from(response), &[])
+        .await
+        .expect("write");
+
+    assert_eq!(std::fs::read(&path).expect("read"), b"hello");
+    assert_eq!(
+        *recorder.phases.lock().unwrap(),
+        vec![TransferPhase::Downloading, TransferPhase::Finished]
+    );
+    assert_eq!(
+        recorder.received.lock().unwrap().last(),
+        Some(&(5, Some(5)))
+    );
+}
+
+#[test]
+fn unknown_content_encoding_ignores_identity() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(unknown_content_encoding(&headers), None);
+    headers.insert("content-encoding", HeaderValue::from_static("identity"));
+    assert_eq!(unknown_content_encoding(&headers), None);
+    headers.insert("content-encoding", HeaderValue::from_static("zstd"));
+    assert_eq!(unknown_content_encoding(&headers), Some("zstd".to_string()));
+}
@@ -0,0 +1,30 @@
+//! Transfer progress callbacks
+//!
+//! `OutputManager` reports download progress, and `HttpClient` upload
+//! progress, through [`ProgressObserver`]. The terminal progress meter is one
+//! such observer; GUI and TUI frontends can register their own instead of
+//! parsing stderr.
+
+/// Stage of a transfer reported by [`ProgressObserver::on_phase`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferPhase {
+    /// The request body is being sent
+    Uploading,
+    /// The response body is being received
+    Downloading,
+    /// The response body was received completely
+    Finished,
+    /// The transfer was cancelled
+    Aborted,
+}
+
+/// Receives progress updates; every method has a no-op default
+pub trait ProgressObserver: Send + Sync {
+    fn on_phase(&self, _phase: TransferPhase) {}
+
+    /// `sent` request body bytes so far, out of `total` when known
+    fn on_upload(&self, _sent: u64, _total: Option<u64>) {}
+
+    /// `received` response body bytes so far, out of `total` when known
+    fn on_download(&self, _received: u64, _total: Option<u64>) {}
+}
@@ -0,0 +1,285 @@
+//! Named sessions (`--session NAME`)
+//!
+//! Like HTTPie sessions, custom headers, basic auth credentials and cookies
+//! are kept per host in `~/.config/rurl/sessions/HOST/NAME.json` (the platform
+//! config directory) and applied again on later invocations with the same
+//! NAME. Headers and credentials given on the command line override and
+//! update the stored ones; `Content-*` and `If-*` headers describe a single
+//! request and are not saved.
+
+use crate::browser::{Cookie, CookieStore};
+use crate::config::Config;
+use crate::cookie_store::upsert_cookie;
+use crate::error::{Result, RurlError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionAuth {
+    pub username: String,
+    pub password: String,
+}
+
+/// State saved between invocations of one named session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub auth: Option<SessionAuth>,
+    #[serde(default)]
+    pub cookies: Vec<Cookie>,
+}
+
+impl Session {
+    /// Read a session file; a missing file is an empty session
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Store the headers and credentials of `config`, then fill in what
+    /// `config` lacks from the session
+    pub fn merge(&mut self, config: &mut Config) {
+        for (name, value) in &config.headers {
+            if is_request_specific(name) {
+                continue;
+            }
+            self.headers
+                .retain(|stored, _| !stored.eq_ignore_ascii_case(name));
+            self.headers.insert(name.clone(), value.clone());
+        }
+        // `-H 'Name:'` drops a stored header for good
+        for name in config.headers.suppressed() {
+            self.headers
+                .retain(|stored, _| !stored.eq_ignore_ascii_case(name));
+        }
+        for (name, value) in &self.headers {
+            if !config.headers.contains_key(name) {
+                config.headers.append(name.clone(), value.clone());
+            }
+        }
+
+        match (&config.auth_username, &config.auth_password) {
+            (Some(username), Some(password)) => {
+                self.auth = Some(SessionAuth {
+                    username: username.clone(),
+                    password: password.clone(),
+                });
+            }
+            _ => {
+                if let Some(auth) = &self.auth {
+                    config.auth_username = Some(auth.username.clone());
+                    config.auth_password = Some(auth.password.clone());
+                }
+            }
+        }
+    }
+
+    /// Session cookies grouped by domain
+    pub fn cookie_store(&self) -> CookieStore {
+        let mut store = CookieStore::new();
+        for cookie in &self.cookies {
+            upsert_cookie(&mut store, cookie.clone());
+        }
+        store
+    }
+
+    /// Add or replace a cookie received in a response; expired ones are dropped
+    pub fn store_cookie(&mut self, cookie: Cookie) {
+        let mut store = self.cookie_store();
+        upsert_cookie(&mut store, cookie);
+        self.cookies = store.into_values().flatten().collect();
+        self.cookies
+            .sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+    }
+}
+
+/// A session bound to its file; cookies are written back as they arrive
+pub struct SessionFile {
+    path: PathBuf,
+    session: Mutex<Session>,
+}
+
+impl SessionFile {
+    /// Load session `name` for the host of `config.url` and merge it with `config`
+    pub fn open(name: &str, config: &mut Config) -> Result<Self> {
+        let url = Url::parse(&config.url)
+            .map_err(|e| RurlError::InvalidUrl(format!("Invalid URL '{}': {}", config.url, e)))?;
+        let path = session_path(name, &url)?;
+        let mut session = Session::load(&path)?;
+        session.merge(config);
+        Ok(Self {
+            path,
+            session: Mutex::new(session),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn cookie_store(&self) -> CookieStore {
+        self.lock().cookie_store()
+    }
+
+    /// Record `cookies` and save the session, including merged headers and auth
+    pub fn save_with_cookies(&self, cookies: Vec<Cookie>) -> Result<()> {
+        let mut session = self.lock();
+        for cookie in cookies {
+            session.store_cookie(cookie);
+        }
+        session.save(&self.path)
+    }
+}
+
+/// File of session `name` for the host of `url`
+pub fn session_path(name: &str, url: &Url) -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| RurlError::Config("Cannot determine config directory".to_string()))?;
+    session_path_in(&config_dir.join("rurl").join("sessions"), name, url)
+}
+
+fn session_path_in(dir: &Path, name: &str, url: &Url) -> Result<PathBuf> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(RurlError::Config(format!(
+            "Invalid session name '{}': use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| RurlError::InvalidUrl(format!("No host in URL: {}", url)))?;
+    let host_dir = match url.port() {
+        Some(port) => format!("{}_{}", host, port),
+        None => host.to_string(),
+    };
+    Ok(dir
+        .join(host_dir.replace(['[', ']', ':'], "_"))
+        .join(format!("{}.json", name)))
+}
+
+fn is_request_specific(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    lower.starts_with("content-") || lower.starts_with("if-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{session_path_in, Session};
+    use crate::browser::Cookie;
+    use crate::config::Config;
+    use std::path::Path;
+    use url::Url;
+
+    fn cookie(name: &str, value: &str) -> Cookie {
+        Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: "example.com".to_string(),
+            path: "/".to_string(),
+            secure: false,
+            http_only: false,
+            expires: None,
+        }
+    }
+
+    #[test]
+    fn session_path_uses_host_and_port() {
+        let dir = Path::new("/sessions");
+        let url = Url::parse("http://localhost:8080/api").expect("url");
+        assert_eq!(
+            session_path_in(dir, "work", &url).expect("path"),
+            Path::new("/sessions/localhost_8080/work.json")
+        );
+        assert!(session_path_in(dir, "../evil", &url).is_err());
+    }
+
+    #[test]
+    fn merge_keeps_cli_values_and_fills_missing_ones() {
+        let mut session = Session::default();
+        session
+            .headers
+            .insert("X-Api-Key".to_string(), "old".to_string());
+        session
+            .headers
+            .insert("Accept".to_string(), "text/plain".to_string());
+
+        let mut config = Config::default();
+        config
+            .headers
+            .insert("x-api-key".to_string(), "new".to_string());
+        config
+            .headers
+            .insert("Content-Type".to_string(), "application/json".to_string());
+        config.auth_username = Some("user".to_string());
+        config.auth_password = Some("pass".to_string());
+        session.merge(&mut config);
+
+        assert_eq!(
+            config.headers.get("x-api-key").map(String::as_str),
+            Some("new")
+        );
+        assert_eq!(
+            config.headers.get("Accept").map(String::as_str),
+            Some("text/plain")
+        );
+        assert!(!session.headers.contains_key("X-Api-Key"));
+        assert!(!session.headers.contains_key("Content-Type"));
+        assert_eq!(
+            session.auth.as_ref().map(|a| a.username.as_str()),
+            Some("user")
+        );
+
+        let mut later = Config::default();
+        session.merge(&mut later);
+        assert_eq!(later.auth_password.as_deref(), Some("pass"));
+        assert_eq!(
+            later.headers.get("x-api-key").map(String::as_str),
+            Some("new")
+        );
+        let mut unset = Config::default();
+        unset.headers.suppress("Accept");
+        session.merge(&mut unset);
+        assert!(!unset.headers.contains_key("accept"));
+        assert!(!session.headers.contains_key("Accept"));
+    }
+
+    #[test]
+    fn store_cookie_replaces_same_name() {
+        let mut session = Session::default();
+        session.store_cookie(cookie("sid", "1"));
+        session.store_cookie(cookie("sid", "2"));
+        assert_eq!(session.cookies.len(), 1);
+        assert_eq!(session.cookies[0].value, "2");
+    }
+
+    #[test]
+    fn load_missing_file_is_empty_and_save_round_trips() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("example.com").join("work.json");
+        let mut session = Session::load(&path).expect("load");
+        assert!(session.cookies.is_empty());
+        session.store_cookie(cookie("sid", "1"));
+        session.save(&path).expect("save");
+        assert_eq!(Session::load(&path).expect("reload").cookies.len(), 1);
+    }
+}
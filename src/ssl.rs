@@ -19,6 +19,15 @@ impl SslUtils {
             }
         }
 
+        if let Some(uri) = &config.pkcs11_uri {
+            Pkcs11Uri::parse(uri)?;
+            if !cfg!(feature = "pkcs11") {
+                return Err(RurlError::Unsupported(
+                    "PKCS#11 support requires building rurl with --features pkcs11".to_string(),
+                ));
+            }
+        }
+
         if let Some(cert) = &config.client_cert_file {
             if !cert.exists() {
                 return Err(RurlError::FileNotFound(format!(
@@ -46,5 +55,80 @@ impl SslUtils {
     }
 }
 
+/// Parsed RFC 7512 `pkcs11:` URI selecting a token-held certificate and key
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pkcs11Uri {
+    pub token: Option<String>,
+    pub object: Option<String>,
+    pub id: Option<Vec<u8>>,
+    pub module_path: Option<String>,
+    pub pin: Option<String>,
+}
+
+impl Pkcs11Uri {
+    /// Parse `pkcs11:token=...;object=...[?module-path=...&pin-value=...]`
+    pub fn parse(uri: &str) -> Result<Self> {
+        let rest = uri
+            .strip_prefix("pkcs11:")
+            .ok_or_else(|| RurlError::Ssl(format!("Not a PKCS#11 URI: {}", uri)))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let mut parsed = Pkcs11Uri::default();
+        let path_attrs = path.split(';').map(|attr| (attr, true));
+        let query_attrs = query.split('&').map(|attr| (attr, false));
+        for (attr, in_path) in path_attrs.chain(query_attrs) {
+            if attr.is_empty() {
+                continue;
+            }
+            let (name, value) = attr.split_once('=').ok_or_else(|| {
+                RurlError::Ssl(format!("Invalid PKCS#11 URI attribute: {}", attr))
+            })?;
+            let value = percent_decode(value)?;
+            match (name, in_path) {
+                ("token", true) => parsed.token = Some(String::from_utf8_lossy(&value).into()),
+                ("object", true) => parsed.object = Some(String::from_utf8_lossy(&value).into()),
+                ("id", true) => parsed.id = Some(value),
+                ("module-path", false) => {
+                    parsed.module_path = Some(String::from_utf8_lossy(&value).into())
+                }
+                ("pin-value", false) => parsed.pin = Some(String::from_utf8_lossy(&value).into()),
+                // Other attributes (type, manufacturer, ...) only narrow the match
+                _ => {}
+            }
+        }
+
+        if parsed.object.is_none() && parsed.id.is_none() {
+            return Err(RurlError::Ssl(
+                "PKCS#11 URI must name an object or id".to_string(),
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+fn percent_decode(value: &str) -> Result<Vec<u8>> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| RurlError::Ssl(format!("Invalid percent-encoding: {}", value)))?;
+            decoded.push(hex);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+pub(crate) mod tls;
+
 #[cfg(test)]
 mod tests;
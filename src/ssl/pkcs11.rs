@@ -0,0 +1,333 @@
+//! PKCS#11 client certificates (`--engine pkcs11`)
+//!
+//! The certificate is read from the token and every TLS handshake signature
+//! is computed by the token, so the private key never leaves the device. The
+//! resulting rustls configuration replaces reqwest's default TLS setup.
+
+use super::tls::verifying_builder;
+use super::Pkcs11Uri;
+use crate::config::SslConfig;
+use crate::error::{Result, RurlError};
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::rsa::{PkcsMgfType, PkcsPssParams};
+use cryptoki::mechanism::{Mechanism, MechanismType};
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use rustls::client::ResolvesClientCert;
+use rustls::pki_types::CertificateDer;
+use rustls::sign::{CertifiedKey, Signer, SigningKey};
+use rustls::{ClientConfig, SignatureAlgorithm, SignatureScheme};
+use std::sync::{Arc, Mutex};
+
+/// Environment variables used when the URI has no `module-path`/`pin-value`
+const MODULE_ENV: &str = "RURL_PKCS11_MODULE";
+const PIN_ENV: &str = "RURL_PKCS11_PIN";
+
+/// DER OID of the secp384r1 curve as stored in `CKA_EC_PARAMS`
+const SECP384R1_PARAMS: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22];
+
+/// Build a rustls client configuration that authenticates with the token key
+pub fn client_tls_config(ssl: &SslConfig) -> Result<ClientConfig> {
+    let uri_str = ssl
+        .pkcs11_uri
+        .as_deref()
+        .ok_or_else(|| RurlError::Ssl("No PKCS#11 URI configured".to_string()))?;
+    let uri = Pkcs11Uri::parse(uri_str)?;
+    let certified_key = Arc::new(load_certified_key(&uri)?);
+    Ok(verifying_builder(ssl)?
+        .with_client_cert_resolver(Arc::new(TokenCertResolver(certified_key))))
+}
+
+fn load_certified_key(uri: &Pkcs11Uri) -> Result<CertifiedKey> {
+    let module = uri
+        .module_path
+        .clone()
+        .or_else(|| std::env::var(MODULE_ENV).ok())
+        .ok_or_else(|| {
+            RurlError::Ssl(format!(
+                "PKCS#11 module not specified; add module-path to the URI or set {}",
+                MODULE_ENV
+            ))
+        })?;
+    let pkcs11 = Pkcs11::new(&module)
+        .map_err(|e| RurlError::Ssl(format!("Failed to load PKCS#11 module {}: {}", module, e)))?;
+    pkcs11
+        .initialize(CInitializeArgs::OsThreads)
+        .map_err(token_error)?;
+
+    let slot = pkcs11
+        .get_slots_with_token()
+        .map_err(token_error)?
+        .into_iter()
+        .find(|slot| match &uri.token {
+            Some(label) => pkcs11
+                .get_token_info(*slot)
+                .map(|info| info.label() == label.as_str())
+                .unwrap_or(false),
+            None => true,
+        })
+        .ok_or_else(|| RurlError::Ssl("PKCS#11 token not found".to_string()))?;
+
+    let session = pkcs11.open_ro_session(slot).map_err(token_error)?;
+    if let Some(pin) = uri.pin.clone().or_else(|| std::env::var(PIN_ENV).ok()) {
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin)))
+            .map_err(token_error)?;
+    }
+
+    let cert_handle = find_object(&session, uri, ObjectClass::CERTIFICATE)?;
+    let cert_der = match session
+        .get_attributes(cert_handle, &[AttributeType::Value])
+        .map_err(token_error)?
+        .into_iter()
+        .next()
+    {
+        Some(Attribute::Value(der)) => der,
+        _ => {
+            return Err(RurlError::Ssl(
+                "PKCS#11 certificate has no value".to_string(),
+            ))
+        }
+    };
+
+    let key_handle = find_object(&session, uri, ObjectClass::PRIVATE_KEY)?;
+    let attributes = session
+        .get_attributes(
+            key_handle,
+            &[AttributeType::KeyType, AttributeType::EcParams],
+        )
+        .map_err(token_error)?;
+    let mut key_type = None;
+    let mut ec_params = None;
+    for attribute in attributes {
+        match attribute {
+            Attribute::KeyType(kind) => key_type = Some(kind),
+            Attribute::EcParams(params) => ec_params = Some(params),
+            _ => {}
+        }
+    }
+    let kind = match key_type {
+        Some(KeyType::RSA) => TokenKeyKind::Rsa,
+        Some(KeyType::EC) if ec_params.as_deref() == Some(SECP384R1_PARAMS) => {
+            TokenKeyKind::EcdsaP384
+        }
+        Some(KeyType::EC) => TokenKeyKind::EcdsaP256,
+        other => {
+            return Err(RurlError::Ssl(format!(
+                "Unsupported PKCS#11 key type: {:?}",
+                other
+            )))
+        }
+    };
+
+    let key = TokenSigningKey {
+        session: Arc::new(Mutex::new(session)),
+        handle: key_handle,
+        kind,
+    };
+    Ok(CertifiedKey::new(
+        vec![CertificateDer::from(cert_der)],
+        Arc::new(key),
+    ))
+}
+
+fn find_object(session: &Session, uri: &Pkcs11Uri, class: ObjectClass) -> Result<ObjectHandle> {
+    let mut template = vec![Attribute::Class(class)];
+    if let Some(label) = &uri.object {
+        template.push(Attribute::Label(label.as_bytes().to_vec()));
+    }
+    if let Some(id) = &uri.id {
+        template.push(Attribute::Id(id.clone()));
+    }
+    session
+        .find_objects(&template)
+        .map_err(token_error)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            RurlError::Ssl(format!(
+                "PKCS#11 {} not found on token",
+                if class == ObjectClass::CERTIFICATE {
+                    "certificate"
+                } else {
+                    "private key"
+                }
+            ))
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKeyKind {
+    Rsa,
+    EcdsaP256,
+    EcdsaP384,
+}
+
+impl TokenKeyKind {
+    /// Schemes in preference order
+    fn schemes(self) -> &'static [SignatureScheme] {
+        match self {
+            TokenKeyKind::Rsa => &[
+                SignatureScheme::RSA_PSS_SHA256,
+                SignatureScheme::RSA_PKCS1_SHA256,
+            ],
+            TokenKeyKind::EcdsaP256 => &[SignatureScheme::ECDSA_NISTP256_SHA256],
+            TokenKeyKind::EcdsaP384 => &[SignatureScheme::ECDSA_NISTP384_SHA384],
+        }
+    }
+}
+
+/// Private key that stays on the token
+#[derive(Debug)]
+struct TokenSigningKey {
+    session: Arc<Mutex<Session>>,
+    handle: ObjectHandle,
+    kind: TokenKeyKind,
+}
+
+impl SigningKey for TokenSigningKey {
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<dyn Signer>> {
+        let scheme = self
+            .kind
+            .schemes()
+            .iter()
+            .find(|scheme| offered.contains(scheme))?;
+        Some(Box::new(TokenSigner {
+            session: self.session.clone(),
+            handle: self.handle,
+            scheme: *scheme,
+        }))
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        match self.kind {
+            TokenKeyKind::Rsa => SignatureAlgorithm::RSA,
+            TokenKeyKind::EcdsaP256 | TokenKeyKind::EcdsaP384 => SignatureAlgorithm::ECDSA,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenSigner {
+    session: Arc<Mutex<Session>>,
+    handle: ObjectHandle,
+    scheme: SignatureScheme,
+}
+
+impl Signer for TokenSigner {
+    fn sign(&self, message: &[u8]) -> std::result::Result<Vec<u8>, rustls::Error> {
+        let mechanism = match self.scheme {
+            SignatureScheme::RSA_PSS_SHA256 => Mechanism::Sha256RsaPkcsPss(PkcsPssParams {
+                hash_alg: MechanismType::SHA256,
+                mgf: PkcsMgfType::MGF1_SHA256,
+                s_len: 32.into(),
+            }),
+            SignatureScheme::RSA_PKCS1_SHA256 => Mechanism::Sha256RsaPkcs,
+            SignatureScheme::ECDSA_NISTP256_SHA256 => Mechanism::EcdsaSha256,
+            SignatureScheme::ECDSA_NISTP384_SHA384 => Mechanism::EcdsaSha384,
+            other => {
+                return Err(rustls::Error::General(format!(
+                    "Unsupported signature scheme {:?}",
+                    other
+                )))
+            }
+        };
+        let session = self
+            .session
+            .lock()
+            .map_err(|_| rustls::Error::General("PKCS#11 session is poisoned".to_string()))?;
+        let signature = session
+            .sign(&mechanism, self.handle, message)
+            .map_err(|e| rustls::Error::General(format!("PKCS#11 signing failed: {}", e)))?;
+        match self.scheme {
+            // Tokens return raw r || s; TLS expects a DER ECDSA-Sig-Value
+            SignatureScheme::ECDSA_NISTP256_SHA256 | SignatureScheme::ECDSA_NISTP384_SHA384 => {
+                Ok(ecdsa_raw_to_der(&signature))
+            }
+            _ => Ok(signature),
+        }
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+}
+
+#[derive(Debug)]
+struct TokenCertResolver(Arc<CertifiedKey>);
+
+impl ResolvesClientCert for TokenCertResolver {
+    fn resolve(
+        &self,
+        _root_hint_subjects: &[&[u8]],
+        sigschemes: &[SignatureScheme],
+    ) -> Option<Arc<CertifiedKey>> {
+        self.0.key.choose_scheme(sigschemes).map(|_| self.0.clone())
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+/// Encode a raw `r || s` ECDSA signature as `SEQUENCE { INTEGER r, INTEGER s }`
+fn ecdsa_raw_to_der(raw: &[u8]) -> Vec<u8> {
+    let (r, s) = raw.split_at(raw.len() / 2);
+    let r = der_integer(r);
+    let s = der_integer(s);
+    let mut der = vec![0x30];
+    der_length(&mut der, r.len() + s.len());
+    der.extend(r);
+    der.extend(s);
+    der
+}
+
+fn der_integer(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(bytes.len() - 1);
+    let value = &bytes[start..];
+    let pad = value[0] & 0x80 != 0;
+    let mut der = vec![0x02];
+    der_length(&mut der, value.len() + pad as usize);
+    if pad {
+        der.push(0);
+    }
+    der.extend_from_slice(value);
+    der
+}
+
+fn der_length(der: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        der.push(len as u8);
+    } else {
+        der.push(0x81);
+        der.push(len as u8);
+    }
+}
+
+fn token_error(err: cryptoki::error::Error) -> RurlError {
+    RurlError::Ssl(format!("PKCS#11 error: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ecdsa_raw_to_der;
+
+    #[test]
+    fn ecdsa_raw_to_der_pads_high_bit_and_strips_zeros() {
+        let mut raw = vec![0u8; 64];
+        raw[0] = 0x80;
+        raw[32] = 0x00;
+        raw[33] = 0x01;
+        let der = ecdsa_raw_to_der(&raw);
+        assert_eq!(der[0], 0x30);
+        // r: 0x80 followed by 31 zero bytes needs a leading zero
+        assert_eq!(&der[2..5], &[0x02, 33, 0x00]);
+        // s: leading zero stripped, 31 bytes remain
+        assert_eq!(&der[2 + 35..2 + 37], &[0x02, 31]);
+        assert_eq!(der[1] as usize, der.len() - 2);
+    }
+}
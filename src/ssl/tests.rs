@@ -1,4 +1,4 @@
-use super::SslUtils;
+use super::{Pkcs11Uri, SslUtils};
 use crate::config::SslConfig;
 use crate::error::RurlError;
 use std::fs;
@@ -18,6 +18,7 @@ fn validate_config_accepts_existing_files() {
         ca_cert_file: Some(ca),
         client_cert_file: Some(cert),
         client_key_file: Some(key),
+        pkcs11_uri: None,
     };
     SslUtils::validate_config(&config).expect("valid config");
 }
@@ -30,6 +31,7 @@ fn validate_config_rejects_missing_files() {
         ca_cert_file: Some(temp.path().join("missing.pem")),
         client_cert_file: Some(temp.path().join("client.pem")),
         client_key_file: Some(temp.path().join("client.key")),
+        pkcs11_uri: None,
     };
     let err = SslUtils::validate_config(&config).expect_err("missing");
     assert!(matches!(err, RurlError::FileNotFound(_)));
@@ -45,6 +47,7 @@ fn validate_config_rejects_missing_client_key() {
         ca_cert_file: None,
         client_cert_file: Some(cert),
         client_key_file: Some(temp.path().join("absent.key")),
+        pkcs11_uri: None,
     };
     let err = SslUtils::validate_config(&config).expect_err("missing key");
     assert!(matches!(err, RurlError::FileNotFound(_)));
@@ -60,6 +63,7 @@ fn validate_config_rejects_missing_client_cert() {
         ca_cert_file: None,
         client_cert_file: Some(temp.path().join("absent.pem")),
         client_key_file: Some(key),
+        pkcs11_uri: None,
     };
     let err = SslUtils::validate_config(&config).expect_err("missing cert");
     assert!(matches!(err, RurlError::FileNotFound(_)));
@@ -73,3 +77,27 @@ fn read_cert_file_reads_bytes() {
     let data = SslUtils::read_cert_file(&path).expect("read");
     assert_eq!(data, b"data");
 }
+
+#[test]
+fn pkcs11_uri_parses_path_and_query_attributes() {
+    let uri = Pkcs11Uri::parse(
+        "pkcs11:token=My%20Token;object=client;id=%01%02;type=private\
+         ?module-path=/usr/lib/opensc-pkcs11.so&pin-value=1234",
+    )
+    .expect("uri");
+    assert_eq!(uri.token.as_deref(), Some("My Token"));
+    assert_eq!(uri.object.as_deref(), Some("client"));
+    assert_eq!(uri.id, Some(vec![1, 2]));
+    assert_eq!(
+        uri.module_path.as_deref(),
+        Some("/usr/lib/opensc-pkcs11.so")
+    );
+    assert_eq!(uri.pin.as_deref(), Some("1234"));
+}
+
+#[test]
+fn pkcs11_uri_rejects_invalid_input() {
+    assert!(Pkcs11Uri::parse("file:client.pem").is_err());
+    assert!(Pkcs11Uri::parse("pkcs11:token=t").is_err());
+    assert!(Pkcs11Uri::parse("pkcs11:object=%zz").is_err());
+}
@@ -0,0 +1,129 @@
+//! rustls client configuration built from [`SslConfig`]
+//!
+//! reqwest sets up TLS for the requests it sends itself. Requests rurl sends
+//! with hyper (`-H 'Accept:'`) and PKCS#11 client keys need a rustls
+//! configuration of their own, which verifies servers the same way: the
+//! webpki roots plus `--cacert`, or nothing at all with `--insecure`.
+
+use crate::config::SslConfig;
+use crate::error::{Result, RurlError};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WantsClientCert;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ConfigBuilder, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use std::sync::Arc;
+
+/// Client configuration with the `--cert`/`--key` identity, or the token
+/// key with `--engine pkcs11`
+pub(crate) fn client_config(ssl: &SslConfig) -> Result<ClientConfig> {
+    #[cfg(feature = "pkcs11")]
+    if ssl.pkcs11_uri.is_some() {
+        return super::pkcs11::client_tls_config(ssl);
+    }
+    let builder = verifying_builder(ssl)?;
+    let Some(cert_file) = &ssl.client_cert_file else {
+        return Ok(builder.with_no_client_auth());
+    };
+    let invalid = |err: rustls::pki_types::pem::Error| {
+        RurlError::Ssl(format!(
+            "Invalid client certificate {:?}: {}",
+            cert_file, err
+        ))
+    };
+    let certs = CertificateDer::pem_file_iter(cert_file)
+        .map_err(invalid)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(invalid)?;
+    let key_file = ssl.client_key_file.as_ref().unwrap_or(cert_file);
+    let key = PrivateKeyDer::from_pem_file(key_file).map_err(invalid)?;
+    builder.with_client_auth_cert(certs, key).map_err(tls_error)
+}
+
+/// Start a client configuration that checks server certificates as `ssl`
+/// asks
+pub(crate) fn verifying_builder(
+    ssl: &SslConfig,
+) -> Result<ConfigBuilder<ClientConfig, WantsClientCert>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(tls_error)?;
+    Ok(if ssl.verify_certs {
+        builder.with_root_certificates(root_store(ssl)?)
+    } else {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyServerCert(provider)))
+    })
+}
+
+fn root_store(ssl: &SslConfig) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(ca_file) = &ssl.ca_cert_file {
+        for cert in CertificateDer::pem_file_iter(ca_file)
+            .map_err(|e| RurlError::Ssl(format!("Invalid CA certificate {:?}: {}", ca_file, e)))?
+        {
+            let cert = cert.map_err(|e| {
+                RurlError::Ssl(format!("Invalid CA certificate {:?}: {}", ca_file, e))
+            })?;
+            roots.add(cert).map_err(tls_error)?;
+        }
+    }
+    Ok(roots)
+}
+
+/// `--insecure`: accept any server certificate but still check signatures
+#[derive(Debug)]
+struct AcceptAnyServerCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyServerCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+pub(crate) fn tls_error(err: rustls::Error) -> RurlError {
+    RurlError::Ssl(format!("TLS configuration error: {}", err))
+}
@@ -0,0 +1,103 @@
+//! Connection and transfer statistics (`--stats`)
+//!
+//! Fed from the `HttpClient` event bus like [`crate::metrics`], and written to
+//! stderr as one line once the run finishes. A connection is identified by
+//! its local and remote address, so responses that share both came over a
+//! reused connection. reqwest does not report TLS session resumption, so it
+//! is not counted.
+
+use crate::http::events::HttpEvent;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    requests: u64,
+    dns_lookups: u64,
+    /// Responses that came over a known connection
+    responses: u64,
+    connections: HashSet<(String, String)>,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl Stats {
+    pub fn record(&mut self, event: &HttpEvent) {
+        match event {
+            HttpEvent::RequestStart { .. } => self.requests += 1,
+            HttpEvent::Resolved { .. } => self.dns_lookups += 1,
+            HttpEvent::Connected {
+                remote_addr: Some(remote),
+                local_addr: Some(local),
+                ..
+            } => {
+                self.responses += 1;
+                self.connections.insert((local.clone(), remote.clone()));
+            }
+            HttpEvent::BodySent { bytes, .. } => self.bytes_sent += bytes,
+            HttpEvent::BodyReceived { bytes, .. } => self.bytes_received += bytes,
+            _ => {}
+        }
+    }
+
+    /// `* Stats: key=value ...` line for stderr
+    pub fn summary(&self) -> String {
+        let opened = self.connections.len() as u64;
+        format!(
+            "* Stats: requests={} connections_opened={} connections_reused={} dns_lookups={} bytes_sent={} bytes_received={}",
+            self.requests,
+            opened,
+            self.responses.saturating_sub(opened),
+            self.dns_lookups,
+            self.bytes_sent,
+            self.bytes_received
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stats;
+    use crate::http::events::HttpEvent;
+
+    fn connected(local: &str) -> HttpEvent {
+        HttpEvent::Connected {
+            url: "http://example.com/".to_string(),
+            remote_addr: Some("192.0.2.1:80".to_string()),
+            local_addr: Some(local.to_string()),
+            version: "HTTP/1.1".to_string(),
+            tls: false,
+            alpn: None,
+            cert_verified: None,
+        }
+    }
+
+    #[test]
+    fn summary_counts_reused_connections_and_bytes() {
+        let mut stats = Stats::default();
+        for local in ["10.0.0.2:50000", "10.0.0.2:50000", "10.0.0.2:50001"] {
+            stats.record(&HttpEvent::RequestStart {
+                method: "GET".to_string(),
+                url: "http://example.com/".to_string(),
+                attempt: 1,
+                request_id: None,
+            });
+            stats.record(&connected(local));
+        }
+        stats.record(&HttpEvent::Resolved {
+            host: "example.com".to_string(),
+            addrs: vec!["192.0.2.1".to_string()],
+        });
+        stats.record(&HttpEvent::BodySent {
+            url: "http://example.com/".to_string(),
+            bytes: 12,
+        });
+        stats.record(&HttpEvent::BodyReceived {
+            url: "http://example.com/".to_string(),
+            bytes: 345,
+        });
+        assert_eq!(
+            stats.summary(),
+            "* Stats: requests=3 connections_opened=2 connections_reused=1 dns_lookups=1 bytes_sent=12 bytes_received=345"
+        );
+    }
+}
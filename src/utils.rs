@@ -2,6 +2,7 @@
 
 use crate::error::{Result, RurlError};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Url;
 
 /// URL validation and parsing utilities
@@ -10,17 +11,70 @@ pub struct UrlUtils;
 impl UrlUtils {
     /// Validate and normalize URL
     pub fn validate_url(input: &str) -> Result<Url> {
-        // Add http:// if no scheme is provided
-        let url_str = if input.contains("://") {
+        Self::validate_url_with_scheme(input, "http")
+    }
+
+    /// Validate and normalize URL, adding `scheme://` if no scheme is provided
+    pub fn validate_url_with_scheme(input: &str, scheme: &str) -> Result<Url> {
+        let url_str = if Self::has_scheme(input) {
             input.to_string()
         } else {
-            format!("http://{}", input)
+            format!("{}://{}", scheme, input)
         };
 
         Url::parse(&url_str)
             .map_err(|e| RurlError::InvalidUrl(format!("Invalid URL '{}': {}", input, e)))
     }
 
+    pub fn has_scheme(input: &str) -> bool {
+        input.contains("://")
+    }
+
+    /// Query string part for a `--url-query` argument, following curl:
+    /// `+raw` is used as-is, otherwise `name=content`, `=content`, `content`,
+    /// `name@file` and `@file` percent-encode the content or file (`-` is stdin)
+    pub fn url_query_part(spec: &str) -> Result<String> {
+        if let Some(raw) = spec.strip_prefix('+') {
+            return Ok(raw.to_string());
+        }
+        let (name, content) = match spec.find(['=', '@']) {
+            Some(pos) if spec.as_bytes()[pos] == b'@' => {
+                let path = &spec[pos + 1..];
+                let content = if path == "-" {
+                    std::io::read_to_string(std::io::stdin())?
+                } else {
+                    std::fs::read_to_string(FileUtils::expand_path(path)?)?
+                };
+                (&spec[..pos], content)
+            }
+            Some(pos) => (&spec[..pos], spec[pos + 1..].to_string()),
+            None => ("", spec.to_string()),
+        };
+        let encoded = StringUtils::percent_encode(&content);
+        if name.is_empty() {
+            Ok(encoded)
+        } else {
+            Ok(format!("{}={}", name, encoded))
+        }
+    }
+
+    /// Append `part` to the query of `url`, separated by `&`
+    pub fn append_query(url: &mut Url, part: &str) {
+        let query = match url.query() {
+            Some(existing) if !existing.is_empty() => format!("{}&{}", existing, part),
+            _ => part.to_string(),
+        };
+        url.set_query(Some(&query));
+    }
+
+    /// File name for `-O/--remote-name`: the last non-empty path segment
+    pub fn remote_file_name(url: &Url) -> Option<String> {
+        url.path_segments()?
+            .filter(|segment| !segment.is_empty())
+            .next_back()
+            .map(|segment| segment.to_string())
+    }
+
     /// Extract domain from URL for cookie filtering
     pub fn extract_domain(url: &Url) -> Option<String> {
         url.domain().map(|d| d.to_string())
@@ -88,6 +142,90 @@ impl StringUtils {
         }
     }
 
+    /// Percent-encode everything except RFC 3986 unreserved characters
+    pub fn percent_encode(input: &str) -> String {
+        let mut encoded = String::with_capacity(input.len());
+        for byte in input.bytes() {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        encoded
+    }
+
+    /// Decode `%XX` escapes; invalid escapes and UTF-8 are kept as-is
+    pub fn percent_decode(input: &str) -> String {
+        let bytes = input.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match input
+                .get(i + 1..i + 3)
+                .filter(|_| bytes[i] == b'%')
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 3;
+                }
+                None => {
+                    decoded.push(bytes[i]);
+                    i += 1;
+                }
+            }
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
+
+    /// The candidate closest to `input` by edit distance, if it is close
+    /// enough to be a typo (swapped letters count as one edit)
+    pub fn closest_match<'a>(
+        input: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Option<&'a str> {
+        let input = input.to_lowercase();
+        let limit = (input.chars().count() / 3).max(1);
+        candidates
+            .into_iter()
+            .map(|candidate| (edit_distance(&input, &candidate.to_lowercase()), candidate))
+            .filter(|(distance, _)| *distance <= limit)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+
+    /// `hexdump -C` style lines for `data`, numbered from `offset`
+    pub fn hexdump(data: &[u8], offset: u64) -> String {
+        let mut out = String::new();
+        for (index, line) in data.chunks(16).enumerate() {
+            let mut hex = String::with_capacity(49);
+            for (column, byte) in line.iter().enumerate() {
+                if column == 8 {
+                    hex.push(' ');
+                }
+                hex.push_str(&format!("{:02x} ", byte));
+            }
+            let text: String = line
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            out.push_str(&format!(
+                "{:08x}  {:<49} |{}|\n",
+                offset + (index as u64) * 16,
+                hex,
+                text
+            ));
+        }
+        out
+    }
+
     /// Parse timeout values (supports suffixes like 's', 'm', 'h')
     pub fn parse_timeout(input: &str) -> Result<std::time::Duration> {
         if let Ok(seconds) = input.parse::<u64>() {
@@ -113,6 +251,196 @@ impl StringUtils {
 
         Ok(std::time::Duration::from_secs(number * suffix))
     }
+
+    /// Parse a byte count with an optional binary `k`, `M` or `G` suffix
+    pub fn parse_size(input: &str) -> Result<u64> {
+        let trimmed = input.trim();
+        let (number_part, multiplier) = match trimmed.chars().last() {
+            Some('k' | 'K') => (&trimmed[..trimmed.len() - 1], 1u64 << 10),
+            Some('m' | 'M') => (&trimmed[..trimmed.len() - 1], 1 << 20),
+            Some('g' | 'G') => (&trimmed[..trimmed.len() - 1], 1 << 30),
+            _ => (trimmed, 1),
+        };
+        number_part
+            .parse::<u64>()
+            .ok()
+            .and_then(|number| number.checked_mul(multiplier))
+            .ok_or_else(|| {
+                RurlError::Config(format!(
+                    "Invalid size: '{}'. Use bytes with optional suffix (k/M/G)",
+                    input
+                ))
+            })
+    }
+
+    /// Parse a local port or port range (`PORT` or `FIRST-LAST`)
+    pub fn parse_port_range(input: &str) -> Result<(u16, u16)> {
+        let parse_port = |value: &str| {
+            value
+                .trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|port| *port > 0)
+                .ok_or_else(|| RurlError::Config(format!("Invalid port: '{}'", value)))
+        };
+        let (first, last) = match input.split_once('-') {
+            Some((first, last)) => (parse_port(first)?, parse_port(last)?),
+            None => {
+                let port = parse_port(input)?;
+                (port, port)
+            }
+        };
+        if first > last {
+            return Err(RurlError::Config(format!(
+                "Invalid port range: '{}'. First port must not exceed last port",
+                input
+            )));
+        }
+        Ok((first, last))
+    }
+
+    /// Parse an HTTP-date (IMF-fixdate, RFC 850, or asctime format)
+    pub fn parse_http_date(input: &str) -> Option<SystemTime> {
+        let input = input.trim();
+        let (day, month, year, time) = if let Some((_, rest)) = input.split_once(", ") {
+            let parts: Vec<&str> = rest.split_whitespace().collect();
+            match parts.as_slice() {
+                // IMF-fixdate: Sun, 06 Nov 1994 08:49:37 GMT
+                [day, month, year, time, "GMT"] => (*day, *month, year.parse::<i64>().ok()?, *time),
+                // RFC 850: Sunday, 06-Nov-94 08:49:37 GMT
+                [date, time, "GMT"] => {
+                    let mut date_parts = date.split('-');
+                    let day = date_parts.next()?;
+                    let month = date_parts.next()?;
+                    let year = date_parts.next()?.parse::<i64>().ok()?;
+                    let year = if year < 70 {
+                        year + 2000
+                    } else if year < 100 {
+                        year + 1900
+                    } else {
+                        year
+                    };
+                    (day, month, year, *time)
+                }
+                _ => return None,
+            }
+        } else {
+            // asctime: Sun Nov  6 08:49:37 1994
+            let parts: Vec<&str> = input.split_whitespace().collect();
+            match parts.as_slice() {
+                [_, month, day, time, year] => (*day, *month, year.parse::<i64>().ok()?, *time),
+                _ => return None,
+            }
+        };
+
+        let day = day.parse::<i64>().ok()?;
+        let month = match month {
+            "Jan" => 1,
+            "Feb" => 2,
+            "Mar" => 3,
+            "Apr" => 4,
+            "May" => 5,
+            "Jun" => 6,
+            "Jul" => 7,
+            "Aug" => 8,
+            "Sep" => 9,
+            "Oct" => 10,
+            "Nov" => 11,
+            "Dec" => 12,
+            _ => return None,
+        };
+        let mut time_parts = time.split(':').map(|part| part.parse::<i64>().ok());
+        let hour = time_parts.next()??;
+        let minute = time_parts.next()??;
+        let second = time_parts.next()??;
+        if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+
+        let seconds =
+            days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second;
+        let seconds = u64::try_from(seconds).ok()?;
+        Some(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    /// UTC timestamp like `2024-05-01T12:30:00.250Z`
+    pub fn format_rfc3339(time: SystemTime) -> String {
+        let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = elapsed.as_secs() as i64;
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let second_of_day = seconds.rem_euclid(86_400);
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year,
+            month,
+            day,
+            second_of_day / 3600,
+            second_of_day / 60 % 60,
+            second_of_day % 60,
+            elapsed.subsec_millis()
+        )
+    }
+}
+
+/// Days since the Unix epoch for a proleptic Gregorian calendar date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 {
+        year / 400
+    } else {
+        (year - 399) / 400
+    };
+    let year_of_era = year - era * 400;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian calendar date of a day count since the Unix epoch
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and swaps of neighbouring characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
 }
 
 #[cfg(test)]
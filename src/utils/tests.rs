@@ -12,6 +12,39 @@ fn validate_url_adds_scheme() {
     assert_eq!(url.host_str(), Some("example.com"));
 }
 
+#[test]
+fn validate_url_with_scheme_only_fills_missing_scheme() {
+    let url = UrlUtils::validate_url_with_scheme("example.com", "https").expect("valid url");
+    assert_eq!(url.scheme(), "https");
+    let url = UrlUtils::validate_url_with_scheme("http://example.com", "https").expect("valid url");
+    assert_eq!(url.scheme(), "http");
+}
+
+#[test]
+fn url_query_part_follows_curl_syntax() {
+    assert_eq!(UrlUtils::url_query_part("q=a b").expect("part"), "q=a%20b");
+    assert_eq!(UrlUtils::url_query_part("=x/y").expect("part"), "x%2Fy");
+    assert_eq!(
+        UrlUtils::url_query_part("hello!").expect("part"),
+        "hello%21"
+    );
+    assert_eq!(UrlUtils::url_query_part("+a=b c").expect("part"), "a=b c");
+
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("value.txt");
+    fs::write(&path, "1+1").expect("write");
+    let spec = format!("sum@{}", path.display());
+    assert_eq!(UrlUtils::url_query_part(&spec).expect("part"), "sum=1%2B1");
+}
+
+#[test]
+fn append_query_joins_with_ampersand() {
+    let mut url = Url::parse("http://example.com/").expect("url");
+    UrlUtils::append_query(&mut url, "a=1");
+    UrlUtils::append_query(&mut url, "b=2");
+    assert_eq!(url.query(), Some("a=1&b=2"));
+}
+
 #[test]
 fn validate_url_rejects_invalid_input() {
     let err = UrlUtils::validate_url("http://").expect_err("invalid url");
@@ -36,6 +69,17 @@ fn extract_domain_handles_ip_and_hostname() {
     assert_eq!(UrlUtils::extract_domain(&ip), None);
 }
 
+#[test]
+fn remote_file_name_uses_last_segment() {
+    let url = Url::parse("https://example.com/files/archive.tar.gz?x=1").expect("valid url");
+    assert_eq!(
+        UrlUtils::remote_file_name(&url),
+        Some("archive.tar.gz".to_string())
+    );
+    let url = Url::parse("https://example.com/").expect("valid url");
+    assert_eq!(UrlUtils::remote_file_name(&url), None);
+}
+
 #[test]
 fn expand_path_expands_home() {
     let home = dirs::home_dir().expect("home dir");
@@ -74,6 +118,32 @@ fn parse_header_splits_key_value() {
     assert!(matches!(err, RurlError::Config(_)));
 }
 
+#[test]
+fn percent_decode_reverses_percent_encode() {
+    let raw = "p@ss:w/rd ü";
+    assert_eq!(
+        StringUtils::percent_decode(&StringUtils::percent_encode(raw)),
+        raw
+    );
+    assert_eq!(StringUtils::percent_decode("100%"), "100%");
+    assert_eq!(StringUtils::percent_decode("%zz%41"), "%zzA");
+}
+
+#[test]
+fn closest_match_suggests_typos_only() {
+    let options = ["retry", "retry-delay", "referer", "request"];
+    assert_eq!(StringUtils::closest_match("retyr", options), Some("retry"));
+    assert_eq!(
+        StringUtils::closest_match("Retry-Dealy", options),
+        Some("retry-delay")
+    );
+    assert_eq!(StringUtils::closest_match("xyz", options), None);
+    assert_eq!(
+        StringUtils::closest_match("chorme", ["chrome", "chromium", "firefox"]),
+        Some("chrome")
+    );
+}
+
 #[test]
 fn parse_timeout_parses_suffixes() {
     assert_eq!(
@@ -95,3 +165,79 @@ fn parse_timeout_parses_suffixes() {
     let err = StringUtils::parse_timeout("xs").expect_err("invalid number");
     assert!(matches!(err, RurlError::Config(_)));
 }
+
+#[test]
+fn parse_size_accepts_binary_suffixes() {
+    assert_eq!(StringUtils::parse_size("512").expect("bytes"), 512);
+    assert_eq!(StringUtils::parse_size("4k").expect("kib"), 4096);
+    assert_eq!(StringUtils::parse_size("64M").expect("mib"), 64 << 20);
+    assert_eq!(StringUtils::parse_size("1G").expect("gib"), 1 << 30);
+    assert!(StringUtils::parse_size("12x").is_err());
+    assert!(StringUtils::parse_size("M").is_err());
+}
+
+#[test]
+fn parse_http_date_accepts_all_formats() {
+    let expected = std::time::UNIX_EPOCH + std::time::Duration::from_secs(784_111_777);
+    for input in [
+        "Sun, 06 Nov 1994 08:49:37 GMT",
+        "Sunday, 06-Nov-94 08:49:37 GMT",
+        "Sun Nov  6 08:49:37 1994",
+    ] {
+        assert_eq!(
+            StringUtils::parse_http_date(input),
+            Some(expected),
+            "{}",
+            input
+        );
+    }
+    assert_eq!(StringUtils::parse_http_date("not a date"), None);
+    assert_eq!(
+        StringUtils::parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"),
+        None
+    );
+}
+
+#[test]
+fn parse_port_range_accepts_single_and_range() {
+    assert_eq!(
+        StringUtils::parse_port_range("8080").expect("port"),
+        (8080, 8080)
+    );
+    assert_eq!(
+        StringUtils::parse_port_range("50000-50100").expect("range"),
+        (50000, 50100)
+    );
+
+    for input in ["0", "70000", "50100-50000", "a-b"] {
+        let err = StringUtils::parse_port_range(input).expect_err("invalid range");
+        assert!(matches!(err, RurlError::Config(_)));
+    }
+}
+
+#[test]
+fn hexdump_formats_offset_hex_and_text() {
+    let dump = StringUtils::hexdump(b"HTTP/1.1 200 OK\r\nok", 16);
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0],
+        "00000010  48 54 54 50 2f 31 2e 31  20 32 30 30 20 4f 4b 0d  |HTTP/1.1 200 OK.|"
+    );
+    assert!(lines[1].starts_with("00000020  0a 6f 6b "));
+    assert!(lines[1].ends_with("|.ok|"));
+}
+
+#[test]
+fn format_rfc3339_round_trips_http_dates() {
+    let time = StringUtils::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").expect("date");
+    assert_eq!(
+        StringUtils::format_rfc3339(time + std::time::Duration::from_millis(250)),
+        "1994-11-06T08:49:37.250Z"
+    );
+    let leap = StringUtils::parse_http_date("Thu, 29 Feb 2024 23:59:59 GMT").expect("date");
+    assert_eq!(
+        StringUtils::format_rfc3339(leap),
+        "2024-02-29T23:59:59.000Z"
+    );
+}
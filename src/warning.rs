@@ -0,0 +1,93 @@
+//! curl-style warnings
+//!
+//! Non-fatal conditions the user should act on, such as ignored options,
+//! cookies that could not be decrypted or an unknown `Content-Encoding`, are
+//! printed as `Warning: ...` on stderr. Like curl, `-s` hides them unless
+//! `-S` is also given. Every warning is also logged at warn level, so
+//! `--log-file` keeps it whatever the console shows.
+
+use crate::logging::LogFormat;
+use serde::Serialize;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Log target of warning records; the console logger skips them
+pub const TARGET: &str = "rurl::warning";
+
+const HIDDEN: u8 = 0;
+const TEXT: u8 = 1;
+const JSON: u8 = 2;
+
+static MODE: AtomicU8 = AtomicU8::new(TEXT);
+
+/// Show or hide warnings on stderr, as JSON lines in `LogFormat::Json`
+pub fn configure(show: bool, format: LogFormat) {
+    let mode = match (show, format) {
+        (false, _) => HIDDEN,
+        (true, LogFormat::Text) => TEXT,
+        (true, LogFormat::Json) => JSON,
+    };
+    MODE.store(mode, Ordering::Relaxed);
+}
+
+/// Report a non-fatal condition to the user
+pub fn warn(message: impl Display) {
+    let message = message.to_string();
+    log::warn!(target: TARGET, "{}", message);
+    match MODE.load(Ordering::Relaxed) {
+        TEXT => eprintln!("{}", text_line(&message)),
+        JSON => eprintln!("{}", json_line(&message, SystemTime::now())),
+        _ => {}
+    }
+}
+
+/// Print a `Warning:` line built from format arguments, like `log::warn!`
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)+) => {
+        $crate::warning::warn(format_args!($($arg)+))
+    };
+}
+
+fn text_line(message: &str) -> String {
+    format!("Warning: {}", message)
+}
+
+#[derive(Serialize)]
+struct WarningEvent<'a> {
+    ts_ms: u64,
+    event: &'static str,
+    message: &'a str,
+}
+
+fn json_line(message: &str, now: SystemTime) -> String {
+    let ts_ms = now
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0);
+    serde_json::to_string(&WarningEvent {
+        ts_ms,
+        event: "warning",
+        message,
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_line, text_line};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn lines_match_curl_and_json_events() {
+        assert_eq!(
+            text_line("--remote-time ignored"),
+            "Warning: --remote-time ignored"
+        );
+        assert_eq!(
+            json_line("bad \"cookie\"", UNIX_EPOCH + Duration::from_millis(1500)),
+            r#"{"ts_ms":1500,"event":"warning","message":"bad \"cookie\""}"#
+        );
+    }
+}
@@ -86,12 +86,8 @@ async fn test_extract_chrome_cookies_from_db() {
     let db_path = dir.path().join("Cookies");
     create_chrome_cookie_db(&db_path);
 
-    let config = BrowserCookieConfig {
-        browser: Browser::Chrome,
-        profile: Some(db_path.to_string_lossy().to_string()),
-        container: None,
-        keyring: None,
-    };
+    let mut config = BrowserCookieConfig::new(Browser::Chrome);
+    config.profile = Some(db_path.to_string_lossy().to_string());
     let extractor = BrowserCookieExtractor::new(config);
     let store = extractor.extract_cookies().await.expect("extract cookies");
 
@@ -107,12 +103,8 @@ async fn test_extract_firefox_cookies_from_db() {
     let db_path = dir.path().join("cookies.sqlite");
     create_firefox_cookie_db(&db_path);
 
-    let config = BrowserCookieConfig {
-        browser: Browser::Firefox,
-        profile: Some(db_path.to_string_lossy().to_string()),
-        container: None,
-        keyring: None,
-    };
+    let mut config = BrowserCookieConfig::new(Browser::Firefox);
+    config.profile = Some(db_path.to_string_lossy().to_string());
     let extractor = BrowserCookieExtractor::new(config);
     let store = extractor.extract_cookies().await.expect("extract cookies");
 
@@ -123,12 +115,7 @@ async fn test_extract_firefox_cookies_from_db() {
 #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
 #[tokio::test]
 async fn test_browser_cookie_extraction_unsupported() {
-    let config = BrowserCookieConfig {
-        browser: Browser::Chrome,
-        profile: None,
-        container: None,
-        keyring: None,
-    };
+    let config = BrowserCookieConfig::new(Browser::Chrome);
     let extractor = BrowserCookieExtractor::new(config);
     let err = extractor.extract_cookies().await.expect_err("unsupported");
     assert!(matches!(err, RurlError::Unsupported(_)));
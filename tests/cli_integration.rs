@@ -1,6 +1,6 @@
 use assert_cmd::cargo::cargo_bin_cmd;
 use tempfile::tempdir;
-use wiremock::matchers::{header, method, path};
+use wiremock::matchers::{body_string, header, header_exists, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[test]
@@ -79,176 +79,1292 @@ async fn test_cli_include_headers() {
 
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
-async fn test_cli_writes_output_file() {
+async fn test_cli_raw_keeps_wire_bytes() {
     if !can_bind_localhost() {
         return;
     }
 
     let server = MockServer::start().await;
     Mock::given(method("GET"))
-        .and(path("/file"))
-        .respond_with(ResponseTemplate::new(200).set_body_string("file-body"))
+        .and(path("/raw"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-encoding", "gzip")
+                .set_body_raw(b"caf\xe9".to_vec(), "text/plain; charset=iso-8859-1"),
+        )
         .mount(&server)
         .await;
 
-    let dir = tempdir().expect("tempdir");
-    let output_path = dir.path().join("out.txt");
-    let url = format!("{}/file", server.uri());
+    let url = format!("{}/raw", server.uri());
     let output = cargo_bin_cmd!("rurl")
         .arg(&url)
-        .arg("-o")
-        .arg(&output_path)
+        .arg("--raw")
         .arg("--no-progress-meter")
         .output()
         .expect("run rurl");
 
     assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.is_empty());
-    let written = std::fs::read_to_string(output_path).expect("read output file");
-    assert_eq!(written, "file-body");
+    assert_eq!(output.stdout, b"caf\xe9");
 }
 
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
-async fn test_cli_sets_user_agent() {
+async fn test_cli_streams_stdin_upload() {
     if !can_bind_localhost() {
         return;
     }
 
     let server = MockServer::start().await;
-    Mock::given(method("GET"))
-        .and(path("/ua"))
-        .and(header("user-agent", "rurl-test-agent"))
-        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+    Mock::given(method("PUT"))
+        .and(path("/upload"))
+        .and(body_string("streamed payload"))
+        .respond_with(ResponseTemplate::new(201))
         .mount(&server)
         .await;
 
-    let url = format!("{}/ua", server.uri());
+    let url = format!("{}/upload", server.uri());
     let output = cargo_bin_cmd!("rurl")
         .arg(&url)
-        .arg("-A")
-        .arg("rurl-test-agent")
+        .arg("-T")
+        .arg("-")
         .arg("--no-progress-meter")
+        .write_stdin("streamed payload")
         .output()
         .expect("run rurl");
 
     assert!(output.status.success());
     let requests = server.received_requests().await.expect("requests");
     assert_eq!(requests.len(), 1);
+    assert!(requests[0].headers.get("content-length").is_none());
 }
 
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
-async fn test_cli_basic_auth_header() {
+async fn test_cli_upload_resume_sends_remaining_bytes() {
     if !can_bind_localhost() {
         return;
     }
 
     let server = MockServer::start().await;
-    Mock::given(method("GET"))
-        .and(path("/auth"))
-        .and(header("authorization", "Basic dXNlcjpwYXNz"))
-        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+    Mock::given(method("HEAD"))
+        .and(path("/resumable"))
+        .respond_with(ResponseTemplate::new(200).insert_header("upload-offset", "4"))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/resumable"))
+        .and(header("content-range", "bytes 4-9/10"))
+        .and(body_string("456789"))
+        .respond_with(ResponseTemplate::new(204))
         .mount(&server)
         .await;
 
-    let url = format!("{}/auth", server.uri());
+    let dir = tempdir().expect("tempdir");
+    let file = dir.path().join("payload.bin");
+    std::fs::write(&file, "0123456789").expect("write");
+    let url = format!("{}/resumable", server.uri());
     let output = cargo_bin_cmd!("rurl")
         .arg(&url)
-        .arg("-u")
-        .arg("user:pass")
+        .arg("-T")
+        .arg(&file)
+        .arg("--upload-resume")
         .arg("--no-progress-meter")
         .output()
         .expect("run rurl");
 
     assert!(output.status.success());
     let requests = server.received_requests().await.expect("requests");
-    assert_eq!(requests.len(), 1);
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].headers.get("content-length").unwrap(), "6");
 }
 
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
-async fn test_cli_explicit_method() {
+async fn test_cli_batch_writes_results_in_order() {
     if !can_bind_localhost() {
         return;
     }
 
     let server = MockServer::start().await;
-    Mock::given(method("PUT"))
-        .and(path("/method"))
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("first")
+                .set_delay(std::time::Duration::from_millis(200)),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .and(body_string("{\"n\":1}"))
+        .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+        .mount(&server)
+        .await;
+
+    let requests = format!(
+        "{{\"url\":\"{0}/slow\",\"id\":\"a\"}}\n{{\"url\":\"{0}/items\",\"body\":\"{{\\\"n\\\":1}}\"}}\n",
+        server.uri()
+    );
+    let output = cargo_bin_cmd!("rurl")
+        .arg("batch")
+        .arg("-")
+        .write_stdin(requests)
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let results: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json line"))
+        .collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["id"], "a");
+    assert_eq!(results[0]["body"], "first");
+    assert_eq!(results[1]["line"], 2);
+    assert_eq!(results[1]["status"], 201);
+    assert_eq!(results[1]["body"], "created");
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_batch_skips_failing_host() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/down"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let requests = format!("{{\"url\":\"{}/down\"}}\n", server.uri()).repeat(4);
+    let output = cargo_bin_cmd!("rurl")
+        .args(["batch", "-", "-P", "1", "--fail-fast-threshold", "2"])
+        .write_stdin(requests)
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let results: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json line"))
+        .collect();
+    assert_eq!(results[1]["status"], 503);
+    assert_eq!(results[2]["skipped"], true);
+    assert_eq!(results[3]["skipped"], true);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("0 succeeded, 2 failed, 2 skipped"));
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 2);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_json_log_events() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/events"))
         .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
         .mount(&server)
         .await;
 
-    let url = format!("{}/method", server.uri());
+    let url = format!("{}/events", server.uri());
     let output = cargo_bin_cmd!("rurl")
         .arg(&url)
-        .arg("-X")
-        .arg("PUT")
+        .arg("--log-format")
+        .arg("json")
         .arg("--no-progress-meter")
         .output()
         .expect("run rurl");
 
     assert!(output.status.success());
-    let requests = server.received_requests().await.expect("requests");
-    assert_eq!(requests.len(), 1);
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json line"))
+        .collect();
+    let names: Vec<&str> = events
+        .iter()
+        .filter_map(|event| event["event"].as_str())
+        .filter(|name| *name != "log")
+        .collect();
+    assert_eq!(
+        names,
+        ["request_start", "connected", "response", "body_received"]
+    );
+    let response = events
+        .iter()
+        .find(|event| event["event"] == "response")
+        .expect("response event");
+    assert_eq!(response["status"], 200);
+    assert_eq!(events.last().expect("event")["bytes"], 2);
 }
 
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
-async fn test_cli_max_redirs_zero_returns_error() {
+async fn test_cli_stats_summary() {
     if !can_bind_localhost() {
         return;
     }
 
     let server = MockServer::start().await;
     Mock::given(method("GET"))
-        .and(path("/redir"))
-        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/next"))
+        .and(path("/old"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/new"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/new"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("moved here"))
         .mount(&server)
         .await;
 
-    let url = format!("{}/redir", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/old", server.uri()))
+        .args(["-L", "--stats", "--no-progress-meter"])
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let summary = stderr
+        .lines()
+        .find(|line| line.starts_with("* Stats:"))
+        .expect("stats line");
+    assert!(summary.contains("requests=2"));
+    assert!(summary.contains("bytes_received=10"));
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_dry_run_prints_request_without_sending() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/items", server.uri()))
+        .args(["--dry-run", "--url-query", "page=2", "-d", "name=x"])
+        .args(["-H", "Authorization: Bearer secret-token"])
+        .args(["-H", "Cookie: session=abc"])
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(&format!("POST {}/items?page=2\n", server.uri())));
+    assert!(stdout.contains("authorization: Bearer <redacted>\n"));
+    assert!(stdout.contains("cookie: session=<redacted>\n"));
+    assert!(stdout.ends_with("\nname=x\n"));
+    assert!(!stdout.contains("secret-token"));
+    assert!(server
+        .received_requests()
+        .await
+        .expect("requests")
+        .is_empty());
+}
+
+#[cfg(unix)]
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_edit_sends_the_saved_body() {
+    use std::os::unix::fs::PermissionsExt;
+
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .and(body_string(r#"{"name":"edited"}"#))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+
+    // Stands in for an editor: checks the pre-filled body and replaces it
+    let dir = tempdir().expect("tempdir");
+    let editor = dir.path().join("editor.sh");
+    std::fs::write(
+        &editor,
+        "#!/bin/sh\ngrep -q draft \"$1\" && printf '{\"name\":\"edited\"}\\n' > \"$1\"\n",
+    )
+    .expect("write editor");
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/items", server.uri()))
+        .args(["--edit", "-d", r#"{"name":"draft"}"#, "--no-progress-meter"])
+        .env("VISUAL", &editor)
+        .output()
+        .expect("run rurl");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(server.received_requests().await.expect("requests").len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_extract_prints_export_lines() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"access_token":"abc.def","expires_in":3600,"scope":"read write"}"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/token", server.uri()))
+        .args(["-d", "grant_type=client_credentials", "--no-progress-meter"])
+        .args([
+            "--extract",
+            "token=.access_token",
+            "--extract",
+            "scope=.scope",
+        ])
+        .arg("--export-env")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "export TOKEN=abc.def\nexport SCOPE='read write'\n"
+    );
+
+    let dir = tempdir().expect("tempdir");
+    let env_file = dir.path().join("auth.env");
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/token", server.uri()))
+        .args([
+            "-d",
+            "x",
+            "--no-progress-meter",
+            "--extract",
+            "ttl=.expires_in",
+        ])
+        .arg(format!("--export-env={}", env_file.display()))
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        std::fs::read_to_string(&env_file).expect("env file"),
+        "TTL=3600\n"
+    );
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_writes_output_file() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/file"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("file-body"))
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("out.txt");
+    let url = format!("{}/file", server.uri());
     let output = cargo_bin_cmd!("rurl")
         .arg(&url)
-        .arg("-L")
-        .arg("--max-redirs=0")
+        .arg("-o")
+        .arg(&output_path)
         .arg("--no-progress-meter")
         .output()
         .expect("run rurl");
 
-    assert_eq!(output.status.code(), Some(47));
-    let requests = server.received_requests().await.expect("requests");
-    assert_eq!(requests.len(), 1);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.is_empty());
+    let written = std::fs::read_to_string(output_path).expect("read output file");
+    assert_eq!(written, "file-body");
 }
 
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
-async fn test_cli_retries_on_http_error() {
+async fn test_cli_resumes_part_file() {
     if !can_bind_localhost() {
         return;
     }
 
     let server = MockServer::start().await;
     Mock::given(method("GET"))
-        .and(path("/retry"))
-        .respond_with(ResponseTemplate::new(503).set_body_string("retry"))
+        .and(path("/resume"))
+        .and(header("range", "bytes=5-"))
+        .and(header("accept-encoding", "identity"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("Content-Range", "bytes 5-8/9")
+                .set_body_string("body"),
+        )
         .mount(&server)
         .await;
 
-    let url = format!("{}/retry", server.uri());
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("out.txt");
+    std::fs::write(dir.path().join("out.txt.part"), "file-").expect("write part");
+    let url = format!("{}/resume", server.uri());
     let output = cargo_bin_cmd!("rurl")
         .arg(&url)
-        .arg("--retry")
-        .arg("1")
-        .arg("--retry-delay")
-        .arg("0")
+        .arg("-o")
+        .arg(&output_path)
         .arg("--no-progress-meter")
         .output()
         .expect("run rurl");
 
     assert!(output.status.success());
-    let requests = server.received_requests().await.expect("requests");
-    assert_eq!(requests.len(), 2);
+    let written = std::fs::read_to_string(&output_path).expect("read output file");
+    assert_eq!(written, "file-body");
+    assert!(!dir.path().join("out.txt.part").exists());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_restarts_part_file_longer_than_resource() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/resume"))
+        .and(header("range", "bytes=12-"))
+        .respond_with(ResponseTemplate::new(416).insert_header("Content-Range", "bytes */9"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/resume"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("file-body"))
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("out.txt");
+    std::fs::write(dir.path().join("out.txt.part"), "stale-output").expect("write part");
+    let url = format!("{}/resume", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("-o")
+        .arg(&output_path)
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let written = std::fs::read_to_string(&output_path).expect("read output file");
+    assert_eq!(written, "file-body");
+    assert!(!dir.path().join("out.txt.part").exists());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_include_headers_restarts_part_file() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/resume"))
+        .and(header_exists("range"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("Content-Range", "bytes 17-20/21")
+                .set_body_string("body"),
+        )
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/resume"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("file-body"))
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("out.txt");
+    std::fs::write(dir.path().join("out.txt.part"), "HTTP/1.1 200 OK\r\n").expect("write part");
+    let url = format!("{}/resume", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("-i")
+        .arg("-o")
+        .arg(&output_path)
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let written = std::fs::read_to_string(&output_path).expect("read output file");
+    assert!(written.starts_with("HTTP/1.1 200 OK"), "{}", written);
+    assert_eq!(written.matches("HTTP/1.1").count(), 1, "{}", written);
+    assert!(written.ends_with("file-body"), "{}", written);
+    assert!(!dir.path().join("out.txt.part").exists());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_sets_user_agent() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ua"))
+        .and(header("user-agent", "rurl-test-agent"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/ua", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("-A")
+        .arg("rurl-test-agent")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_basic_auth_header() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/auth"))
+        .and(header("authorization", "Basic dXNlcjpwYXNz"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/auth", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("-u")
+        .arg("user:pass")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_explicit_method() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/method"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/method", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("-X")
+        .arg("PUT")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_max_redirs_zero_returns_error() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/redir"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/next"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/redir", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("-L")
+        .arg("--max-redirs=0")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert_eq!(output.status.code(), Some(47));
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_retries_on_http_error() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/retry"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("retry"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/retry", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--retry")
+        .arg("1")
+        .arg("--retry-delay")
+        .arg("0")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 2);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_max_time_spans_retries() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(ResponseTemplate::new(503).set_delay(std::time::Duration::from_millis(600)))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/slow", server.uri());
+    let started = std::time::Instant::now();
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--retry")
+        .arg("5")
+        .arg("--retry-delay")
+        .arg("0")
+        .arg("--max-time")
+        .arg("1")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert_eq!(output.status.code(), Some(28));
+    assert!(started.elapsed() < std::time::Duration::from_secs(3));
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 2);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_fail_returns_22_without_body() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("not here"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/missing", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--fail")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert_eq!(output.status.code(), Some(22));
+    assert!(output.stdout.is_empty());
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn test_cli_empty_reply_returns_52() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let server = std::thread::spawn(move || {
+        use std::io::Read;
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf);
+    });
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("http://{}/", addr))
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    server.join().expect("server thread");
+
+    assert_eq!(output.status.code(), Some(52));
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_diff_reports_differences() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/staging"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-env", "staging")
+                .set_body_raw(r#"{"b":1,"a":2}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/prod"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-env", "prod")
+                .set_body_raw(r#"{"a":2,"b":1}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let staging = format!("{}/staging", server.uri());
+    let prod = format!("{}/prod", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .args(["diff", "--json", "--ignore-header", "date", &staging, &prod])
+        .output()
+        .expect("run rurl");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("-x-env: staging\n+x-env: prod\n"));
+
+    let output = cargo_bin_cmd!("rurl")
+        .args(["diff", "--json", "--body-only", &staging, &prod])
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_output_template_names_file_after_response() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/reports/daily.json"))
+        .respond_with(ResponseTemplate::new(201).set_body_string("{}"))
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("tempdir");
+    let output = cargo_bin_cmd!("rurl")
+        .current_dir(dir.path())
+        .arg(format!("{}/reports/daily.json", server.uri()))
+        .arg("-o")
+        .arg("{host}_{path_basename}_{status}")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success());
+    let written = dir.path().join("127.0.0.1_daily.json_201");
+    assert_eq!(std::fs::read_to_string(written).expect("read"), "{}");
+}
+
+#[cfg(unix)]
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_pre_and_post_hooks_wrap_the_transfer() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/signed"))
+        .and(header("x-signature", "GET-signed"))
+        .respond_with(ResponseTemplate::new(201).set_body_string("stored"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("tempdir");
+    let body = dir.path().join("body.txt");
+    let report = dir.path().join("report.txt");
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/signed", server.uri()))
+        .arg("--pre-hook")
+        .arg("echo \"X-Signature: $RURL_METHOD-${RURL_URL##*/}\"")
+        .arg("--post-hook")
+        .arg(format!(
+            "printf '%s %s' \"$RURL_STATUS\" \"$(cat \"$RURL_BODY_FILE\")\" > '{}'",
+            report.display()
+        ))
+        .arg("-o")
+        .arg(&body)
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        std::fs::read_to_string(&report).expect("report"),
+        "201 stored"
+    );
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_print_filename_saves_body_to_a_temp_file() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/report"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("%PDF-1.4", "application/pdf"))
+        .mount(&server)
+        .await;
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/report", server.uri()))
+        .arg("--print-filename")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let saved = std::path::PathBuf::from(stdout.trim_end());
+    assert_eq!(saved.extension().and_then(|e| e.to_str()), Some("pdf"));
+    assert_eq!(std::fs::read(&saved).expect("saved body"), b"%PDF-1.4");
+    std::fs::remove_file(saved).expect("remove");
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_summary_line_and_budget_warning() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/sized"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(1500)))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/sized", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--summary-budget")
+        .arg("size=1k")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let summary = stderr
+        .lines()
+        .find(|line| line.starts_with("200 OK  "))
+        .expect("summary line");
+    assert!(summary.contains("  1.5 kB  "), "{}", summary);
+    assert!(summary.ends_with(&format!("  {}", url)), "{}", summary);
+    assert!(stderr.contains("Warning: Response body of 1.5 kB is over the 1.0 kB budget"));
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_show_redirect_chain_prints_each_hop() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/old"))
+        .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/new"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("moved"))
+        .mount(&server)
+        .await;
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/old", server.uri()))
+        .arg("-L")
+        .arg("--show-redirect-chain")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "moved");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!(
+        "1  301 Moved Permanently  {0}/old -> {0}/new\n2  200 OK  {0}/new\n",
+        server.uri()
+    )));
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_tee_and_repeated_output_write_every_copy() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/tee"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("tee-body"))
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("tempdir");
+    let url = format!("{}/tee", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--tee")
+        .arg(dir.path().join("stdout-copy.txt"))
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "tee-body");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("stdout-copy.txt")).expect("read copy"),
+        "tee-body"
+    );
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("-o")
+        .arg(dir.path().join("a.txt"))
+        .arg("-o")
+        .arg(dir.path().join("b.txt"))
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stdout.is_empty());
+    for name in ["a.txt", "b.txt"] {
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(name)).expect("read output file"),
+            "tee-body"
+        );
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_grep_filters_lines_and_exits_one_without_a_match() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/log"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("INFO start\nWARN slow\nERROR disk full\nINFO done\n"),
+        )
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/log", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--grep")
+        .arg("^ERROR")
+        .arg("--grep-context")
+        .arg("1")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "WARN slow\nERROR disk full\nINFO done\n"
+    );
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--grep")
+        .arg("FATAL")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_template_prints_a_report_instead_of_the_body() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Total", "3")
+                .set_body_raw(r#"{"items":["a","b","c"]}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/items", server.uri()))
+        .arg("--template")
+        .arg(r#"{{ .status }}: {{ .json.items | length }} of {{ .headers.x-total }} ({{ .json.items | join "," }})"#)
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "200: 3 of 3 (a,b,c)\n"
+    );
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_list_and_download_links() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"<a href="/files/a.txt">a</a> <img src="files/b.png"> <a href="/about#team">about</a>"#,
+            "text/html",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/files/a.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("a-body"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/files/b.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("b-body", "image/png"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/page", server.uri());
+    let output = cargo_bin_cmd!("rurl")
+        .arg(&url)
+        .arg("--list-links")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{0}/files/a.txt\n{0}/files/b.png\n{0}/about\n",
+            server.uri()
+        )
+    );
+
+    let dir = tempdir().expect("tempdir");
+    let output = cargo_bin_cmd!("rurl")
+        .current_dir(dir.path())
+        .arg(&url)
+        .arg("--download-links")
+        .arg("/files/")
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a.txt\nb.png\n");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("a.txt")).expect("a.txt"),
+        "a-body"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("b.png")).expect("b.png"),
+        "b-body"
+    );
+    assert!(!dir.path().join("about").exists());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_repeated_data_posts_a_urlencoded_form() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/form"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(body_string("name=rurl&lang=rust"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let output = cargo_bin_cmd!("rurl")
+        .arg(format!("{}/form", server.uri()))
+        .args(["-d", "name=rurl", "-d", "lang=rust"])
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok");
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cli_recursive_mirrors_pages_to_the_depth_limit() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let pages = [
+        (
+            "/docs/",
+            r#"<a href="guide.html">guide</a> <link href="/docs/style.css"> <a href="https://example.org/">out</a>"#,
+        ),
+        (
+            "/docs/guide.html",
+            r#"<a href="/docs/">up</a> <a href="deep.html">deep</a>"#,
+        ),
+        ("/docs/deep.html", r#"<a href="deeper.html">deeper</a>"#),
+    ];
+    for (page, html) in pages {
+        Mock::given(method("GET"))
+            .and(path(page))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(html, "text/html"))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/docs/style.css"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("body {}", "text/css"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/docs/deeper.html"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().expect("tempdir");
+    let output = cargo_bin_cmd!("rurl")
+        .current_dir(dir.path())
+        .arg(format!("{}/docs/", server.uri()))
+        .args(["--recursive", "--depth", "2", "--wait", "0"])
+        .arg("--no-progress-meter")
+        .output()
+        .expect("run rurl");
+    assert!(output.status.success(), "{:?}", output);
+    let host = server.uri().replace("http://", "").replace(':', "_");
+    let saved: Vec<String> = ["index.html", "guide.html", "style.css", "deep.html"]
+        .iter()
+        .map(|name| {
+            std::path::Path::new(&host)
+                .join("docs")
+                .join(name)
+                .display()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{}\n", saved.join("\n"))
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join(&saved[2])).expect("style.css"),
+        "body {}"
+    );
+
+    let manifest =
+        std::fs::read_to_string(dir.path().join("rurl-manifest.json")).expect("manifest");
+    let entries: Vec<serde_json::Value> = manifest
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json"))
+        .collect();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[0]["url"], format!("{}/docs/", server.uri()));
+    assert_eq!(entries[0]["status"], 200);
+    assert_eq!(entries[3]["depth"], 2);
+    assert_eq!(entries[2]["bytes"], 7);
 }
@@ -1,6 +1,9 @@
 use rurl::config::Config;
+use rurl::credentials::StoredCredential;
+use rurl::http::oauth2::{self, OAuth2Client};
 use rurl::http::HttpClient;
-use wiremock::matchers::{header, method, path};
+use url::Url;
+use wiremock::matchers::{body_string_contains, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn can_bind_localhost() -> bool {
@@ -22,12 +25,10 @@ async fn test_basic_auth_header_sent() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/auth", server.uri()),
-        auth_username: Some("user".to_string()),
-        auth_password: Some("pass".to_string()),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/auth", server.uri());
+    config.auth_username = Some("user".to_string());
+    config.auth_password = Some("pass".to_string());
 
     let client = HttpClient::new(config).expect("client should build");
     let response = client.execute().await.expect("request should succeed");
@@ -36,3 +37,122 @@ async fn test_basic_auth_header_sent() {
     let requests = server.received_requests().await.expect("requests");
     assert_eq!(requests.len(), 1);
 }
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_oauth2_device_flow_polls_until_authorized() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/device"))
+        .and(body_string_contains("client_id=cli"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "device_code": "dev-1",
+            "user_code": "ABCD-EFGH",
+            "verification_uri": "https://example.com/activate",
+            "expires_in": 60,
+            "interval": 0
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .set_body_json(serde_json::json!({"error": "authorization_pending"})),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("device_code=dev-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "at-1",
+            "refresh_token": "rt-1",
+            "expires_in": 3600
+        })))
+        .mount(&server)
+        .await;
+
+    let client = OAuth2Client {
+        token_url: Url::parse(&format!("{}/token", server.uri())).expect("url"),
+        client_id: "cli".to_string(),
+        client_secret: None,
+        scope: Some("read".to_string()),
+    };
+    let device_url = Url::parse(&format!("{}/device", server.uri())).expect("url");
+    let credential = client
+        .device_flow(&reqwest::Client::new(), &device_url)
+        .await
+        .expect("sign-in should succeed");
+    match credential {
+        StoredCredential::OAuth2 {
+            access_token,
+            refresh_token,
+            expires_at,
+            ..
+        } => {
+            assert_eq!(access_token, "at-1");
+            assert_eq!(refresh_token.as_deref(), Some("rt-1"));
+            assert!(expires_at.is_some());
+        }
+        other => panic!("unexpected credential {:?}", other),
+    }
+
+    let token_requests = server
+        .received_requests()
+        .await
+        .expect("requests")
+        .into_iter()
+        .filter(|request| request.url.path() == "/token")
+        .count();
+    assert_eq!(token_requests, 2);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_oauth2_refresh_keeps_refresh_token_when_not_rotated() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("grant_type=refresh_token"))
+        .and(body_string_contains("refresh_token=rt-1"))
+        .and(body_string_contains("client_secret=s3cret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "at-2",
+            "expires_in": 3600
+        })))
+        .mount(&server)
+        .await;
+
+    let expired = StoredCredential::OAuth2 {
+        token_url: format!("{}/token", server.uri()),
+        client_id: "cli".to_string(),
+        client_secret: Some("s3cret".to_string()),
+        access_token: "at-1".to_string(),
+        refresh_token: Some("rt-1".to_string()),
+        expires_at: Some(0),
+    };
+    let refreshed = oauth2::refresh(&reqwest::Client::new(), &expired)
+        .await
+        .expect("refresh should succeed");
+    match refreshed {
+        StoredCredential::OAuth2 {
+            access_token,
+            refresh_token,
+            ..
+        } => {
+            assert_eq!(access_token, "at-2");
+            assert_eq!(refresh_token.as_deref(), Some("rt-1"));
+        }
+        other => panic!("unexpected credential {:?}", other),
+    }
+}
@@ -22,12 +22,10 @@ async fn test_post_body_sent() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/body", server.uri()),
-        method: HttpMethod::Post,
-        data: Some("payload".to_string()),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/body", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("payload".to_string());
 
     let client = HttpClient::new(config).expect("client should build");
     let response = client.execute().await.expect("request should succeed");
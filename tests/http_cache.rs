@@ -0,0 +1,105 @@
+use rurl::config::{CacheMode, Config};
+use rurl::http::HttpClient;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn can_bind_localhost() -> bool {
+    std::net::TcpListener::bind("127.0.0.1:0").is_ok()
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_fresh_response_served_from_cache() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/cached"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("cache-control", "max-age=60")
+                .set_body_string("cached body"),
+        )
+        .mount(&server)
+        .await;
+
+    let cache_dir = tempfile::tempdir().expect("tempdir");
+    let mut config = Config::default();
+    config.url = format!("{}/cached", server.uri());
+    config.cache_dir = Some(cache_dir.path().to_path_buf());
+
+    for _ in 0..2 {
+        let client = HttpClient::new(config.clone()).expect("client should build");
+        let response = client.execute().await.expect("request should succeed");
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.expect("body"), "cached body");
+    }
+
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_stale_response_revalidated_with_etag() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/etag"))
+        .and(header("if-none-match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304).insert_header("etag", "\"v1\""))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/etag"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("cache-control", "no-cache")
+                .insert_header("etag", "\"v1\"")
+                .set_body_string("original"),
+        )
+        .mount(&server)
+        .await;
+
+    let cache_dir = tempfile::tempdir().expect("tempdir");
+    let mut config = Config::default();
+    config.url = format!("{}/etag", server.uri());
+    config.cache_dir = Some(cache_dir.path().to_path_buf());
+
+    for _ in 0..2 {
+        let client = HttpClient::new(config.clone()).expect("client should build");
+        let response = client.execute().await.expect("request should succeed");
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.expect("body"), "original");
+    }
+
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 2);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_only_if_cached_miss_returns_504() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let cache_dir = tempfile::tempdir().expect("tempdir");
+    let mut config = Config::default();
+    config.url = format!("{}/missing", server.uri());
+    config.cache_dir = Some(cache_dir.path().to_path_buf());
+    config.cache_mode = CacheMode::OnlyIfCached;
+
+    let client = HttpClient::new(config).expect("client should build");
+    let response = client.execute().await.expect("request should succeed");
+    assert_eq!(response.status(), 504);
+
+    let requests = server.received_requests().await.expect("requests");
+    assert!(requests.is_empty());
+}
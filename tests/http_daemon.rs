@@ -0,0 +1,148 @@
+#![cfg(unix)]
+
+use rurl::config::Config;
+use rurl::daemon;
+use rurl::error::RurlError;
+use rurl::http::HttpClient;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::tempdir;
+use tokio::task::JoinHandle;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn can_bind_localhost() -> bool {
+    std::net::TcpListener::bind("127.0.0.1:0").is_ok()
+}
+
+/// Run an agent on a socket in `dir` until the handle is aborted
+async fn start_agent(dir: &Path) -> (PathBuf, JoinHandle<()>) {
+    let socket = dir.join("agent.sock");
+    let agent = tokio::spawn({
+        let socket = socket.clone();
+        async move {
+            daemon::serve(&socket, daemon::DEFAULT_COOKIE_TTL)
+                .await
+                .expect("agent");
+        }
+    });
+    for _ in 0..200 {
+        if socket.exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    (socket, agent)
+}
+
+/// The mock server's URL with `localhost` for its address, so only the
+/// resolved address can be refused
+fn localhost_url(server: &MockServer) -> String {
+    format!("{}/", server.uri().replace("127.0.0.1", "localhost"))
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_agent_refuses_a_socket_directory_others_can_reach() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("tempdir");
+    std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let socket = dir.path().join("agent.sock");
+    let result = daemon::serve(&socket, daemon::DEFAULT_COOKIE_TTL).await;
+    assert!(matches!(result, Err(RurlError::Config(_))), "{:?}", result);
+    assert!(!socket.exists());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_internal_targets_are_refused_with_an_agent_running() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let dir = tempdir().expect("tempdir");
+    let (socket, agent) = start_agent(dir.path()).await;
+
+    let mut config = Config::default();
+    config.url = localhost_url(&server);
+    config.daemon_socket = Some(socket);
+    config.host_filter.block_internal();
+    let result = HttpClient::new(config).expect("client").execute().await;
+    agent.abort();
+    assert!(
+        matches!(result, Err(RurlError::InternalTarget(_))),
+        "{:?}",
+        result.map(|response| response.status())
+    );
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_deny_host_networks_are_refused_with_an_agent_running() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let dir = tempdir().expect("tempdir");
+    let (socket, agent) = start_agent(dir.path()).await;
+
+    let mut config = Config::default();
+    config.url = localhost_url(&server);
+    config.daemon_socket = Some(socket);
+    config.host_filter.deny("127.0.0.0/8").expect("pattern");
+    config.host_filter.deny("::1").expect("pattern");
+    let result = HttpClient::new(config).expect("client").execute().await;
+    agent.abort();
+    assert!(
+        matches!(result, Err(RurlError::HostBlocked(_))),
+        "{:?}",
+        result.map(|response| response.status())
+    );
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_header_limits_apply_to_responses_from_the_agent() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-one", "1")
+                .insert_header("x-two", "2")
+                .insert_header("x-three", "3"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let dir = tempdir().expect("tempdir");
+    let (socket, agent) = start_agent(dir.path()).await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/", server.uri());
+    config.daemon_socket = Some(socket);
+    config.max_headers = 2;
+    let result = HttpClient::new(config).expect("client").execute().await;
+    agent.abort();
+    assert!(
+        matches!(result, Err(RurlError::HeadersTooLarge(_))),
+        "{:?}",
+        result.map(|response| response.status())
+    );
+}
@@ -1,6 +1,9 @@
-use rurl::config::Config;
+use rurl::config::{Config, VerbosityLevel};
+use rurl::error::RurlError;
 use rurl::http::HttpClient;
 use rurl::VERSION;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use wiremock::matchers::{header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -23,13 +26,11 @@ async fn test_custom_header_sent() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/headers", server.uri()),
-        headers: [("X-Test-Header".to_string(), "rurl".to_string())]
-            .into_iter()
-            .collect(),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/headers", server.uri());
+    config.headers = [("X-Test-Header".to_string(), "rurl".to_string())]
+        .into_iter()
+        .collect();
 
     let client = HttpClient::new(config).expect("client should build");
     let response = client.execute().await.expect("request should succeed");
@@ -39,6 +40,226 @@ async fn test_custom_header_sent() {
     assert_eq!(requests.len(), 1);
 }
 
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_repeated_headers_are_sent_in_order() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/forwarded"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let config = Config::builder()
+        .url(format!("{}/forwarded", server.uri()))
+        .header("X-Forwarded-For", "10.0.0.1")
+        .header("Cookie", "a=1")
+        .header("X-Forwarded-For", "10.0.0.2")
+        .header("Cookie", "b=2")
+        .build()
+        .expect("config");
+    HttpClient::new(config)
+        .expect("client should build")
+        .execute()
+        .await
+        .expect("request should succeed");
+
+    let requests = server.received_requests().await.expect("requests");
+    let forwarded: Vec<_> = requests[0]
+        .headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .map(|value| value.to_str().expect("ascii"))
+        .collect();
+    assert_eq!(forwarded, ["10.0.0.1", "10.0.0.2"]);
+    let cookies: Vec<_> = requests[0].headers.get_all("cookie").iter().collect();
+    assert_eq!(cookies, ["a=1; b=2"]);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_request_id_is_kept_across_retries() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/traced"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/traced", server.uri());
+    config.request_id_header = Some("X-Correlation-Id".to_string());
+    config.retry_count = 1;
+    config.retry_delay = std::time::Duration::from_millis(0);
+    HttpClient::new(config)
+        .expect("client should build")
+        .execute()
+        .await
+        .expect("request should succeed");
+
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 2);
+    let ids: Vec<_> = requests
+        .iter()
+        .map(|request| request.headers.get("x-correlation-id").expect("request id"))
+        .collect();
+    assert_eq!(ids[0].len(), 36);
+    assert_eq!(ids[0], ids[1]);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_suppressed_and_empty_headers() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/edited"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/edited", server.uri());
+    config.headers.add_arg("User-Agent:").expect("suppress");
+    config.headers.add_arg("X-Empty;").expect("empty");
+    HttpClient::new(config)
+        .expect("client should build")
+        .execute()
+        .await
+        .expect("request should succeed");
+
+    let requests = server.received_requests().await.expect("requests");
+    assert!(requests[0].headers.get("user-agent").is_none());
+    assert_eq!(
+        requests[0]
+            .headers
+            .get("x-empty")
+            .map(|value| value.as_bytes()),
+        Some(&b""[..])
+    );
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_suppressed_accept_is_not_sent() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/no-accept"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("plain"))
+        .mount(&server)
+        .await;
+
+    let config = Config::builder()
+        .url(format!("{}/no-accept?q=1", server.uri()))
+        .without_header("Accept")
+        .without_header("Accept-Encoding")
+        .build()
+        .expect("config");
+    let response = HttpClient::new(config)
+        .expect("client should build")
+        .execute()
+        .await
+        .expect("request should succeed");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.expect("body"), "plain");
+
+    let requests = server.received_requests().await.expect("requests");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.query(), Some("q=1"));
+    assert!(requests[0].headers.get("accept").is_none());
+    assert!(requests[0].headers.get("accept-encoding").is_none());
+    assert!(requests[0].headers.get("host").is_some());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_suppressed_accept_still_decodes_compressed_bodies() {
+    use async_compression::tokio::bufread::GzipEncoder;
+    use tokio::io::AsyncReadExt;
+
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let mut compressed = Vec::new();
+    GzipEncoder::new(&b"decoded body"[..])
+        .read_to_end(&mut compressed)
+        .await
+        .expect("compress");
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/gzip"))
+        .and(header("accept-encoding", "gzip, br, deflate"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .set_body_bytes(compressed),
+        )
+        .mount(&server)
+        .await;
+
+    let config = Config::builder()
+        .url(format!("{}/gzip", server.uri()))
+        .without_header("Accept")
+        .build()
+        .expect("config");
+    let response = HttpClient::new(config)
+        .expect("client should build")
+        .execute()
+        .await
+        .expect("request should succeed");
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("content-encoding").is_none());
+    assert_eq!(response.text().await.expect("body"), "decoded body");
+
+    let requests = server.received_requests().await.expect("requests");
+    assert!(requests[0].headers.get("accept").is_none());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_suppressed_host_is_still_sent() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/host"))
+        .and(header("host", server.address().to_string().as_str()))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config = Config::builder()
+        .url(format!("{}/host", server.uri()))
+        .without_header("Host")
+        .build()
+        .expect("config");
+    let response = HttpClient::new(config)
+        .expect("a suppressed Host only warns")
+        .execute()
+        .await
+        .expect("request should succeed");
+    assert_eq!(response.status(), 200);
+}
+
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
 async fn test_user_agent_default_sent() {
@@ -55,10 +276,8 @@ async fn test_user_agent_default_sent() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/ua", server.uri()),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/ua", server.uri());
 
     let client = HttpClient::new(config).expect("client should build");
     let response = client.execute().await.expect("request should succeed");
@@ -79,13 +298,297 @@ async fn test_verbose_request_headers_path() {
         .mount(&server)
         .await;
 
-    let mut config = Config {
-        url: format!("{}/verbose", server.uri()),
-        ..Config::default()
-    };
-    config.output.verbose = true;
+    let mut config = Config::default();
+    config.url = format!("{}/verbose", server.uri());
+    config.output.verbosity = VerbosityLevel::Headers;
+
+    let client = HttpClient::new(config).expect("client should build");
+    let response = client.execute().await.expect("request should succeed");
+    assert_eq!(response.status(), 200);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_early_hints_recorded_in_chain() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    if !can_bind_localhost() {
+        return;
+    }
+
+    // wiremock cannot send interim responses, so answer by hand
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.expect("accept");
+        let mut buffer = [0u8; 1024];
+        let _ = socket.read(&mut buffer).await.expect("read");
+        socket
+            .write_all(
+                b"HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload\r\n\r\n\
+                  HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok",
+            )
+            .await
+            .expect("write");
+    });
 
+    let mut config = Config::default();
+    config.url = format!("http://{}/hints", addr);
     let client = HttpClient::new(config).expect("client should build");
+    let history = client
+        .execute_with_history()
+        .await
+        .expect("request should succeed");
+    server.await.expect("server");
+
+    assert_eq!(history.chain.len(), 2);
+    assert_eq!(history.chain[0].status, 103);
+    assert_eq!(
+        history.chain[0].headers.get("link").expect("link"),
+        "</style.css>; rel=preload"
+    );
+    assert_eq!(history.chain[1].status, 200);
+    assert_eq!(history.response.text().await.expect("body"), "ok");
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_interceptor_mutates_request_and_sees_response() {
+    use rurl::http::middleware::Interceptor;
+    use std::sync::{Arc, Mutex};
+
+    struct AddToken(Arc<Mutex<Vec<u16>>>);
+
+    impl Interceptor for AddToken {
+        fn on_request(&self, request: &mut reqwest::Request) -> rurl::Result<()> {
+            request.headers_mut().insert(
+                "authorization",
+                reqwest::header::HeaderValue::from_static("Bearer refreshed"),
+            );
+            Ok(())
+        }
+
+        fn on_response(&self, response: &reqwest::Response) -> rurl::Result<()> {
+            self.0
+                .lock()
+                .expect("lock")
+                .push(response.status().as_u16());
+            Ok(())
+        }
+    }
+
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/intercepted"))
+        .and(header("authorization", "Bearer refreshed"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/intercepted", server.uri());
+    let statuses = Arc::new(Mutex::new(Vec::new()));
+    let mut client = HttpClient::new(config).expect("client should build");
+    client.add_interceptor(AddToken(statuses.clone()));
     let response = client.execute().await.expect("request should succeed");
+
     assert_eq!(response.status(), 200);
+    assert_eq!(*statuses.lock().expect("lock"), vec![200]);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_custom_cookie_sources_are_merged() {
+    use rurl::browser::Cookie;
+    use rurl::cookie_source::{CookieSource, NetscapeCookieFile};
+
+    struct Fixed;
+
+    impl CookieSource for Fixed {
+        fn cookies_for_url(&self, _url: &url::Url) -> Vec<Cookie> {
+            vec![Cookie {
+                name: "custom".to_string(),
+                value: "1".to_string(),
+                domain: "127.0.0.1".to_string(),
+                path: "/".to_string(),
+                secure: false,
+                http_only: false,
+                expires: None,
+            }]
+        }
+    }
+
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/cookies"))
+        .and(header("cookie", "custom=1; jar=2"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/cookies", server.uri());
+    let mut client = HttpClient::new(config).expect("client should build");
+    client.add_cookie_source(Box::new(Fixed));
+    client.add_cookie_source(Box::new(NetscapeCookieFile::parse(
+        "127.0.0.1\tFALSE\t/\tFALSE\t0\tjar\t2\n",
+    )));
+    let response = client.execute().await.expect("request should succeed");
+    assert_eq!(response.status(), 200);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_response_header_limits_abort_the_transfer() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let mut template = ResponseTemplate::new(200).set_body_string("ok");
+    for index in 0..8 {
+        template = template.insert_header(format!("x-filler-{}", index).as_str(), "x".repeat(64));
+    }
+    Mock::given(method("GET"))
+        .and(path("/many"))
+        .respond_with(template)
+        .mount(&server)
+        .await;
+
+    let run = |max_headers: usize, max_header_size: u64| {
+        let mut config = Config::default();
+        config.url = format!("{}/many", server.uri());
+        config.max_headers = max_headers;
+        config.max_header_size = max_header_size;
+        async move { HttpClient::new(config).expect("client").execute().await }
+    };
+
+    let err = run(4, 300 * 1024).await.expect_err("too many headers");
+    assert!(matches!(err, RurlError::HeadersTooLarge(_)), "{:?}", err);
+    let err = run(100, 256).await.expect_err("headers too large");
+    assert!(matches!(err, RurlError::HeadersTooLarge(_)), "{:?}", err);
+    assert_eq!(
+        run(100, 300 * 1024).await.expect("within limits").status(),
+        200
+    );
+}
+
+/// Keep-alive server answering every request with 200; returns the number of
+/// connections accepted and the request heads received
+fn spawn_keepalive_server() -> (
+    std::net::SocketAddr,
+    Arc<AtomicUsize>,
+    Arc<Mutex<Vec<String>>>,
+) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let connections = Arc::new(AtomicUsize::new(0));
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let (accepted, seen) = (connections.clone(), requests.clone());
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            let seen = seen.clone();
+            std::thread::spawn(move || {
+                use std::io::{BufRead, BufReader, Write};
+                let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+                let mut stream = stream;
+                loop {
+                    let mut head = String::new();
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).is_ok_and(|n| n > 0) {
+                        head.push_str(&line);
+                        if line == "\r\n" {
+                            break;
+                        }
+                        line.clear();
+                    }
+                    if head.is_empty() {
+                        return;
+                    }
+                    seen.lock().expect("lock").push(head);
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+                }
+            });
+        }
+    });
+    (addr, connections, requests)
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_no_keepalive_opens_a_connection_per_request() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    for (no_keepalive, expected_connections) in [(false, 1), (true, 2)] {
+        let (addr, connections, requests) = spawn_keepalive_server();
+        let mut config = Config::default();
+        config.url = format!("http://{}/", addr);
+        config.no_keepalive = no_keepalive;
+        let client = HttpClient::new(config).expect("client should build");
+        for _ in 0..2 {
+            let response = client.execute().await.expect("request");
+            assert_eq!(response.text().await.expect("body"), "ok");
+        }
+
+        assert_eq!(connections.load(Ordering::SeqCst), expected_connections);
+        let requests = requests.lock().expect("lock");
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|head| head.to_ascii_lowercase().contains("connection: close") == no_keepalive));
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_local_port_skips_ports_in_use() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    // Hold the first port of the range so the connection must use another
+    let held = std::net::TcpListener::bind("0.0.0.0:0").expect("bind");
+    let first = held.local_addr().expect("addr").port();
+    let Some(last) = first.checked_add(20) else {
+        return;
+    };
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let (peer_tx, peer_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        use std::io::{BufRead, BufReader, Write};
+        let (mut stream, peer) = listener.accept().expect("accept");
+        peer_tx.send(peer).expect("send");
+        let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+        let mut line = String::new();
+        while reader.read_line(&mut line).is_ok_and(|n| n > 0) && line != "\r\n" {
+            line.clear();
+        }
+        let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+    });
+
+    let mut config = Config::default();
+    config.url = format!("http://{}/", addr);
+    config.local_port = Some((first, last));
+    let client = HttpClient::new(config).expect("client should build");
+    let response = client.execute().await.expect("request");
+    assert_eq!(response.text().await.expect("body"), "ok");
+
+    let peer = peer_rx.recv().expect("peer");
+    assert!((first + 1..=last).contains(&peer.port()), "{}", peer);
+    drop(held);
 }
@@ -21,11 +21,9 @@ async fn execute_with_method(server: &MockServer, http_method: HttpMethod) {
         .mount(server)
         .await;
 
-    let config = Config {
-        url: format!("{}/resource", server.uri()),
-        method: http_method,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/resource", server.uri());
+    config.method = http_method;
 
     let client = HttpClient::new(config).expect("client should build");
     let response = client.execute().await.expect("request should succeed");
@@ -74,3 +72,67 @@ async fn test_delete_request() {
     let server = MockServer::start().await;
     execute_with_method(&server, HttpMethod::Delete).await;
 }
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_async_entry_points_run_inside_runtime() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/embedded"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/embedded", server.uri());
+    let config = Config::builder().url(&url).build().expect("config");
+    let history = rurl::execute(config).await.expect("execute");
+    assert_eq!(history.response.status(), 200);
+
+    let output = tempfile::tempdir().expect("tempdir");
+    let out_file = output.path().join("body.txt");
+    let matches = rurl::cli::create_app()
+        .try_get_matches_from([
+            "rurl",
+            url.as_str(),
+            "--no-progress-meter",
+            "-o",
+            out_file.to_str().expect("path"),
+        ])
+        .expect("matches");
+    rurl::run_async(&matches).await.expect("run_async");
+    assert_eq!(std::fs::read_to_string(out_file).expect("read"), "ok");
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_cancellation_aborts_in_flight_request() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(10)))
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/slow", server.uri());
+    let client = HttpClient::new(config).expect("client should build");
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let trigger = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        trigger.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let result = client.execute_with_history_cancellable(&cancel).await;
+    assert!(matches!(result, Err(rurl::RurlError::Aborted)));
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
@@ -0,0 +1,203 @@
+use rurl::config::{Config, ProxyConfig};
+use rurl::error::RurlError;
+use rurl::http::HttpClient;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn can_bind_localhost() -> bool {
+    std::net::TcpListener::bind("127.0.0.1:0").is_ok()
+}
+
+/// Minimal SOCKS5 proxy without authentication; records each target it is
+/// asked to connect to
+fn spawn_socks5_server() -> (SocketAddr, Arc<Mutex<Vec<SocketAddr>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let targets = Arc::new(Mutex::new(Vec::new()));
+    let seen = targets.clone();
+    std::thread::spawn(move || {
+        for mut client in listener.incoming().flatten() {
+            let mut greeting = [0u8; 2];
+            client.read_exact(&mut greeting).expect("greeting");
+            let mut methods = vec![0u8; greeting[1] as usize];
+            client.read_exact(&mut methods).expect("methods");
+            client.write_all(&[5, 0]).expect("choose method");
+
+            // Only IPv4 targets: the test proxy address is 127.0.0.1
+            let mut request = [0u8; 10];
+            client.read_exact(&mut request).expect("connect request");
+            assert_eq!(&request[..4], &[5, 1, 0, 1]);
+            let ip = std::net::Ipv4Addr::new(request[4], request[5], request[6], request[7]);
+            let target = SocketAddr::from((ip, u16::from_be_bytes([request[8], request[9]])));
+            seen.lock().expect("lock").push(target);
+
+            let upstream = TcpStream::connect(target).expect("connect target");
+            client
+                .write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+                .expect("connect reply");
+            let (mut client_reader, mut upstream_writer) = (
+                client.try_clone().expect("clone"),
+                upstream.try_clone().expect("clone"),
+            );
+            std::thread::spawn(move || {
+                let _ = std::io::copy(&mut client_reader, &mut upstream_writer);
+            });
+            let (mut upstream_reader, mut client_writer) = (upstream, client);
+            std::thread::spawn(move || {
+                let _ = std::io::copy(&mut upstream_reader, &mut client_writer);
+            });
+        }
+    });
+    (addr, targets)
+}
+
+/// Minimal HTTP proxy that only tunnels; records the head of each CONNECT
+fn spawn_connect_proxy() -> (SocketAddr, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let connects = Arc::new(Mutex::new(Vec::new()));
+    let seen = connects.clone();
+    std::thread::spawn(move || {
+        for client in listener.incoming().flatten() {
+            let mut reader = BufReader::new(client.try_clone().expect("clone"));
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).expect("read") == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let target = head
+                .split_whitespace()
+                .nth(1)
+                .expect("CONNECT target")
+                .to_string();
+            seen.lock().expect("lock").push(head);
+
+            let upstream = TcpStream::connect(target).expect("connect target");
+            let mut client_writer = client;
+            client_writer
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .expect("connect reply");
+            let mut upstream_writer = upstream.try_clone().expect("clone");
+            std::thread::spawn(move || {
+                let _ = std::io::copy(&mut reader, &mut upstream_writer);
+            });
+            let mut upstream_reader = upstream;
+            std::thread::spawn(move || {
+                let _ = std::io::copy(&mut upstream_reader, &mut client_writer);
+            });
+        }
+    });
+    (addr, connects)
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_preproxy_reaches_http_proxy_through_socks() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    // The mock server plays the HTTP proxy; it sees absolute-form requests
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/via-proxy"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("proxied"))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+    let (socks_addr, targets) = spawn_socks5_server();
+
+    let mut config = Config::default();
+    config.url = "http://origin.invalid/via-proxy".to_string();
+    config.proxy = Some(ProxyConfig::new(proxy.uri()));
+    config.preproxy = Some(format!("socks5://{}", socks_addr));
+    let client = HttpClient::new(config).expect("client should build");
+    let response = client.execute().await.expect("request");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.expect("body"), "proxied");
+    assert_eq!(targets.lock().expect("lock").as_slice(), [*proxy.address()]);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_preproxy_tunnels_through_http_proxy_on_the_socks_stream() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/tunneled"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("tunneled"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let (proxy_addr, connects) = spawn_connect_proxy();
+    let (socks_addr, targets) = spawn_socks5_server();
+
+    let mut proxy = ProxyConfig::new(format!("http://{}", proxy_addr));
+    proxy.tunnel = true;
+    let mut config = Config::default();
+    config.url = format!("{}/tunneled", server.uri());
+    config.proxy = Some(proxy);
+    config.preproxy = Some(format!("socks5://{}", socks_addr));
+    let client = HttpClient::new(config).expect("client should build");
+    let response = client.execute().await.expect("request");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.expect("body"), "tunneled");
+
+    assert_eq!(targets.lock().expect("lock").as_slice(), [proxy_addr]);
+    let connects = connects.lock().expect("lock");
+    assert_eq!(connects.len(), 1);
+    assert!(connects[0].starts_with(&format!("CONNECT {} HTTP/1.1\r\n", server.address())));
+}
+
+#[test]
+fn test_preproxy_requires_an_http_proxy() {
+    let mut config = Config::default();
+    config.url = "http://example.com/".to_string();
+    config.preproxy = Some("socks5://127.0.0.1:1080".to_string());
+    assert!(matches!(HttpClient::new(config), Err(RurlError::Config(_))));
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_proxytunnel_sends_plain_http_through_connect() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/tunneled"))
+        .and(header("host", server.address().to_string().as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_string("tunneled"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let (proxy_addr, connects) = spawn_connect_proxy();
+
+    let mut proxy = ProxyConfig::new(format!("http://{}", proxy_addr));
+    proxy.username = Some("user".to_string());
+    proxy.password = Some("secret".to_string());
+    proxy.tunnel = true;
+    let mut config = Config::default();
+    config.url = format!("{}/tunneled", server.uri());
+    config.proxy = Some(proxy);
+    let client = HttpClient::new(config).expect("client should build");
+    let response = client.execute().await.expect("request");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.expect("body"), "tunneled");
+
+    let connects = connects.lock().expect("lock");
+    assert_eq!(connects.len(), 1);
+    assert!(connects[0].starts_with(&format!("CONNECT {} HTTP/1.1\r\n", server.address())));
+    // "user:secret" in base64
+    assert!(connects[0].contains("Proxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n"));
+}
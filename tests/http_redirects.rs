@@ -1,6 +1,7 @@
 use reqwest::header::{AUTHORIZATION, COOKIE};
 use rurl::config::{Config, HttpMethod};
 use rurl::error::RurlError;
+use rurl::http::redirect::RedirectPolicy;
 use rurl::http::HttpClient;
 use wiremock::matchers::{body_string, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -32,12 +33,10 @@ async fn test_follow_redirect_get() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -71,13 +70,11 @@ async fn test_post_redirect_switches_to_get_when_not_explicit() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Post,
-        data: Some("payload".to_string()),
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("payload".to_string());
+    config.follow_redirects = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -117,14 +114,12 @@ async fn test_post_redirect_keeps_method_when_explicit() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Post,
-        data: Some("payload".to_string()),
-        request_method_explicit: true,
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("payload".to_string());
+    config.request_method_explicit = true;
+    config.follow_redirects = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -163,14 +158,12 @@ async fn test_basic_auth_not_forwarded_to_other_origin() {
         .mount(&target_server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", start_server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        auth_username: Some("user".to_string()),
-        auth_password: Some("pass".to_string()),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", start_server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+    config.auth_username = Some("user".to_string());
+    config.auth_password = Some("pass".to_string());
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -217,15 +210,13 @@ async fn test_basic_auth_forwarded_with_location_trusted() {
         .mount(&target_server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", start_server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        location_trusted: true,
-        auth_username: Some("user".to_string()),
-        auth_password: Some("pass".to_string()),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", start_server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+    config.location_trusted = true;
+    config.auth_username = Some("user".to_string());
+    config.auth_password = Some("pass".to_string());
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -242,6 +233,58 @@ async fn test_basic_auth_forwarded_with_location_trusted() {
         .is_some());
 }
 
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_custom_host_header_only_sent_to_first_origin() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let start_server = MockServer::start().await;
+    let target_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/start"))
+        .and(header("host", "vhost.example"))
+        .respond_with(
+            ResponseTemplate::new(302)
+                .insert_header("Location", format!("{}/final", target_server.uri())),
+        )
+        .mount(&start_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/final"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&target_server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/start", start_server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+    config.location_trusted = true;
+    config
+        .headers
+        .insert("Host".to_string(), "vhost.example".to_string());
+
+    let client = HttpClient::new(config).expect("client should build");
+    let response_history = client
+        .execute_with_history()
+        .await
+        .expect("request should succeed");
+    assert_eq!(response_history.response.status(), 200);
+
+    let target_requests = received_requests(&target_server).await;
+    assert_eq!(target_requests.len(), 1);
+    let target_host = target_server.uri().replace("http://", "");
+    assert_eq!(
+        target_requests[0]
+            .headers
+            .get("host")
+            .and_then(|value| value.to_str().ok()),
+        Some(target_host.as_str())
+    );
+}
+
 #[cfg_attr(miri, ignore)]
 #[tokio::test]
 async fn test_sensitive_headers_forwarded_on_same_origin_redirect() {
@@ -263,12 +306,10 @@ async fn test_sensitive_headers_forwarded_on_same_origin_redirect() {
         .mount(&server)
         .await;
 
-    let mut config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
     config
         .headers
         .insert("Authorization".to_string(), "Bearer token".to_string());
@@ -313,14 +354,12 @@ async fn test_post303_keeps_method_when_explicit() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Post,
-        data: Some("payload".to_string()),
-        request_method_explicit: true,
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("payload".to_string());
+    config.request_method_explicit = true;
+    config.follow_redirects = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -354,13 +393,11 @@ async fn test_redirect_limit_exceeded() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        max_redirects: Some(1),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+    config.max_redirects = Some(1);
 
     let client = HttpClient::new(config).expect("client should build");
     let result = client.execute_with_history().await;
@@ -393,12 +430,10 @@ async fn test_redirects_disabled_returns_first_response() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: false,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = false;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -426,12 +461,10 @@ async fn test_redirect_without_location_stops() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -459,12 +492,10 @@ async fn test_redirect_invalid_location_errors() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let result = client.execute_with_history().await;
@@ -500,12 +531,10 @@ async fn test_sensitive_headers_not_forwarded_to_other_origin() {
         .mount(&target_server)
         .await;
 
-    let mut config = Config {
-        url: format!("{}/start", start_server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", start_server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
     config
         .headers
         .insert("Authorization".to_string(), "Bearer token".to_string());
@@ -553,13 +582,11 @@ async fn test_location_trusted_forwards_sensitive_headers() {
         .mount(&target_server)
         .await;
 
-    let mut config = Config {
-        url: format!("{}/start", start_server.uri()),
-        method: HttpMethod::Get,
-        follow_redirects: true,
-        location_trusted: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", start_server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+    config.location_trusted = true;
     config
         .headers
         .insert("Authorization".to_string(), "Bearer token".to_string());
@@ -601,14 +628,12 @@ async fn test_post301_keeps_post() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Post,
-        data: Some("payload".to_string()),
-        follow_redirects: true,
-        post301: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("payload".to_string());
+    config.follow_redirects = true;
+    config.post301 = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -642,14 +667,12 @@ async fn test_post302_keeps_post() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Post,
-        data: Some("payload".to_string()),
-        follow_redirects: true,
-        post302: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("payload".to_string());
+    config.follow_redirects = true;
+    config.post302 = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -683,14 +706,12 @@ async fn test_post303_keeps_post() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Post,
-        data: Some("payload".to_string()),
-        follow_redirects: true,
-        post303: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("payload".to_string());
+    config.follow_redirects = true;
+    config.post303 = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -723,13 +744,11 @@ async fn test_put_redirect_303_switches_to_get() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/start", server.uri()),
-        method: HttpMethod::Put,
-        data: Some("payload".to_string()),
-        follow_redirects: true,
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Put;
+    config.data = Some("payload".to_string());
+    config.follow_redirects = true;
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -743,3 +762,155 @@ async fn test_put_redirect_303_switches_to_get() {
         .iter()
         .any(|req| req.method.as_str() == "GET" && req.url.path() == "/final"));
 }
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_redirect_policy_same_host_stops_at_other_host() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    let other_host = server.uri().replace("127.0.0.1", "localhost");
+    Mock::given(method("GET"))
+        .and(path("/start"))
+        .respond_with(
+            ResponseTemplate::new(302).insert_header("Location", format!("{other_host}/final")),
+        )
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+
+    let mut client = HttpClient::new(config).expect("client should build");
+    client.set_redirect_policy(RedirectPolicy::limited(None).same_host_only());
+    let response_history = client
+        .execute_with_history()
+        .await
+        .expect("request should succeed");
+    assert_eq!(response_history.response.status(), 302);
+
+    let requests = received_requests(&server).await;
+    assert_eq!(requests.len(), 1);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_history_records_hop_urls_and_effective_url() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/start"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/final"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/final"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/start", server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+
+    let client = HttpClient::new(config).expect("client should build");
+    let response_history = client
+        .execute_with_history()
+        .await
+        .expect("request should succeed");
+    let paths: Vec<_> = response_history
+        .chain
+        .iter()
+        .map(|info| info.url.path().to_string())
+        .collect();
+    assert_eq!(paths, ["/start", "/final"]);
+    assert_eq!(response_history.url_effective().path(), "/final");
+    assert!(response_history
+        .chain
+        .iter()
+        .all(|info| info.remote_addr.is_some()));
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_deny_host_blocks_redirect_hop() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let start_server = MockServer::start().await;
+    let target_server = MockServer::start().await;
+    let target_port = target_server.address().port();
+    Mock::given(method("GET"))
+        .and(path("/start"))
+        .respond_with(ResponseTemplate::new(302).insert_header(
+            "Location",
+            format!("http://localhost:{}/final", target_port),
+        ))
+        .mount(&start_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/final"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&target_server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/start", start_server.uri());
+    config.method = HttpMethod::Get;
+    config.follow_redirects = true;
+    config.host_filter.deny("localhost").expect("pattern");
+
+    let client = HttpClient::new(config).expect("client should build");
+    let err = client
+        .execute_with_history()
+        .await
+        .expect_err("redirect should be refused");
+    assert!(matches!(err, RurlError::HostBlocked(_)), "{:?}", err);
+    assert_eq!(received_requests(&start_server).await.len(), 1);
+    assert!(received_requests(&target_server).await.is_empty());
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_no_internal_targets_refuses_loopback_before_connecting() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+    let port = server.address().port();
+
+    for url in [
+        format!("http://127.0.0.1:{}/", port),
+        format!("http://localhost:{}/", port),
+    ] {
+        let mut config = Config::default();
+        config.url = url.clone();
+        config.host_filter.block_internal();
+        let client = HttpClient::new(config).expect("client should build");
+        let err = client
+            .execute_with_history()
+            .await
+            .expect_err("loopback should be refused");
+        assert!(
+            matches!(err, RurlError::InternalTarget(_)),
+            "{}: {:?}",
+            url,
+            err
+        );
+    }
+    assert!(received_requests(&server).await.is_empty());
+}
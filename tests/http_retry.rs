@@ -1,6 +1,8 @@
-use rurl::config::{Config, HttpMethod};
+use rurl::config::{Config, HttpMethod, IdempotencyKey};
 use rurl::error::RurlError;
 use rurl::http::HttpClient;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -23,13 +25,11 @@ async fn test_retries_on_http_500() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/retry", server.uri()),
-        method: HttpMethod::Get,
-        retry_count: 1,
-        retry_delay: Duration::from_millis(0),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/retry", server.uri());
+    config.method = HttpMethod::Get;
+    config.retry_count = 1;
+    config.retry_delay = Duration::from_millis(0);
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -60,13 +60,11 @@ async fn test_retries_on_retry_after_header() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/retry-after", server.uri()),
-        method: HttpMethod::Get,
-        retry_count: 1,
-        retry_delay: Duration::from_millis(0),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/retry-after", server.uri());
+    config.method = HttpMethod::Get;
+    config.retry_count = 1;
+    config.retry_delay = Duration::from_millis(0);
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -93,13 +91,11 @@ async fn test_does_not_retry_on_non_retryable_status() {
         .mount(&server)
         .await;
 
-    let config = Config {
-        url: format!("{}/client-error", server.uri()),
-        method: HttpMethod::Get,
-        retry_count: 2,
-        retry_delay: Duration::from_millis(0),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("{}/client-error", server.uri());
+    config.method = HttpMethod::Get;
+    config.retry_count = 2;
+    config.retry_delay = Duration::from_millis(0);
 
     let client = HttpClient::new(config).expect("client should build");
     let response_history = client
@@ -123,13 +119,11 @@ async fn test_retries_and_fails_on_connect_error() {
     let addr = listener.local_addr().expect("addr");
     drop(listener);
 
-    let config = Config {
-        url: format!("http://{}:{}/", addr.ip(), addr.port()),
-        method: HttpMethod::Get,
-        retry_count: 1,
-        retry_delay: Duration::from_millis(0),
-        ..Config::default()
-    };
+    let mut config = Config::default();
+    config.url = format!("http://{}:{}/", addr.ip(), addr.port());
+    config.method = HttpMethod::Get;
+    config.retry_count = 1;
+    config.retry_delay = Duration::from_millis(0);
 
     let client = HttpClient::new(config).expect("client should build");
     let result = client.execute_with_history().await;
@@ -141,3 +135,113 @@ async fn test_retries_and_fails_on_connect_error() {
         Err(other) => panic!("unexpected error: {:?}", other),
     }
 }
+
+/// Accept connections, read the request, and close without replying
+fn spawn_empty_reply_server() -> (std::net::SocketAddr, Arc<AtomicUsize>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+    std::thread::spawn(move || {
+        use std::io::Read;
+        for mut stream in listener.incoming().flatten() {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+        }
+    });
+    (addr, accepted)
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_empty_reply_is_retried_only_with_retry_all_errors() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let (addr, accepted) = spawn_empty_reply_server();
+    let mut config = Config::default();
+    config.url = format!("http://{}/", addr);
+    config.method = HttpMethod::Get;
+    config.retry_count = 2;
+    config.retry_delay = Duration::from_millis(0);
+    let client = HttpClient::new(config.clone()).expect("client should build");
+    let result = client.execute_with_history().await;
+    assert!(
+        matches!(result, Err(RurlError::EmptyReply)),
+        "unexpected result: {:?}",
+        result.map(|history| history.response.status())
+    );
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+    let (addr, accepted) = spawn_empty_reply_server();
+    config.url = format!("http://{}/", addr);
+    config.retry_all_errors = true;
+    let client = HttpClient::new(config).expect("client should build");
+    let result = client.execute_with_history().await;
+    assert!(matches!(result, Err(RurlError::EmptyReply)));
+    assert_eq!(accepted.load(Ordering::SeqCst), 3);
+}
+
+#[cfg_attr(miri, ignore)]
+#[tokio::test]
+async fn test_idempotency_key_is_reused_across_retries() {
+    if !can_bind_localhost() {
+        return;
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/charge"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/charge"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+
+    let mut config = Config::default();
+    config.url = format!("{}/charge", server.uri());
+    config.method = HttpMethod::Post;
+    config.data = Some("amount=5".to_string());
+    config.retry_count = 1;
+    config.retry_delay = Duration::from_millis(0);
+    config.idempotency_key = Some(IdempotencyKey::Auto);
+    let client = HttpClient::new(config).expect("client should build");
+    let response = client.execute().await.expect("request");
+    assert_eq!(response.status(), 201);
+
+    let keys: Vec<String> = server
+        .received_requests()
+        .await
+        .expect("requests")
+        .iter()
+        .map(|request| {
+            request
+                .headers
+                .get("idempotency-key")
+                .and_then(|value| value.to_str().ok())
+                .expect("idempotency key")
+                .to_string()
+        })
+        .collect();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0], keys[1]);
+    assert_eq!(keys[0].len(), 36);
+
+    // The next transfer gets a new key
+    let response = client.execute().await.expect("request");
+    assert_eq!(response.status(), 201);
+    let requests = server.received_requests().await.expect("requests");
+    let last = requests.last().expect("request");
+    assert_ne!(
+        last.headers
+            .get("idempotency-key")
+            .and_then(|value| value.to_str().ok()),
+        Some(keys[0].as_str())
+    );
+}